@@ -38,6 +38,14 @@ struct ProviderError {
     message: String,
 }
 
+impl From<scim_server::providers::ProviderError> for ProviderError {
+    fn from(error: scim_server::providers::ProviderError) -> Self {
+        ProviderError {
+            message: error.to_string(),
+        }
+    }
+}
+
 impl ResourceProvider for AdvancedProvider {
     type Error = ProviderError;
 
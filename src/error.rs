@@ -3,6 +3,8 @@
 //! This module provides comprehensive error handling for all SCIM operations,
 //! following Rust's error handling best practices with detailed error information.
 
+use serde::{Deserialize, Serialize};
+
 /// Main error type for SCIM server operations.
 ///
 /// This enum covers all possible error conditions that can occur during
@@ -18,7 +20,9 @@ pub enum ScimError {
     Provider(#[source] Box<dyn std::error::Error + Send + Sync>),
 
     /// JSON serialization/deserialization errors
-    #[error("JSON error: {0}")]
+    ///
+    /// The message describes the problem without serde's internal details.
+    #[error("Malformed JSON: {}", describe_json_error(.0))]
     Json(#[from] serde_json::Error),
 
     /// Resource not found errors
@@ -30,6 +34,15 @@ pub enum ScimError {
         id: String,
     },
 
+    /// Resource existed but has been soft-deleted
+    #[error("Resource deleted: {resource_type} with ID {id}")]
+    ResourceDeleted {
+        /// The type of resource that was deleted
+        resource_type: String,
+        /// The ID of the resource that was deleted
+        id: String,
+    },
+
     /// Schema not found errors
     #[error("Schema not found: {schema_id}")]
     SchemaNotFound {
@@ -51,6 +64,13 @@ pub enum ScimError {
         message: String,
     },
 
+    /// A query filter could not be parsed or exceeds the parser's limits
+    #[error("{message}")]
+    InvalidFilter {
+        /// Description of the problem, including where it was detected
+        message: String,
+    },
+
     /// Unsupported resource type
     #[error("Unsupported resource type: {0}")]
     UnsupportedResourceType(String),
@@ -67,6 +87,90 @@ pub enum ScimError {
     /// Resource provider error with string message
     #[error("Resource provider error: {0}")]
     ProviderError(String),
+
+    /// Operation not permitted by the requesting tenant's permissions
+    #[error("Permission denied: operation '{operation}' not permitted for tenant '{tenant_id}'")]
+    PermissionDenied {
+        /// The operation that was attempted (e.g., "create", "read")
+        operation: String,
+        /// The tenant whose permissions rejected the operation
+        tenant_id: String,
+    },
+
+    /// Operation denied by the server's authorization policy
+    #[error("Authorization denied: {operation} on '{target}': {reason}")]
+    AuthorizationDenied {
+        /// The operation that was attempted (e.g., "Create", "Delete")
+        operation: String,
+        /// The resource type, followed by `/` and the resource id when the
+        /// operation targets a single resource
+        target: String,
+        /// Why the policy denied the operation
+        reason: String,
+    },
+
+    /// A unique attribute value is already held by another resource.
+    ///
+    /// `id` and `location` identify the existing resource when the provider
+    /// reports it; they are only ever populated for a resource in the
+    /// requesting tenant.
+    #[error("Uniqueness conflict: {resource_type} attribute '{attribute}' is already in use")]
+    Conflict {
+        /// The type of resource that conflicted
+        resource_type: String,
+        /// The attribute whose value must be unique
+        attribute: String,
+        /// ID of the existing resource holding the value
+        id: Option<String>,
+        /// Location URL of the existing resource
+        location: Option<String>,
+    },
+
+    /// The write conflicts with the stored state, e.g. a concurrent
+    /// modification detected by the storage backend
+    #[error("Storage conflict: {message}")]
+    StorageConflict {
+        /// Description of the conflict
+        message: String,
+    },
+
+    /// The storage backend is temporarily unavailable
+    #[error("Service unavailable: {message}")]
+    Unavailable {
+        /// Description of the outage
+        message: String,
+        /// How long to wait before retrying, if known
+        retry_after: Option<std::time::Duration>,
+    },
+
+    /// Storage has no room for a new resource
+    #[error("Storage capacity exceeded: {message}")]
+    CapacityExceeded {
+        /// Description of the exhausted capacity
+        message: String,
+        /// Maximum number of resources, if known
+        limit: Option<usize>,
+    },
+
+    /// Request payload exceeds the configured maximum size
+    #[error("Payload too large: {size} bytes exceeds the maximum of {limit} bytes")]
+    PayloadTooLarge {
+        /// Size of the serialized payload in bytes
+        size: usize,
+        /// Configured maximum payload size in bytes
+        limit: usize,
+    },
+
+    /// A write would leave a Group with more members than allowed
+    #[error(
+        "Too many members: the Group would have {count} members, exceeding the maximum of {limit}"
+    )]
+    TooManyMembers {
+        /// Number of members the Group would have after the write
+        count: usize,
+        /// Configured maximum number of members
+        limit: usize,
+    },
 }
 
 /// Validation errors for schema compliance checking.
@@ -143,6 +247,23 @@ pub enum ValidationError {
         schema_id: String,
     },
 
+    /// Part of a resource does not have the JSON structure its attribute
+    /// requires, e.g. an `emails` entry that is a string instead of an object
+    #[error("Malformed JSON at '{location}': {problem}")]
+    MalformedJson {
+        /// Attribute path of the malformed part, e.g. `emails[1]`
+        location: String,
+        /// What is wrong with it
+        problem: String,
+    },
+
+    /// JSON nested deeper than [`MAX_JSON_DEPTH`]
+    #[error("JSON is nested deeper than the maximum of {limit} levels")]
+    JsonTooDeep {
+        /// Maximum nesting depth
+        limit: usize,
+    },
+
     /// General validation error with custom message
     #[error("Validation failed: {message}")]
     Custom {
@@ -154,10 +275,26 @@ pub enum ValidationError {
     #[error("Missing required 'schemas' attribute")]
     MissingSchemas,
 
+    /// `schemas` is present but not an array
+    #[error("'schemas' must be an array of schema URIs, found {actual}")]
+    SchemasNotArray {
+        /// JSON type found instead of an array
+        actual: String,
+    },
+
     /// Empty schemas array
     #[error("'schemas' array cannot be empty")]
     EmptySchemas,
 
+    /// A `schemas` element that is not a string or not a valid schema URI
+    #[error("Invalid schema URI at schemas[{index}]: {value}")]
+    InvalidSchemaUriElement {
+        /// Position of the element in the `schemas` array
+        index: usize,
+        /// The element, as a string or its JSON text
+        value: String,
+    },
+
     /// Invalid schema URI format
     #[error("Invalid schema URI format: {uri}")]
     InvalidSchemaUri {
@@ -499,6 +636,23 @@ pub enum ValidationError {
     #[error("Attribute '{0}' is not multi-valued")]
     NotMultiValued(String),
 
+    /// Pagination start index below 1
+    #[error("startIndex must be at least 1, got {start_index}")]
+    InvalidStartIndex { start_index: usize },
+
+    /// PATCH path naming no attribute of the resource's schemas
+    #[error("PATCH path '{path}' does not name a known attribute")]
+    UnknownPatchPath { path: String },
+
+    /// A PATCH operation rejected before any operation was applied
+    #[error("Operations[{index}] rejected: {error}")]
+    PatchOperationRejected {
+        /// Position of the operation in the `Operations` array
+        index: usize,
+        /// Why the operation was rejected
+        error: Box<ValidationError>,
+    },
+
     /// Reserved username error
     #[error("Username '{0}' is reserved and cannot be used")]
     ReservedUsername(String),
@@ -539,6 +693,48 @@ pub enum ValidationError {
     EmptyFormattedName,
 }
 
+/// Structural problems detected in a schema definition.
+///
+/// Produced by [`SchemaRegistry::validate_schemas`](crate::schema::SchemaRegistry::validate_schemas)
+/// and surfaced at registration time. Each variant names the schema and the
+/// dotted path of the offending attribute (e.g. `name.givenName`).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SchemaError {
+    /// Complex attribute declares no sub-attributes
+    #[error("Schema '{schema_id}': complex attribute '{attribute}' has no subAttributes")]
+    ComplexWithoutSubAttributes {
+        schema_id: String,
+        attribute: String,
+    },
+
+    /// Reference attribute declares no reference types
+    #[error("Schema '{schema_id}': reference attribute '{attribute}' has no referenceTypes")]
+    ReferenceWithoutReferenceTypes {
+        schema_id: String,
+        attribute: String,
+    },
+
+    /// Attribute name appears more than once at the same level
+    #[error("Schema '{schema_id}': duplicate attribute '{attribute}'")]
+    DuplicateAttribute {
+        schema_id: String,
+        attribute: String,
+    },
+
+    /// Attribute has an empty name
+    #[error("Schema '{schema_id}': attribute with empty name under '{parent}'")]
+    EmptyAttributeName { schema_id: String, parent: String },
+
+    /// Complex sub-attribute of a complex attribute (RFC 7643 Section 2.3.8)
+    #[error(
+        "Schema '{schema_id}': nested complex attributes are not allowed: '{attribute}' is a complex sub-attribute"
+    )]
+    NestedComplexAttributes {
+        schema_id: String,
+        attribute: String,
+    },
+}
+
 /// Errors that can occur during server building/configuration.
 ///
 /// These errors are typically programming errors and should be caught
@@ -568,6 +764,14 @@ impl ScimError {
         }
     }
 
+    /// Create a resource deleted error
+    pub fn resource_deleted(resource_type: impl Into<String>, id: impl Into<String>) -> Self {
+        Self::ResourceDeleted {
+            resource_type: resource_type.into(),
+            id: id.into(),
+        }
+    }
+
     /// Create a schema not found error
     pub fn schema_not_found(schema_id: impl Into<String>) -> Self {
         Self::SchemaNotFound {
@@ -589,6 +793,13 @@ impl ScimError {
         }
     }
 
+    /// Create an invalid filter error
+    pub fn invalid_filter(message: impl Into<String>) -> Self {
+        Self::InvalidFilter {
+            message: message.into(),
+        }
+    }
+
     /// Wrap a provider error
     pub fn provider_error<E>(error: E) -> Self
     where
@@ -596,9 +807,242 @@ impl ScimError {
     {
         Self::Provider(Box::new(error))
     }
+
+    /// Create a permission denied error
+    pub fn permission_denied(operation: impl Into<String>, tenant_id: impl Into<String>) -> Self {
+        Self::PermissionDenied {
+            operation: operation.into(),
+            tenant_id: tenant_id.into(),
+        }
+    }
+
+    /// Create an authorization denied error for an operation on a resource
+    /// type, or on one of its resources when `resource_id` is given
+    pub fn authorization_denied(
+        operation: &crate::resource::ScimOperation,
+        resource_type: &str,
+        resource_id: Option<&str>,
+        reason: impl Into<String>,
+    ) -> Self {
+        let target = match resource_id {
+            Some(id) => format!("{}/{}", resource_type, id),
+            None => resource_type.to_string(),
+        };
+        Self::AuthorizationDenied {
+            operation: format!("{:?}", operation),
+            target,
+            reason: reason.into(),
+        }
+    }
+
+    /// Create a payload too large error
+    pub fn payload_too_large(size: usize, limit: usize) -> Self {
+        Self::PayloadTooLarge { size, limit }
+    }
+
+    /// Create a too many Group members error
+    pub fn too_many_members(count: usize, limit: usize) -> Self {
+        Self::TooManyMembers { count, limit }
+    }
+
+    /// Create a storage capacity exceeded error
+    pub fn capacity_exceeded(message: impl Into<String>, limit: Option<usize>) -> Self {
+        Self::CapacityExceeded {
+            message: message.into(),
+            limit,
+        }
+    }
+
+    /// Create a storage conflict error
+    pub fn storage_conflict(message: impl Into<String>) -> Self {
+        Self::StorageConflict {
+            message: message.into(),
+        }
+    }
+
+    /// Create a service unavailable error
+    pub fn unavailable(
+        message: impl Into<String>,
+        retry_after: Option<std::time::Duration>,
+    ) -> Self {
+        Self::Unavailable {
+            message: message.into(),
+            retry_after,
+        }
+    }
+
+    /// Create a uniqueness conflict error
+    pub fn conflict(
+        resource_type: impl Into<String>,
+        attribute: impl Into<String>,
+        id: Option<String>,
+        location: Option<String>,
+    ) -> Self {
+        Self::Conflict {
+            resource_type: resource_type.into(),
+            attribute: attribute.into(),
+            id,
+            location,
+        }
+    }
+
+    /// SCIM `scimType` detail keyword for this error, per RFC 7644 Section 3.12.
+    ///
+    /// Validation errors take the keyword of the underlying
+    /// [`ValidationError`]. Errors without a matching keyword, such as missing
+    /// resources or internal failures, return `None`.
+    pub fn scim_type(&self) -> Option<ScimType> {
+        match self {
+            Self::Validation(error) => Some(error.scim_type()),
+            Self::Json(_) => Some(ScimType::InvalidSyntax),
+            Self::Conflict { .. } => Some(ScimType::Uniqueness),
+            Self::InvalidFilter { .. } => Some(ScimType::InvalidFilter),
+            Self::TooManyMembers { .. } => Some(ScimType::TooMany),
+            Self::Provider(_)
+            | Self::ResourceNotFound { .. }
+            | Self::ResourceDeleted { .. }
+            | Self::SchemaNotFound { .. }
+            | Self::Internal { .. }
+            | Self::InvalidRequest { .. }
+            | Self::UnsupportedResourceType(_)
+            | Self::UnsupportedOperation { .. }
+            | Self::ProviderError(_)
+            | Self::PermissionDenied { .. }
+            | Self::AuthorizationDenied { .. }
+            | Self::StorageConflict { .. }
+            | Self::Unavailable { .. }
+            | Self::CapacityExceeded { .. }
+            | Self::PayloadTooLarge { .. } => None,
+        }
+    }
+
+    /// Render this error as a SCIM error response body (RFC 7644 Section 3.12).
+    ///
+    /// Conflicts additionally carry a `conflictingResource` object with the
+    /// existing resource's `id` and `location`, when known.
+    pub fn to_scim_json(&self) -> serde_json::Value {
+        let mut body = serde_json::json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+            "status": self.status_code().to_string(),
+            "detail": self.to_string(),
+        });
+        if let Some(scim_type) = self.scim_type() {
+            body["scimType"] = scim_type.as_str().into();
+        }
+        if let Self::Conflict {
+            id: Some(id),
+            location,
+            ..
+        } = self
+        {
+            let mut existing = serde_json::json!({ "id": id });
+            if let Some(location) = location {
+                existing["location"] = location.as_str().into();
+            }
+            body["conflictingResource"] = existing;
+        }
+        body
+    }
+
+    /// HTTP status code corresponding to this error, per RFC 7644 Section 3.12.
+    ///
+    /// An unavailable storage backend maps to 503 and exhausted storage
+    /// capacity to 507 (Insufficient Storage). Deleted resources map to 404
+    /// like missing ones.
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Self::Validation(_)
+            | Self::Json(_)
+            | Self::InvalidRequest { .. }
+            | Self::InvalidFilter { .. } => 400,
+            Self::PermissionDenied { .. } | Self::AuthorizationDenied { .. } => 403,
+            Self::ResourceNotFound { .. }
+            | Self::ResourceDeleted { .. }
+            | Self::SchemaNotFound { .. }
+            | Self::UnsupportedResourceType(_) => 404,
+            Self::Conflict { .. } | Self::StorageConflict { .. } => 409,
+            Self::PayloadTooLarge { .. } | Self::TooManyMembers { .. } => 413,
+            Self::UnsupportedOperation { .. } => 501,
+            Self::Unavailable { .. } => 503,
+            Self::CapacityExceeded { .. } => 507,
+            Self::Provider(_) | Self::ProviderError(_) | Self::Internal { .. } => 500,
+        }
+    }
+}
+
+/// SCIM `scimType` detail keywords for error responses (RFC 7644 Section 3.12).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScimType {
+    /// The filter syntax was invalid or an attribute in it is not supported
+    InvalidFilter,
+    /// The filter yields more results than the server is willing to return
+    TooMany,
+    /// One or more attribute values are already in use or reserved
+    Uniqueness,
+    /// The attempted modification is not compatible with an attribute's
+    /// mutability
+    Mutability,
+    /// The request body was invalid or did not conform to the request schema
+    InvalidSyntax,
+    /// The PATCH path was invalid or not supported
+    InvalidPath,
+    /// The PATCH path did not yield an attribute or value to operate on
+    NoTarget,
+    /// A required value was missing or a value was not compatible with the
+    /// attribute's type
+    InvalidValue,
+    /// The specified SCIM protocol version is not supported
+    InvalidVers,
+    /// The request cannot be completed because it would expose sensitive
+    /// information
+    Sensitive,
+}
+
+impl ScimType {
+    /// The keyword as it appears in a SCIM error response, e.g. `invalidValue`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidFilter => "invalidFilter",
+            Self::TooMany => "tooMany",
+            Self::Uniqueness => "uniqueness",
+            Self::Mutability => "mutability",
+            Self::InvalidSyntax => "invalidSyntax",
+            Self::InvalidPath => "invalidPath",
+            Self::NoTarget => "noTarget",
+            Self::InvalidValue => "invalidValue",
+            Self::InvalidVers => "invalidVers",
+            Self::Sensitive => "sensitive",
+        }
+    }
+}
+
+impl std::fmt::Display for ScimType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 impl ValidationError {
+    /// SCIM `scimType` detail keyword for this error.
+    ///
+    /// Mutability and uniqueness violations and unknown PATCH paths have
+    /// their own keywords; every other validation failure is an
+    /// `invalidValue`.
+    pub fn scim_type(&self) -> ScimType {
+        match self {
+            Self::ReadOnlyMutabilityViolation { .. }
+            | Self::ImmutableMutabilityViolation { .. } => ScimType::Mutability,
+            Self::UniquenesViolation { .. }
+            | Self::ServerUniquenessViolation { .. }
+            | Self::GlobalUniquenessViolation { .. } => ScimType::Uniqueness,
+            Self::UnknownPatchPath { .. } => ScimType::InvalidPath,
+            Self::MalformedJson { .. } | Self::JsonTooDeep { .. } => ScimType::InvalidSyntax,
+            Self::PatchOperationRejected { error, .. } => error.scim_type(),
+            _ => ScimType::InvalidValue,
+        }
+    }
+
     /// Create a missing required attribute error
     pub fn missing_required(attribute: impl Into<String>) -> Self {
         Self::MissingRequiredAttribute {
@@ -619,6 +1063,15 @@ impl ValidationError {
         }
     }
 
+    /// Create a malformed JSON error for the part of a resource at
+    /// `location` that failed to deserialize
+    pub fn malformed_json(location: impl Into<String>, error: &serde_json::Error) -> Self {
+        Self::MalformedJson {
+            location: location.into(),
+            problem: describe_json_error(error),
+        }
+    }
+
     /// Create a custom validation error
     pub fn custom(message: impl Into<String>) -> Self {
         Self::Custom {
@@ -627,12 +1080,88 @@ impl ValidationError {
     }
 }
 
+/// Deepest nesting of JSON arrays and objects accepted in a resource.
+pub const MAX_JSON_DEPTH: usize = 64;
+
+/// Reject JSON nested deeper than [`MAX_JSON_DEPTH`].
+///
+/// The depth is measured without recursion, so arbitrarily deep input cannot
+/// exhaust the stack.
+pub fn check_json_depth(value: &serde_json::Value) -> ValidationResult<()> {
+    let mut pending = vec![(value, 0)];
+    while let Some((value, depth)) = pending.pop() {
+        let children: Box<dyn Iterator<Item = &serde_json::Value>> = match value {
+            serde_json::Value::Array(values) => Box::new(values.iter()),
+            serde_json::Value::Object(map) => Box::new(map.values()),
+            _ => continue,
+        };
+        if depth >= MAX_JSON_DEPTH {
+            return Err(ValidationError::JsonTooDeep {
+                limit: MAX_JSON_DEPTH,
+            });
+        }
+        pending.extend(children.map(|child| (child, depth + 1)));
+    }
+    Ok(())
+}
+
+/// Describe a JSON error for clients.
+///
+/// Syntax errors report their position. Structural errors name the offending
+/// kind of value or field but not the Rust types serde expected, and other
+/// errors are described generically.
+fn describe_json_error(error: &serde_json::Error) -> String {
+    use serde_json::error::Category;
+
+    match error.classify() {
+        Category::Syntax => format!(
+            "invalid syntax at line {}, column {}",
+            error.line(),
+            error.column()
+        ),
+        Category::Eof => "unexpected end of input".to_string(),
+        Category::Io => "input could not be read".to_string(),
+        Category::Data => {
+            let message = error.to_string();
+            let quoted = |rest: &str| rest.split('`').nth(1).unwrap_or_default().to_string();
+            if let Some(rest) = message.strip_prefix("invalid type: ") {
+                let kind = match rest.split([' ', ',']).next().unwrap_or_default() {
+                    "map" => "object",
+                    "sequence" => "array",
+                    "string" => "string",
+                    "boolean" => "boolean",
+                    "integer" | "floating" => "number",
+                    "null" | "unit" => "null",
+                    _ => "value",
+                };
+                format!("unexpected {}", kind)
+            } else if let Some(rest) = message.strip_prefix("missing field ") {
+                format!("missing field '{}'", quoted(rest))
+            } else if let Some(rest) = message.strip_prefix("unknown field ") {
+                format!("unknown field '{}'", quoted(rest))
+            } else if let Some(rest) = message.strip_prefix("duplicate field ") {
+                format!("duplicate field '{}'", quoted(rest))
+            } else if message.starts_with("invalid length ") {
+                "wrong number of elements".to_string()
+            } else {
+                "invalid value".to_string()
+            }
+        }
+    }
+}
+
 // Result type aliases for convenience
 pub type ScimResult<T> = Result<T, ScimError>;
 pub type ValidationResult<T> = Result<T, ValidationError>;
 pub type BuildResult<T> = Result<T, BuildError>;
 
 // Implement From for common error conversions
+impl From<crate::filter::FilterError> for ScimError {
+    fn from(error: crate::filter::FilterError) -> Self {
+        Self::invalid_filter(error.to_string())
+    }
+}
+
 impl From<serde_json::Error> for ValidationError {
     fn from(error: serde_json::Error) -> Self {
         ValidationError::Custom {
@@ -658,6 +1187,204 @@ mod tests {
         assert!(error.to_string().contains("userName"));
     }
 
+    #[test]
+    fn test_permission_denied_status_code() {
+        let error = ScimError::permission_denied("create", "tenant-a");
+        assert_eq!(error.status_code(), 403);
+        assert!(error.to_string().contains("create"));
+        assert!(error.to_string().contains("tenant-a"));
+    }
+
+    #[test]
+    fn test_payload_too_large_status_code() {
+        let error = ScimError::payload_too_large(2048, 1024);
+        assert_eq!(error.status_code(), 413);
+        assert!(error.to_string().contains("2048"));
+    }
+
+    #[test]
+    fn test_resource_deleted_distinct_from_not_found() {
+        use crate::operation_handler::create_error_response;
+
+        let deleted = ScimError::resource_deleted("User", "123");
+        let missing = ScimError::resource_not_found("User", "123");
+        assert_eq!(deleted.status_code(), 404);
+        assert_eq!(missing.status_code(), 404);
+
+        let deleted = create_error_response(deleted, "req-1".to_string());
+        assert_eq!(deleted.error_code.as_deref(), Some("RESOURCE_DELETED"));
+        assert_eq!(deleted.metadata.additional["deleted"], true);
+        assert_eq!(deleted.into_http_parts().0, 404);
+
+        let missing = create_error_response(missing, "req-2".to_string());
+        assert_eq!(missing.error_code.as_deref(), Some("RESOURCE_NOT_FOUND"));
+        assert!(!missing.metadata.additional.contains_key("deleted"));
+    }
+
+    #[test]
+    fn test_conflict_scim_json() {
+        let error = ScimError::conflict(
+            "User",
+            "userName",
+            Some("123".to_string()),
+            Some("https://example.com/v2/Users/123".to_string()),
+        );
+        assert_eq!(error.status_code(), 409);
+
+        let body = error.to_scim_json();
+        assert_eq!(body["status"], "409");
+        assert_eq!(body["scimType"], "uniqueness");
+        assert_eq!(body["conflictingResource"]["id"], "123");
+        assert_eq!(
+            body["conflictingResource"]["location"],
+            "https://example.com/v2/Users/123"
+        );
+
+        let anonymous = ScimError::conflict("User", "userName", None, None).to_scim_json();
+        assert!(anonymous.get("conflictingResource").is_none());
+    }
+
+    #[test]
+    fn test_scim_type_mapping() {
+        let readonly = ScimError::from(ValidationError::ReadOnlyMutabilityViolation {
+            attribute: "id".to_string(),
+        });
+        assert_eq!(readonly.scim_type(), Some(ScimType::Mutability));
+        assert_eq!(readonly.to_scim_json()["scimType"], "mutability");
+        let (status, _, body) =
+            crate::operation_handler::create_error_response(readonly, "req-1".to_string())
+                .into_http_parts();
+        assert_eq!(status, 400);
+        assert_eq!(body["scimType"], "mutability");
+
+        let cases = [
+            (
+                ScimError::from(ValidationError::missing_required("userName")),
+                Some(ScimType::InvalidValue),
+            ),
+            (
+                ScimError::from(ValidationError::ImmutableMutabilityViolation {
+                    attribute: "userName".to_string(),
+                }),
+                Some(ScimType::Mutability),
+            ),
+            (
+                ScimError::from(ValidationError::ServerUniquenessViolation {
+                    attribute: "userName".to_string(),
+                    value: "jdoe".to_string(),
+                }),
+                Some(ScimType::Uniqueness),
+            ),
+            (
+                ScimError::from(serde_json::from_str::<serde_json::Value>("{").unwrap_err()),
+                Some(ScimType::InvalidSyntax),
+            ),
+            (
+                ScimError::conflict("User", "userName", None, None),
+                Some(ScimType::Uniqueness),
+            ),
+            (
+                ScimError::invalid_filter("Invalid filter at position 0: filter is empty"),
+                Some(ScimType::InvalidFilter),
+            ),
+            (ScimError::resource_not_found("User", "123"), None),
+            (ScimError::schema_not_found("urn:example"), None),
+            (ScimError::internal("boom"), None),
+            (ScimError::invalid_request("bad"), None),
+            (
+                ScimError::UnsupportedResourceType("Device".to_string()),
+                None,
+            ),
+            (
+                ScimError::UnsupportedOperation {
+                    resource_type: "User".to_string(),
+                    operation: "Patch".to_string(),
+                },
+                None,
+            ),
+            (ScimError::ProviderError("down".to_string()), None),
+            (
+                ScimError::provider_error(std::io::Error::other("down")),
+                None,
+            ),
+            (ScimError::permission_denied("update", "tenant"), None),
+            (ScimError::capacity_exceeded("full", Some(10)), None),
+            (ScimError::storage_conflict("modified concurrently"), None),
+            (ScimError::unavailable("down", None), None),
+            (ScimError::payload_too_large(2048, 1024), None),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(error.scim_type(), expected, "{}", error);
+            let body = error.to_scim_json();
+            assert_eq!(
+                body.get("scimType").and_then(|v| v.as_str()),
+                expected.map(|scim_type| scim_type.as_str())
+            );
+        }
+
+        assert_eq!(ScimType::NoTarget.to_string(), "noTarget");
+        assert_eq!(
+            serde_json::to_value(ScimType::InvalidVers).unwrap(),
+            "invalidVers"
+        );
+    }
+
+    #[test]
+    fn test_json_errors_are_invalid_syntax() {
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct InternalName {
+            value: String,
+        }
+
+        let syntax =
+            ScimError::from(serde_json::from_str::<serde_json::Value>("{\"a\":").unwrap_err());
+        assert_eq!(syntax.status_code(), 400);
+        assert_eq!(syntax.scim_type(), Some(ScimType::InvalidSyntax));
+        assert_eq!(
+            syntax.to_string(),
+            "Malformed JSON: unexpected end of input"
+        );
+
+        // Structural errors name the part and the problem, not the Rust type
+        let data = serde_json::from_value::<InternalName>(serde_json::json!("x")).unwrap_err();
+        let error = ScimError::from(ValidationError::malformed_json("emails[2]", &data));
+        assert_eq!(error.status_code(), 400);
+        assert_eq!(error.scim_type(), Some(ScimType::InvalidSyntax));
+        assert_eq!(
+            error.to_string(),
+            "Validation error: Malformed JSON at 'emails[2]': unexpected string"
+        );
+        let missing = serde_json::from_value::<InternalName>(serde_json::json!({})).unwrap_err();
+        assert_eq!(
+            ValidationError::malformed_json("name", &missing).to_string(),
+            "Malformed JSON at 'name': missing field 'value'"
+        );
+        assert!(!error.to_string().contains("InternalName"));
+    }
+
+    #[test]
+    fn test_check_json_depth() {
+        let nested = |depth: usize| {
+            let mut value = serde_json::json!("leaf");
+            for _ in 0..depth {
+                value = serde_json::json!({ "a": [value] });
+            }
+            value
+        };
+        // Each level nests an object and an array
+        assert!(check_json_depth(&nested(MAX_JSON_DEPTH / 2)).is_ok());
+        let error = check_json_depth(&nested(MAX_JSON_DEPTH / 2 + 1)).unwrap_err();
+        assert!(matches!(
+            error,
+            ValidationError::JsonTooDeep {
+                limit: MAX_JSON_DEPTH
+            }
+        ));
+        assert_eq!(error.scim_type(), ScimType::InvalidSyntax);
+        assert!(check_json_depth(&nested(200)).is_err());
+    }
+
     #[test]
     fn test_error_chain() {
         let validation_error = ValidationError::missing_required("userName");
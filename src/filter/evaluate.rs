@@ -0,0 +1,226 @@
+//! Evaluation of parsed filters against resource JSON.
+//!
+//! Multi-valued attributes match if any of their values match, so `not`
+//! around a comparison on a multi-valued attribute matches only when no value
+//! does. When a primitive comparison targets a complex value (e.g.
+//! `emails co "@example"`), the value's `value` sub-attribute is compared.
+//!
+//! String comparisons are case-insensitive unless the caller reports the
+//! compared attribute as `caseExact`. Inside a value path the attribute is
+//! reported as a sub-attribute of the value path, e.g. `emails.value` for
+//! `emails[value co "x"]`. Case folding is shared with sorting and
+//! uniqueness checks through [`crate::schema::case`].
+
+use super::{AttributePath, CompareOperator, Filter};
+use crate::schema::case;
+use chrono::{DateTime, FixedOffset};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// Whether string values of an attribute are compared case-sensitively.
+pub(super) type CaseExact<'a> = &'a dyn Fn(&AttributePath) -> bool;
+
+/// Evaluate `filter` against a resource.
+pub(super) fn matches(filter: &Filter, resource: &Value, case_exact: CaseExact<'_>) -> bool {
+    matches_within(filter, resource, None, case_exact)
+}
+
+/// Evaluate `filter` against a resource or, inside the value path `parent`,
+/// against one of its complex values.
+fn matches_within(
+    filter: &Filter,
+    resource: &Value,
+    parent: Option<&AttributePath>,
+    case_exact: CaseExact<'_>,
+) -> bool {
+    match filter {
+        Filter::Present(path) => resolve(resource, path).into_iter().any(is_present),
+        Filter::Compare(path, operator, expected) => {
+            let values = resolve(resource, path);
+            let path = qualify(parent, path);
+            let exact = case_exact(&path);
+            // Complex values are compared by their `value` sub-attribute
+            let value_exact = match &path.sub_attribute {
+                None => case_exact(&AttributePath {
+                    sub_attribute: Some("value".to_string()),
+                    ..path.clone()
+                }),
+                Some(_) => exact,
+            };
+            let compare = |actual: &Value, operator: CompareOperator| {
+                let exact = if actual.is_object() {
+                    value_exact
+                } else {
+                    exact
+                };
+                compare(actual, operator, expected, exact)
+            };
+            match (operator, expected) {
+                // `eq null` / `ne null` test for absence / presence
+                (CompareOperator::Equal, Value::Null) => !values.into_iter().any(is_present),
+                (CompareOperator::NotEqual, Value::Null) => values.into_iter().any(is_present),
+                (CompareOperator::NotEqual, _) => !values
+                    .into_iter()
+                    .any(|v| compare(v, CompareOperator::Equal)),
+                _ => values.into_iter().any(|v| compare(v, *operator)),
+            }
+        }
+        Filter::ValuePath(path, inner) => {
+            let qualified = qualify(parent, path);
+            resolve(resource, path)
+                .into_iter()
+                .any(|value| matches_within(inner, value, Some(&qualified), case_exact))
+        }
+        Filter::And(left, right) => {
+            matches_within(left, resource, parent, case_exact)
+                && matches_within(right, resource, parent, case_exact)
+        }
+        Filter::Or(left, right) => {
+            matches_within(left, resource, parent, case_exact)
+                || matches_within(right, resource, parent, case_exact)
+        }
+        Filter::Not(inner) => !matches_within(inner, resource, parent, case_exact),
+    }
+}
+
+/// The full path of an attribute named inside the value path `parent`.
+fn qualify(parent: Option<&AttributePath>, path: &AttributePath) -> AttributePath {
+    match parent {
+        Some(parent) if path.schema_uri.is_none() && path.sub_attribute.is_none() => {
+            AttributePath {
+                schema_uri: parent.schema_uri.clone(),
+                attribute: parent.attribute.clone(),
+                sub_attribute: Some(path.attribute.clone()),
+            }
+        }
+        _ => path.clone(),
+    }
+}
+
+/// Collect the values an attribute path refers to, flattening multi-valued attributes.
+pub(super) fn resolve<'v>(resource: &'v Value, path: &AttributePath) -> Vec<&'v Value> {
+    // Extension attributes live under their schema URN; core attributes are top-level
+    let base = path
+        .schema_uri
+        .as_deref()
+        .and_then(|uri| get_ignore_case(resource, uri))
+        .unwrap_or(resource);
+
+    let mut values = Vec::new();
+    if let Some(value) = get_ignore_case(base, &path.attribute) {
+        flatten_into(value, &mut values);
+    }
+
+    match &path.sub_attribute {
+        None => values,
+        Some(sub) => {
+            let mut sub_values = Vec::new();
+            for value in values {
+                if let Some(sub_value) = get_ignore_case(value, sub) {
+                    flatten_into(sub_value, &mut sub_values);
+                }
+            }
+            sub_values
+        }
+    }
+}
+
+fn flatten_into<'v>(value: &'v Value, out: &mut Vec<&'v Value>) {
+    match value {
+        Value::Array(items) => out.extend(items.iter()),
+        other => out.push(other),
+    }
+}
+
+fn get_ignore_case<'v>(value: &'v Value, key: &str) -> Option<&'v Value> {
+    let object = value.as_object()?;
+    object.get(key).or_else(|| {
+        object
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    })
+}
+
+fn is_present(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+        _ => true,
+    }
+}
+
+fn compare(actual: &Value, operator: CompareOperator, expected: &Value, case_exact: bool) -> bool {
+    // A primitive compared with a complex value targets its `value` sub-attribute
+    let actual = match actual {
+        Value::Object(_) if !expected.is_object() => match get_ignore_case(actual, "value") {
+            Some(inner) => inner,
+            None => return false,
+        },
+        other => other,
+    };
+
+    match (actual, expected) {
+        (Value::String(a), Value::String(e)) => compare_strings(a, operator, e, case_exact),
+        (Value::Number(a), Value::Number(e)) => match (a.as_f64(), e.as_f64()) {
+            (Some(a), Some(e)) => a
+                .partial_cmp(&e)
+                .is_some_and(|ordering| ordering_satisfies(ordering, operator)),
+            _ => false,
+        },
+        (Value::Bool(a), Value::Bool(e)) => match operator {
+            CompareOperator::Equal => a == e,
+            CompareOperator::NotEqual => a != e,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn compare_strings(
+    actual: &str,
+    operator: CompareOperator,
+    expected: &str,
+    case_exact: bool,
+) -> bool {
+    let (a, e) = (
+        case::fold(case_exact, actual),
+        case::fold(case_exact, expected),
+    );
+
+    match operator {
+        CompareOperator::Equal => a == e,
+        CompareOperator::NotEqual => a != e,
+        CompareOperator::Contains => a.contains(e.as_ref()),
+        CompareOperator::StartsWith => a.starts_with(e.as_ref()),
+        CompareOperator::EndsWith => a.ends_with(e.as_ref()),
+        _ => {
+            // Order dateTime values chronologically, everything else lexically
+            let ordering = match (parse_datetime(actual), parse_datetime(expected)) {
+                (Some(a), Some(e)) => a.cmp(&e),
+                _ => a.cmp(&e),
+            };
+            ordering_satisfies(ordering, operator)
+        }
+    }
+}
+
+fn parse_datetime(value: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(value).ok()
+}
+
+fn ordering_satisfies(ordering: Ordering, operator: CompareOperator) -> bool {
+    match operator {
+        CompareOperator::Equal => ordering == Ordering::Equal,
+        CompareOperator::NotEqual => ordering != Ordering::Equal,
+        CompareOperator::GreaterThan => ordering == Ordering::Greater,
+        CompareOperator::GreaterThanOrEqual => ordering != Ordering::Less,
+        CompareOperator::LessThan => ordering == Ordering::Less,
+        CompareOperator::LessThanOrEqual => ordering != Ordering::Greater,
+        CompareOperator::Contains | CompareOperator::StartsWith | CompareOperator::EndsWith => {
+            false
+        }
+    }
+}
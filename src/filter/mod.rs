@@ -0,0 +1,291 @@
+//! SCIM filter expressions (RFC 7644 Section 3.4.2.2).
+//!
+//! This module parses SCIM filter strings into a [`Filter`] tree and evaluates
+//! them against resource JSON. It is independent of any storage backend, so
+//! providers can use it to filter resources they have already loaded.
+//!
+//! # Supported Syntax
+//!
+//! - Comparison operators: `eq`, `ne`, `co`, `sw`, `ew`, `gt`, `ge`, `lt`, `le`
+//! - Presence: `pr`
+//! - Logical operators: `and`, `or`, `not (...)`, with grouping parentheses
+//! - Complex value filters: `emails[type eq "work" and value co "@example.com"]`
+//! - Sub-attributes and schema-qualified paths: `name.familyName`,
+//!   `urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:department`
+//!
+//! Operator keywords and attribute names are case-insensitive. String
+//! comparisons are case-insensitive unless the attribute is `caseExact`; see
+//! [`Filter::matches_with_case_exact`]. Comparisons on multi-valued attributes
+//! match if any value matches.
+//!
+//! # Limits
+//!
+//! Filters usually arrive from untrusted clients, so parsing is bounded:
+//! filters longer than [`MAX_FILTER_LENGTH`] bytes or nested deeper than
+//! [`MAX_FILTER_DEPTH`] levels are rejected with a [`FilterError`]. Each
+//! `not`, grouping parenthesis and value path adds a level; a flat chain of
+//! `and` or `or` terms does not.
+//! Malformed input of any kind yields an error rather than a panic.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use scim_server::filter::Filter;
+//! use serde_json::json;
+//!
+//! let filter = Filter::parse(r#"userName sw "j" and not (active eq false)"#).unwrap();
+//!
+//! let user = json!({"userName": "john.doe", "active": true});
+//! assert!(filter.matches(&user));
+//! ```
+
+mod evaluate;
+mod parser;
+
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// Maximum length in bytes of a filter string accepted by [`Filter::parse`].
+pub const MAX_FILTER_LENGTH: usize = 8 * 1024;
+
+/// Maximum nesting depth of a filter accepted by [`Filter::parse`].
+pub const MAX_FILTER_DEPTH: usize = 128;
+
+/// A parsed SCIM filter expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// `attrPath pr`
+    Present(AttributePath),
+    /// `attrPath compareOp compValue`
+    Compare(AttributePath, CompareOperator, Value),
+    /// `attrPath "[" valFilter "]"`, evaluated against each complex value
+    ValuePath(AttributePath, Box<Filter>),
+    /// `filter and filter`
+    And(Box<Filter>, Box<Filter>),
+    /// `filter or filter`
+    Or(Box<Filter>, Box<Filter>),
+    /// `not (filter)`
+    Not(Box<Filter>),
+}
+
+/// Attribute reference within a filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributePath {
+    /// Schema URN prefix, if the path was fully qualified
+    pub schema_uri: Option<String>,
+    /// Top-level attribute name
+    pub attribute: String,
+    /// Sub-attribute name for complex attributes
+    pub sub_attribute: Option<String>,
+}
+
+/// Comparison operators defined by RFC 7644.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompareOperator {
+    /// Equal
+    Equal,
+    /// Not equal
+    NotEqual,
+    /// Contains
+    Contains,
+    /// Starts with
+    StartsWith,
+    /// Ends with
+    EndsWith,
+    /// Greater than
+    GreaterThan,
+    /// Greater than or equal
+    GreaterThanOrEqual,
+    /// Less than
+    LessThan,
+    /// Less than or equal
+    LessThanOrEqual,
+}
+
+/// Error produced when a filter string cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid filter at position {position}: {message}")]
+pub struct FilterError {
+    /// Description of the problem
+    pub message: String,
+    /// Byte offset in the filter string where the problem was detected
+    pub position: usize,
+}
+
+impl FilterError {
+    pub(crate) fn new(message: impl Into<String>, position: usize) -> Self {
+        Self {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+impl Filter {
+    /// Parse a SCIM filter string.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FilterError`] if the filter is malformed, longer than
+    /// [`MAX_FILTER_LENGTH`] bytes or nested deeper than [`MAX_FILTER_DEPTH`].
+    pub fn parse(input: &str) -> Result<Self, FilterError> {
+        parser::parse(input)
+    }
+
+    /// Whether the given resource JSON satisfies this filter.
+    ///
+    /// String comparisons are case-insensitive; use
+    /// [`matches_with_case_exact`](Self::matches_with_case_exact) to honour
+    /// the schema's `caseExact` characteristic.
+    pub fn matches(&self, resource: &Value) -> bool {
+        evaluate::matches(self, resource, &|_| false)
+    }
+
+    /// Whether the given resource JSON satisfies this filter, comparing the
+    /// string values of attributes for which `case_exact` returns `true`
+    /// case-sensitively.
+    ///
+    /// Attributes compared inside a value path are passed as sub-attributes
+    /// of it, e.g. `emails.value` for `emails[value sw "a"]`.
+    pub fn matches_with_case_exact(
+        &self,
+        resource: &Value,
+        case_exact: impl Fn(&AttributePath) -> bool,
+    ) -> bool {
+        evaluate::matches(self, resource, &case_exact)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl AttributePath {
+    /// Create a path to a top-level attribute.
+    pub fn new(attribute: impl Into<String>) -> Self {
+        Self {
+            schema_uri: None,
+            attribute: attribute.into(),
+            sub_attribute: None,
+        }
+    }
+
+    /// Parse an attribute path such as `name.givenName` or
+    /// `urn:...:enterprise:2.0:User:manager.value`.
+    pub fn parse(path: &str) -> Option<Self> {
+        let (schema_uri, rest) = match path.rfind(':') {
+            Some(idx) => (Some(path[..idx].to_string()), &path[idx + 1..]),
+            None => (None, path),
+        };
+
+        let (attribute, sub_attribute) = match rest.split_once('.') {
+            Some((attr, sub)) => (attr, Some(sub)),
+            None => (rest, None),
+        };
+
+        let valid = |name: &str| {
+            let mut chars = name.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '$' || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '$')
+        };
+
+        if !valid(attribute) || sub_attribute.is_some_and(|sub| !valid(sub)) {
+            return None;
+        }
+
+        Some(Self {
+            schema_uri,
+            attribute: attribute.to_string(),
+            sub_attribute: sub_attribute.map(str::to_string),
+        })
+    }
+
+    /// Values this path refers to within a resource, with multi-valued
+    /// attributes flattened. Attribute names are matched case-insensitively.
+    pub fn values<'v>(&self, resource: &'v Value) -> Vec<&'v Value> {
+        evaluate::resolve(resource, self)
+    }
+}
+
+impl fmt::Display for AttributePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(uri) = &self.schema_uri {
+            write!(f, "{}:", uri)?;
+        }
+        write!(f, "{}", self.attribute)?;
+        if let Some(sub) = &self.sub_attribute {
+            write!(f, ".{}", sub)?;
+        }
+        Ok(())
+    }
+}
+
+impl CompareOperator {
+    /// Look up an operator by its (case-insensitive) keyword.
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword.to_ascii_lowercase().as_str() {
+            "eq" => Some(Self::Equal),
+            "ne" => Some(Self::NotEqual),
+            "co" => Some(Self::Contains),
+            "sw" => Some(Self::StartsWith),
+            "ew" => Some(Self::EndsWith),
+            "gt" => Some(Self::GreaterThan),
+            "ge" => Some(Self::GreaterThanOrEqual),
+            "lt" => Some(Self::LessThan),
+            "le" => Some(Self::LessThanOrEqual),
+            _ => None,
+        }
+    }
+
+    /// The operator's filter keyword.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::Equal => "eq",
+            Self::NotEqual => "ne",
+            Self::Contains => "co",
+            Self::StartsWith => "sw",
+            Self::EndsWith => "ew",
+            Self::GreaterThan => "gt",
+            Self::GreaterThanOrEqual => "ge",
+            Self::LessThan => "lt",
+            Self::LessThanOrEqual => "le",
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Present(path) => write!(f, "{} pr", path),
+            Self::Compare(path, op, value) => write!(f, "{} {} {}", path, op.keyword(), value),
+            Self::ValuePath(path, inner) => write!(f, "{}[{}]", path, inner),
+            // Chains associate to the left, so only a nested chain on the
+            // right, or an `or` under an `and`, needs grouping
+            Self::And(left, right) => {
+                match **left {
+                    Self::Or(..) => write!(f, "({})", left)?,
+                    _ => write!(f, "{}", left)?,
+                }
+                match **right {
+                    Self::And(..) | Self::Or(..) => write!(f, " and ({})", right),
+                    _ => write!(f, " and {}", right),
+                }
+            }
+            Self::Or(left, right) => match **right {
+                Self::Or(..) => write!(f, "{} or ({})", left, right),
+                _ => write!(f, "{} or {}", left, right),
+            },
+            Self::Not(inner) => write!(f, "not ({})", inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
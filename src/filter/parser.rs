@@ -0,0 +1,352 @@
+//! Recursive-descent parser for SCIM filter expressions.
+//!
+//! Grammar (RFC 7644 Section 3.4.2.2, precedence `not` > `and` > `or`):
+//!
+//! ```text
+//! filter    = or-expr
+//! or-expr   = and-expr *("or" and-expr)
+//! and-expr  = unary *("and" unary)
+//! unary     = "not" "(" filter ")" / "(" filter ")" / attr-expr
+//! attr-expr = attrPath "pr" / attrPath compareOp compValue / attrPath "[" filter "]"
+//! ```
+//!
+//! Input longer than [`MAX_FILTER_LENGTH`] is rejected before tokenizing, and
+//! nesting is tracked while parsing so no input can recurse deeper than
+//! [`MAX_FILTER_DEPTH`] levels. Only `not`, grouping parentheses and value
+//! paths nest; `and`/`or` chains are built in a loop, so their length is
+//! bounded by the input length alone.
+
+use super::{
+    AttributePath, CompareOperator, Filter, FilterError, MAX_FILTER_DEPTH, MAX_FILTER_LENGTH,
+};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    /// Attribute path, keyword, operator or bare literal
+    Word(String),
+    /// JSON string literal, already unescaped
+    Str(String),
+}
+
+#[derive(Debug, Clone)]
+struct Spanned {
+    token: Token,
+    position: usize,
+}
+
+/// Parse a complete filter string.
+pub(super) fn parse(input: &str) -> Result<Filter, FilterError> {
+    if input.len() > MAX_FILTER_LENGTH {
+        return Err(FilterError::new(
+            format!(
+                "filter exceeds the maximum length of {} bytes",
+                MAX_FILTER_LENGTH
+            ),
+            MAX_FILTER_LENGTH,
+        ));
+    }
+
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens,
+        index: 0,
+        end: input.len(),
+    };
+
+    if parser.tokens.is_empty() {
+        return Err(FilterError::new("filter is empty", 0));
+    }
+
+    let filter = parser.parse_or(0)?;
+    match parser.peek() {
+        None => Ok(filter),
+        Some(spanned) => Err(FilterError::new(
+            format!("unexpected {}", describe(&spanned.token)),
+            spanned.position,
+        )),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Spanned>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '"' => {
+                let end = string_end(input, position)?;
+                let literal: String = serde_json::from_str(&input[position..end])
+                    .map_err(|e| FilterError::new(format!("invalid string: {}", e), position))?;
+                while chars.peek().is_some_and(|&(i, _)| i < end) {
+                    chars.next();
+                }
+                tokens.push(Spanned {
+                    token: Token::Str(literal),
+                    position,
+                });
+                continue;
+            }
+            _ => {
+                let mut end = position;
+                while let Some(&(i, ch)) = chars.peek() {
+                    if ch.is_whitespace() || matches!(ch, '(' | ')' | '[' | ']' | '"') {
+                        break;
+                    }
+                    end = i + ch.len_utf8();
+                    chars.next();
+                }
+                tokens.push(Spanned {
+                    token: Token::Word(input[position..end].to_string()),
+                    position,
+                });
+                continue;
+            }
+        };
+        chars.next();
+        tokens.push(Spanned { token, position });
+    }
+
+    Ok(tokens)
+}
+
+/// Byte offset just past the closing quote of the string starting at `start`.
+fn string_end(input: &str, start: usize) -> Result<usize, FilterError> {
+    let mut escaped = false;
+    for (offset, c) in input[start + 1..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Ok(start + 1 + offset + 1),
+            _ => {}
+        }
+    }
+    Err(FilterError::new("unterminated string", start))
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::LeftParen => "'('".to_string(),
+        Token::RightParen => "')'".to_string(),
+        Token::LeftBracket => "'['".to_string(),
+        Token::RightBracket => "']'".to_string(),
+        Token::Word(word) => format!("'{}'", word),
+        Token::Str(s) => format!("string \"{}\"", s),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    index: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Spanned> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Option<Spanned> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn position(&self) -> usize {
+        self.peek().map_or(self.end, |spanned| spanned.position)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Spanned { token: Token::Word(word), .. }) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), FilterError> {
+        let position = self.position();
+        match self.next() {
+            Some(spanned) if spanned.token == expected => Ok(()),
+            Some(spanned) => Err(FilterError::new(
+                format!(
+                    "expected {}, found {}",
+                    describe(&expected),
+                    describe(&spanned.token)
+                ),
+                spanned.position,
+            )),
+            None => Err(FilterError::new(
+                format!("expected {}, found end of filter", describe(&expected)),
+                position,
+            )),
+        }
+    }
+
+    /// Reject nesting beyond [`MAX_FILTER_DEPTH`] at the current token.
+    fn check_depth(&self, depth: usize) -> Result<usize, FilterError> {
+        if depth > MAX_FILTER_DEPTH {
+            return Err(FilterError::new(
+                format!(
+                    "filter nesting exceeds the maximum depth of {}",
+                    MAX_FILTER_DEPTH
+                ),
+                self.position(),
+            ));
+        }
+        Ok(depth)
+    }
+
+    // Each parse function takes the nesting depth it starts at. Only the
+    // constructs that recurse into `parse_or` add a level.
+
+    fn parse_or(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        let mut left = self.parse_and(depth)?;
+        while self.peek_keyword("or") {
+            self.next();
+            let right = self.parse_and(depth)?;
+            left = Filter::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        let mut left = self.parse_unary(depth)?;
+        while self.peek_keyword("and") {
+            self.next();
+            let right = self.parse_unary(depth)?;
+            left = Filter::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        if self.peek_keyword("not") {
+            self.next();
+            let depth = self.check_depth(depth + 1)?;
+            self.expect(Token::LeftParen)?;
+            let inner = self.parse_or(depth)?;
+            self.expect(Token::RightParen)?;
+            return Ok(Filter::Not(Box::new(inner)));
+        }
+
+        if matches!(
+            self.peek(),
+            Some(Spanned {
+                token: Token::LeftParen,
+                ..
+            })
+        ) {
+            self.next();
+            let depth = self.check_depth(depth + 1)?;
+            let inner = self.parse_or(depth)?;
+            self.expect(Token::RightParen)?;
+            return Ok(inner);
+        }
+
+        self.parse_attribute_expression(depth)
+    }
+
+    fn parse_attribute_expression(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        let position = self.position();
+        let path = match self.next() {
+            Some(Spanned {
+                token: Token::Word(word),
+                position,
+            }) => AttributePath::parse(&word).ok_or_else(|| {
+                FilterError::new(format!("invalid attribute path '{}'", word), position)
+            })?,
+            Some(spanned) => {
+                return Err(FilterError::new(
+                    format!(
+                        "expected attribute path, found {}",
+                        describe(&spanned.token)
+                    ),
+                    spanned.position,
+                ));
+            }
+            None => {
+                return Err(FilterError::new(
+                    "expected attribute path, found end of filter",
+                    position,
+                ));
+            }
+        };
+
+        let position = self.position();
+        match self.next() {
+            Some(Spanned {
+                token: Token::LeftBracket,
+                ..
+            }) => {
+                let depth = self.check_depth(depth + 1)?;
+                let inner = self.parse_or(depth)?;
+                self.expect(Token::RightBracket)?;
+                Ok(Filter::ValuePath(path, Box::new(inner)))
+            }
+            Some(Spanned {
+                token: Token::Word(word),
+                position,
+            }) => {
+                if word.eq_ignore_ascii_case("pr") {
+                    return Ok(Filter::Present(path));
+                }
+                let operator = CompareOperator::from_keyword(&word).ok_or_else(|| {
+                    FilterError::new(format!("unknown operator '{}'", word), position)
+                })?;
+                let value = self.parse_value()?;
+                Ok(Filter::Compare(path, operator, value))
+            }
+            Some(spanned) => Err(FilterError::new(
+                format!("expected operator, found {}", describe(&spanned.token)),
+                spanned.position,
+            )),
+            None => Err(FilterError::new(
+                "expected operator, found end of filter",
+                position,
+            )),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, FilterError> {
+        let position = self.position();
+        match self.next() {
+            Some(Spanned {
+                token: Token::Str(s),
+                ..
+            }) => Ok(Value::String(s)),
+            Some(Spanned {
+                token: Token::Word(word),
+                position,
+            }) => match word.to_ascii_lowercase().as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                "null" => Ok(Value::Null),
+                _ => serde_json::from_str::<serde_json::Number>(&word)
+                    .map(Value::Number)
+                    .map_err(|_| {
+                        FilterError::new(format!("invalid comparison value '{}'", word), position)
+                    }),
+            },
+            Some(spanned) => Err(FilterError::new(
+                format!(
+                    "expected comparison value, found {}",
+                    describe(&spanned.token)
+                ),
+                spanned.position,
+            )),
+            None => Err(FilterError::new(
+                "expected comparison value, found end of filter",
+                position,
+            )),
+        }
+    }
+}
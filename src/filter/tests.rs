@@ -0,0 +1,287 @@
+//! Tests for filter parsing and evaluation.
+
+use super::{AttributePath, CompareOperator, Filter, MAX_FILTER_DEPTH, MAX_FILTER_LENGTH};
+use proptest::prelude::*;
+use serde_json::{Value, json};
+
+fn user() -> Value {
+    json!({
+        "schemas": [
+            "urn:ietf:params:scim:schemas:core:2.0:User",
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User"
+        ],
+        "id": "2819c223",
+        "userName": "Bjensen",
+        "active": true,
+        "name": {"familyName": "Jensen", "givenName": "Barbara"},
+        "emails": [
+            {"value": "bjensen@example.com", "type": "work", "primary": true},
+            {"value": "babs@jensen.org", "type": "home"}
+        ],
+        "meta": {"lastModified": "2011-05-13T04:42:34Z"},
+        "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+            "department": "Tour Operations",
+            "employeeNumber": "701984"
+        }
+    })
+}
+
+fn check(filter: &str) -> bool {
+    Filter::parse(filter)
+        .unwrap_or_else(|e| panic!("failed to parse '{}': {}", filter, e))
+        .matches(&user())
+}
+
+#[test]
+fn test_parse_structure() {
+    let filter = Filter::parse(r#"userName eq "bjensen" and not (active eq false)"#).unwrap();
+    assert_eq!(
+        filter,
+        Filter::And(
+            Box::new(Filter::Compare(
+                AttributePath::new("userName"),
+                CompareOperator::Equal,
+                json!("bjensen"),
+            )),
+            Box::new(Filter::Not(Box::new(Filter::Compare(
+                AttributePath::new("active"),
+                CompareOperator::Equal,
+                json!(false),
+            )))),
+        )
+    );
+}
+
+#[test]
+fn test_and_binds_tighter_than_or() {
+    let filter = Filter::parse("a pr or b pr and c pr").unwrap();
+    assert!(matches!(filter, Filter::Or(_, ref right) if matches!(**right, Filter::And(_, _))));
+}
+
+#[test]
+fn test_comparison_operators() {
+    assert!(check(r#"userName eq "bjensen""#));
+    assert!(check(r#"USERNAME EQ "BJENSEN""#));
+    assert!(check(r#"userName ne "someone""#));
+    assert!(check(r#"name.familyName co "ens""#));
+    assert!(check(r#"userName sw "bj""#));
+    assert!(check(r#"userName ew "sen""#));
+    assert!(check(r#"meta.lastModified gt "2011-05-13T04:42:34+01:00""#));
+    assert!(check(r#"meta.lastModified le "2011-05-13T04:42:34Z""#));
+    assert!(!check(r#"meta.lastModified lt "2011-05-13T04:42:34Z""#));
+    assert!(check("active eq true"));
+    assert!(!check(r#"userName eq "other""#));
+}
+
+#[test]
+fn test_presence_and_null() {
+    assert!(check("title pr or userName pr"));
+    assert!(!check("title pr"));
+    assert!(check("title eq null"));
+    assert!(!check("userName eq null"));
+}
+
+#[test]
+fn test_multi_valued_and_value_path() {
+    assert!(check(r#"emails co "example.com""#));
+    assert!(check(r#"emails.type eq "home""#));
+    assert!(check(
+        r#"emails[type eq "work" and value co "@example.com"]"#
+    ));
+    assert!(!check(
+        r#"emails[type eq "home" and value co "@example.com"]"#
+    ));
+    assert!(check(r#"emails[primary eq true]"#));
+}
+
+#[test]
+fn test_extension_paths() {
+    assert!(check(
+        r#"urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:department eq "Tour Operations""#
+    ));
+    assert!(check(
+        r#"urn:ietf:params:scim:schemas:core:2.0:User:userName sw "B""#
+    ));
+}
+
+#[test]
+fn test_parse_errors_report_position() {
+    let err = Filter::parse(r#"userName xx "a""#).unwrap_err();
+    assert_eq!(err.position, 9);
+
+    let err = Filter::parse(r#"userName eq "unterminated"#).unwrap_err();
+    assert_eq!(err.position, 12);
+
+    assert!(Filter::parse("").is_err());
+    assert!(Filter::parse("(userName pr").is_err());
+    assert!(Filter::parse("userName pr)").is_err());
+    assert!(Filter::parse("not userName pr").is_err());
+    assert!(Filter::parse("userName eq").is_err());
+}
+
+#[test]
+fn test_substring_operators_on_multi_valued_attributes() {
+    // Any value may match, whether addressed by value path or sub-attribute
+    assert!(check(r#"emails[value co "@example.com"]"#));
+    assert!(check(r#"emails.value sw "babs""#));
+    assert!(check(r#"emails.value ew "jensen.org""#));
+    assert!(!check(r#"emails.value sw "admin""#));
+
+    // Negation holds only when no value matches
+    assert!(!check(r#"not (emails.value co "jensen")"#));
+    assert!(check(r#"not (emails.value co "admin")"#));
+    assert!(!check(r#"not (emails[value ew ".org"])"#));
+}
+
+#[test]
+fn test_case_exact_attributes() {
+    let filter = |filter: &str| Filter::parse(filter).unwrap();
+    let case_exact = |path: &AttributePath| {
+        path.attribute.eq_ignore_ascii_case("emails")
+            && path
+                .sub_attribute
+                .as_deref()
+                .is_some_and(|sub| sub.eq_ignore_ascii_case("value"))
+    };
+    let matches = |f: &str| filter(f).matches_with_case_exact(&user(), case_exact);
+
+    assert!(filter(r#"emails.value sw "BJENSEN""#).matches(&user()));
+    assert!(!matches(r#"emails.value sw "BJENSEN""#));
+    assert!(matches(r#"emails.value sw "bjensen""#));
+    assert!(!matches(r#"emails[value co "EXAMPLE"]"#));
+    assert!(!matches(r#"emails co "EXAMPLE""#));
+    assert!(matches(r#"not (emails.value co "EXAMPLE")"#));
+
+    // Other sub-attributes of the same attribute keep their own setting
+    assert!(matches(r#"emails[type eq "WORK" and value co "example"]"#));
+    assert!(matches(r#"emails.type eq "HOME""#));
+}
+
+#[test]
+fn test_filter_limits() {
+    let nested = |depth: usize| format!("{}userName pr{}", "(".repeat(depth), ")".repeat(depth));
+    assert!(Filter::parse(&nested(MAX_FILTER_DEPTH)).is_ok());
+    let error = Filter::parse(&nested(MAX_FILTER_DEPTH + 1)).unwrap_err();
+    assert!(error.message.contains("maximum depth"));
+
+    // Far deeper input is rejected without exhausting the stack
+    assert!(Filter::parse(&nested(100_000)).is_err());
+    assert!(Filter::parse(&"not (".repeat(100_000)).is_err());
+    assert!(Filter::parse(&"emails[".repeat(100_000)).is_err());
+
+    // Flat and/or chains do not nest, so they are bounded only by length,
+    // and print back to a filter that parses
+    let chain = |terms: usize, operator: &str| vec!["a pr"; terms].join(operator);
+    for operator in [" or ", " and "] {
+        let filter = Filter::parse(&chain(MAX_FILTER_LENGTH / 9, operator)).unwrap();
+        assert!(filter.matches(&json!({"a": 1})));
+        assert_eq!(Filter::parse(&filter.to_string()), Ok(filter));
+    }
+
+    let long = format!(r#"userName eq "{}""#, "a".repeat(MAX_FILTER_LENGTH));
+    let error = Filter::parse(&long).unwrap_err();
+    assert!(error.message.contains("maximum length"));
+    assert_eq!(error.position, MAX_FILTER_LENGTH);
+}
+
+fn attribute_path() -> impl Strategy<Value = AttributePath> {
+    let name = "[a-zA-Z][a-zA-Z0-9_]{0,8}"
+        .prop_filter("not a keyword", |name| !name.eq_ignore_ascii_case("not"));
+    (
+        prop::option::of(Just(
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User".to_string(),
+        )),
+        name.clone(),
+        prop::option::of(name),
+    )
+        .prop_map(|(schema_uri, attribute, sub_attribute)| AttributePath {
+            schema_uri,
+            attribute,
+            sub_attribute,
+        })
+}
+
+fn comparison_value() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        any::<String>().prop_map(Value::String),
+        any::<i64>().prop_map(|n| json!(n)),
+        any::<bool>().prop_map(Value::Bool),
+        Just(Value::Null),
+    ]
+}
+
+fn valid_filter() -> impl Strategy<Value = Filter> {
+    let operator = prop::sample::select(vec![
+        CompareOperator::Equal,
+        CompareOperator::NotEqual,
+        CompareOperator::Contains,
+        CompareOperator::StartsWith,
+        CompareOperator::EndsWith,
+        CompareOperator::GreaterThan,
+        CompareOperator::GreaterThanOrEqual,
+        CompareOperator::LessThan,
+        CompareOperator::LessThanOrEqual,
+    ]);
+    let leaf = prop_oneof![
+        attribute_path().prop_map(Filter::Present),
+        (attribute_path(), operator, comparison_value())
+            .prop_map(|(path, operator, value)| Filter::Compare(path, operator, value)),
+    ];
+    leaf.prop_recursive(4, 32, 2, |inner| {
+        prop_oneof![
+            (inner.clone(), inner.clone())
+                .prop_map(|(left, right)| Filter::And(Box::new(left), Box::new(right))),
+            (inner.clone(), inner.clone())
+                .prop_map(|(left, right)| Filter::Or(Box::new(left), Box::new(right))),
+            inner
+                .clone()
+                .prop_map(|filter| Filter::Not(Box::new(filter))),
+            (attribute_path(), inner)
+                .prop_map(|(path, filter)| Filter::ValuePath(path, Box::new(filter))),
+        ]
+    })
+}
+
+/// Fragments of filter syntax, so generated input reaches past the tokenizer.
+fn filter_fragments() -> impl Strategy<Value = String> {
+    let fragment = prop_oneof![
+        Just("(".to_string()),
+        Just(")".to_string()),
+        Just("[".to_string()),
+        Just("]".to_string()),
+        Just("\"".to_string()),
+        Just("\\".to_string()),
+        Just(" ".to_string()),
+        Just(".".to_string()),
+        Just(":".to_string()),
+        prop::sample::select(vec![
+            "and", "or", "not", "pr", "eq", "co", "gt", "true", "null", "1e999", "-0",
+        ])
+        .prop_map(str::to_string),
+        "[a-zA-Z0-9_]{1,6}",
+        r#""[^"]{0,6}""#,
+    ];
+    prop::collection::vec(fragment, 0..32).prop_map(|fragments| fragments.concat())
+}
+
+proptest! {
+    #[test]
+    fn prop_arbitrary_input_never_panics(input in any::<String>()) {
+        let _ = Filter::parse(&input);
+    }
+
+    #[test]
+    fn prop_malformed_filters_are_errors_not_panics(input in filter_fragments()) {
+        // Whatever parses must evaluate and print back to an equivalent filter
+        if let Ok(filter) = Filter::parse(&input) {
+            filter.matches(&user());
+            prop_assert_eq!(Filter::parse(&filter.to_string()), Ok(filter));
+        }
+    }
+
+    #[test]
+    fn prop_valid_filters_always_parse(filter in valid_filter()) {
+        let text = filter.to_string();
+        prop_assert_eq!(Filter::parse(&text), Ok(filter));
+    }
+}
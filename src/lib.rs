@@ -61,6 +61,7 @@
 
 pub mod auth;
 pub mod error;
+pub mod filter;
 /// Model Context Protocol integration for AI agents.
 ///
 /// This module is only available when the `mcp` feature is enabled.
@@ -79,13 +80,19 @@ pub mod scim_server;
 pub mod storage;
 
 // Re-export commonly used types for convenience
-pub use error::{ScimError, ScimResult};
+pub use error::{ScimError, ScimResult, ScimType};
 pub use providers::ResourceProvider;
 pub use resource::{IsolationLevel, TenantPermissions};
-pub use resource::{ListQuery, RequestContext, Resource, ScimOperation, TenantContext};
+pub use resource::{ListQuery, RequestContext, Resource, ScimOperation, SortOrder, TenantContext};
 pub use schema::{Schema, SchemaRegistry};
 pub use schema_discovery::SchemaDiscovery;
-pub use scim_server::{ScimServer, ScimServerBuilder, ScimServerConfig, TenantStrategy};
+pub use scim_server::{
+    AllowAll, AuthorizationPolicy, ComputedAttribute, DanglingReference, DefaultListOrder,
+    DiscoveryDocument, DuplicateMemberPolicy, EntryDisplayPolicy, GroupMembership, ImportFailure,
+    ImportReport, LocationPolicy, PartialPatch, PatchMode, PatchOperationOutcome,
+    ResourceTypeResolution, ScimServer, ScimServerBuilder, ScimServerConfig, SelfTestProblem,
+    SelfTestReport, TenantStrategy, ValidationSettings, VersionExposure,
+};
 
 // Re-export additional types needed by examples and advanced usage
 pub use operation_handler::{
@@ -93,13 +100,15 @@ pub use operation_handler::{
 };
 pub use provider_capabilities::{
     AuthenticationCapabilities, BulkCapabilities, CapabilityIntrospectable, ExtendedCapabilities,
-    FilterOperator, PaginationCapabilities, ProviderCapabilities,
+    FilterOperator, PaginationCapabilities, ProviderCapabilities, ProviderCapabilitiesBuilder,
 };
 pub use resource_handlers::{create_group_resource_handler, create_user_resource_handler};
 pub use schema_discovery::AuthenticationScheme;
 
 // Multi-tenant types
-pub use multi_tenant::{ScimTenantConfiguration, StaticTenantResolver, TenantResolver};
+pub use multi_tenant::{
+    ScimTenantConfiguration, StaticTenantResolver, TenantResolver, UnknownAttributePolicy,
+};
 
 // MCP integration re-exports (feature-gated)
 /// Model Context Protocol integration types.
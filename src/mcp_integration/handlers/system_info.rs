@@ -70,6 +70,7 @@ pub async fn handle_server_info<P: ResourceProvider + Send + Sync + 'static>(
         "version": server.server_info.version,
         "description": server.server_info.description,
         "supported_resource_types": server.server_info.supported_resource_types,
+        "authentication_schemes": server.operation_handler.server().config().authentication_schemes,
         "capabilities": {
             "user_management": true,
             "multi_tenant": true,
@@ -366,6 +366,30 @@ mod mcp_tests {
         }
     }
 
+    /// Test that configured authentication schemes are reported by server info
+    #[tokio::test]
+    async fn test_server_info_reports_authentication_schemes() {
+        let scim_server =
+            ScimServerBuilder::new(StandardResourceProvider::new(InMemoryStorage::new()))
+                .with_base_url("mcp://scim".to_string())
+                .with_authentication_scheme(crate::AuthenticationScheme::bearer().as_primary())
+                .build()
+                .expect("Failed to build SCIM server");
+        let mcp_server = ScimMcpServer::new(scim_server);
+
+        let result = mcp_server.execute_tool("scim_server_info", json!({})).await;
+        assert!(result.success);
+
+        let schemes = result.content["authentication_schemes"].as_array().unwrap();
+        assert_eq!(schemes.len(), 1);
+        assert_eq!(schemes[0]["type"], "oauthbearertoken");
+        assert_eq!(
+            schemes[0]["specUri"],
+            "https://www.rfc-editor.org/info/rfc6750"
+        );
+        assert_eq!(schemes[0]["primary"], true);
+    }
+
     /// Test server info functionality
     #[test]
     fn test_server_info() {
@@ -6,7 +6,7 @@
 //! Since the ResourceProvider is now unified, these are primarily validation and
 //! convenience utilities rather than true adapters.
 
-use crate::providers::ResourceProvider;
+use crate::providers::{ProviderError, ResourceProvider};
 use crate::resource::version::RawVersion;
 use crate::resource::{ListQuery, RequestContext, TenantContext, versioned::VersionedResource};
 use serde_json::Value;
@@ -28,6 +28,12 @@ pub enum AdapterError<E> {
     ContextConversion { message: String },
 }
 
+impl<E: From<ProviderError>> From<ProviderError> for AdapterError<E> {
+    fn from(error: ProviderError) -> Self {
+        AdapterError::Provider(error.into())
+    }
+}
+
 /// Validation wrapper that ensures tenant context is properly handled.
 ///
 /// This wrapper validates tenant contexts and provides clear error messages
@@ -144,6 +150,42 @@ where
         }
     }
 
+    fn default_tenant_id(&self) -> &str {
+        self.inner.default_tenant_id()
+    }
+
+    fn set_default_tenant_id(&mut self, tenant_id: &str) {
+        self.inner.set_default_tenant_id(tenant_id)
+    }
+
+    async fn count_resources(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> Result<usize, Self::Error> {
+        self.validate_context_consistency(context)?;
+
+        self.inner
+            .count_resources(resource_type, query, context)
+            .await
+            .map_err(AdapterError::Provider)
+    }
+
+    async fn list_ids(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> Result<Vec<String>, Self::Error> {
+        self.validate_context_consistency(context)?;
+
+        self.inner
+            .list_ids(resource_type, query, context)
+            .await
+            .map_err(AdapterError::Provider)
+    }
+
     fn find_resources_by_attribute(
         &self,
         resource_type: &str,
@@ -184,6 +226,30 @@ where
         }
     }
 
+    async fn patch_resource_checked<E: Send>(
+        &self,
+        resource_type: &str,
+        id: &str,
+        patch_request: &Value,
+        expected_version: Option<&RawVersion>,
+        check: impl Fn(&Value, &mut Value) -> Result<(), E> + Send + Sync,
+        context: &RequestContext,
+    ) -> Result<Result<VersionedResource, E>, Self::Error> {
+        self.validate_context_consistency(context)?;
+
+        self.inner
+            .patch_resource_checked(
+                resource_type,
+                id,
+                patch_request,
+                expected_version,
+                check,
+                context,
+            )
+            .await
+            .map_err(AdapterError::Provider)
+    }
+
     fn resource_exists(
         &self,
         resource_type: &str,
@@ -294,6 +360,12 @@ mod tests {
     #[error("Mock error")]
     struct MockError;
 
+    impl From<ProviderError> for MockError {
+        fn from(_: ProviderError) -> Self {
+            MockError
+        }
+    }
+
     struct MockProvider;
 
     impl ResourceProvider for MockProvider {
@@ -0,0 +1,478 @@
+//! Caching decorator for tenant resolvers.
+//!
+//! [`CachingTenantResolver`] wraps any [`TenantResolver`] and remembers the
+//! tenant context each credential resolved to, so that costly resolution,
+//! such as validating a JWT or looking up an API key, runs once per
+//! credential rather than once per request. Only successful resolutions are
+//! cached; failures always reach the wrapped resolver.
+//!
+//! Credentials are never stored: entries are keyed by the SHA-256 hash of the
+//! credential. The cache is a bounded LRU, so many distinct credentials evict
+//! the least recently used entries instead of growing it without limit.
+//!
+//! An entry is served until the configured TTL elapses, or until the `exp`
+//! claim of a JWT credential passes, whichever comes first; a JWT that has
+//! already expired is not cached at all. The claim is only read to bound the
+//! entry's lifetime, and validating the token remains the wrapped resolver's
+//! job. Revoked credentials or suspended tenants can be evicted with
+//! [`invalidate`](CachingTenantResolver::invalidate) and
+//! [`invalidate_tenant`](CachingTenantResolver::invalidate_tenant).
+//!
+//! # Examples
+//!
+//! ```rust
+//! use scim_server::multi_tenant::{CachingTenantResolver, StaticTenantResolver};
+//! use scim_server::providers::CacheConfig;
+//! use std::time::Duration;
+//!
+//! let resolver = CachingTenantResolver::with_config(
+//!     StaticTenantResolver::new(),
+//!     CacheConfig::new(10_000).with_ttl(Duration::from_secs(300)),
+//! );
+//! ```
+
+use super::resolver::TenantResolver;
+use crate::providers::CacheConfig;
+use crate::providers::lru::LruCache;
+use crate::resource::TenantContext;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// SHA-256 hash of a credential.
+type CredentialHash = [u8; 32];
+
+/// A [`TenantResolver`] decorator that caches successful resolutions.
+pub struct CachingTenantResolver<R> {
+    inner: R,
+    config: CacheConfig,
+    cache: Mutex<Cache>,
+}
+
+/// Cached resolutions, and a count of the invalidations that removed them.
+#[derive(Default)]
+struct Cache {
+    entries: LruCache<CredentialHash, TenantContext>,
+    // Bumped by every invalidation, so a resolution can tell whether one ran
+    // while it was in flight
+    generation: u64,
+}
+
+impl<R> CachingTenantResolver<R> {
+    /// Wrap a resolver with the default cache configuration.
+    pub fn new(inner: R) -> Self {
+        Self::with_config(inner, CacheConfig::default())
+    }
+
+    /// Wrap a resolver with the given cache configuration.
+    pub fn with_config(inner: R, config: CacheConfig) -> Self {
+        Self {
+            inner,
+            config,
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    /// The wrapped resolver.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// The cache configuration.
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Number of credentials currently cached, including expired entries not
+    /// yet evicted.
+    pub fn cached_len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// When the cached resolution of a credential expires, if it is cached.
+    /// `Some(None)` means the entry has no expiry.
+    pub fn cached_until(&self, credential: &str) -> Option<Option<Instant>> {
+        self.lock().entries.expires_at(&hash_credential(credential))
+    }
+
+    /// Drop the cached resolution of a credential, e.g. after revoking it.
+    pub fn invalidate(&self, credential: &str) {
+        let mut cache = self.lock();
+        cache.entries.remove(&hash_credential(credential));
+        cache.generation += 1;
+    }
+
+    /// Drop every cached resolution to a tenant, e.g. after suspending it.
+    pub fn invalidate_tenant(&self, tenant_id: &str) {
+        let mut cache = self.lock();
+        cache
+            .entries
+            .retain(|tenant_context| tenant_context.tenant_id != tenant_id);
+        cache.generation += 1;
+    }
+
+    /// Drop every cached resolution.
+    pub fn invalidate_all(&self) {
+        let mut cache = self.lock();
+        cache.entries.clear();
+        cache.generation += 1;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cache> {
+        // A panic cannot leave the LRU bookkeeping half-updated in a way that
+        // matters; at worst an entry is evicted early
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// When a resolution cached now should expire, or `None` if the
+    /// credential is an expired JWT and must not be cached.
+    fn expiry(&self, credential: &str) -> Option<Option<Instant>> {
+        let now = Instant::now();
+        let ttl_expiry = self.config.ttl.and_then(|ttl| now.checked_add(ttl));
+        let Some(exp) = jwt_expiry(credential) else {
+            return Some(ttl_expiry);
+        };
+
+        let remaining = exp.duration_since(SystemTime::now()).ok()?;
+        // An expiry too far away to represent does not bound the entry
+        let Some(token_expiry) = now.checked_add(remaining) else {
+            return Some(ttl_expiry);
+        };
+        Some(Some(match ttl_expiry {
+            Some(ttl_expiry) => ttl_expiry.min(token_expiry),
+            None => token_expiry,
+        }))
+    }
+}
+
+impl<R: TenantResolver> TenantResolver for CachingTenantResolver<R> {
+    type Error = R::Error;
+
+    async fn resolve_tenant(&self, credential: &str) -> Result<TenantContext, Self::Error> {
+        let key = hash_credential(credential);
+        let generation = {
+            let mut cache = self.lock();
+            if let Some(tenant_context) = cache.entries.get(&key) {
+                return Ok(tenant_context);
+            }
+            cache.generation
+        };
+
+        let tenant_context = self.inner.resolve_tenant(credential).await?;
+        if let Some(expires_at) = self.expiry(credential) {
+            // A resolution that overlapped an invalidation may predate a
+            // revocation, so it is returned but not cached
+            let mut cache = self.lock();
+            if cache.generation == generation {
+                cache.entries.insert(
+                    key,
+                    tenant_context.clone(),
+                    expires_at,
+                    self.config.capacity,
+                );
+            }
+        }
+        Ok(tenant_context)
+    }
+
+    async fn validate_tenant(&self, tenant_id: &str) -> Result<bool, Self::Error> {
+        self.inner.validate_tenant(tenant_id).await
+    }
+
+    async fn list_tenants(&self) -> Result<Vec<String>, Self::Error> {
+        self.inner.list_tenants().await
+    }
+}
+
+fn hash_credential(credential: &str) -> CredentialHash {
+    Sha256::digest(credential.as_bytes()).into()
+}
+
+/// The `exp` claim of a credential that is a JWT, optionally prefixed with
+/// `Bearer `, or `None` if it has none that can be represented. Signatures
+/// are not checked.
+fn jwt_expiry(credential: &str) -> Option<SystemTime> {
+    let token = credential
+        .get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("bearer "))
+        .map_or(credential, |_| &credential[7..]);
+    let mut segments = token.split('.');
+    let (Some(_header), Some(payload), Some(_signature), None) = (
+        segments.next(),
+        segments.next(),
+        segments.next(),
+        segments.next(),
+    ) else {
+        return None;
+    };
+
+    let payload = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: Value = serde_json::from_slice(&payload).ok()?;
+    // NumericDate may be fractional; a second that has begun counts as passed
+    let exp = claims.get("exp")?.as_f64()?.floor();
+    if !(0.0..=u64::MAX as f64).contains(&exp) {
+        return None;
+    }
+    UNIX_EPOCH.checked_add(Duration::from_secs(exp as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multi_tenant::StaticTenantResolver;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts resolutions reaching the wrapped resolver.
+    struct CountingResolver {
+        inner: StaticTenantResolver,
+        calls: AtomicUsize,
+    }
+
+    impl TenantResolver for CountingResolver {
+        type Error = <StaticTenantResolver as TenantResolver>::Error;
+
+        async fn resolve_tenant(&self, credential: &str) -> Result<TenantContext, Self::Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.inner.resolve_tenant(credential).await
+        }
+
+        async fn validate_tenant(&self, tenant_id: &str) -> Result<bool, Self::Error> {
+            self.inner.validate_tenant(tenant_id).await
+        }
+    }
+
+    /// Holds every resolution until a permit is added.
+    struct GatedResolver {
+        inner: StaticTenantResolver,
+        gate: tokio::sync::Semaphore,
+    }
+
+    impl TenantResolver for GatedResolver {
+        type Error = <StaticTenantResolver as TenantResolver>::Error;
+
+        async fn resolve_tenant(&self, credential: &str) -> Result<TenantContext, Self::Error> {
+            let _permit = self.gate.acquire().await.unwrap();
+            self.inner.resolve_tenant(credential).await
+        }
+
+        async fn validate_tenant(&self, tenant_id: &str) -> Result<bool, Self::Error> {
+            self.inner.validate_tenant(tenant_id).await
+        }
+    }
+
+    fn jwt(exp: impl serde::Serialize) -> String {
+        let segment = |value: Value| URL_SAFE_NO_PAD.encode(value.to_string());
+        format!(
+            "{}.{}.signature",
+            segment(json!({"alg": "HS256", "typ": "JWT"})),
+            segment(json!({"sub": "client", "exp": exp}))
+        )
+    }
+
+    fn unix_now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    async fn resolver(
+        credentials: &[(&str, &str)],
+        config: CacheConfig,
+    ) -> CachingTenantResolver<CountingResolver> {
+        let inner = StaticTenantResolver::new();
+        for (credential, tenant_id) in credentials {
+            inner
+                .add_tenant(
+                    credential,
+                    TenantContext::new(tenant_id.to_string(), "client".to_string()),
+                )
+                .await;
+        }
+        CachingTenantResolver::with_config(
+            CountingResolver {
+                inner,
+                calls: AtomicUsize::new(0),
+            },
+            config,
+        )
+    }
+
+    fn calls(resolver: &CachingTenantResolver<CountingResolver>) -> usize {
+        resolver.inner().calls.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_resolutions_are_cached_and_invalidated() {
+        let resolver = resolver(
+            &[("key-a", "acme"), ("key-b", "acme"), ("key-c", "globex")],
+            CacheConfig::default(),
+        )
+        .await;
+
+        for _ in 0..3 {
+            let tenant = resolver.resolve_tenant("key-a").await.unwrap();
+            assert_eq!(tenant.tenant_id, "acme");
+        }
+        assert_eq!(calls(&resolver), 1);
+
+        // Failures are not cached
+        assert!(resolver.resolve_tenant("unknown").await.is_err());
+        assert!(resolver.resolve_tenant("unknown").await.is_err());
+        assert_eq!(calls(&resolver), 3);
+
+        // Revoking a credential evicts its entry
+        resolver.inner().inner.remove_tenant("key-a").await;
+        assert!(resolver.resolve_tenant("key-a").await.is_ok());
+        resolver.invalidate("key-a");
+        assert!(resolver.resolve_tenant("key-a").await.is_err());
+
+        resolver.resolve_tenant("key-b").await.unwrap();
+        resolver.resolve_tenant("key-c").await.unwrap();
+        resolver.invalidate_tenant("acme");
+        assert!(resolver.cached_until("key-b").is_none());
+        assert!(resolver.cached_until("key-c").is_some());
+        resolver.invalidate_all();
+        assert_eq!(resolver.cached_len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_capacity_bounds_the_cache() {
+        let credentials: Vec<(String, &str)> =
+            (0..10).map(|n| (format!("key-{}", n), "acme")).collect();
+        let credentials: Vec<(&str, &str)> = credentials
+            .iter()
+            .map(|(credential, tenant)| (credential.as_str(), *tenant))
+            .collect();
+        let resolver = resolver(&credentials, CacheConfig::new(3)).await;
+
+        for (credential, _) in &credentials {
+            resolver.resolve_tenant(credential).await.unwrap();
+        }
+        assert_eq!(resolver.cached_len(), 3);
+        // The least recently used credentials were evicted
+        assert!(resolver.cached_until("key-0").is_none());
+        assert!(resolver.cached_until("key-9").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_entries_do_not_outlive_jwt_expiry() {
+        let live = jwt(unix_now() + 30);
+        let expired = jwt(unix_now() - 30);
+        let fractional = jwt(unix_now() as f64 + 30.5);
+        let resolver = resolver(
+            &[
+                (live.as_str(), "acme"),
+                (expired.as_str(), "acme"),
+                (fractional.as_str(), "acme"),
+            ],
+            CacheConfig::new(10).with_ttl(Duration::from_secs(3600)),
+        )
+        .await;
+
+        resolver.resolve_tenant(&live).await.unwrap();
+        let expires_at = resolver.cached_until(&live).unwrap().unwrap();
+        assert!(expires_at <= Instant::now() + Duration::from_secs(30));
+        assert!(expires_at > Instant::now() + Duration::from_secs(20));
+
+        // A fractional NumericDate is honoured too
+        resolver.resolve_tenant(&fractional).await.unwrap();
+        let expires_at = resolver.cached_until(&fractional).unwrap().unwrap();
+        assert!(expires_at <= Instant::now() + Duration::from_secs(31));
+        assert!(expires_at > Instant::now() + Duration::from_secs(20));
+
+        // An expired token is never cached, so every use reaches the resolver
+        resolver.resolve_tenant(&expired).await.unwrap();
+        resolver.resolve_tenant(&expired).await.unwrap();
+        assert!(resolver.cached_until(&expired).is_none());
+        assert_eq!(calls(&resolver), 4);
+
+        // Without a TTL the token's expiry still applies
+        let resolver = super::CachingTenantResolver::with_config(
+            StaticTenantResolver::new(),
+            CacheConfig::new(10),
+        );
+        let bearer = format!("Bearer {}", live);
+        resolver
+            .inner()
+            .add_tenant(
+                &bearer,
+                TenantContext::new("acme".to_string(), "client".to_string()),
+            )
+            .await;
+        resolver.resolve_tenant(&bearer).await.unwrap();
+        assert!(resolver.cached_until(&bearer).unwrap().is_some());
+        resolver
+            .inner()
+            .add_tenant(
+                "opaque",
+                TenantContext::new("acme".to_string(), "client".to_string()),
+            )
+            .await;
+        resolver.resolve_tenant("opaque").await.unwrap();
+        assert_eq!(resolver.cached_until("opaque"), Some(None));
+    }
+
+    #[tokio::test]
+    async fn test_far_future_jwt_expiry_falls_back_to_ttl() {
+        let far_future = jwt(1e19);
+        let resolver = resolver(
+            &[(far_future.as_str(), "acme")],
+            CacheConfig::new(10).with_ttl(Duration::from_secs(60)),
+        )
+        .await;
+
+        // An `exp` beyond what SystemTime can hold leaves only the TTL
+        resolver.resolve_tenant(&far_future).await.unwrap();
+        let expires_at = resolver.cached_until(&far_future).unwrap().unwrap();
+        assert!(expires_at <= Instant::now() + Duration::from_secs(60));
+        assert!(expires_at > Instant::now() + Duration::from_secs(50));
+
+        let resolver = super::CachingTenantResolver::with_config(
+            StaticTenantResolver::new(),
+            CacheConfig::new(10),
+        );
+        resolver
+            .inner()
+            .add_tenant(
+                &far_future,
+                TenantContext::new("acme".to_string(), "client".to_string()),
+            )
+            .await;
+        resolver.resolve_tenant(&far_future).await.unwrap();
+        assert_eq!(resolver.cached_until(&far_future), Some(None));
+    }
+
+    #[tokio::test]
+    async fn test_resolution_overlapping_an_invalidation_is_not_cached() {
+        let inner = StaticTenantResolver::new();
+        inner
+            .add_tenant(
+                "key-a",
+                TenantContext::new("acme".to_string(), "client".to_string()),
+            )
+            .await;
+        let resolver = CachingTenantResolver::new(GatedResolver {
+            inner,
+            gate: tokio::sync::Semaphore::new(0),
+        });
+
+        // The credential is revoked while its resolution is in flight
+        let mut resolution = Box::pin(resolver.resolve_tenant("key-a"));
+        assert!(futures::poll!(resolution.as_mut()).is_pending());
+        resolver.invalidate("key-a");
+        resolver.inner().gate.add_permits(1);
+        assert_eq!(resolution.await.unwrap().tenant_id, "acme");
+        assert!(resolver.cached_until("key-a").is_none());
+
+        // Later resolutions are cached again
+        resolver.resolve_tenant("key-a").await.unwrap();
+        assert!(resolver.cached_until("key-a").is_some());
+    }
+}
@@ -45,6 +45,7 @@
 //! ```
 
 pub mod adapter;
+pub mod caching_resolver;
 
 pub mod provider;
 pub mod resolver;
@@ -52,12 +53,14 @@ pub mod scim_config;
 
 // Re-export key types for convenience
 pub use adapter::{SingleTenantAdapter, ToSingleTenant};
+pub use caching_resolver::CachingTenantResolver;
 
 // SCIM-focused configuration (recommended)
 pub use scim_config::{
-    RateLimit, ScimAuditConfig, ScimAuthScheme, ScimClientAuth, ScimClientConfig,
-    ScimConfigurationError, ScimCustomAttribute, ScimEndpointConfig, ScimOperation, ScimRateLimits,
-    ScimSchemaConfig, ScimSchemaExtension, ScimSearchConfig, ScimTenantConfiguration,
+    PiiScrubbingMode, RateLimit, ScimAuditConfig, ScimAuthScheme, ScimClientAuth, ScimClientConfig,
+    ScimComplianceConfig, ScimConfigurationError, ScimCustomAttribute, ScimEndpointConfig,
+    ScimOperation, ScimRateLimits, ScimSchemaConfig, ScimSchemaExtension, ScimSearchConfig,
+    ScimTenantConfiguration, ScimValidationConfig, UnknownAttributePolicy,
 };
 
 pub use provider::TenantValidator;
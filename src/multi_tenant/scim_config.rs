@@ -20,6 +20,7 @@
 //! - SCIM schema extensions and customizations
 //! - SCIM operation audit trails
 //! - SCIM filtering and search configuration
+//! - PII scrubbing of exported SCIM resources
 //!
 //! ## ❌ Out of Scope (General Application Configuration)
 //! - UI branding and theming
@@ -99,6 +100,12 @@ pub struct ScimTenantConfiguration {
     pub audit_config: ScimAuditConfig,
     /// SCIM filtering and search configuration
     pub search_config: ScimSearchConfig,
+    /// Compliance settings applied to exported resources
+    #[serde(default)]
+    pub compliance_config: ScimComplianceConfig,
+    /// Validation strictness overriding the server-wide settings
+    #[serde(default)]
+    pub validation_config: ScimValidationConfig,
 }
 
 impl ScimTenantConfiguration {
@@ -145,6 +152,10 @@ pub struct ScimEndpointConfig {
     pub tenant_path_pattern: Option<String>,
     /// Maximum request payload size for SCIM operations
     pub max_payload_size: usize,
+    /// Maximum number of `members` a Group may have, overriding the
+    /// server-wide limit
+    #[serde(default)]
+    pub max_group_members: Option<usize>,
     /// SCIM protocol version (typically "2.0")
     pub scim_version: String,
     /// Supported SCIM authentication schemes
@@ -158,6 +169,7 @@ impl Default for ScimEndpointConfig {
             include_tenant_in_path: false,
             tenant_path_pattern: None,
             max_payload_size: 1024 * 1024, // 1MB
+            max_group_members: None,
             scim_version: "2.0".to_string(),
             supported_auth_schemes: vec![ScimAuthScheme::Bearer, ScimAuthScheme::ApiKey],
         }
@@ -451,6 +463,10 @@ pub struct ScimSearchConfig {
     pub case_insensitive_filtering: bool,
     /// Custom search operators supported
     pub custom_operators: Vec<String>,
+    /// Maximum page size of list responses per resource type, taking
+    /// precedence over the server's caps for this tenant
+    #[serde(default)]
+    pub resource_type_max_results: HashMap<String, u32>,
 }
 
 impl Default for ScimSearchConfig {
@@ -475,10 +491,83 @@ impl Default for ScimSearchConfig {
             ],
             case_insensitive_filtering: true,
             custom_operators: Vec::new(),
+            resource_type_max_results: HashMap::new(),
         }
     }
 }
 
+/// Compliance settings for a tenant's exported SCIM resources.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScimComplianceConfig {
+    /// Whether PII attributes are scrubbed from exports
+    pub enable_pii_scrubbing: bool,
+    /// Attribute paths treated as PII, e.g. `emails.value` or `name`
+    pub pii_attributes: Vec<String>,
+    /// How PII values are replaced
+    pub pii_scrubbing_mode: PiiScrubbingMode,
+}
+
+impl Default for ScimComplianceConfig {
+    fn default() -> Self {
+        Self {
+            enable_pii_scrubbing: false,
+            pii_attributes: vec![
+                "name".to_string(),
+                "displayName".to_string(),
+                "nickName".to_string(),
+                "emails.value".to_string(),
+                "emails.display".to_string(),
+                "phoneNumbers.value".to_string(),
+                "phoneNumbers.display".to_string(),
+                "addresses".to_string(),
+                "members.display".to_string(),
+            ],
+            pii_scrubbing_mode: PiiScrubbingMode::default(),
+        }
+    }
+}
+
+/// How scrubbed PII values are replaced.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PiiScrubbingMode {
+    /// Replace every value with the same mask
+    #[default]
+    Mask,
+    /// Replace each value with a salted SHA-256 hash, so equal values stay
+    /// equal and exported records can still be joined on them
+    Hash {
+        /// Secret mixed into every hash
+        salt: String,
+    },
+}
+
+/// Validation strictness for a tenant's write requests.
+///
+/// Each setting left as `None` falls back to the server-wide setting, so a
+/// tenant only overrides what it needs, such as a legacy tenant relaxing
+/// validation while new tenants stay strict.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ScimValidationConfig {
+    /// Whether schema URNs not registered for a resource type are rejected
+    pub strict_schemas: Option<bool>,
+    /// How attributes the schema does not define are handled
+    pub unknown_attributes: Option<UnknownAttributePolicy>,
+    /// Whether required attributes must be present on create and replace
+    /// and may not be emptied by PATCH
+    pub enforce_required: Option<bool>,
+}
+
+/// How attributes a resource's schemas do not define are handled on writes.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum UnknownAttributePolicy {
+    /// Reject the request
+    #[default]
+    Reject,
+    /// Drop the attributes, and PATCH operations targeting them, before
+    /// validation so they are never stored
+    Ignore,
+}
+
 /// Builder for creating SCIM tenant configurations.
 pub struct ScimTenantConfigurationBuilder {
     tenant_id: String,
@@ -488,6 +577,8 @@ pub struct ScimTenantConfigurationBuilder {
     schema_config: Option<ScimSchemaConfig>,
     audit_config: Option<ScimAuditConfig>,
     search_config: Option<ScimSearchConfig>,
+    compliance_config: Option<ScimComplianceConfig>,
+    validation_config: ScimValidationConfig,
 }
 
 impl ScimTenantConfigurationBuilder {
@@ -500,6 +591,8 @@ impl ScimTenantConfigurationBuilder {
             schema_config: None,
             audit_config: None,
             search_config: None,
+            compliance_config: None,
+            validation_config: ScimValidationConfig::default(),
         }
     }
 
@@ -510,6 +603,15 @@ impl ScimTenantConfigurationBuilder {
         self
     }
 
+    /// Cap the number of `members` of this tenant's Groups, overriding the
+    /// server's [`max_group_members`](crate::ScimServerConfig::max_group_members).
+    pub fn with_max_group_members(mut self, max_members: usize) -> Self {
+        let mut endpoint = self.endpoint.unwrap_or_default();
+        endpoint.max_group_members = Some(max_members);
+        self.endpoint = Some(endpoint);
+        self
+    }
+
     pub fn with_scim_rate_limit(mut self, max_requests: u32, window: Duration) -> Self {
         let rate_limit = RateLimit::new(max_requests, window);
         let mut rate_limits = self.rate_limits.unwrap_or_default();
@@ -578,6 +680,52 @@ impl ScimTenantConfigurationBuilder {
         self
     }
 
+    /// Cap list responses for `resource_type` at `max_results` resources per
+    /// page for this tenant.
+    pub fn with_resource_type_max_results(mut self, resource_type: &str, max_results: u32) -> Self {
+        let mut search_config = self.search_config.unwrap_or_default();
+        search_config
+            .resource_type_max_results
+            .insert(resource_type.to_string(), max_results);
+        self.search_config = Some(search_config);
+        self
+    }
+
+    /// Scrub the default PII attributes from exports using `mode`.
+    pub fn with_pii_scrubbing(mut self, mode: PiiScrubbingMode) -> Self {
+        let mut compliance_config = self.compliance_config.unwrap_or_default();
+        compliance_config.enable_pii_scrubbing = true;
+        compliance_config.pii_scrubbing_mode = mode;
+        self.compliance_config = Some(compliance_config);
+        self
+    }
+
+    /// Replace the attribute paths treated as PII.
+    pub fn with_pii_attributes(mut self, attributes: Vec<String>) -> Self {
+        let mut compliance_config = self.compliance_config.unwrap_or_default();
+        compliance_config.pii_attributes = attributes;
+        self.compliance_config = Some(compliance_config);
+        self
+    }
+
+    /// Override whether unregistered schema URNs are rejected for this tenant.
+    pub fn with_strict_schemas(mut self, strict: bool) -> Self {
+        self.validation_config.strict_schemas = Some(strict);
+        self
+    }
+
+    /// Override how unknown attributes are handled for this tenant.
+    pub fn with_unknown_attribute_policy(mut self, policy: UnknownAttributePolicy) -> Self {
+        self.validation_config.unknown_attributes = Some(policy);
+        self
+    }
+
+    /// Override whether required attributes are enforced for this tenant.
+    pub fn with_required_enforcement(mut self, enforce: bool) -> Self {
+        self.validation_config.enforce_required = Some(enforce);
+        self
+    }
+
     pub fn build(self) -> Result<ScimTenantConfiguration, ScimConfigurationError> {
         let now = Utc::now();
 
@@ -592,6 +740,8 @@ impl ScimTenantConfigurationBuilder {
             schema_config: self.schema_config.unwrap_or_default(),
             audit_config: self.audit_config.unwrap_or_default(),
             search_config: self.search_config.unwrap_or_default(),
+            compliance_config: self.compliance_config.unwrap_or_default(),
+            validation_config: self.validation_config,
         })
     }
 }
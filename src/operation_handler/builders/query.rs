@@ -3,7 +3,9 @@
 //! This module provides convenient builder methods for constructing
 //! ScimQuery instances with various filtering and pagination options.
 
+use crate::error::{ScimError, ScimResult};
 use crate::operation_handler::core::ScimQuery;
+use crate::resource::{ListQuery, SortOrder};
 use serde_json::Value;
 
 impl ScimQuery {
@@ -13,6 +15,8 @@ impl ScimQuery {
             count: None,
             start_index: None,
             filter: None,
+            sort_by: None,
+            sort_order: None,
             attributes: None,
             excluded_attributes: None,
             search_attribute: None,
@@ -33,6 +37,13 @@ impl ScimQuery {
         self
     }
 
+    /// Set the attribute and direction to sort results by.
+    pub fn with_sort(mut self, sort_by: impl Into<String>, sort_order: SortOrder) -> Self {
+        self.sort_by = Some(sort_by.into());
+        self.sort_order = Some(sort_order);
+        self
+    }
+
     /// Set search parameters.
     pub fn with_search(mut self, attribute: impl Into<String>, value: Value) -> Self {
         self.search_attribute = Some(attribute.into());
@@ -51,6 +62,42 @@ impl ScimQuery {
         self.excluded_attributes = Some(excluded_attributes);
         self
     }
+
+    /// Reject parameter combinations a request may not use together.
+    ///
+    /// `attributes` and `excludedAttributes` are alternative ways of asking
+    /// for a partial representation (RFC 7644 Section 3.9), so a query may
+    /// set one or the other but not both.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScimError::InvalidRequest`] if both are set.
+    pub fn validate(&self) -> ScimResult<()> {
+        let is_set =
+            |attributes: &Option<Vec<String>>| attributes.as_ref().is_some_and(|a| !a.is_empty());
+        if is_set(&self.attributes) && is_set(&self.excluded_attributes) {
+            return Err(ScimError::invalid_request(
+                "attributes and excludedAttributes cannot both be specified",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Convert to the provider-level [`ListQuery`].
+    ///
+    /// Search-specific parameters have no `ListQuery` equivalent and are dropped.
+    pub fn to_list_query(&self) -> ListQuery {
+        ListQuery {
+            count: self.count,
+            start_index: self.start_index,
+            filter: self.filter.clone(),
+            sort_by: self.sort_by.clone(),
+            sort_order: self.sort_order.unwrap_or_default(),
+            attributes: self.attributes.clone().unwrap_or_default(),
+            excluded_attributes: self.excluded_attributes.clone().unwrap_or_default(),
+            case_exact_attributes: Vec::new(),
+        }
+    }
 }
 
 impl Default for ScimQuery {
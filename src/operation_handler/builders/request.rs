@@ -5,9 +5,10 @@
 
 use crate::{
     operation_handler::core::{ScimOperationRequest, ScimOperationType, ScimQuery},
-    resource::{TenantContext, version::RawVersion},
+    resource::{SortOrder, TenantContext, version::RawVersion},
 };
 use serde_json::Value;
+use std::collections::HashMap;
 
 impl ScimOperationRequest {
     /// Create a new create operation request.
@@ -21,6 +22,8 @@ impl ScimOperationRequest {
             tenant_context: None,
             request_id: None,
             expected_version: None,
+            idempotency_key: None,
+            context_attributes: HashMap::new(),
         }
     }
 
@@ -35,6 +38,8 @@ impl ScimOperationRequest {
             tenant_context: None,
             request_id: None,
             expected_version: None,
+            idempotency_key: None,
+            context_attributes: HashMap::new(),
         }
     }
 
@@ -53,6 +58,8 @@ impl ScimOperationRequest {
             tenant_context: None,
             request_id: None,
             expected_version: None,
+            idempotency_key: None,
+            context_attributes: HashMap::new(),
         }
     }
 
@@ -67,6 +74,8 @@ impl ScimOperationRequest {
             tenant_context: None,
             request_id: None,
             expected_version: None,
+            idempotency_key: None,
+            context_attributes: HashMap::new(),
         }
     }
 
@@ -81,6 +90,8 @@ impl ScimOperationRequest {
             tenant_context: None,
             request_id: None,
             expected_version: None,
+            idempotency_key: None,
+            context_attributes: HashMap::new(),
         }
     }
 
@@ -99,6 +110,8 @@ impl ScimOperationRequest {
                 count: None,
                 start_index: None,
                 filter: None,
+                sort_by: None,
+                sort_order: None,
                 attributes: None,
                 excluded_attributes: None,
                 search_attribute: Some(attribute.into()),
@@ -107,6 +120,8 @@ impl ScimOperationRequest {
             tenant_context: None,
             request_id: None,
             expected_version: None,
+            idempotency_key: None,
+            context_attributes: HashMap::new(),
         }
     }
 
@@ -121,6 +136,8 @@ impl ScimOperationRequest {
             tenant_context: None,
             request_id: None,
             expected_version: None,
+            idempotency_key: None,
+            context_attributes: HashMap::new(),
         }
     }
 
@@ -135,6 +152,28 @@ impl ScimOperationRequest {
             tenant_context: None,
             request_id: None,
             expected_version: None,
+            idempotency_key: None,
+            context_attributes: HashMap::new(),
+        }
+    }
+
+    /// Create a new upsert operation request, creating the resource or
+    /// replacing the one with the same `externalId`.
+    ///
+    /// The response's [`operation`](crate::operation_handler::OperationMetadata::operation)
+    /// records whether the upsert created or updated the resource.
+    pub fn upsert(resource_type: impl Into<String>, data: Value) -> Self {
+        Self {
+            operation: ScimOperationType::Upsert,
+            resource_type: resource_type.into(),
+            resource_id: None,
+            data: Some(data),
+            query: None,
+            tenant_context: None,
+            request_id: None,
+            expected_version: None,
+            idempotency_key: None,
+            context_attributes: HashMap::new(),
         }
     }
 
@@ -149,6 +188,8 @@ impl ScimOperationRequest {
             tenant_context: None,
             request_id: None,
             expected_version: None,
+            idempotency_key: None,
+            context_attributes: HashMap::new(),
         }
     }
 
@@ -170,6 +211,69 @@ impl ScimOperationRequest {
         self
     }
 
+    /// Set the filter expression of a list or search request.
+    ///
+    /// The query parameter builders below start from an empty [`ScimQuery`]
+    /// when the request has none and compose with each other and with the
+    /// search attribute of [`search`](Self::search). Invalid combinations,
+    /// such as both `attributes` and `excludedAttributes`, are rejected when
+    /// the request is handled.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scim_server::operation_handler::ScimOperationRequest;
+    /// use scim_server::resource::SortOrder;
+    ///
+    /// let request = ScimOperationRequest::list("User")
+    ///     .with_filter(r#"userName sw "j""#)
+    ///     .with_attributes(vec!["userName".to_string()])
+    ///     .with_sort("userName", SortOrder::Descending)
+    ///     .with_pagination(1, 10);
+    /// let query = request.query.unwrap();
+    /// assert_eq!(query.filter.as_deref(), Some(r#"userName sw "j""#));
+    /// assert_eq!(query.count, Some(10));
+    /// ```
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.query = Some(self.query.unwrap_or_default().with_filter(filter));
+        self
+    }
+
+    /// Set the attributes to return for a list or search request.
+    pub fn with_attributes(mut self, attributes: Vec<String>) -> Self {
+        self.query = Some(self.query.unwrap_or_default().with_attributes(attributes));
+        self
+    }
+
+    /// Set the attributes to leave out for a list or search request.
+    pub fn with_excluded_attributes(mut self, excluded_attributes: Vec<String>) -> Self {
+        self.query = Some(
+            self.query
+                .unwrap_or_default()
+                .with_excluded_attributes(excluded_attributes),
+        );
+        self
+    }
+
+    /// Set the attribute and direction to sort a list or search request by.
+    pub fn with_sort(mut self, sort_by: impl Into<String>, sort_order: SortOrder) -> Self {
+        self.query = Some(
+            self.query
+                .unwrap_or_default()
+                .with_sort(sort_by, sort_order),
+        );
+        self
+    }
+
+    /// Set the 1-based start index and page size of a list or search request.
+    pub fn with_pagination(mut self, start_index: usize, count: usize) -> Self {
+        self.query = Some(
+            self.query
+                .unwrap_or_default()
+                .with_pagination(start_index, count),
+        );
+        self
+    }
+
     /// Add expected version for conditional operations.
     ///
     /// This enables ETag-based concurrency control for update and delete operations.
@@ -194,4 +298,26 @@ impl ScimOperationRequest {
 
         self
     }
+
+    /// Add an idempotency key, so that a replay of this create, update, patch
+    /// or delete returns the original response instead of applying it again.
+    ///
+    /// Keys only take effect on a handler configured with
+    /// [`ScimOperationHandler::with_idempotency_store`](crate::operation_handler::ScimOperationHandler::with_idempotency_store).
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Add a request-scoped client hint, such as the client IP or user
+    /// agent, made available to middleware and resource hooks through
+    /// [`RequestContext::attribute`](crate::resource::RequestContext::attribute).
+    pub fn with_context_attribute(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.context_attributes.insert(name.into(), value.into());
+        self
+    }
 }
@@ -4,15 +4,23 @@
 //! It provides the central handler struct and operation dispatch functionality that other
 //! operation handler modules depend on.
 
+use super::idempotency::{
+    self, ClaimGuard, IdempotencyClaim, IdempotencyFingerprint, IdempotencyKey, IdempotencyStore,
+};
+use super::middleware::OperationMiddleware;
 use crate::{
-    ResourceProvider, ScimServer,
+    ResourceProvider, ScimError, ScimServer,
+    error::check_json_depth,
+    resource::serialization::serialized_size,
     resource::version::RawVersion,
-    resource::{RequestContext, TenantContext},
+    resource::{RequestContext, SortOrder, TenantContext},
 };
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::task::Poll;
 
 /// Framework-agnostic operation handler for SCIM operations
 ///
@@ -20,6 +28,8 @@ use std::collections::HashMap;
 /// without being tied to any specific transport layer (HTTP, MCP, etc.).
 pub struct ScimOperationHandler<P: ResourceProvider> {
     pub(super) server: ScimServer<P>,
+    pub(super) idempotency_store: Option<Arc<dyn IdempotencyStore>>,
+    pub(super) middleware: Vec<Arc<dyn OperationMiddleware>>,
 }
 
 /// Structured request for SCIM operations
@@ -44,6 +54,11 @@ pub struct ScimOperationRequest {
     pub request_id: Option<String>,
     /// Expected version for conditional operations
     pub expected_version: Option<RawVersion>,
+    /// Client-supplied key making a mutating operation idempotent
+    pub idempotency_key: Option<String>,
+    /// Request-scoped client hints copied into the
+    /// [`RequestContext::attributes`] seen by middleware and resource hooks
+    pub context_attributes: HashMap<String, String>,
 }
 
 /// Types of SCIM operations supported by the handler
@@ -69,6 +84,8 @@ pub enum ScimOperationType {
     GetSchema,
     /// Check if a resource exists
     Exists,
+    /// Create a resource, or replace the one with the same `externalId`
+    Upsert,
 }
 
 /// Query parameters for list and search operations
@@ -80,6 +97,10 @@ pub struct ScimQuery {
     pub start_index: Option<usize>,
     /// Filter expression for search
     pub filter: Option<String>,
+    /// Attribute to sort results by
+    pub sort_by: Option<String>,
+    /// Direction to sort results in
+    pub sort_order: Option<SortOrder>,
     /// Attributes to include in results
     pub attributes: Option<Vec<String>>,
     /// Attributes to exclude from results
@@ -128,6 +149,19 @@ pub struct OperationMetadata {
     pub tenant_id: Option<String>,
     /// Resource schemas involved
     pub schemas: Option<Vec<String>>,
+    /// Operation that produced the response, set by
+    /// [`ScimOperationHandler::handle_operation`] once the operation has run.
+    /// A successful upsert reports the operation it resolved to,
+    /// [`Create`](ScimOperationType::Create) or
+    /// [`Update`](ScimOperationType::Update)
+    pub operation: Option<ScimOperationType>,
+    /// Size in bytes of the compact JSON serialization of the response data,
+    /// `None` when there is no data
+    pub response_size_bytes: Option<usize>,
+    /// Advisory hint that the response data is at least the configured
+    /// [`compression_threshold`](crate::ScimServerConfig::compression_threshold)
+    /// and worth compressing. HTTP layers are free to ignore it.
+    pub compressible: bool,
     /// Additional metadata including version information
     pub additional: HashMap<String, Value>,
 }
@@ -135,14 +169,40 @@ pub struct OperationMetadata {
 impl<P: ResourceProvider + Sync> ScimOperationHandler<P> {
     /// Create a new operation handler with the given SCIM server.
     pub fn new(server: ScimServer<P>) -> Self {
-        Self { server }
+        Self {
+            server,
+            idempotency_store: None,
+            middleware: Vec::new(),
+        }
+    }
+
+    /// Honour idempotency keys on mutating requests using `store`.
+    ///
+    /// Without a store, [`ScimOperationRequest::idempotency_key`] is ignored.
+    /// See the [`idempotency`](super::idempotency) module for the semantics.
+    pub fn with_idempotency_store(mut self, store: impl IdempotencyStore + 'static) -> Self {
+        self.idempotency_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Run `middleware` around every operation, after any middleware
+    /// registered before it.
+    ///
+    /// See the [`middleware`](super::middleware) module for the ordering.
+    pub fn with_middleware(mut self, middleware: impl OperationMiddleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
     }
 
     /// Handle a structured SCIM operation request.
     ///
     /// This is the main entry point that dispatches to specific operation handlers
-    /// based on the operation type.
-    pub async fn handle_operation(&self, request: ScimOperationRequest) -> ScimOperationResponse {
+    /// based on the operation type, running any registered
+    /// [`OperationMiddleware`] around the dispatch.
+    pub async fn handle_operation(
+        &self,
+        mut request: ScimOperationRequest,
+    ) -> ScimOperationResponse {
         let request_id = request
             .request_id
             .clone()
@@ -154,37 +214,263 @@ impl<P: ResourceProvider + Sync> ScimOperationHandler<P> {
         );
 
         let context = self.create_request_context(&request, &request_id);
+        let operation = request.operation;
+
+        // Requests may name the endpoint rather than the registered type
+        if !matches!(
+            operation,
+            ScimOperationType::GetSchemas | ScimOperationType::GetSchema
+        ) && let Some(resource_type) = self.server.resolve_resource_type(&request.resource_type)
+        {
+            request.resource_type = resource_type.to_string();
+        }
+
+        // Expected versions may arrive as an ETag or as a raw `meta.version`
+        request.expected_version = request
+            .expected_version
+            .map(|version| RawVersion::parse_any(version.as_str()).unwrap_or(version));
+
+        let mut entered = 0;
+        let mut rejection = None;
+        for middleware in &self.middleware {
+            if let Err(e) = middleware.before(&mut request, &context) {
+                rejection = Some(e);
+                break;
+            }
+            entered += 1;
+        }
+
+        let mut response = match rejection {
+            Some(e) => {
+                warn!(
+                    "SCIM operation handler rejected by middleware: {} (request: '{}')",
+                    e, request_id
+                );
+                let mut response = super::errors::create_error_response(e, request_id);
+                response.metadata.operation = Some(operation);
+                response
+            }
+            None => self.execute(request, &context, request_id).await,
+        };
+
+        for middleware in self.middleware[..entered].iter().rev() {
+            middleware.after(&mut response, &context);
+        }
+        response
+    }
+
+    /// Handle independent requests one after another.
+    ///
+    /// Unlike an RFC 7644 bulk request there are no `bulkId` references and
+    /// nothing is rolled back: each request is handled exactly as by
+    /// [`handle_operation`](Self::handle_operation), with its own tenant
+    /// context, and a failure only shows in its own response. Responses are
+    /// returned in request order.
+    ///
+    /// The configured maximum payload size also bounds the batch as a whole:
+    /// if the request bodies together exceed it, every request is rejected
+    /// without being run.
+    pub async fn handle_batch(
+        &self,
+        requests: Vec<ScimOperationRequest>,
+    ) -> Vec<ScimOperationResponse> {
+        if let Some(rejected) = self.reject_oversized_batch(&requests) {
+            return rejected;
+        }
+
+        let mut responses = Vec::with_capacity(requests.len());
+        for request in requests {
+            responses.push(self.handle_operation(request).await);
+        }
+        responses
+    }
+
+    /// Handle independent requests concurrently.
+    ///
+    /// Behaves like [`handle_batch`](Self::handle_batch), including the
+    /// response order, but the requests make progress together on the
+    /// current task, so their provider calls may interleave. Use it only for
+    /// requests that do not depend on each other's effects.
+    pub async fn handle_batch_concurrent(
+        &self,
+        requests: Vec<ScimOperationRequest>,
+    ) -> Vec<ScimOperationResponse> {
+        if let Some(rejected) = self.reject_oversized_batch(&requests) {
+            return rejected;
+        }
+
+        let mut pending: Vec<_> = requests
+            .into_iter()
+            .map(|request| Some(Box::pin(self.handle_operation(request))))
+            .collect();
+        let mut responses: Vec<Option<ScimOperationResponse>> =
+            pending.iter().map(|_| None).collect();
+
+        std::future::poll_fn(|cx| {
+            let mut done = true;
+            for (slot, response) in pending.iter_mut().zip(responses.iter_mut()) {
+                let Some(future) = slot else {
+                    continue;
+                };
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(ready) => {
+                        *response = Some(ready);
+                        *slot = None;
+                    }
+                    Poll::Pending => done = false,
+                }
+            }
+            if done { Poll::Ready(()) } else { Poll::Pending }
+        })
+        .await;
+
+        responses.into_iter().flatten().collect()
+    }
+
+    /// Reject every request of a batch whose bodies together are too large.
+    ///
+    /// Bodies nested too deeply are left out of the total, since measuring
+    /// them walks the payload recursively; they are rejected on their own.
+    fn reject_oversized_batch(
+        &self,
+        requests: &[ScimOperationRequest],
+    ) -> Option<Vec<ScimOperationResponse>> {
+        let payloads = requests
+            .iter()
+            .filter_map(|request| request.data.as_ref())
+            .filter(|data| check_json_depth(data).is_ok());
+        let Err(ScimError::PayloadTooLarge { size, limit }) =
+            self.server.config().check_batch_payload_size(payloads)
+        else {
+            return None;
+        };
+
+        warn!(
+            "SCIM operation handler rejected batch payload of {} bytes (limit {})",
+            size, limit
+        );
+        Some(
+            requests
+                .iter()
+                .map(|request| {
+                    let request_id = request
+                        .request_id
+                        .clone()
+                        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+                    let mut response = super::errors::create_error_response(
+                        ScimError::payload_too_large(size, limit),
+                        request_id,
+                    );
+                    response.metadata.operation = Some(request.operation);
+                    response
+                })
+                .collect(),
+        )
+    }
+
+    /// Run a request through payload checks, idempotency and dispatch.
+    async fn execute(
+        &self,
+        request: ScimOperationRequest,
+        context: &RequestContext,
+        request_id: String,
+    ) -> ScimOperationResponse {
+        let operation = request.operation;
+
+        // Depth is checked first, as measuring the size walks the payload
+        // recursively
+        if let Some(data) = &request.data
+            && let Err(e) = check_json_depth(data)
+                .map_err(ScimError::from)
+                .and_then(|()| self.server.config().check_payload_size(data))
+        {
+            warn!(
+                "SCIM operation handler rejected payload: {} (request: '{}')",
+                e, request_id
+            );
+            return super::errors::create_error_response(e, request_id);
+        }
+
+        if let Some(query) = &request.query
+            && let Err(e) = query.validate()
+        {
+            warn!(
+                "SCIM operation handler rejected query: {} (request: '{}')",
+                e, request_id
+            );
+            return super::errors::create_error_response(e, request_id);
+        }
+
+        // Claim the idempotency key before running, so a replay never re-applies.
+        // The guard releases it if this future is dropped before completing
+        let claim_guard = match (&self.idempotency_store, &request.idempotency_key) {
+            (Some(store), Some(key)) if idempotency::is_mutating(operation) => {
+                let key = IdempotencyKey {
+                    tenant_id: context.tenant_id().map(str::to_string),
+                    key: key.clone(),
+                };
+                let fingerprint = IdempotencyFingerprint::of(&request);
+                match store.claim(&key, &fingerprint) {
+                    IdempotencyClaim::Claimed => Some(ClaimGuard::new(store.as_ref(), key)),
+                    IdempotencyClaim::Completed(mut response) => {
+                        info!(
+                            "SCIM operation handler replaying response for idempotency key '{}' (request: '{}')",
+                            key.key, request_id
+                        );
+                        response.metadata.request_id = request_id;
+                        response
+                            .metadata
+                            .additional
+                            .insert("idempotent_replay".to_string(), Value::Bool(true));
+                        return *response;
+                    }
+                    claim => {
+                        warn!(
+                            "SCIM operation handler rejected idempotency key '{}' (request: '{}')",
+                            key.key, request_id
+                        );
+                        return idempotency::create_idempotency_error_response(
+                            &claim, &key, request_id,
+                        );
+                    }
+                }
+            }
+            _ => None,
+        };
 
         let result = match request.operation {
             ScimOperationType::Create => {
-                super::handlers::crud::handle_create(self, request, &context).await
+                super::handlers::crud::handle_create(self, request, context).await
             }
             ScimOperationType::Get => {
-                super::handlers::crud::handle_get(self, request, &context).await
+                super::handlers::crud::handle_get(self, request, context).await
             }
             ScimOperationType::Update => {
-                super::handlers::crud::handle_update(self, request, &context).await
+                super::handlers::crud::handle_update(self, request, context).await
             }
             ScimOperationType::Patch => {
-                super::handlers::crud::handle_patch(self, request, &context).await
+                super::handlers::crud::handle_patch(self, request, context).await
             }
             ScimOperationType::Delete => {
-                super::handlers::crud::handle_delete(self, request, &context).await
+                super::handlers::crud::handle_delete(self, request, context).await
             }
             ScimOperationType::List => {
-                super::handlers::query::handle_list(self, request, &context).await
+                super::handlers::query::handle_list(self, request, context).await
             }
             ScimOperationType::Search => {
-                super::handlers::query::handle_search(self, request, &context).await
+                super::handlers::query::handle_search(self, request, context).await
             }
             ScimOperationType::GetSchemas => {
-                super::handlers::schema::handle_get_schemas(self, request, &context).await
+                super::handlers::schema::handle_get_schemas(self, request, context).await
             }
             ScimOperationType::GetSchema => {
-                super::handlers::schema::handle_get_schema(self, request, &context).await
+                super::handlers::schema::handle_get_schema(self, request, context).await
             }
             ScimOperationType::Exists => {
-                super::handlers::utility::handle_exists(self, request, &context).await
+                super::handlers::utility::handle_exists(self, request, context).await
+            }
+            ScimOperationType::Upsert => {
+                super::handlers::crud::handle_upsert(self, request, context).await
             }
         };
 
@@ -203,7 +489,68 @@ impl<P: ResourceProvider + Sync> ScimOperationHandler<P> {
             }
         }
 
-        result.unwrap_or_else(|e| super::errors::create_error_response(e, request_id))
+        let mut response = result
+            .map(|mut response| {
+                self.apply_version_exposure(operation, &mut response);
+                self.apply_external_id_echo(operation, &mut response);
+                self.apply_size_hints(&mut response);
+                response
+            })
+            .unwrap_or_else(|e| super::errors::create_error_response(e, request_id));
+        // An upsert has already recorded whether it created or updated
+        response.metadata.operation.get_or_insert(operation);
+
+        if let Some(guard) = claim_guard {
+            guard.finish(&response);
+        }
+
+        response
+    }
+
+    /// Record the response data size and whether it is worth compressing.
+    ///
+    /// Runs once the data is final, so the size is measured in a single pass.
+    fn apply_size_hints(&self, response: &mut ScimOperationResponse) {
+        let size = response.data.as_ref().map(serialized_size);
+        let threshold = self.server.config().compression_threshold;
+
+        response.metadata.response_size_bytes = size;
+        response.metadata.compressible = size
+            .zip(threshold)
+            .is_some_and(|(size, threshold)| size >= threshold);
+    }
+
+    /// Drop whichever version representation the server is configured to omit.
+    fn apply_version_exposure(
+        &self,
+        operation: ScimOperationType,
+        response: &mut ScimOperationResponse,
+    ) {
+        let exposure = self.server.config().version_exposure;
+
+        if !exposure.includes_etag() {
+            response.metadata.additional.remove("etag");
+            response.metadata.additional.remove("version");
+        }
+
+        if !exposure.includes_meta_version() {
+            for_each_returned_resource(operation, response, strip_meta_version);
+        }
+    }
+
+    /// Drop `externalId` from returned resources unless the server echoes it.
+    fn apply_external_id_echo(
+        &self,
+        operation: ScimOperationType,
+        response: &mut ScimOperationResponse,
+    ) {
+        if !self.server.config().echo_external_id {
+            for_each_returned_resource(operation, response, |resource| {
+                if let Some(resource) = resource.as_object_mut() {
+                    resource.remove("externalId");
+                }
+            });
+        }
     }
 
     /// Create a RequestContext from the operation request.
@@ -212,16 +559,48 @@ impl<P: ResourceProvider + Sync> ScimOperationHandler<P> {
         request: &ScimOperationRequest,
         request_id: &str,
     ) -> RequestContext {
-        match &request.tenant_context {
+        let context = match &request.tenant_context {
             Some(tenant_ctx) => {
                 RequestContext::with_tenant(request_id.to_string(), tenant_ctx.clone())
             }
             None => RequestContext::new(request_id.to_string()),
-        }
+        };
+        context.with_attributes(request.context_attributes.clone())
     }
 
     /// Get access to the underlying SCIM server.
-    pub(super) fn server(&self) -> &ScimServer<P> {
+    pub(crate) fn server(&self) -> &ScimServer<P> {
         &self.server
     }
 }
+
+/// Apply `f` to each resource in the response of an operation that returns
+/// resources.
+fn for_each_returned_resource(
+    operation: ScimOperationType,
+    response: &mut ScimOperationResponse,
+    mut f: impl FnMut(&mut Value),
+) {
+    let returns_resources = matches!(
+        operation,
+        ScimOperationType::Create
+            | ScimOperationType::Get
+            | ScimOperationType::Update
+            | ScimOperationType::Patch
+            | ScimOperationType::List
+            | ScimOperationType::Search
+            | ScimOperationType::Upsert
+    );
+    match response.data.as_mut() {
+        Some(Value::Array(resources)) if returns_resources => resources.iter_mut().for_each(f),
+        Some(resource) if returns_resources => f(resource),
+        _ => {}
+    }
+}
+
+/// Remove `meta.version` from a serialized resource.
+fn strip_meta_version(resource: &mut Value) {
+    if let Some(meta) = resource.get_mut("meta").and_then(Value::as_object_mut) {
+        meta.remove("version");
+    }
+}
@@ -22,6 +22,7 @@ pub fn create_error_response(error: ScimError, request_id: String) -> ScimOperat
             format!("Resource not found: {} with ID {}", resource_type, id),
             Some("RESOURCE_NOT_FOUND"),
         ),
+        ScimError::ResourceDeleted { .. } => (error.to_string(), Some("RESOURCE_DELETED")),
         ScimError::SchemaNotFound { schema_id } => (
             format!("Schema not found: {}", schema_id),
             Some("SCHEMA_NOT_FOUND"),
@@ -40,10 +41,12 @@ pub fn create_error_response(error: ScimError, request_id: String) -> ScimOperat
             ),
             Some("UNSUPPORTED_OPERATION"),
         ),
+        ScimError::Json(_) => (error.to_string(), Some("INVALID_SYNTAX")),
         ScimError::InvalidRequest { message } => (
             format!("Invalid request: {}", message),
             Some("INVALID_REQUEST"),
         ),
+        ScimError::InvalidFilter { .. } => (error.to_string(), Some("INVALID_FILTER")),
         ScimError::Provider(provider_error) => (
             format!("Provider error: {}", provider_error),
             Some("PROVIDER_ERROR"),
@@ -52,9 +55,50 @@ pub fn create_error_response(error: ScimError, request_id: String) -> ScimOperat
             format!("Internal error: {}", message),
             Some("INTERNAL_ERROR"),
         ),
+        ScimError::PermissionDenied { .. } => (error.to_string(), Some("PERMISSION_DENIED")),
+        ScimError::AuthorizationDenied { .. } => (error.to_string(), Some("AUTHORIZATION_DENIED")),
+        ScimError::PayloadTooLarge { .. } => (error.to_string(), Some("PAYLOAD_TOO_LARGE")),
+        ScimError::TooManyMembers { .. } => (error.to_string(), Some("TOO_MANY_MEMBERS")),
+        ScimError::Conflict { .. } => (error.to_string(), Some("UNIQUENESS_CONFLICT")),
+        ScimError::StorageConflict { .. } => (error.to_string(), Some("STORAGE_CONFLICT")),
+        ScimError::Unavailable { .. } => (error.to_string(), Some("SERVICE_UNAVAILABLE")),
+        ScimError::CapacityExceeded { .. } => (error.to_string(), Some("CAPACITY_EXCEEDED")),
         _ => (error.to_string(), Some("UNKNOWN_ERROR")),
     };
 
+    // Conflicts point clients at the existing resource so they can reconcile
+    let mut additional = HashMap::new();
+    if let ScimError::ResourceDeleted { .. } = &error {
+        additional.insert("deleted".to_string(), Value::Bool(true));
+    }
+    if let Some(scim_type) = error.scim_type() {
+        additional.insert(
+            "scim_type".to_string(),
+            Value::String(scim_type.as_str().to_string()),
+        );
+    }
+    if let ScimError::Unavailable {
+        retry_after: Some(retry_after),
+        ..
+    } = &error
+    {
+        additional.insert(
+            "retry_after".to_string(),
+            Value::from(retry_after.as_secs()),
+        );
+    }
+    if let ScimError::Conflict { id, location, .. } = &error {
+        if let Some(id) = id {
+            additional.insert("conflicting_id".to_string(), Value::String(id.clone()));
+        }
+        if let Some(location) = location {
+            additional.insert(
+                "conflicting_location".to_string(),
+                Value::String(location.clone()),
+            );
+        }
+    }
+
     ScimOperationResponse {
         success: false,
         data: None,
@@ -68,7 +112,10 @@ pub fn create_error_response(error: ScimError, request_id: String) -> ScimOperat
             request_id,
             tenant_id: None,
             schemas: None,
-            additional: HashMap::new(),
+            operation: None,
+            response_size_bytes: None,
+            compressible: false,
+            additional,
         },
     }
 }
@@ -111,6 +158,9 @@ pub fn create_version_conflict_response(
             request_id,
             tenant_id: None,
             schemas: None,
+            operation: None,
+            response_size_bytes: None,
+            compressible: false,
             additional,
         },
     }
@@ -9,20 +9,44 @@ use crate::{
     operation_handler::{
         core::{
             OperationMetadata, ScimOperationHandler, ScimOperationRequest, ScimOperationResponse,
+            ScimOperationType, ScimQuery,
         },
         create_version_conflict_response,
     },
-    resource::{RequestContext, version::HttpVersion, versioned::VersionedResource},
+    providers::UpsertOutcome,
+    resource::{
+        RequestContext, Resource, ScimOperation,
+        serialization::project_attributes,
+        version::{ConditionalResult, HttpVersion},
+        versioned::VersionedResource,
+    },
+    scim_server::PatchMode,
 };
+use serde_json::Value;
 use std::collections::HashMap;
 
+/// Apply the request's `attributes`/`excludedAttributes` to a serialized resource.
+///
+/// Only the response is projected; the stored resource is always complete.
+/// `id`, `schemas` and `meta` survive projection.
+fn project_response(mut data: Value, query: Option<&ScimQuery>) -> Value {
+    if let Some(query) = query {
+        project_attributes(
+            &mut data,
+            query.attributes.as_deref().unwrap_or_default(),
+            query.excluded_attributes.as_deref().unwrap_or_default(),
+        );
+    }
+    data
+}
+
 /// Handle create operations.
 pub async fn handle_create<P: ResourceProvider + Sync>(
     handler: &ScimOperationHandler<P>,
-    request: ScimOperationRequest,
+    mut request: ScimOperationRequest,
     context: &RequestContext,
 ) -> ScimResult<ScimOperationResponse> {
-    let data = request.data.ok_or_else(|| {
+    let data = request.data.take().ok_or_else(|| {
         ScimError::invalid_request("Missing data for create operation".to_string())
     })?;
 
@@ -31,6 +55,43 @@ pub async fn handle_create<P: ResourceProvider + Sync>(
         .create_resource(&request.resource_type, data, context)
         .await?;
 
+    resource_response(handler, resource, request, context)
+}
+
+/// Handle upsert operations.
+///
+/// The response records the operation the upsert resolved to, so it is
+/// rendered as `201 Created` with a `Location` header or `200 OK`, and
+/// middleware sees which of the two happened.
+pub async fn handle_upsert<P: ResourceProvider + Sync>(
+    handler: &ScimOperationHandler<P>,
+    mut request: ScimOperationRequest,
+    context: &RequestContext,
+) -> ScimResult<ScimOperationResponse> {
+    let data = request.data.take().ok_or_else(|| {
+        ScimError::invalid_request("Missing data for upsert operation".to_string())
+    })?;
+
+    let (resource, outcome) = handler
+        .server()
+        .upsert_resource(&request.resource_type, data, context)
+        .await?;
+
+    let mut response = resource_response(handler, resource, request, context)?;
+    response.metadata.operation = Some(match outcome {
+        UpsertOutcome::Created => ScimOperationType::Create,
+        UpsertOutcome::Updated => ScimOperationType::Update,
+    });
+    Ok(response)
+}
+
+/// Response carrying a written resource with its version.
+fn resource_response<P: ResourceProvider + Sync>(
+    handler: &ScimOperationHandler<P>,
+    resource: Resource,
+    request: ScimOperationRequest,
+    context: &RequestContext,
+) -> ScimResult<ScimOperationResponse> {
     // Include version information in response
     let versioned_resource = VersionedResource::new(resource.clone());
     let mut additional = HashMap::new();
@@ -71,11 +132,12 @@ pub async fn handle_create<P: ResourceProvider + Sync>(
 
     Ok(ScimOperationResponse {
         success: true,
-        data: Some(
+        data: Some(project_response(
             handler
                 .server()
                 .serialize_resource_with_refs(&updated_resource, context.tenant_id())?,
-        ),
+            request.query.as_ref(),
+        )),
         error: None,
         error_code: None,
         metadata: OperationMetadata {
@@ -92,6 +154,9 @@ pub async fn handle_create<P: ResourceProvider + Sync>(
                     .map(|s| s.as_str().to_string())
                     .collect(),
             ),
+            operation: None,
+            response_size_bytes: None,
+            compressible: false,
             additional,
         },
     })
@@ -130,11 +195,12 @@ pub async fn handle_get<P: ResourceProvider + Sync>(
 
             Ok(ScimOperationResponse {
                 success: true,
-                data: Some(
+                data: Some(project_response(
                     handler
                         .server()
                         .serialize_resource_with_refs(&resource, context.tenant_id())?,
-                ),
+                    request.query.as_ref(),
+                )),
                 error: None,
                 error_code: None,
                 metadata: OperationMetadata {
@@ -151,6 +217,9 @@ pub async fn handle_get<P: ResourceProvider + Sync>(
                             .map(|s| s.as_str().to_string())
                             .collect(),
                     ),
+                    operation: None,
+                    response_size_bytes: None,
+                    compressible: false,
                     additional,
                 },
             })
@@ -178,20 +247,26 @@ pub async fn handle_update<P: ResourceProvider + Sync>(
 
     // Check if this is a conditional update request
     if let Some(expected_version) = &request.expected_version {
+        // Same checks and preparation as an unconditional update
+        let data = handler
+            .server()
+            .prepare_update(&request.resource_type, &resource_id, data, context)
+            .await?;
+
         // Use conditional update
         match handler
             .server()
             .provider()
-            .update_resource(
+            .update_resource_conditional(
                 &request.resource_type,
                 &resource_id,
                 data,
-                Some(expected_version),
+                expected_version,
                 context,
             )
             .await
         {
-            Ok(versioned_resource) => {
+            Ok(ConditionalResult::Success(versioned_resource)) => {
                 let mut additional = HashMap::new();
                 additional.insert(
                     "version".to_string(),
@@ -205,7 +280,11 @@ pub async fn handle_update<P: ResourceProvider + Sync>(
                 );
 
                 // Update the resource's meta field with the new version
-                let mut updated_resource = versioned_resource.resource().clone();
+                let mut updated_resource = handler.server().run_on_load(
+                    &request.resource_type,
+                    versioned_resource.resource().clone(),
+                    context,
+                )?;
                 if let Some(meta) = updated_resource.get_meta() {
                     if let Ok(updated_meta) = meta
                         .clone()
@@ -230,11 +309,12 @@ pub async fn handle_update<P: ResourceProvider + Sync>(
 
                 Ok(ScimOperationResponse {
                     success: true,
-                    data: Some(
+                    data: Some(project_response(
                         handler
                             .server()
                             .serialize_resource_with_refs(&updated_resource, context.tenant_id())?,
-                    ),
+                        request.query.as_ref(),
+                    )),
                     error: None,
                     error_code: None,
                     metadata: OperationMetadata {
@@ -252,46 +332,26 @@ pub async fn handle_update<P: ResourceProvider + Sync>(
                                 .map(|s| s.as_str().to_string())
                                 .collect(),
                         ),
+                        operation: None,
+                        response_size_bytes: None,
+                        compressible: false,
                         additional,
                     },
                 })
             }
-            Err(e) => match &e {
-                e if e.to_string().contains("Version conflict") => {
-                    // Extract VersionConflict from ProviderError::VersionConflict
-                    if let Some(conflict_start) = e.to_string().find("Version conflict: ") {
-                        let _conflict_msg =
-                            &e.to_string()[conflict_start + "Version conflict: ".len()..];
-                        Ok(create_version_conflict_response(
-                            crate::resource::version::VersionConflict::standard_message(
-                                crate::resource::version::RawVersion::from_hash("unknown"),
-                                crate::resource::version::RawVersion::from_hash("unknown"),
-                            ),
-                            context.request_id.clone(),
-                            Some(request.resource_type),
-                            Some(resource_id),
-                        ))
-                    } else {
-                        Err(ScimError::ProviderError(e.to_string()))
-                    }
-                }
-                e if e.to_string().contains("Precondition failed") => {
-                    Ok(create_version_conflict_response(
-                        crate::resource::version::VersionConflict::standard_message(
-                            crate::resource::version::RawVersion::from_hash("unknown"),
-                            crate::resource::version::RawVersion::from_hash("unknown"),
-                        ),
-                        context.request_id.clone(),
-                        Some(request.resource_type),
-                        Some(resource_id),
-                    ))
-                }
-                e if e.to_string().contains("not found") => Err(ScimError::resource_not_found(
-                    request.resource_type,
-                    resource_id,
-                )),
-                _ => Err(ScimError::ProviderError(e.to_string())),
-            },
+            Ok(ConditionalResult::VersionMismatch(conflict)) => {
+                Ok(create_version_conflict_response(
+                    conflict,
+                    context.request_id.clone(),
+                    Some(request.resource_type),
+                    Some(resource_id),
+                ))
+            }
+            Ok(ConditionalResult::NotFound) => Err(ScimError::resource_not_found(
+                request.resource_type,
+                resource_id,
+            )),
+            Err(e) => Err(handler.server().map_provider_error(e, context)),
         }
     } else {
         // Regular update
@@ -340,11 +400,12 @@ pub async fn handle_update<P: ResourceProvider + Sync>(
 
         Ok(ScimOperationResponse {
             success: true,
-            data: Some(
+            data: Some(project_response(
                 handler
                     .server()
                     .serialize_resource_with_refs(&updated_resource, context.tenant_id())?,
-            ),
+                request.query.as_ref(),
+            )),
             error: None,
             error_code: None,
             metadata: OperationMetadata {
@@ -361,6 +422,9 @@ pub async fn handle_update<P: ResourceProvider + Sync>(
                         .map(|s| s.as_str().to_string())
                         .collect(),
                 ),
+                operation: None,
+                response_size_bytes: None,
+                compressible: false,
                 additional,
             },
         })
@@ -379,19 +443,29 @@ pub async fn handle_delete<P: ResourceProvider + Sync>(
 
     // Check if this is a conditional delete request
     if let Some(expected_version) = &request.expected_version {
+        handler
+            .server()
+            .ensure_operation_permitted(context, "delete")?;
+        handler.server().ensure_operation_authorized(
+            context,
+            &ScimOperation::Delete,
+            &request.resource_type,
+            Some(&resource_id),
+        )?;
+
         // Use conditional delete
         match handler
             .server()
             .provider()
-            .delete_resource(
+            .delete_resource_conditional(
                 &request.resource_type,
                 &resource_id,
-                Some(expected_version),
+                expected_version,
                 context,
             )
             .await
         {
-            Ok(_) => Ok(ScimOperationResponse {
+            Ok(ConditionalResult::Success(())) => Ok(ScimOperationResponse {
                 success: true,
                 data: None,
                 error: None,
@@ -404,44 +478,32 @@ pub async fn handle_delete<P: ResourceProvider + Sync>(
                     request_id: context.request_id.clone(),
                     tenant_id: context.tenant_context.as_ref().map(|t| t.tenant_id.clone()),
                     schemas: None,
-                    additional: HashMap::new(),
+                    operation: None,
+                    response_size_bytes: None,
+                    compressible: false,
+                    additional: HashMap::from([("removed".to_string(), Value::Bool(true))]),
                 },
             }),
-            Err(e) => match &e {
-                e if e.to_string().contains("Version conflict") => {
-                    Ok(create_version_conflict_response(
-                        crate::resource::version::VersionConflict::standard_message(
-                            crate::resource::version::RawVersion::from_hash("unknown"),
-                            crate::resource::version::RawVersion::from_hash("unknown"),
-                        ),
-                        context.request_id.clone(),
-                        Some(request.resource_type),
-                        Some(resource_id),
-                    ))
-                }
-                e if e.to_string().contains("Precondition failed") => {
-                    Ok(create_version_conflict_response(
-                        crate::resource::version::VersionConflict::standard_message(
-                            crate::resource::version::RawVersion::from_hash("unknown"),
-                            crate::resource::version::RawVersion::from_hash("unknown"),
-                        ),
-                        context.request_id.clone(),
-                        Some(request.resource_type),
-                        Some(resource_id),
-                    ))
-                }
-                e if e.to_string().contains("not found") => Err(ScimError::resource_not_found(
-                    request.resource_type,
-                    resource_id,
-                )),
-                _ => Err(ScimError::ProviderError(e.to_string())),
-            },
+            Ok(ConditionalResult::VersionMismatch(conflict)) => {
+                Ok(create_version_conflict_response(
+                    conflict,
+                    context.request_id.clone(),
+                    Some(request.resource_type),
+                    Some(resource_id),
+                ))
+            }
+            Ok(ConditionalResult::NotFound) => Err(ScimError::resource_not_found(
+                request.resource_type,
+                resource_id,
+            )),
+            Err(e) => Err(handler.server().map_provider_error(e, context)),
         }
     } else {
-        // Regular delete
-        handler
+        // Regular delete; a missing resource only succeeds when deletes are
+        // idempotent, and is then reported as not removed
+        let removed = handler
             .server()
-            .delete_resource(&request.resource_type, &resource_id, context)
+            .remove_resource(&request.resource_type, &resource_id, context)
             .await?;
 
         Ok(ScimOperationResponse {
@@ -457,7 +519,10 @@ pub async fn handle_delete<P: ResourceProvider + Sync>(
                 request_id: context.request_id.clone(),
                 tenant_id: context.tenant_context.as_ref().map(|t| t.tenant_id.clone()),
                 schemas: None,
-                additional: HashMap::new(),
+                operation: None,
+                response_size_bytes: None,
+                compressible: false,
+                additional: HashMap::from([("removed".to_string(), Value::Bool(removed))]),
             },
         })
     }
@@ -477,14 +542,28 @@ pub async fn handle_patch<P: ResourceProvider + Sync>(
         ScimError::invalid_request("Missing data for patch operation".to_string())
     })?;
 
-    let resource = handler
-        .server()
-        .patch_resource(&request.resource_type, &resource_id, &data, context)
-        .await?;
+    // In per-operation mode each operation's outcome is reported alongside
+    // the resource
+    let mut additional = HashMap::new();
+    let resource = if handler.server().config().patch_mode == PatchMode::PerOperation {
+        let patch = handler
+            .server()
+            .patch_resource_per_operation(&request.resource_type, &resource_id, &data, context)
+            .await?;
+        additional.insert(
+            "operation_results".to_string(),
+            Value::Array(patch.outcomes.iter().map(|o| o.to_json()).collect()),
+        );
+        patch.resource
+    } else {
+        handler
+            .server()
+            .patch_resource(&request.resource_type, &resource_id, &data, context)
+            .await?
+    };
 
     // Include version information in response
     let versioned_resource = VersionedResource::new(resource.clone());
-    let mut additional = HashMap::new();
     additional.insert(
         "version".to_string(),
         serde_json::Value::String(versioned_resource.version().as_str().to_string()),
@@ -522,11 +601,12 @@ pub async fn handle_patch<P: ResourceProvider + Sync>(
 
     Ok(ScimOperationResponse {
         success: true,
-        data: Some(
+        data: Some(project_response(
             handler
                 .server()
                 .serialize_resource_with_refs(&updated_resource, context.tenant_id())?,
-        ),
+            request.query.as_ref(),
+        )),
         error: None,
         error_code: None,
         metadata: OperationMetadata {
@@ -543,6 +623,9 @@ pub async fn handle_patch<P: ResourceProvider + Sync>(
                     .map(|s| s.as_str().to_string())
                     .collect(),
             ),
+            operation: None,
+            response_size_bytes: None,
+            compressible: false,
             additional,
         },
     })
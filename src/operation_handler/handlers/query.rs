@@ -2,14 +2,20 @@
 //!
 //! This module contains handlers for List and Search operations that involve
 //! querying multiple resources with optional filtering, pagination, and sorting.
+//!
+//! Both operations evaluate `filter` (and a search's attribute match) against
+//! complete resources before `attributes`/`excludedAttributes` are applied, so
+//! a resource can be selected by an attribute that its projection then omits
+//! (RFC 7644 Section 3.4.2).
 
 use crate::{
     ResourceProvider, ScimError,
     error::ScimResult,
+    filter::Filter,
     operation_handler::core::{
         OperationMetadata, ScimOperationHandler, ScimOperationRequest, ScimOperationResponse,
     },
-    resource::RequestContext,
+    resource::{ListQuery, RequestContext},
 };
 use std::collections::HashMap;
 
@@ -19,14 +25,44 @@ pub async fn handle_list<P: ResourceProvider + Sync>(
     request: ScimOperationRequest,
     context: &RequestContext,
 ) -> ScimResult<ScimOperationResponse> {
+    let mut query = request.query.as_ref().map(|q| q.to_list_query());
+    if let Some(filter) = query.as_ref().and_then(|q| q.filter.as_deref()) {
+        Filter::parse(filter)?;
+    }
+
+    // Pages never exceed the most specific cap for the resource type
+    if let Some(max_page_size) = handler
+        .server()
+        .max_page_size(&request.resource_type, context)
+    {
+        let query = query.get_or_insert_with(ListQuery::default);
+        query.count = Some(
+            query
+                .count
+                .map_or(max_page_size, |count| count.min(max_page_size)),
+        );
+    }
+
     let resources = handler
         .server()
-        .list_resources(&request.resource_type, context)
+        .list_resources_with_query(&request.resource_type, query.as_ref(), context)
+        .await?;
+
+    // totalResults counts every match, not just the page returned
+    let total_results = handler
+        .server()
+        .count_resources(&request.resource_type, query.as_ref(), context)
         .await?;
+    let start_index = query.as_ref().and_then(|q| q.start_index).unwrap_or(1);
 
     let resource_count = resources.len();
-    let resources_json: Result<Vec<_>, _> = resources.iter()
-        .map(|r| handler.server().serialize_resource_with_refs(r, context.tenant_id()))
+    let resources_json: Result<Vec<_>, _> = resources
+        .iter()
+        .map(|r| {
+            handler
+                .server()
+                .serialize_resource_with_refs(r, context.tenant_id())
+        })
         .collect();
 
     let resources_json = resources_json?;
@@ -40,11 +76,14 @@ pub async fn handle_list<P: ResourceProvider + Sync>(
             resource_type: Some(request.resource_type),
             resource_id: None,
             resource_count: Some(resource_count),
-            total_results: Some(resource_count),
+            total_results: Some(total_results),
             request_id: context.request_id.clone(),
             tenant_id: context.tenant_context.as_ref().map(|t| t.tenant_id.clone()),
             schemas: None,
-            additional: HashMap::new(),
+            operation: None,
+            response_size_bytes: None,
+            compressible: false,
+            additional: HashMap::from([("start_index".to_string(), start_index.into())]),
         },
     })
 }
@@ -59,6 +98,8 @@ pub async fn handle_search<P: ResourceProvider + Sync>(
         ScimError::invalid_request("Missing query parameters for search operation".to_string())
     })?;
 
+    let list_query = query.to_list_query();
+    list_query.validate_pagination()?;
     let search_attribute = query.search_attribute.ok_or_else(|| {
         ScimError::invalid_request("Missing search_attribute for search operation".to_string())
     })?;
@@ -67,28 +108,43 @@ pub async fn handle_search<P: ResourceProvider + Sync>(
         ScimError::invalid_request("Missing search_value for search operation".to_string())
     })?;
 
+    // Match against complete resources; pagination and projection come last
+    if let Some(filter) = list_query.filter.as_deref() {
+        Filter::parse(filter)?;
+    }
+    let matching_query = ListQuery {
+        filter: list_query.filter.clone(),
+        sort_by: list_query.sort_by.clone(),
+        sort_order: list_query.sort_order,
+        ..ListQuery::default()
+    };
+
     let resources = handler
         .server()
-        .list_resources(&request.resource_type, context)
-        .await?
-        .into_iter()
-        .filter(|resource| {
-            // Simple attribute-based filtering for now
-            if let Ok(json) = handler.server().serialize_resource_with_refs(resource, context.tenant_id()) {
-                if let Some(value) = json.get(&search_attribute) {
-                    return value == &search_value;
-                }
-            }
-            false
-        })
-        .collect::<Vec<_>>();
+        .list_resources_with_query(&request.resource_type, Some(&matching_query), context)
+        .await?;
 
-    let resource_count = resources.len();
-    let resources_json: Result<Vec<_>, _> = resources.iter()
-        .map(|r| handler.server().serialize_resource_with_refs(r, context.tenant_id()))
-        .collect();
+    let mut matches = Vec::new();
+    for resource in &resources {
+        let json = handler
+            .server()
+            .serialize_resource_with_refs(resource, context.tenant_id())?;
+        if json.get(&search_attribute) == Some(&search_value) {
+            matches.push(json);
+        }
+    }
 
-    let resources_json = resources_json?;
+    let total_results = matches.len();
+    let start_index = list_query.start_index.unwrap_or(1);
+    let page_query = ListQuery {
+        filter: None,
+        sort_by: None,
+        ..list_query
+    };
+    let resources_json = page_query
+        .apply_to(matches)
+        .map_err(|e| ScimError::invalid_request(e.to_string()))?;
+    let resource_count = resources_json.len();
 
     Ok(ScimOperationResponse {
         success: true,
@@ -99,11 +155,14 @@ pub async fn handle_search<P: ResourceProvider + Sync>(
             resource_type: Some(request.resource_type),
             resource_id: None,
             resource_count: Some(resource_count),
-            total_results: Some(resource_count),
+            total_results: Some(total_results),
             request_id: context.request_id.clone(),
             tenant_id: context.tenant_context.as_ref().map(|t| t.tenant_id.clone()),
             schemas: None,
-            additional: HashMap::new(),
+            operation: None,
+            response_size_bytes: None,
+            compressible: false,
+            additional: HashMap::from([("start_index".to_string(), start_index.into())]),
         },
     })
 }
@@ -49,6 +49,9 @@ pub async fn handle_get_schemas<P: ResourceProvider + Sync>(
             request_id: context.request_id.clone(),
             tenant_id: context.tenant_context.as_ref().map(|t| t.tenant_id.clone()),
             schemas: None,
+            operation: None,
+            response_size_bytes: None,
+            compressible: false,
             additional: HashMap::new(),
         },
     })
@@ -84,6 +87,9 @@ pub async fn handle_get_schema<P: ResourceProvider + Sync>(
                     request_id: context.request_id.clone(),
                     tenant_id: context.tenant_context.as_ref().map(|t| t.tenant_id.clone()),
                     schemas: None,
+                    operation: None,
+                    response_size_bytes: None,
+                    compressible: false,
                     additional: HashMap::new(),
                 },
             })
@@ -25,10 +25,8 @@ pub async fn handle_exists<P: ResourceProvider + Sync>(
 
     let exists = handler
         .server()
-        .provider()
         .resource_exists(&request.resource_type, &resource_id, context)
-        .await
-        .map_err(|e| ScimError::ProviderError(e.to_string()))?;
+        .await?;
 
     let mut additional = HashMap::new();
     additional.insert("exists".to_string(), serde_json::Value::Bool(exists));
@@ -46,6 +44,9 @@ pub async fn handle_exists<P: ResourceProvider + Sync>(
             request_id: context.request_id.clone(),
             tenant_id: context.tenant_context.as_ref().map(|t| t.tenant_id.clone()),
             schemas: None,
+            operation: None,
+            response_size_bytes: None,
+            compressible: false,
             additional,
         },
     })
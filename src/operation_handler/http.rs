@@ -0,0 +1,150 @@
+//! HTTP translation of operation responses
+//!
+//! [`ScimOperationResponse::into_http_parts`] maps a response onto the status
+//! code, headers and body an HTTP integration sends, so that every transport
+//! renders responses the same way (RFC 7644 Section 3):
+//!
+//! - create, and an upsert that creates, answer `201 Created` with a
+//!   `Location` header
+//! - delete answers `204 No Content` with no body
+//! - list and search results are wrapped in a `ListResponse`
+//! - failures carry a SCIM `Error` body with the status for their error code
+
+use super::core::{ScimOperationResponse, ScimOperationType};
+use crate::error::ScimType;
+use serde_json::{Value, json};
+
+/// Media type of SCIM request and response bodies.
+pub const SCIM_CONTENT_TYPE: &str = "application/scim+json";
+
+impl ScimOperationResponse {
+    /// Split the response into an HTTP status code, headers and body.
+    ///
+    /// Headers are `(name, value)` pairs: `ETag` when the response carries a
+    /// version, `Location` for created resources, `Retry-After` for an
+    /// unavailable backend that reported one and `Content-Type` whenever
+    /// there is a body. The body is [`Value::Null`] when there is none.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::operation_handler::{ScimOperationHandler, ScimOperationRequest};
+    /// use scim_server::{ScimServer, providers::StandardResourceProvider};
+    /// use scim_server::storage::InMemoryStorage;
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let server = ScimServer::new(StandardResourceProvider::new(InMemoryStorage::new()))?;
+    /// let handler = ScimOperationHandler::new(server);
+    ///
+    /// let response = handler
+    ///     .handle_operation(ScimOperationRequest::get("User", "missing"))
+    ///     .await;
+    /// let (status, _headers, body) = response.into_http_parts();
+    /// assert_eq!(status, 404);
+    /// assert_eq!(body["status"], "404");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_http_parts(self) -> (u16, Vec<(String, String)>, Value) {
+        let mut headers = Vec::new();
+        if let Some(Value::String(etag)) = self.metadata.additional.get("etag") {
+            headers.push(("ETag".to_string(), etag.clone()));
+        }
+
+        if !self.success {
+            let (status, scim_type) = error_status(self.error_code.as_deref());
+            let mut body = json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+                "status": status.to_string(),
+                "detail": self.error.unwrap_or_default(),
+            });
+            // The error's own keyword is more specific than its code's
+            match self.metadata.additional.get("scim_type") {
+                Some(scim_type) => body["scimType"] = scim_type.clone(),
+                None => {
+                    if let Some(scim_type) = scim_type {
+                        body["scimType"] = scim_type.as_str().into();
+                    }
+                }
+            }
+            if let Some(id) = self.metadata.additional.get("conflicting_id") {
+                body["conflictingResource"] = json!({ "id": id });
+                if let Some(location) = self.metadata.additional.get("conflicting_location") {
+                    body["conflictingResource"]["location"] = location.clone();
+                }
+            }
+            if let Some(retry_after) = self.metadata.additional.get("retry_after") {
+                headers.push(("Retry-After".to_string(), retry_after.to_string()));
+            }
+            headers.push(("Content-Type".to_string(), SCIM_CONTENT_TYPE.to_string()));
+            return (status, headers, body);
+        }
+
+        let status = match self.metadata.operation {
+            Some(ScimOperationType::Create) => 201,
+            Some(ScimOperationType::Delete) => 204,
+            _ => 200,
+        };
+        if status == 201
+            && let Some(Value::String(location)) = self
+                .data
+                .as_ref()
+                .and_then(|data| data.pointer("/meta/location"))
+        {
+            headers.push(("Location".to_string(), location.clone()));
+        }
+        if status == 204 {
+            return (status, headers, Value::Null);
+        }
+
+        let body = match self.data {
+            // Collections are enveloped; their metadata carries the totals
+            Some(Value::Array(resources)) => json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:ListResponse"],
+                "totalResults": self.metadata.total_results.unwrap_or(resources.len()),
+                "startIndex": self
+                    .metadata
+                    .additional
+                    .get("start_index")
+                    .cloned()
+                    .unwrap_or_else(|| 1.into()),
+                "itemsPerPage": resources.len(),
+                "Resources": resources,
+            }),
+            Some(data) => data,
+            None => Value::Null,
+        };
+        if !body.is_null() {
+            headers.push(("Content-Type".to_string(), SCIM_CONTENT_TYPE.to_string()));
+        }
+        (status, headers, body)
+    }
+}
+
+/// HTTP status and SCIM `scimType` for a handler error code.
+fn error_status(error_code: Option<&str>) -> (u16, Option<ScimType>) {
+    match error_code {
+        Some("VALIDATION_ERROR") => (400, Some(ScimType::InvalidValue)),
+        Some("INVALID_SYNTAX") => (400, Some(ScimType::InvalidSyntax)),
+        Some("INVALID_FILTER") => (400, Some(ScimType::InvalidFilter)),
+        Some("INVALID_REQUEST") => (400, None),
+        Some("PERMISSION_DENIED" | "AUTHORIZATION_DENIED") => (403, None),
+        Some(
+            "RESOURCE_NOT_FOUND"
+            | "RESOURCE_DELETED"
+            | "SCHEMA_NOT_FOUND"
+            | "UNSUPPORTED_RESOURCE_TYPE",
+        ) => (404, None),
+        Some("UNIQUENESS_CONFLICT") => (409, Some(ScimType::Uniqueness)),
+        Some("STORAGE_CONFLICT" | "IDEMPOTENCY_KEY_IN_PROGRESS") => (409, None),
+        Some("version_mismatch") => (412, None),
+        Some("PAYLOAD_TOO_LARGE") => (413, None),
+        Some("TOO_MANY_MEMBERS") => (413, Some(ScimType::TooMany)),
+        Some("IDEMPOTENCY_KEY_MISMATCH") => (422, None),
+        Some("UNSUPPORTED_OPERATION") => (501, None),
+        Some("SERVICE_UNAVAILABLE") => (503, None),
+        Some("CAPACITY_EXCEEDED") => (507, None),
+        _ => (500, None),
+    }
+}
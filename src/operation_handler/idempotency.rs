@@ -0,0 +1,324 @@
+//! Idempotency keys for mutating operations
+//!
+//! Upstream identity providers often deliver provisioning requests at least
+//! once, so the same create or update may arrive twice. A request carrying an
+//! [`idempotency_key`](super::ScimOperationRequest::idempotency_key) is
+//! executed at most once per key within the store's TTL; replays receive the
+//! original response instead of applying the change again.
+//!
+//! Keys are scoped to the request's tenant and only honoured for create,
+//! update, patch and delete. A key is claimed before the operation runs, so a
+//! replay that arrives while the original is still executing is rejected
+//! rather than applied a second time. Only successful responses are kept: a
+//! failed operation releases its key so that a retry can run, as does an
+//! operation whose future is dropped before it completes.
+
+use super::core::{
+    OperationMetadata, ScimOperationRequest, ScimOperationResponse, ScimOperationType,
+};
+use crate::resource::serialization::write_canonical;
+use crate::schema::{Schema, embedded};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// A client-supplied idempotency key, scoped to a tenant.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey {
+    /// Tenant the request was made for, `None` in single-tenant mode
+    pub tenant_id: Option<String>,
+    /// The key supplied by the client
+    pub key: String,
+}
+
+/// The request a key was first used for.
+///
+/// A key reused for a different request, including the same operation with a
+/// different body, is rejected rather than replayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotencyFingerprint {
+    /// Operation performed
+    pub operation: ScimOperationType,
+    /// Resource type targeted
+    pub resource_type: String,
+    /// Resource targeted, for operations on an existing resource
+    pub resource_id: Option<String>,
+    /// SHA-256 of the canonical request body, `None` for a request without one
+    pub body_hash: Option<[u8; 32]>,
+}
+
+impl IdempotencyFingerprint {
+    /// Fingerprint of a request.
+    pub fn of(request: &ScimOperationRequest) -> Self {
+        Self {
+            operation: request.operation,
+            resource_type: request.resource_type.clone(),
+            resource_id: request.resource_id.clone(),
+            body_hash: request
+                .data
+                .as_ref()
+                .map(|data| body_hash(&request.resource_type, data)),
+        }
+    }
+}
+
+/// SHA-256 of `data` in canonical form, so key order and equivalent number or
+/// `dateTime` attribute spellings do not make a replay look like a different
+/// request.
+fn body_hash(resource_type: &str, data: &Value) -> [u8; 32] {
+    let schemas: Vec<&Schema> = embedded::parsed_core_schema(resource_type)
+        .into_iter()
+        .collect();
+    let mut canonical = Vec::new();
+    write_canonical(data, &schemas, &mut canonical);
+    Sha256::digest(&canonical).into()
+}
+
+/// Outcome of claiming an idempotency key.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotencyClaim {
+    /// The key was unused; the caller must run the operation and then call
+    /// [`IdempotencyStore::complete`] or [`IdempotencyStore::release`].
+    Claimed,
+    /// The key was already used for this request; replay this response.
+    Completed(Box<ScimOperationResponse>),
+    /// A request with this key is still executing.
+    InProgress,
+    /// The key was used for a different request.
+    Mismatch,
+}
+
+/// Storage for idempotency keys and the responses they produced.
+///
+/// Implementations must make [`claim`](Self::claim) atomic: of several
+/// concurrent claims for the same key, exactly one may return
+/// [`IdempotencyClaim::Claimed`].
+pub trait IdempotencyStore: Send + Sync {
+    /// Claim `key` for the request described by `fingerprint`.
+    fn claim(&self, key: &IdempotencyKey, fingerprint: &IdempotencyFingerprint)
+    -> IdempotencyClaim;
+
+    /// Record the response of a claimed request for replay.
+    fn complete(&self, key: &IdempotencyKey, response: &ScimOperationResponse);
+
+    /// Release a claimed key without recording a response.
+    fn release(&self, key: &IdempotencyKey);
+}
+
+impl<T: IdempotencyStore + ?Sized> IdempotencyStore for Arc<T> {
+    fn claim(
+        &self,
+        key: &IdempotencyKey,
+        fingerprint: &IdempotencyFingerprint,
+    ) -> IdempotencyClaim {
+        (**self).claim(key, fingerprint)
+    }
+
+    fn complete(&self, key: &IdempotencyKey, response: &ScimOperationResponse) {
+        (**self).complete(key, response)
+    }
+
+    fn release(&self, key: &IdempotencyKey) {
+        (**self).release(key)
+    }
+}
+
+/// Default time a completed response is kept for replay (24 hours).
+pub const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// In-memory [`IdempotencyStore`] that keeps responses for a fixed TTL.
+///
+/// Expired entries are purged lazily when new keys are claimed.
+#[derive(Debug)]
+pub struct InMemoryIdempotencyStore {
+    ttl: Duration,
+    entries: Mutex<HashMap<IdempotencyKey, Entry>>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    fingerprint: IdempotencyFingerprint,
+    response: Option<ScimOperationResponse>,
+    expires_at: Instant,
+}
+
+impl InMemoryIdempotencyStore {
+    /// Create a store that keeps responses for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// How long responses are kept.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Number of keys currently held, including in-flight requests.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no keys are held.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<IdempotencyKey, Entry>> {
+        // Each entry is written whole, so a panicking holder leaves none half-updated
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for InMemoryIdempotencyStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_IDEMPOTENCY_TTL)
+    }
+}
+
+impl IdempotencyStore for InMemoryIdempotencyStore {
+    fn claim(
+        &self,
+        key: &IdempotencyKey,
+        fingerprint: &IdempotencyFingerprint,
+    ) -> IdempotencyClaim {
+        let now = Instant::now();
+        let mut entries = self.lock();
+        entries.retain(|_, entry| entry.expires_at > now);
+
+        match entries.get(key) {
+            Some(entry) if entry.fingerprint != *fingerprint => IdempotencyClaim::Mismatch,
+            Some(Entry {
+                response: Some(response),
+                ..
+            }) => IdempotencyClaim::Completed(Box::new(response.clone())),
+            Some(_) => IdempotencyClaim::InProgress,
+            None => {
+                entries.insert(
+                    key.clone(),
+                    Entry {
+                        fingerprint: fingerprint.clone(),
+                        response: None,
+                        expires_at: now + self.ttl,
+                    },
+                );
+                IdempotencyClaim::Claimed
+            }
+        }
+    }
+
+    fn complete(&self, key: &IdempotencyKey, response: &ScimOperationResponse) {
+        if let Some(entry) = self.lock().get_mut(key) {
+            entry.response = Some(response.clone());
+            entry.expires_at = Instant::now() + self.ttl;
+        }
+    }
+
+    fn release(&self, key: &IdempotencyKey) {
+        self.lock().remove(key);
+    }
+}
+
+/// Releases a claimed key unless the request completed.
+///
+/// Held for the duration of the operation, so a future that is cancelled or
+/// dropped mid-flight frees its key instead of leaving it in progress until
+/// the TTL expires.
+pub(super) struct ClaimGuard<'a> {
+    store: &'a dyn IdempotencyStore,
+    key: Option<IdempotencyKey>,
+}
+
+impl<'a> ClaimGuard<'a> {
+    pub(super) fn new(store: &'a dyn IdempotencyStore, key: IdempotencyKey) -> Self {
+        Self {
+            store,
+            key: Some(key),
+        }
+    }
+
+    /// Record the response for replay if it succeeded; otherwise release the
+    /// key so a retry can run.
+    pub(super) fn finish(mut self, response: &ScimOperationResponse) {
+        if let Some(key) = self.key.take() {
+            if response.success {
+                self.store.complete(&key, response);
+            } else {
+                self.store.release(&key);
+            }
+        }
+    }
+}
+
+impl Drop for ClaimGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.store.release(&key);
+        }
+    }
+}
+
+/// Whether an operation honours idempotency keys.
+pub(super) fn is_mutating(operation: ScimOperationType) -> bool {
+    matches!(
+        operation,
+        ScimOperationType::Create
+            | ScimOperationType::Update
+            | ScimOperationType::Patch
+            | ScimOperationType::Delete
+            | ScimOperationType::Upsert
+    )
+}
+
+/// Response for a key that cannot be used for this request.
+pub(super) fn create_idempotency_error_response(
+    claim: &IdempotencyClaim,
+    key: &IdempotencyKey,
+    request_id: String,
+) -> ScimOperationResponse {
+    let (error, error_code) = match claim {
+        IdempotencyClaim::InProgress => (
+            format!(
+                "A request with idempotency key '{}' is still in progress",
+                key.key
+            ),
+            "IDEMPOTENCY_KEY_IN_PROGRESS",
+        ),
+        _ => (
+            format!(
+                "Idempotency key '{}' was already used for a different request",
+                key.key
+            ),
+            "IDEMPOTENCY_KEY_MISMATCH",
+        ),
+    };
+
+    ScimOperationResponse {
+        success: false,
+        data: None,
+        error: Some(error),
+        error_code: Some(error_code.to_string()),
+        metadata: OperationMetadata {
+            resource_type: None,
+            resource_id: None,
+            resource_count: None,
+            total_results: None,
+            request_id,
+            tenant_id: key.tenant_id.clone(),
+            schemas: None,
+            operation: None,
+            response_size_bytes: None,
+            compressible: false,
+            additional: HashMap::from([(
+                "idempotency_key".to_string(),
+                Value::String(key.key.clone()),
+            )]),
+        },
+    }
+}
@@ -0,0 +1,55 @@
+//! Middleware around operation handling
+//!
+//! Middleware registered with
+//! [`ScimOperationHandler::with_middleware`](super::ScimOperationHandler::with_middleware)
+//! can inspect and rewrite requests before they are dispatched and responses
+//! before they are returned, e.g. to inject a default attribute or enforce a
+//! naming convention.
+//!
+//! Middleware runs as a chain in registration order: every `before` hook runs
+//! in the order the middleware was registered, and the `after` hooks run in
+//! the reverse order, so the first middleware registered sees the request
+//! first and the response last. A `before` hook that returns an error rejects
+//! the request: later middleware and the operation itself do not run, and
+//! only the `after` hooks of middleware whose `before` already ran see the
+//! error response.
+//!
+//! Both hooks receive the request's [`RequestContext`], including its tenant
+//! context. Endpoint names have already been resolved to resource types when
+//! the chain runs.
+
+use super::core::{ScimOperationRequest, ScimOperationResponse};
+use crate::error::ScimResult;
+use crate::resource::RequestContext;
+use std::sync::Arc;
+
+/// Hooks run before and after each operation.
+pub trait OperationMiddleware: Send + Sync {
+    /// Inspect or rewrite a request before it is dispatched.
+    ///
+    /// Returning an error rejects the request with that error.
+    fn before(
+        &self,
+        _request: &mut ScimOperationRequest,
+        _context: &RequestContext,
+    ) -> ScimResult<()> {
+        Ok(())
+    }
+
+    /// Inspect or rewrite a response before it is returned.
+    fn after(&self, _response: &mut ScimOperationResponse, _context: &RequestContext) {}
+}
+
+impl<T: OperationMiddleware + ?Sized> OperationMiddleware for Arc<T> {
+    fn before(
+        &self,
+        request: &mut ScimOperationRequest,
+        context: &RequestContext,
+    ) -> ScimResult<()> {
+        (**self).before(request, context)
+    }
+
+    fn after(&self, response: &mut ScimOperationResponse, context: &RequestContext) {
+        (**self).after(response, context)
+    }
+}
@@ -7,7 +7,9 @@
 //!
 //! - [`ScimOperationHandler`] - Main handler for processing SCIM operations
 //! - [`ScimOperationRequest`] - Structured request wrapper with validation
-//! - [`ScimOperationResponse`] - Response with metadata and ETag information
+//! - [`ScimOperationResponse`] - Response with metadata and ETag information, convertible
+//!   to HTTP parts with [`ScimOperationResponse::into_http_parts`]
+//! - `ScimService` - The handler as a `tower::Service`, with the `tower` feature
 //!
 //! # Examples
 //!
@@ -33,6 +35,11 @@ mod builders;
 mod core;
 mod errors;
 mod handlers;
+mod http;
+pub mod idempotency;
+pub mod middleware;
+#[cfg(feature = "tower")]
+pub mod service;
 
 // Re-export all public types and functions
 pub use core::{
@@ -45,3 +52,8 @@ pub use builders::*;
 
 // Re-export error utilities for advanced usage
 pub use errors::{create_error_response, create_version_conflict_response};
+pub use http::SCIM_CONTENT_TYPE;
+pub use idempotency::{IdempotencyStore, InMemoryIdempotencyStore};
+pub use middleware::OperationMiddleware;
+#[cfg(feature = "tower")]
+pub use service::ScimService;
@@ -0,0 +1,143 @@
+//! [`tower::Service`](tower_service::Service) adapter for the operation handler.
+//!
+//! Available with the `tower` feature. [`ScimService`] wraps a
+//! [`ScimOperationHandler`] so it can sit at the bottom of a tower stack and
+//! have timeouts, concurrency limits or tracing layered on top with tower
+//! middleware. It only depends on the `tower-service` trait crate, not on
+//! tower itself or any HTTP framework.
+//!
+//! The service is always ready and never fails: errors are reported in the
+//! [`ScimOperationResponse`], as with
+//! [`handle_operation`](ScimOperationHandler::handle_operation). Cloning it
+//! is cheap and every clone shares the same handler, so it can be handed to
+//! each connection or request of a server.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use scim_server::operation_handler::{ScimOperationHandler, ScimOperationRequest, ScimService};
+//! use scim_server::{ScimServer, providers::StandardResourceProvider};
+//! use scim_server::storage::InMemoryStorage;
+//! use tower_service::Service;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let provider = StandardResourceProvider::new(InMemoryStorage::new());
+//! let handler = ScimOperationHandler::new(ScimServer::new(provider)?);
+//! let mut service = ScimService::new(handler);
+//!
+//! std::future::poll_fn(|cx| service.poll_ready(cx)).await?;
+//! let response = service.call(ScimOperationRequest::list("User")).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::core::{ScimOperationHandler, ScimOperationRequest, ScimOperationResponse};
+use crate::providers::ResourceProvider;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// A [`ScimOperationHandler`] as a `tower::Service`.
+pub struct ScimService<P: ResourceProvider> {
+    handler: Arc<ScimOperationHandler<P>>,
+}
+
+impl<P: ResourceProvider> ScimService<P> {
+    /// Wrap a handler in a service.
+    pub fn new(handler: ScimOperationHandler<P>) -> Self {
+        Self::from_shared(Arc::new(handler))
+    }
+
+    /// Wrap a handler that is already shared, e.g. with other transports.
+    pub fn from_shared(handler: Arc<ScimOperationHandler<P>>) -> Self {
+        Self { handler }
+    }
+
+    /// The handler the service dispatches to.
+    pub fn handler(&self) -> &ScimOperationHandler<P> {
+        &self.handler
+    }
+}
+
+impl<P: ResourceProvider> Clone for ScimService<P> {
+    fn clone(&self) -> Self {
+        Self {
+            handler: Arc::clone(&self.handler),
+        }
+    }
+}
+
+impl<P> tower_service::Service<ScimOperationRequest> for ScimService<P>
+where
+    P: ResourceProvider + Send + Sync + 'static,
+{
+    type Response = ScimOperationResponse;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<ScimOperationResponse, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: ScimOperationRequest) -> Self::Future {
+        let handler = Arc::clone(&self.handler);
+        Box::pin(async move { Ok(handler.handle_operation(request).await) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScimServer;
+    use crate::providers::StandardResourceProvider;
+    use crate::resource::ScimOperation;
+    use crate::resource_handlers::create_user_resource_handler;
+    use crate::storage::InMemoryStorage;
+    use serde_json::json;
+    use tower_service::Service;
+
+    #[tokio::test]
+    async fn test_service_clones_share_handler() {
+        let provider = StandardResourceProvider::new(InMemoryStorage::new());
+        let mut server = ScimServer::new(provider).unwrap();
+        let user_schema = server
+            .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+            .unwrap()
+            .clone();
+        server
+            .register_resource_type(
+                "User",
+                create_user_resource_handler(user_schema),
+                vec![ScimOperation::Create, ScimOperation::List],
+            )
+            .unwrap();
+        let mut service = ScimService::new(ScimOperationHandler::new(server));
+        let mut other = service.clone();
+
+        std::future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .unwrap();
+        let created = service
+            .call(ScimOperationRequest::create(
+                "User",
+                json!({"userName": "tower.user"}),
+            ))
+            .await
+            .unwrap();
+        assert!(created.success, "{:?}", created.error);
+
+        // The clone sees what the original wrote; failures are responses
+        let listed = tokio::spawn(other.call(ScimOperationRequest::list("User")))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(listed.metadata.total_results, Some(1));
+        let missing = other
+            .call(ScimOperationRequest::get("User", "missing"))
+            .await
+            .unwrap();
+        assert!(!missing.success);
+    }
+}
@@ -576,6 +576,252 @@ impl Default for ExtendedCapabilities {
     }
 }
 
+impl ProviderCapabilities {
+    /// Start building capabilities by hand, with every optional feature
+    /// unsupported.
+    pub fn builder() -> ProviderCapabilitiesBuilder {
+        ProviderCapabilitiesBuilder::new()
+    }
+}
+
+/// Fluent builder for [`ProviderCapabilities`].
+///
+/// Sections that are not configured are reported as unsupported: no bulk,
+/// filtering, pagination, PATCH, sorting or password changes, and no
+/// authentication schemes. ETag support is always on, as conditional
+/// operations are mandatory.
+///
+/// # Examples
+///
+/// ```rust
+/// use scim_server::provider_capabilities::{
+///     CapabilityDiscovery, FilterOperator, ProviderCapabilities,
+/// };
+/// use scim_server::resource::ScimOperation;
+///
+/// let capabilities = ProviderCapabilities::builder()
+///     .with_resource_type("User", vec![ScimOperation::Create, ScimOperation::Read])
+///     .supports_filter_operators([FilterOperator::Equal, FilterOperator::Present])
+///     .with_bulk(100, 1_048_576)
+///     .with_pagination(20, 200)
+///     .with_patch()
+///     .build()
+///     .unwrap();
+///
+/// let config = CapabilityDiscovery::generate_service_provider_config(&capabilities);
+/// assert!(config.patch_supported && config.bulk_supported && config.filter_supported);
+/// assert!(!config.sort_supported);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProviderCapabilitiesBuilder {
+    capabilities: ProviderCapabilities,
+}
+
+impl ProviderCapabilitiesBuilder {
+    /// Create a builder with every optional feature unsupported.
+    pub fn new() -> Self {
+        Self {
+            capabilities: ProviderCapabilities {
+                supported_operations: HashMap::new(),
+                supported_schemas: Vec::new(),
+                supported_resource_types: Vec::new(),
+                bulk_capabilities: BulkCapabilities::default(),
+                filter_capabilities: FilterCapabilities {
+                    supported_operators: Vec::new(),
+                    ..FilterCapabilities::default()
+                },
+                pagination_capabilities: PaginationCapabilities {
+                    supported: false,
+                    default_page_size: None,
+                    max_page_size: None,
+                    cursor_based_supported: false,
+                },
+                authentication_capabilities: AuthenticationCapabilities::default(),
+                extended_capabilities: ExtendedCapabilities::default(),
+            },
+        }
+    }
+
+    /// Add a resource type with the operations it supports. Adding a type
+    /// again replaces its operations.
+    pub fn with_resource_type(
+        mut self,
+        resource_type: impl Into<String>,
+        operations: Vec<ScimOperation>,
+    ) -> Self {
+        let resource_type = resource_type.into();
+        if !self
+            .capabilities
+            .supported_resource_types
+            .contains(&resource_type)
+        {
+            self.capabilities
+                .supported_resource_types
+                .push(resource_type.clone());
+        }
+        self.capabilities
+            .supported_operations
+            .insert(resource_type, operations);
+        self
+    }
+
+    /// Add a supported schema URI.
+    pub fn with_schema(mut self, schema_id: impl Into<String>) -> Self {
+        let schema_id = schema_id.into();
+        if !self.capabilities.supported_schemas.contains(&schema_id) {
+            self.capabilities.supported_schemas.push(schema_id);
+        }
+        self
+    }
+
+    /// Support filtering with the given operators, in addition to any
+    /// added before.
+    pub fn supports_filter_operators(
+        mut self,
+        operators: impl IntoIterator<Item = FilterOperator>,
+    ) -> Self {
+        let filter = &mut self.capabilities.filter_capabilities;
+        for operator in operators {
+            if !filter.supported_operators.contains(&operator) {
+                filter.supported_operators.push(operator);
+            }
+        }
+        filter.supported = !filter.supported_operators.is_empty();
+        self
+    }
+
+    /// Support `and`/`or`/`not` in filters.
+    pub fn with_complex_filters(mut self) -> Self {
+        self.capabilities
+            .filter_capabilities
+            .complex_filters_supported = true;
+        self
+    }
+
+    /// Set the maximum number of results a filtered query returns.
+    pub fn with_filter_max_results(mut self, max_results: usize) -> Self {
+        self.capabilities.filter_capabilities.max_results = Some(max_results);
+        self
+    }
+
+    /// Support bulk requests of up to `max_operations` operations and
+    /// `max_payload_size` bytes.
+    pub fn with_bulk(mut self, max_operations: usize, max_payload_size: usize) -> Self {
+        self.capabilities.bulk_capabilities = BulkCapabilities {
+            supported: true,
+            max_operations: Some(max_operations),
+            max_payload_size: Some(max_payload_size),
+            fail_on_errors_supported: true,
+        };
+        self
+    }
+
+    /// Support pagination with the given default and maximum page sizes.
+    pub fn with_pagination(mut self, default_page_size: usize, max_page_size: usize) -> Self {
+        self.capabilities.pagination_capabilities = PaginationCapabilities {
+            supported: true,
+            default_page_size: Some(default_page_size),
+            max_page_size: Some(max_page_size),
+            cursor_based_supported: false,
+        };
+        self
+    }
+
+    /// Support PATCH.
+    pub fn with_patch(mut self) -> Self {
+        self.capabilities.extended_capabilities.patch_supported = true;
+        self
+    }
+
+    /// Support sorting.
+    pub fn with_sort(mut self) -> Self {
+        self.capabilities.extended_capabilities.sort_supported = true;
+        self
+    }
+
+    /// Support password changes.
+    pub fn with_change_password(mut self) -> Self {
+        self.capabilities
+            .extended_capabilities
+            .change_password_supported = true;
+        self
+    }
+
+    /// Advertise an authentication scheme.
+    pub fn with_authentication_scheme(mut self, scheme: AuthenticationScheme) -> Self {
+        self.capabilities
+            .authentication_capabilities
+            .schemes
+            .push(scheme);
+        self
+    }
+
+    /// Set a provider-specific capability.
+    pub fn with_custom_capability(
+        mut self,
+        name: impl Into<String>,
+        value: serde_json::Value,
+    ) -> Self {
+        self.capabilities
+            .extended_capabilities
+            .custom_capabilities
+            .insert(name.into(), value);
+        self
+    }
+
+    /// Build the capabilities.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScimError::Internal`] if a limit is zero, the default page
+    /// size exceeds the maximum, or more than one authentication scheme is
+    /// primary.
+    pub fn build(self) -> Result<ProviderCapabilities, ScimError> {
+        let capabilities = self.capabilities;
+
+        let bulk = &capabilities.bulk_capabilities;
+        if bulk.max_operations == Some(0) || bulk.max_payload_size == Some(0) {
+            return Err(ScimError::internal("Bulk limits must be non-zero"));
+        }
+
+        if capabilities.filter_capabilities.max_results == Some(0) {
+            return Err(ScimError::internal(
+                "Filter maximum results must be non-zero",
+            ));
+        }
+
+        let pagination = &capabilities.pagination_capabilities;
+        if let (Some(default), Some(max)) = (pagination.default_page_size, pagination.max_page_size)
+            && (default == 0 || default > max)
+        {
+            return Err(ScimError::internal(
+                "Default page size must be non-zero and at most the maximum page size",
+            ));
+        }
+
+        if capabilities
+            .authentication_capabilities
+            .schemes
+            .iter()
+            .filter(|scheme| scheme.primary)
+            .count()
+            > 1
+        {
+            return Err(ScimError::internal(
+                "At most one authentication scheme can be primary",
+            ));
+        }
+
+        Ok(capabilities)
+    }
+}
+
+impl Default for ProviderCapabilitiesBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // Default implementation can be provided via a blanket impl, but users can override
 // by implementing the trait directly on their provider types
 
@@ -654,4 +900,67 @@ mod tests {
         assert!(operators.contains(&FilterOperator::GreaterThan));
         assert!(operators.contains(&FilterOperator::LessThan));
     }
+
+    #[test]
+    fn test_builder_defaults_to_unsupported() {
+        let capabilities = ProviderCapabilities::builder().build().unwrap();
+        let config = CapabilityDiscovery::generate_service_provider_config(&capabilities);
+        assert_eq!(
+            config,
+            ServiceProviderConfig {
+                etag_supported: true,
+                ..ServiceProviderConfig::default()
+            }
+        );
+        assert!(!capabilities.pagination_capabilities.supported);
+        assert!(
+            capabilities
+                .filter_capabilities
+                .supported_operators
+                .is_empty()
+        );
+
+        let capabilities = ProviderCapabilities::builder()
+            .with_resource_type("User", vec![ScimOperation::Create, ScimOperation::Patch])
+            .with_schema("urn:ietf:params:scim:schemas:core:2.0:User")
+            .supports_filter_operators([FilterOperator::Equal, FilterOperator::Equal])
+            .with_bulk(50, 4096)
+            .with_pagination(10, 100)
+            .with_patch()
+            .with_sort()
+            .with_authentication_scheme(AuthenticationScheme::oauth2().as_primary())
+            .build()
+            .unwrap();
+        assert_eq!(
+            capabilities.filter_capabilities.supported_operators,
+            vec![FilterOperator::Equal]
+        );
+        let config = CapabilityDiscovery::generate_service_provider_config(&capabilities);
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["patch"], true);
+        assert_eq!(json["bulk"], true);
+        assert_eq!(json["bulk.maxOperations"], 50);
+        assert_eq!(json["bulk.maxPayloadSize"], 4096);
+        assert_eq!(json["filter"], true);
+        assert_eq!(json["sort"], true);
+        assert_eq!(json["changePassword"], false);
+        assert_eq!(json["authenticationSchemes"][0]["type"], "oauth2");
+        assert_eq!(
+            serde_json::from_value::<ServiceProviderConfig>(json).unwrap(),
+            config
+        );
+
+        assert!(
+            ProviderCapabilities::builder()
+                .with_pagination(50, 10)
+                .build()
+                .is_err()
+        );
+        assert!(
+            ProviderCapabilities::builder()
+                .with_bulk(0, 1)
+                .build()
+                .is_err()
+        );
+    }
 }
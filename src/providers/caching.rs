@@ -0,0 +1,641 @@
+//! Read-through caching decorator for resource providers.
+//!
+//! [`CachingProvider`] wraps any [`ResourceProvider`] and keeps recently read
+//! resources in a bounded LRU cache keyed by (tenant, resource type, id).
+//! Writes and deletes always go to the wrapped provider; successful writes
+//! refresh the cached entry and deletes or failed writes evict it.
+//!
+//! A read that misses is cached only if no write went through the cache while
+//! it was in flight, so a slow read cannot replace a newer written copy with
+//! the older one it fetched.
+//!
+//! Each entry stores the [`VersionedResource`] as read, including its version.
+//! A cached read can therefore be stale only in ways a conditional update
+//! detects: the wrapped provider still checks `expected_version`, and a
+//! failed conditional write evicts the entry so the next read is fresh.
+//! List and search results are not cached, but a result whose version differs
+//! from the cached one evicts it.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use scim_server::providers::{CacheConfig, CachingProvider, StandardResourceProvider};
+//! use scim_server::storage::InMemoryStorage;
+//! use std::time::Duration;
+//!
+//! let provider = CachingProvider::with_config(
+//!     StandardResourceProvider::new(InMemoryStorage::new()),
+//!     CacheConfig::new(10_000).with_ttl(Duration::from_secs(30)),
+//! );
+//! ```
+
+use crate::providers::ResourceProvider;
+use crate::providers::lru::LruCache;
+use crate::resource::{
+    ListQuery, RequestContext, version::RawVersion, versioned::VersionedResource,
+};
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Size and expiry settings for a [`CachingProvider`] or a
+/// [`CachingTenantResolver`](crate::multi_tenant::CachingTenantResolver).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// Maximum number of cached entries. Zero disables caching.
+    pub capacity: usize,
+    /// How long an entry is served before it is re-read. `None` keeps entries
+    /// until they are evicted or invalidated.
+    pub ttl: Option<Duration>,
+}
+
+impl CacheConfig {
+    /// Create a configuration with the given capacity and no TTL.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ttl: None,
+        }
+    }
+
+    /// Expire entries after `ttl`.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+}
+
+impl Default for CacheConfig {
+    /// 1024 entries with a 60 second TTL.
+    fn default() -> Self {
+        Self::new(1024).with_ttl(Duration::from_secs(60))
+    }
+}
+
+/// Cache key: tenant (`None` for single-tenant requests), resource type, id.
+type CacheKey = (Option<String>, String, String);
+
+/// Cached resources, and a count of the writes that changed them.
+#[derive(Default)]
+struct Cache {
+    entries: LruCache<CacheKey, VersionedResource>,
+    // Bumped by every write or eviction, so a read can tell whether the
+    // entries changed while it was in flight
+    generation: u64,
+}
+
+/// A [`ResourceProvider`] decorator that caches single-resource reads.
+///
+/// Cache hits still honour the request's read permission; requests without
+/// it are passed to the wrapped provider, which reports the error.
+pub struct CachingProvider<P> {
+    inner: P,
+    config: CacheConfig,
+    cache: Mutex<Cache>,
+}
+
+impl<P> CachingProvider<P> {
+    /// Wrap a provider with the default cache configuration.
+    pub fn new(inner: P) -> Self {
+        Self::with_config(inner, CacheConfig::default())
+    }
+
+    /// Wrap a provider with the given cache configuration.
+    pub fn with_config(inner: P, config: CacheConfig) -> Self {
+        Self {
+            inner,
+            config,
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    /// The wrapped provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// The cache configuration.
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Number of resources currently cached, including expired entries not
+    /// yet evicted.
+    pub fn cached_len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Version of the cached copy of a resource, if it is cached and fresh.
+    pub fn cached_version(
+        &self,
+        resource_type: &str,
+        id: &str,
+        context: &RequestContext,
+    ) -> Option<RawVersion> {
+        self.lock()
+            .entries
+            .get(&Self::key(resource_type, id, context))
+            .map(|resource| resource.version().clone())
+    }
+
+    /// Drop a single resource from the cache.
+    pub fn invalidate(&self, resource_type: &str, id: &str, context: &RequestContext) {
+        let mut cache = self.lock();
+        cache.entries.remove(&Self::key(resource_type, id, context));
+        cache.generation += 1;
+    }
+
+    /// Drop every cached resource.
+    pub fn invalidate_all(&self) {
+        let mut cache = self.lock();
+        cache.entries.clear();
+        cache.generation += 1;
+    }
+
+    fn key(resource_type: &str, id: &str, context: &RequestContext) -> CacheKey {
+        (
+            context.tenant_id().map(str::to_string),
+            resource_type.to_string(),
+            id.to_string(),
+        )
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cache> {
+        // The cache holds no invariants a panicking holder could break
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn lookup(
+        &self,
+        resource_type: &str,
+        id: &str,
+        context: &RequestContext,
+    ) -> Option<VersionedResource> {
+        if !context.can_perform_operation("read") {
+            return None;
+        }
+        self.lock()
+            .entries
+            .get(&Self::key(resource_type, id, context))
+    }
+
+    /// The current write generation, taken before reading from the wrapped
+    /// provider.
+    fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    /// Cache a resource written through this provider.
+    fn store(&self, resource_type: &str, resource: &VersionedResource, context: &RequestContext) {
+        let mut cache = self.lock();
+        cache.generation += 1;
+        self.insert(&mut cache, resource_type, resource, context);
+    }
+
+    /// Cache resources read from the wrapped provider, unless a write went
+    /// through the cache since `generation` was taken.
+    fn store_read<'a>(
+        &self,
+        resource_type: &str,
+        resources: impl IntoIterator<Item = &'a VersionedResource>,
+        generation: u64,
+        context: &RequestContext,
+    ) {
+        let mut cache = self.lock();
+        if cache.generation != generation {
+            return;
+        }
+        for resource in resources {
+            self.insert(&mut cache, resource_type, resource, context);
+        }
+    }
+
+    fn insert(
+        &self,
+        cache: &mut Cache,
+        resource_type: &str,
+        resource: &VersionedResource,
+        context: &RequestContext,
+    ) {
+        if let Some(id) = resource.resource().get_id() {
+            cache.entries.insert(
+                Self::key(resource_type, id, context),
+                resource.clone(),
+                self.config.ttl.map(|ttl| Instant::now() + ttl),
+                self.config.capacity,
+            );
+        }
+    }
+
+    /// Evict cached copies whose version differs from freshly read resources.
+    fn evict_stale(
+        &self,
+        resource_type: &str,
+        resources: &[VersionedResource],
+        context: &RequestContext,
+    ) {
+        let mut cache = self.lock();
+        for resource in resources {
+            let Some(id) = resource.resource().get_id() else {
+                continue;
+            };
+            let key = Self::key(resource_type, id, context);
+            if cache
+                .entries
+                .peek(&key)
+                .is_some_and(|cached| cached.version() != resource.version())
+            {
+                cache.entries.remove(&key);
+                cache.generation += 1;
+            }
+        }
+    }
+
+    /// Refresh the cache from a write's outcome.
+    fn record_write<E>(
+        &self,
+        resource_type: &str,
+        id: &str,
+        result: &Result<VersionedResource, E>,
+        context: &RequestContext,
+    ) {
+        match result {
+            Ok(resource) => self.store(resource_type, resource, context),
+            Err(_) => self.invalidate(resource_type, id, context),
+        }
+    }
+}
+
+impl<P: ResourceProvider + Sync> ResourceProvider for CachingProvider<P> {
+    type Error = P::Error;
+
+    async fn create_resource(
+        &self,
+        resource_type: &str,
+        data: Value,
+        context: &RequestContext,
+    ) -> Result<VersionedResource, Self::Error> {
+        let result = self
+            .inner
+            .create_resource(resource_type, data, context)
+            .await;
+        if let Ok(resource) = &result {
+            self.store(resource_type, resource, context);
+        }
+        result
+    }
+
+    async fn get_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        context: &RequestContext,
+    ) -> Result<Option<VersionedResource>, Self::Error> {
+        if let Some(resource) = self.lookup(resource_type, id, context) {
+            return Ok(Some(resource));
+        }
+
+        let generation = self.generation();
+        let result = self.inner.get_resource(resource_type, id, context).await?;
+        match &result {
+            Some(resource) => self.store_read(resource_type, [resource], generation, context),
+            None => self.invalidate(resource_type, id, context),
+        }
+        Ok(result)
+    }
+
+    async fn get_resources(
+        &self,
+        resource_type: &str,
+        ids: &[String],
+        context: &RequestContext,
+    ) -> Result<Vec<Option<VersionedResource>>, Self::Error> {
+        let mut resources: Vec<_> = ids
+            .iter()
+            .map(|id| self.lookup(resource_type, id, context))
+            .collect();
+        let missing: Vec<String> = ids
+            .iter()
+            .zip(&resources)
+            .filter(|(_, cached)| cached.is_none())
+            .map(|(id, _)| id.clone())
+            .collect();
+        if missing.is_empty() {
+            return Ok(resources);
+        }
+
+        let generation = self.generation();
+        let fetched = self
+            .inner
+            .get_resources(resource_type, &missing, context)
+            .await?;
+        self.store_read(resource_type, fetched.iter().flatten(), generation, context);
+        let mut fetched = fetched.into_iter();
+        for resource in resources.iter_mut().filter(|cached| cached.is_none()) {
+            *resource = fetched.next().flatten();
+        }
+        Ok(resources)
+    }
+
+    async fn update_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        data: Value,
+        expected_version: Option<&RawVersion>,
+        context: &RequestContext,
+    ) -> Result<VersionedResource, Self::Error> {
+        let result = self
+            .inner
+            .update_resource(resource_type, id, data, expected_version, context)
+            .await;
+        self.record_write(resource_type, id, &result, context);
+        result
+    }
+
+    async fn delete_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        expected_version: Option<&RawVersion>,
+        context: &RequestContext,
+    ) -> Result<(), Self::Error> {
+        let result = self
+            .inner
+            .delete_resource(resource_type, id, expected_version, context)
+            .await;
+        self.invalidate(resource_type, id, context);
+        result
+    }
+
+    async fn list_resources(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> Result<Vec<VersionedResource>, Self::Error> {
+        let resources = self
+            .inner
+            .list_resources(resource_type, query, context)
+            .await?;
+        self.evict_stale(resource_type, &resources, context);
+        Ok(resources)
+    }
+
+    fn default_tenant_id(&self) -> &str {
+        self.inner.default_tenant_id()
+    }
+
+    fn set_default_tenant_id(&mut self, tenant_id: &str) {
+        self.inner.set_default_tenant_id(tenant_id)
+    }
+
+    async fn count_resources(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> Result<usize, Self::Error> {
+        self.inner
+            .count_resources(resource_type, query, context)
+            .await
+    }
+
+    async fn list_ids(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> Result<Vec<String>, Self::Error> {
+        self.inner.list_ids(resource_type, query, context).await
+    }
+
+    async fn find_resources_by_attribute(
+        &self,
+        resource_type: &str,
+        attribute_name: &str,
+        attribute_value: &str,
+        context: &RequestContext,
+    ) -> Result<Vec<VersionedResource>, Self::Error> {
+        let resources = self
+            .inner
+            .find_resources_by_attribute(resource_type, attribute_name, attribute_value, context)
+            .await?;
+        self.evict_stale(resource_type, &resources, context);
+        Ok(resources)
+    }
+
+    async fn patch_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        patch_request: &Value,
+        expected_version: Option<&RawVersion>,
+        context: &RequestContext,
+    ) -> Result<VersionedResource, Self::Error> {
+        let result = self
+            .inner
+            .patch_resource(resource_type, id, patch_request, expected_version, context)
+            .await;
+        self.record_write(resource_type, id, &result, context);
+        result
+    }
+
+    async fn patch_resource_checked<E: Send>(
+        &self,
+        resource_type: &str,
+        id: &str,
+        patch_request: &Value,
+        expected_version: Option<&RawVersion>,
+        check: impl Fn(&Value, &mut Value) -> Result<(), E> + Send + Sync,
+        context: &RequestContext,
+    ) -> Result<Result<VersionedResource, E>, Self::Error> {
+        let result = self
+            .inner
+            .patch_resource_checked(
+                resource_type,
+                id,
+                patch_request,
+                expected_version,
+                check,
+                context,
+            )
+            .await;
+        match &result {
+            Ok(Ok(resource)) => self.store(resource_type, resource, context),
+            Ok(Err(_)) => {}
+            Err(_) => self.invalidate(resource_type, id, context),
+        }
+        result
+    }
+
+    async fn resource_exists(
+        &self,
+        resource_type: &str,
+        id: &str,
+        context: &RequestContext,
+    ) -> Result<bool, Self::Error> {
+        if self.lookup(resource_type, id, context).is_some() {
+            return Ok(true);
+        }
+        self.inner.resource_exists(resource_type, id, context).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::StandardResourceProvider;
+    use crate::storage::InMemoryStorage;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_reads_are_cached_and_refreshed_by_writes() {
+        let storage = InMemoryStorage::new();
+        let cached = CachingProvider::new(StandardResourceProvider::new(storage.clone()));
+        let other_writer = StandardResourceProvider::new(storage);
+        let context = RequestContext::with_generated_id();
+
+        let created = cached
+            .create_resource("User", json!({"userName": "alice"}), &context)
+            .await
+            .unwrap();
+        let id = created.resource().get_id().unwrap().to_string();
+        assert_eq!(
+            cached.cached_version("User", &id, &context).as_ref(),
+            Some(created.version())
+        );
+
+        // A write that bypasses the cache is not seen by cached reads...
+        let external = other_writer
+            .update_resource("User", &id, json!({"userName": "alice2"}), None, &context)
+            .await
+            .unwrap();
+        let stale = cached
+            .get_resource("User", &id, &context)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stale.resource().get_username(), Some("alice"));
+
+        // ...but the cached version exposes it: a conditional update fails and evicts
+        let conflict = cached
+            .update_resource(
+                "User",
+                &id,
+                json!({"userName": "alice3"}),
+                Some(stale.version()),
+                &context,
+            )
+            .await;
+        assert!(conflict.is_err());
+        assert!(cached.cached_version("User", &id, &context).is_none());
+
+        let fresh = cached
+            .get_resource("User", &id, &context)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fresh.version(), external.version());
+
+        cached
+            .delete_resource("User", &id, None, &context)
+            .await
+            .unwrap();
+        assert!(
+            cached
+                .get_resource("User", &id, &context)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_overlapping_a_write_is_not_cached() {
+        let cached = CachingProvider::new(StandardResourceProvider::new(InMemoryStorage::new()));
+        let context = RequestContext::with_generated_id();
+        let created = cached
+            .create_resource("User", json!({"userName": "alice"}), &context)
+            .await
+            .unwrap();
+        let id = created.resource().get_id().unwrap().to_string();
+
+        // A read that fetched the created copy finishes after an update
+        cached.invalidate("User", &id, &context);
+        let generation = cached.generation();
+        let updated = cached
+            .update_resource("User", &id, json!({"userName": "alice2"}), None, &context)
+            .await
+            .unwrap();
+        cached.store_read("User", [&created], generation, &context);
+        assert_eq!(
+            cached.cached_version("User", &id, &context).as_ref(),
+            Some(updated.version())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_resources_reads_through_the_cache() {
+        let cached = CachingProvider::new(StandardResourceProvider::new(InMemoryStorage::new()));
+        let context = RequestContext::with_generated_id();
+        let mut ids = Vec::new();
+        for name in ["a", "b"] {
+            let created = cached
+                .create_resource("User", json!({"userName": name}), &context)
+                .await
+                .unwrap();
+            ids.push(created.resource().get_id().unwrap().to_string());
+        }
+        cached.invalidate("User", &ids[1], &context);
+        ids.insert(1, "missing".to_string());
+
+        let resources = cached.get_resources("User", &ids, &context).await.unwrap();
+        let names: Vec<_> = resources
+            .iter()
+            .map(|resource| resource.as_ref().and_then(|r| r.resource().get_username()))
+            .collect();
+        assert_eq!(names, [Some("a"), None, Some("b")]);
+        assert!(cached.cached_version("User", &ids[2], &context).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_capacity_ttl_and_tenant_keys() {
+        let cached = CachingProvider::with_config(
+            StandardResourceProvider::new(InMemoryStorage::new()),
+            CacheConfig::new(2),
+        );
+        let context = RequestContext::with_generated_id();
+
+        let mut ids = Vec::new();
+        for name in ["a", "b", "c"] {
+            let created = cached
+                .create_resource("User", json!({"userName": name}), &context)
+                .await
+                .unwrap();
+            ids.push(created.resource().get_id().unwrap().to_string());
+        }
+        assert_eq!(cached.cached_len(), 2);
+        assert!(cached.cached_version("User", &ids[0], &context).is_none());
+
+        // Same id under another tenant is a different entry
+        let tenant = RequestContext::with_tenant_generated_id(crate::resource::TenantContext::new(
+            "other".to_string(),
+            "client".to_string(),
+        ));
+        assert!(cached.cached_version("User", &ids[2], &tenant).is_none());
+
+        let expiring = CachingProvider::with_config(
+            StandardResourceProvider::new(InMemoryStorage::new()),
+            CacheConfig::new(10).with_ttl(Duration::ZERO),
+        );
+        let created = expiring
+            .create_resource("User", json!({"userName": "d"}), &context)
+            .await
+            .unwrap();
+        let id = created.resource().get_id().unwrap();
+        assert!(expiring.cached_version("User", id, &context).is_none());
+    }
+}
@@ -49,6 +49,8 @@ pub enum ProviderError {
         value: String,
         /// The tenant ID where the duplicate was found
         tenant_id: String,
+        /// ID of the existing resource holding the value, if a single one is known
+        existing_id: Option<String>,
     },
 
     #[error("Invalid resource data: {message}")]
@@ -89,6 +91,45 @@ pub enum ProviderError {
         id: String,
     },
 
+    /// The resource existed but has been soft-deleted.
+    ///
+    /// Providers that keep tombstones for deleted resources return this from
+    /// get, update and delete so clients can tell it apart from a resource
+    /// that never existed.
+    #[error("Resource deleted: {resource_type} with id '{id}'")]
+    ResourceDeleted {
+        /// The type of the deleted resource
+        resource_type: String,
+        /// The ID of the deleted resource
+        id: String,
+    },
+
+    /// The storage backend has no room for a new resource.
+    #[error("Capacity exceeded: {message}")]
+    CapacityExceeded {
+        /// Description of the exhausted capacity
+        message: String,
+        /// Maximum number of resources, if known
+        limit: Option<usize>,
+    },
+
+    /// The write conflicts with the stored state, e.g. a concurrent
+    /// modification detected by the storage backend.
+    #[error("Conflict: {message}")]
+    Conflict {
+        /// Description of the conflict
+        message: String,
+    },
+
+    /// The storage backend is temporarily unavailable.
+    #[error("Service unavailable: {message}")]
+    Unavailable {
+        /// Description of the outage
+        message: String,
+        /// How long to wait before retrying, if known
+        retry_after: Option<std::time::Duration>,
+    },
+
     #[error("Precondition failed: {message}")]
     PreconditionFailed {
         /// Description of the precondition failure
@@ -113,6 +154,14 @@ pub enum ProviderError {
         message: String,
     },
 
+    #[error("Permission denied: operation '{operation}' not permitted for tenant '{tenant_id}'")]
+    PermissionDenied {
+        /// The operation that was attempted (e.g., "create", "read")
+        operation: String,
+        /// The tenant whose permissions rejected the operation
+        tenant_id: String,
+    },
+
     #[error("Version conflict: {conflict}")]
     VersionConflict {
         /// Details of the version conflict
@@ -153,8 +153,9 @@ pub trait ScimMetadataManager: ResourceProvider {
     /// Compute a version hash for a resource.
     ///
     /// Generates a deterministic version identifier based on the resource content
-    /// using SHA-256 hashing. The version changes whenever the resource content
-    /// changes, enabling optimistic concurrency control.
+    /// using SHA-256 hashing, as described by [`Resource::content_version`]. The
+    /// version changes whenever the resource content changes, including
+    /// extension attributes, enabling optimistic concurrency control.
     ///
     /// # Arguments
     /// * `resource` - The resource to compute version for
@@ -162,11 +163,8 @@ pub trait ScimMetadataManager: ResourceProvider {
     /// # Returns
     /// A `RawVersion` containing the computed hash
     fn compute_resource_version(&self, resource: &Resource) -> RawVersion {
-        match resource.to_json() {
-            Ok(resource_json) => {
-                let content = resource_json.to_string();
-                RawVersion::from_content(content.as_bytes())
-            }
+        match resource.content_version() {
+            Ok(version) => version,
             Err(_) => {
                 // Fallback to a timestamp-based version if serialization fails
                 let timestamp = Utc::now().timestamp_millis();
@@ -37,7 +37,12 @@
 //! // - Value filtering and selection
 //! ```
 
-use crate::providers::ResourceProvider;
+use crate::providers::{ProviderError, ResourceProvider};
+use crate::resource::{
+    RequestContext,
+    version::{RawVersion, VersionConflict},
+    versioned::VersionedResource,
+};
 use serde_json::{Value, json};
 
 /// Trait providing RFC 7644 compliant PATCH operations for SCIM resources.
@@ -78,12 +83,12 @@ pub trait ScimPatchOperations: ResourceProvider {
         let value = operation.get("value");
 
         // Check if the operation targets a readonly attribute
-        if let Some(path_str) = path {
-            if self.is_readonly_attribute(path_str) {
-                return Err(
-                    self.patch_error(&format!("Cannot modify readonly attribute: {}", path_str))
-                );
-            }
+        if let Some(path_str) = path
+            && self.is_readonly_attribute(path_str)
+        {
+            return Err(
+                self.patch_error(&format!("Cannot modify readonly attribute: {}", path_str))
+            );
         }
 
         match op.to_lowercase().as_str() {
@@ -117,16 +122,7 @@ pub trait ScimPatchOperations: ResourceProvider {
             Some(path_str) => {
                 self.set_value_at_path(resource_data, path_str, value.clone())?;
             }
-            None => {
-                // No path means add to root - merge objects
-                if let (Some(current_obj), Some(value_obj)) =
-                    (resource_data.as_object_mut(), value.as_object())
-                {
-                    for (key, val) in value_obj {
-                        current_obj.insert(key.clone(), val.clone());
-                    }
-                }
-            }
+            None => merge_into_root(resource_data, value),
         }
         Ok(())
     }
@@ -175,16 +171,7 @@ pub trait ScimPatchOperations: ResourceProvider {
             Some(path_str) => {
                 self.set_value_at_path(resource_data, path_str, value.clone())?;
             }
-            None => {
-                // No path means replace entire resource
-                if let Some(value_obj) = value.as_object() {
-                    if let Some(current_obj) = resource_data.as_object_mut() {
-                        for (key, val) in value_obj {
-                            current_obj.insert(key.clone(), val.clone());
-                        }
-                    }
-                }
-            }
+            None => merge_into_root(resource_data, value),
         }
         Ok(())
     }
@@ -195,6 +182,8 @@ pub trait ScimPatchOperations: ResourceProvider {
     /// - Simple attributes (e.g., "userName")
     /// - Complex attributes (e.g., "name.givenName")
     /// - Multi-valued attributes (e.g., "emails[type eq \"work\"].value")
+    /// - Schema URN prefixed attributes (e.g., "urn:...:enterprise:2.0:User:department"),
+    ///   set under the extension URN unless the URN is the resource's base schema
     ///
     /// # Arguments
     /// * `data` - The JSON object to modify
@@ -206,47 +195,22 @@ pub trait ScimPatchOperations: ResourceProvider {
         path: &str,
         value: Value,
     ) -> Result<(), Self::Error> {
-        if !self.is_valid_scim_path(path) {
-            return Err(self.patch_error(&format!("Invalid SCIM path: {}", path)));
-        }
-
-        // Handle simple path (no dots)
-        if !path.contains('.') {
-            if let Some(obj) = data.as_object_mut() {
-                obj.insert(path.to_string(), value);
-            }
-            return Ok(());
-        }
-
-        // Handle complex path - use pointer navigation to avoid borrow checker issues
-        let parts: Vec<&str> = path.split('.').collect();
-
-        // Build the path string for JSON pointer
-        let mut pointer_path = String::new();
-        for part in &parts {
-            pointer_path.push('/');
-            pointer_path.push_str(part);
-        }
-
-        // Navigate to parent and ensure it exists
-        let parent_parts = &parts[..parts.len() - 1];
-        let mut current = data;
-
-        for part in parent_parts {
-            match current {
-                Value::Object(obj) => {
-                    let entry = obj.entry(part.to_string()).or_insert_with(|| json!({}));
-                    current = entry;
-                }
-                _ => return Ok(()), // Can't navigate further
+        if let Some((urn, attribute)) = split_schema_urn(path) {
+            if is_base_schema(data, urn) {
+                return self.set_value_at_path(data, attribute, value);
             }
+            let Some(obj) = data.as_object_mut() else {
+                return Ok(());
+            };
+            let extension = obj.entry(urn.to_string()).or_insert_with(|| json!({}));
+            return self.set_value_at_path(extension, attribute, value);
         }
 
-        // Set the final value
-        if let Some(obj) = current.as_object_mut() {
-            obj.insert(parts.last().unwrap().to_string(), value);
+        if !self.is_valid_scim_path(path) {
+            return Err(self.patch_error(&format!("Invalid SCIM path: {}", path)));
         }
 
+        insert_at_path(data, path, value);
         Ok(())
     }
 
@@ -259,6 +223,16 @@ pub trait ScimPatchOperations: ResourceProvider {
     /// * `data` - The JSON object to modify
     /// * `path` - The SCIM attribute path to remove
     fn remove_value_at_path(&self, data: &mut Value, path: &str) -> Result<(), Self::Error> {
+        if let Some((urn, attribute)) = split_schema_urn(path) {
+            if is_base_schema(data, urn) {
+                return self.remove_value_at_path(data, attribute);
+            }
+            return match data.get_mut(urn) {
+                Some(extension) => self.remove_value_at_path(extension, attribute),
+                None => Ok(()),
+            };
+        }
+
         if !self.is_valid_scim_path(path) {
             return Err(self.patch_error(&format!("Invalid SCIM path: {}", path)));
         }
@@ -271,7 +245,6 @@ pub trait ScimPatchOperations: ResourceProvider {
             return Ok(());
         }
 
-        // Handle complex path by rebuilding the structure without the target
         let parts: Vec<&str> = path.split('.').collect();
         self.remove_nested_value(data, &parts, 0)
     }
@@ -283,83 +256,22 @@ pub trait ScimPatchOperations: ResourceProvider {
         parts: &[&str],
         depth: usize,
     ) -> Result<(), Self::Error> {
-        if depth >= parts.len() {
-            return Ok(());
-        }
-
-        let part = parts[depth];
-
-        if depth == parts.len() - 1 {
-            // We're at the final part, remove it
-            if let Some(obj) = current.as_object_mut() {
-                obj.remove(part);
-            }
-        } else {
-            // Navigate deeper
-            if let Some(obj) = current.as_object_mut() {
-                if let Some(child) = obj.get_mut(part) {
-                    self.remove_nested_value(child, parts, depth + 1)?;
-                }
-            }
-        }
-
+        remove_nested_value(current, parts.get(depth..).unwrap_or_default());
         Ok(())
     }
 
     /// Check if an attribute path refers to a readonly attribute.
     ///
-    /// Default implementation covers RFC 7644 readonly attributes:
-    /// - `id` - Resource identifier
-    /// - `meta.created` - Creation timestamp
-    /// - `meta.resourceType` - Resource type
-    /// - `meta.location` - Resource location
-    ///
-    /// Override this method to add custom readonly attributes.
+    /// Default implementation is [`is_readonly_attribute`].
     fn is_readonly_attribute(&self, path: &str) -> bool {
-        match path.to_lowercase().as_str() {
-            // Core readonly attributes
-            "id" => true,
-            "meta.created" => true,
-            "meta.resourcetype" => true,
-            "meta.location" => true,
-            // Pattern matching for meta attributes
-            path if path.starts_with("meta.")
-                && (path.ends_with(".created")
-                    || path.ends_with(".resourcetype")
-                    || path.ends_with(".location")) =>
-            {
-                true
-            }
-            _ => false,
-        }
+        is_readonly_attribute(path)
     }
 
     /// Validate if a path represents a valid SCIM attribute.
     ///
-    /// Default implementation provides basic validation:
-    /// - Non-empty paths
-    /// - Valid attribute name characters
-    /// - Proper dot notation for complex attributes
-    ///
-    /// Override for more sophisticated validation.
+    /// Default implementation is [`is_valid_scim_path`].
     fn is_valid_scim_path(&self, path: &str) -> bool {
-        if path.is_empty() {
-            return false;
-        }
-
-        // Handle schema URN prefixed paths
-        let actual_path = if path.contains(':') && path.contains("urn:ietf:params:scim:schemas:") {
-            // Extract the attribute name after the schema URN
-            path.split(':').last().unwrap_or(path)
-        } else {
-            path
-        };
-
-        // Basic validation - can be enhanced by implementers
-        !actual_path.is_empty()
-            && actual_path
-                .chars()
-                .all(|c| c.is_alphanumeric() || c == '.' || c == '_')
+        is_valid_scim_path(path)
     }
 
     /// Create a PATCH-specific error.
@@ -380,3 +292,325 @@ where
         Self::Error::from(message.to_string())
     }
 }
+
+/// Apply the operations of a PATCH request to `resource_data`.
+///
+/// The same RFC 7644 semantics as [`ScimPatchOperations`], without a
+/// provider, e.g. to preview the outcome of a patch. Operations are applied
+/// in order; on error `resource_data` may hold the effect of the earlier
+/// ones.
+pub fn apply_patch_request(resource_data: &mut Value, patch_request: &Value) -> Result<(), String> {
+    let operations = patch_request
+        .get("Operations")
+        .and_then(Value::as_array)
+        .ok_or("PATCH request must contain Operations array")?;
+    for operation in operations {
+        apply_patch_operation(resource_data, operation)?;
+    }
+    Ok(())
+}
+
+/// PATCH a resource by reading it, applying the operations with
+/// [`apply_patch_request`] and writing it back with
+/// [`update_resource`](ResourceProvider::update_resource).
+///
+/// The default [`ResourceProvider::patch_resource`], for providers without a
+/// native PATCH. The write is
+/// conditional on the version that was read, so a concurrent change makes the
+/// patch fail rather than be lost, provided `update_resource` honours its
+/// expected version. A missing resource is reported as
+/// [`ProviderError::NotFound`] and a stale `expected_version` as
+/// [`ProviderError::VersionConflict`].
+pub async fn patch_via_update<P>(
+    provider: &P,
+    resource_type: &str,
+    id: &str,
+    patch_request: &Value,
+    expected_version: Option<&RawVersion>,
+    context: &RequestContext,
+) -> Result<VersionedResource, P::Error>
+where
+    P: ResourceProvider + Sync + ?Sized,
+{
+    let current = provider
+        .get_resource(resource_type, id, context)
+        .await?
+        .ok_or_else(|| ProviderError::NotFound {
+            resource_type: resource_type.to_string(),
+            id: id.to_string(),
+        })?;
+    if let Some(expected_version) = expected_version
+        && current.version() != expected_version
+    {
+        let conflict =
+            VersionConflict::standard_message(expected_version.clone(), current.version().clone());
+        return Err(ProviderError::from(conflict).into());
+    }
+
+    let mut resource_data = current
+        .resource()
+        .to_json()
+        .map_err(|e| ProviderError::Internal {
+            message: format!("Failed to serialize resource for patching: {}", e),
+        })?;
+    apply_patch_request(&mut resource_data, patch_request)
+        .map_err(|message| ProviderError::InvalidInput { message })?;
+
+    provider
+        .update_resource(
+            resource_type,
+            id,
+            resource_data,
+            Some(current.version()),
+            context,
+        )
+        .await
+}
+
+/// Apply a single PATCH operation to resource data.
+///
+/// Validates the operation structure, rejects readonly attributes, and
+/// applies an `add`, `remove` or `replace`.
+pub fn apply_patch_operation(resource_data: &mut Value, operation: &Value) -> Result<(), String> {
+    let op = operation
+        .get("op")
+        .and_then(|v| v.as_str())
+        .ok_or("PATCH operation must have 'op' field")?;
+
+    let path = operation.get("path").and_then(|v| v.as_str());
+    let value = operation.get("value");
+
+    // Check if the operation targets a readonly attribute
+    if let Some(path_str) = path
+        && is_readonly_attribute(path_str)
+    {
+        return Err(format!("Cannot modify readonly attribute: {}", path_str));
+    }
+
+    match op.to_lowercase().as_str() {
+        "add" => apply_add_operation(resource_data, path, value),
+        "remove" => apply_remove_operation(resource_data, path),
+        "replace" => apply_replace_operation(resource_data, path, value),
+        _ => Err(format!("Unsupported PATCH operation: {}", op)),
+    }
+}
+
+fn apply_add_operation(
+    resource_data: &mut Value,
+    path: Option<&str>,
+    value: Option<&Value>,
+) -> Result<(), String> {
+    let value = value.ok_or("ADD operation requires a value")?;
+
+    match path {
+        Some(path_str) => {
+            set_value_at_path(resource_data, path_str, value.clone())?;
+        }
+        None => merge_into_root(resource_data, value),
+    }
+    Ok(())
+}
+
+fn apply_remove_operation(resource_data: &mut Value, path: Option<&str>) -> Result<(), String> {
+    if let Some(path_str) = path {
+        remove_value_at_path(resource_data, path_str)?;
+    }
+    Ok(())
+}
+
+fn apply_replace_operation(
+    resource_data: &mut Value,
+    path: Option<&str>,
+    value: Option<&Value>,
+) -> Result<(), String> {
+    let value = value.ok_or("REPLACE operation requires a value")?;
+
+    match path {
+        Some(path_str) => {
+            set_value_at_path(resource_data, path_str, value.clone())?;
+        }
+        None => merge_into_root(resource_data, value),
+    }
+    Ok(())
+}
+
+/// Merge the members of a path-less `add` or `replace` value into the
+/// resource root.
+fn merge_into_root(resource_data: &mut Value, value: &Value) {
+    if let (Some(current_obj), Some(value_obj)) = (resource_data.as_object_mut(), value.as_object())
+    {
+        for (key, val) in value_obj {
+            current_obj.insert(key.clone(), val.clone());
+        }
+    }
+}
+
+fn set_value_at_path(data: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    if let Some((urn, attribute)) = split_schema_urn(path) {
+        if is_base_schema(data, urn) {
+            return set_value_at_path(data, attribute, value);
+        }
+        let Some(obj) = data.as_object_mut() else {
+            return Ok(());
+        };
+        let extension = obj.entry(urn.to_string()).or_insert_with(|| json!({}));
+        return set_value_at_path(extension, attribute, value);
+    }
+
+    if !is_valid_scim_path(path) {
+        return Err(format!("Invalid SCIM path: {}", path));
+    }
+
+    insert_at_path(data, path, value);
+    Ok(())
+}
+
+/// Insert `value` at a validated, dot separated attribute path, creating
+/// missing parent objects.
+fn insert_at_path(data: &mut Value, path: &str, value: Value) {
+    // Handle simple path (no dots)
+    if !path.contains('.') {
+        if let Some(obj) = data.as_object_mut() {
+            obj.insert(path.to_string(), value);
+        }
+        return;
+    }
+
+    // Navigate to parent and ensure it exists
+    let parts: Vec<&str> = path.split('.').collect();
+    let parent_parts = &parts[..parts.len() - 1];
+    let mut current = data;
+
+    for part in parent_parts {
+        match current {
+            Value::Object(obj) => {
+                let entry = obj.entry(part.to_string()).or_insert_with(|| json!({}));
+                current = entry;
+            }
+            _ => return, // Can't navigate further
+        }
+    }
+
+    // Set the final value
+    if let Some(obj) = current.as_object_mut() {
+        obj.insert(parts.last().unwrap().to_string(), value);
+    }
+}
+
+fn remove_value_at_path(data: &mut Value, path: &str) -> Result<(), String> {
+    if let Some((urn, attribute)) = split_schema_urn(path) {
+        if is_base_schema(data, urn) {
+            return remove_value_at_path(data, attribute);
+        }
+        return match data.get_mut(urn) {
+            Some(extension) => remove_value_at_path(extension, attribute),
+            None => Ok(()),
+        };
+    }
+
+    if !is_valid_scim_path(path) {
+        return Err(format!("Invalid SCIM path: {}", path));
+    }
+
+    // Handle simple path
+    if !path.contains('.') {
+        if let Some(obj) = data.as_object_mut() {
+            obj.remove(path);
+        }
+        return Ok(());
+    }
+
+    // Handle complex path by rebuilding the structure without the target
+    let parts: Vec<&str> = path.split('.').collect();
+    remove_nested_value(data, &parts);
+    Ok(())
+}
+
+/// Remove the value at `parts` below `current`, if present.
+fn remove_nested_value(current: &mut Value, parts: &[&str]) {
+    let Some((part, rest)) = parts.split_first() else {
+        return;
+    };
+    let Some(obj) = current.as_object_mut() else {
+        return;
+    };
+    if rest.is_empty() {
+        obj.remove(*part);
+    } else if let Some(child) = obj.get_mut(*part) {
+        remove_nested_value(child, rest);
+    }
+}
+
+/// Check if an attribute path refers to a readonly attribute.
+///
+/// Covers the RFC 7644 readonly attributes:
+/// - `id` - Resource identifier
+/// - `meta.created` - Creation timestamp
+/// - `meta.resourceType` - Resource type
+/// - `meta.location` - Resource location
+pub fn is_readonly_attribute(path: &str) -> bool {
+    match path.to_lowercase().as_str() {
+        // Core readonly attributes
+        "id" => true,
+        "meta.created" => true,
+        "meta.resourcetype" => true,
+        "meta.location" => true,
+        // Pattern matching for meta attributes
+        path if path.starts_with("meta.")
+            && (path.ends_with(".created")
+                || path.ends_with(".resourcetype")
+                || path.ends_with(".location")) =>
+        {
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Validate if a path represents a valid SCIM attribute.
+///
+/// Provides basic validation:
+/// - Non-empty paths
+/// - Valid attribute name characters
+/// - Proper dot notation for complex attributes
+pub fn is_valid_scim_path(path: &str) -> bool {
+    if path.is_empty() {
+        return false;
+    }
+
+    // Handle schema URN prefixed paths
+    let actual_path = if path.contains(':') && path.contains("urn:ietf:params:scim:schemas:") {
+        // Extract the attribute name after the schema URN
+        path.split(':').next_back().unwrap_or(path)
+    } else {
+        path
+    };
+
+    // Basic validation - can be enhanced by implementers
+    !actual_path.is_empty()
+        && actual_path
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '.' || c == '_')
+}
+
+/// Split a schema URN prefixed path into the URN and the attribute path.
+///
+/// The URN ends at the last `:` before any value filter, so a filter value
+/// containing `:` does not move the split.
+fn split_schema_urn(path: &str) -> Option<(&str, &str)> {
+    if !path.get(..4)?.eq_ignore_ascii_case("urn:") {
+        return None;
+    }
+    let head = path.split('[').next().unwrap_or(path);
+    let split = head.rfind(':')?;
+    Some((&path[..split], &path[split + 1..]))
+}
+
+/// Whether `urn` is the first, base schema listed in the resource's `schemas`.
+fn is_base_schema(data: &Value, urn: &str) -> bool {
+    data.get("schemas")
+        .and_then(Value::as_array)
+        .and_then(|schemas| schemas.first())
+        .and_then(Value::as_str)
+        .is_some_and(|base| base.eq_ignore_ascii_case(urn))
+}
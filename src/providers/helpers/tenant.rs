@@ -9,7 +9,7 @@
 //! This implementation supports common multi-tenant patterns:
 //! - Single-tenant mode (no tenant context required)
 //! - Multi-tenant mode with explicit tenant identification
-//! - Tenant isolation with fallback to "default" tenant
+//! - Tenant isolation with fallback to the provider's default tenant
 //! - Context validation and extraction
 //!
 //! # Usage
@@ -40,7 +40,9 @@ pub trait MultiTenantProvider: ResourceProvider {
     ///
     /// Resolves the tenant ID from the request context using standard patterns:
     /// - If context has a tenant ID, use it
-    /// - If no tenant ID, fall back to "default" for single-tenant operations
+    /// - If no tenant ID, fall back to the provider's
+    ///   [`default_tenant_id`](ResourceProvider::default_tenant_id) for
+    ///   single-tenant operations
     /// - Ensures consistent tenant identification across operations
     ///
     /// # Arguments
@@ -61,7 +63,10 @@ pub trait MultiTenantProvider: ResourceProvider {
     /// // MultiTenantProvider.effective_tenant_id(&multi_context) returns: "acme-corp"
     /// ```
     fn effective_tenant_id(&self, context: &RequestContext) -> String {
-        context.tenant_id().unwrap_or("default").to_string()
+        context
+            .tenant_id()
+            .unwrap_or(self.default_tenant_id())
+            .to_string()
     }
 
     /// Create a tenant-scoped storage key.
@@ -198,7 +203,7 @@ pub trait MultiTenantProvider: ResourceProvider {
     /// An error message with tenant context
     fn tenant_error_message(&self, context: &RequestContext, base_message: &str) -> String {
         let tenant_id = self.effective_tenant_id(context);
-        if tenant_id == "default" {
+        if tenant_id == self.default_tenant_id() {
             base_message.to_string()
         } else {
             format!("[Tenant: {}] {}", tenant_id, base_message)
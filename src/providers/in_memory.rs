@@ -0,0 +1,224 @@
+//! Compatibility shim for the removed `InMemoryProvider`.
+//!
+//! `InMemoryProvider` was removed in v0.4.0 in favour of
+//! [`StandardResourceProvider`] over [`InMemoryStorage`]. This deprecated
+//! wrapper lets code written against the old API keep compiling, with a
+//! warning pointing at the replacement, while it migrates.
+//!
+//! It is a newtype rather than a type alias because an alias could not offer
+//! the old argument-less `new()` next to `StandardResourceProvider::new`.
+//! Every [`ResourceProvider`] method delegates to the wrapped provider, and
+//! its inherent methods are reachable through `Deref`, so it behaves exactly
+//! like the standard provider.
+//!
+//! # Migration
+//!
+//! ```rust
+//! use scim_server::providers::StandardResourceProvider;
+//! use scim_server::storage::InMemoryStorage;
+//!
+//! // Before: let provider = InMemoryProvider::new();
+//! let provider = StandardResourceProvider::new(InMemoryStorage::new());
+//! ```
+
+#![allow(deprecated)]
+
+use crate::providers::{ProviderError, ResourceProvider, StandardResourceProvider};
+use crate::resource::{
+    ListQuery, RequestContext, version::RawVersion, versioned::VersionedResource,
+};
+use crate::storage::InMemoryStorage;
+use serde_json::Value;
+use std::ops::{Deref, DerefMut};
+
+/// In-memory resource provider from before v0.4.0.
+#[deprecated(
+    since = "0.5.4",
+    note = "use `StandardResourceProvider::new(InMemoryStorage::new())` instead"
+)]
+#[derive(Clone)]
+pub struct InMemoryProvider(StandardResourceProvider<InMemoryStorage>);
+
+impl InMemoryProvider {
+    /// Create a provider backed by a new, empty [`InMemoryStorage`].
+    pub fn new() -> Self {
+        Self(StandardResourceProvider::new(InMemoryStorage::new()))
+    }
+
+    /// Unwrap the standard provider this shim delegates to.
+    pub fn into_inner(self) -> StandardResourceProvider<InMemoryStorage> {
+        self.0
+    }
+}
+
+impl Default for InMemoryProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<InMemoryProvider> for StandardResourceProvider<InMemoryStorage> {
+    fn from(provider: InMemoryProvider) -> Self {
+        provider.0
+    }
+}
+
+impl Deref for InMemoryProvider {
+    type Target = StandardResourceProvider<InMemoryStorage>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for InMemoryProvider {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl ResourceProvider for InMemoryProvider {
+    type Error = ProviderError;
+
+    async fn create_resource(
+        &self,
+        resource_type: &str,
+        data: Value,
+        context: &RequestContext,
+    ) -> Result<VersionedResource, Self::Error> {
+        self.0.create_resource(resource_type, data, context).await
+    }
+
+    async fn get_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        context: &RequestContext,
+    ) -> Result<Option<VersionedResource>, Self::Error> {
+        self.0.get_resource(resource_type, id, context).await
+    }
+
+    async fn get_resources(
+        &self,
+        resource_type: &str,
+        ids: &[String],
+        context: &RequestContext,
+    ) -> Result<Vec<Option<VersionedResource>>, Self::Error> {
+        self.0.get_resources(resource_type, ids, context).await
+    }
+
+    async fn update_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        data: Value,
+        expected_version: Option<&RawVersion>,
+        context: &RequestContext,
+    ) -> Result<VersionedResource, Self::Error> {
+        self.0
+            .update_resource(resource_type, id, data, expected_version, context)
+            .await
+    }
+
+    async fn delete_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        expected_version: Option<&RawVersion>,
+        context: &RequestContext,
+    ) -> Result<(), Self::Error> {
+        self.0
+            .delete_resource(resource_type, id, expected_version, context)
+            .await
+    }
+
+    async fn list_resources(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> Result<Vec<VersionedResource>, Self::Error> {
+        self.0.list_resources(resource_type, query, context).await
+    }
+
+    async fn find_resources_by_attribute(
+        &self,
+        resource_type: &str,
+        attribute_name: &str,
+        attribute_value: &str,
+        context: &RequestContext,
+    ) -> Result<Vec<VersionedResource>, Self::Error> {
+        self.0
+            .find_resources_by_attribute(resource_type, attribute_name, attribute_value, context)
+            .await
+    }
+
+    async fn patch_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        patch_request: &Value,
+        expected_version: Option<&RawVersion>,
+        context: &RequestContext,
+    ) -> Result<VersionedResource, Self::Error> {
+        self.0
+            .patch_resource(resource_type, id, patch_request, expected_version, context)
+            .await
+    }
+
+    async fn patch_resource_checked<E: Send>(
+        &self,
+        resource_type: &str,
+        id: &str,
+        patch_request: &Value,
+        expected_version: Option<&RawVersion>,
+        check: impl Fn(&Value, &mut Value) -> Result<(), E> + Send + Sync,
+        context: &RequestContext,
+    ) -> Result<Result<VersionedResource, E>, Self::Error> {
+        self.0
+            .patch_resource_checked(
+                resource_type,
+                id,
+                patch_request,
+                expected_version,
+                check,
+                context,
+            )
+            .await
+    }
+
+    async fn resource_exists(
+        &self,
+        resource_type: &str,
+        id: &str,
+        context: &RequestContext,
+    ) -> Result<bool, Self::Error> {
+        self.0.resource_exists(resource_type, id, context).await
+    }
+
+    fn default_tenant_id(&self) -> &str {
+        self.0.default_tenant_id()
+    }
+
+    fn set_default_tenant_id(&mut self, tenant_id: &str) {
+        self.0.set_default_tenant_id(tenant_id)
+    }
+
+    async fn count_resources(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> Result<usize, Self::Error> {
+        self.0.count_resources(resource_type, query, context).await
+    }
+
+    async fn list_ids(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> Result<Vec<String>, Self::Error> {
+        self.0.list_ids(resource_type, query, context).await
+    }
+}
@@ -0,0 +1,127 @@
+//! Bounded least-recently-used map shared by the caching decorators.
+//!
+//! Entries carry an optional expiry; an expired entry is dropped when it is
+//! next read. Inserting into a full cache evicts the least recently read or
+//! written entry. A capacity of zero caches nothing.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::time::Instant;
+
+struct Entry<V> {
+    value: V,
+    expires_at: Option<Instant>,
+    last_used: u64,
+}
+
+/// LRU bookkeeping: entries by key, and keys by recency tick.
+pub(crate) struct LruCache<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    recency: BTreeMap<u64, K>,
+    tick: u64,
+}
+
+impl<K, V> Default for LruCache<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> LruCache<K, V> {
+    /// Number of entries held, including expired entries not yet evicted.
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// The value under `key`, marking it most recently used, unless it has
+    /// expired.
+    pub(crate) fn get(&mut self, key: &K) -> Option<V> {
+        let expired = {
+            let entry = self.entries.get(key)?;
+            entry
+                .expires_at
+                .is_some_and(|expires_at| Instant::now() >= expires_at)
+        };
+        if expired {
+            self.remove(key);
+            return None;
+        }
+
+        self.tick += 1;
+        let tick = self.tick;
+        let entry = self.entries.get_mut(key)?;
+        self.recency.remove(&entry.last_used);
+        self.recency.insert(tick, key.clone());
+        entry.last_used = tick;
+        Some(entry.value.clone())
+    }
+
+    /// The value under `key`, without touching its recency or expiry.
+    pub(crate) fn peek(&self, key: &K) -> Option<&V> {
+        self.entries.get(key).map(|entry| &entry.value)
+    }
+
+    /// When the entry under `key` expires; `Some(None)` if it never does.
+    pub(crate) fn expires_at(&self, key: &K) -> Option<Option<Instant>> {
+        self.entries.get(key).map(|entry| entry.expires_at)
+    }
+
+    /// Store `value` under `key`, evicting the least recently used entries
+    /// to stay within `capacity`.
+    pub(crate) fn insert(
+        &mut self,
+        key: K,
+        value: V,
+        expires_at: Option<Instant>,
+        capacity: usize,
+    ) {
+        if capacity == 0 {
+            return;
+        }
+        self.remove(&key);
+        while self.entries.len() >= capacity {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+
+        self.tick += 1;
+        self.recency.insert(self.tick, key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                expires_at,
+                last_used: self.tick,
+            },
+        );
+    }
+
+    pub(crate) fn remove(&mut self, key: &K) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.recency.remove(&entry.last_used);
+        }
+    }
+
+    /// Keep only the entries whose value satisfies `keep`.
+    pub(crate) fn retain(&mut self, keep: impl Fn(&V) -> bool) {
+        let recency = &mut self.recency;
+        self.entries.retain(|_, entry| {
+            let kept = keep(&entry.value);
+            if !kept {
+                recency.remove(&entry.last_used);
+            }
+            kept
+        });
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
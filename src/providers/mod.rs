@@ -7,7 +7,8 @@
 //! # Available Providers
 //!
 //! * [`StandardResourceProvider`] - **RECOMMENDED** Production-ready provider with pluggable storage backends
-//! * **InMemoryProvider** - ⚠️ **REMOVED** in v0.4.0 - Use `StandardResourceProvider<InMemoryStorage>` instead
+//! * [`CachingProvider`] - Read-through LRU cache that wraps any provider
+//! * [`InMemoryProvider`] - ⚠️ **DEPRECATED** compatibility shim for the provider removed in v0.4.0 - Use `StandardResourceProvider<InMemoryStorage>` instead
 //!
 //! All providers in this module implement the unified ResourceProvider trait,
 //! supporting both single-tenant and multi-tenant operations through the
@@ -24,16 +25,27 @@
 //! let provider = StandardResourceProvider::new(storage);
 //! ```
 
+pub mod caching;
 pub mod error;
 pub mod helpers;
+pub mod in_memory;
+pub(crate) mod lru;
 pub mod provider;
 pub mod standard;
 
 // Re-export the recommended types
 pub use crate::storage::{InMemoryStorage, ProviderStats, StorageProvider};
+pub use caching::{CacheConfig, CachingProvider};
 pub use error::ProviderError;
-pub use provider::ResourceProvider;
-pub use standard::StandardResourceProvider;
+#[allow(deprecated)]
+pub use in_memory::InMemoryProvider;
+pub use provider::{DEFAULT_TENANT_ID, ResourceProvider, UpsertOutcome};
+#[cfg(feature = "encryption")]
+pub use standard::AesGcmAttributeCrypto;
+pub use standard::{
+    AttributeCrypto, AttributeCryptoError, NoopAttributeCrypto, ScanUniquenessChecker,
+    StandardResourceProvider, StorageKeyPolicy, UniquenessChecker, UniquenessQuery,
+};
 
 // Re-export helper traits for composable provider development
 pub use helpers::{
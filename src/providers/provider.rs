@@ -17,12 +17,36 @@
 //! // Implement ResourceProvider for your storage backend
 //! ```
 
+use crate::providers::ProviderError;
+use crate::providers::helpers::patch::{apply_patch_request, patch_via_update};
 use crate::resource::{
-    ListQuery, RequestContext, version::RawVersion, versioned::VersionedResource,
+    ListQuery, RequestContext,
+    version::{ConditionalResult, RawVersion},
+    versioned::VersionedResource,
 };
 use serde_json::Value;
 use std::future::Future;
 
+/// Tenant ID under which requests without tenant context are stored, unless
+/// the server is configured with another one.
+pub const DEFAULT_TENANT_ID: &str = "default";
+
+/// Whether an upsert created a resource or replaced an existing one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertOutcome {
+    /// No resource matched, so a new one was created
+    Created,
+    /// The matching resource was replaced
+    Updated,
+}
+
+impl UpsertOutcome {
+    /// Whether the upsert created a new resource.
+    pub fn is_created(self) -> bool {
+        self == UpsertOutcome::Created
+    }
+}
+
 /// Unified resource provider trait supporting both single and multi-tenant operations.
 ///
 /// This trait provides a unified interface for SCIM resource operations that works
@@ -40,11 +64,17 @@ use std::future::Future;
 /// When provided, operations perform optimistic concurrency control to prevent lost updates.
 /// When `None`, operations proceed without version checking.
 pub trait ResourceProvider {
-    /// Error type returned by all provider operations
-    type Error: std::error::Error + Send + Sync + 'static;
+    /// Error type returned by all provider operations.
+    ///
+    /// It converts from [`ProviderError`] so that default methods, such as
+    /// [`patch_resource`](Self::patch_resource), can report their failures.
+    type Error: std::error::Error + From<ProviderError> + Send + Sync + 'static;
 
     /// Create a resource for the tenant specified in the request context.
     ///
+    /// `data` is stored as given. Schema defaults are populated by the server
+    /// before it calls this, so providers need not apply them.
+    ///
     /// # Arguments
     /// * `resource_type` - The type of resource to create (e.g., "User", "Group")
     /// * `data` - The resource data as JSON
@@ -84,6 +114,10 @@ pub trait ResourceProvider {
     /// Supports both regular updates and conditional updates with version checking.
     /// When `expected_version` is provided, performs optimistic concurrency control.
     ///
+    /// This is a PUT: `data` replaces the stored resource. A multi-valued
+    /// attribute sent as an empty array (`"emails": []`) is cleared and kept as
+    /// `[]`; one omitted from `data` is cleared and left absent.
+    ///
     /// # Arguments
     /// * `resource_type` - The type of resource to update
     /// * `id` - The unique identifier of the resource
@@ -132,6 +166,12 @@ pub trait ResourceProvider {
     /// * `query` - Optional query parameters for filtering, sorting, pagination
     /// * `context` - Request context containing tenant information (if multi-tenant)
     ///
+    /// Implementations should honor every part of the query; [`ListQuery::apply_to`]
+    /// does so for resources already loaded as JSON. The filter must be
+    /// evaluated against complete resources, before `attributes` or
+    /// `excluded_attributes` are applied, so filtering on an attribute the
+    /// projection omits still works.
+    ///
     /// # Returns
     /// A vector of resources with version information from the specified tenant
     fn list_resources(
@@ -164,6 +204,18 @@ pub trait ResourceProvider {
     /// Supports both regular patches and conditional patches with version checking.
     /// When `expected_version` is provided, performs optimistic concurrency control.
     ///
+    /// Only attributes targeted by an operation change; anything not mentioned
+    /// in the patch, including multi-valued attributes, is left as stored. A
+    /// `replace` with an empty array clears the attribute to `[]`.
+    ///
+    /// The server routes every PATCH through this method. The default
+    /// implementation is
+    /// [`patch_via_update`](crate::providers::helpers::patch::patch_via_update),
+    /// which applies the operations with
+    /// [`apply_patch_request`](crate::providers::helpers::patch::apply_patch_request)
+    /// and writes the result back with [`update_resource`](Self::update_resource).
+    /// Providers with a native PATCH should override it.
+    ///
     /// # Arguments
     /// * `resource_type` - The type of resource to patch
     /// * `id` - The unique identifier of the resource
@@ -181,7 +233,93 @@ pub trait ResourceProvider {
         patch_request: &Value,
         expected_version: Option<&RawVersion>,
         context: &RequestContext,
-    ) -> impl Future<Output = Result<VersionedResource, Self::Error>> + Send;
+    ) -> impl Future<Output = Result<VersionedResource, Self::Error>> + Send
+    where
+        Self: Sync,
+    {
+        patch_via_update(
+            self,
+            resource_type,
+            id,
+            patch_request,
+            expected_version,
+            context,
+        )
+    }
+
+    /// Apply a PATCH operation to a resource, storing the result only if
+    /// `check` accepts it.
+    ///
+    /// `check` receives the stored and the patched resource JSON and runs
+    /// before anything is written, so a rejected patch leaves the resource
+    /// untouched and is returned as the inner `Err`. It may also adjust the
+    /// patched resource, and what it leaves is what gets stored. The server
+    /// uses it to run `on_save` hooks and enforce constraints, such as
+    /// required attributes, on the resource the patch actually applies to.
+    ///
+    /// The default implementation reads the resource, previews the patch with
+    /// [`apply_patch_request`](crate::providers::helpers::patch::apply_patch_request)
+    /// and then patches conditionally on the version it checked, so a
+    /// concurrent write fails with a version conflict rather than slipping
+    /// past the check. If `check` changed the patched resource, it is written
+    /// with [`update_resource`](Self::update_resource) instead, conditional on
+    /// the same version. Providers that patch atomically should override it
+    /// to run `check` within their own patch.
+    fn patch_resource_checked<E: Send>(
+        &self,
+        resource_type: &str,
+        id: &str,
+        patch_request: &Value,
+        expected_version: Option<&RawVersion>,
+        check: impl Fn(&Value, &mut Value) -> Result<(), E> + Send + Sync,
+        context: &RequestContext,
+    ) -> impl Future<Output = Result<Result<VersionedResource, E>, Self::Error>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            // A missing resource, a stale expected version or a patch that
+            // cannot be applied is left for `patch_resource` to report
+            let current = self.get_resource(resource_type, id, context).await?;
+            let mut expected_version = expected_version.cloned();
+            if let Some(current) = current
+                && expected_version
+                    .as_ref()
+                    .is_none_or(|expected| expected == current.version())
+                && let Ok(stored) = current.resource().to_json()
+            {
+                let mut patched = stored.clone();
+                if apply_patch_request(&mut patched, patch_request).is_ok() {
+                    let previewed = patched.clone();
+                    if let Err(rejection) = check(&stored, &mut patched) {
+                        return Ok(Err(rejection));
+                    }
+                    if patched != previewed {
+                        return self
+                            .update_resource(
+                                resource_type,
+                                id,
+                                patched,
+                                Some(current.version()),
+                                context,
+                            )
+                            .await
+                            .map(Ok);
+                    }
+                    expected_version = Some(current.version().clone());
+                }
+            }
+            self.patch_resource(
+                resource_type,
+                id,
+                patch_request,
+                expected_version.as_ref(),
+                context,
+            )
+            .await
+            .map(Ok)
+        }
+    }
 
     /// Check if a resource exists within the tenant specified in the request context.
     ///
@@ -198,6 +336,219 @@ pub trait ResourceProvider {
         id: &str,
         context: &RequestContext,
     ) -> impl Future<Output = Result<bool, Self::Error>> + Send;
+
+    /// Tenant ID used for requests without tenant context.
+    ///
+    /// The default implementation returns [`DEFAULT_TENANT_ID`].
+    fn default_tenant_id(&self) -> &str {
+        DEFAULT_TENANT_ID
+    }
+
+    /// Use `tenant_id` for requests without tenant context.
+    ///
+    /// [`ScimServer`](crate::ScimServer) calls this with its configured
+    /// [`default_tenant_id`](crate::ScimServerConfig::default_tenant_id) when
+    /// it is created. Providers that do not scope storage by tenant can ignore
+    /// it, which the default implementation does.
+    fn set_default_tenant_id(&mut self, _tenant_id: &str) {}
+
+    /// Count the resources matching a query's filter within the tenant
+    /// specified in the request context.
+    ///
+    /// Pagination, sorting and projection are ignored, so this is the
+    /// `totalResults` of a list response. The default implementation lists
+    /// the matching resources and counts them; providers that can count
+    /// without loading resources should override it.
+    ///
+    /// # Arguments
+    /// * `resource_type` - The type of resources to count
+    /// * `query` - Optional query whose filter selects the resources
+    /// * `context` - Request context containing tenant information (if multi-tenant)
+    fn count_resources(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> impl Future<Output = Result<usize, Self::Error>> + Send
+    where
+        Self: Sync,
+    {
+        let filter_only = ListQuery {
+            filter: query.and_then(|q| q.filter.clone()),
+            case_exact_attributes: query
+                .map(|q| q.case_exact_attributes.clone())
+                .unwrap_or_default(),
+            ..ListQuery::default()
+        };
+        async move {
+            let resources = self
+                .list_resources(resource_type, Some(&filter_only), context)
+                .await?;
+            Ok(resources.len())
+        }
+    }
+
+    /// Ids of the resources matching a query's filter within the tenant
+    /// specified in the request context.
+    ///
+    /// For callers that only need ids, such as membership resolution and
+    /// existence checks. Pagination, sorting and projection are ignored. The
+    /// default implementation lists the matching resources and collects their
+    /// ids; providers that can read ids without building resources should
+    /// override it.
+    ///
+    /// # Arguments
+    /// * `resource_type` - The type of resources to list
+    /// * `query` - Optional query whose filter selects the resources
+    /// * `context` - Request context containing tenant information (if multi-tenant)
+    fn list_ids(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> impl Future<Output = Result<Vec<String>, Self::Error>> + Send
+    where
+        Self: Sync,
+    {
+        let filter_only = ListQuery {
+            filter: query.and_then(|q| q.filter.clone()),
+            case_exact_attributes: query
+                .map(|q| q.case_exact_attributes.clone())
+                .unwrap_or_default(),
+            ..ListQuery::default()
+        };
+        async move {
+            let resources = self
+                .list_resources(resource_type, Some(&filter_only), context)
+                .await?;
+            Ok(resources
+                .iter()
+                .filter_map(|resource| resource.resource().get_id().map(str::to_string))
+                .collect())
+        }
+    }
+
+    /// Get several resources of one type by ID from the tenant specified in
+    /// the request context.
+    ///
+    /// Returns one entry per ID, in the order of `ids`, with `None` for IDs
+    /// that don't exist within the tenant scope. The default implementation
+    /// calls [`get_resource`](Self::get_resource) for each ID in turn;
+    /// providers that can read all of them from one consistent snapshot
+    /// should override it.
+    ///
+    /// # Arguments
+    /// * `resource_type` - The type of resources to retrieve
+    /// * `ids` - The identifiers of the resources
+    /// * `context` - Request context containing tenant information (if multi-tenant)
+    fn get_resources(
+        &self,
+        resource_type: &str,
+        ids: &[String],
+        context: &RequestContext,
+    ) -> impl Future<Output = Result<Vec<Option<VersionedResource>>, Self::Error>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            let mut resources = Vec::with_capacity(ids.len());
+            for id in ids {
+                resources.push(self.get_resource(resource_type, id, context).await?);
+            }
+            Ok(resources)
+        }
+    }
+
+    /// Update a resource only if its current version matches `expected_version`.
+    ///
+    /// Unlike [`update_resource`](Self::update_resource), a version mismatch or
+    /// missing resource is reported as a [`ConditionalResult`] rather than an
+    /// error, so callers get the current version without parsing messages.
+    ///
+    /// The default implementation delegates to `update_resource`, so the check
+    /// is as atomic as the provider's own, and recognizes
+    /// [`ProviderError::VersionConflict`], [`ProviderError::NotFound`] and
+    /// [`ProviderError::ResourceNotFound`], also when wrapped as the source of
+    /// the provider's own error. Any other error is returned unchanged, so
+    /// providers whose errors do not carry a `ProviderError` should override
+    /// this method to report mismatches and missing resources.
+    ///
+    /// # Returns
+    /// `Ok(ConditionalResult::Success)` with the updated resource,
+    /// `Ok(ConditionalResult::VersionMismatch)` with the expected and current
+    /// versions, `Ok(ConditionalResult::NotFound)`, or any other provider error
+    fn update_resource_conditional(
+        &self,
+        resource_type: &str,
+        id: &str,
+        data: Value,
+        expected_version: &RawVersion,
+        context: &RequestContext,
+    ) -> impl Future<Output = Result<ConditionalResult<VersionedResource>, Self::Error>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            match self
+                .update_resource(resource_type, id, data, Some(expected_version), context)
+                .await
+            {
+                Ok(resource) => Ok(ConditionalResult::Success(resource)),
+                Err(error) => conditional_failure(error),
+            }
+        }
+    }
+
+    /// Delete a resource only if its current version matches `expected_version`.
+    ///
+    /// The counterpart of [`update_resource_conditional`](Self::update_resource_conditional)
+    /// for [`delete_resource`](Self::delete_resource).
+    fn delete_resource_conditional(
+        &self,
+        resource_type: &str,
+        id: &str,
+        expected_version: &RawVersion,
+        context: &RequestContext,
+    ) -> impl Future<Output = Result<ConditionalResult<()>, Self::Error>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            match self
+                .delete_resource(resource_type, id, Some(expected_version), context)
+                .await
+            {
+                Ok(()) => Ok(ConditionalResult::Success(())),
+                Err(error) => conditional_failure(error),
+            }
+        }
+    }
+}
+
+/// Turn a failed conditional write into its [`ConditionalResult`], if the
+/// error describes a version mismatch or missing resource.
+///
+/// The error and its [`source`](std::error::Error::source) chain are searched
+/// for a [`ProviderError`], so provider errors that wrap one are recognized.
+/// Errors with no `ProviderError` in their chain are returned unchanged.
+fn conditional_failure<T, E>(error: E) -> Result<ConditionalResult<T>, E>
+where
+    E: std::error::Error + 'static,
+{
+    let provider_error = std::iter::successors(
+        Some(&error as &(dyn std::error::Error + 'static)),
+        |error| error.source(),
+    )
+    .find_map(|error| error.downcast_ref::<ProviderError>());
+    match provider_error {
+        Some(ProviderError::VersionConflict { conflict }) => {
+            Ok(ConditionalResult::VersionMismatch(conflict.clone()))
+        }
+        Some(ProviderError::NotFound { .. } | ProviderError::ResourceNotFound { .. }) => {
+            Ok(ConditionalResult::NotFound)
+        }
+        _ => Err(error),
+    }
 }
 
 /// Extension trait providing convenience methods for ResourceProvider implementations.
@@ -295,3 +646,60 @@ pub trait ResourceProviderExt: ResourceProvider {
 
 // Automatically implement ResourceProviderExt for all ResourceProvider implementations
 impl<T: ResourceProvider> ResourceProviderExt for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::version::VersionConflict;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("storage failed")]
+    struct WrappingError(#[source] ProviderError);
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("{0}")]
+    struct ForeignError(String);
+
+    #[test]
+    fn test_conditional_failure_finds_wrapped_provider_error() {
+        let expected = RawVersion::from_hash("expected");
+        let conflict =
+            VersionConflict::standard_message(expected.clone(), RawVersion::from_hash("current"));
+        let wrapped = WrappingError(ProviderError::from(conflict.clone()));
+
+        match conditional_failure::<(), _>(wrapped) {
+            Ok(ConditionalResult::VersionMismatch(found)) => assert_eq!(found, conflict),
+            other => panic!("expected a version mismatch, got {:?}", other),
+        }
+
+        let missing = WrappingError(ProviderError::NotFound {
+            resource_type: "User".to_string(),
+            id: "123".to_string(),
+        });
+        assert!(matches!(
+            conditional_failure::<(), _>(missing),
+            Ok(ConditionalResult::NotFound)
+        ));
+
+        let other = WrappingError(ProviderError::Internal {
+            message: "disk full".to_string(),
+        });
+        assert!(conditional_failure::<(), _>(other).is_err());
+    }
+
+    #[test]
+    fn test_conditional_failure_returns_foreign_errors_unchanged() {
+        // Messages are not parsed: only a ProviderError in the chain counts
+        for message in [
+            "Version conflict: resource changed",
+            "Precondition failed",
+            "Schema not found",
+            "tenant not found",
+        ] {
+            match conditional_failure::<(), _>(ForeignError(message.to_string())) {
+                Err(ForeignError(returned)) => assert_eq!(returned, message),
+                other => panic!("expected the error back, got {:?}", other),
+            }
+        }
+    }
+}
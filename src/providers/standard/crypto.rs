@@ -0,0 +1,419 @@
+//! Attribute-level encryption at rest for the standard resource provider.
+//!
+//! The [`StandardResourceProvider`](super::StandardResourceProvider) passes
+//! every resource through an [`AttributeCrypto`] on its way to and from the
+//! storage backend: the attributes it names are encrypted before `put` and
+//! decrypted after `get`, `list` and `find_by_attribute`. Everything above
+//! the storage layer, including filters and version computation, only ever
+//! sees plaintext, so a resource's version does not change when its
+//! ciphertext does.
+//!
+//! Encrypted values are stored as strings, so the backend can no longer
+//! match them by value. Unless the crypto is deterministic, lookups and
+//! uniqueness checks on encrypted attributes find nothing.
+//!
+//! The default [`NoopAttributeCrypto`] encrypts nothing. With the
+//! `encryption` feature, [`AesGcmAttributeCrypto`] provides AES-256-GCM.
+
+use serde_json::Value;
+use std::fmt;
+
+/// Error raised when an attribute value cannot be encrypted or decrypted.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Attribute encryption failed: {message}")]
+pub struct AttributeCryptoError {
+    /// Description of the failure
+    pub message: String,
+}
+
+impl AttributeCryptoError {
+    /// Create a new crypto error.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+/// Hook that encrypts selected attributes before they reach storage.
+///
+/// Values are serialized to JSON before encryption, so simple, complex and
+/// multi-valued attributes are all supported; the ciphertext replaces the
+/// value as a JSON string.
+pub trait AttributeCrypto: fmt::Debug + Send + Sync {
+    /// Attribute paths encrypted for a resource type.
+    ///
+    /// Paths are dotted, e.g. `name.familyName` or `emails.value`. Extension
+    /// attributes are named by their schema URN, a `.` and the attribute,
+    /// e.g. `urn:ietf:params:scim:schemas:extension:enterprise:2.0:User.employeeNumber`.
+    /// `id`, `schemas` and `meta` must not be encrypted.
+    fn encrypted_attributes(&self, resource_type: &str) -> &[String];
+
+    /// Whether equal plaintexts always produce equal ciphertexts.
+    ///
+    /// Deterministic encryption lets `find_by_attribute` and uniqueness
+    /// checks match encrypted attributes, at the cost of revealing which
+    /// resources share a value. Defaults to `false`.
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+
+    /// Encrypt a serialized attribute value.
+    fn encrypt(&self, plaintext: &[u8]) -> Result<String, AttributeCryptoError>;
+
+    /// Decrypt a value produced by [`encrypt`](Self::encrypt).
+    fn decrypt(&self, ciphertext: &str) -> Result<Vec<u8>, AttributeCryptoError>;
+}
+
+/// Attribute crypto that encrypts nothing. This is the provider's default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopAttributeCrypto;
+
+impl AttributeCrypto for NoopAttributeCrypto {
+    fn encrypted_attributes(&self, _resource_type: &str) -> &[String] {
+        &[]
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Result<String, AttributeCryptoError> {
+        String::from_utf8(plaintext.to_vec()).map_err(|e| AttributeCryptoError::new(e.to_string()))
+    }
+
+    fn decrypt(&self, ciphertext: &str) -> Result<Vec<u8>, AttributeCryptoError> {
+        Ok(ciphertext.as_bytes().to_vec())
+    }
+}
+
+/// Whether `attribute` is one of the encrypted paths of the resource type.
+pub(super) fn is_encrypted(
+    crypto: &dyn AttributeCrypto,
+    resource_type: &str,
+    attribute: &str,
+) -> bool {
+    crypto
+        .encrypted_attributes(resource_type)
+        .iter()
+        .any(|path| path == attribute)
+}
+
+/// Ciphertext of a string attribute value, as it would be stored.
+pub(super) fn encrypt_string(
+    crypto: &dyn AttributeCrypto,
+    value: &str,
+) -> Result<String, AttributeCryptoError> {
+    let plaintext = serde_json::to_vec(&Value::String(value.to_string()))
+        .map_err(|e| AttributeCryptoError::new(e.to_string()))?;
+    crypto.encrypt(&plaintext)
+}
+
+/// Encrypt the resource's protected attributes in place.
+pub(super) fn encrypt_resource(
+    crypto: &dyn AttributeCrypto,
+    resource_type: &str,
+    data: &mut Value,
+) -> Result<(), AttributeCryptoError> {
+    for path in crypto.encrypted_attributes(resource_type) {
+        for_each_at_path(data, path, &mut |value| {
+            if value.is_null() {
+                return Ok(());
+            }
+            let plaintext =
+                serde_json::to_vec(value).map_err(|e| AttributeCryptoError::new(e.to_string()))?;
+            *value = Value::String(crypto.encrypt(&plaintext)?);
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+/// Decrypt the resource's protected attributes in place.
+pub(super) fn decrypt_resource(
+    crypto: &dyn AttributeCrypto,
+    resource_type: &str,
+    data: &mut Value,
+) -> Result<(), AttributeCryptoError> {
+    for path in crypto.encrypted_attributes(resource_type) {
+        for_each_at_path(data, path, &mut |value| {
+            let ciphertext = match value {
+                Value::Null => return Ok(()),
+                Value::String(ciphertext) => ciphertext,
+                _ => {
+                    return Err(AttributeCryptoError::new(format!(
+                        "Stored value of '{}' is not ciphertext",
+                        path
+                    )));
+                }
+            };
+            let plaintext = crypto.decrypt(ciphertext)?;
+            *value = serde_json::from_slice(&plaintext)
+                .map_err(|e| AttributeCryptoError::new(e.to_string()))?;
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+/// Apply `f` to every value at a dotted path, descending into arrays.
+///
+/// Object keys may themselves contain dots (extension schema URNs), so each
+/// level matches the key that prefixes the remaining path.
+fn for_each_at_path(
+    data: &mut Value,
+    path: &str,
+    f: &mut dyn FnMut(&mut Value) -> Result<(), AttributeCryptoError>,
+) -> Result<(), AttributeCryptoError> {
+    match data {
+        Value::Array(items) => items
+            .iter_mut()
+            .try_for_each(|item| for_each_at_path(item, path, f)),
+        Value::Object(object) => {
+            if let Some(value) = object.get_mut(path) {
+                return f(value);
+            }
+            for (key, value) in object.iter_mut() {
+                if let Some(rest) = path
+                    .strip_prefix(key.as_str())
+                    .and_then(|rest| rest.strip_prefix('.'))
+                {
+                    return for_each_at_path(value, rest, f);
+                }
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(feature = "encryption")]
+pub use aes::AesGcmAttributeCrypto;
+
+#[cfg(feature = "encryption")]
+mod aes {
+    use super::{AttributeCrypto, AttributeCryptoError};
+    use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+    use aes_gcm::{Aes256Gcm, Nonce};
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use hkdf::Hkdf;
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+    use std::collections::HashMap;
+    use std::fmt;
+
+    const NONCE_LEN: usize = 12;
+
+    /// HKDF info separating the nonce key from the encryption key.
+    const NONCE_KEY_INFO: &[u8] = b"scim-server attribute crypto deterministic nonce";
+
+    /// AES-256-GCM attribute crypto.
+    ///
+    /// Ciphertexts are stored as base64 of the nonce followed by the sealed
+    /// value. Nonces are random unless deterministic mode is enabled, in
+    /// which case they are an HMAC-SHA256 of the plaintext under a key derived
+    /// from the encryption key with HKDF, so the encryption key is used only
+    /// by AES-GCM.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::providers::{AesGcmAttributeCrypto, StandardResourceProvider};
+    /// use scim_server::storage::InMemoryStorage;
+    ///
+    /// let crypto = AesGcmAttributeCrypto::new([7; 32])
+    ///     .with_attribute("User", "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User.employeeNumber");
+    /// let provider = StandardResourceProvider::new(InMemoryStorage::new())
+    ///     .with_attribute_crypto(crypto);
+    /// ```
+    #[derive(Clone)]
+    pub struct AesGcmAttributeCrypto {
+        cipher: Aes256Gcm,
+        nonce_key: [u8; 32],
+        attributes: HashMap<String, Vec<String>>,
+        deterministic: bool,
+    }
+
+    impl AesGcmAttributeCrypto {
+        /// Create a crypto with a 256-bit key and no encrypted attributes.
+        pub fn new(key: [u8; 32]) -> Self {
+            let mut nonce_key = [0; 32];
+            Hkdf::<Sha256>::new(None, &key)
+                .expand(NONCE_KEY_INFO, &mut nonce_key)
+                .expect("32 bytes is a valid HKDF-SHA256 output length");
+            Self {
+                cipher: Aes256Gcm::new(&key.into()),
+                nonce_key,
+                attributes: HashMap::new(),
+                deterministic: false,
+            }
+        }
+
+        /// Encrypt an attribute path of a resource type.
+        pub fn with_attribute(
+            mut self,
+            resource_type: impl Into<String>,
+            path: impl Into<String>,
+        ) -> Self {
+            self.attributes
+                .entry(resource_type.into())
+                .or_default()
+                .push(path.into());
+            self
+        }
+
+        /// Derive nonces from the plaintext so equal values encrypt equally.
+        ///
+        /// Disabled by default. Enable it only when encrypted attributes must
+        /// stay searchable or unique.
+        pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+            self.deterministic = deterministic;
+            self
+        }
+
+        fn nonce(&self, plaintext: &[u8]) -> Nonce<<Aes256Gcm as AeadCore>::NonceSize> {
+            if !self.deterministic {
+                return Aes256Gcm::generate_nonce(&mut OsRng);
+            }
+            let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.nonce_key)
+                .expect("HMAC accepts keys of any length");
+            mac.update(plaintext);
+            *Nonce::from_slice(&mac.finalize().into_bytes()[..NONCE_LEN])
+        }
+    }
+
+    impl fmt::Debug for AesGcmAttributeCrypto {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("AesGcmAttributeCrypto")
+                .field("attributes", &self.attributes)
+                .field("deterministic", &self.deterministic)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl AttributeCrypto for AesGcmAttributeCrypto {
+        fn encrypted_attributes(&self, resource_type: &str) -> &[String] {
+            self.attributes
+                .get(resource_type)
+                .map_or(&[], Vec::as_slice)
+        }
+
+        fn is_deterministic(&self) -> bool {
+            self.deterministic
+        }
+
+        fn encrypt(&self, plaintext: &[u8]) -> Result<String, AttributeCryptoError> {
+            let nonce = self.nonce(plaintext);
+            let sealed = self
+                .cipher
+                .encrypt(&nonce, plaintext)
+                .map_err(|_| AttributeCryptoError::new("AES-GCM encryption failed"))?;
+            let mut bytes = nonce.to_vec();
+            bytes.extend(sealed);
+            Ok(STANDARD.encode(bytes))
+        }
+
+        fn decrypt(&self, ciphertext: &str) -> Result<Vec<u8>, AttributeCryptoError> {
+            let bytes = STANDARD
+                .decode(ciphertext)
+                .map_err(|e| AttributeCryptoError::new(format!("Invalid ciphertext: {}", e)))?;
+            if bytes.len() < NONCE_LEN {
+                return Err(AttributeCryptoError::new("Ciphertext too short"));
+            }
+            let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+            self.cipher
+                .decrypt(Nonce::from_slice(nonce), sealed)
+                .map_err(|_| AttributeCryptoError::new("AES-GCM decryption failed"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Reversible stand-in for a real cipher.
+    #[derive(Debug)]
+    struct ReverseCrypto(Vec<String>);
+
+    impl AttributeCrypto for ReverseCrypto {
+        fn encrypted_attributes(&self, _resource_type: &str) -> &[String] {
+            &self.0
+        }
+
+        fn encrypt(&self, plaintext: &[u8]) -> Result<String, AttributeCryptoError> {
+            Ok(plaintext.iter().rev().map(|&b| b as char).collect())
+        }
+
+        fn decrypt(&self, ciphertext: &str) -> Result<Vec<u8>, AttributeCryptoError> {
+            Ok(ciphertext.bytes().rev().collect())
+        }
+    }
+
+    #[test]
+    fn test_round_trip_nested_and_extension_paths() {
+        let crypto = ReverseCrypto(vec![
+            "name.familyName".to_string(),
+            "emails.value".to_string(),
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User.employeeNumber".to_string(),
+        ]);
+        let original = json!({
+            "id": "1",
+            "userName": "alice",
+            "name": {"familyName": "Smith", "givenName": "Alice"},
+            "emails": [{"value": "a@example.com"}, {"value": "b@example.com"}],
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+                "employeeNumber": "42"
+            }
+        });
+
+        let mut data = original.clone();
+        encrypt_resource(&crypto, "User", &mut data).unwrap();
+        assert_eq!(data["name"]["familyName"], "\"htimS\"");
+        assert_eq!(data["name"]["givenName"], "Alice");
+        assert_eq!(data["emails"][1]["value"], "\"moc.elpmaxe@b\"");
+        assert_eq!(
+            data["urn:ietf:params:scim:schemas:extension:enterprise:2.0:User"]["employeeNumber"],
+            "\"24\""
+        );
+
+        decrypt_resource(&crypto, "User", &mut data).unwrap();
+        assert_eq!(data, original);
+    }
+
+    #[test]
+    fn test_decrypt_rejects_plaintext_values() {
+        let crypto = ReverseCrypto(vec!["active".to_string()]);
+        let mut data = json!({"active": true});
+        assert!(decrypt_resource(&crypto, "User", &mut data).is_err());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_aes_gcm_nonces() {
+        use base64::Engine;
+
+        let random = AesGcmAttributeCrypto::new([1; 32]);
+        let first = random.encrypt(b"secret").unwrap();
+        assert_ne!(first, random.encrypt(b"secret").unwrap());
+        assert_eq!(random.decrypt(&first).unwrap(), b"secret");
+
+        let deterministic = random.with_deterministic(true);
+        let first = deterministic.encrypt(b"secret").unwrap();
+        assert_eq!(first, deterministic.encrypt(b"secret").unwrap());
+        assert_eq!(deterministic.decrypt(&first).unwrap(), b"secret");
+
+        // Distinct plaintexts get distinct nonces
+        let nonce = |ciphertext: &str| {
+            base64::engine::general_purpose::STANDARD
+                .decode(ciphertext)
+                .unwrap()[..12]
+                .to_vec()
+        };
+        assert_ne!(
+            nonce(&first),
+            nonce(&deterministic.encrypt(b"secret2").unwrap())
+        );
+
+        let other_key = AesGcmAttributeCrypto::new([2; 32]);
+        assert!(other_key.decrypt(&first).is_err());
+    }
+}
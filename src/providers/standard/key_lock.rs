@@ -0,0 +1,89 @@
+//! Per-key write locks.
+//!
+//! Read-modify-write of a stored resource holds the lock for its storage key,
+//! so writes to one resource are serialized while writes to other resources,
+//! in the same or any other tenant, proceed concurrently. A lock exists only
+//! while a writer holds or waits for it. Writers needing several keys, such
+//! as a resource moving to a new key, take them in key order so two of them
+//! cannot deadlock.
+
+use crate::storage::StorageKey;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::OwnedMutexGuard;
+
+/// Write locks by storage key, shared across clones of a provider.
+#[derive(Debug, Default)]
+pub(super) struct KeyLocks {
+    locks: Mutex<HashMap<StorageKey, Weak<tokio::sync::Mutex<()>>>>,
+}
+
+/// Locks held on a set of storage keys.
+pub(super) struct KeyGuards {
+    held: Vec<(StorageKey, OwnedMutexGuard<()>)>,
+}
+
+impl KeyLocks {
+    /// Wait for exclusive write access to `key`.
+    pub(super) async fn lock(&self, key: &StorageKey) -> OwnedMutexGuard<()> {
+        self.entry(key).lock_owned().await
+    }
+
+    /// Wait for exclusive write access to every key in `keys`, taken in key
+    /// order.
+    pub(super) async fn lock_all(&self, keys: impl IntoIterator<Item = StorageKey>) -> KeyGuards {
+        let mut keys: Vec<StorageKey> = keys.into_iter().collect();
+        keys.sort_by(|a, b| order(a).cmp(&order(b)));
+        keys.dedup();
+        let mut guards = KeyGuards { held: Vec::new() };
+        for key in keys {
+            let guard = self.lock(&key).await;
+            guards.held.push((key, guard));
+        }
+        guards
+    }
+
+    /// Add `key` to `guards` if that keeps the key order, i.e. it sorts after
+    /// every key held. Returns whether `key` is held afterwards; if not, the
+    /// caller must release `guards` and take all the keys it needs with
+    /// [`lock_all`](Self::lock_all).
+    pub(super) async fn extend(&self, guards: &mut KeyGuards, key: &StorageKey) -> bool {
+        if guards.holds(key) {
+            return true;
+        }
+        if guards.held.iter().any(|(held, _)| order(held) > order(key)) {
+            return false;
+        }
+        let guard = self.lock(key).await;
+        guards.held.push((key.clone(), guard));
+        true
+    }
+
+    fn entry(&self, key: &StorageKey) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = self
+            .locks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match locks.get(key).and_then(Weak::upgrade) {
+            Some(lock) => lock,
+            None => {
+                locks.retain(|_, lock| lock.strong_count() > 0);
+                let lock = Arc::new(tokio::sync::Mutex::new(()));
+                locks.insert(key.clone(), Arc::downgrade(&lock));
+                lock
+            }
+        }
+    }
+}
+
+impl KeyGuards {
+    /// Whether the lock for `key` is held.
+    pub(super) fn holds(&self, key: &StorageKey) -> bool {
+        self.held.iter().any(|(held, _)| held == key)
+    }
+}
+
+/// The fixed order in which keys are locked.
+fn order(key: &StorageKey) -> (&str, &str, &str) {
+    (key.tenant_id(), key.resource_type(), key.resource_id())
+}
@@ -0,0 +1,29 @@
+//! Choice of the resource id component of storage keys.
+//!
+//! The [`StandardResourceProvider`](super::StandardResourceProvider) keys
+//! stored resources by their SCIM `id` unless configured otherwise with
+//! [`with_storage_key_policy`](super::StandardResourceProvider::with_storage_key_policy).
+//! Keying by `externalId` lets integrators address stored records by their
+//! own stable identifiers, while the SCIM `id` stays in the resource data and
+//! remains the only identifier clients see in URLs, `meta.location` and
+//! `$ref` values.
+
+/// Which resource attribute supplies the resource id of a [`StorageKey`](crate::storage::StorageKey).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageKeyPolicy {
+    /// Key resources by their SCIM `id`
+    #[default]
+    Id,
+    /// Key resources by their `externalId`, which every resource must then carry
+    ExternalId,
+}
+
+impl StorageKeyPolicy {
+    /// Attribute whose value is used as the storage key's resource id.
+    pub fn attribute(self) -> &'static str {
+        match self {
+            StorageKeyPolicy::Id => "id",
+            StorageKeyPolicy::ExternalId => "externalId",
+        }
+    }
+}
@@ -4,6 +4,15 @@
 //! and related functionality for SCIM resource management with pluggable
 //! storage backends.
 
+mod crypto;
+mod key_lock;
+mod key_policy;
 mod standard;
+mod uniqueness;
 
+#[cfg(feature = "encryption")]
+pub use crypto::AesGcmAttributeCrypto;
+pub use crypto::{AttributeCrypto, AttributeCryptoError, NoopAttributeCrypto};
+pub use key_policy::StorageKeyPolicy;
 pub use standard::StandardResourceProvider;
+pub use uniqueness::{ScanUniquenessChecker, UniquenessChecker, UniquenessQuery};
@@ -9,10 +9,18 @@
 //! * Pluggable storage backends through the StorageProvider trait
 //! * Complete SCIM protocol logic preservation
 //! * Automatic tenant isolation when tenant context is provided
-//! * Fallback to "default" tenant for single-tenant operations
+//! * Fallback to a configurable default tenant, `"default"` unless set, for
+//!   single-tenant operations
 //! * Comprehensive error handling
 //! * Resource metadata tracking (created/updated timestamps)
-//! * Duplicate detection for userName attributes
+//! * Duplicate detection for userName and externalId attributes via a pluggable
+//!   [`UniquenessChecker`]
+//! * Atomic PATCH: reading, patching and storing a resource cannot interleave
+//!   with another update, patch or delete through the same provider
+//! * Optional attribute-level encryption at rest via an [`AttributeCrypto`]
+//! * Storage keyed by SCIM `id` or by `externalId`, per [`StorageKeyPolicy`]
+//! * Optional soft delete, reporting operations on deleted resources as
+//!   [`ProviderError::ResourceDeleted`] rather than not found
 //!
 //! # Example Usage
 //!
@@ -43,67 +51,574 @@
 //! # }
 //! ```
 
+use super::crypto::{self, AttributeCrypto, NoopAttributeCrypto};
+use super::key_lock::{KeyGuards, KeyLocks};
+use super::key_policy::StorageKeyPolicy;
+use super::uniqueness::{ScanUniquenessChecker, UniquenessChecker, UniquenessQuery};
+use crate::filter::{CompareOperator, Filter};
 use crate::providers::ProviderError;
-use crate::providers::ResourceProvider;
 use crate::providers::helpers::{
     metadata::ScimMetadataManager, patch::ScimPatchOperations, tenant::MultiTenantProvider,
 };
+use crate::providers::{DEFAULT_TENANT_ID, ResourceProvider, UpsertOutcome};
 use crate::resource::{
-    ListQuery, RequestContext, Resource, version::RawVersion, versioned::VersionedResource,
+    ListQuery, Meta, RequestContext, Resource, SortOrder, TimestampPrecision,
+    version::{RawVersion, VersionConflict},
+    versioned::VersionedResource,
 };
+use crate::schema::{case, embedded};
 use crate::storage::ProviderStats;
-use crate::storage::{StorageKey, StorageProvider};
+use crate::storage::{StorageError, StorageKey, StoragePrefix, StorageProvider};
+use chrono::{DateTime, Utc};
 use log::{debug, info, trace, warn};
 use serde_json::{Value, json};
+use std::collections::{BTreeSet, HashMap};
+use std::convert::Infallible;
+use std::sync::Arc;
 
 /// Standard resource provider with pluggable storage backend.
 ///
 /// This provider separates SCIM protocol logic from storage concerns by delegating
 /// data persistence to a StorageProvider implementation while handling all SCIM-specific
 /// business logic, validation, and metadata management.
+///
+/// Uniqueness of `userName` and `externalId` is enforced through a
+/// [`UniquenessChecker`]; the default [`ScanUniquenessChecker`] queries the
+/// storage backend directly.
 #[derive(Debug, Clone)]
-pub struct StandardResourceProvider<S: StorageProvider> {
+pub struct StandardResourceProvider<S: StorageProvider, U = ScanUniquenessChecker> {
     // Pluggable storage backend
     storage: S,
+    // Pluggable uniqueness enforcement
+    uniqueness: U,
+    // Precision used when comparing meta timestamps
+    timestamp_precision: TimestampPrecision,
+    // Whether updates that change no attributes skip the write
+    skip_noop_updates: bool,
+    // Tenant used for requests without tenant context
+    default_tenant_id: String,
+    // Encrypts selected attributes before they reach storage
+    crypto: Arc<dyn AttributeCrypto>,
+    // Attribute supplying the resource id of storage keys
+    key_policy: StorageKeyPolicy,
+    // Serializes read-modify-write of each existing resource, and creates
+    // under each derived key, across clones
+    write_locks: Arc<KeyLocks>,
+    // Whether deletes leave a tombstone behind
+    soft_delete: bool,
 }
 
+/// Suffix of the storage resource type under which tombstones of deleted
+/// resources of a type are kept.
+const TOMBSTONE_SUFFIX: &str = "#deleted";
+
 impl<S: StorageProvider> StandardResourceProvider<S> {
     /// Create a new standard provider with the given storage backend.
     pub fn new(storage: S) -> Self {
-        Self { storage }
+        Self {
+            storage,
+            uniqueness: ScanUniquenessChecker,
+            timestamp_precision: TimestampPrecision::default(),
+            skip_noop_updates: false,
+            default_tenant_id: DEFAULT_TENANT_ID.to_string(),
+            crypto: Arc::new(NoopAttributeCrypto),
+            key_policy: StorageKeyPolicy::default(),
+            write_locks: Arc::default(),
+            soft_delete: false,
+        }
+    }
+}
+
+impl<S: StorageProvider, U: UniquenessChecker<S>> StandardResourceProvider<S, U> {
+    /// Create a standard provider with a custom uniqueness checker.
+    ///
+    /// Use this when the storage backend can answer uniqueness queries from an
+    /// index rather than scanning.
+    pub fn with_uniqueness_checker(storage: S, uniqueness: U) -> Self {
+        Self {
+            storage,
+            uniqueness,
+            timestamp_precision: TimestampPrecision::default(),
+            skip_noop_updates: false,
+            default_tenant_id: DEFAULT_TENANT_ID.to_string(),
+            crypto: Arc::new(NoopAttributeCrypto),
+            key_policy: StorageKeyPolicy::default(),
+            write_locks: Arc::default(),
+            soft_delete: false,
+        }
+    }
+
+    /// Set the precision at which `meta` timestamps are compared.
+    ///
+    /// Defaults to [`TimestampPrecision::Milliseconds`].
+    pub fn with_timestamp_precision(mut self, precision: TimestampPrecision) -> Self {
+        self.timestamp_precision = precision;
+        self
+    }
+
+    /// Skip the write for updates and patches that change no attributes.
+    ///
+    /// When enabled, a PUT or PATCH whose result equals the stored resource
+    /// (ignoring `meta`) returns the stored resource unchanged, keeping its
+    /// `meta.lastModified` and version, so no-op syncs do not churn versions.
+    /// Version preconditions are still checked first. Disabled by default,
+    /// for clients that expect a new version on every update.
+    pub fn with_noop_update_detection(mut self, enabled: bool) -> Self {
+        self.skip_noop_updates = enabled;
+        self
+    }
+
+    /// Encrypt attributes at rest with the given [`AttributeCrypto`].
+    ///
+    /// The attributes it names are encrypted before every write to storage
+    /// and decrypted after every read, so resources returned by the provider
+    /// and their versions are always computed from plaintext. Lookups and
+    /// uniqueness checks on encrypted attributes only match when the crypto
+    /// is deterministic. Defaults to [`NoopAttributeCrypto`].
+    pub fn with_attribute_crypto(mut self, crypto: impl AttributeCrypto + 'static) -> Self {
+        self.crypto = Arc::new(crypto);
+        self
+    }
+
+    /// Choose which attribute keys resources in storage.
+    ///
+    /// With [`StorageKeyPolicy::ExternalId`], every resource must carry an
+    /// `externalId`, which becomes the resource id of its [`StorageKey`] and
+    /// must be unique within the tenant and resource type. The SCIM `id` is
+    /// still generated, stored with the resource and used for every lookup
+    /// through [`ResourceProvider`], so locations and references are
+    /// unaffected. Changing a resource's `externalId` moves it to a new key.
+    /// Defaults to [`StorageKeyPolicy::Id`].
+    pub fn with_storage_key_policy(mut self, policy: StorageKeyPolicy) -> Self {
+        self.key_policy = policy;
+        self
     }
 
-    /// Check for duplicate userName in User resources within the same tenant.
-    async fn check_username_duplicate(
+    /// Keep a tombstone for every deleted resource.
+    ///
+    /// When enabled, deleting a resource removes its data and records only its
+    /// `id` and the time of deletion, under a storage resource type of its
+    /// own, so lists, searches and [`stats`](Self::stats) never include it.
+    /// Getting, updating, patching or deleting the resource afterwards fails
+    /// with [`ProviderError::ResourceDeleted`] instead of reporting it as not
+    /// found; the server answers both with `404`. Tombstones are kept until
+    /// the storage is cleared. Disabled by default, in which case deleted
+    /// resources are indistinguishable from ones that never existed.
+    pub fn with_soft_delete(mut self, enabled: bool) -> Self {
+        self.soft_delete = enabled;
+        self
+    }
+
+    /// Storage key of the tombstone of a deleted resource.
+    fn tombstone_key(tenant_id: &str, resource_type: &str, id: &str) -> StorageKey {
+        StorageKey::new(
+            tenant_id,
+            format!("{}{}", resource_type, TOMBSTONE_SUFFIX),
+            id,
+        )
+    }
+
+    /// Fail with [`ProviderError::ResourceDeleted`] if the resource with the
+    /// given SCIM `id` has a tombstone; called wherever a resource turns out
+    /// not to be stored, before reporting it as not found.
+    async fn ensure_not_deleted(
         &self,
         tenant_id: &str,
-        username: &str,
-        exclude_id: Option<&str>,
+        resource_type: &str,
+        id: &str,
     ) -> Result<(), ProviderError> {
-        let prefix = StorageKey::prefix(tenant_id, "User");
-        let matches = self
+        if !self.soft_delete {
+            return Ok(());
+        }
+        let deleted = self
             .storage
-            .find_by_attribute(prefix, "userName", username)
+            .exists(Self::tombstone_key(tenant_id, resource_type, id))
             .await
-            .map_err(|e| ProviderError::Internal {
-                message: format!("Storage error during username check: {}", e),
+            .map_err(|e| storage_error(e, "tombstone check"))?;
+        if deleted {
+            return Err(ProviderError::ResourceDeleted {
+                resource_type: resource_type.to_string(),
+                id: id.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Storage key of the resource with the given SCIM `id`, or `None` if
+    /// keys are derived from another attribute and no resource has the id.
+    async fn storage_key(
+        &self,
+        tenant_id: &str,
+        resource_type: &str,
+        id: &str,
+    ) -> Result<Option<StorageKey>, ProviderError> {
+        if self.key_policy == StorageKeyPolicy::Id {
+            return Ok(Some(StorageKey::new(tenant_id, resource_type, id)));
+        }
+        let found = self
+            .storage
+            .find_by_attribute(StorageKey::prefix(tenant_id, resource_type), "id", id)
+            .await
+            .map_err(|e| storage_error(e, "key lookup"))?;
+        Ok(found.into_iter().next().map(|(key, _data)| key))
+    }
+
+    /// Lock the resource with the given SCIM `id` for writing, along with
+    /// `also` if given, and return its storage key, or `None` if no resource
+    /// has the id.
+    ///
+    /// Keys are locked in a fixed order. With keys derived from another
+    /// attribute, the key is resolved again once the locks are held, since a
+    /// concurrent write may have moved the resource while this one waited.
+    async fn lock_existing(
+        &self,
+        tenant_id: &str,
+        resource_type: &str,
+        id: &str,
+        also: Option<StorageKey>,
+    ) -> Result<Option<(StorageKey, KeyGuards)>, ProviderError> {
+        loop {
+            let Some(key) = self.storage_key(tenant_id, resource_type, id).await? else {
+                return Ok(None);
+            };
+            let guards = self
+                .write_locks
+                .lock_all([key.clone()].into_iter().chain(also.clone()))
+                .await;
+            if self.key_policy == StorageKeyPolicy::Id {
+                return Ok(Some((key, guards)));
+            }
+            match self.storage_key(tenant_id, resource_type, id).await? {
+                Some(current) if current == key => return Ok(Some((key, guards))),
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Storage key under which the resource data is written.
+    fn key_for(
+        &self,
+        tenant_id: &str,
+        resource_type: &str,
+        data: &Value,
+    ) -> Result<StorageKey, ProviderError> {
+        let attribute = self.key_policy.attribute();
+        let key_id = data
+            .get(attribute)
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| ProviderError::InvalidInput {
+                message: format!(
+                    "{} resources are keyed by {} and require a non-empty value",
+                    resource_type, attribute
+                ),
             })?;
+        Ok(StorageKey::new(tenant_id, resource_type, key_id))
+    }
 
-        for (key, _data) in matches {
-            // Skip the resource we're updating
-            if Some(key.resource_id()) != exclude_id {
-                return Err(ProviderError::DuplicateAttribute {
-                    resource_type: "User".to_string(),
-                    attribute: "userName".to_string(),
-                    value: username.to_string(),
-                    tenant_id: tenant_id.to_string(),
-                });
+    /// SCIM `id` of the resource stored under a key.
+    async fn scim_id(&self, key: &StorageKey) -> Result<String, ProviderError> {
+        if self.key_policy == StorageKeyPolicy::Id {
+            return Ok(key.resource_id().to_string());
+        }
+        let data = self
+            .storage
+            .get(key.clone())
+            .await
+            .map_err(|e| storage_error(e, "id lookup"))?;
+        Ok(data
+            .as_ref()
+            .and_then(|data| data.get("id"))
+            .and_then(Value::as_str)
+            .unwrap_or(key.resource_id())
+            .to_string())
+    }
+
+    /// Reject a write to `key` if another resource is already stored there.
+    async fn check_key_free(&self, key: &StorageKey) -> Result<(), ProviderError> {
+        let taken = self
+            .storage
+            .exists(key.clone())
+            .await
+            .map_err(|e| storage_error(e, "key check"))?;
+        if !taken {
+            return Ok(());
+        }
+        let attribute = self.key_policy.attribute();
+        Err(ProviderError::DuplicateAttribute {
+            resource_type: key.resource_type().to_string(),
+            attribute: attribute.to_string(),
+            value: key.resource_id().to_string(),
+            tenant_id: key.tenant_id().to_string(),
+            existing_id: Some(self.scim_id(key).await?),
+        })
+    }
+
+    /// Write an existing resource stored under `key`, moving it when the
+    /// attribute its key is derived from has changed.
+    ///
+    /// Resources keyed by `id` never move, since their `id` is immutable. The
+    /// caller must hold the write locks for both the old and the new key, so
+    /// a create or another move cannot claim the new key between the check
+    /// and the write.
+    async fn store_existing(
+        &self,
+        key: StorageKey,
+        data: Value,
+        operation: &str,
+    ) -> Result<Value, ProviderError> {
+        if self.key_policy == StorageKeyPolicy::Id {
+            return self.store(key, data, operation).await;
+        }
+        let new_key = self.key_for(key.tenant_id(), key.resource_type(), &data)?;
+        if new_key == key {
+            return self.store(key, data, operation).await;
+        }
+        self.check_key_free(&new_key).await?;
+        let stored = self.store(new_key, data, operation).await?;
+        self.storage
+            .delete(key)
+            .await
+            .map_err(|e| storage_error(e, operation))?;
+        Ok(stored)
+    }
+
+    /// Read a resource from storage and decrypt its protected attributes.
+    async fn load(&self, key: StorageKey, operation: &str) -> Result<Option<Value>, ProviderError> {
+        let resource_type = key.resource_type().to_string();
+        let Some(mut data) = self
+            .storage
+            .get(key)
+            .await
+            .map_err(|e| storage_error(e, operation))?
+        else {
+            return Ok(None);
+        };
+        self.decrypt(&resource_type, &mut data)?;
+        Ok(Some(data))
+    }
+
+    /// Read every resource under a prefix and decrypt their protected attributes.
+    async fn load_all(
+        &self,
+        prefix: StoragePrefix,
+        operation: &str,
+    ) -> Result<Vec<(StorageKey, Value)>, ProviderError> {
+        let resource_type = prefix.resource_type().to_string();
+        let mut stored = self
+            .storage
+            .list(prefix, 0, usize::MAX)
+            .await
+            .map_err(|e| storage_error(e, operation))?;
+        for (_key, data) in &mut stored {
+            self.decrypt(&resource_type, data)?;
+        }
+        Ok(stored)
+    }
+
+    /// Encrypt a resource's protected attributes, write it and return the
+    /// stored resource in plaintext.
+    async fn store(
+        &self,
+        key: StorageKey,
+        mut data: Value,
+        operation: &str,
+    ) -> Result<Value, ProviderError> {
+        let resource_type = key.resource_type().to_string();
+        crypto::encrypt_resource(self.crypto.as_ref(), &resource_type, &mut data).map_err(|e| {
+            ProviderError::Internal {
+                message: e.to_string(),
             }
+        })?;
+        let mut stored = self
+            .storage
+            .put(key, data)
+            .await
+            .map_err(|e| storage_error(e, operation))?;
+        self.decrypt(&resource_type, &mut stored)?;
+        Ok(stored)
+    }
+
+    fn decrypt(&self, resource_type: &str, data: &mut Value) -> Result<(), ProviderError> {
+        crypto::decrypt_resource(self.crypto.as_ref(), resource_type, data).map_err(|e| {
+            ProviderError::Storage {
+                message: e.to_string(),
+            }
+        })
+    }
+
+    /// Whether equality lookups on the attribute can be answered by storage.
+    ///
+    /// Returns the value to look up, which is the ciphertext for attributes
+    /// encrypted deterministically, or `None` when the attribute is encrypted
+    /// with random nonces and can never match.
+    fn stored_lookup_value(
+        &self,
+        resource_type: &str,
+        attribute: &str,
+        value: &str,
+    ) -> Result<Option<String>, ProviderError> {
+        if !crypto::is_encrypted(self.crypto.as_ref(), resource_type, attribute) {
+            return Ok(Some(value.to_string()));
+        }
+        if !self.crypto.is_deterministic() {
+            return Ok(None);
+        }
+        crypto::encrypt_string(self.crypto.as_ref(), value)
+            .map(Some)
+            .map_err(|e| ProviderError::Internal {
+                message: e.to_string(),
+            })
+    }
+
+    /// The storage attribute and value to look up when a list query's filter
+    /// can be evaluated by [`StorageProvider::find_by_attribute`].
+    ///
+    /// Storage compares strings exactly, so only a lone `eq` on a string
+    /// attribute the query marks as case-exact qualifies. The attribute is
+    /// looked up under its schema spelling, as stored. Schema-qualified paths
+    /// and attributes storage cannot match, such as randomly encrypted ones,
+    /// are left to the in-memory evaluator, which still checks every
+    /// pushed-down match.
+    fn pushdown_term(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+    ) -> Result<Option<(String, String)>, ProviderError> {
+        let Some(query) = query else {
+            return Ok(None);
+        };
+        // Malformed filters are reported by the full evaluation
+        let Some(Ok(Filter::Compare(path, CompareOperator::Equal, Value::String(value)))) =
+            query.filter.as_deref().map(Filter::parse)
+        else {
+            return Ok(None);
+        };
+        if path.schema_uri.is_some() {
+            return Ok(None);
         }
+        let path = path.to_string();
+        let Some(attribute) = query
+            .case_exact_attributes
+            .iter()
+            .find(|attribute| attribute.eq_ignore_ascii_case(&path))
+        else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .stored_lookup_value(resource_type, attribute, &value)?
+            .map(|stored_value| (attribute.clone(), stored_value)))
+    }
+
+    /// Whether `updated` leaves the stored resource unchanged and should not
+    /// be written.
+    fn is_noop_update(&self, current: &Resource, updated: &Resource) -> bool {
+        self.skip_noop_updates
+            && updated
+                .diff(current)
+                .is_ok_and(|changed| changed.is_empty())
+    }
 
+    /// Check a resource's unique attributes against the other resources of
+    /// its type within the same tenant.
+    ///
+    /// `userName` is checked on Users with the core User schema's `caseExact`,
+    /// so `Alice` and `alice` conflict. `externalId` is checked on every
+    /// resource type and is case-sensitive.
+    async fn check_unique_attributes(
+        &self,
+        tenant_id: &str,
+        resource_type: &str,
+        resource: &Resource,
+        exclude_id: Option<&str>,
+    ) -> Result<(), ProviderError> {
+        if resource_type == "User"
+            && let Some(username) = resource.get_username()
+        {
+            self.check_unique_attribute(
+                UniquenessQuery::new(tenant_id, "User", "userName", username)
+                    .case_exact(case::is_case_exact(
+                        embedded::parsed_core_user_schema()
+                            .ok()
+                            .and_then(|schema| schema.attribute_definition("userName")),
+                    ))
+                    .excluding(exclude_id),
+            )
+            .await?;
+        }
+        if let Some(external_id) = resource.get_external_id() {
+            self.check_unique_attribute(
+                UniquenessQuery::new(tenant_id, resource_type, "externalId", external_id)
+                    .excluding(exclude_id),
+            )
+            .await?;
+        }
         Ok(())
     }
 
+    /// Reject the query's value if another resource in the tenant already holds it.
+    async fn check_unique_attribute(
+        &self,
+        query: UniquenessQuery<'_>,
+    ) -> Result<(), ProviderError> {
+        // Encrypted values are compared as stored, so only exact matches count
+        let Some(stored_value) =
+            self.stored_lookup_value(query.resource_type, query.attribute, query.value)?
+        else {
+            debug!(
+                "Skipping {} uniqueness check on non-deterministically encrypted attribute",
+                query.attribute
+            );
+            return Ok(());
+        };
+        let stored_query = if stored_value == query.value {
+            query
+        } else {
+            UniquenessQuery {
+                value: &stored_value,
+                case_exact: true,
+                ..query
+            }
+        };
+
+        let conflict = self
+            .uniqueness
+            .find_conflict(&self.storage, &stored_query)
+            .await
+            .map_err(|e| storage_error(e, &format!("{} check", query.attribute)))?;
+
+        match conflict {
+            Some(key) => Err(ProviderError::DuplicateAttribute {
+                resource_type: query.resource_type.to_string(),
+                attribute: query.attribute.to_string(),
+                value: query.value.to_string(),
+                tenant_id: query.tenant_id.to_string(),
+                existing_id: Some(self.scim_id(&key).await?),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that the request's tenant is permitted to perform an operation.
+    ///
+    /// Single-tenant requests (no tenant context) are always permitted.
+    fn check_permission(
+        &self,
+        context: &RequestContext,
+        operation: &str,
+    ) -> Result<(), ProviderError> {
+        if context.can_perform_operation(operation) {
+            Ok(())
+        } else {
+            Err(ProviderError::PermissionDenied {
+                operation: operation.to_string(),
+                tenant_id: self.effective_tenant_id(context),
+            })
+        }
+    }
+
     /// Clear all data from storage.
     ///
     /// Removes all resources from all tenants by delegating to the storage backend's
@@ -145,31 +660,94 @@ impl<S: StorageProvider> StandardResourceProvider<S> {
         }
     }
 
-    /// Get comprehensive statistics about stored data across all tenants.
+    /// Get a snapshot of stored data across all tenants.
     ///
-    /// Dynamically discovers all tenants and resource types from storage to provide
-    /// accurate statistics without relying on hardcoded patterns. This method uses
-    /// the storage provider's discovery capabilities to enumerate actual data.
+    /// Counts are taken from a single [`StorageProvider::stats`] call, so the
+    /// cost does not grow with the number of tenants beyond what the backend
+    /// itself needs to scan.
     ///
     /// # Returns
     ///
     /// [`ProviderStats`] containing:
+    /// - `collected_at`: When the snapshot was taken
     /// - `tenant_count`: Number of tenants with at least one resource
     /// - `total_resources`: Sum of all resources across all tenants and types
     /// - `resource_type_count`: Number of distinct resource types found
-    /// - `resource_types`: List of all resource type names
+    /// - `resource_types`: List of all resource type names, sorted
+    /// - `resource_counts`: Number of resources per tenant and resource type
     ///
     /// # Errors
     ///
-    /// This method handles storage errors gracefully by using default values
-    /// (empty collections) when discovery operations fail.
+    /// This method handles storage errors gracefully by logging them and
+    /// returning empty statistics.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::providers::{ResourceProvider, StandardResourceProvider};
+    /// use scim_server::resource::{RequestContext, TenantContext};
+    /// use scim_server::storage::InMemoryStorage;
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    /// let context = RequestContext::with_tenant_generated_id(TenantContext::new(
+    ///     "acme".to_string(),
+    ///     "client".to_string(),
+    /// ));
+    /// provider
+    ///     .create_resource("User", json!({"userName": "bjensen"}), &context)
+    ///     .await?;
+    ///
+    /// let stats = provider.stats().await;
+    /// assert_eq!(stats.count("acme", "User"), 1);
+    /// assert_eq!(stats.total_resources, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stats(&self) -> ProviderStats {
+        let storage_stats = match self.storage.stats().await {
+            Ok(stats) => stats,
+            Err(e) => {
+                warn!("Storage error in stats: {}", e);
+                return ProviderStats::new();
+            }
+        };
+
+        // Tombstones of deleted resources are not resources
+        let mut resource_counts = storage_stats.resource_counts;
+        for types in resource_counts.values_mut() {
+            types.retain(|resource_type, _| !resource_type.ends_with(TOMBSTONE_SUFFIX));
+        }
+        resource_counts.retain(|_, types| !types.is_empty());
+
+        let resource_types: BTreeSet<String> = resource_counts
+            .values()
+            .flat_map(|types| types.keys().cloned())
+            .collect();
+
+        ProviderStats {
+            collected_at: Utc::now(),
+            tenant_count: resource_counts.len(),
+            total_resources: resource_counts
+                .values()
+                .flat_map(|types| types.values())
+                .sum(),
+            resource_type_count: resource_types.len(),
+            resource_types: resource_types.into_iter().collect(),
+            resource_counts,
+        }
+    }
+
+    /// Get comprehensive statistics about stored data across all tenants.
+    ///
+    /// Equivalent to [`stats`](Self::stats).
     ///
     /// # Examples
     ///
     /// ```rust
     /// use scim_server::providers::StandardResourceProvider;
     /// use scim_server::storage::InMemoryStorage;
-    /// use scim_server::resource::{RequestContext, TenantContext};
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let storage = InMemoryStorage::new();
@@ -185,32 +763,7 @@ impl<S: StorageProvider> StandardResourceProvider<S> {
     /// # }
     /// ```
     pub async fn get_stats(&self) -> ProviderStats {
-        // Dynamically discover all tenants and resource types from storage
-        let tenants = self.storage.list_tenants().await.unwrap_or_default();
-        let resource_types = self
-            .storage
-            .list_all_resource_types()
-            .await
-            .unwrap_or_default();
-
-        let mut total_resources = 0;
-
-        // Count total resources across all tenants and resource types
-        for tenant_id in &tenants {
-            for resource_type in &resource_types {
-                let prefix = StorageKey::prefix(tenant_id, resource_type);
-                if let Ok(count) = self.storage.count(prefix).await {
-                    total_resources += count;
-                }
-            }
-        }
-
-        ProviderStats {
-            tenant_count: tenants.len(),
-            total_resources,
-            resource_type_count: resource_types.len(),
-            resource_types,
-        }
+        self.stats().await
     }
 
     /// List all resources of a specific type in a tenant.
@@ -220,7 +773,7 @@ impl<S: StorageProvider> StandardResourceProvider<S> {
         resource_type: &str,
     ) -> Vec<Resource> {
         let prefix = StorageKey::prefix(tenant_id, resource_type);
-        match self.storage.list(prefix, 0, usize::MAX).await {
+        match self.load_all(prefix, "list").await {
             Ok(storage_results) => {
                 let mut resources = Vec::new();
                 for (_key, data) in storage_results {
@@ -243,6 +796,317 @@ impl<S: StorageProvider> StandardResourceProvider<S> {
         }
     }
 
+    /// Look up a resource by its `externalId` within the request's tenant.
+    ///
+    /// Provisioning clients identify resources by their own `externalId`; this
+    /// resolves it to the SCIM resource using the storage backend's
+    /// `find_by_attribute`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::DuplicateAttribute`] if more than one resource
+    /// in the tenant carries the `externalId`, and
+    /// [`ProviderError::PermissionDenied`] if the tenant may not read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::providers::{ResourceProvider, StandardResourceProvider};
+    /// use scim_server::resource::RequestContext;
+    /// use scim_server::storage::InMemoryStorage;
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    /// let context = RequestContext::with_generated_id();
+    /// provider
+    ///     .create_resource("User", json!({"userName": "jdoe", "externalId": "hr-42"}), &context)
+    ///     .await?;
+    ///
+    /// let user = provider.get_by_external_id("User", "hr-42", &context).await?;
+    /// assert_eq!(user.unwrap().get_username(), Some("jdoe"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_by_external_id(
+        &self,
+        resource_type: &str,
+        external_id: &str,
+        context: &RequestContext,
+    ) -> Result<Option<Resource>, ProviderError> {
+        let mut matches = self
+            .find_resources_by_attribute(resource_type, "externalId", external_id, context)
+            .await?;
+
+        if matches.len() > 1 {
+            return Err(ProviderError::DuplicateAttribute {
+                resource_type: resource_type.to_string(),
+                attribute: "externalId".to_string(),
+                value: external_id.to_string(),
+                tenant_id: self.effective_tenant_id(context),
+                existing_id: None,
+            });
+        }
+
+        Ok(matches.pop().map(VersionedResource::into_resource))
+    }
+
+    /// Create a resource, or replace the one with the same `externalId`.
+    ///
+    /// The resource is matched through [`get_by_external_id`](Self::get_by_external_id),
+    /// then written with [`ResourceProvider::update_resource`] if found or
+    /// [`ResourceProvider::create_resource`] otherwise. The returned
+    /// [`UpsertOutcome`] says which of the two happened.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::InvalidInput`] if `data` has no string
+    /// `externalId`, plus any error from the lookup or write.
+    pub async fn upsert_by_external_id(
+        &self,
+        resource_type: &str,
+        data: Value,
+        context: &RequestContext,
+    ) -> Result<(VersionedResource, UpsertOutcome), ProviderError> {
+        let external_id = data
+            .get("externalId")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ProviderError::InvalidInput {
+                message: "Upsert requires a string externalId".to_string(),
+            })?
+            .to_string();
+
+        let existing_id = self
+            .get_by_external_id(resource_type, &external_id, context)
+            .await?
+            .and_then(|resource| resource.get_id().map(str::to_string));
+
+        match existing_id {
+            Some(id) => {
+                debug!(
+                    "Upsert of {} with externalId '{}' updates resource '{}'",
+                    resource_type, external_id, id
+                );
+                let resource = self
+                    .update_resource(resource_type, &id, data, None, context)
+                    .await?;
+                Ok((resource, UpsertOutcome::Updated))
+            }
+            None => {
+                let resource = self.create_resource(resource_type, data, context).await?;
+                Ok((resource, UpsertOutcome::Created))
+            }
+        }
+    }
+
+    /// List resources modified at or after `since` in the request's tenant.
+    ///
+    /// Intended for incremental sync. `meta.lastModified` and `since` are both
+    /// truncated to the provider's [`TimestampPrecision`] before comparison, so
+    /// a client echoing a previous `lastModified` back at a coarser precision
+    /// still receives that resource. Results are ordered oldest first unless
+    /// `query` sets its own sort; its filter, pagination and projection are
+    /// applied to the modified resources.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::QueryError`] if the query filter cannot be
+    /// parsed and [`ProviderError::PermissionDenied`] if the tenant may not list.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use chrono::{Duration, Utc};
+    /// use scim_server::providers::{ResourceProvider, StandardResourceProvider};
+    /// use scim_server::resource::{ListQuery, RequestContext};
+    /// use scim_server::storage::InMemoryStorage;
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    /// let context = RequestContext::with_generated_id();
+    /// let checkpoint = Utc::now() - Duration::seconds(1);
+    /// provider.create_resource("User", json!({"userName": "new.hire"}), &context).await?;
+    ///
+    /// let query = ListQuery::new().with_filter(r#"userName sw "new""#.to_string());
+    /// let changed = provider
+    ///     .list_modified_since("User", checkpoint, Some(&query), &context)
+    ///     .await?;
+    /// assert_eq!(changed.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_modified_since(
+        &self,
+        resource_type: &str,
+        since: DateTime<Utc>,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> Result<Vec<VersionedResource>, ProviderError> {
+        self.check_permission(context, "list")?;
+
+        let tenant_id = self.effective_tenant_id(context);
+        let since = self.timestamp_precision.truncate(since);
+        let prefix = StorageKey::prefix(&tenant_id, resource_type);
+        let stored = self.load_all(prefix, "list").await?;
+
+        let modified: Vec<Value> = stored
+            .into_iter()
+            .map(|(_key, data)| data)
+            .filter(|data| {
+                data.pointer("/meta/lastModified")
+                    .and_then(Value::as_str)
+                    .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                    .is_some_and(|last_modified| {
+                        self.timestamp_precision
+                            .truncate(last_modified.with_timezone(&Utc))
+                            >= since
+                    })
+            })
+            .collect();
+
+        let mut query = query.cloned().unwrap_or_default();
+        if query.sort_by.is_none() {
+            query.sort_by = Some("meta.lastModified".to_string());
+            query.sort_order = SortOrder::Ascending;
+        }
+        let modified = query
+            .apply_to(modified)
+            .map_err(|e| ProviderError::QueryError {
+                message: e.to_string(),
+            })?;
+
+        debug!(
+            "Found {} {} resources modified since {} for tenant '{}' (request: '{}')",
+            modified.len(),
+            resource_type,
+            since.to_rfc3339(),
+            tenant_id,
+            context.request_id
+        );
+
+        Ok(versioned_resources(resource_type, modified))
+    }
+
+    /// Delete every resource of a type matching a SCIM filter in the request's tenant.
+    ///
+    /// Each match is removed through [`ResourceProvider::delete_resource`], so the
+    /// regular per-resource delete path (permission check, logging) runs for every
+    /// deletion. Attributes the core schema marks `caseExact`, such as
+    /// `externalId`, are compared case-sensitively, as in a filtered list. Resources removed concurrently between matching and deletion are
+    /// skipped. Use [`delete_by_filter_dry_run`](Self::delete_by_filter_dry_run)
+    /// to preview the count first.
+    ///
+    /// # Returns
+    ///
+    /// The number of resources deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::QueryError`] if the filter cannot be parsed and
+    /// [`ProviderError::PermissionDenied`] if the tenant may not delete.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::providers::{ResourceProvider, StandardResourceProvider};
+    /// use scim_server::storage::InMemoryStorage;
+    /// use scim_server::resource::RequestContext;
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    /// let context = RequestContext::with_generated_id();
+    /// provider.create_resource("User", json!({"userName": "temp.one"}), &context).await?;
+    ///
+    /// let filter = r#"userName sw "temp.""#;
+    /// assert_eq!(provider.delete_by_filter_dry_run("User", filter, &context).await?, 1);
+    /// assert_eq!(provider.delete_by_filter("User", filter, &context).await?, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_by_filter(
+        &self,
+        resource_type: &str,
+        filter: &str,
+        context: &RequestContext,
+    ) -> Result<usize, ProviderError> {
+        let ids = self
+            .find_ids_matching_filter(resource_type, filter, context)
+            .await?;
+
+        let mut deleted = 0;
+        for id in &ids {
+            match self.delete_resource(resource_type, id, None, context).await {
+                Ok(()) => deleted += 1,
+                Err(
+                    ProviderError::ResourceNotFound { .. } | ProviderError::ResourceDeleted { .. },
+                ) => {
+                    debug!(
+                        "{} resource '{}' already removed during delete by filter",
+                        resource_type, id
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        info!(
+            "Deleted {} {} resources matching filter '{}' for tenant '{}' (request: '{}')",
+            deleted,
+            resource_type,
+            filter,
+            self.effective_tenant_id(context),
+            context.request_id
+        );
+        Ok(deleted)
+    }
+
+    /// Count the resources [`delete_by_filter`](Self::delete_by_filter) would delete,
+    /// without deleting anything.
+    pub async fn delete_by_filter_dry_run(
+        &self,
+        resource_type: &str,
+        filter: &str,
+        context: &RequestContext,
+    ) -> Result<usize, ProviderError> {
+        self.find_ids_matching_filter(resource_type, filter, context)
+            .await
+            .map(|ids| ids.len())
+    }
+
+    /// Ids of the tenant's resources of a type that match a filter.
+    async fn find_ids_matching_filter(
+        &self,
+        resource_type: &str,
+        filter: &str,
+        context: &RequestContext,
+    ) -> Result<Vec<String>, ProviderError> {
+        self.check_permission(context, "delete")?;
+
+        let filter = Filter::parse(filter).map_err(|e| ProviderError::QueryError {
+            message: e.to_string(),
+        })?;
+
+        let tenant_id = self.effective_tenant_id(context);
+        let prefix = StorageKey::prefix(&tenant_id, resource_type);
+        let stored = self.load_all(prefix, "filter evaluation").await?;
+
+        // Compare caseExact attributes as a filtered list does
+        let query = ListQuery::default()
+            .with_case_exact_attributes(core_case_exact_attributes(resource_type));
+        Ok(stored
+            .into_iter()
+            .filter(|(_key, data)| {
+                filter.matches_with_case_exact(data, |path| query.is_case_exact(path))
+            })
+            .map(|(key, data)| match data.get("id").and_then(Value::as_str) {
+                Some(id) => id.to_string(),
+                None => key.resource_id().to_string(),
+            })
+            .collect())
+    }
+
     /// Count resources of a specific type for a tenant (used for limit checking).
     async fn count_resources_for_tenant(&self, tenant_id: &str, resource_type: &str) -> usize {
         let prefix = StorageKey::prefix(tenant_id, resource_type);
@@ -256,6 +1120,89 @@ impl<S: StorageProvider> StandardResourceProvider<S> {
     }
 }
 
+/// `caseExact` attribute paths of a core resource type's schema.
+fn core_case_exact_attributes(resource_type: &str) -> Vec<String> {
+    embedded::parsed_core_schema(resource_type)
+        .map(|schema| schema.case_exact_attributes())
+        .unwrap_or_default()
+}
+
+/// Convert stored resource JSON to versioned resources, skipping entries that
+/// fail to deserialize.
+fn versioned_resources(resource_type: &str, data: Vec<Value>) -> Vec<VersionedResource> {
+    let mut resources = Vec::new();
+    for data in data {
+        match Resource::from_json(resource_type.to_string(), data) {
+            Ok(resource) => resources.push(resource),
+            Err(e) => {
+                warn!("Failed to deserialize resource during list: {}", e);
+                // Continue with other resources instead of failing entirely
+            }
+        }
+    }
+    VersionedResource::new_batch(resources)
+}
+
+/// Convert a storage failure into the provider error for it, so the server
+/// can answer with the matching SCIM status.
+///
+/// [`StorageError`] variants map as follows: missing resources to
+/// [`ProviderError::ResourceNotFound`], id clashes to
+/// [`ProviderError::DuplicateAttribute`] on `id`, other conflicts and
+/// concurrent modifications to [`ProviderError::Conflict`], outages, timeouts
+/// and network failures to [`ProviderError::Unavailable`], and exhausted
+/// capacity to [`ProviderError::CapacityExceeded`]. Serialization failures
+/// and errors of other storage backends become [`ProviderError::Storage`] and
+/// [`ProviderError::Internal`].
+fn storage_error<E: std::error::Error + 'static>(error: E, operation: &str) -> ProviderError {
+    let message = format!("Storage error during {}: {}", operation, error);
+    let Some(storage_error) = (&error as &dyn std::error::Error).downcast_ref::<StorageError>()
+    else {
+        return ProviderError::Internal { message };
+    };
+    match storage_error {
+        StorageError::ResourceNotFound {
+            tenant_id,
+            resource_type,
+            id,
+        } => ProviderError::ResourceNotFound {
+            resource_type: resource_type.clone(),
+            id: id.clone(),
+            tenant_id: tenant_id.clone(),
+        },
+        StorageError::ResourceAlreadyExists {
+            tenant_id,
+            resource_type,
+            id,
+        } => ProviderError::DuplicateAttribute {
+            resource_type: resource_type.clone(),
+            attribute: "id".to_string(),
+            value: id.clone(),
+            tenant_id: tenant_id.clone(),
+            existing_id: Some(id.clone()),
+        },
+        StorageError::Conflict { .. } | StorageError::ConcurrentModification { .. } => {
+            ProviderError::Conflict { message }
+        }
+        StorageError::Unavailable { retry_after, .. } => ProviderError::Unavailable {
+            message,
+            retry_after: *retry_after,
+        },
+        StorageError::Timeout { .. } | StorageError::Network { .. } => ProviderError::Unavailable {
+            message,
+            retry_after: None,
+        },
+        StorageError::CapacityExceeded { message, limit, .. } => ProviderError::CapacityExceeded {
+            message: message.clone(),
+            limit: *limit,
+        },
+        StorageError::Serialization { .. } | StorageError::DataCorruption { .. } => {
+            ProviderError::Storage { message }
+        }
+        _ => ProviderError::Internal { message },
+    }
+}
+
 // Note: No Default implementation for StandardResourceProvider as it requires storage parameter
 
 // Reuse error and stats types from the in_memory module for compatibility
@@ -263,7 +1210,9 @@ impl<S: StorageProvider> StandardResourceProvider<S> {
 // Helper traits are automatically implemented via blanket implementations
 // since StandardResourceProvider implements ResourceProvider and ProviderError implements From<String>
 
-impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
+impl<S: StorageProvider, U: UniquenessChecker<S>> ResourceProvider
+    for StandardResourceProvider<S, U>
+{
     type Error = ProviderError;
 
     async fn create_resource(
@@ -284,9 +1233,7 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
         );
 
         // Check permissions first
-        context
-            .validate_operation("create")
-            .map_err(|e| ProviderError::Internal { message: e })?;
+        self.check_permission(context, "create")?;
 
         // Check resource limits if this is a multi-tenant context
         if let Some(tenant_context) = &context.tenant_context {
@@ -332,13 +1279,8 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
             }
         })?;
 
-        // Check for duplicate userName if this is a User resource
-        if resource_type == "User" {
-            if let Some(username) = resource.get_username() {
-                self.check_username_duplicate(&tenant_id, username, None)
-                    .await?;
-            }
-        }
+        self.check_unique_attributes(&tenant_id, resource_type, &resource, None)
+            .await?;
 
         // Add metadata using ScimMetadataManager trait
         let mut resource_with_meta = resource;
@@ -346,25 +1288,42 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
             .map_err(|e| ProviderError::Internal {
                 message: format!("Failed to add metadata: {}", e),
             })?;
-        let resource_id = resource_with_meta.get_id().unwrap_or("unknown").to_string();
-
-        // Store resource using storage provider
-        let key = StorageKey::new(&tenant_id, resource_type, &resource_id);
-        let stored_data = self
-            .storage
-            .put(
-                key,
-                resource_with_meta
-                    .to_json()
-                    .map_err(|e| ProviderError::Internal {
-                        message: format!("Failed to serialize resource: {}", e),
-                    })?,
-            )
-            .await
+        let resource_json = resource_with_meta
+            .to_json()
             .map_err(|e| ProviderError::Internal {
-                message: format!("Storage error during create: {}", e),
+                message: format!("Failed to serialize resource: {}", e),
             })?;
 
+        // Keys derived from another attribute are not protected by the id.
+        // The write lock is held from the check to the put, so a concurrent
+        // create for the same key cannot replace this one
+        let key = self.key_for(&tenant_id, resource_type, &resource_json)?;
+        let _write = if self.key_policy != StorageKeyPolicy::Id {
+            Some(self.write_locks.lock(&key).await)
+        } else {
+            None
+        };
+        if self.key_policy != StorageKeyPolicy::Id {
+            self.check_key_free(&key).await?;
+            let resource_id = resource_with_meta.get_id().unwrap_or("unknown");
+            if self
+                .storage_key(&tenant_id, resource_type, resource_id)
+                .await?
+                .is_some()
+            {
+                return Err(ProviderError::DuplicateAttribute {
+                    resource_type: resource_type.to_string(),
+                    attribute: "id".to_string(),
+                    value: resource_id.to_string(),
+                    tenant_id,
+                    existing_id: Some(resource_id.to_string()),
+                });
+            }
+        }
+
+        // Store resource using storage provider
+        let stored_data = self.store(key, resource_json, "create").await?;
+
         // Return the resource as stored, wrapped in VersionedResource
         let resource =
             Resource::from_json(resource_type.to_string(), stored_data).map_err(|e| {
@@ -390,18 +1349,12 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
         );
 
         // Check permissions first
-        context
-            .validate_operation("read")
-            .map_err(|e| ProviderError::Internal { message: e })?;
+        self.check_permission(context, "read")?;
 
-        let key = StorageKey::new(&tenant_id, resource_type, id);
-        let resource_data = self
-            .storage
-            .get(key)
-            .await
-            .map_err(|e| ProviderError::Internal {
-                message: format!("Storage error during get: {}", e),
-            })?;
+        let resource_data = match self.storage_key(&tenant_id, resource_type, id).await? {
+            Some(key) => self.load(key, "get").await?,
+            None => None,
+        };
 
         let resource = match resource_data {
             Some(data) => {
@@ -415,6 +1368,8 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
                 Some(VersionedResource::new(resource))
             }
             None => {
+                self.ensure_not_deleted(&tenant_id, resource_type, id)
+                    .await?;
                 debug!("Resource not found");
                 None
             }
@@ -423,6 +1378,73 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
         Ok(resource)
     }
 
+    async fn get_resources(
+        &self,
+        resource_type: &str,
+        ids: &[String],
+        context: &RequestContext,
+    ) -> Result<Vec<Option<VersionedResource>>, Self::Error> {
+        let tenant_id = self.effective_tenant_id(context);
+
+        debug!(
+            "Getting {} {} resources for tenant '{}' (request: '{}')",
+            ids.len(),
+            resource_type,
+            tenant_id,
+            context.request_id
+        );
+
+        self.check_permission(context, "read")?;
+
+        let stored = if self.key_policy == StorageKeyPolicy::Id {
+            let keys: Vec<StorageKey> = ids
+                .iter()
+                .map(|id| StorageKey::new(&tenant_id, resource_type, id))
+                .collect();
+            self.storage
+                .get_many(&keys)
+                .await
+                .map_err(|e| storage_error(e, "get"))?
+        } else {
+            // Resolve ids from one snapshot rather than a lookup per id
+            let prefix = StorageKey::prefix(&tenant_id, resource_type);
+            let mut by_id: HashMap<String, (StorageKey, Value)> = self
+                .storage
+                .list(prefix, 0, usize::MAX)
+                .await
+                .map_err(|e| storage_error(e, "get"))?
+                .into_iter()
+                .filter_map(|(key, data)| {
+                    let id = data.get("id")?.as_str()?.to_string();
+                    Some((id, (key, data)))
+                })
+                .collect();
+            ids.iter()
+                .map(|id| match by_id.remove(id) {
+                    Some((key, data)) => (key, Some(data)),
+                    None => (StorageKey::new(&tenant_id, resource_type, id), None),
+                })
+                .collect()
+        };
+
+        stored
+            .into_iter()
+            .map(|(_key, data)| {
+                let Some(mut data) = data else {
+                    return Ok(None);
+                };
+                self.decrypt(resource_type, &mut data)?;
+                let resource =
+                    Resource::from_json(resource_type.to_string(), data).map_err(|e| {
+                        ProviderError::InvalidData {
+                            message: format!("Failed to deserialize resource: {}", e),
+                        }
+                    })?;
+                Ok(Some(VersionedResource::new(resource)))
+            })
+            .collect()
+    }
+
     async fn update_resource(
         &self,
         resource_type: &str,
@@ -443,15 +1465,30 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
         );
 
         // Check permissions first
-        context
-            .validate_operation("update")
-            .map_err(|e| ProviderError::Internal { message: e })?;
+        self.check_permission(context, "update")?;
+        // A changed key attribute moves the resource, so its new key is
+        // locked along with the current one
+        let new_key = match self.key_policy {
+            StorageKeyPolicy::Id => None,
+            _ => self.key_for(&tenant_id, resource_type, &data).ok(),
+        };
+        let Some((key, _write)) = self
+            .lock_existing(&tenant_id, resource_type, id, new_key)
+            .await?
+        else {
+            self.ensure_not_deleted(&tenant_id, resource_type, id)
+                .await?;
+            return Err(ProviderError::ResourceNotFound {
+                resource_type: resource_type.to_string(),
+                id: id.to_string(),
+                tenant_id,
+            });
+        };
 
         // Handle version checking if expected_version is provided
         if let Some(expected_version) = expected_version {
             // Get current resource to check version
-            let key = StorageKey::new(&tenant_id, resource_type, id);
-            match self.storage.get(key.clone()).await {
+            match self.load(key.clone(), "version check").await {
                 Ok(Some(current_data)) => {
                     // Parse current resource to extract version
                     let current_resource =
@@ -466,16 +1503,16 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
                         .clone();
 
                     if &current_version != expected_version {
-                        return Err(ProviderError::PreconditionFailed {
-                            message: format!(
-                                "Version mismatch: expected {}, got {}",
-                                expected_version.as_str(),
-                                current_version.as_str()
-                            ),
-                        });
+                        return Err(VersionConflict::standard_message(
+                            expected_version.clone(),
+                            current_version,
+                        )
+                        .into());
                     }
                 }
                 Ok(None) => {
+                    self.ensure_not_deleted(&tenant_id, resource_type, id)
+                        .await?;
                     return Err(ProviderError::NotFound {
                         resource_type: resource_type.to_string(),
                         id: id.to_string(),
@@ -501,34 +1538,49 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
             }
         })?;
 
-        // Check for duplicate userName if this is a User resource
-        if resource_type == "User" {
-            if let Some(username) = resource.get_username() {
-                self.check_username_duplicate(&tenant_id, username, Some(id))
-                    .await?;
-            }
-        }
+        self.check_unique_attributes(
+            &tenant_id,
+            resource_type,
+            &resource,
+            Some(key.resource_id()),
+        )
+        .await?;
 
         // Verify resource exists using storage provider
-        let key = StorageKey::new(&tenant_id, resource_type, id);
-        let exists =
-            self.storage
-                .exists(key.clone())
-                .await
-                .map_err(|e| ProviderError::Internal {
-                    message: format!("Storage error during existence check: {}", e),
-                })?;
-
-        if !exists {
+        let Some(current_data) = self.load(key.clone(), "existence check").await? else {
+            self.ensure_not_deleted(&tenant_id, resource_type, id)
+                .await?;
             return Err(ProviderError::ResourceNotFound {
                 resource_type: resource_type.to_string(),
                 id: id.to_string(),
                 tenant_id,
             });
+        };
+
+        // Server-managed meta fields come from the stored resource. Unrecognized
+        // meta sub-attributes in the replacement override the stored ones.
+        let mut resource_with_meta = resource;
+        if let Some(meta) = current_data
+            .get("meta")
+            .and_then(|meta| serde_json::from_value::<Meta>(meta.clone()).ok())
+        {
+            let extra = resource_with_meta.meta_extra();
+            resource_with_meta.set_meta(meta.with_extra(extra));
+        }
+
+        let current_resource = Resource::from_json(resource_type.to_string(), current_data)
+            .map_err(|e| ProviderError::InvalidInput {
+                message: format!("Failed to deserialize stored resource: {}", e),
+            })?;
+        if self.is_noop_update(&current_resource, &resource_with_meta) {
+            debug!(
+                "Skipping no-op update of {} resource with ID '{}' (request: '{}')",
+                resource_type, id, context.request_id
+            );
+            return Ok(VersionedResource::new(current_resource));
         }
 
         // Add metadata using ScimMetadataManager trait (preserve created time, update modified time)
-        let mut resource_with_meta = resource;
         self.update_modification_metadata(&mut resource_with_meta)
             .map_err(|e| ProviderError::Internal {
                 message: format!("Failed to update metadata: {}", e),
@@ -536,19 +1588,16 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
 
         // Store updated resource using storage provider
         let stored_data = self
-            .storage
-            .put(
+            .store_existing(
                 key,
                 resource_with_meta
                     .to_json()
                     .map_err(|e| ProviderError::Internal {
                         message: format!("Failed to serialize resource: {}", e),
                     })?,
+                "update",
             )
-            .await
-            .map_err(|e| ProviderError::Internal {
-                message: format!("Storage error during update: {}", e),
-            })?;
+            .await?;
 
         // Return the updated resource as stored, wrapped in VersionedResource
         let resource =
@@ -576,15 +1625,24 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
         );
 
         // Check permissions first
-        context
-            .validate_operation("delete")
-            .map_err(|e| ProviderError::Internal { message: e })?;
+        self.check_permission(context, "delete")?;
+        let Some((key, _write)) = self
+            .lock_existing(&tenant_id, resource_type, id, None)
+            .await?
+        else {
+            self.ensure_not_deleted(&tenant_id, resource_type, id)
+                .await?;
+            return Err(ProviderError::ResourceNotFound {
+                resource_type: resource_type.to_string(),
+                id: id.to_string(),
+                tenant_id,
+            });
+        };
 
         // Handle version checking if expected_version is provided
         if let Some(expected_version) = expected_version {
             // Get current resource to check version
-            let key = StorageKey::new(&tenant_id, resource_type, id);
-            match self.storage.get(key.clone()).await {
+            match self.load(key.clone(), "version check").await {
                 Ok(Some(current_data)) => {
                     // Parse current resource to extract version
                     let current_resource =
@@ -599,16 +1657,16 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
                         .clone();
 
                     if &current_version != expected_version {
-                        return Err(ProviderError::PreconditionFailed {
-                            message: format!(
-                                "Version mismatch: expected {}, got {}",
-                                expected_version.as_str(),
-                                current_version.as_str()
-                            ),
-                        });
+                        return Err(VersionConflict::standard_message(
+                            expected_version.clone(),
+                            current_version,
+                        )
+                        .into());
                     }
                 }
                 Ok(None) => {
+                    self.ensure_not_deleted(&tenant_id, resource_type, id)
+                        .await?;
                     return Err(ProviderError::NotFound {
                         resource_type: resource_type.to_string(),
                         id: id.to_string(),
@@ -623,20 +1681,19 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
         }
 
         // Delete resource using storage provider
-        let key = StorageKey::new(&tenant_id, resource_type, id);
         let removed = self
             .storage
             .delete(key)
             .await
-            .map_err(|e| ProviderError::Internal {
-                message: format!("Storage error during delete: {}", e),
-            })?;
+            .map_err(|e| storage_error(e, "delete"))?;
 
         if !removed {
             warn!(
                 "Attempted to delete non-existent {} resource with ID '{}' for tenant '{}'",
                 resource_type, id, tenant_id
             );
+            self.ensure_not_deleted(&tenant_id, resource_type, id)
+                .await?;
             return Err(ProviderError::ResourceNotFound {
                 resource_type: resource_type.to_string(),
                 id: id.to_string(),
@@ -644,6 +1701,16 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
             });
         }
 
+        if self.soft_delete {
+            self.storage
+                .put(
+                    Self::tombstone_key(&tenant_id, resource_type, id),
+                    json!({"id": id, "deleted": Utc::now().to_rfc3339()}),
+                )
+                .await
+                .map_err(|e| storage_error(e, "tombstone"))?;
+        }
+
         debug!(
             "Successfully deleted {} resource with ID '{}' for tenant '{}'",
             resource_type, id, tenant_id
@@ -665,51 +1732,36 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
         );
 
         // Check permissions first
-        context
-            .validate_operation("list")
-            .map_err(|e| ProviderError::Internal { message: e })?;
+        self.check_permission(context, "list")?;
 
-        // List resources using storage provider
+        // List resources using storage provider; pagination is applied later.
+        // A single equality the backend can answer itself avoids a full scan
         let prefix = StorageKey::prefix(&tenant_id, resource_type);
-        let storage_results = self
-            .storage
-            .list(prefix, 0, usize::MAX) // Get all resources for now, apply pagination later
-            .await
-            .map_err(|e| ProviderError::Internal {
-                message: format!("Storage error during list: {}", e),
-            })?;
-
-        // Convert storage results to VersionedResource objects
-        let mut resources = Vec::new();
-        for (_key, data) in storage_results {
-            match Resource::from_json(resource_type.to_string(), data) {
-                Ok(resource) => resources.push(VersionedResource::new(resource)),
-                Err(e) => {
-                    warn!("Failed to deserialize resource during list: {}", e);
-                    // Continue with other resources instead of failing entirely
+        let storage_results = match self.pushdown_term(resource_type, query)? {
+            Some((attribute, value)) => {
+                let mut matches = self
+                    .storage
+                    .find_by_attribute(prefix, &attribute, &value)
+                    .await
+                    .map_err(|e| storage_error(e, "list"))?;
+                for (_key, data) in &mut matches {
+                    self.decrypt(resource_type, data)?;
                 }
+                matches
             }
-        }
-
-        // Apply simple filtering and pagination if query is provided
-        let mut filtered_resources = resources;
+            None => self.load_all(prefix, "list").await?,
+        };
 
+        // Filter, sort, paginate and project the stored JSON before conversion
+        let mut data: Vec<Value> = storage_results.into_iter().map(|(_, data)| data).collect();
         if let Some(q) = query {
-            // Apply start_index and count for pagination
-            if let Some(start_index) = q.start_index {
-                let start = (start_index.saturating_sub(1)) as usize; // SCIM uses 1-based indexing
-                if start < filtered_resources.len() {
-                    filtered_resources = filtered_resources.into_iter().skip(start).collect();
-                } else {
-                    filtered_resources = Vec::new();
-                }
-            }
-
-            if let Some(count) = q.count {
-                filtered_resources.truncate(count as usize);
-            }
+            data = q.apply_to(data).map_err(|e| ProviderError::QueryError {
+                message: e.to_string(),
+            })?;
         }
 
+        let filtered_resources = versioned_resources(resource_type, data);
+
         debug!(
             "Found {} {} resources for tenant '{}' (after filtering)",
             filtered_resources.len(),
@@ -720,6 +1772,94 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
         Ok(filtered_resources)
     }
 
+    fn default_tenant_id(&self) -> &str {
+        &self.default_tenant_id
+    }
+
+    fn set_default_tenant_id(&mut self, tenant_id: &str) {
+        self.default_tenant_id = tenant_id.to_string();
+    }
+
+    async fn count_resources(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> Result<usize, Self::Error> {
+        let tenant_id = self.effective_tenant_id(context);
+        self.check_permission(context, "list")?;
+
+        let prefix = StorageKey::prefix(&tenant_id, resource_type);
+
+        // Without a filter the backend's own count is exact
+        let Some((query, filter)) = query.and_then(|q| q.filter.as_deref().map(|f| (q, f))) else {
+            return self
+                .storage
+                .count(prefix)
+                .await
+                .map_err(|e| storage_error(e, "count"));
+        };
+
+        // Match the stored JSON directly; nothing is deserialized or projected
+        let filter = Filter::parse(filter).map_err(|e| ProviderError::QueryError {
+            message: e.to_string(),
+        })?;
+        let stored = self.load_all(prefix, "count").await?;
+        Ok(stored
+            .iter()
+            .filter(|(_, data)| {
+                filter.matches_with_case_exact(data, |path| query.is_case_exact(path))
+            })
+            .count())
+    }
+
+    async fn list_ids(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> Result<Vec<String>, Self::Error> {
+        let tenant_id = self.effective_tenant_id(context);
+        self.check_permission(context, "list")?;
+
+        let prefix = StorageKey::prefix(&tenant_id, resource_type);
+        let filter = query.and_then(|q| q.filter.as_deref().map(|f| (q, f)));
+
+        // Keys carry the SCIM id, so no resource data needs to be read
+        if filter.is_none() && self.key_policy == StorageKeyPolicy::Id {
+            let keys = self
+                .storage
+                .list_keys(prefix)
+                .await
+                .map_err(|e| storage_error(e, "list ids"))?;
+            return Ok(keys
+                .into_iter()
+                .map(|key| key.resource_id().to_string())
+                .collect());
+        }
+
+        // Match the stored JSON directly; nothing is deserialized
+        let filter = filter
+            .map(|(query, filter)| {
+                Filter::parse(filter)
+                    .map(|filter| (query, filter))
+                    .map_err(|e| ProviderError::QueryError {
+                        message: e.to_string(),
+                    })
+            })
+            .transpose()?;
+        let stored = self.load_all(prefix, "list ids").await?;
+        Ok(stored
+            .into_iter()
+            .filter(|(_, data)| {
+                filter.as_ref().is_none_or(|(query, filter)| {
+                    filter.matches_with_case_exact(data, |path| query.is_case_exact(path))
+                })
+            })
+            .filter_map(|(_, data)| data.get("id").and_then(Value::as_str).map(str::to_string))
+            .collect())
+    }
+
     async fn find_resources_by_attribute(
         &self,
         resource_type: &str,
@@ -729,20 +1869,28 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
     ) -> Result<Vec<VersionedResource>, Self::Error> {
         let tenant_id = self.effective_tenant_id(context);
 
+        // Check permissions first
+        self.check_permission(context, "read")?;
+
         // Find resource by attribute using storage provider
         let prefix = StorageKey::prefix(&tenant_id, resource_type);
 
+        // Randomly encrypted values never match a lookup
+        let Some(stored_value) =
+            self.stored_lookup_value(resource_type, attribute_name, attribute_value)?
+        else {
+            return Ok(Vec::new());
+        };
         let matches = self
             .storage
-            .find_by_attribute(prefix, attribute_name, attribute_value)
+            .find_by_attribute(prefix, attribute_name, &stored_value)
             .await
-            .map_err(|e| ProviderError::Internal {
-                message: format!("Storage error during find by attribute: {}", e),
-            })?;
+            .map_err(|e| storage_error(e, "find by attribute"))?;
 
         // Return all matches as VersionedResources
         let mut results = Vec::new();
-        for (_key, data) in matches {
+        for (_key, mut data) in matches {
+            self.decrypt(resource_type, &mut data)?;
             match Resource::from_json(resource_type.to_string(), data) {
                 Ok(resource) => results.push(VersionedResource::new(resource)),
                 Err(e) => {
@@ -763,99 +1911,146 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
         expected_version: Option<&RawVersion>,
         context: &RequestContext,
     ) -> Result<VersionedResource, Self::Error> {
+        let patched = self
+            .patch_resource_checked(
+                resource_type,
+                id,
+                patch_request,
+                expected_version,
+                |_, _| Ok::<(), Infallible>(()),
+                context,
+            )
+            .await?;
+        match patched {
+            Ok(resource) => Ok(resource),
+            Err(never) => match never {},
+        }
+    }
+
+    async fn patch_resource_checked<E: Send>(
+        &self,
+        resource_type: &str,
+        id: &str,
+        patch_request: &Value,
+        expected_version: Option<&RawVersion>,
+        check: impl Fn(&Value, &mut Value) -> Result<(), E> + Send + Sync,
+        context: &RequestContext,
+    ) -> Result<Result<VersionedResource, E>, Self::Error> {
         let tenant_id = self.effective_tenant_id(context);
 
-        // Handle version checking if expected_version is provided
-        if let Some(expected_version) = expected_version {
-            // Get current resource to check version
-            let key = StorageKey::new(&tenant_id, resource_type, id);
-            match self.storage.get(key.clone()).await {
-                Ok(Some(current_data)) => {
-                    // Parse current resource to extract version
-                    let current_resource =
-                        Resource::from_json(resource_type.to_string(), current_data.clone())
-                            .map_err(|e| ProviderError::InvalidInput {
-                                message: format!("Failed to deserialize stored resource: {}", e),
-                            })?;
+        // Check permissions first
+        self.check_permission(context, "update")?;
+        let mut new_key = None;
+        loop {
+            let Some((key, mut write)) = self
+                .lock_existing(&tenant_id, resource_type, id, new_key.take())
+                .await?
+            else {
+                self.ensure_not_deleted(&tenant_id, resource_type, id)
+                    .await?;
+                return Err(ProviderError::ResourceNotFound {
+                    resource_type: resource_type.to_string(),
+                    id: id.to_string(),
+                    tenant_id,
+                });
+            };
 
-                    // Check if version matches
-                    let current_version = VersionedResource::new(current_resource.clone())
-                        .version()
-                        .clone();
+            // Load the stored resource once, under the lock, for the version
+            // check and as the base of the patch
+            let Some(current_data) = self.load(key.clone(), "patch").await? else {
+                self.ensure_not_deleted(&tenant_id, resource_type, id)
+                    .await?;
+                return Err(ProviderError::NotFound {
+                    resource_type: resource_type.to_string(),
+                    id: id.to_string(),
+                });
+            };
+            let current_resource = VersionedResource::new(
+                Resource::from_json(resource_type.to_string(), current_data).map_err(|e| {
+                    ProviderError::InvalidData {
+                        message: format!("Failed to deserialize stored resource: {}", e),
+                    }
+                })?,
+            );
 
-                    if &current_version != expected_version {
-                        return Err(ProviderError::PreconditionFailed {
-                            message: format!(
-                                "Version mismatch: expected {}, got {}",
-                                expected_version.as_str(),
-                                current_version.as_str()
-                            ),
-                        });
+            // Handle version checking if expected_version is provided
+            if let Some(expected_version) = expected_version
+                && current_resource.version() != expected_version
+            {
+                return Err(VersionConflict::standard_message(
+                    expected_version.clone(),
+                    current_resource.version().clone(),
+                )
+                .into());
+            }
+
+            // Convert to JSON for patching
+            let mut resource_data =
+                current_resource
+                    .resource()
+                    .to_json()
+                    .map_err(|e| ProviderError::Internal {
+                        message: format!("Failed to serialize resource for patching: {}", e),
+                    })?;
+
+            // Apply patch operations using helper trait
+            let stored_data = resource_data.clone();
+            if let Some(operations) = patch_request.get("Operations") {
+                if let Some(ops_array) = operations.as_array() {
+                    for operation in ops_array {
+                        self.apply_patch_operation(&mut resource_data, operation)?;
                     }
                 }
-                Ok(None) => {
-                    return Err(ProviderError::NotFound {
-                        resource_type: resource_type.to_string(),
-                        id: id.to_string(),
-                    });
-                }
-                Err(_) => {
-                    return Err(ProviderError::Internal {
-                        message: "Failed to retrieve resource for version check".to_string(),
-                    });
-                }
             }
-        }
 
-        // Regular patch without version checking - get current resource
-        let current_resource = self
-            .get_resource(resource_type, id, context)
-            .await?
-            .ok_or_else(|| ProviderError::NotFound {
-                resource_type: resource_type.to_string(),
-                id: id.to_string(),
-            })?;
+            // Checked under the write lock, so nothing changes before the store
+            if let Err(rejection) = check(&stored_data, &mut resource_data) {
+                return Ok(Err(rejection));
+            }
 
-        // Convert to JSON for patching
-        let mut resource_data =
-            current_resource
-                .resource()
+            // Parse back to Resource
+            let patched_resource = Resource::from_json(resource_type.to_string(), resource_data)
+                .map_err(|e| ProviderError::InvalidData {
+                    message: format!("Failed to create patched resource: {}", e),
+                })?;
+
+            self.check_unique_attributes(
+                &tenant_id,
+                resource_type,
+                &patched_resource,
+                Some(key.resource_id()),
+            )
+            .await?;
+
+            if self.is_noop_update(current_resource.resource(), &patched_resource) {
+                debug!(
+                    "Skipping no-op patch of {} resource with ID '{}' (request: '{}')",
+                    resource_type, id, context.request_id
+                );
+                return Ok(Ok(current_resource));
+            }
+
+            // Store the patched resource
+            let patched_json = patched_resource
                 .to_json()
                 .map_err(|e| ProviderError::Internal {
-                    message: format!("Failed to serialize resource for patching: {}", e),
+                    message: format!("Failed to serialize patched resource: {}", e),
                 })?;
 
-        // Apply patch operations using helper trait
-        if let Some(operations) = patch_request.get("Operations") {
-            if let Some(ops_array) = operations.as_array() {
-                for operation in ops_array {
-                    self.apply_patch_operation(&mut resource_data, operation)?;
+            // A patch that moves the resource needs its new key locked too.
+            // If taking it now would break the lock order, start over with
+            // both keys locked up front
+            if self.key_policy != StorageKeyPolicy::Id {
+                let target = self.key_for(&tenant_id, resource_type, &patched_json)?;
+                if !self.write_locks.extend(&mut write, &target).await {
+                    new_key = Some(target);
+                    continue;
                 }
             }
-        }
-
-        // Parse back to Resource
-        let patched_resource = Resource::from_json(resource_type.to_string(), resource_data)
-            .map_err(|e| ProviderError::InvalidData {
-                message: format!("Failed to create patched resource: {}", e),
-            })?;
-
-        // Store the patched resource
-        let key = StorageKey::new(&tenant_id, resource_type, id);
-        let patched_json = patched_resource
-            .to_json()
-            .map_err(|e| ProviderError::Internal {
-                message: format!("Failed to serialize patched resource: {}", e),
-            })?;
-
-        self.storage
-            .put(key, patched_json)
-            .await
-            .map_err(|e| ProviderError::Internal {
-                message: format!("Storage error during patch: {}", e),
-            })?;
+            self.store_existing(key, patched_json, "patch").await?;
 
-        Ok(VersionedResource::new(patched_resource))
+            return Ok(Ok(VersionedResource::new(patched_resource)));
+        }
     }
 
     async fn resource_exists(
@@ -866,12 +2061,15 @@ impl<S: StorageProvider> ResourceProvider for StandardResourceProvider<S> {
     ) -> Result<bool, Self::Error> {
         let tenant_id = self.effective_tenant_id(context);
 
-        let key = StorageKey::new(&tenant_id, resource_type, id);
+        // Check permissions first
+        self.check_permission(context, "read")?;
+
+        let Some(key) = self.storage_key(&tenant_id, resource_type, id).await? else {
+            return Ok(false);
+        };
         self.storage
             .exists(key)
             .await
-            .map_err(|e| ProviderError::Internal {
-                message: format!("Storage error during exists check: {}", e),
-            })
+            .map_err(|e| storage_error(e, "exists check"))
     }
 }
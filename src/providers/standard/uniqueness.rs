@@ -0,0 +1,317 @@
+//! Pluggable uniqueness checks for the standard resource provider.
+//!
+//! The [`StandardResourceProvider`](super::StandardResourceProvider) enforces
+//! unique attributes such as `userName` through a [`UniquenessChecker`]. The
+//! default [`ScanUniquenessChecker`] uses the storage backend's
+//! `find_by_attribute`, which is a linear scan for in-memory storage. Backends
+//! with a unique index can supply their own checker instead.
+//!
+//! Checkers receive the storage backend along with each query so they can
+//! either reuse it or consult an index of their own.
+
+use crate::schema::case;
+use crate::storage::{StorageKey, StorageProvider};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::future::Future;
+
+/// A single uniqueness lookup.
+///
+/// Lookups are always scoped to one tenant and resource type.
+#[derive(Debug, Clone, Copy)]
+pub struct UniquenessQuery<'a> {
+    /// Tenant the resource belongs to
+    pub tenant_id: &'a str,
+    /// Resource type (e.g., "User")
+    pub resource_type: &'a str,
+    /// Attribute name, optionally a dotted path (e.g., "userName")
+    pub attribute: &'a str,
+    /// Candidate value
+    pub value: &'a str,
+    /// Whether values are compared case-sensitively
+    pub case_exact: bool,
+    /// Resource to ignore, typically the one being updated
+    pub exclude_id: Option<&'a str>,
+}
+
+impl<'a> UniquenessQuery<'a> {
+    /// Create a case-sensitive query with no excluded resource.
+    pub fn new(
+        tenant_id: &'a str,
+        resource_type: &'a str,
+        attribute: &'a str,
+        value: &'a str,
+    ) -> Self {
+        Self {
+            tenant_id,
+            resource_type,
+            attribute,
+            value,
+            case_exact: true,
+            exclude_id: None,
+        }
+    }
+
+    /// Set whether values are compared case-sensitively.
+    pub fn case_exact(mut self, case_exact: bool) -> Self {
+        self.case_exact = case_exact;
+        self
+    }
+
+    /// Ignore the given resource when looking for conflicts.
+    pub fn excluding(mut self, resource_id: Option<&'a str>) -> Self {
+        self.exclude_id = resource_id;
+        self
+    }
+
+    /// Whether a stored value matches the candidate value.
+    pub fn matches(&self, stored: &str) -> bool {
+        case::compare_case(self.case_exact, stored, self.value) == Ordering::Equal
+    }
+}
+
+/// Strategy for detecting attribute uniqueness conflicts.
+///
+/// Implementations return the key of a conflicting resource, or `None` if the
+/// value is free within the query's tenant and resource type.
+pub trait UniquenessChecker<S: StorageProvider>: Send + Sync {
+    /// Find a resource, other than `query.exclude_id`, already holding the value.
+    fn find_conflict(
+        &self,
+        storage: &S,
+        query: &UniquenessQuery<'_>,
+    ) -> impl Future<Output = Result<Option<StorageKey>, S::Error>> + Send;
+}
+
+/// Default uniqueness checker backed by the storage provider.
+///
+/// Case-sensitive queries use `find_by_attribute`. Case-insensitive queries
+/// scan every resource of the type within the tenant.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanUniquenessChecker;
+
+impl<S: StorageProvider> UniquenessChecker<S> for ScanUniquenessChecker {
+    async fn find_conflict(
+        &self,
+        storage: &S,
+        query: &UniquenessQuery<'_>,
+    ) -> Result<Option<StorageKey>, S::Error> {
+        let prefix = StorageKey::prefix(query.tenant_id, query.resource_type);
+
+        let candidates = if query.case_exact {
+            storage
+                .find_by_attribute(prefix, query.attribute, query.value)
+                .await?
+        } else {
+            let total = storage.count(prefix.clone()).await?;
+            storage
+                .list(prefix, 0, total)
+                .await?
+                .into_iter()
+                .filter(|(_, data)| {
+                    attribute_value(data, query.attribute).is_some_and(|v| query.matches(v))
+                })
+                .collect()
+        };
+
+        Ok(candidates
+            .into_iter()
+            .map(|(key, _)| key)
+            .find(|key| Some(key.resource_id()) != query.exclude_id))
+    }
+}
+
+/// Resolve a dotted attribute path to a string value.
+fn attribute_value<'v>(data: &'v Value, path: &str) -> Option<&'v str> {
+    path.split('.')
+        .try_fold(data, |current, part| current.get(part))
+        .and_then(Value::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{ProviderError, ResourceProvider, StandardResourceProvider};
+    use crate::resource::RequestContext;
+    use crate::storage::InMemoryStorage;
+    use serde_json::json;
+
+    /// Checker that treats every attribute as case-insensitive.
+    struct CaseInsensitiveChecker;
+
+    impl<S: StorageProvider> UniquenessChecker<S> for CaseInsensitiveChecker {
+        async fn find_conflict(
+            &self,
+            storage: &S,
+            query: &UniquenessQuery<'_>,
+        ) -> Result<Option<StorageKey>, S::Error> {
+            ScanUniquenessChecker
+                .find_conflict(storage, &query.case_exact(false))
+                .await
+        }
+    }
+
+    async fn storage_with_user(tenant: &str, id: &str, user_name: &str) -> InMemoryStorage {
+        let storage = InMemoryStorage::new();
+        storage
+            .put(
+                StorageKey::new(tenant, "User", id),
+                json!({"id": id, "userName": user_name}),
+            )
+            .await
+            .unwrap();
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_scan_checker_case_exact() {
+        let storage = storage_with_user("t1", "1", "Alice").await;
+        let checker = ScanUniquenessChecker;
+
+        let query = UniquenessQuery::new("t1", "User", "userName", "Alice");
+        let conflict = checker.find_conflict(&storage, &query).await.unwrap();
+        assert_eq!(
+            conflict.map(|k| k.resource_id().to_string()),
+            Some("1".into())
+        );
+
+        let query = UniquenessQuery::new("t1", "User", "userName", "alice");
+        assert!(
+            checker
+                .find_conflict(&storage, &query)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_checker_case_insensitive_and_tenant_scoped() {
+        let storage = storage_with_user("t1", "1", "Alice").await;
+        let checker = ScanUniquenessChecker;
+
+        let query = UniquenessQuery::new("t1", "User", "userName", "ALICE").case_exact(false);
+        assert!(
+            checker
+                .find_conflict(&storage, &query)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        let other_tenant =
+            UniquenessQuery::new("t2", "User", "userName", "Alice").case_exact(false);
+        assert!(
+            checker
+                .find_conflict(&storage, &other_tenant)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let excluded = query.excluding(Some("1"));
+        assert!(
+            checker
+                .find_conflict(&storage, &excluded)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_provider_uses_custom_checker() {
+        let provider = StandardResourceProvider::with_uniqueness_checker(
+            InMemoryStorage::new(),
+            CaseInsensitiveChecker,
+        );
+        let context = RequestContext::with_generated_id();
+
+        provider
+            .create_resource("User", json!({"userName": "alice"}), &context)
+            .await
+            .unwrap();
+
+        let result = provider
+            .create_resource("User", json!({"userName": "ALICE"}), &context)
+            .await;
+        assert!(matches!(
+            result,
+            Err(ProviderError::DuplicateAttribute { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_provider_checks_external_id() {
+        let provider = StandardResourceProvider::new(InMemoryStorage::new());
+        let context = RequestContext::with_generated_id();
+
+        let engineering = provider
+            .create_resource(
+                "Group",
+                json!({"displayName": "Engineering", "externalId": "g-1"}),
+                &context,
+            )
+            .await
+            .unwrap();
+        let sales = provider
+            .create_resource(
+                "Group",
+                json!({"displayName": "Sales", "externalId": "g-2"}),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        let result = provider
+            .create_resource(
+                "Group",
+                json!({"displayName": "Marketing", "externalId": "g-1"}),
+                &context,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(ProviderError::DuplicateAttribute { ref attribute, ref existing_id, .. })
+                if attribute == "externalId" && existing_id.as_deref() == engineering.resource().get_id()
+        ));
+
+        // Patching onto another resource's externalId is rejected too
+        let patch = json!({
+            "Operations": [{"op": "replace", "path": "externalId", "value": "g-1"}]
+        });
+        let result = provider
+            .patch_resource(
+                "Group",
+                sales.resource().get_id().unwrap(),
+                &patch,
+                None,
+                &context,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(ProviderError::DuplicateAttribute { .. })
+        ));
+
+        // Values are case-sensitive, and a resource keeps its own value
+        provider
+            .create_resource(
+                "Group",
+                json!({"displayName": "Support", "externalId": "G-1"}),
+                &context,
+            )
+            .await
+            .unwrap();
+        provider
+            .update_resource(
+                "Group",
+                engineering.resource().get_id().unwrap(),
+                json!({"displayName": "Platform", "externalId": "g-1"}),
+                None,
+                &context,
+            )
+            .await
+            .unwrap();
+    }
+}
@@ -3,19 +3,34 @@
 //! This module provides request tracking, tenant context, and query parameters
 //! for SCIM operations with support for multi-tenant environments.
 
+use crate::error::ValidationError;
+use crate::filter::{AttributePath, Filter, FilterError};
+use crate::resource::serialization::project_attributes;
 use crate::resource::tenant::{IsolationLevel, TenantContext};
+use crate::schema::case;
+use chrono::DateTime;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Request context for SCIM operations.
 ///
 /// Provides request tracking for logging and auditing purposes.
-/// Optionally includes tenant context for multi-tenant operations.
+/// Optionally includes tenant context for multi-tenant operations, and
+/// free-form attributes such as the client IP or a correlation id that
+/// middleware and resource hooks can read for auditing or rate limiting.
 #[derive(Debug, Clone)]
 pub struct RequestContext {
     /// Unique identifier for this request
     pub request_id: String,
     /// Optional tenant context for multi-tenant operations
     pub tenant_context: Option<TenantContext>,
+    /// Request-scoped client hints, e.g. `client_ip` or `user_agent`.
+    ///
+    /// Attributes are informational only: they never affect resource data
+    /// or the versions computed from it.
+    pub attributes: HashMap<String, String>,
 }
 
 impl RequestContext {
@@ -24,6 +39,7 @@ impl RequestContext {
         Self {
             request_id,
             tenant_context: None,
+            attributes: HashMap::new(),
         }
     }
 
@@ -32,6 +48,7 @@ impl RequestContext {
         Self {
             request_id: Uuid::new_v4().to_string(),
             tenant_context: None,
+            attributes: HashMap::new(),
         }
     }
 
@@ -40,6 +57,7 @@ impl RequestContext {
         Self {
             request_id,
             tenant_context: Some(tenant_context),
+            attributes: HashMap::new(),
         }
     }
 
@@ -48,9 +66,40 @@ impl RequestContext {
         Self {
             request_id: Uuid::new_v4().to_string(),
             tenant_context: Some(tenant_context),
+            attributes: HashMap::new(),
         }
     }
 
+    /// Add request-scoped attributes, replacing any with the same names.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::resource::RequestContext;
+    /// use std::collections::HashMap;
+    ///
+    /// let context = RequestContext::new("req-1".to_string()).with_attributes(HashMap::from([
+    ///     ("client_ip".to_string(), "203.0.113.7".to_string()),
+    ///     ("user_agent".to_string(), "okta-scim/1.0".to_string()),
+    /// ]));
+    /// assert_eq!(context.attribute("client_ip"), Some("203.0.113.7"));
+    /// ```
+    pub fn with_attributes(mut self, attributes: HashMap<String, String>) -> Self {
+        self.attributes.extend(attributes);
+        self
+    }
+
+    /// Add a single request-scoped attribute.
+    pub fn with_attribute(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attributes.insert(name.into(), value.into());
+        self
+    }
+
+    /// Get a request-scoped attribute by name.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
     /// Get the tenant ID if this is a multi-tenant request.
     pub fn tenant_id(&self) -> Option<&str> {
         self.tenant_context.as_ref().map(|t| t.tenant_id.as_str())
@@ -98,22 +147,42 @@ impl Default for RequestContext {
     }
 }
 
+/// Sort direction for list results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Smallest values first
+    #[default]
+    Ascending,
+    /// Largest values first
+    Descending,
+}
+
 /// Query parameters for listing resources.
 ///
-/// This structure supports pagination, filtering, and attribute selection
-/// for SCIM list operations.
+/// This structure supports pagination, filtering, sorting, and attribute
+/// selection for SCIM list operations. Providers can call
+/// [`apply_to`](Self::apply_to) on the resource JSON they have loaded so
+/// every provider interprets a query the same way.
 #[derive(Debug, Clone, Default)]
 pub struct ListQuery {
     /// Maximum number of results to return
     pub count: Option<usize>,
-    /// Starting index for pagination
+    /// 1-based index of the first result to return. Indexes past the last
+    /// result yield an empty page.
     pub start_index: Option<usize>,
     /// Filter expression
     pub filter: Option<String>,
+    /// Attribute to sort results by
+    pub sort_by: Option<String>,
+    /// Direction to sort results in when `sort_by` is set
+    pub sort_order: SortOrder,
     /// Attributes to include in results
     pub attributes: Vec<String>,
     /// Attributes to exclude from results
     pub excluded_attributes: Vec<String>,
+    /// Attribute paths whose string values the filter and sort compare
+    /// case-sensitively, e.g. `externalId` or `groups.value`
+    pub case_exact_attributes: Vec<String>,
 }
 
 impl ListQuery {
@@ -134,12 +203,36 @@ impl ListQuery {
         self
     }
 
+    /// Reject a `startIndex` below 1.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidStartIndex`] for a start index of 0.
+    pub fn validate_pagination(&self) -> Result<(), ValidationError> {
+        match self.start_index {
+            Some(0) => Err(ValidationError::InvalidStartIndex { start_index: 0 }),
+            _ => Ok(()),
+        }
+    }
+
     /// Set a filter expression.
     pub fn with_filter(mut self, filter: String) -> Self {
         self.filter = Some(filter);
         self
     }
 
+    /// Set the attribute to sort results by.
+    pub fn with_sort_by(mut self, sort_by: String) -> Self {
+        self.sort_by = Some(sort_by);
+        self
+    }
+
+    /// Set the sort direction.
+    pub fn with_sort_order(mut self, sort_order: SortOrder) -> Self {
+        self.sort_order = sort_order;
+        self
+    }
+
     /// Add an attribute to include in results.
     pub fn with_attribute(mut self, attribute: String) -> Self {
         self.attributes.push(attribute);
@@ -163,4 +256,122 @@ impl ListQuery {
         self.excluded_attributes.extend(attributes);
         self
     }
+
+    /// Compare the string values of these attributes case-sensitively when
+    /// filtering and sorting, as for schema attributes marked `caseExact`.
+    pub fn with_case_exact_attributes(mut self, attributes: Vec<String>) -> Self {
+        self.case_exact_attributes.extend(attributes);
+        self
+    }
+
+    /// Whether the filter and sort compare an attribute's string values
+    /// case-sensitively. Schema URN prefixes are ignored.
+    pub fn is_case_exact(&self, path: &AttributePath) -> bool {
+        self.case_exact_attributes.iter().any(|attribute| {
+            let (name, sub) = match attribute.split_once('.') {
+                Some((name, sub)) => (name, Some(sub)),
+                None => (attribute.as_str(), None),
+            };
+            name.eq_ignore_ascii_case(&path.attribute)
+                && match (sub, &path.sub_attribute) {
+                    (Some(sub), Some(path_sub)) => sub.eq_ignore_ascii_case(path_sub),
+                    (None, None) => true,
+                    _ => false,
+                }
+        })
+    }
+
+    /// Apply this query to a set of resources.
+    ///
+    /// Resources are filtered, sorted, paginated and finally projected, in
+    /// that order, as described in RFC 7644 Section 3.4.2. Resources without
+    /// a value for the sort attribute are placed last in either direction.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FilterError`] if the filter expression cannot be parsed.
+    pub fn apply_to(&self, resources: Vec<Value>) -> Result<Vec<Value>, FilterError> {
+        let mut resources = match &self.filter {
+            Some(filter) => {
+                let filter = Filter::parse(filter)?;
+                resources
+                    .into_iter()
+                    .filter(|resource| {
+                        filter.matches_with_case_exact(resource, |path| self.is_case_exact(path))
+                    })
+                    .collect()
+            }
+            None => resources,
+        };
+
+        if let Some(path) = self.sort_by.as_deref().and_then(AttributePath::parse) {
+            // A complex attribute sorts by its `value`; an attribute is either
+            // simple or complex, so at most one of these is configured
+            let case_exact = self.is_case_exact(&path)
+                || (path.sub_attribute.is_none()
+                    && self.is_case_exact(&AttributePath {
+                        sub_attribute: Some("value".to_string()),
+                        ..path.clone()
+                    }));
+            resources.sort_by(|a, b| match (sort_value(&path, a), sort_value(&path, b)) {
+                (Some(a), Some(b)) => match self.sort_order {
+                    SortOrder::Ascending => compare_sort_values(a, b, case_exact),
+                    SortOrder::Descending => compare_sort_values(b, a, case_exact),
+                },
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            });
+        }
+
+        // SCIM uses 1-based indexing
+        let start = self.start_index.unwrap_or(1).saturating_sub(1);
+        let count = self.count.unwrap_or(usize::MAX);
+
+        Ok(resources
+            .into_iter()
+            .skip(start)
+            .take(count)
+            .map(|mut resource| {
+                project_attributes(&mut resource, &self.attributes, &self.excluded_attributes);
+                resource
+            })
+            .collect())
+    }
+}
+
+/// The value a resource is sorted by: the primary (or first) value of a
+/// multi-valued attribute, and the `value` sub-attribute of a complex one.
+fn sort_value<'v>(path: &AttributePath, resource: &'v Value) -> Option<&'v Value> {
+    let values = path.values(resource);
+    let value = values
+        .iter()
+        .find(|v| v.get("primary") == Some(&Value::Bool(true)))
+        .or(values.first())?;
+
+    match value {
+        Value::Object(_) => value.get("value"),
+        Value::Null => None,
+        other => Some(other),
+    }
+}
+
+fn compare_sort_values(a: &Value, b: &Value, case_exact: bool) -> Ordering {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => {
+            match (
+                DateTime::parse_from_rfc3339(a),
+                DateTime::parse_from_rfc3339(b),
+            ) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => case::compare_case(case_exact, a, b),
+            }
+        }
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        _ => Ordering::Equal,
+    }
 }
@@ -4,18 +4,37 @@
 //! dynamic resource handlers that can be configured at runtime with
 //! custom attribute handlers, mappers, and methods.
 
+use crate::error::ValidationError;
+use crate::resource::RequestContext;
 use crate::schema::Schema;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Transformation applied to a resource's JSON as it is loaded or saved.
+///
+/// Hooks receive the request context, including any tenant context, and may
+/// reject the resource with a [`ValidationError`], which surfaces to the
+/// caller as [`ScimError::Validation`](crate::ScimError::Validation).
+pub type ResourceHook =
+    Arc<dyn Fn(&mut Value, &RequestContext) -> Result<(), ValidationError> + Send + Sync>;
 
 /// Handler for a specific resource type containing its schema
 #[derive(Clone)]
 pub struct ResourceHandler {
     pub schema: Schema,
+    /// Runs on every resource the provider returns, before it reaches the caller
+    pub on_load: Option<ResourceHook>,
+    /// Runs on create and replace payloads, and on patched resources, before
+    /// schema validation and storage
+    pub on_save: Option<ResourceHook>,
 }
 
 impl std::fmt::Debug for ResourceHandler {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ResourceHandler")
             .field("schema", &self.schema)
+            .field("on_load", &self.on_load.is_some())
+            .field("on_save", &self.on_save.is_some())
             .finish()
     }
 }
@@ -23,16 +42,44 @@ impl std::fmt::Debug for ResourceHandler {
 /// Builder for creating resource handlers
 pub struct SchemaResourceBuilder {
     schema: Schema,
+    on_load: Option<ResourceHook>,
+    on_save: Option<ResourceHook>,
 }
 
 impl SchemaResourceBuilder {
     pub fn new(schema: Schema) -> Self {
-        Self { schema }
+        Self {
+            schema,
+            on_load: None,
+            on_save: None,
+        }
+    }
+
+    /// Transform resources after they are read from the provider.
+    pub fn on_load<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&mut Value, &RequestContext) -> Result<(), ValidationError> + Send + Sync + 'static,
+    {
+        self.on_load = Some(Arc::new(hook));
+        self
+    }
+
+    /// Transform create and replace payloads before they are validated and
+    /// stored. For PATCH, the hook runs on the resource with the operations
+    /// applied, before it is checked and stored.
+    pub fn on_save<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&mut Value, &RequestContext) -> Result<(), ValidationError> + Send + Sync + 'static,
+    {
+        self.on_save = Some(Arc::new(hook));
+        self
     }
 
     pub fn build(self) -> ResourceHandler {
         ResourceHandler {
             schema: self.schema,
+            on_load: self.on_load,
+            on_save: self.on_save,
         }
     }
 }
@@ -35,18 +35,21 @@ pub mod value_objects;
 pub mod version;
 
 // Re-export all public types to maintain API compatibility
-pub use context::{ListQuery, RequestContext};
+pub use context::{ListQuery, RequestContext, SortOrder};
 pub use resource::Resource;
+pub use serialization::{SchemaOrdering, SerializationOptions};
 pub use tenant::{IsolationLevel, TenantContext, TenantPermissions};
 // Re-export ScimOperation from multi_tenant module for backward compatibility
 pub use crate::multi_tenant::ScimOperation;
-pub use handlers::{ResourceHandler, SchemaResourceBuilder};
+pub use handlers::{ResourceHandler, ResourceHook, SchemaResourceBuilder};
 pub use mapper::{DatabaseMapper, SchemaMapper};
 pub use value_objects::{
-    Address, EmailAddress, ExternalId, Meta, Name, PhoneNumber, ResourceId, SchemaUri, UserName,
+    Address, EmailAddress, ExternalId, Meta, Name, PhoneNumber, ResourceId, SchemaUri,
+    TimestampPrecision, UserName,
 };
 pub use version::{
     ConditionalResult, HttpVersion, RawVersion, ScimVersion, VersionConflict, VersionError,
+    VersionKind,
 };
 pub use versioned::VersionedResource;
 
@@ -140,6 +143,11 @@ mod tests {
         });
         let default_resource = Resource::from_json("User".to_string(), no_active_data).unwrap();
         assert!(default_resource.is_active()); // Default to true
+
+        // The User default does not apply to other resource types
+        let group =
+            Resource::from_json("Group".to_string(), json!({"displayName": "Admins"})).unwrap();
+        assert!(!group.is_active());
     }
 
     #[test]
@@ -274,6 +282,51 @@ mod tests {
         assert!(json_output.get("meta").is_some());
     }
 
+    #[test]
+    fn test_meta_extra_fields_round_trip() {
+        use crate::resource::value_objects::Meta;
+        use chrono::Utc;
+
+        let resource = Resource::from_json(
+            "User".to_string(),
+            json!({
+                "userName": "testuser",
+                "meta": {
+                    "resourceType": "User",
+                    "created": "2023-01-01T00:00:00Z",
+                    "lastModified": "2023-01-01T00:00:00Z",
+                    "idpSource": "okta",
+                    "syncHints": {"priority": 1}
+                }
+            }),
+        )
+        .unwrap();
+
+        let meta = resource.get_meta().unwrap();
+        assert_eq!(meta.extra()["idpSource"], "okta");
+        let json_output = resource.to_json().unwrap();
+        assert_eq!(json_output["meta"]["idpSource"], "okta");
+        assert_eq!(json_output["meta"]["syncHints"]["priority"], 1);
+
+        // Extras of an incomplete meta are kept until metadata is set, and
+        // typed fields always come from the new metadata
+        let mut resource = Resource::from_json(
+            "User".to_string(),
+            json!({
+                "userName": "testuser",
+                "meta": {"created": "not-a-server-value", "idpSource": "okta"}
+            }),
+        )
+        .unwrap();
+        assert!(resource.get_meta().is_none());
+
+        let now = Utc::now();
+        resource.set_meta(Meta::new_simple("User".to_string(), now, now).unwrap());
+        let json_output = resource.to_json().unwrap();
+        assert_eq!(json_output["meta"]["idpSource"], "okta");
+        assert_eq!(json_output["meta"]["created"], json!(now));
+    }
+
     #[test]
     fn test_create_meta() {
         let mut resource = Resource::from_json(
@@ -326,6 +379,85 @@ mod tests {
         assert_eq!(resource.get_meta().unwrap().created(), now);
     }
 
+    #[test]
+    fn test_resource_diff_ignores_meta() {
+        let user = |data: serde_json::Value| Resource::from_json("User".to_string(), data).unwrap();
+        let mut original = user(json!({
+            "id": "123",
+            "userName": "jdoe",
+            "displayName": "John Doe",
+            "title": "Engineer"
+        }));
+        original.create_meta("https://example.com").unwrap();
+
+        let same = user(json!({
+            "id": "123",
+            "userName": "jdoe",
+            "displayName": "John Doe",
+            "title": "Engineer"
+        }));
+        assert!(original.diff(&same).unwrap().is_empty());
+
+        let changed = user(json!({
+            "id": "123",
+            "userName": "jdoe",
+            "displayName": "Johnny",
+            "nickName": "JD"
+        }));
+        assert_eq!(
+            original.diff(&changed).unwrap(),
+            ["displayName", "nickName", "title"]
+        );
+    }
+
+    #[test]
+    fn test_from_json_lenient_collects_recoverable_errors() {
+        use crate::error::ValidationError;
+
+        let data = json!({
+            "id": "123",
+            "userName": "jdoe",
+            "displayName": "John Doe",
+            "meta": {
+                "resourceType": "User",
+                "created": "yesterday",
+                "lastModified": "2024-01-01T00:00:00Z"
+            },
+            "phoneNumbers": [{"value": "+1-555-0100", "type": "beeper"}],
+            "emails": [{"value": "jdoe@example.com", "type": "work"}],
+            "x509Certificates": [{"value": "not base64!"}]
+        });
+        assert!(Resource::from_json("User".to_string(), data.clone()).is_err());
+
+        let (resource, issues) = Resource::from_json_lenient("User".to_string(), data).unwrap();
+        assert_eq!(resource.get_id(), Some("123"));
+        assert_eq!(resource.get_username(), Some("jdoe"));
+        assert!(resource.get_emails().is_some());
+        assert_eq!(
+            resource.get_attribute("displayName"),
+            Some(&json!("John Doe"))
+        );
+        assert!(resource.get_meta().is_none());
+        assert!(resource.phone_numbers.is_none());
+        assert!(resource.get_attribute("x509Certificates").is_none());
+
+        assert_eq!(issues.len(), 3);
+        assert!(matches!(
+            &issues[0],
+            ValidationError::InvalidCanonicalValue { attribute, value, .. }
+                if attribute == "phoneNumbers.type" && value == "beeper"
+        ));
+        assert!(matches!(issues[1], ValidationError::InvalidCreatedDateTime));
+        assert!(matches!(
+            issues[2],
+            ValidationError::InvalidBinaryData { .. }
+        ));
+
+        // Unparseable required fields still fail
+        let invalid = json!({"userName": 42, "name": "not an object"});
+        assert!(Resource::from_json_lenient("User".to_string(), invalid).is_err());
+    }
+
     #[test]
     fn test_meta_serialization_in_to_json() {
         use crate::resource::value_objects::Meta;
@@ -4,13 +4,17 @@
 //! for creating, validating, and manipulating SCIM resources with type safety
 //! for core attributes while maintaining JSON flexibility for extensions.
 
-use crate::error::{ValidationError, ValidationResult};
+use crate::error::{ValidationError, ValidationResult, check_json_depth};
+use crate::resource::serialization::{SchemaOrdering, SerializationOptions, write_canonical};
 use crate::resource::value_objects::{
-    Address, EmailAddress, ExternalId, GroupMembers, Meta, MultiValuedAddresses, MultiValuedEmails,
-    MultiValuedPhoneNumbers, Name, PhoneNumber, ResourceId, SchemaUri, UserName,
+    Address, EmailAddress, ExtensionAttributeValue, ExtensionCollection, ExternalId, GroupMembers,
+    Meta, MultiValuedAddresses, MultiValuedEmails, MultiValuedPhoneNumbers, Name, PhoneNumber,
+    ResourceId, SchemaUri, UserName,
 };
 use crate::resource::version::RawVersion;
+use crate::schema::{AttributeDefinition, Schema, SchemaRegistry, binary, embedded};
 
+use serde::de::DeserializeOwned;
 use serde_json::{Map, Value};
 
 /// Generic SCIM resource representation with type-safe core attributes.
@@ -74,6 +78,7 @@ impl Resource {
     /// }
     /// ```
     pub fn from_json(resource_type: String, data: Value) -> ValidationResult<Self> {
+        check_json_depth(&data)?;
         let obj = data
             .as_object()
             .ok_or_else(|| ValidationError::custom("Resource must be a JSON object"))?;
@@ -89,6 +94,7 @@ impl Resource {
         let phone_numbers = Self::extract_phone_numbers(obj)?;
         let emails = Self::extract_emails(obj)?;
         let members = Self::extract_members(obj)?;
+        Self::validate_binary_attributes(&resource_type, obj)?;
 
         // Collect remaining attributes (excluding core primitives)
         let mut attributes = obj.clone();
@@ -97,11 +103,23 @@ impl Resource {
         attributes.remove("externalId");
         attributes.remove("userName");
         attributes.remove("meta");
+        if meta.is_none() {
+            // Keep unrecognized sub-attributes of an incomplete meta until
+            // server-managed metadata is set
+            let extra = Self::raw_meta_extra(obj.get("meta"));
+            if !extra.is_empty() {
+                attributes.insert("meta".to_string(), Value::Object(extra));
+            }
+        }
         attributes.remove("name");
-        attributes.remove("addresses");
-        attributes.remove("phoneNumbers");
-        attributes.remove("emails");
-        attributes.remove("members");
+
+        // An explicitly empty multi-valued attribute has no value object, so it is
+        // kept as `[]` to stay distinguishable from an absent attribute
+        for key in ["addresses", "phoneNumbers", "emails", "members"] {
+            if !matches!(attributes.get(key), Some(Value::Array(values)) if values.is_empty()) {
+                attributes.remove(key);
+            }
+        }
 
         Ok(Self {
             resource_type,
@@ -119,6 +137,124 @@ impl Resource {
         })
     }
 
+    /// Create a resource from JSON data, collecting recoverable problems
+    /// instead of failing on the first one.
+    ///
+    /// Problems with optional attributes, such as an unparseable `meta`
+    /// timestamp, a malformed `name`, a non-canonical `type` in a
+    /// multi-valued attribute or invalid binary data, are returned alongside
+    /// the resource and the offending attribute is left out of it. Problems
+    /// with `id`, `schemas` or `userName` still fail as in
+    /// [`from_json`](Self::from_json).
+    ///
+    /// # Example
+    /// ```rust
+    /// use scim_server::Resource;
+    /// use serde_json::json;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let user_data = json!({
+    ///         "userName": "jdoe",
+    ///         "phoneNumbers": [{"value": "+1-555-0100", "type": "beeper"}]
+    ///     });
+    ///     let (resource, issues) = Resource::from_json_lenient("User".to_string(), user_data)?;
+    ///     assert_eq!(resource.get_username(), Some("jdoe"));
+    ///     assert_eq!(issues.len(), 1);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_json_lenient(
+        resource_type: String,
+        data: Value,
+    ) -> ValidationResult<(Self, Vec<ValidationError>)> {
+        let Value::Object(mut obj) = data else {
+            return Err(ValidationError::custom("Resource must be a JSON object"));
+        };
+
+        let mut issues = Vec::new();
+        Self::drop_non_canonical_types(
+            &mut obj,
+            "phoneNumbers",
+            PhoneNumber::CANONICAL_TYPES,
+            &mut issues,
+        );
+        Self::drop_non_canonical_types(
+            &mut obj,
+            "addresses",
+            Address::CANONICAL_TYPES,
+            &mut issues,
+        );
+
+        type Check = fn(&Map<String, Value>) -> ValidationResult<()>;
+        let checks: [(&str, Check); 7] = [
+            ("externalId", |obj| Self::extract_external_id(obj).map(drop)),
+            ("meta", |obj| {
+                Self::extract_meta(&Value::Object(obj.clone())).map(drop)
+            }),
+            ("name", |obj| Self::extract_name(obj).map(drop)),
+            ("addresses", |obj| Self::extract_addresses(obj).map(drop)),
+            ("phoneNumbers", |obj| {
+                Self::extract_phone_numbers(obj).map(drop)
+            }),
+            ("emails", |obj| Self::extract_emails(obj).map(drop)),
+            ("members", |obj| Self::extract_members(obj).map(drop)),
+        ];
+        for (key, check) in checks {
+            if let Err(error) = check(&obj) {
+                obj.remove(key);
+                issues.push(error);
+            }
+        }
+
+        // Binary errors name the offending path, e.g. `x509Certificates.value`
+        while let Err(error) = Self::validate_binary_attributes(&resource_type, &obj) {
+            let ValidationError::InvalidBinaryData { attribute, .. } = &error else {
+                return Err(error);
+            };
+            let key = attribute.split('.').next().unwrap_or_default().to_string();
+            if obj.remove(&key).is_none() {
+                return Err(error);
+            }
+            issues.push(error);
+        }
+
+        let resource = Self::from_json(resource_type, Value::Object(obj))?;
+        Ok((resource, issues))
+    }
+
+    /// Remove values of a multi-valued attribute whose `type` is not one of
+    /// `allowed`, recording each as an issue. An attribute left without
+    /// values is removed.
+    fn drop_non_canonical_types(
+        obj: &mut Map<String, Value>,
+        key: &str,
+        allowed: &[&str],
+        issues: &mut Vec<ValidationError>,
+    ) {
+        let Some(Value::Array(values)) = obj.get_mut(key) else {
+            return;
+        };
+        if values.is_empty() {
+            return;
+        }
+
+        values.retain(|value| match value.get("type").and_then(Value::as_str) {
+            Some(value_type) if !allowed.contains(&value_type) => {
+                issues.push(ValidationError::InvalidCanonicalValue {
+                    attribute: format!("{}.type", key),
+                    value: value_type.to_string(),
+                    allowed: allowed.iter().map(|allowed| allowed.to_string()).collect(),
+                });
+                false
+            }
+            _ => true,
+        });
+        if values.is_empty() {
+            obj.remove(key);
+        }
+    }
+
     /// Create a new resource with validated core fields.
     ///
     /// This is the preferred constructor for new resources where core fields
@@ -195,21 +331,7 @@ impl Resource {
         resource_type: &str,
     ) -> ValidationResult<Vec<SchemaUri>> {
         if let Some(schemas_value) = obj.get("schemas") {
-            if let Some(schemas_array) = schemas_value.as_array() {
-                if schemas_array.is_empty() {
-                    return Err(ValidationError::EmptySchemas);
-                }
-
-                let mut schemas = Vec::new();
-                for schema_value in schemas_array {
-                    if let Some(uri_str) = schema_value.as_str() {
-                        schemas.push(SchemaUri::new(uri_str.to_string())?);
-                    }
-                }
-                if !schemas.is_empty() {
-                    return Ok(schemas);
-                }
-            }
+            return SchemaUri::parse_schemas(schemas_value);
         }
 
         // Default schema based on resource type
@@ -253,8 +375,7 @@ impl Resource {
         if let Some(name_value) = obj.get("name") {
             if let Some(_) = name_value.as_object() {
                 // Deserialize using serde
-                let name: Name = serde_json::from_value(name_value.clone())
-                    .map_err(|e| ValidationError::custom(format!("Invalid name format: {}", e)))?;
+                let name: Name = deserialize_attribute(name_value, "name")?;
                 return Ok(Some(name));
             } else {
                 return Err(ValidationError::custom(
@@ -270,14 +391,11 @@ impl Resource {
         obj: &Map<String, Value>,
     ) -> ValidationResult<Option<MultiValuedAddresses>> {
         if let Some(addresses_value) = obj.get("addresses") {
-            if let Some(_) = addresses_value.as_array() {
-                // Deserialize using serde
-                let addresses: Vec<Address> = serde_json::from_value(addresses_value.clone())
-                    .map_err(|e| {
-                        ValidationError::custom(format!("Invalid addresses format: {}", e))
-                    })?;
+            if let Some(values) = addresses_value.as_array() {
+                let addresses: Vec<Address> = deserialize_elements(values, "addresses")?;
                 if !addresses.is_empty() {
-                    let multi_addresses = MultiValuedAddresses::new(addresses)?;
+                    let multi_addresses = MultiValuedAddresses::new(addresses)?
+                        .with_primary_where(Address::is_primary);
                     return Ok(Some(multi_addresses));
                 }
             } else {
@@ -294,14 +412,11 @@ impl Resource {
         obj: &Map<String, Value>,
     ) -> ValidationResult<Option<MultiValuedPhoneNumbers>> {
         if let Some(phones_value) = obj.get("phoneNumbers") {
-            if let Some(_) = phones_value.as_array() {
-                // Deserialize using serde
-                let phone_numbers: Vec<PhoneNumber> = serde_json::from_value(phones_value.clone())
-                    .map_err(|e| {
-                        ValidationError::custom(format!("Invalid phoneNumbers format: {}", e))
-                    })?;
+            if let Some(values) = phones_value.as_array() {
+                let phone_numbers: Vec<PhoneNumber> = deserialize_elements(values, "phoneNumbers")?;
                 if !phone_numbers.is_empty() {
-                    let multi_phones = MultiValuedPhoneNumbers::new(phone_numbers)?;
+                    let multi_phones = MultiValuedPhoneNumbers::new(phone_numbers)?
+                        .with_primary_where(PhoneNumber::is_primary);
                     return Ok(Some(multi_phones));
                 }
             } else {
@@ -316,14 +431,11 @@ impl Resource {
     /// Extract and validate email addresses from JSON
     fn extract_emails(obj: &Map<String, Value>) -> ValidationResult<Option<MultiValuedEmails>> {
         if let Some(emails_value) = obj.get("emails") {
-            if let Some(_) = emails_value.as_array() {
-                // Deserialize using serde
-                let emails: Vec<EmailAddress> = serde_json::from_value(emails_value.clone())
-                    .map_err(|e| {
-                        ValidationError::custom(format!("Invalid emails format: {}", e))
-                    })?;
+            if let Some(values) = emails_value.as_array() {
+                let emails: Vec<EmailAddress> = deserialize_elements(values, "emails")?;
                 if !emails.is_empty() {
-                    let multi_emails = MultiValuedEmails::new(emails)?;
+                    let multi_emails = MultiValuedEmails::new(emails)?
+                        .with_primary_where(EmailAddress::is_primary);
                     return Ok(Some(multi_emails));
                 }
             } else {
@@ -335,6 +447,24 @@ impl Resource {
         Ok(None)
     }
 
+    /// Validate base64 values of the core schema's binary attributes, such as
+    /// `x509Certificates.value`. Extension schemas' binary attributes are
+    /// checked by schema validation.
+    fn validate_binary_attributes(
+        resource_type: &str,
+        obj: &Map<String, Value>,
+    ) -> ValidationResult<()> {
+        match resource_type {
+            "User" => {
+                let schema = embedded::parsed_core_user_schema().map_err(|e| {
+                    ValidationError::custom(format!("Invalid embedded User schema: {}", e))
+                })?;
+                binary::validate_binary_attributes(&schema.attributes, obj)
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Extract and validate group members from JSON
     fn extract_members(obj: &Map<String, Value>) -> ValidationResult<Option<GroupMembers>> {
         if let Some(members_value) = obj.get("members") {
@@ -506,6 +636,64 @@ impl Resource {
         self.attributes.get(attribute_name)
     }
 
+    /// Decode a top-level binary attribute from its base64 value.
+    ///
+    /// Returns `Ok(None)` if the attribute is absent or not a string.
+    pub fn get_binary_attribute(&self, attribute_name: &str) -> ValidationResult<Option<Vec<u8>>> {
+        self.get_attribute(attribute_name)
+            .and_then(Value::as_str)
+            .map(|encoded| binary::decode_binary(attribute_name, encoded))
+            .transpose()
+    }
+
+    /// Decode the DER bytes of the User's `x509Certificates`.
+    pub fn get_x509_certificates(&self) -> ValidationResult<Vec<Vec<u8>>> {
+        let Some(certificates) = self
+            .get_attribute("x509Certificates")
+            .and_then(Value::as_array)
+        else {
+            return Ok(Vec::new());
+        };
+
+        certificates
+            .iter()
+            .filter_map(|certificate| certificate.get("value").and_then(Value::as_str))
+            .map(|encoded| binary::decode_binary("x509Certificates.value", encoded))
+            .collect()
+    }
+
+    /// Validate this resource against an explicit set of schemas.
+    ///
+    /// `schemas[0]` is the base schema, which the resource must declare in
+    /// `schemas`; the rest are extensions. Every URN the resource declares
+    /// must be among `schemas`. An extension's attributes are read from the
+    /// object under its URN and validated, with its required attributes
+    /// enforced, only when the resource declares that URN; data for an
+    /// undeclared extension is rejected.
+    ///
+    /// # Example
+    /// ```rust
+    /// use scim_server::Resource;
+    /// use scim_server::schema::SchemaRegistry;
+    /// use serde_json::json;
+    ///
+    /// let registry = SchemaRegistry::new().unwrap();
+    /// let user_schema = registry.get_user_schema();
+    ///
+    /// let user = Resource::from_json(
+    ///     "User".to_string(),
+    ///     json!({
+    ///         "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+    ///         "userName": "jdoe"
+    ///     }),
+    /// )
+    /// .unwrap();
+    /// assert!(user.validate_against(&[user_schema]).is_ok());
+    /// ```
+    pub fn validate_against(&self, schemas: &[&Schema]) -> ValidationResult<()> {
+        SchemaRegistry::embedded().validate_resource_against(self, schemas)
+    }
+
     /// Set a specific attribute value in the extended attributes.
     ///
     /// # Arguments
@@ -515,6 +703,138 @@ impl Resource {
         self.attributes.insert(attribute_name, value);
     }
 
+    /// Get an extension attribute, validated against the extension's schema.
+    ///
+    /// `extension` is the extension schema registered for the resource type,
+    /// e.g. from [`ScimServer::list_registered_schemas_for`]; the value is read
+    /// from the object under its URN. Attribute names match case-insensitively.
+    /// Returns `Ok(None)` when the resource has no value for the attribute.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::UnknownAttribute`] if the extension does not
+    /// define `attribute`, or the validation error for a stored value that
+    /// does not match the attribute's definition.
+    ///
+    /// [`ScimServer::list_registered_schemas_for`]: crate::ScimServer::list_registered_schemas_for
+    pub fn get_extension(
+        &self,
+        extension: &Schema,
+        attribute: &str,
+    ) -> ValidationResult<Option<&Value>> {
+        let definition = extension_attribute(extension, attribute)?;
+        let Some(value) = self
+            .attributes
+            .get(&extension.id)
+            .and_then(Value::as_object)
+            .and_then(|object| {
+                object
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(&definition.name))
+            })
+            .map(|(_, value)| value)
+        else {
+            return Ok(None);
+        };
+
+        ExtensionAttributeValue::new(
+            SchemaUri::new(extension.id.clone())?,
+            definition.name.clone(),
+            value.clone(),
+            Some(definition.clone()),
+        )?;
+        Ok(Some(value))
+    }
+
+    /// Set an extension attribute, validated against the extension's schema.
+    ///
+    /// The value is stored under the attribute's schema name in the object
+    /// keyed by the extension's URN, and the URN is added to `schemas` if the
+    /// resource does not declare it yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::UnknownAttribute`] if the extension does not
+    /// define `attribute`, or the validation error for a value that does not
+    /// match the attribute's definition. The resource is unchanged on error.
+    ///
+    /// # Example
+    /// ```rust
+    /// use scim_server::Resource;
+    /// use scim_server::schema::{AttributeDefinition, Schema};
+    /// use serde_json::json;
+    ///
+    /// let enterprise = &Schema {
+    ///     id: "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User".to_string(),
+    ///     name: "EnterpriseUser".to_string(),
+    ///     description: "Enterprise User".to_string(),
+    ///     attributes: vec![AttributeDefinition {
+    ///         name: "employeeNumber".to_string(),
+    ///         ..Default::default()
+    ///     }],
+    /// };
+    ///
+    /// let mut user =
+    ///     Resource::from_json("User".to_string(), json!({"userName": "bjensen"})).unwrap();
+    /// user.set_extension(enterprise, "employeeNumber", json!("701984")).unwrap();
+    /// assert!(user.set_extension(enterprise, "employeeNumber", json!(701984)).is_err());
+    ///
+    /// assert_eq!(
+    ///     user.get_extension(enterprise, "employeeNumber").unwrap(),
+    ///     Some(&json!("701984"))
+    /// );
+    /// assert!(user.get_schemas().contains(&enterprise.id));
+    /// ```
+    pub fn set_extension(
+        &mut self,
+        extension: &Schema,
+        attribute: &str,
+        value: Value,
+    ) -> ValidationResult<()> {
+        let definition = extension_attribute(extension, attribute)?;
+        let schema_uri = SchemaUri::new(extension.id.clone())?;
+        let value = ExtensionAttributeValue::new(
+            schema_uri.clone(),
+            definition.name.clone(),
+            value,
+            Some(definition.clone()),
+        )?
+        .value()
+        .clone();
+
+        let object = self
+            .attributes
+            .entry(extension.id.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !object.is_object() {
+            *object = Value::Object(Map::new());
+        }
+        if let Value::Object(object) = object {
+            object.retain(|name, _| !name.eq_ignore_ascii_case(&definition.name));
+            object.insert(definition.name.clone(), value);
+        }
+
+        if !self.schemas.contains(&schema_uri) {
+            self.schemas.push(schema_uri);
+        }
+        Ok(())
+    }
+
+    /// Collect the resource's extension data, grouped by schema URN.
+    ///
+    /// Every declared non-base schema with an object under its URN is
+    /// included. Values are not validated; use
+    /// [`get_extension`](Self::get_extension) to read them against a schema.
+    pub fn extensions(&self) -> ValidationResult<ExtensionCollection> {
+        let mut extensions = serde_json::Map::new();
+        for schema_uri in self.schemas.iter().skip(1) {
+            if let Some(data @ Value::Object(_)) = self.attributes.get(schema_uri.as_str()) {
+                extensions.insert(schema_uri.as_str().to_string(), data.clone());
+            }
+        }
+        ExtensionCollection::from_json(&Value::Object(extensions))
+    }
+
     /// Get the schemas associated with this resource.
     pub fn get_schemas(&self) -> Vec<String> {
         self.schemas
@@ -557,10 +877,9 @@ impl Resource {
 
         // Generate version from resource content using content-based versioning
         let version = if self.id.is_some() {
-            let resource_json = self.to_json().unwrap_or_default();
-            let content_bytes = resource_json.to_string().as_bytes().to_vec();
-            let scim_version = RawVersion::from_content(&content_bytes);
-            Some(scim_version.as_str().to_string())
+            self.content_version()
+                .ok()
+                .map(|version| version.as_str().to_string())
         } else {
             None
         };
@@ -579,19 +898,158 @@ impl Resource {
 
     /// Check if this resource is active.
     ///
-    /// Returns the value of the "active" field, defaulting to true if not present.
+    /// Returns the value of the "active" field. A User without one falls
+    /// back to the core User schema's declared default; other resources
+    /// without one are inactive.
     pub fn is_active(&self) -> bool {
         self.attributes
             .get("active")
+            .or_else(|| {
+                (self.resource_type == "User")
+                    .then(embedded::parsed_core_user_schema)
+                    .and_then(Result::ok)
+                    .and_then(|schema| schema.default_value("active"))
+            })
             .and_then(|v| v.as_bool())
-            .unwrap_or(true)
+            .unwrap_or(false)
     }
 
     /// Convert the resource to JSON format for serialization.
     ///
     /// This combines the type-safe core fields with the extended attributes
-    /// into a single JSON object.
+    /// into a single JSON object. Equivalent to [`to_json_with`](Self::to_json_with)
+    /// using default [`SerializationOptions`].
     pub fn to_json(&self) -> ValidationResult<Value> {
+        self.to_json_with(&SerializationOptions::default())
+    }
+
+    /// Convert the resource to JSON using the given serialization options.
+    ///
+    /// Options control `meta` inclusion, `schemas` ordering and attribute
+    /// projection (`attributes`/`excludedAttributes`).
+    pub fn to_json_with(&self, options: &SerializationOptions) -> ValidationResult<Value> {
+        let mut json = self.build_json()?;
+        options.apply(&mut json);
+        Ok(json)
+    }
+
+    /// Serialize the resource to a JSON string using the given serialization options.
+    ///
+    /// Produces indented output when [`SerializationOptions::pretty`] is set,
+    /// compact output otherwise.
+    pub fn to_json_string_with(&self, options: &SerializationOptions) -> ValidationResult<String> {
+        let json = self.to_json_with(options)?;
+        let result = if options.pretty {
+            serde_json::to_string_pretty(&json)
+        } else {
+            serde_json::to_string(&json)
+        };
+        result.map_err(|e| ValidationError::custom(format!("Serialization error: {}", e)))
+    }
+
+    /// Deterministic byte representation of this resource's content, used
+    /// for version computation.
+    ///
+    /// Covers every attribute, including extension data under its schema
+    /// URN, and the `schemas` array, sorted. `meta` is excluded. Object keys
+    /// are sorted, values of the core schema's `dateTime` attributes
+    /// normalized to UTC and integral numbers written
+    /// as integers, so the bytes do not depend on attribute order, map
+    /// implementation or how the resource was last serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::resource::Resource;
+    /// use serde_json::json;
+    ///
+    /// let a = Resource::from_json("User".to_string(), json!({
+    ///     "userName": "bjensen",
+    ///     "name": {"givenName": "Barbara", "familyName": "Jensen"},
+    /// })).unwrap();
+    /// let b = Resource::from_json("User".to_string(), json!({
+    ///     "name": {"familyName": "Jensen", "givenName": "Barbara"},
+    ///     "userName": "bjensen",
+    /// })).unwrap();
+    /// assert_eq!(a.canonicalize().unwrap(), b.canonicalize().unwrap());
+    /// ```
+    pub fn canonicalize(&self) -> ValidationResult<Vec<u8>> {
+        let schemas: Vec<&Schema> = embedded::parsed_core_schema(&self.resource_type)
+            .into_iter()
+            .collect();
+        self.canonicalize_with(&schemas)
+    }
+
+    /// Canonical bytes of this resource, normalizing the `dateTime`
+    /// attributes `schemas` define.
+    ///
+    /// Like [`canonicalize`](Self::canonicalize), for resources whose schema
+    /// or extensions declare `dateTime` attributes beyond the core schema.
+    pub fn canonicalize_with(&self, schemas: &[&Schema]) -> ValidationResult<Vec<u8>> {
+        let options = SerializationOptions::new()
+            .with_meta(false)
+            .with_schema_ordering(SchemaOrdering::Alphabetical);
+        let mut bytes = Vec::new();
+        write_canonical(&self.to_json_with(&options)?, schemas, &mut bytes);
+        Ok(bytes)
+    }
+
+    /// Compute a content-based version for this resource.
+    ///
+    /// The hash is taken over [`canonicalize`](Self::canonicalize), so
+    /// resources that differ only in key or schema order, or in the
+    /// representation of equal numbers or core `dateTime` values, share a
+    /// version.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::resource::Resource;
+    /// use serde_json::json;
+    ///
+    /// let enterprise = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+    /// let user = |number: &str| {
+    ///     Resource::from_json("User".to_string(), json!({
+    ///         "userName": "bjensen",
+    ///         enterprise: {"employeeNumber": number}
+    ///     }))
+    ///     .unwrap()
+    /// };
+    /// assert_ne!(
+    ///     user("701984").content_version().unwrap(),
+    ///     user("701985").content_version().unwrap()
+    /// );
+    /// ```
+    pub fn content_version(&self) -> ValidationResult<RawVersion> {
+        Ok(RawVersion::from_content(&self.canonicalize()?))
+    }
+
+    /// Top-level attributes whose values differ between this resource and
+    /// `other`, sorted by name.
+    ///
+    /// Attributes present in only one of the resources are included. `meta`
+    /// is server-managed and never compared, so two resources differing only
+    /// in timestamps or version have no differences.
+    pub fn diff(&self, other: &Resource) -> ValidationResult<Vec<String>> {
+        let options = SerializationOptions::new().with_meta(false);
+        let (Value::Object(ours), Value::Object(theirs)) =
+            (self.to_json_with(&options)?, other.to_json_with(&options)?)
+        else {
+            return Ok(Vec::new());
+        };
+
+        let mut changed: Vec<String> = ours
+            .iter()
+            .filter(|(name, value)| theirs.get(*name) != Some(*value))
+            .chain(theirs.iter().filter(|(name, _)| !ours.contains_key(*name)))
+            .map(|(name, _)| name.clone())
+            .collect();
+        changed.sort_unstable();
+        Ok(changed)
+    }
+
+    /// Build the full JSON representation of the resource.
+    fn build_json(&self) -> ValidationResult<Value> {
         let mut result = self.attributes.clone();
 
         // Add core fields
@@ -737,7 +1195,8 @@ impl Resource {
                         last_modified_dt,
                         location,
                         version,
-                    )?;
+                    )?
+                    .with_extra(meta_obj.clone());
                     Ok(Some(meta))
                 } else {
                     // Meta exists but is incomplete - ignore it for backward compatibility
@@ -751,13 +1210,45 @@ impl Resource {
         }
     }
 
+    /// Unrecognized meta sub-attributes carried by this resource, whether or
+    /// not it has complete typed metadata.
+    pub fn meta_extra(&self) -> Map<String, Value> {
+        match &self.meta {
+            Some(meta) => meta.extra().clone(),
+            None => Self::raw_meta_extra(self.attributes.get("meta")),
+        }
+    }
+
+    /// Unrecognized sub-attributes of a raw `meta` value.
+    fn raw_meta_extra(meta: Option<&Value>) -> Map<String, Value> {
+        meta.and_then(Value::as_object)
+            .map(|meta| {
+                meta.iter()
+                    .filter(|(name, _)| !Meta::is_typed_field(name))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Get the meta attributes if present.
     pub fn get_meta(&self) -> Option<&Meta> {
         self.meta.as_ref()
     }
 
     /// Set meta attributes for the resource.
+    ///
+    /// Unrecognized meta sub-attributes already on the resource are kept
+    /// unless `meta` sets the same keys; the typed fields always come from `meta`.
     pub fn set_meta(&mut self, meta: Meta) {
+        let mut extra = self.meta_extra();
+        extra.extend(meta.extra.clone());
+        let meta = Meta {
+            extra: Map::new(),
+            ..meta
+        }
+        .with_extra(extra);
+
         // Update the JSON representation before moving
         let meta_json = serde_json::to_value(&meta).unwrap_or(Value::Null);
         self.set_attribute("meta".to_string(), meta_json);
@@ -785,3 +1276,40 @@ impl Resource {
         }
     }
 }
+
+/// Definition of a top-level attribute of an extension schema.
+fn extension_attribute<'s>(
+    extension: &'s Schema,
+    attribute: &str,
+) -> ValidationResult<&'s AttributeDefinition> {
+    extension
+        .attributes
+        .iter()
+        .find(|definition| definition.name.eq_ignore_ascii_case(attribute))
+        .ok_or_else(|| ValidationError::UnknownAttribute {
+            attribute: attribute.to_string(),
+            schema_id: extension.id.clone(),
+        })
+}
+
+/// Deserialize the attribute at `location`, reporting a structural mismatch
+/// as [`ValidationError::MalformedJson`].
+fn deserialize_attribute<T: DeserializeOwned>(
+    value: &Value,
+    location: &str,
+) -> ValidationResult<T> {
+    T::deserialize(value).map_err(|error| ValidationError::malformed_json(location, &error))
+}
+
+/// Deserialize each element of a multi-valued attribute, reporting the first
+/// malformed one by its index.
+fn deserialize_elements<T: DeserializeOwned>(
+    values: &[Value],
+    attribute: &str,
+) -> ValidationResult<Vec<T>> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| deserialize_attribute(value, &format!("{}[{}]", attribute, index)))
+        .collect()
+}
@@ -2,11 +2,458 @@
 //!
 //! This module provides Serde implementations for the Resource struct,
 //! enabling seamless JSON serialization/deserialization while maintaining
-//! type safety for core attributes.
+//! type safety for core attributes. It also provides [`SerializationOptions`]
+//! and attribute projection so HTTP layers can shape resource output to match
+//! client requests (`Accept`, `attributes`, `excludedAttributes`).
 
 use crate::resource::resource::Resource;
+use crate::schema::{AttributeDefinition, AttributeType, Mutability, Schema};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use serde_json::Value;
+use serde_json::{Map, Value};
+
+/// Attributes that are always returned regardless of attribute projection.
+const ALWAYS_RETURNED: &[&str] = &["id", "schemas", "meta"];
+
+/// Ordering applied to the `schemas` array when serializing a resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaOrdering {
+    /// Keep schemas in the order they are stored on the resource
+    #[default]
+    Preserve,
+    /// Core schemas first, followed by extension schemas in stored order
+    CoreFirst,
+    /// Sort schema URIs alphabetically
+    Alphabetical,
+}
+
+/// Options controlling the JSON shape produced by [`Resource::to_json_with`].
+///
+/// The default options reproduce [`Resource::to_json`] exactly: compact output,
+/// `meta` included, schemas in stored order, and no attribute projection.
+///
+/// # Examples
+///
+/// ```rust
+/// use scim_server::resource::{Resource, SerializationOptions};
+/// use serde_json::json;
+///
+/// let resource = Resource::from_json(
+///     "User".to_string(),
+///     json!({"id": "123", "userName": "jdoe", "displayName": "John Doe"}),
+/// ).unwrap();
+///
+/// let options = SerializationOptions::new()
+///     .with_attributes(vec!["userName".to_string()])
+///     .pretty();
+/// let json = resource.to_json_with(&options).unwrap();
+/// assert!(json.get("displayName").is_none());
+/// assert_eq!(json["id"], "123");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SerializationOptions {
+    /// Emit indented JSON from [`Resource::to_json_string_with`]
+    pub pretty: bool,
+    /// Exclude the `meta` attribute from output
+    pub exclude_meta: bool,
+    /// Ordering applied to the `schemas` array
+    pub schema_ordering: SchemaOrdering,
+    /// Attributes to include (SCIM `attributes` parameter); empty means all
+    pub attributes: Vec<String>,
+    /// Attributes to exclude (SCIM `excludedAttributes` parameter)
+    pub excluded_attributes: Vec<String>,
+}
+
+impl SerializationOptions {
+    /// Create options matching the default serialization.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Emit indented JSON when serializing to a string.
+    pub fn pretty(mut self) -> Self {
+        self.pretty = true;
+        self
+    }
+
+    /// Include or exclude the `meta` attribute.
+    pub fn with_meta(mut self, include_meta: bool) -> Self {
+        self.exclude_meta = !include_meta;
+        self
+    }
+
+    /// Set the ordering applied to the `schemas` array.
+    pub fn with_schema_ordering(mut self, ordering: SchemaOrdering) -> Self {
+        self.schema_ordering = ordering;
+        self
+    }
+
+    /// Set the attributes to include in output.
+    pub fn with_attributes(mut self, attributes: Vec<String>) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Set the attributes to exclude from output.
+    pub fn with_excluded_attributes(mut self, excluded_attributes: Vec<String>) -> Self {
+        self.excluded_attributes = excluded_attributes;
+        self
+    }
+
+    /// Check whether these options request attribute projection.
+    pub fn has_projection(&self) -> bool {
+        !self.attributes.is_empty() || !self.excluded_attributes.is_empty()
+    }
+
+    /// Apply these options to an already-serialized resource.
+    ///
+    /// This allows callers holding resource JSON (for example after `$ref`
+    /// injection) to shape output without re-serializing the resource.
+    pub fn apply(&self, value: &mut Value) {
+        if let Some(obj) = value.as_object_mut() {
+            if self.exclude_meta {
+                obj.remove("meta");
+            }
+            if let Some(Value::Array(schemas)) = obj.get_mut("schemas") {
+                order_schemas(schemas, self.schema_ordering);
+            }
+        }
+        project_attributes(value, &self.attributes, &self.excluded_attributes);
+    }
+}
+
+/// Apply SCIM attribute projection to resource JSON (RFC 7644 Section 3.4.2.5).
+///
+/// When `attributes` is non-empty only the listed attributes are returned;
+/// `excluded_attributes` are then removed. Attribute names are matched
+/// case-insensitively, may use dotted sub-attribute paths (`name.givenName`),
+/// and may be fully qualified with a schema URN. `id`, `schemas` and `meta`
+/// are always returned.
+pub fn project_attributes(
+    value: &mut Value,
+    attributes: &[String],
+    excluded_attributes: &[String],
+) {
+    let Some(obj) = value.as_object_mut() else {
+        return;
+    };
+
+    if !attributes.is_empty() {
+        let paths: Vec<Vec<String>> = attributes
+            .iter()
+            .map(|a| split_attribute_path(obj, a))
+            .collect();
+        let mut projected = Map::new();
+        for (key, val) in obj.iter() {
+            if ALWAYS_RETURNED.contains(&key.as_str()) {
+                projected.insert(key.clone(), val.clone());
+            }
+        }
+        for path in &paths {
+            include_path(obj, &mut projected, path);
+        }
+        *obj = projected;
+    }
+
+    for excluded in excluded_attributes {
+        let path = split_attribute_path(obj, excluded);
+        if path.len() == 1 && ALWAYS_RETURNED.contains(&path[0].as_str()) {
+            continue;
+        }
+        exclude_path(obj, &path);
+    }
+}
+
+/// Remove attributes that are never returned from resource JSON.
+///
+/// An attribute is never returned when it is `writeOnly` or declares
+/// `returned: never` (RFC 7643 Section 7), such as a User's `password`.
+/// Names are matched case-insensitively and sub-attributes of complex values,
+/// single or multi-valued, are stripped too.
+pub fn strip_never_returned(value: &mut Value, attributes: &[AttributeDefinition]) {
+    let Some(obj) = value.as_object_mut() else {
+        return;
+    };
+    for attribute in attributes {
+        let Some(key) = find_key(obj, &attribute.name).cloned() else {
+            continue;
+        };
+        if attribute.mutability == Mutability::WriteOnly
+            || attribute.returned.as_deref() == Some("never")
+        {
+            obj.remove(&key);
+        } else if !attribute.sub_attributes.is_empty() {
+            match obj.get_mut(&key) {
+                Some(Value::Array(entries)) => entries
+                    .iter_mut()
+                    .for_each(|entry| strip_never_returned(entry, &attribute.sub_attributes)),
+                Some(entry) => strip_never_returned(entry, &attribute.sub_attributes),
+                None => {}
+            }
+        }
+    }
+}
+
+/// Size in bytes of the compact JSON serialization of `value`.
+///
+/// The JSON is counted as it is written rather than buffered, so measuring a
+/// large payload does not allocate a copy of it.
+pub fn serialized_size(value: &Value) -> usize {
+    struct Counter(usize);
+
+    impl std::io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    // Serializing a `Value` into an infallible writer cannot fail
+    let _ = serde_json::to_writer(&mut counter, value);
+    counter.0
+}
+
+/// Write the canonical byte representation of resource data to `out`.
+///
+/// The output is compact JSON with object keys sorted by their UTF-8 bytes,
+/// whatever order the map iterates in. Numbers with an integral value are
+/// written as integers, so `1.0` and `1` are written identically. Values of
+/// attributes `schemas` declare as `dateTime` are written as the same instant
+/// in UTC with the shortest exact fractional seconds, so `+01:00` and `Z`
+/// offsets of one instant are written identically; every other string is
+/// written as it is. Array order is kept.
+pub(crate) fn write_canonical(value: &Value, schemas: &[&Schema], out: &mut Vec<u8>) {
+    write_canonical_value(value, CanonicalScope::Resource(schemas), None, out);
+}
+
+/// Where the keys of an object being canonicalized are defined.
+#[derive(Clone, Copy)]
+enum CanonicalScope<'a> {
+    /// Top level of a resource: schema attributes and extension objects
+    Resource(&'a [&'a Schema]),
+    /// Attributes of one schema, or sub-attributes of a complex attribute
+    Attributes(&'a [AttributeDefinition]),
+}
+
+impl<'a> CanonicalScope<'a> {
+    /// Scope and definition of the value under `key`.
+    fn child(self, key: &str) -> (CanonicalScope<'a>, Option<&'a AttributeDefinition>) {
+        let find = |attributes: &'a [AttributeDefinition]| {
+            attributes
+                .iter()
+                .find(|attribute| attribute.name.eq_ignore_ascii_case(key))
+        };
+        let definition = match self {
+            CanonicalScope::Resource(schemas) => {
+                if let Some(extension) = schemas
+                    .iter()
+                    .find(|schema| schema.id.eq_ignore_ascii_case(key))
+                {
+                    return (CanonicalScope::Attributes(&extension.attributes), None);
+                }
+                schemas.iter().find_map(|schema| find(&schema.attributes))
+            }
+            CanonicalScope::Attributes(attributes) => find(attributes),
+        };
+        let sub_attributes = definition.map_or(&[][..], |definition| &definition.sub_attributes);
+        (CanonicalScope::Attributes(sub_attributes), definition)
+    }
+}
+
+fn write_canonical_value(
+    value: &Value,
+    scope: CanonicalScope<'_>,
+    definition: Option<&AttributeDefinition>,
+    out: &mut Vec<u8>,
+) {
+    match value {
+        Value::Object(obj) => {
+            let mut entries: Vec<(&String, &Value)> = obj.iter().collect();
+            entries.sort_unstable_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+            out.push(b'{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_json(&Value::String(key.clone()), out);
+                out.push(b':');
+                let (scope, definition) = scope.child(key);
+                write_canonical_value(value, scope, definition, out);
+            }
+            out.push(b'}');
+        }
+        // Items of a multi-valued attribute share its definition
+        Value::Array(values) => {
+            out.push(b'[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_canonical_value(value, scope, definition, out);
+            }
+            out.push(b']');
+        }
+        Value::String(s)
+            if definition
+                .is_some_and(|definition| definition.data_type == AttributeType::DateTime) =>
+        {
+            match chrono::DateTime::parse_from_rfc3339(s) {
+                Ok(datetime) => {
+                    let utc = datetime
+                        .with_timezone(&chrono::Utc)
+                        .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true);
+                    write_json(&Value::String(utc), out);
+                }
+                Err(_) => write_json(value, out),
+            }
+        }
+        Value::Number(n) => match n.as_f64() {
+            // Integral floats within the exactly representable range
+            Some(f) if n.is_f64() && f.fract() == 0.0 && f.abs() < 9_007_199_254_740_992.0 => {
+                out.extend_from_slice((f as i64).to_string().as_bytes())
+            }
+            _ => write_json(value, out),
+        },
+        Value::String(_) | Value::Bool(_) | Value::Null => write_json(value, out),
+    }
+}
+
+fn write_json(value: &Value, out: &mut Vec<u8>) {
+    // Serializing a `Value` into a `Vec` cannot fail
+    let _ = serde_json::to_writer(out, value);
+}
+
+/// Split an attribute path into segments, resolving schema URN prefixes.
+fn split_attribute_path(obj: &Map<String, Value>, attribute: &str) -> Vec<String> {
+    if attribute.starts_with("urn:") {
+        // Extension attributes live under a top-level key named by their schema URN
+        if let Some(ext_key) = obj
+            .keys()
+            .filter(|k| k.starts_with("urn:"))
+            .filter(|k| attribute.len() > k.len() && attribute[..k.len()].eq_ignore_ascii_case(k))
+            .max_by_key(|k| k.len())
+        {
+            let rest = attribute[ext_key.len()..].trim_start_matches(':');
+            let mut path = vec![ext_key.clone()];
+            path.extend(rest.split('.').map(|s| s.to_string()));
+            return path;
+        }
+        if obj
+            .keys()
+            .any(|k| k.starts_with("urn:") && k.eq_ignore_ascii_case(attribute))
+        {
+            return vec![attribute.to_string()];
+        }
+        // Core attributes qualified with the resource's own schema URN
+        if let Some(pos) = attribute.rfind(':') {
+            return attribute[pos + 1..]
+                .split('.')
+                .map(|s| s.to_string())
+                .collect();
+        }
+    }
+    attribute.split('.').map(|s| s.to_string()).collect()
+}
+
+/// Find the actual key in an object matching an attribute name case-insensitively.
+fn find_key<'a>(obj: &'a Map<String, Value>, name: &str) -> Option<&'a String> {
+    obj.keys().find(|k| k.eq_ignore_ascii_case(name))
+}
+
+/// Copy the value at `path` from `source` into `target`, merging sub-attributes.
+fn include_path(source: &Map<String, Value>, target: &mut Map<String, Value>, path: &[String]) {
+    let Some((head, rest)) = path.split_first() else {
+        return;
+    };
+    let Some(key) = find_key(source, head) else {
+        return;
+    };
+    let value = &source[key];
+
+    if rest.is_empty() {
+        target.insert(key.clone(), value.clone());
+        return;
+    }
+
+    match value {
+        Value::Object(sub_source) => {
+            let entry = target
+                .entry(key.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(sub_target) = entry {
+                include_path(sub_source, sub_target, rest);
+            }
+        }
+        Value::Array(items) => {
+            let existing = match target.remove(key) {
+                Some(Value::Array(existing)) if existing.len() == items.len() => existing,
+                _ => vec![Value::Object(Map::new()); items.len()],
+            };
+            let merged = items
+                .iter()
+                .zip(existing)
+                .map(|(item, mut projected)| {
+                    if let (Value::Object(item_obj), Value::Object(projected_obj)) =
+                        (item, &mut projected)
+                    {
+                        include_path(item_obj, projected_obj, rest);
+                    }
+                    projected
+                })
+                .collect();
+            target.insert(key.clone(), Value::Array(merged));
+        }
+        _ => {}
+    }
+}
+
+/// Remove the value at `path` from `obj`, descending into complex and multi-valued attributes.
+fn exclude_path(obj: &mut Map<String, Value>, path: &[String]) {
+    let Some((head, rest)) = path.split_first() else {
+        return;
+    };
+    let Some(key) = find_key(obj, head).cloned() else {
+        return;
+    };
+
+    if rest.is_empty() {
+        obj.remove(&key);
+        return;
+    }
+
+    match obj.get_mut(&key) {
+        Some(Value::Object(sub)) => exclude_path(sub, rest),
+        Some(Value::Array(items)) => {
+            for item in items.iter_mut() {
+                if let Value::Object(item_obj) = item {
+                    exclude_path(item_obj, rest);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Reorder a `schemas` array according to the requested ordering.
+fn order_schemas(schemas: &mut [Value], ordering: SchemaOrdering) {
+    match ordering {
+        SchemaOrdering::Preserve => {}
+        SchemaOrdering::CoreFirst => {
+            // Stable sort keeps the relative order within core and extension groups
+            schemas.sort_by_key(|s| {
+                !s.as_str()
+                    .is_some_and(|uri| uri.starts_with("urn:ietf:params:scim:schemas:core:"))
+            });
+        }
+        SchemaOrdering::Alphabetical => {
+            schemas.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+        }
+    }
+}
 
 impl Serialize for Resource {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -90,6 +537,134 @@ mod tests {
         assert_eq!(resource.resource_type, "User");
     }
 
+    fn projection_user() -> Resource {
+        Resource::from_json(
+            "User".to_string(),
+            json!({
+                "id": "123",
+                "schemas": [
+                    "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User",
+                    "urn:ietf:params:scim:schemas:core:2.0:User"
+                ],
+                "userName": "jdoe",
+                "displayName": "John Doe",
+                "name": {"givenName": "John", "familyName": "Doe"},
+                "emails": [{"value": "jdoe@example.com", "type": "work"}],
+                "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+                    "employeeNumber": "42",
+                    "department": "Engineering"
+                },
+                "meta": {
+                    "resourceType": "User",
+                    "created": "2024-01-01T00:00:00Z",
+                    "lastModified": "2024-01-01T00:00:00Z"
+                }
+            }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_to_json_with_default_matches_to_json() {
+        let resource = projection_user();
+        assert_eq!(
+            resource.to_json().unwrap(),
+            resource
+                .to_json_with(&SerializationOptions::default())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_to_json_with_meta_and_schema_ordering() {
+        let resource = projection_user();
+        let options = SerializationOptions::new()
+            .with_meta(false)
+            .with_schema_ordering(SchemaOrdering::CoreFirst);
+        let json = resource.to_json_with(&options).unwrap();
+
+        assert!(json.get("meta").is_none());
+        assert_eq!(
+            json["schemas"][0],
+            "urn:ietf:params:scim:schemas:core:2.0:User"
+        );
+    }
+
+    #[test]
+    fn test_to_json_string_with_pretty() {
+        let resource = projection_user();
+        let compact = resource
+            .to_json_string_with(&SerializationOptions::new())
+            .unwrap();
+        let pretty = resource
+            .to_json_string_with(&SerializationOptions::new().pretty())
+            .unwrap();
+
+        assert!(!compact.contains('\n'));
+        assert!(pretty.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<Value>(&compact).unwrap(),
+            serde_json::from_str::<Value>(&pretty).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_projection_include_attributes() {
+        let resource = projection_user();
+        let options = SerializationOptions::new().with_attributes(vec![
+            "username".to_string(),
+            "name.givenName".to_string(),
+            "emails.value".to_string(),
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:employeeNumber".to_string(),
+        ]);
+        let json = resource.to_json_with(&options).unwrap();
+
+        assert_eq!(json["id"], "123");
+        assert!(json.get("meta").is_some());
+        assert_eq!(json["userName"], "jdoe");
+        assert!(json.get("displayName").is_none());
+        assert_eq!(json["name"], json!({"givenName": "John"}));
+        assert_eq!(json["emails"], json!([{"value": "jdoe@example.com"}]));
+        assert_eq!(
+            json["urn:ietf:params:scim:schemas:extension:enterprise:2.0:User"],
+            json!({"employeeNumber": "42"})
+        );
+    }
+
+    #[test]
+    fn test_projection_exclude_attributes() {
+        let resource = projection_user();
+        let options = SerializationOptions::new().with_excluded_attributes(vec![
+            "displayName".to_string(),
+            "name.familyName".to_string(),
+            "id".to_string(),
+        ]);
+        let json = resource.to_json_with(&options).unwrap();
+
+        assert_eq!(json["id"], "123");
+        assert!(json.get("displayName").is_none());
+        assert!(json["name"].get("familyName").is_none());
+        assert_eq!(json["name"]["givenName"], "John");
+        assert_eq!(json["userName"], "jdoe");
+    }
+
+    #[test]
+    fn test_strip_never_returned() {
+        let registry = crate::schema::SchemaRegistry::new().unwrap();
+        let user_schema = registry.get_user_schema();
+        let mut json = json!({
+            "userName": "jdoe",
+            "Password": "t1meMa$heen",
+            "name": {"givenName": "John"}
+        });
+
+        strip_never_returned(&mut json, &user_schema.attributes);
+
+        assert!(json.get("Password").is_none());
+        assert_eq!(json["userName"], "jdoe");
+        assert_eq!(json["name"]["givenName"], "John");
+    }
+
     #[test]
     fn test_round_trip_serialization() {
         let original_json = json!({
@@ -106,4 +681,151 @@ mod tests {
         assert_eq!(serialized["displayName"], "Test Group");
         assert!(serialized["schemas"].is_array());
     }
+
+    /// Extension schema declaring `dateTime` attributes, directly and nested.
+    const LOGIN_EXTENSION: &str = "urn:example:params:scim:schemas:extension:login:2.0:User";
+
+    fn login_extension() -> Schema {
+        let date_time = |name: &str| AttributeDefinition {
+            name: name.to_string(),
+            data_type: AttributeType::DateTime,
+            ..Default::default()
+        };
+        Schema {
+            id: LOGIN_EXTENSION.to_string(),
+            name: "Login".to_string(),
+            description: "Login tracking".to_string(),
+            attributes: vec![
+                date_time("lastLogin"),
+                AttributeDefinition {
+                    name: "sessions".to_string(),
+                    data_type: AttributeType::Complex,
+                    multi_valued: true,
+                    sub_attributes: vec![date_time("started")],
+                    ..Default::default()
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_canonical_form_normalizes_representation() {
+        let extension = login_extension();
+        let canonical = |value: Value| {
+            let mut out = Vec::new();
+            write_canonical(&value, &[&extension], &mut out);
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(
+            canonical(json!({"b": [2, 1.0, -0.0], "a": {"z": null, "y": true}})),
+            r#"{"a":{"y":true,"z":null},"b":[2,1,0]}"#
+        );
+        assert_eq!(canonical(json!(1.5)), "1.5");
+        assert_eq!(
+            canonical(json!({"lastLogin": "2024-03-01T10:00:00.500+01:00"})),
+            canonical(json!({"lastLogin": "2024-03-01T09:00:00.5Z"}))
+        );
+        assert_eq!(
+            canonical(json!({"sessions": [{"started": "2024-03-01T10:00:00+01:00"}]})),
+            canonical(json!({"sessions": [{"started": "2024-03-01T09:00:00Z"}]}))
+        );
+        assert_eq!(
+            canonical(json!({LOGIN_EXTENSION: {"LASTLOGIN": "2024-03-01T10:00:00+01:00"}})),
+            canonical(json!({LOGIN_EXTENSION: {"LASTLOGIN": "2024-03-01T09:00:00Z"}}))
+        );
+        assert_eq!(
+            canonical(json!({"lastLogin": "not a date"})),
+            r#"{"lastLogin":"not a date"}"#
+        );
+
+        // Strings outside dateTime attributes are kept as written
+        assert_ne!(
+            canonical(json!({"nickName": "2024-03-01T10:00:00+01:00"})),
+            canonical(json!({"nickName": "2024-03-01T09:00:00Z"}))
+        );
+        assert_eq!(
+            canonical(json!("2024-03-01T10:00:00+01:00")),
+            r#""2024-03-01T10:00:00+01:00""#
+        );
+    }
+
+    #[test]
+    fn test_version_ignores_order_and_reserialization() {
+        let enterprise = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+        let user = Resource::from_json(
+            "User".to_string(),
+            json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User", enterprise],
+                "id": "123",
+                "userName": "bjensen",
+                "name": {"givenName": "Barbara", "familyName": "Jensen"},
+                "emails": [{"value": "bjensen@example.com", "type": "work", "primary": true}],
+                "loginCount": 3.0,
+                LOGIN_EXTENSION: {"lastLogin": "2024-03-01T10:00:00+01:00"},
+                enterprise: {"employeeNumber": "701984", "costCenter": "4130"}
+            }),
+        )
+        .unwrap();
+        let reordered = Resource::from_json(
+            "User".to_string(),
+            json!({
+                enterprise: {"costCenter": "4130", "employeeNumber": "701984"},
+                LOGIN_EXTENSION: {"lastLogin": "2024-03-01T09:00:00Z"},
+                "loginCount": 3,
+                "emails": [{"primary": true, "type": "work", "value": "bjensen@example.com"}],
+                "name": {"familyName": "Jensen", "givenName": "Barbara"},
+                "userName": "bjensen",
+                "id": "123",
+                "schemas": [enterprise, "urn:ietf:params:scim:schemas:core:2.0:User"]
+            }),
+        )
+        .unwrap();
+        let extension = login_extension();
+        assert_eq!(
+            user.canonicalize_with(&[&extension]).unwrap(),
+            reordered.canonicalize_with(&[&extension]).unwrap()
+        );
+        // Without the extension schema the datetime spelling is content
+        assert_ne!(
+            user.canonicalize().unwrap(),
+            reordered.canonicalize().unwrap()
+        );
+
+        // Round-tripping through a JSON string keeps the version
+        let text = user
+            .to_json_string_with(&SerializationOptions::new().pretty())
+            .unwrap();
+        let reparsed =
+            Resource::from_json("User".to_string(), serde_json::from_str(&text).unwrap()).unwrap();
+        assert_eq!(
+            user.content_version().unwrap(),
+            reparsed.content_version().unwrap()
+        );
+
+        // A string that is not a dateTime attribute keeps its spelling
+        let mut respelled = user.to_json().unwrap();
+        respelled["nickName"] = json!("2024-03-01T10:00:00+01:00");
+        let mut utc = respelled.clone();
+        utc["nickName"] = json!("2024-03-01T09:00:00Z");
+        assert_ne!(
+            Resource::from_json("User".to_string(), respelled)
+                .unwrap()
+                .content_version()
+                .unwrap(),
+            Resource::from_json("User".to_string(), utc)
+                .unwrap()
+                .content_version()
+                .unwrap()
+        );
+
+        // A changed value still changes the version
+        let mut changed = user.to_json().unwrap();
+        changed[enterprise]["costCenter"] = json!("4131");
+        let changed = Resource::from_json("User".to_string(), changed).unwrap();
+        assert_ne!(
+            user.content_version().unwrap(),
+            changed.content_version().unwrap()
+        );
+    }
 }
@@ -65,6 +65,9 @@ pub struct Address {
 }
 
 impl Address {
+    /// SCIM canonical values for the address `type`.
+    pub const CANONICAL_TYPES: &[&str] = &["work", "home", "other"];
+
     /// Create a new Address with all components.
     ///
     /// This is the primary constructor that enforces all validation rules.
@@ -406,7 +409,7 @@ impl Address {
         }
 
         // SCIM canonical values for address type
-        let valid_types = ["work", "home", "other"];
+        let valid_types = Self::CANONICAL_TYPES;
         if !valid_types.contains(&address_type) {
             return Err(ValidationError::custom(format!(
                 "type: '{}' is not a valid address type. Valid types are: {:?}",
@@ -15,8 +15,8 @@
 use super::value_object_trait::{ExtensionAttribute, ValueObject};
 use crate::error::{ValidationError, ValidationResult};
 use crate::resource::value_objects::SchemaUri;
+use crate::schema::binary;
 use crate::schema::types::{AttributeDefinition, AttributeType};
-use base64::Engine;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::any::Any;
@@ -151,24 +151,26 @@ impl ExtensionAttributeValue {
     }
 
     /// Validate the value type against the attribute definition.
+    ///
+    /// Multi-valued attributes must be arrays whose items each match the
+    /// attribute's type.
     fn validate_value_type(&self, definition: &AttributeDefinition) -> ValidationResult<()> {
-        let matches_type = match (&definition.data_type, &self.value) {
-            (AttributeType::String, Value::String(_)) => true,
-            (AttributeType::Boolean, Value::Bool(_)) => true,
-            (AttributeType::Integer, Value::Number(n)) if n.is_i64() => true,
-            (AttributeType::Decimal, Value::Number(_)) => true,
-            (AttributeType::DateTime, Value::String(s)) => {
-                // Basic datetime format validation
-                chrono::DateTime::parse_from_rfc3339(s).is_ok()
+        let matches_type = match (&self.value, definition.multi_valued) {
+            (Value::Null, _) => !definition.required,
+            (Value::Array(items), true) => items
+                .iter()
+                .all(|item| value_matches_type(&definition.data_type, item)),
+            (_, true) => {
+                return Err(ValidationError::ExpectedMultiValue {
+                    attribute: self.attribute_name.clone(),
+                });
             }
-            (AttributeType::Binary, Value::String(s)) => {
-                // Basic base64 validation
-                base64::engine::general_purpose::STANDARD.decode(s).is_ok()
+            (Value::Array(_), false) => {
+                return Err(ValidationError::ExpectedSingleValue {
+                    attribute: self.attribute_name.clone(),
+                });
             }
-            (AttributeType::Reference, Value::String(_)) => true, // URI validation could be added
-            (AttributeType::Complex, Value::Object(_)) => true,
-            (_, Value::Null) => !definition.required,
-            _ => false,
+            (value, false) => value_matches_type(&definition.data_type, value),
         };
 
         if !matches_type {
@@ -210,6 +212,24 @@ impl ExtensionAttributeValue {
     }
 }
 
+/// Whether a single (non-array) value has the given SCIM type.
+fn value_matches_type(data_type: &AttributeType, value: &Value) -> bool {
+    match (data_type, value) {
+        (AttributeType::String, Value::String(_)) => true,
+        (AttributeType::Boolean, Value::Bool(_)) => true,
+        (AttributeType::Integer, Value::Number(n)) if n.is_i64() => true,
+        (AttributeType::Decimal, Value::Number(_)) => true,
+        (AttributeType::DateTime, Value::String(s)) => {
+            // Basic datetime format validation
+            chrono::DateTime::parse_from_rfc3339(s).is_ok()
+        }
+        (AttributeType::Binary, Value::String(s)) => binary::check_base64(s).is_ok(),
+        (AttributeType::Reference, Value::String(_)) => true, // URI validation could be added
+        (AttributeType::Complex, Value::Object(_)) => true,
+        _ => false,
+    }
+}
+
 impl ValueObject for ExtensionAttributeValue {
     fn attribute_type(&self) -> AttributeType {
         if let Some(ref def) = self.definition {
@@ -437,6 +457,8 @@ mod tests {
             canonical_values: vec![],
             sub_attributes: vec![],
             returned: None,
+            reference_types: Vec::new(),
+            default_value: None,
         }
     }
 
@@ -556,6 +556,8 @@ mod tests {
             canonical_values: vec![],
             sub_attributes: vec![],
             returned: None,
+            reference_types: Vec::new(),
+            default_value: None,
         }
     }
 
@@ -5,8 +5,9 @@
 //! and version information.
 
 use crate::error::{ValidationError, ValidationResult};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SubsecRound, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::fmt;
 
 /// A validated SCIM meta attribute.
@@ -24,6 +25,10 @@ use std::fmt;
 /// - Location URI, if provided, must be valid format
 /// - Version, if provided, must follow ETag format
 ///
+/// Sub-attributes other than the typed ones (for example custom keys added by
+/// an identity provider) are kept unvalidated in [`extra`](Self::extra) so they
+/// survive round-trips.
+///
 /// ## Examples
 ///
 /// ```rust
@@ -53,6 +58,9 @@ pub struct Meta {
     pub last_modified: DateTime<Utc>,
     pub location: Option<String>,
     pub version: Option<String>,
+    /// Unrecognized sub-attributes, preserved as received
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl Meta {
@@ -95,6 +103,7 @@ impl Meta {
             last_modified,
             location,
             version,
+            extra: Map::new(),
         })
     }
 
@@ -174,9 +183,37 @@ impl Meta {
             last_modified: Utc::now(),
             location: self.location.clone(),
             version: self.version.clone(),
+            extra: self.extra.clone(),
         }
     }
 
+    /// Get the unrecognized sub-attributes.
+    pub fn extra(&self) -> &Map<String, Value> {
+        &self.extra
+    }
+
+    /// Create a new Meta with additional sub-attributes.
+    ///
+    /// Entries named like a typed field (`resourceType`, `created`,
+    /// `lastModified`, `location`, `version`) are ignored; those are always
+    /// taken from the typed values.
+    pub fn with_extra(mut self, extra: Map<String, Value>) -> Self {
+        self.extra.extend(
+            extra
+                .into_iter()
+                .filter(|(name, _)| !Self::is_typed_field(name)),
+        );
+        self
+    }
+
+    /// Whether a sub-attribute name belongs to one of the typed fields.
+    pub fn is_typed_field(name: &str) -> bool {
+        matches!(
+            name,
+            "resourceType" | "created" | "lastModified" | "location" | "version"
+        )
+    }
+
     /// Create a new Meta with a specific location.
     ///
     /// This method creates a new Meta instance with the location set to the
@@ -249,8 +286,10 @@ impl Meta {
             return Err(ValidationError::InvalidLocationUri);
         }
 
-        // Basic URI validation - should start with http:// or https://
-        if !location.starts_with("http://") && !location.starts_with("https://") {
+        // Basic URI validation - should start with http:// or https://, or be
+        // a host-relative path as emitted with relative locations
+        let relative = location.starts_with('/') && !location.starts_with("//");
+        if !location.starts_with("http://") && !location.starts_with("https://") && !relative {
             return Err(ValidationError::InvalidLocationUri);
         }
 
@@ -276,6 +315,37 @@ impl Meta {
     }
 }
 
+/// Precision at which `meta` timestamps are compared.
+///
+/// Stored timestamps carry nanoseconds, while clients usually echo them back
+/// at a coarser precision. Truncating both sides to a common precision keeps
+/// comparisons such as "modified since" inclusive of the boundary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TimestampPrecision {
+    /// Whole seconds
+    Seconds,
+    /// Milliseconds (the default)
+    #[default]
+    Milliseconds,
+    /// Microseconds
+    Microseconds,
+    /// Nanoseconds, i.e. no truncation
+    Nanoseconds,
+}
+
+impl TimestampPrecision {
+    /// Truncate a timestamp to this precision.
+    pub fn truncate(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let digits = match self {
+            Self::Seconds => 0,
+            Self::Milliseconds => 3,
+            Self::Microseconds => 6,
+            Self::Nanoseconds => 9,
+        };
+        timestamp.trunc_subsecs(digits)
+    }
+}
+
 impl fmt::Display for Meta {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -53,7 +53,7 @@ pub use factory::GenericMultiValuedAttribute;
 pub use group_member::{
     GroupMember, GroupMembers, MultiValuedAddresses, MultiValuedEmails, MultiValuedPhoneNumbers,
 };
-pub use meta::Meta;
+pub use meta::{Meta, TimestampPrecision};
 pub use multi_valued::MultiValuedAttribute;
 pub use name::Name;
 pub use phone_number::PhoneNumber;
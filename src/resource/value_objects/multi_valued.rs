@@ -9,6 +9,19 @@
 //! - **Primary Constraint**: Ensures at most one primary value exists
 //! - **Immutable Operations**: Most operations return new instances
 //! - **SCIM Compliance**: Follows SCIM 2.0 multi-valued attribute patterns
+//! - **Stable Ordering**: Values keep their insertion order
+//!
+//! ## Ordering
+//!
+//! Values are kept in the order they were given, and no operation reorders
+//! them: [`Resource::from_json`](crate::resource::Resource::from_json) and
+//! [`Resource::to_json`](crate::resource::Resource::to_json) round-trip a
+//! multi-valued attribute element for element, the primary value stays at its
+//! position, and [`dedup_by_key`](MultiValuedAttribute::dedup_by_key) keeps
+//! the first-seen value of each key. Since a resource's
+//! [content version](crate::resource::Resource::content_version) is taken over
+//! its values in this order, a round trip never changes the version, while a
+//! client reordering values does.
 //!
 //! ## Usage Pattern
 //!
@@ -37,7 +50,9 @@
 
 use crate::error::{ValidationError, ValidationResult};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fmt;
+use std::hash::Hash;
 
 /// A generic container for multi-valued SCIM attributes.
 ///
@@ -338,6 +353,40 @@ impl<T> MultiValuedAttribute<T> {
         Ok(self)
     }
 
+    /// Creates a new multi-valued attribute whose primary value is the first
+    /// value matching `predicate`, or with no primary value if none does.
+    ///
+    /// The order of the values is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::resource::value_objects::{MultiValuedAttribute, EmailAddress};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let emails = vec![
+    ///         EmailAddress::new_simple("work@example.com".to_string())?,
+    ///         EmailAddress::new(
+    ///             "home@example.com".to_string(),
+    ///             Some("home".to_string()),
+    ///             Some(true),
+    ///             None,
+    ///         )?,
+    ///     ];
+    ///     let multi_attr =
+    ///         MultiValuedAttribute::new(emails)?.with_primary_where(EmailAddress::is_primary);
+    ///     assert_eq!(multi_attr.primary_index(), Some(1));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_primary_where<P>(mut self, predicate: P) -> Self
+    where
+        P: Fn(&T) -> bool,
+    {
+        self.primary_index = self.values.iter().position(predicate);
+        self
+    }
+
     /// Creates a new multi-valued attribute with no primary value set.
     ///
     /// # Examples
@@ -426,6 +475,44 @@ impl<T> MultiValuedAttribute<T> {
         self
     }
 
+    /// Creates a new multi-valued attribute keeping only the first value for
+    /// each key.
+    ///
+    /// Kept values stay in first-seen order. When the primary value is
+    /// dropped as a duplicate, the kept value with the same key becomes
+    /// primary.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::resource::value_objects::{MultiValuedAttribute, EmailAddress};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let emails = vec![
+    ///         EmailAddress::new_simple("a@example.com".to_string())?,
+    ///         EmailAddress::new_simple("b@example.com".to_string())?,
+    ///         EmailAddress::new_simple("a@example.com".to_string())?,
+    ///     ];
+    ///     let multi_attr = MultiValuedAttribute::new(emails)?
+    ///         .dedup_by_key(|email| email.value().to_string());
+    ///     let values: Vec<&str> = multi_attr.iter().map(|email| email.value()).collect();
+    ///     assert_eq!(values, ["a@example.com", "b@example.com"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn dedup_by_key<K, F>(mut self, key: F) -> Self
+    where
+        K: Eq + Hash,
+        F: Fn(&T) -> K,
+    {
+        let primary_key = self.primary().map(&key);
+        let mut seen = HashSet::new();
+        self.values.retain(|value| seen.insert(key(value)));
+        self.primary_index = primary_key
+            .and_then(|primary| self.values.iter().position(|value| key(value) == primary));
+        self
+    }
+
     /// Returns an iterator over the values in the collection.
     ///
     /// # Examples
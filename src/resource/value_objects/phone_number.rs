@@ -54,6 +54,9 @@ pub struct PhoneNumber {
 }
 
 impl PhoneNumber {
+    /// SCIM canonical values for the phone `type`.
+    pub const CANONICAL_TYPES: &[&str] = &["work", "home", "mobile", "fax", "pager", "other"];
+
     /// Create a new PhoneNumber with all components.
     ///
     /// This is the primary constructor that enforces all validation rules.
@@ -282,7 +285,7 @@ impl PhoneNumber {
         }
 
         // SCIM canonical values for phone type
-        let valid_types = ["work", "home", "mobile", "fax", "pager", "other"];
+        let valid_types = Self::CANONICAL_TYPES;
         if !valid_types.contains(&phone_type) {
             return Err(ValidationError::custom(format!(
                 "type: '{}' is not a valid phone type. Valid types are: {:?}",
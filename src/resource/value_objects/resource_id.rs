@@ -418,6 +418,8 @@ mod tests {
             canonical_values: vec![],
             sub_attributes: vec![],
             returned: None,
+            reference_types: Vec::new(),
+            default_value: None,
         };
 
         let value = Value::String("test-id".to_string());
@@ -449,6 +451,8 @@ mod tests {
             canonical_values: vec![],
             sub_attributes: vec![],
             returned: None,
+            reference_types: Vec::new(),
+            default_value: None,
         };
 
         assert!(id.validate_against_schema(&valid_definition).is_ok());
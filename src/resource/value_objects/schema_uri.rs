@@ -5,6 +5,7 @@
 
 use crate::error::{ValidationError, ValidationResult};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
 use std::fmt;
 
 /// A validated SCIM schema URI.
@@ -58,6 +59,39 @@ impl SchemaUri {
         Ok(Self(value))
     }
 
+    /// Parse the value of a resource's `schemas` attribute.
+    ///
+    /// Each malformation has its own error: [`ValidationError::SchemasNotArray`]
+    /// when the value is not an array, [`ValidationError::EmptySchemas`] when
+    /// the array is empty, and [`ValidationError::InvalidSchemaUriElement`]
+    /// with the element's index when an element is not a string or not a
+    /// valid schema URI.
+    pub fn parse_schemas(schemas: &Value) -> ValidationResult<Vec<SchemaUri>> {
+        let elements = schemas
+            .as_array()
+            .ok_or_else(|| ValidationError::SchemasNotArray {
+                actual: json_type_name(schemas).to_string(),
+            })?;
+        if elements.is_empty() {
+            return Err(ValidationError::EmptySchemas);
+        }
+        elements
+            .iter()
+            .enumerate()
+            .map(|(index, element)| {
+                element
+                    .as_str()
+                    .and_then(|uri| Self::new(uri.to_string()).ok())
+                    .ok_or_else(|| ValidationError::InvalidSchemaUriElement {
+                        index,
+                        value: element
+                            .as_str()
+                            .map_or_else(|| element.to_string(), str::to_string),
+                    })
+            })
+            .collect()
+    }
+
     /// Get the string representation of the SchemaUri.
     pub fn as_str(&self) -> &str {
         &self.0
@@ -97,6 +131,18 @@ impl SchemaUri {
     }
 }
 
+/// JSON type of a value, as named in error messages.
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
 impl fmt::Display for SchemaUri {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
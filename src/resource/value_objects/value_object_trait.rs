@@ -381,6 +381,8 @@ mod tests {
             canonical_values: vec![],
             sub_attributes: vec![],
             returned: None,
+            reference_types: Vec::new(),
+            default_value: None,
         };
 
         assert!(obj.validate_against_schema(&definition).is_ok());
@@ -11,6 +11,11 @@
 //! simultaneously. All versions are computed deterministically from resource content
 //! using SHA-256 hashing.
 //!
+//! Versions are opaque and compared for equality only. Counter-based versions
+//! created with [`ScimVersion::from_sequence`] or parsed with
+//! [`ScimVersion::parse_sequence`] can additionally be ordered (see
+//! [`VersionKind`]); conditional operations never rely on ordering.
+//!
 //! # Type-Safe Format Management
 //!
 //! This module uses phantom types to distinguish between HTTP ETag format and raw
@@ -83,7 +88,13 @@
 use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
-use std::{fmt, marker::PhantomData, str::FromStr};
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    str::FromStr,
+};
 use thiserror::Error;
 
 // Phantom type markers for format distinction
@@ -133,10 +144,12 @@ pub struct Raw;
 /// let raw_parsed: RawVersion = "abc123def".parse().unwrap();
 /// let http_parsed: HttpVersion = "\"abc123def\"".parse().unwrap();
 /// ```
-#[derive(Debug, Clone, Eq, Hash)]
+#[derive(Debug, Clone, Eq)]
 pub struct ScimVersion<Format> {
     /// Opaque version identifier
     opaque: String,
+    /// How the version may be compared, fixed by the constructor
+    kind: VersionKind,
     /// Phantom type marker for compile-time format distinction
     #[allow(dead_code)]
     _format: PhantomData<Format>,
@@ -148,6 +161,19 @@ pub type HttpVersion = ScimVersion<Http>;
 /// Type alias for raw internal format versions ("abc123")
 pub type RawVersion = ScimVersion<Raw>;
 
+/// How a version may be compared.
+///
+/// Content-hash and most provider-specific versions are opaque: two of them
+/// can only be tested for equality. Sequence versions carry a monotonically
+/// increasing counter and can also be ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VersionKind {
+    /// Counter-based version that supports ordering
+    Sequence(u64),
+    /// Opaque version that only supports equality comparison
+    Opaque,
+}
+
 // Core constructors (always produce Raw format as the canonical form)
 impl<Format> ScimVersion<Format> {
     /// Create a version from resource content.
@@ -176,6 +202,7 @@ impl<Format> ScimVersion<Format> {
 
         ScimVersion {
             opaque: encoded,
+            kind: VersionKind::Opaque,
             _format: PhantomData,
         }
     }
@@ -207,6 +234,7 @@ impl<Format> ScimVersion<Format> {
     pub fn from_hash(hash_string: impl AsRef<str>) -> RawVersion {
         ScimVersion {
             opaque: hash_string.as_ref().to_string(),
+            kind: VersionKind::Opaque,
             _format: PhantomData,
         }
     }
@@ -218,6 +246,120 @@ impl<Format> ScimVersion<Format> {
     pub fn as_str(&self) -> &str {
         &self.opaque
     }
+
+    /// Create a counter-based version for monotonic versioning schemes.
+    ///
+    /// Always produces a [`RawVersion`] whose opaque value is the counter in
+    /// decimal. The version is orderable; parsing its value back, say from an
+    /// ETag, gives an opaque version unless done with
+    /// [`parse_sequence`](ScimVersion::parse_sequence).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scim_server::resource::version::{RawVersion, VersionKind};
+    ///
+    /// let version = RawVersion::from_sequence(7);
+    /// assert_eq!(version.as_str(), "7");
+    /// assert_eq!(version.kind(), VersionKind::Sequence(7));
+    /// ```
+    pub fn from_sequence(sequence: u64) -> RawVersion {
+        ScimVersion {
+            opaque: sequence.to_string(),
+            kind: VersionKind::Sequence(sequence),
+            _format: PhantomData,
+        }
+    }
+
+    /// Whether this version can be ordered or only compared for equality.
+    ///
+    /// The kind is recorded by the constructor, never inferred from the
+    /// value: only [`from_sequence`](Self::from_sequence) and
+    /// [`parse_sequence`](ScimVersion::parse_sequence) produce a
+    /// [`VersionKind::Sequence`], so a hash or provider identifier that happens
+    /// to look like a number stays [`VersionKind::Opaque`].
+    pub fn kind(&self) -> VersionKind {
+        self.kind
+    }
+
+    /// Whether this version is newer than `other`.
+    ///
+    /// Returns `None` unless both versions are sequence versions; opaque
+    /// versions have no order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scim_server::resource::version::RawVersion;
+    ///
+    /// let first = RawVersion::from_sequence(1);
+    /// let second = RawVersion::from_sequence(2);
+    /// assert_eq!(second.is_newer_than(&first), Some(true));
+    ///
+    /// let hash = RawVersion::from_content(b"content");
+    /// assert_eq!(hash.is_newer_than(&first), None);
+    /// ```
+    pub fn is_newer_than<Other>(&self, other: &ScimVersion<Other>) -> Option<bool> {
+        match (self.kind(), other.kind()) {
+            (VersionKind::Sequence(this), VersionKind::Sequence(that)) => Some(this > that),
+            _ => None,
+        }
+    }
+}
+
+impl ScimVersion<Raw> {
+    /// Parse a version given in either representation.
+    ///
+    /// Accepts an ETag (`W/"abc123"` or `"abc123"`) as returned in response
+    /// metadata, or a raw value (`abc123`) as found in `meta.version`. Both
+    /// resolve to the same version.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::resource::version::RawVersion;
+    ///
+    /// let from_etag = RawVersion::parse_any("W/\"abc123\"").unwrap();
+    /// let from_meta = RawVersion::parse_any("abc123").unwrap();
+    /// assert_eq!(from_etag, from_meta);
+    /// ```
+    pub fn parse_any(value: &str) -> Result<RawVersion, VersionError> {
+        value
+            .parse::<HttpVersion>()
+            .map(RawVersion::from)
+            .or_else(|_| value.parse::<RawVersion>())
+    }
+
+    /// Parse a counter-based version given in either representation.
+    ///
+    /// Parsed versions are otherwise opaque, since nothing in the string says
+    /// how it was created. A provider that issues versions with
+    /// [`from_sequence`](ScimVersion::from_sequence) uses this for the
+    /// versions clients send back, to order them. Fails unless the value is a
+    /// canonical decimal counter.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::resource::version::{RawVersion, VersionKind};
+    ///
+    /// let version = RawVersion::parse_sequence("W/\"7\"").unwrap();
+    /// assert_eq!(version.kind(), VersionKind::Sequence(7));
+    /// assert_eq!(RawVersion::parse_any("7").unwrap().kind(), VersionKind::Opaque);
+    /// assert!(RawVersion::parse_sequence("007").is_err());
+    /// ```
+    pub fn parse_sequence(value: &str) -> Result<RawVersion, VersionError> {
+        let version = Self::parse_any(value)?;
+        let opaque = version.as_str();
+        let canonical = opaque.bytes().all(|b| b.is_ascii_digit())
+            && (opaque == "0" || !opaque.starts_with('0'));
+
+        match opaque.parse() {
+            Ok(sequence) if canonical => Ok(RawVersion::from_sequence(sequence)),
+            _ => Err(VersionError::ParseError(format!(
+                "'{}' is not a sequence version",
+                value
+            ))),
+        }
+    }
 }
 
 // Display implementation for Raw format (simple string output)
@@ -249,6 +391,7 @@ impl FromStr for ScimVersion<Raw> {
 
         Ok(ScimVersion {
             opaque: trimmed.to_string(),
+            kind: VersionKind::Opaque,
             _format: PhantomData,
         })
     }
@@ -281,6 +424,7 @@ impl FromStr for ScimVersion<Http> {
 
         Ok(ScimVersion {
             opaque,
+            kind: VersionKind::Opaque,
             _format: PhantomData,
         })
     }
@@ -291,6 +435,7 @@ impl From<ScimVersion<Raw>> for ScimVersion<Http> {
     fn from(raw: ScimVersion<Raw>) -> Self {
         ScimVersion {
             opaque: raw.opaque,
+            kind: raw.kind,
             _format: PhantomData,
         }
     }
@@ -300,6 +445,7 @@ impl From<ScimVersion<Http>> for ScimVersion<Raw> {
     fn from(http: ScimVersion<Http>) -> Self {
         ScimVersion {
             opaque: http.opaque,
+            kind: http.kind,
             _format: PhantomData,
         }
     }
@@ -312,6 +458,27 @@ impl<F1, F2> PartialEq<ScimVersion<F2>> for ScimVersion<F1> {
     }
 }
 
+// Hashes the opaque string only, consistent with equality
+impl<Format> Hash for ScimVersion<Format> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.opaque.hash(state);
+    }
+}
+
+// Ordering is only defined between sequence versions; opaque versions are
+// equal or incomparable
+impl<F1, F2> PartialOrd<ScimVersion<F2>> for ScimVersion<F1> {
+    fn partial_cmp(&self, other: &ScimVersion<F2>) -> Option<Ordering> {
+        if self.opaque == other.opaque {
+            return Some(Ordering::Equal);
+        }
+        match (self.kind(), other.kind()) {
+            (VersionKind::Sequence(this), VersionKind::Sequence(that)) => Some(this.cmp(&that)),
+            _ => None,
+        }
+    }
+}
+
 // Serde implementations that preserve the opaque string regardless of format
 impl<Format> Serialize for ScimVersion<Format> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -330,6 +497,7 @@ impl<'de, Format> Deserialize<'de> for ScimVersion<Format> {
         let opaque = String::deserialize(deserializer)?;
         Ok(ScimVersion {
             opaque,
+            kind: VersionKind::Opaque,
             _format: PhantomData,
         })
     }
@@ -588,6 +756,35 @@ mod tests {
         assert_eq!(raw_version, http_version);
     }
 
+    #[test]
+    fn test_version_kind_and_ordering() {
+        let older = RawVersion::from_sequence(9);
+        let newer = HttpVersion::from(RawVersion::parse_sequence("W/\"10\"").unwrap());
+
+        assert_eq!(older.kind(), VersionKind::Sequence(9));
+        assert_eq!(newer.is_newer_than(&older), Some(true));
+        assert_eq!(older.is_newer_than(&newer), Some(false));
+        assert!(older < newer);
+
+        // Hashes and parsed values are opaque and never ordered, even when
+        // they look like counters
+        let hash = RawVersion::from_content(b"content");
+        assert_eq!(hash.kind(), VersionKind::Opaque);
+        let numeric = RawVersion::from_hash("10");
+        assert_eq!(numeric.kind(), VersionKind::Opaque);
+        assert_eq!(numeric.is_newer_than(&older), None);
+        assert_eq!(
+            "10".parse::<RawVersion>().unwrap().kind(),
+            VersionKind::Opaque
+        );
+        assert_eq!(numeric, newer);
+        assert!(RawVersion::parse_sequence("007").is_err());
+        assert!(RawVersion::parse_sequence("+7").is_err());
+        assert_eq!(hash.is_newer_than(&older), None);
+        assert_eq!(hash.partial_cmp(&older), None);
+        assert_eq!(hash.partial_cmp(&hash.clone()), Some(Ordering::Equal));
+    }
+
     #[test]
     fn test_conditional_result() {
         let success: ConditionalResult<i32> = ConditionalResult::Success(42);
@@ -103,6 +103,42 @@ impl VersionedResource {
         Self { resource, version }
     }
 
+    /// Create versioned resources for a batch of resources.
+    ///
+    /// Versions are identical to calling [`new`](Self::new) on each resource
+    /// and the input order is preserved. With the `parallel` feature enabled
+    /// the versions are computed on the rayon thread pool, which speeds up
+    /// large list responses.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scim_server::resource::{
+    ///     versioned::VersionedResource,
+    ///     Resource,
+    /// };
+    /// use serde_json::json;
+    ///
+    /// let resources = (0..3)
+    ///     .map(|i| Resource::from_json("User".to_string(), json!({"id": i.to_string()})).unwrap())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// let versioned = VersionedResource::new_batch(resources);
+    /// assert_eq!(versioned.len(), 3);
+    /// assert_eq!(versioned[2].get_id(), Some("2"));
+    /// ```
+    pub fn new_batch(resources: Vec<Resource>) -> Vec<Self> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            resources.into_par_iter().map(Self::new).collect()
+        }
+
+        #[cfg(not(feature = "parallel"))]
+        {
+            resources.into_iter().map(Self::new).collect()
+        }
+    }
+
     /// Create a versioned resource with a specific version.
     ///
     /// This is useful when migrating from existing systems or when the version
@@ -257,11 +293,9 @@ impl VersionedResource {
 
     /// Compute version from resource content.
     ///
-    /// This uses the resource's JSON representation to generate a consistent
-    /// hash-based version that reflects all resource data.
+    /// See [`Resource::content_version`] for what the version covers.
     fn compute_version(resource: &Resource) -> RawVersion {
-        let json_bytes = resource.to_json().unwrap().to_string().into_bytes();
-        RawVersion::from_content(&json_bytes)
+        resource.content_version().unwrap()
     }
 }
 
@@ -392,6 +426,30 @@ mod tests {
         assert!(versioned.version() != &custom_version);
     }
 
+    #[test]
+    fn test_versioned_resource_batch_matches_single() {
+        let resources = (0..64)
+            .map(|i| {
+                Resource::from_json(
+                    "User".to_string(),
+                    json!({"id": i.to_string(), "userName": format!("user{}", i)}),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let batch = VersionedResource::new_batch(resources.clone());
+
+        assert_eq!(batch.len(), resources.len());
+        for (versioned, resource) in batch.iter().zip(resources) {
+            assert_eq!(versioned.get_id(), resource.get_id());
+            assert_eq!(
+                versioned.version(),
+                VersionedResource::new(resource).version()
+            );
+        }
+    }
+
     #[test]
     fn test_versioned_resource_serialization() {
         let resource = Resource::from_json(
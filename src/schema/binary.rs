@@ -0,0 +1,155 @@
+//! Base64 handling for `binary` attributes (RFC 7643 Section 2.3.6).
+//!
+//! Binary values are carried as standard, padded base64. Validation is a
+//! single allocation-free pass over the encoded string, so large values such
+//! as `x509Certificates` blobs are cheap to check; decoding only happens when
+//! a caller asks for the bytes.
+
+use super::types::{AttributeDefinition, AttributeType};
+use crate::error::{ValidationError, ValidationResult};
+use base64::Engine;
+use serde_json::{Map, Value};
+
+/// Check that `value` is well-formed standard base64.
+///
+/// Returns a description of the first problem found.
+pub fn check_base64(value: &str) -> Result<(), String> {
+    let bytes = value.as_bytes();
+    if bytes.is_empty() {
+        return Err("Empty base64 value".to_string());
+    }
+    if !bytes.len().is_multiple_of(4) {
+        return Err(format!(
+            "Invalid base64 length {}, expected a multiple of 4",
+            bytes.len()
+        ));
+    }
+
+    let padding = bytes.iter().rev().take_while(|&&b| b == b'=').count();
+    if padding > 2 {
+        return Err("Too much base64 padding".to_string());
+    }
+
+    let data = &bytes[..bytes.len() - padding];
+    if let Some(position) = data.iter().position(|&b| sextet(b).is_none()) {
+        return Err(format!("Invalid base64 character at position {}", position));
+    }
+
+    // The bits after the last whole byte must be zero for a canonical encoding
+    let last = data.last().and_then(|&b| sextet(b)).unwrap_or(0);
+    let unused_bits_mask = match padding {
+        1 => 0b0000_0011,
+        2 => 0b0000_1111,
+        _ => 0,
+    };
+    if last & unused_bits_mask != 0 {
+        return Err("Non-canonical base64 padding bits".to_string());
+    }
+
+    Ok(())
+}
+
+/// Decode a binary attribute's base64 value.
+pub fn decode_binary(attribute: &str, value: &str) -> ValidationResult<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(value)
+        .map_err(|e| ValidationError::InvalidBinaryData {
+            attribute: attribute.to_string(),
+            details: e.to_string(),
+        })
+}
+
+/// Validate every `binary` attribute and sub-attribute defined in
+/// `attributes` that is present in `data`.
+pub fn validate_binary_attributes(
+    attributes: &[AttributeDefinition],
+    data: &Map<String, Value>,
+) -> ValidationResult<()> {
+    for definition in attributes {
+        let Some(value) = data.get(&definition.name) else {
+            continue;
+        };
+
+        for item in as_values(value) {
+            match (&definition.data_type, item) {
+                (AttributeType::Binary, Value::String(encoded)) => {
+                    check_base64(encoded).map_err(|details| {
+                        ValidationError::InvalidBinaryData {
+                            attribute: definition.name.clone(),
+                            details,
+                        }
+                    })?;
+                }
+                (AttributeType::Complex, Value::Object(sub_data)) => {
+                    validate_binary_attributes(&definition.sub_attributes, sub_data).map_err(
+                        |e| match e {
+                            ValidationError::InvalidBinaryData { attribute, details } => {
+                                ValidationError::InvalidBinaryData {
+                                    attribute: format!("{}.{}", definition.name, attribute),
+                                    details,
+                                }
+                            }
+                            other => other,
+                        },
+                    )?;
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+fn as_values(value: &Value) -> &[Value] {
+    match value {
+        Value::Array(items) => items,
+        other => std::slice::from_ref(other),
+    }
+}
+
+fn sextet(byte: u8) -> Option<u8> {
+    match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_base64() {
+        assert!(check_base64("TWFu").is_ok());
+        assert!(check_base64("TWE=").is_ok());
+        assert!(check_base64("TQ==").is_ok());
+
+        assert!(check_base64("").is_err());
+        assert!(check_base64("TWF").is_err());
+        assert!(check_base64("TW=u").is_err());
+        assert!(check_base64("T===").is_err());
+        assert!(check_base64("TWF!").is_err());
+        assert!(check_base64("TR==").is_err());
+
+        // Validation agrees with decoding
+        for value in ["TWFu", "TWE=", "TQ==", "TR==", "TW=u"] {
+            assert_eq!(
+                check_base64(value).is_ok(),
+                decode_binary("x", value).is_ok(),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_large_values_validate_without_decoding() {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(vec![0xA5u8; 4 << 20]);
+        assert!(check_base64(&encoded).is_ok());
+        assert_eq!(decode_binary("x", &encoded).unwrap().len(), 4 << 20);
+    }
+}
@@ -0,0 +1,59 @@
+//! Case handling for attribute value comparisons.
+//!
+//! RFC 7643 Section 2.2 makes string values case-insensitive unless their
+//! attribute is `caseExact`. Filters, sorting and uniqueness checks all
+//! compare values through this module, with `caseExact` read from the
+//! attribute's schema definition, so they cannot disagree about case. An
+//! attribute without a definition is compared case-insensitively, the RFC
+//! default.
+
+use super::types::AttributeDefinition;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+/// Whether values of an attribute are compared case-sensitively.
+pub fn is_case_exact(definition: Option<&AttributeDefinition>) -> bool {
+    definition.is_some_and(|definition| definition.case_exact)
+}
+
+/// Compare two string values of an attribute, honouring its `caseExact`.
+pub fn compare(definition: Option<&AttributeDefinition>, a: &str, b: &str) -> Ordering {
+    compare_case(is_case_exact(definition), a, b)
+}
+
+/// Compare two string values, ignoring case unless `case_exact`.
+pub fn compare_case(case_exact: bool, a: &str, b: &str) -> Ordering {
+    fold(case_exact, a).cmp(&fold(case_exact, b))
+}
+
+/// A value as it takes part in comparisons: unchanged when `case_exact`,
+/// otherwise lowercased. Substring matches compare folded values.
+pub fn fold(case_exact: bool, value: &str) -> Cow<'_, str> {
+    if case_exact {
+        Cow::Borrowed(value)
+    } else {
+        Cow::Owned(value.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::embedded;
+
+    #[test]
+    fn test_compare_reads_case_exact_from_schema() {
+        let user = embedded::parsed_core_user_schema().unwrap();
+
+        let user_name = user.attribute_definition("userName");
+        assert!(!is_case_exact(user_name));
+        assert_eq!(compare(user_name, "Alice", "alice"), Ordering::Equal);
+
+        let id = user.attribute_definition("id");
+        assert!(is_case_exact(id));
+        assert_eq!(compare(id, "Abc", "abc"), Ordering::Less);
+
+        assert_eq!(compare(None, "B", "a"), Ordering::Greater);
+        assert_eq!(fold(false, "MiXed"), "mixed");
+    }
+}
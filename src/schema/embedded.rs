@@ -4,6 +4,47 @@
 //! embedded as static strings, eliminating the need for external schema files
 //! for basic schema discovery functionality.
 
+use super::types::Schema;
+use std::sync::OnceLock;
+
+/// Returns the parsed core User schema.
+///
+/// Parsed once on first use, for lookups that don't have a registry at hand.
+///
+/// # Errors
+///
+/// Returns the parse error if the embedded schema is not a valid [`Schema`].
+pub fn parsed_core_user_schema() -> Result<&'static Schema, &'static serde_json::Error> {
+    static SCHEMA: OnceLock<Result<Schema, serde_json::Error>> = OnceLock::new();
+    SCHEMA
+        .get_or_init(|| serde_json::from_str(core_user_schema()))
+        .as_ref()
+}
+
+/// Returns the parsed core Group schema.
+///
+/// Parsed once on first use, like [`parsed_core_user_schema`].
+///
+/// # Errors
+///
+/// Returns the parse error if the embedded schema is not a valid [`Schema`].
+pub fn parsed_core_group_schema() -> Result<&'static Schema, &'static serde_json::Error> {
+    static SCHEMA: OnceLock<Result<Schema, serde_json::Error>> = OnceLock::new();
+    SCHEMA
+        .get_or_init(|| serde_json::from_str(core_group_schema()))
+        .as_ref()
+}
+
+/// Returns the parsed core schema of `resource_type`, if it is `User` or
+/// `Group` and the embedded schema parses.
+pub fn parsed_core_schema(resource_type: &str) -> Option<&'static Schema> {
+    match resource_type {
+        "User" => parsed_core_user_schema().ok(),
+        "Group" => parsed_core_group_schema().ok(),
+        _ => None,
+    }
+}
+
 /// Returns the core User schema as a JSON string.
 ///
 /// This is the standard SCIM 2.0 User schema as defined in RFC 7643.
@@ -71,7 +112,8 @@ pub fn core_user_schema() -> &'static str {
       "caseExact": false,
       "mutability": "readWrite",
       "returned": "default",
-      "uniqueness": "none"
+      "uniqueness": "none",
+      "referenceTypes": ["external"]
     },
     {
       "name": "title",
@@ -402,6 +444,17 @@ pub fn core_user_schema() -> &'static str {
       "caseExact": false,
       "mutability": "readWrite",
       "returned": "default",
+      "uniqueness": "none",
+      "defaultValue": true
+    },
+    {
+      "name": "password",
+      "type": "string",
+      "multiValued": false,
+      "required": false,
+      "caseExact": false,
+      "mutability": "writeOnly",
+      "returned": "never",
       "uniqueness": "none"
     },
     {
@@ -452,7 +505,8 @@ pub fn core_user_schema() -> &'static str {
           "caseExact": false,
           "mutability": "readOnly",
           "returned": "default",
-          "uniqueness": "none"
+          "uniqueness": "none",
+          "referenceTypes": ["uri"]
         },
         {
           "name": "version",
@@ -466,6 +520,58 @@ pub fn core_user_schema() -> &'static str {
         }
       ]
     },
+    {
+      "name": "x509Certificates",
+      "type": "complex",
+      "multiValued": true,
+      "required": false,
+      "caseExact": false,
+      "mutability": "readWrite",
+      "returned": "default",
+      "uniqueness": "none",
+      "subAttributes": [
+        {
+          "name": "value",
+          "type": "binary",
+          "multiValued": false,
+          "required": true,
+          "caseExact": true,
+          "mutability": "readWrite",
+          "returned": "default",
+          "uniqueness": "none"
+        },
+        {
+          "name": "display",
+          "type": "string",
+          "multiValued": false,
+          "required": false,
+          "caseExact": false,
+          "mutability": "readWrite",
+          "returned": "default",
+          "uniqueness": "none"
+        },
+        {
+          "name": "type",
+          "type": "string",
+          "multiValued": false,
+          "required": false,
+          "caseExact": false,
+          "mutability": "readWrite",
+          "returned": "default",
+          "uniqueness": "none"
+        },
+        {
+          "name": "primary",
+          "type": "boolean",
+          "multiValued": false,
+          "required": false,
+          "caseExact": false,
+          "mutability": "readWrite",
+          "returned": "default",
+          "uniqueness": "none"
+        }
+      ]
+    },
     {
       "name": "groups",
       "type": "complex",
@@ -714,7 +820,8 @@ pub fn service_provider_config_schema() -> &'static str {
       "caseExact": false,
       "mutability": "readOnly",
       "returned": "default",
-      "uniqueness": "none"
+      "uniqueness": "none",
+      "referenceTypes": ["external"]
     },
     {
       "name": "patch",
@@ -916,7 +1023,8 @@ pub fn service_provider_config_schema() -> &'static str {
           "caseExact": false,
           "mutability": "readOnly",
           "returned": "default",
-          "uniqueness": "none"
+          "uniqueness": "none",
+          "referenceTypes": ["external"]
         },
         {
           "name": "documentationUri",
@@ -926,7 +1034,8 @@ pub fn service_provider_config_schema() -> &'static str {
           "caseExact": false,
           "mutability": "readOnly",
           "returned": "default",
-          "uniqueness": "none"
+          "uniqueness": "none",
+          "referenceTypes": ["external"]
         },
         {
           "name": "type",
@@ -21,6 +21,8 @@
 //! # }
 //! ```
 
+pub mod binary;
+pub mod case;
 pub mod embedded;
 pub mod registry;
 pub mod types;
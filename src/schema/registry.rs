@@ -8,11 +8,13 @@ use super::{
     types::{AttributeDefinition, AttributeType, Schema},
 };
 
+use crate::error::SchemaError;
 use chrono::{DateTime, FixedOffset};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::OnceLock;
 
 /// Registry for SCIM schemas with validation capabilities.
 ///
@@ -54,6 +56,16 @@ impl SchemaRegistry {
         })
     }
 
+    /// Shared registry holding only the embedded core schemas.
+    ///
+    /// Built once on first use, for validation that doesn't have a registry
+    /// at hand.
+    pub(crate) fn embedded() -> &'static SchemaRegistry {
+        static REGISTRY: OnceLock<SchemaRegistry> = OnceLock::new();
+        REGISTRY
+            .get_or_init(|| Self::with_embedded_schemas().expect("embedded schemas are valid JSON"))
+    }
+
     /// Create a schema registry by loading schemas from a directory.
     pub fn from_schema_dir<P: AsRef<Path>>(
         schema_dir: P,
@@ -132,16 +144,177 @@ impl SchemaRegistry {
     }
 
     /// Add a schema to the registry.
+    ///
+    /// The schema is checked with [`validate_schema`](Self::validate_schema) first;
+    /// structurally inconsistent schemas are rejected with every problem listed.
     pub fn add_schema(&mut self, schema: Schema) -> Result<(), Box<dyn std::error::Error>> {
+        if let Err(errors) = Self::validate_schema(&schema) {
+            let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            return Err(format!("Inconsistent schema definition: {}", messages.join("; ")).into());
+        }
         self.schemas.insert(schema.id.clone(), schema);
         Ok(())
     }
 
+    /// Check every registered schema for structural consistency.
+    ///
+    /// Returns all problems found across all schemas rather than stopping at the first.
+    pub fn validate_schemas(&self) -> Result<(), Vec<SchemaError>> {
+        let mut schemas: Vec<&Schema> = self.schemas.values().collect();
+        schemas.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let errors: Vec<SchemaError> = schemas
+            .into_iter()
+            .filter_map(|schema| Self::validate_schema(schema).err())
+            .flatten()
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Check a single schema definition for structural consistency.
+    ///
+    /// Flags complex attributes without sub-attributes, complex
+    /// sub-attributes of complex attributes, reference attributes without
+    /// `referenceTypes`, empty names, and duplicate attribute names (compared
+    /// case-insensitively) at any nesting level.
+    pub fn validate_schema(schema: &Schema) -> Result<(), Vec<SchemaError>> {
+        let mut errors = Vec::new();
+        Self::check_attributes(&schema.id, "", &schema.attributes, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Recursively check a list of sibling attributes.
+    fn check_attributes(
+        schema_id: &str,
+        parent: &str,
+        attributes: &[AttributeDefinition],
+        errors: &mut Vec<SchemaError>,
+    ) {
+        let mut seen: Vec<String> = Vec::new();
+
+        for attr in attributes {
+            if attr.name.is_empty() {
+                errors.push(SchemaError::EmptyAttributeName {
+                    schema_id: schema_id.to_string(),
+                    parent: parent.to_string(),
+                });
+                continue;
+            }
+
+            let path = if parent.is_empty() {
+                attr.name.clone()
+            } else {
+                format!("{}.{}", parent, attr.name)
+            };
+
+            let lowered = attr.name.to_lowercase();
+            if seen.contains(&lowered) {
+                errors.push(SchemaError::DuplicateAttribute {
+                    schema_id: schema_id.to_string(),
+                    attribute: path.clone(),
+                });
+            } else {
+                seen.push(lowered);
+            }
+
+            match attr.data_type {
+                // Complex attributes may only be nested one level deep
+                AttributeType::Complex if !parent.is_empty() => {
+                    errors.push(SchemaError::NestedComplexAttributes {
+                        schema_id: schema_id.to_string(),
+                        attribute: path.clone(),
+                    });
+                }
+                AttributeType::Complex if attr.sub_attributes.is_empty() => {
+                    errors.push(SchemaError::ComplexWithoutSubAttributes {
+                        schema_id: schema_id.to_string(),
+                        attribute: path.clone(),
+                    });
+                }
+                AttributeType::Reference if attr.reference_types.is_empty() => {
+                    errors.push(SchemaError::ReferenceWithoutReferenceTypes {
+                        schema_id: schema_id.to_string(),
+                        attribute: path.clone(),
+                    });
+                }
+                _ => {}
+            }
+
+            Self::check_attributes(schema_id, &path, &attr.sub_attributes, errors);
+        }
+    }
+
     /// Get a schema by ID.
     pub fn get_schema_by_id(&self, schema_id: &str) -> Option<&Schema> {
         self.schemas.get(schema_id)
     }
 
+    /// Definition of the attribute or sub-attribute an attribute path
+    /// targets, resolved against the schema `schema_id`.
+    ///
+    /// Paths may be qualified with the URN of any registered schema, such as
+    /// `urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager.value`,
+    /// in which case they resolve against that schema instead. Sub-attributes
+    /// of complex attributes are separated by `.`, names and URNs match
+    /// case-insensitively and value filters are ignored, as in
+    /// [`Schema::attribute_definition`].
+    ///
+    /// Returns `None` if either schema is not registered or the path names no
+    /// attribute.
+    pub fn get_attribute_definition(
+        &self,
+        schema_id: &str,
+        attribute_path: &str,
+    ) -> Option<&AttributeDefinition> {
+        let (schema, attribute) = self.resolve_attribute_path(schema_id, attribute_path)?;
+        schema.attribute_definition(attribute)
+    }
+
+    /// Split an attribute path into the schema it belongs to and the path
+    /// within that schema.
+    ///
+    /// A path qualified with a registered schema URN resolves to that schema,
+    /// with an empty remainder if it names only the schema. Unqualified paths
+    /// resolve to `schema_id`. Returns `None` if that schema is not
+    /// registered, or the path is qualified with an unregistered URN.
+    pub fn resolve_attribute_path<'p>(
+        &self,
+        schema_id: &str,
+        attribute_path: &'p str,
+    ) -> Option<(&Schema, &'p str)> {
+        // A filter value may itself contain ':', so only look before it
+        let unfiltered = attribute_path.split('[').next().unwrap_or(attribute_path);
+        if !unfiltered.contains(':') {
+            return Some((self.get_schema_by_id(schema_id)?, attribute_path));
+        }
+
+        self.schemas
+            .values()
+            .filter_map(|schema| {
+                let prefix = unfiltered.get(..schema.id.len())?;
+                let rest = &attribute_path[schema.id.len()..];
+                let rest = match rest.strip_prefix(':') {
+                    Some(rest) => rest,
+                    None if rest.is_empty() => rest,
+                    None => return None,
+                };
+                prefix
+                    .eq_ignore_ascii_case(&schema.id)
+                    .then_some((schema, rest))
+            })
+            // Prefer the most specific URN if one schema's id prefixes another's
+            .max_by_key(|(schema, _)| schema.id.len())
+    }
+
     /// Validate datetime format using chrono for full RFC3339 compliance
     ///
     /// This leverages chrono's well-tested RFC3339 parser, which provides:
@@ -161,22 +334,6 @@ impl SchemaRegistry {
         DateTime::<FixedOffset>::parse_from_rfc3339(value).is_ok()
     }
 
-    /// Validate base64 encoding (basic character set validation)
-    ///
-    /// This performs basic character set validation for base64 data.
-    /// For production use, consider using a dedicated base64 crate like `base64`
-    /// for proper padding validation and decode verification.
-    pub(super) fn is_valid_base64(&self, value: &str) -> bool {
-        if value.is_empty() {
-            return false;
-        }
-
-        // Basic character set validation - sufficient for type checking
-        // Note: Doesn't validate padding rules or decode correctness
-        let base64_chars = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=";
-        value.chars().all(|c| base64_chars.contains(c))
-    }
-
     /// Validate URI format (basic scheme validation)
     ///
     /// This performs basic URI scheme validation sufficient for SCIM reference checking.
@@ -187,8 +344,11 @@ impl SchemaRegistry {
         }
 
         // Basic scheme validation - sufficient for SCIM reference URIs
-        // Accepts HTTP(S) URLs and URN schemes commonly used in SCIM
-        value.contains("://") || value.starts_with("urn:")
+        // Accepts HTTP(S) URLs and URN schemes commonly used in SCIM, plus
+        // host-relative paths as emitted with relative locations
+        value.contains("://")
+            || value.starts_with("urn:")
+            || (value.starts_with('/') && !value.starts_with("//"))
     }
 
     /// Get the type name of a JSON value for error messages.
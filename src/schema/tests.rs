@@ -3,10 +3,11 @@
 //! This module contains comprehensive tests for schema loading, validation,
 //! and all the various validation scenarios including edge cases and error conditions.
 
+use super::embedded;
 use super::registry::SchemaRegistry;
-use super::types::AttributeType;
+use super::types::{AttributeDefinition, AttributeType, Schema};
 use super::validation::OperationContext;
-use crate::error::ValidationError;
+use crate::error::{SchemaError, ValidationError};
 use serde_json::json;
 
 #[test]
@@ -453,3 +454,273 @@ fn test_group_schema_retrieval() {
         assert!(attr.multi_valued);
     }
 }
+
+#[test]
+fn test_embedded_schemas_are_consistent() {
+    let registry = SchemaRegistry::new().expect("Failed to create registry");
+    assert_eq!(registry.validate_schemas(), Ok(()));
+
+    let spc: Schema = serde_json::from_str(embedded::service_provider_config_schema())
+        .expect("Failed to parse ServiceProviderConfig schema");
+    assert_eq!(SchemaRegistry::validate_schema(&spc), Ok(()));
+}
+
+#[test]
+fn test_schema_consistency_errors_name_attribute() {
+    let schema = Schema {
+        id: "urn:example:schemas:Broken".to_string(),
+        name: "Broken".to_string(),
+        description: "Structurally broken schema".to_string(),
+        attributes: vec![
+            AttributeDefinition {
+                name: "address".to_string(),
+                data_type: AttributeType::Complex,
+                ..Default::default()
+            },
+            AttributeDefinition {
+                name: "owner".to_string(),
+                data_type: AttributeType::Complex,
+                sub_attributes: vec![
+                    AttributeDefinition {
+                        name: "$ref".to_string(),
+                        data_type: AttributeType::Reference,
+                        ..Default::default()
+                    },
+                    AttributeDefinition {
+                        name: "value".to_string(),
+                        ..Default::default()
+                    },
+                    AttributeDefinition {
+                        name: "Value".to_string(),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            AttributeDefinition {
+                name: "Address".to_string(),
+                data_type: AttributeType::String,
+                ..Default::default()
+            },
+        ],
+    };
+
+    let errors = SchemaRegistry::validate_schema(&schema).expect_err("schema should be rejected");
+    let schema_id = schema.id.clone();
+    assert_eq!(
+        errors,
+        vec![
+            SchemaError::ComplexWithoutSubAttributes {
+                schema_id: schema_id.clone(),
+                attribute: "address".to_string(),
+            },
+            SchemaError::ReferenceWithoutReferenceTypes {
+                schema_id: schema_id.clone(),
+                attribute: "owner.$ref".to_string(),
+            },
+            SchemaError::DuplicateAttribute {
+                schema_id: schema_id.clone(),
+                attribute: "owner.Value".to_string(),
+            },
+            SchemaError::DuplicateAttribute {
+                schema_id,
+                attribute: "Address".to_string(),
+            },
+        ]
+    );
+
+    let mut registry = SchemaRegistry::new().expect("Failed to create registry");
+    let err = registry
+        .add_schema(schema)
+        .expect_err("registration should fail");
+    assert!(err.to_string().contains("owner.$ref"));
+    assert!(registry.get_schema("urn:example:schemas:Broken").is_none());
+}
+
+#[test]
+fn test_nested_complex_attributes_rejected_at_registration() {
+    let schema = Schema {
+        id: "urn:example:schemas:Nested".to_string(),
+        name: "Nested".to_string(),
+        description: "Complex attribute nested in a complex attribute".to_string(),
+        attributes: vec![AttributeDefinition {
+            name: "location".to_string(),
+            data_type: AttributeType::Complex,
+            sub_attributes: vec![
+                AttributeDefinition {
+                    name: "building".to_string(),
+                    ..Default::default()
+                },
+                AttributeDefinition {
+                    name: "coordinates".to_string(),
+                    data_type: AttributeType::Complex,
+                    sub_attributes: vec![AttributeDefinition {
+                        name: "latitude".to_string(),
+                        data_type: AttributeType::Decimal,
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }],
+    };
+
+    assert_eq!(
+        SchemaRegistry::validate_schema(&schema),
+        Err(vec![SchemaError::NestedComplexAttributes {
+            schema_id: schema.id.clone(),
+            attribute: "location.coordinates".to_string(),
+        }])
+    );
+
+    let mut registry = SchemaRegistry::new().expect("Failed to create registry");
+    let err = registry
+        .add_schema(schema)
+        .expect_err("registration should fail");
+    assert!(err.to_string().contains("urn:example:schemas:Nested"));
+    assert!(err.to_string().contains("'location.coordinates'"));
+    assert!(registry.get_schema("urn:example:schemas:Nested").is_none());
+}
+
+#[test]
+fn test_apply_defaults_only_fills_missing_attributes() {
+    let registry = SchemaRegistry::new().expect("Failed to create registry");
+    let user_schema = registry.get_user_schema();
+    assert_eq!(user_schema.default_value("active"), Some(&json!(true)));
+
+    let mut omitted = json!({"userName": "defaults"});
+    user_schema.apply_defaults(&mut omitted);
+    assert_eq!(omitted["active"], json!(true));
+
+    let mut explicit = json!({"userName": "defaults", "active": false});
+    user_schema.apply_defaults(&mut explicit);
+    assert_eq!(explicit["active"], json!(false));
+}
+
+#[test]
+fn test_apply_defaults_within_complex_values() {
+    let schema = Schema {
+        id: "urn:example:schemas:Device".to_string(),
+        name: "Device".to_string(),
+        description: "Device with defaulted sub-attributes".to_string(),
+        attributes: vec![AttributeDefinition {
+            name: "ports".to_string(),
+            data_type: AttributeType::Complex,
+            multi_valued: true,
+            sub_attributes: vec![AttributeDefinition {
+                name: "enabled".to_string(),
+                data_type: AttributeType::Boolean,
+                default_value: Some(json!(false)),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+    };
+
+    let mut data = json!({"ports": [{"name": "eth0"}, {"name": "eth1", "enabled": true}]});
+    schema.apply_defaults(&mut data);
+    assert_eq!(data["ports"][0]["enabled"], json!(false));
+    assert_eq!(data["ports"][1]["enabled"], json!(true));
+
+    // Absent complex attributes are not synthesized
+    let mut empty = json!({});
+    schema.apply_defaults(&mut empty);
+    assert!(empty.get("ports").is_none());
+}
+
+#[test]
+fn test_required_attributes_depend_on_operation() {
+    let registry = SchemaRegistry::new().expect("Failed to create registry");
+    let schema = registry.get_user_schema();
+    let partial = json!({"active": false});
+
+    for operation in [OperationContext::Create, OperationContext::Update] {
+        assert!(matches!(
+            registry.validate_resource_for_operation(schema, &partial, operation),
+            Err(ValidationError::MissingRequiredAttribute { ref attribute }) if attribute == "userName"
+        ));
+    }
+    registry
+        .validate_resource_for_operation(schema, &partial, OperationContext::Patch)
+        .expect("PATCH should not re-require userName");
+
+    // Attributes that are present are still validated
+    assert!(
+        registry
+            .validate_resource_for_operation(
+                schema,
+                &json!({"active": "yes"}),
+                OperationContext::Patch
+            )
+            .is_err()
+    );
+}
+
+#[test]
+fn test_get_attribute_definition_by_path() {
+    const USER: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+    const ENTERPRISE: &str = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+
+    let mut registry = SchemaRegistry::new().expect("Failed to create registry");
+    registry
+        .add_schema(Schema {
+            id: ENTERPRISE.to_string(),
+            name: "EnterpriseUser".to_string(),
+            description: String::new(),
+            attributes: vec![AttributeDefinition {
+                name: "manager".to_string(),
+                data_type: AttributeType::Complex,
+                sub_attributes: vec![AttributeDefinition {
+                    name: "value".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        })
+        .expect("Failed to add extension schema");
+
+    let name = |definition: Option<&AttributeDefinition>| definition.map(|d| d.name.clone());
+    assert_eq!(
+        name(registry.get_attribute_definition(USER, "userName")),
+        Some("userName".to_string())
+    );
+    assert_eq!(
+        name(registry.get_attribute_definition(USER, "NAME.givenName")),
+        Some("givenName".to_string())
+    );
+    assert_eq!(
+        name(registry.get_attribute_definition(USER, r#"emails[value co "a:b"].primary"#)),
+        Some("primary".to_string())
+    );
+    assert_eq!(
+        name(registry.get_attribute_definition(USER, &format!("{}:name.familyName", USER))),
+        Some("familyName".to_string())
+    );
+    assert_eq!(
+        name(registry.get_attribute_definition(USER, &format!("{}:manager.value", ENTERPRISE))),
+        Some("value".to_string())
+    );
+
+    assert!(registry.get_attribute_definition(USER, "manager").is_none());
+    assert!(
+        registry
+            .get_attribute_definition(USER, "name.unknown")
+            .is_none()
+    );
+    assert!(
+        registry
+            .get_attribute_definition(USER, "urn:example:unknown:2.0:User:manager")
+            .is_none()
+    );
+    assert!(
+        registry
+            .get_attribute_definition("urn:example:unknown", "id")
+            .is_none()
+    );
+
+    let (schema, rest) = registry
+        .resolve_attribute_path(USER, ENTERPRISE)
+        .expect("schema URN resolves");
+    assert_eq!((schema.id.as_str(), rest), (ENTERPRISE, ""));
+}
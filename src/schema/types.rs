@@ -3,14 +3,16 @@
 //! This module contains the fundamental data structures that define SCIM schemas,
 //! attribute definitions, and their characteristics as specified in RFC 7643.
 
+use crate::error::{ValidationError, ValidationResult};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// A SCIM schema definition.
 ///
 /// Represents a complete schema with its metadata and attribute definitions.
 /// Each schema defines the structure and validation rules for a specific
 /// resource type like User or Group.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Schema {
     /// Unique schema identifier (URI)
     pub id: String,
@@ -22,11 +24,272 @@ pub struct Schema {
     pub attributes: Vec<AttributeDefinition>,
 }
 
+impl Schema {
+    /// Get the default value declared for a top-level attribute, if any.
+    pub fn default_value(&self, attribute: &str) -> Option<&Value> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name.eq_ignore_ascii_case(attribute))
+            .and_then(|attr| attr.default_value.as_ref())
+    }
+
+    /// Populate declared defaults for attributes missing from resource data.
+    ///
+    /// Sub-attribute defaults are applied within complex values that are
+    /// present. Attributes that are present, even as `null`, are left alone.
+    /// Intended for resource creation only, and applied by
+    /// [`ScimServer::create_resource`](crate::ScimServer::create_resource)
+    /// rather than by providers.
+    pub fn apply_defaults(&self, data: &mut Value) {
+        apply_attribute_defaults(&self.attributes, data);
+    }
+
+    /// Rename attribute keys in resource data to the schema's casing.
+    ///
+    /// SCIM attribute names are case-insensitive (RFC 7643 Section 2.1), so
+    /// `username` becomes `userName`. Sub-attributes of complex values are
+    /// renamed recursively, and the common attributes `schemas`, `id`,
+    /// `externalId` and `meta` are included. Keys the schema does not define
+    /// are left as they are, as is a variant whose canonical key is also
+    /// present.
+    pub fn normalize_attribute_names(&self, data: &mut Value) {
+        normalize_attribute_names(&self.attributes, COMMON_ATTRIBUTES, data);
+    }
+
+    /// Convert string-encoded values in resource data to the `boolean`,
+    /// `integer` or `decimal` type their attribute declares.
+    ///
+    /// Sub-attributes and the items of multi-valued attributes are included,
+    /// and keys the schema does not define are left alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidBooleanValue`],
+    /// [`ValidationError::InvalidIntegerValue`] or
+    /// [`ValidationError::InvalidDecimalFormat`] for a string that does not
+    /// parse as its attribute's type.
+    pub fn coerce_values(&self, data: &mut Value) -> ValidationResult<()> {
+        coerce_attribute_values(&self.attributes, data)
+    }
+
+    /// Paths of the attributes and sub-attributes whose values are
+    /// `caseExact`, e.g. `externalId` or `groups.value`.
+    pub fn case_exact_attributes(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        for attribute in &self.attributes {
+            if attribute.case_exact {
+                paths.push(attribute.name.clone());
+            }
+            for sub_attribute in &attribute.sub_attributes {
+                if sub_attribute.case_exact {
+                    paths.push(format!("{}.{}", attribute.name, sub_attribute.name));
+                }
+            }
+        }
+        paths
+    }
+
+    /// Definition of the attribute or sub-attribute an attribute path
+    /// targets, such as `name.givenName` or `emails[type eq "work"].primary`.
+    ///
+    /// Names match case-insensitively and value filters are ignored.
+    pub fn attribute_definition(&self, path: &str) -> Option<&AttributeDefinition> {
+        let mut unfiltered = String::with_capacity(path.len());
+        let mut depth = 0usize;
+        for c in path.chars() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth = depth.saturating_sub(1),
+                c if depth == 0 => unfiltered.push(c),
+                _ => {}
+            }
+        }
+
+        let mut attributes = self.attributes.as_slice();
+        let mut definition = None;
+        for name in unfiltered.split('.') {
+            let found = attributes
+                .iter()
+                .find(|attr| attr.name.eq_ignore_ascii_case(name))?;
+            attributes = &found.sub_attributes;
+            definition = Some(found);
+        }
+        definition
+    }
+}
+
+impl AttributeDefinition {
+    /// Convert a string-encoded value of this attribute to its declared
+    /// `boolean`, `integer` or `decimal` type, as
+    /// [`Schema::coerce_values`] does.
+    pub fn coerce_value(&self, value: &mut Value) -> ValidationResult<()> {
+        let coerced = match value {
+            Value::Array(items) => {
+                return items
+                    .iter_mut()
+                    .try_for_each(|item| self.coerce_value(item));
+            }
+            Value::Object(_) => {
+                return coerce_attribute_values(&self.sub_attributes, value)
+                    .map_err(|e| prefix_attribute(e, &self.name));
+            }
+            Value::String(s) => {
+                let s = s.trim();
+                let attribute = self.name.clone();
+                match self.data_type {
+                    AttributeType::Boolean if s.eq_ignore_ascii_case("true") => true.into(),
+                    AttributeType::Boolean if s.eq_ignore_ascii_case("false") => false.into(),
+                    AttributeType::Boolean => {
+                        return Err(ValidationError::InvalidBooleanValue {
+                            attribute,
+                            value: s.to_string(),
+                        });
+                    }
+                    AttributeType::Integer => s.parse::<i64>().map(Value::from).map_err(|_| {
+                        ValidationError::InvalidIntegerValue {
+                            attribute,
+                            value: s.to_string(),
+                        }
+                    })?,
+                    AttributeType::Decimal => s
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(serde_json::Number::from_f64)
+                        .map(Value::Number)
+                        .ok_or_else(|| ValidationError::InvalidDecimalFormat {
+                            attribute,
+                            value: s.to_string(),
+                        })?,
+                    _ => return Ok(()),
+                }
+            }
+            _ => return Ok(()),
+        };
+        *value = coerced;
+        Ok(())
+    }
+}
+
+const COMMON_ATTRIBUTES: &[&str] = &["schemas", "id", "externalId", "meta"];
+
+fn normalize_attribute_names(
+    attributes: &[AttributeDefinition],
+    common: &[&str],
+    data: &mut Value,
+) {
+    let Some(object) = data.as_object_mut() else {
+        return;
+    };
+
+    let renames: Vec<(String, &str)> = object
+        .keys()
+        .filter_map(|key| {
+            let canonical = attributes
+                .iter()
+                .map(|attr| attr.name.as_str())
+                .chain(common.iter().copied())
+                .find(|name| name.eq_ignore_ascii_case(key))?;
+            (canonical != key && !object.contains_key(canonical)).then(|| (key.clone(), canonical))
+        })
+        .collect();
+    for (key, canonical) in renames {
+        if let Some(value) = object.remove(&key) {
+            object.insert(canonical.to_string(), value);
+        }
+    }
+
+    for attr in attributes
+        .iter()
+        .filter(|attr| !attr.sub_attributes.is_empty())
+    {
+        match object.get_mut(&attr.name) {
+            Some(Value::Array(items)) => items
+                .iter_mut()
+                .for_each(|item| normalize_attribute_names(&attr.sub_attributes, &[], item)),
+            Some(value) => normalize_attribute_names(&attr.sub_attributes, &[], value),
+            None => {}
+        }
+    }
+}
+
+fn coerce_attribute_values(
+    attributes: &[AttributeDefinition],
+    data: &mut Value,
+) -> ValidationResult<()> {
+    let Some(object) = data.as_object_mut() else {
+        return Ok(());
+    };
+
+    for (key, value) in object.iter_mut() {
+        if let Some(attr) = attributes
+            .iter()
+            .find(|attr| attr.name.eq_ignore_ascii_case(key))
+        {
+            attr.coerce_value(value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Qualify the attribute named in a sub-attribute coercion error with its parent.
+fn prefix_attribute(error: ValidationError, parent: &str) -> ValidationError {
+    match error {
+        ValidationError::InvalidBooleanValue { attribute, value } => {
+            ValidationError::InvalidBooleanValue {
+                attribute: format!("{}.{}", parent, attribute),
+                value,
+            }
+        }
+        ValidationError::InvalidIntegerValue { attribute, value } => {
+            ValidationError::InvalidIntegerValue {
+                attribute: format!("{}.{}", parent, attribute),
+                value,
+            }
+        }
+        ValidationError::InvalidDecimalFormat { attribute, value } => {
+            ValidationError::InvalidDecimalFormat {
+                attribute: format!("{}.{}", parent, attribute),
+                value,
+            }
+        }
+        other => other,
+    }
+}
+
+fn apply_attribute_defaults(attributes: &[AttributeDefinition], data: &mut Value) {
+    let Some(object) = data.as_object_mut() else {
+        return;
+    };
+
+    for attr in attributes {
+        let existing = object
+            .keys()
+            .find(|key| key.eq_ignore_ascii_case(&attr.name))
+            .cloned();
+
+        match existing {
+            Some(key) if !attr.sub_attributes.is_empty() => match object.get_mut(&key) {
+                Some(Value::Array(items)) => items
+                    .iter_mut()
+                    .for_each(|item| apply_attribute_defaults(&attr.sub_attributes, item)),
+                Some(value) => apply_attribute_defaults(&attr.sub_attributes, value),
+                None => {}
+            },
+            Some(_) => {}
+            None => {
+                if let Some(default) = &attr.default_value {
+                    object.insert(attr.name.clone(), default.clone());
+                }
+            }
+        }
+    }
+}
+
 /// Definition of a SCIM attribute.
 ///
 /// Defines all characteristics of an attribute including type,
 /// constraints, and validation rules.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AttributeDefinition {
     /// Attribute name
@@ -55,6 +318,20 @@ pub struct AttributeDefinition {
     /// How the attribute is returned in responses
     #[serde(default)]
     pub returned: Option<String>,
+    /// Resource types a reference attribute may point at
+    #[serde(
+        rename = "referenceTypes",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub reference_types: Vec<String>,
+    /// Value populated on create when the attribute is omitted
+    #[serde(
+        rename = "defaultValue",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default_value: Option<Value>,
 }
 
 impl Default for AttributeDefinition {
@@ -70,6 +347,8 @@ impl Default for AttributeDefinition {
             canonical_values: Vec::new(),
             sub_attributes: Vec::new(),
             returned: None,
+            reference_types: Vec::new(),
+            default_value: None,
         }
     }
 }
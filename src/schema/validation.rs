@@ -5,6 +5,7 @@
 //! - Schema-driven validation for complex attributes and business rules
 //! - JSON flexibility for extensible attributes
 
+use super::binary;
 use super::registry::SchemaRegistry;
 use super::types::{AttributeDefinition, AttributeType, Uniqueness};
 use crate::error::{ValidationError, ValidationResult};
@@ -16,9 +17,12 @@ use serde_json::{Map, Value};
 /// Operation context for SCIM resource validation.
 ///
 /// Different SCIM operations have different validation requirements:
-/// - CREATE: Server generates ID, readonly attributes forbidden
-/// - UPDATE: ID required, readonly attributes ignored/forbidden
-/// - PATCH: ID required, partial updates allowed
+/// - CREATE: Server generates ID, readonly attributes forbidden, required
+///   attributes must be present
+/// - UPDATE: ID required, readonly attributes ignored/forbidden, required
+///   attributes must be present since the payload replaces the resource
+/// - PATCH: ID required, partial updates allowed, required attributes are
+///   not re-required; only a patch that removes one is rejected
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OperationContext {
     /// Resource creation operation - server generates ID and metadata
@@ -192,6 +196,57 @@ impl SchemaRegistry {
         Ok(())
     }
 
+    /// Validate a resource against an explicit set of schemas.
+    ///
+    /// See [`Resource::validate_against`].
+    pub(crate) fn validate_resource_against(
+        &self,
+        resource: &Resource,
+        schemas: &[&super::types::Schema],
+    ) -> ValidationResult<()> {
+        let (base, extensions) = schemas
+            .split_first()
+            .ok_or(ValidationError::MissingBaseSchema)?;
+
+        let declared = resource.get_schemas();
+        if !declared.contains(&base.id) {
+            return Err(ValidationError::MissingBaseSchema);
+        }
+        if let Some(uri) = declared
+            .iter()
+            .find(|uri| !schemas.iter().any(|schema| schema.id == **uri))
+        {
+            return Err(ValidationError::UnknownSchemaUri { uri: uri.clone() });
+        }
+
+        let mut resource_json = resource.to_json()?;
+        let obj = resource_json
+            .as_object_mut()
+            .ok_or_else(|| ValidationError::custom("Resource must be a JSON object"))?;
+
+        // Extension attributes live under their schema URN and are only
+        // validated, required attributes included, when the URN is declared
+        let mut extension_data = Vec::new();
+        for extension in extensions {
+            let data = obj.remove(&extension.id);
+            if declared.contains(&extension.id) {
+                extension_data.push((extension, data.unwrap_or_else(|| Value::Object(Map::new()))));
+            } else if data.is_some() {
+                return Err(ValidationError::UnknownAttributeForSchema {
+                    attribute: extension.id.clone(),
+                    schema: base.id.clone(),
+                });
+            }
+        }
+
+        self.validate_resource(base, &resource_json)?;
+        for (extension, data) in extension_data {
+            self.validate_resource(extension, &data)?;
+        }
+
+        Ok(())
+    }
+
     /// Validate resource against a specific schema.
     fn validate_against_schema(
         &self,
@@ -229,29 +284,20 @@ impl SchemaRegistry {
         resource_json: &Value,
         context: OperationContext,
     ) -> ValidationResult<()> {
-        // First validate schemas are present and not empty
-        if let Some(schemas_value) = resource_json.get("schemas") {
-            if let Some(schemas_array) = schemas_value.as_array() {
-                if schemas_array.is_empty() {
-                    return Err(ValidationError::EmptySchemas);
-                }
-
-                // Check for duplicate schema URIs
-                let mut seen_schemas = std::collections::HashSet::new();
-                for schema_value in schemas_array {
-                    if let Some(schema_uri) = schema_value.as_str() {
-                        if !seen_schemas.insert(schema_uri) {
-                            return Err(ValidationError::DuplicateSchemaUri {
-                                uri: schema_uri.to_string(),
-                            });
-                        }
-                    }
-                }
-            } else {
-                return Err(ValidationError::MissingSchemas);
-            }
-        } else {
+        // First validate schemas are present and well-formed
+        let Some(schemas_value) = resource_json.get("schemas") else {
             return Err(ValidationError::MissingSchemas);
+        };
+        let schemas = SchemaUri::parse_schemas(schemas_value)?;
+
+        // Check for duplicate schema URIs
+        let mut seen_schemas = std::collections::HashSet::new();
+        for schema_uri in &schemas {
+            if !seen_schemas.insert(schema_uri.as_str()) {
+                return Err(ValidationError::DuplicateSchemaUri {
+                    uri: schema_uri.to_string(),
+                });
+            }
         }
 
         // Validate meta.resourceType requirement - only if meta object exists
@@ -414,8 +460,13 @@ impl SchemaRegistry {
             return Ok(());
         }
 
-        // Validate data type
-        self.validate_attribute_value(attr_def, value)?;
+        // Validate JSON shape against multiValued, then each value's data type
+        Self::validate_multi_valued_shape(attr_def, value, &attr_def.name)?;
+        if value.is_array() {
+            self.validate_multi_valued_array(attr_def, value)?;
+        } else {
+            self.validate_attribute_value(attr_def, value)?;
+        }
 
         // Validate mutability if this is an update operation
         // (This would need request context to determine operation type)
@@ -426,6 +477,25 @@ impl SchemaRegistry {
         Ok(())
     }
 
+    /// Check that a value is an array exactly when the attribute is
+    /// multi-valued. `path` names the attribute in the error.
+    fn validate_multi_valued_shape(
+        attr_def: &AttributeDefinition,
+        value: &Value,
+        path: &str,
+    ) -> ValidationResult<()> {
+        match (attr_def.multi_valued, value) {
+            (_, Value::Null) => Ok(()),
+            (true, value) if !value.is_array() => Err(ValidationError::SingleValueForMultiValued {
+                attribute: path.to_string(),
+            }),
+            (false, Value::Array(_)) => Err(ValidationError::ArrayForSingleValued {
+                attribute: path.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
     /// Validate an attribute value against its expected data type.
     fn validate_attribute_value(
         &self,
@@ -520,12 +590,12 @@ impl SchemaRegistry {
             }
             AttributeType::Binary => {
                 if let Some(binary_str) = value.as_str() {
-                    if !self.is_valid_base64(binary_str) {
-                        return Err(ValidationError::InvalidBinaryData {
+                    binary::check_base64(binary_str).map_err(|details| {
+                        ValidationError::InvalidBinaryData {
                             attribute: attr_def.name.clone(),
-                            details: "Invalid base64 encoding".to_string(),
-                        });
-                    }
+                            details,
+                        }
+                    })?;
                 } else {
                     return Err(ValidationError::InvalidAttributeType {
                         attribute: attr_def.name.clone(),
@@ -690,15 +760,15 @@ impl SchemaRegistry {
                 actual: Self::get_value_type(value).to_string(),
             })?;
 
+        // Reject nested complex definitions before looking at the value
+        self.validate_no_nested_complex(attr_def, obj)?;
+
         // Validate known sub-attributes
         self.validate_known_sub_attributes(attr_def, obj)?;
 
         // Validate sub-attribute types
         self.validate_sub_attribute_types(attr_def, obj)?;
 
-        // Validate no nested complex attributes
-        self.validate_no_nested_complex(attr_def, obj)?;
-
         // Validate required sub-attributes
         self.validate_required_sub_attributes_complex(attr_def, obj)?;
 
@@ -737,11 +807,18 @@ impl SchemaRegistry {
     ) -> ValidationResult<()> {
         for (key, value) in obj {
             if let Some(sub_attr_def) = attr_def.sub_attributes.iter().find(|sa| sa.name == *key) {
-                self.validate_attribute_value_with_context(
-                    sub_attr_def,
-                    value,
-                    Some(&attr_def.name),
-                )?;
+                let path = format!("{}.{}", attr_def.name, sub_attr_def.name);
+                Self::validate_multi_valued_shape(sub_attr_def, value, &path)?;
+                for item in value
+                    .as_array()
+                    .map_or(std::slice::from_ref(value), Vec::as_slice)
+                {
+                    self.validate_attribute_value_with_context(
+                        sub_attr_def,
+                        item,
+                        Some(&attr_def.name),
+                    )?;
+                }
             }
         }
         Ok(())
@@ -950,6 +1027,21 @@ impl SchemaRegistry {
         &self,
         schema: &super::types::Schema,
         resource: &Value,
+    ) -> ValidationResult<()> {
+        self.validate_resource_for_operation(schema, resource, OperationContext::Create)
+    }
+
+    /// Validate a resource against a specific schema for an operation.
+    ///
+    /// Create and replace payloads must carry every required attribute. A
+    /// PATCH payload only describes changes, so for
+    /// [`OperationContext::Patch`] absent required attributes are accepted
+    /// and only the attributes present are validated.
+    pub fn validate_resource_for_operation(
+        &self,
+        schema: &super::types::Schema,
+        resource: &Value,
+        operation: OperationContext,
     ) -> ValidationResult<()> {
         let obj = resource
             .as_object()
@@ -959,7 +1051,7 @@ impl SchemaRegistry {
         for attr_def in &schema.attributes {
             if let Some(value) = obj.get(&attr_def.name) {
                 self.validate_attribute(attr_def, value)?;
-            } else if attr_def.required {
+            } else if attr_def.required && operation != OperationContext::Patch {
                 return Err(ValidationError::MissingRequiredAttribute {
                     attribute: attr_def.name.clone(),
                 });
@@ -251,6 +251,72 @@ pub struct AuthenticationScheme {
     pub primary: bool,
 }
 
+impl AuthenticationScheme {
+    /// Create a scheme with the given type, name and description.
+    pub fn new(
+        auth_type: impl Into<String>,
+        name: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            spec_uri: None,
+            documentation_uri: None,
+            auth_type: auth_type.into(),
+            primary: false,
+        }
+    }
+
+    /// OAuth 2.0 (RFC 6749), type `oauth2`.
+    pub fn oauth2() -> Self {
+        Self::new(
+            "oauth2",
+            "OAuth 2.0",
+            "Authentication scheme using the OAuth 2.0 Authorization Framework",
+        )
+        .with_spec_uri("https://www.rfc-editor.org/info/rfc6749")
+    }
+
+    /// HTTP Basic (RFC 7617), type `httpbasic`.
+    pub fn http_basic() -> Self {
+        Self::new(
+            "httpbasic",
+            "HTTP Basic",
+            "Authentication scheme using the HTTP Basic Standard",
+        )
+        .with_spec_uri("https://www.rfc-editor.org/info/rfc7617")
+    }
+
+    /// OAuth bearer tokens (RFC 6750), type `oauthbearertoken`.
+    pub fn bearer() -> Self {
+        Self::new(
+            "oauthbearertoken",
+            "OAuth Bearer Token",
+            "Authentication scheme using the OAuth Bearer Token Standard",
+        )
+        .with_spec_uri("https://www.rfc-editor.org/info/rfc6750")
+    }
+
+    /// Set the URI of the scheme's specification.
+    pub fn with_spec_uri(mut self, spec_uri: impl Into<String>) -> Self {
+        self.spec_uri = Some(spec_uri.into());
+        self
+    }
+
+    /// Set the URI of the server's documentation for this scheme.
+    pub fn with_documentation_uri(mut self, documentation_uri: impl Into<String>) -> Self {
+        self.documentation_uri = Some(documentation_uri.into());
+        self
+    }
+
+    /// Mark this scheme as the primary authentication method.
+    pub fn as_primary(mut self) -> Self {
+        self.primary = true;
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,104 @@
+//! External authorization decisions.
+//!
+//! Tenant permissions decide which kinds of operation a tenant may perform at
+//! all. An [`AuthorizationPolicy`] is consulted after them, before each
+//! resource operation reaches the provider, so that a central policy engine
+//! can allow or deny individual requests. It sees the request context (tenant
+//! and client), the operation, the resource type and, for operations on a
+//! single resource, the resource id.
+//!
+//! The server allows everything until a policy is installed with
+//! [`set_authorization_policy`](ScimServer::set_authorization_policy). A
+//! policy denies a request by returning
+//! [`ScimError::AuthorizationDenied`], which is reported as a 403; any other
+//! error it returns, such as [`ScimError::Unavailable`] when the policy
+//! engine cannot be reached, is passed through unchanged.
+
+use super::core::ScimServer;
+use crate::error::{ScimError, ScimResult};
+use crate::providers::ResourceProvider;
+use crate::resource::{RequestContext, ScimOperation};
+use std::sync::Arc;
+
+/// Decides whether a request may perform an operation.
+///
+/// # Examples
+///
+/// ```rust
+/// use scim_server::{AuthorizationPolicy, RequestContext, ScimError, ScimOperation};
+///
+/// /// Only the provisioning client may delete resources.
+/// struct DeleteOnlyByProvisioner;
+///
+/// impl AuthorizationPolicy for DeleteOnlyByProvisioner {
+///     fn authorize(
+///         &self,
+///         context: &RequestContext,
+///         operation: &ScimOperation,
+///         resource_type: &str,
+///         resource_id: Option<&str>,
+///     ) -> Result<(), ScimError> {
+///         if *operation == ScimOperation::Delete && context.client_id() != Some("provisioner") {
+///             return Err(ScimError::authorization_denied(
+///                 operation,
+///                 resource_type,
+///                 resource_id,
+///                 "only the provisioning client may delete resources",
+///             ));
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait AuthorizationPolicy: Send + Sync {
+    /// Allow the operation by returning `Ok(())`, or deny it with
+    /// [`ScimError::AuthorizationDenied`].
+    ///
+    /// `resource_id` is set for operations on a single existing resource
+    /// (read, update, patch and delete) and `None` for creates, lists and
+    /// searches.
+    fn authorize(
+        &self,
+        context: &RequestContext,
+        operation: &ScimOperation,
+        resource_type: &str,
+        resource_id: Option<&str>,
+    ) -> Result<(), ScimError>;
+}
+
+/// Policy allowing every request; the server's default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAll;
+
+impl AuthorizationPolicy for AllowAll {
+    fn authorize(
+        &self,
+        _context: &RequestContext,
+        _operation: &ScimOperation,
+        _resource_type: &str,
+        _resource_id: Option<&str>,
+    ) -> Result<(), ScimError> {
+        Ok(())
+    }
+}
+
+impl<P: ResourceProvider> ScimServer<P> {
+    /// Install the policy consulted before each resource operation,
+    /// replacing the current one.
+    pub fn set_authorization_policy(&mut self, policy: impl AuthorizationPolicy + 'static) {
+        self.authorization_policy = Arc::new(policy);
+    }
+
+    /// Ask the authorization policy whether the request may perform the
+    /// operation.
+    pub(crate) fn ensure_operation_authorized(
+        &self,
+        context: &RequestContext,
+        operation: &ScimOperation,
+        resource_type: &str,
+        resource_id: Option<&str>,
+    ) -> ScimResult<()> {
+        self.authorization_policy
+            .authorize(context, operation, resource_type, resource_id)
+    }
+}
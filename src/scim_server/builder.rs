@@ -5,8 +5,13 @@
 //! This is essential for proper $ref field generation in SCIM responses.
 
 use crate::error::ScimError;
-use crate::providers::ResourceProvider;
+use crate::multi_tenant::{ScimTenantConfiguration, UnknownAttributePolicy};
+use crate::providers::{DEFAULT_TENANT_ID, ResourceProvider};
+use crate::resource::serialization::serialized_size;
+use crate::schema_discovery::AuthenticationScheme;
 use crate::scim_server::ScimServer;
+use serde_json::Value;
+use std::collections::HashMap;
 
 /// Strategy for handling tenant information in URLs.
 ///
@@ -34,6 +39,214 @@ impl Default for TenantStrategy {
     }
 }
 
+/// Where a resource's version is exposed in operation responses.
+///
+/// Both locations always carry the same underlying version; this only controls
+/// which of them are emitted. Some clients reject responses that carry the
+/// version both in the body and as an ETag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VersionExposure {
+    /// `meta.version` in the resource body and `etag`/`version` in the
+    /// response metadata. This is the default.
+    #[default]
+    Both,
+
+    /// Only `meta.version` in the resource body.
+    MetaOnly,
+
+    /// Only `etag`/`version` in the response metadata; `meta.version` is
+    /// omitted from the resource body.
+    EtagOnly,
+}
+
+/// How resource locations are emitted in `meta.location` and `$ref`.
+///
+/// Behind a gateway that rewrites hosts, absolute URLs built from the
+/// configured base URL point at the wrong host. Relative locations keep only
+/// the path, which clients resolve against the URL they called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LocationPolicy {
+    /// Absolute URLs built from the base URL. This is the default.
+    #[default]
+    Absolute,
+
+    /// Host-relative paths such as `/v2/Users/{id}`.
+    Relative,
+
+    /// No `meta.location` or `$ref` at all.
+    Omit,
+}
+
+/// How Group `members` entries sharing a `value` are handled on writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateMemberPolicy {
+    /// Keep the first entry for each member and drop later ones. This is the
+    /// default.
+    #[default]
+    Deduplicate,
+
+    /// Reject the request with a `uniqueness` error.
+    Reject,
+}
+
+/// How the `groups` attribute of Users is produced on reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupMembership {
+    /// Return `groups` as stored with the User. This is the default.
+    #[default]
+    Stored,
+
+    /// Derive `groups` from the Groups listing the User as a member.
+    Direct,
+
+    /// Derive `groups` from direct memberships and the Groups containing
+    /// those Groups, following at most `max_depth` levels of membership
+    /// (`1` is direct memberships only).
+    Nested {
+        /// Maximum number of membership levels followed
+        max_depth: usize,
+    },
+}
+
+/// How list results are ordered when the request does not set `sortBy`.
+///
+/// A request's `sortBy` and `sortOrder` always take precedence. Results that
+/// tie on the sort attribute keep the provider's order, which for
+/// [`StandardResourceProvider`](crate::providers::StandardResourceProvider)
+/// is by resource id, so pagination is stable under either default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DefaultListOrder {
+    /// The provider's order, by resource id for the standard provider. This
+    /// is the default.
+    #[default]
+    Id,
+
+    /// Newest first by `meta.created`.
+    CreatedDescending,
+}
+
+/// How a missing `display` is filled in on `emails` and `phoneNumbers`
+/// entries.
+///
+/// See the [`entry_display`](super::entry_display) module. A `display` the
+/// client sent is never replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EntryDisplayPolicy {
+    /// Leave entries as sent. This is the default.
+    #[default]
+    Off,
+
+    /// Use the entry's `value`, e.g. `bjensen@example.com`.
+    Value,
+
+    /// Prefix the entry's `value` with its `type` when it has one, e.g.
+    /// `work: bjensen@example.com`.
+    TypeAndValue,
+}
+
+/// How the operations of a PATCH request are applied.
+///
+/// See the [`partial_patch`](super::partial_patch) module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PatchMode {
+    /// Apply every operation or none: the first failing operation rejects
+    /// the whole request. This is the default, as RFC 7644 §3.5.2 requires.
+    #[default]
+    Atomic,
+
+    /// Apply the operations in order as one write, skipping those that fail
+    /// and reporting the outcome of every operation alongside the resulting
+    /// resource.
+    PerOperation,
+}
+
+impl VersionExposure {
+    /// Whether `meta.version` is included in resource bodies.
+    pub fn includes_meta_version(&self) -> bool {
+        matches!(self, Self::Both | Self::MetaOnly)
+    }
+
+    /// Whether `etag`/`version` are included in response metadata.
+    pub fn includes_etag(&self) -> bool {
+        matches!(self, Self::Both | Self::EtagOnly)
+    }
+}
+
+/// How resource type names in requests are matched to registered types.
+///
+/// Requests often name the endpoint (`Users`, `/users/`) rather than the
+/// registered resource type (`User`). Matching is exact by default; a name
+/// that resolves is always replaced by the canonical registered name, so
+/// `meta.resourceType` and `$ref` URLs never echo the incoming spelling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResourceTypeResolution {
+    /// Match names ignoring ASCII case.
+    pub case_insensitive: bool,
+    /// Accept a type's plural endpoint name, e.g. `Users` for `User`.
+    pub plural_endpoints: bool,
+    /// Ignore leading and trailing slashes, e.g. `/User/`.
+    pub trim_slashes: bool,
+}
+
+impl ResourceTypeResolution {
+    /// Exact, case-sensitive matching. This is the default.
+    pub fn exact() -> Self {
+        Self::default()
+    }
+
+    /// Case-insensitive matching of singular or plural names, ignoring
+    /// surrounding slashes.
+    pub fn lenient() -> Self {
+        Self {
+            case_insensitive: true,
+            plural_endpoints: true,
+            trim_slashes: true,
+        }
+    }
+
+    /// Normalize a name for lookup under these rules.
+    pub fn normalize(&self, name: &str) -> String {
+        let name = if self.trim_slashes {
+            name.trim_matches('/')
+        } else {
+            name
+        };
+        if self.case_insensitive {
+            name.to_ascii_lowercase()
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Normalized names a registered resource type answers to.
+    pub fn aliases(&self, resource_type: &str) -> Vec<String> {
+        let mut aliases = vec![self.normalize(resource_type)];
+        if self.plural_endpoints {
+            aliases.push(self.normalize(&endpoint_name(resource_type)));
+        }
+        aliases
+    }
+}
+
+/// Plural endpoint name for a resource type, e.g. `Users` for `User`.
+pub fn endpoint_name(resource_type: &str) -> String {
+    let lower = resource_type.to_ascii_lowercase();
+    let consonant_y = lower
+        .strip_suffix('y')
+        .and_then(|stem| stem.chars().last())
+        .is_some_and(|c| !"aeiou".contains(c));
+    if consonant_y {
+        format!("{}ies", &resource_type[..resource_type.len() - 1])
+    } else if ["s", "x", "ch", "sh"]
+        .iter()
+        .any(|suffix| lower.ends_with(suffix))
+    {
+        format!("{}es", resource_type)
+    } else {
+        format!("{}s", resource_type)
+    }
+}
+
 /// Configuration for SCIM server endpoint URLs and tenant handling.
 ///
 /// This configuration is used to generate proper $ref fields in SCIM
@@ -50,6 +263,145 @@ pub struct ScimServerConfig {
 
     /// SCIM protocol version to use in URLs. Defaults to "v2".
     pub scim_version: String,
+
+    /// SCIM protocol version the server implements, advertised in the `meta`
+    /// of the ServiceProviderConfig and ResourceType discovery documents.
+    /// Must be one of [`SCIM_PROTOCOL_VERSIONS`]. Defaults to "2.0".
+    pub protocol_version: String,
+
+    /// Path to the SCIM endpoints, replacing the SCIM version segment in URLs
+    /// when set. Examples: "/scim/v2", "/api/scim". Defaults to `None`.
+    pub base_path: Option<String>,
+
+    /// Maximum size in bytes of an incoming request payload, measured on its
+    /// serialized JSON form. `None` disables the check. Defaults to `None`.
+    pub max_payload_size: Option<usize>,
+
+    /// Where resource versions are exposed in responses. Defaults to both
+    /// `meta.version` and the ETag.
+    pub version_exposure: VersionExposure,
+
+    /// Whether to derive a missing User `displayName` from `name` on create
+    /// and update. Defaults to `false`.
+    pub derive_display_name: bool,
+
+    /// Authentication schemes advertised in the ServiceProviderConfig. When
+    /// empty, schemes reported by the provider are used. Defaults to empty.
+    pub authentication_schemes: Vec<AuthenticationScheme>,
+
+    /// How request resource type names are matched to registered types.
+    /// Defaults to exact matching.
+    pub resource_type_resolution: ResourceTypeResolution,
+
+    /// How `meta.location` and `$ref` values are emitted. Defaults to
+    /// absolute URLs.
+    pub location_policy: LocationPolicy,
+
+    /// Whether schema URNs not registered for a resource type are rejected
+    /// on create and update. When `false` they pass through unvalidated.
+    /// Defaults to `true`.
+    pub strict_schema_extensions: bool,
+
+    /// How attributes the schema does not define are handled on create,
+    /// update and PATCH. Defaults to [`UnknownAttributePolicy::Reject`].
+    pub unknown_attributes: UnknownAttributePolicy,
+
+    /// Whether required attributes must be present on create and update and
+    /// may not be emptied by PATCH. Defaults to `true`.
+    pub enforce_required: bool,
+
+    /// Whether attribute keys in create and update payloads are renamed to
+    /// the schema's casing before validation. Defaults to `false`.
+    pub normalize_attribute_names: bool,
+
+    /// Whether string-encoded `boolean`, `integer` and `decimal` values in
+    /// create, update and PATCH payloads are converted to their schema type.
+    /// Defaults to `false`.
+    pub coerce_attribute_values: bool,
+
+    /// Whether the stored `externalId` is returned in resource responses.
+    /// Defaults to `true`.
+    pub echo_external_id: bool,
+
+    /// Whether deleting a resource that does not exist succeeds instead of
+    /// failing with not-found. Defaults to `false`, the RFC 7644 behavior.
+    pub idempotent_delete: bool,
+
+    /// Response size in bytes from which operation responses are marked
+    /// [`compressible`](crate::operation_handler::OperationMetadata::compressible).
+    /// `None` never marks responses. Defaults to 1 KiB.
+    pub compression_threshold: Option<usize>,
+
+    /// Tenant ID under which requests without tenant context are stored,
+    /// passed to the provider with
+    /// [`ResourceProvider::set_default_tenant_id`]. Defaults to
+    /// [`DEFAULT_TENANT_ID`] (`"default"`), so data stored before this was
+    /// configurable stays reachable unless it is changed.
+    pub default_tenant_id: String,
+
+    /// How Group `members` entries sharing a `value` are handled on create,
+    /// update and PATCH `add`. Defaults to keeping the first entry.
+    pub duplicate_members: DuplicateMemberPolicy,
+
+    /// Maximum number of `members` a Group may have after a create, update
+    /// or PATCH, unless the tenant's configuration sets its own. `None`, the
+    /// default, leaves membership unbounded.
+    pub max_group_members: Option<usize>,
+
+    /// How the `groups` attribute of Users is produced on get and list.
+    /// Defaults to returning it as stored.
+    pub group_membership: GroupMembership,
+
+    /// How list results are ordered when the request does not set `sortBy`.
+    /// Defaults to the provider's order by resource id.
+    pub default_list_order: DefaultListOrder,
+
+    /// How a missing `display` is derived for `emails` and `phoneNumbers`
+    /// entries, and a missing `formatted` for `addresses`, on create and
+    /// update. Defaults to [`EntryDisplayPolicy::Off`].
+    pub entry_display: EntryDisplayPolicy,
+
+    /// Whether PATCH requests are applied atomically or operation by
+    /// operation. Defaults to [`PatchMode::Atomic`].
+    pub patch_mode: PatchMode,
+
+    /// Maximum number of resources returned per list page, reported as
+    /// `filter.maxResults` in the ServiceProviderConfig. `None` leaves pages
+    /// uncapped. Defaults to `None`.
+    pub max_page_size: Option<usize>,
+
+    /// Maximum list page size per resource type, taking precedence over
+    /// [`max_page_size`](Self::max_page_size). Defaults to empty.
+    pub resource_type_max_page_sizes: HashMap<String, usize>,
+
+    /// Whether ResourceType definitions report the page size cap that
+    /// applies to them as `maxResults`. Defaults to `false`.
+    pub advertise_resource_type_page_sizes: bool,
+
+    /// SCIM configuration per tenant, by tenant ID. Defaults to empty.
+    pub tenant_configurations: HashMap<String, ScimTenantConfiguration>,
+}
+
+/// SCIM protocol versions a server may advertise.
+pub const SCIM_PROTOCOL_VERSIONS: &[&str] = &["1.0", "1.1", "2.0"];
+
+/// Default SCIM protocol version.
+pub const DEFAULT_SCIM_PROTOCOL_VERSION: &str = "2.0";
+
+/// Default response size from which responses are hinted as compressible
+/// (1 KiB).
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 1024;
+
+/// Reject a protocol version not in [`SCIM_PROTOCOL_VERSIONS`].
+pub(super) fn validate_protocol_version(version: &str) -> Result<(), ScimError> {
+    if !SCIM_PROTOCOL_VERSIONS.contains(&version) {
+        return Err(ScimError::internal(format!(
+            "Unsupported SCIM protocol version '{}', expected one of: {}",
+            version,
+            SCIM_PROTOCOL_VERSIONS.join(", ")
+        )));
+    }
+    Ok(())
 }
 
 impl Default for ScimServerConfig {
@@ -58,11 +410,49 @@ impl Default for ScimServerConfig {
             base_url: "https://localhost".to_string(),
             tenant_strategy: TenantStrategy::SingleTenant,
             scim_version: "v2".to_string(),
+            protocol_version: DEFAULT_SCIM_PROTOCOL_VERSION.to_string(),
+            base_path: None,
+            max_payload_size: None,
+            version_exposure: VersionExposure::default(),
+            derive_display_name: false,
+            authentication_schemes: Vec::new(),
+            resource_type_resolution: ResourceTypeResolution::default(),
+            location_policy: LocationPolicy::default(),
+            strict_schema_extensions: true,
+            unknown_attributes: UnknownAttributePolicy::default(),
+            enforce_required: true,
+            normalize_attribute_names: false,
+            coerce_attribute_values: false,
+            echo_external_id: true,
+            idempotent_delete: false,
+            compression_threshold: Some(DEFAULT_COMPRESSION_THRESHOLD),
+            default_tenant_id: DEFAULT_TENANT_ID.to_string(),
+            duplicate_members: DuplicateMemberPolicy::default(),
+            max_group_members: None,
+            group_membership: GroupMembership::default(),
+            default_list_order: DefaultListOrder::default(),
+            entry_display: EntryDisplayPolicy::default(),
+            patch_mode: PatchMode::default(),
+            max_page_size: None,
+            resource_type_max_page_sizes: HashMap::new(),
+            advertise_resource_type_page_sizes: false,
+            tenant_configurations: HashMap::new(),
         }
     }
 }
 
 impl ScimServerConfig {
+    /// Path between the host (and any path-based tenant) and the resource
+    /// endpoints, without surrounding slashes.
+    ///
+    /// This is the configured base path if set, otherwise the SCIM version.
+    pub fn api_path(&self) -> &str {
+        match &self.base_path {
+            Some(path) => path.trim_matches('/'),
+            None => &self.scim_version,
+        }
+    }
+
     /// Generate a complete $ref URL for a resource.
     ///
     /// Combines the server configuration with tenant and resource information
@@ -87,11 +477,13 @@ impl ScimServerConfig {
         resource_type: &str,
         resource_id: &str,
     ) -> Result<String, ScimError> {
+        let endpoint = match self.api_path() {
+            "" => format!("{}/{}", resource_type, resource_id),
+            path => format!("{}/{}/{}", path, resource_type, resource_id),
+        };
+
         match &self.tenant_strategy {
-            TenantStrategy::SingleTenant => Ok(format!(
-                "{}/{}/{}/{}",
-                self.base_url, self.scim_version, resource_type, resource_id
-            )),
+            TenantStrategy::SingleTenant => Ok(format!("{}/{}", self.base_url, endpoint)),
             TenantStrategy::Subdomain => {
                 let tenant = tenant_id.ok_or_else(|| {
                     ScimError::invalid_request(
@@ -116,13 +508,8 @@ impl ScimServerConfig {
                 };
 
                 Ok(format!(
-                    "{}://{}.{}/{}/{}/{}",
-                    protocol,
-                    tenant,
-                    url_without_protocol,
-                    self.scim_version,
-                    resource_type,
-                    resource_id
+                    "{}://{}.{}/{}",
+                    protocol, tenant, url_without_protocol, endpoint
                 ))
             }
             TenantStrategy::PathBased => {
@@ -132,14 +519,80 @@ impl ScimServerConfig {
                     )
                 })?;
 
-                Ok(format!(
-                    "{}/{}/{}/{}/{}",
-                    self.base_url, tenant, self.scim_version, resource_type, resource_id
-                ))
+                Ok(format!("{}/{}/{}", self.base_url, tenant, endpoint))
             }
         }
     }
 
+    /// Location of a resource under the configured [`LocationPolicy`].
+    ///
+    /// Returns `None` when locations are omitted. Relative locations are the
+    /// path of the URL [`generate_ref_url`](Self::generate_ref_url) would
+    /// build, including any path-based tenant.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if tenant information is required but missing
+    pub fn resource_location(
+        &self,
+        tenant_id: Option<&str>,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> Result<Option<String>, ScimError> {
+        if self.location_policy == LocationPolicy::Omit {
+            return Ok(None);
+        }
+
+        let url = self.generate_ref_url(tenant_id, resource_type, resource_id)?;
+        if self.location_policy == LocationPolicy::Absolute {
+            return Ok(Some(url));
+        }
+
+        let path = url
+            .split_once("://")
+            .and_then(|(_, rest)| rest.find('/').map(|start| rest[start..].to_string()))
+            .unwrap_or(url);
+        Ok(Some(path))
+    }
+
+    /// Reject a payload larger than the configured maximum.
+    ///
+    /// Payloads reach the server already parsed, so the size is that of the
+    /// compact serialized JSON rather than of the bytes received; whitespace on
+    /// the wire is not counted. Transports that need a hard limit on raw bodies
+    /// should enforce it before parsing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScimError::PayloadTooLarge`] if the limit is exceeded.
+    pub fn check_payload_size(&self, payload: &Value) -> Result<(), ScimError> {
+        self.check_batch_payload_size([payload])
+    }
+
+    /// Reject a batch whose payloads together exceed the configured maximum.
+    ///
+    /// Sizes are measured as by [`check_payload_size`](Self::check_payload_size)
+    /// and summed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScimError::PayloadTooLarge`] if the limit is exceeded.
+    pub fn check_batch_payload_size<'a>(
+        &self,
+        payloads: impl IntoIterator<Item = &'a Value>,
+    ) -> Result<(), ScimError> {
+        let Some(limit) = self.max_payload_size else {
+            return Ok(());
+        };
+
+        let size = payloads.into_iter().map(serialized_size).sum();
+        if size > limit {
+            return Err(ScimError::payload_too_large(size, limit));
+        }
+
+        Ok(())
+    }
+
     /// Validate the configuration.
     ///
     /// Ensures the base URL and other configuration parameters are valid.
@@ -161,6 +614,55 @@ impl ScimServerConfig {
             return Err(ScimError::internal("SCIM version cannot be empty"));
         }
 
+        validate_protocol_version(&self.protocol_version)?;
+
+        if let Some(path) = self.base_path.as_deref().map(|p| p.trim_matches('/'))
+            && !path.is_empty()
+            && path.split('/').any(str::is_empty)
+        {
+            return Err(ScimError::internal(
+                "Base path cannot contain empty segments",
+            ));
+        }
+
+        // Storage keys are `tenant/type/id`, so the tenant cannot contain `/`
+        if self.default_tenant_id.is_empty() || self.default_tenant_id.contains('/') {
+            return Err(ScimError::internal(
+                "Default tenant ID must be non-empty and cannot contain '/'",
+            ));
+        }
+
+        if self.max_payload_size == Some(0) {
+            return Err(ScimError::internal("Maximum payload size must be non-zero"));
+        }
+
+        if self.max_page_size == Some(0)
+            || self
+                .resource_type_max_page_sizes
+                .values()
+                .any(|max| *max == 0)
+        {
+            return Err(ScimError::internal("Maximum page size must be non-zero"));
+        }
+
+        if self.group_membership == (GroupMembership::Nested { max_depth: 0 }) {
+            return Err(ScimError::internal(
+                "Nested group membership depth must be at least 1",
+            ));
+        }
+
+        if self
+            .authentication_schemes
+            .iter()
+            .filter(|scheme| scheme.primary)
+            .count()
+            > 1
+        {
+            return Err(ScimError::internal(
+                "At most one authentication scheme can be primary",
+            ));
+        }
+
         Ok(())
     }
 }
@@ -248,6 +750,317 @@ impl<P: ResourceProvider> ScimServerBuilder<P> {
         self
     }
 
+    /// Set the SCIM protocol version advertised in discovery documents.
+    ///
+    /// Must be one of [`SCIM_PROTOCOL_VERSIONS`], which `build` checks.
+    /// Defaults to "2.0".
+    pub fn with_protocol_version(mut self, version: impl Into<String>) -> Self {
+        self.config.protocol_version = version.into();
+        self
+    }
+
+    /// Set the path the SCIM endpoints are mounted under.
+    ///
+    /// Replaces the SCIM version segment in generated `$ref` and
+    /// `meta.location` URLs, for deployments not served from `/v2`. Leading
+    /// and trailing slashes are optional.
+    ///
+    /// # Examples
+    ///
+    /// - `"/scim/v2"` → `https://scim.company.com/scim/v2/Users/123`
+    /// - `"/api/scim"` → `https://scim.company.com/api/scim/Users/123`
+    pub fn with_base_path(mut self, base_path: impl Into<String>) -> Self {
+        self.config.base_path = Some(base_path.into());
+        self
+    }
+
+    /// Set the maximum accepted request payload size in bytes.
+    ///
+    /// Pass `None` to disable the limit. Defaults to `None`, which accepts
+    /// payloads of any size.
+    pub fn with_max_payload_size(mut self, max_payload_size: Option<usize>) -> Self {
+        self.config.max_payload_size = max_payload_size;
+        self
+    }
+
+    /// Set where resource versions are exposed in responses.
+    ///
+    /// Defaults to [`VersionExposure::Both`].
+    pub fn with_version_exposure(mut self, exposure: VersionExposure) -> Self {
+        self.config.version_exposure = exposure;
+        self
+    }
+
+    /// Derive a missing User `displayName` from `name` on create and update.
+    ///
+    /// A client-provided `displayName` is never replaced. Defaults to off.
+    pub fn with_display_name_derivation(mut self, enabled: bool) -> Self {
+        self.config.derive_display_name = enabled;
+        self
+    }
+
+    /// Set how request resource type names are matched to registered types.
+    ///
+    /// Defaults to [`ResourceTypeResolution::exact`].
+    pub fn with_resource_type_resolution(mut self, resolution: ResourceTypeResolution) -> Self {
+        self.config.resource_type_resolution = resolution;
+        self
+    }
+
+    /// Set how `meta.location` and `$ref` values are emitted.
+    ///
+    /// Defaults to [`LocationPolicy::Absolute`].
+    pub fn with_location_policy(mut self, policy: LocationPolicy) -> Self {
+        self.config.location_policy = policy;
+        self
+    }
+
+    /// Reject schema URNs not registered for a resource type on create and
+    /// update. When disabled they pass through unvalidated.
+    ///
+    /// Defaults to on.
+    pub fn with_strict_schema_extensions(mut self, strict: bool) -> Self {
+        self.config.strict_schema_extensions = strict;
+        self
+    }
+
+    /// Set how attributes the schema does not define are handled on writes.
+    ///
+    /// Tenants may override this in their
+    /// [`validation_config`](crate::multi_tenant::ScimTenantConfiguration::validation_config).
+    /// Defaults to [`UnknownAttributePolicy::Reject`].
+    pub fn with_unknown_attribute_policy(mut self, policy: UnknownAttributePolicy) -> Self {
+        self.config.unknown_attributes = policy;
+        self
+    }
+
+    /// Enforce required attributes on create, update and PATCH.
+    ///
+    /// Tenants may override this in their
+    /// [`validation_config`](crate::multi_tenant::ScimTenantConfiguration::validation_config).
+    /// Defaults to on.
+    pub fn with_required_enforcement(mut self, enforce: bool) -> Self {
+        self.config.enforce_required = enforce;
+        self
+    }
+
+    /// Rename attribute keys in create and update payloads to the schema's
+    /// casing before validation, so `username` is accepted as `userName`.
+    ///
+    /// Keys that match no schema attribute are still rejected. Defaults to off.
+    pub fn with_attribute_name_normalization(mut self, enabled: bool) -> Self {
+        self.config.normalize_attribute_names = enabled;
+        self
+    }
+
+    /// Convert string-encoded values to the type their schema attribute
+    /// declares, so `"true"` is accepted for `active` and `"42"` for an
+    /// integer attribute.
+    ///
+    /// Applies to create and update payloads and to the values of PATCH
+    /// operations whose target attribute the schema defines. Strings that do
+    /// not parse as the declared type are still rejected. Defaults to off.
+    pub fn with_value_coercion(mut self, enabled: bool) -> Self {
+        self.config.coerce_attribute_values = enabled;
+        self
+    }
+
+    /// Return the stored `externalId` in resource responses.
+    ///
+    /// When enabled, `externalId` is returned by default like any other
+    /// attribute and can be selected or excluded with `attributes` and
+    /// `excludedAttributes`. When disabled it is still stored, filterable and
+    /// writable, but never returned. Defaults to on.
+    pub fn with_external_id_echo(mut self, enabled: bool) -> Self {
+        self.config.echo_external_id = enabled;
+        self
+    }
+
+    /// Let deletes of missing resources succeed.
+    ///
+    /// Some identity providers retry deletes and expect a `204` even when the
+    /// resource is already gone. When enabled, deleting a missing or
+    /// soft-deleted resource succeeds and changes nothing, and the
+    /// response metadata reports `removed: false`. Conditional deletes are
+    /// unaffected. Defaults to off, which answers such deletes with `404` as
+    /// RFC 7644 requires.
+    pub fn with_idempotent_delete(mut self, enabled: bool) -> Self {
+        self.config.idempotent_delete = enabled;
+        self
+    }
+
+    /// Set the response size in bytes from which operation responses are
+    /// hinted as compressible.
+    ///
+    /// The hint is advisory: it only sets
+    /// [`OperationMetadata::compressible`](crate::operation_handler::OperationMetadata::compressible)
+    /// and never changes the response itself. Pass `None` to never set it.
+    /// Defaults to 1 KiB.
+    pub fn with_compression_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.config.compression_threshold = threshold;
+        self
+    }
+
+    /// Set the tenant ID under which requests without tenant context are
+    /// stored.
+    ///
+    /// The provider is configured with it when the server is built, so
+    /// single-tenant requests and the provider's storage keys agree. Defaults
+    /// to `"default"`; changing it leaves data stored under the previous ID
+    /// unreachable without tenant context.
+    pub fn with_default_tenant_id(mut self, tenant_id: impl Into<String>) -> Self {
+        self.config.default_tenant_id = tenant_id.into();
+        self
+    }
+
+    /// Set how Group `members` entries sharing a `value` are handled.
+    ///
+    /// See the [`members`](super::members) module. Defaults to
+    /// [`DuplicateMemberPolicy::Deduplicate`].
+    pub fn with_duplicate_member_policy(mut self, policy: DuplicateMemberPolicy) -> Self {
+        self.config.duplicate_members = policy;
+        self
+    }
+
+    /// Cap the number of `members` a Group may have.
+    ///
+    /// Writes that would exceed it fail with [`ScimError::TooManyMembers`]
+    /// (413, `tooMany`). Tenants may override it with
+    /// [`with_max_group_members`](crate::multi_tenant::ScimTenantConfigurationBuilder::with_max_group_members).
+    /// Defaults to `None`, which leaves membership unbounded.
+    pub fn with_max_group_members(mut self, max_members: Option<usize>) -> Self {
+        self.config.max_group_members = max_members;
+        self
+    }
+
+    /// Set how the `groups` attribute of Users is produced on reads.
+    ///
+    /// See the [`groups`](super::groups) module. Defaults to
+    /// [`GroupMembership::Stored`].
+    pub fn with_group_membership(mut self, membership: GroupMembership) -> Self {
+        self.config.group_membership = membership;
+        self
+    }
+
+    /// Set how list results are ordered when the request does not set
+    /// `sortBy`. Defaults to [`DefaultListOrder::Id`].
+    pub fn with_default_list_order(mut self, order: DefaultListOrder) -> Self {
+        self.config.default_list_order = order;
+        self
+    }
+
+    /// Derive a missing `display` for `emails` and `phoneNumbers` entries,
+    /// and a missing `formatted` for `addresses`, on create and update.
+    ///
+    /// See the [`entry_display`](super::entry_display) module. Defaults to
+    /// [`EntryDisplayPolicy::Off`].
+    pub fn with_entry_display_policy(mut self, policy: EntryDisplayPolicy) -> Self {
+        self.config.entry_display = policy;
+        self
+    }
+
+    /// Set how the operations of a PATCH request are applied.
+    ///
+    /// See the [`partial_patch`](super::partial_patch) module. Defaults to
+    /// [`PatchMode::Atomic`].
+    pub fn with_patch_mode(mut self, mode: PatchMode) -> Self {
+        self.config.patch_mode = mode;
+        self
+    }
+
+    /// Cap list responses at `max` resources per page.
+    ///
+    /// The cap is reported as `filter.maxResults` in the ServiceProviderConfig
+    /// and applies to resource types without a more specific cap. See the
+    /// [`pagination`](super::pagination) module. Defaults to uncapped.
+    pub fn with_max_page_size(mut self, max: usize) -> Self {
+        self.config.max_page_size = Some(max);
+        self
+    }
+
+    /// Cap list responses for `resource_type` at `max` resources per page,
+    /// in place of the global cap.
+    ///
+    /// A tenant's
+    /// [`resource_type_max_results`](crate::multi_tenant::ScimSearchConfig::resource_type_max_results)
+    /// takes precedence over this.
+    pub fn with_resource_type_max_page_size(
+        mut self,
+        resource_type: impl Into<String>,
+        max: usize,
+    ) -> Self {
+        self.config
+            .resource_type_max_page_sizes
+            .insert(resource_type.into(), max);
+        self
+    }
+
+    /// Report the page size cap of each resource type as `maxResults` in its
+    /// ResourceType definition. Defaults to off.
+    pub fn with_resource_type_page_size_discovery(mut self, enabled: bool) -> Self {
+        self.config.advertise_resource_type_page_sizes = enabled;
+        self
+    }
+
+    /// Register SCIM configuration for a tenant.
+    ///
+    /// Replaces any configuration previously registered for the same tenant.
+    pub fn with_tenant_configuration(mut self, configuration: ScimTenantConfiguration) -> Self {
+        self.config
+            .tenant_configurations
+            .insert(configuration.tenant_id.clone(), configuration);
+        self
+    }
+
+    /// Advertise an authentication scheme in the ServiceProviderConfig.
+    ///
+    /// Schemes are listed in the order they are added. Adding a scheme marked
+    /// [`primary`](AuthenticationScheme::as_primary) makes it the only primary
+    /// scheme.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::{AuthenticationScheme, ScimServerBuilder};
+    /// # let provider = scim_server::providers::StandardResourceProvider::new(
+    /// #     scim_server::storage::InMemoryStorage::new()
+    /// # );
+    ///
+    /// let server = ScimServerBuilder::new(provider)
+    ///     .with_authentication_scheme(
+    ///         AuthenticationScheme::bearer()
+    ///             .with_documentation_uri("https://docs.company.com/scim/auth")
+    ///             .as_primary(),
+    ///     )
+    ///     .with_authentication_scheme(AuthenticationScheme::http_basic())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let config = server.get_service_provider_config().unwrap();
+    /// assert_eq!(config.authentication_schemes.len(), 2);
+    /// assert!(config.authentication_schemes[0].primary);
+    /// ```
+    pub fn with_authentication_scheme(mut self, scheme: AuthenticationScheme) -> Self {
+        if scheme.primary {
+            for existing in &mut self.config.authentication_schemes {
+                existing.primary = false;
+            }
+        }
+        self.config.authentication_schemes.push(scheme);
+        self
+    }
+
+    /// Advertise several authentication schemes, as with
+    /// [`with_authentication_scheme`](Self::with_authentication_scheme).
+    pub fn with_authentication_schemes(
+        self,
+        schemes: impl IntoIterator<Item = AuthenticationScheme>,
+    ) -> Self {
+        schemes.into_iter().fold(self, |builder, scheme| {
+            builder.with_authentication_scheme(scheme)
+        })
+    }
+
     /// Build the configured SCIM server.
     ///
     /// Validates the configuration and creates the final `ScimServer` instance.
@@ -272,6 +1085,7 @@ mod tests {
             base_url: "https://scim.example.com".to_string(),
             tenant_strategy: TenantStrategy::SingleTenant,
             scim_version: "v2".to_string(),
+            ..Default::default()
         };
 
         let url = config.generate_ref_url(None, "Users", "12345").unwrap();
@@ -284,6 +1098,7 @@ mod tests {
             base_url: "https://scim.example.com".to_string(),
             tenant_strategy: TenantStrategy::Subdomain,
             scim_version: "v2".to_string(),
+            ..Default::default()
         };
 
         let url = config
@@ -298,6 +1113,7 @@ mod tests {
             base_url: "https://api.company.com".to_string(),
             tenant_strategy: TenantStrategy::PathBased,
             scim_version: "v2".to_string(),
+            ..Default::default()
         };
 
         let url = config
@@ -306,12 +1122,90 @@ mod tests {
         assert_eq!(url, "https://api.company.com/tenant1/v2/Users/abc123");
     }
 
+    #[test]
+    fn test_base_path_ref_url_generation() {
+        let config = ScimServerConfig {
+            base_url: "https://api.company.com".to_string(),
+            tenant_strategy: TenantStrategy::PathBased,
+            base_path: Some("/scim/v2/".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            config
+                .generate_ref_url(Some("tenant1"), "Users", "abc123")
+                .unwrap(),
+            "https://api.company.com/tenant1/scim/v2/Users/abc123"
+        );
+
+        let config = ScimServerConfig {
+            base_url: "https://scim.example.com".to_string(),
+            tenant_strategy: TenantStrategy::Subdomain,
+            base_path: Some("api/scim".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            config
+                .generate_ref_url(Some("acme"), "Groups", "67890")
+                .unwrap(),
+            "https://acme.scim.example.com/api/scim/Groups/67890"
+        );
+
+        // An empty base path mounts the endpoints at the root
+        let config = ScimServerConfig {
+            base_path: Some("/".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.generate_ref_url(None, "Users", "1").unwrap(),
+            "https://localhost/Users/1"
+        );
+    }
+
+    #[test]
+    fn test_resource_location_policies() {
+        let config = ScimServerConfig {
+            base_url: "https://api.company.com".to_string(),
+            tenant_strategy: TenantStrategy::PathBased,
+            ..Default::default()
+        };
+        assert_eq!(
+            config
+                .resource_location(Some("tenant1"), "Users", "abc123")
+                .unwrap()
+                .as_deref(),
+            Some("https://api.company.com/tenant1/v2/Users/abc123")
+        );
+
+        let relative = ScimServerConfig {
+            location_policy: LocationPolicy::Relative,
+            ..config.clone()
+        };
+        assert_eq!(
+            relative
+                .resource_location(Some("tenant1"), "Users", "abc123")
+                .unwrap()
+                .as_deref(),
+            Some("/tenant1/v2/Users/abc123")
+        );
+
+        let omit = ScimServerConfig {
+            location_policy: LocationPolicy::Omit,
+            ..config
+        };
+        assert_eq!(
+            omit.resource_location(Some("tenant1"), "Users", "abc123")
+                .unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn test_missing_tenant_error() {
         let config = ScimServerConfig {
             base_url: "https://scim.example.com".to_string(),
             tenant_strategy: TenantStrategy::Subdomain,
             scim_version: "v2".to_string(),
+            ..Default::default()
         };
 
         let result = config.generate_ref_url(None, "Users", "12345");
@@ -338,6 +1232,48 @@ mod tests {
         config.base_url = "https://valid.com".to_string();
         config.scim_version = "".to_string();
         assert!(config.validate().is_err());
+
+        config.scim_version = "v2".to_string();
+        config.protocol_version = "v2".to_string();
+        assert!(config.validate().is_err());
+
+        config.protocol_version = "1.1".to_string();
+        assert!(config.validate().is_ok());
+        config.protocol_version = DEFAULT_SCIM_PROTOCOL_VERSION.to_string();
+        config.base_path = Some("/scim//v2".to_string());
+        assert!(config.validate().is_err());
+
+        config.base_path = None;
+        config.authentication_schemes = vec![
+            AuthenticationScheme::oauth2().as_primary(),
+            AuthenticationScheme::bearer().as_primary(),
+        ];
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_authentication_schemes_have_one_primary() {
+        use crate::providers::StandardResourceProvider;
+        use crate::storage::InMemoryStorage;
+
+        let server = ScimServerBuilder::new(StandardResourceProvider::new(InMemoryStorage::new()))
+            .with_authentication_schemes([
+                AuthenticationScheme::oauth2().as_primary(),
+                AuthenticationScheme::http_basic(),
+            ])
+            .with_authentication_scheme(AuthenticationScheme::bearer().as_primary())
+            .build()
+            .unwrap();
+
+        let schemes = server
+            .get_service_provider_config()
+            .unwrap()
+            .authentication_schemes;
+        let types: Vec<_> = schemes.iter().map(|s| s.auth_type.as_str()).collect();
+        assert_eq!(types, ["oauth2", "httpbasic", "oauthbearertoken"]);
+        let primary: Vec<_> = schemes.iter().filter(|s| s.primary).collect();
+        assert_eq!(primary.len(), 1);
+        assert_eq!(primary[0].auth_type, "oauthbearertoken");
     }
 
     #[test]
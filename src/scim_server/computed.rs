@@ -0,0 +1,187 @@
+//! Read-only computed attributes.
+//!
+//! Computed attributes are virtual attributes whose values are derived from
+//! the stored resource at read time (for example a `riskScore`). They are
+//! registered per resource type, declared in the resource type's schema as
+//! `readOnly` with `returned: "default"`, evaluated after every get and list,
+//! and removed from create, update and patch payloads so they are never
+//! persisted.
+
+use super::core::ScimServer;
+use crate::error::{ScimError, ScimResult};
+use crate::providers::ResourceProvider;
+use crate::resource::{ListQuery, RequestContext, Resource, ResourceHandler};
+use crate::schema::{AttributeDefinition, AttributeType, Mutability};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// A virtual attribute computed from a resource on read.
+///
+/// # Examples
+///
+/// ```rust
+/// use scim_server::{ComputedAttribute, RequestContext, Resource};
+/// use scim_server::schema::AttributeType;
+/// use serde_json::{Value, json};
+///
+/// struct RiskScore;
+///
+/// impl ComputedAttribute for RiskScore {
+///     fn name(&self) -> &str {
+///         "riskScore"
+///     }
+///
+///     fn data_type(&self) -> AttributeType {
+///         AttributeType::Integer
+///     }
+///
+///     fn compute(&self, resource: &Resource, _context: &RequestContext) -> Value {
+///         let active = resource.get_attribute("active").and_then(Value::as_bool);
+///         json!(if active == Some(false) { 100 } else { 0 })
+///     }
+/// }
+/// ```
+pub trait ComputedAttribute: Send + Sync {
+    /// Attribute name as it appears on the resource.
+    fn name(&self) -> &str;
+
+    /// Data type declared for the attribute in the schema.
+    fn data_type(&self) -> AttributeType {
+        AttributeType::String
+    }
+
+    /// Compute the attribute's value. Returning `Value::Null` omits it.
+    fn compute(&self, resource: &Resource, context: &RequestContext) -> Value;
+}
+
+impl<P: ResourceProvider> ScimServer<P> {
+    /// Register a computed attribute for a resource type.
+    ///
+    /// The resource type must already be registered. The attribute is added
+    /// to its schema as `readOnly` and `returned: "default"`; registering a
+    /// name again replaces the previous computation.
+    pub fn register_computed_attribute(
+        &mut self,
+        resource_type: &str,
+        attribute: impl ComputedAttribute + 'static,
+    ) -> Result<(), ScimError> {
+        let handler = self.get_handler(resource_type)?;
+        let name = attribute.name().to_string();
+
+        let mut schema = handler.schema.clone();
+        schema
+            .attributes
+            .retain(|existing| !existing.name.eq_ignore_ascii_case(&name));
+        schema.attributes.push(AttributeDefinition {
+            name: name.clone(),
+            data_type: attribute.data_type(),
+            mutability: Mutability::ReadOnly,
+            returned: Some("default".to_string()),
+            ..Default::default()
+        });
+
+        self.schema_registry
+            .add_schema(schema.clone())
+            .map_err(|e| ScimError::internal(format!("Failed to add schema: {}", e)))?;
+        self.resource_handlers.insert(
+            resource_type.to_string(),
+            Arc::new(ResourceHandler {
+                schema,
+                ..(*handler).clone()
+            }),
+        );
+
+        let computed = self
+            .computed_attributes
+            .entry(resource_type.to_string())
+            .or_default();
+        computed.retain(|existing| !existing.name().eq_ignore_ascii_case(&name));
+        computed.push(Arc::new(attribute));
+
+        Ok(())
+    }
+
+    /// Set computed attribute values on a resource read from the provider,
+    /// honouring the query's `attributes`/`excludedAttributes` if given.
+    pub(super) fn apply_computed_attributes(
+        &self,
+        resource_type: &str,
+        resource: &mut Resource,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) {
+        let Some(computed) = self.computed_attributes.get(resource_type) else {
+            return;
+        };
+
+        for attribute in computed {
+            let name = attribute.name();
+            if let Some(query) = query {
+                let listed = |names: &[String]| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+                if (!query.attributes.is_empty() && !listed(&query.attributes))
+                    || listed(&query.excluded_attributes)
+                {
+                    continue;
+                }
+            }
+
+            match attribute.compute(resource, context) {
+                Value::Null => {
+                    resource.attributes.remove(name);
+                }
+                value => resource.set_attribute(name.to_string(), value),
+            }
+        }
+    }
+
+    /// Remove computed attributes from a write payload.
+    pub(super) fn strip_computed_attributes(&self, resource_type: &str, data: &mut Value) {
+        let (Some(computed), Some(object)) = (
+            self.computed_attributes.get(resource_type),
+            data.as_object_mut(),
+        ) else {
+            return;
+        };
+
+        object.retain(|key, _| !computed.iter().any(|c| c.name().eq_ignore_ascii_case(key)));
+    }
+
+    /// Remove computed attributes from the values of path-less PATCH
+    /// operations, rejecting operations whose path targets one.
+    pub(super) fn strip_computed_from_patch(
+        &self,
+        resource_type: &str,
+        patch_request: &Value,
+    ) -> ScimResult<Value> {
+        let mut patch_request = patch_request.clone();
+        let Some(computed) = self.computed_attributes.get(resource_type) else {
+            return Ok(patch_request);
+        };
+
+        if let Some(operations) = patch_request
+            .get_mut("Operations")
+            .and_then(Value::as_array_mut)
+        {
+            for operation in operations.iter_mut() {
+                match operation.get("path").and_then(Value::as_str) {
+                    Some(path) => {
+                        let root = path.split(['.', '[']).next().unwrap_or(path);
+                        if computed.iter().any(|c| c.name().eq_ignore_ascii_case(root)) {
+                            return Err(ScimError::invalid_request(format!(
+                                "Attribute '{}' is read-only",
+                                root
+                            )));
+                        }
+                    }
+                    None => {
+                        if let Some(value) = operation.get_mut("value") {
+                            self.strip_computed_attributes(resource_type, value);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(patch_request)
+    }
+}
@@ -5,14 +5,19 @@
 //! without specific operational concerns.
 
 use crate::error::ScimError;
+use crate::multi_tenant::ScimTenantConfiguration;
 use crate::provider_capabilities::{
     CapabilityDiscovery, CapabilityIntrospectable, ProviderCapabilities,
 };
 use crate::providers::ResourceProvider;
 use crate::resource::{ResourceHandler, ScimOperation};
-use crate::schema::SchemaRegistry;
+use crate::schema::{Schema, SchemaRegistry};
 use crate::schema_discovery::ServiceProviderConfig;
+use crate::scim_server::authorization::{AllowAll, AuthorizationPolicy};
 use crate::scim_server::builder::ScimServerConfig;
+use crate::scim_server::computed::ComputedAttribute;
+use crate::scim_server::discovery::DiscoveryCache;
+use crate::scim_server::validation_settings::ValidationSettingsCache;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -49,6 +54,13 @@ pub struct ScimServer<P> {
     pub(super) resource_handlers: HashMap<String, Arc<ResourceHandler>>, // resource_type -> handler
     pub(super) supported_operations: HashMap<String, Vec<ScimOperation>>, // resource_type -> supported ops
     pub(super) config: ScimServerConfig,
+    pub(super) computed_attributes: HashMap<String, Vec<Arc<dyn ComputedAttribute>>>, // resource_type -> computed attributes
+    pub(super) resource_type_aliases: HashMap<String, String>, // normalized name -> resource_type
+    pub(super) resource_endpoints: HashMap<String, String>,    // resource_type -> custom endpoint
+    pub(super) schema_extensions: HashMap<String, Vec<Schema>>, // resource_type -> extension schemas
+    pub(super) discovery_cache: DiscoveryCache,
+    pub(super) validation_settings: ValidationSettingsCache,
+    pub(super) authorization_policy: Arc<dyn AuthorizationPolicy>,
 }
 
 impl<P: ResourceProvider> ScimServer<P> {
@@ -84,7 +96,8 @@ impl<P: ResourceProvider> ScimServer<P> {
     /// # Errors
     ///
     /// Returns [`ScimError::Internal`] if the schema registry cannot be initialized.
-    pub fn with_config(provider: P, config: ScimServerConfig) -> Result<Self, ScimError> {
+    pub fn with_config(mut provider: P, config: ScimServerConfig) -> Result<Self, ScimError> {
+        provider.set_default_tenant_id(&config.default_tenant_id);
         let schema_registry = SchemaRegistry::new()
             .map_err(|e| ScimError::internal(format!("Failed to create schema registry: {}", e)))?;
 
@@ -94,6 +107,13 @@ impl<P: ResourceProvider> ScimServer<P> {
             resource_handlers: HashMap::new(),
             supported_operations: HashMap::new(),
             config,
+            computed_attributes: HashMap::new(),
+            schema_extensions: HashMap::new(),
+            resource_type_aliases: HashMap::new(),
+            resource_endpoints: HashMap::new(),
+            discovery_cache: DiscoveryCache::default(),
+            validation_settings: ValidationSettingsCache::default(),
+            authorization_policy: Arc::new(AllowAll),
         })
     }
 
@@ -108,6 +128,7 @@ impl<P: ResourceProvider> ScimServer<P> {
             &self.supported_operations,
             &self.provider,
         )
+        .map(|capabilities| self.with_configured_capabilities(capabilities))
     }
 
     /// Discover capabilities with provider introspection
@@ -127,6 +148,23 @@ impl<P: ResourceProvider> ScimServer<P> {
             &self.supported_operations,
             &self.provider,
         )
+        .map(|capabilities| self.with_configured_capabilities(capabilities))
+    }
+
+    /// Apply authentication schemes and the page size cap configured on the
+    /// builder, which take precedence over any the provider reports.
+    fn with_configured_capabilities(
+        &self,
+        mut capabilities: ProviderCapabilities,
+    ) -> ProviderCapabilities {
+        if !self.config.authentication_schemes.is_empty() {
+            capabilities.authentication_capabilities.schemes =
+                self.config.authentication_schemes.clone();
+        }
+        if let Some(max_page_size) = self.config.max_page_size {
+            capabilities.filter_capabilities.max_results = Some(max_page_size);
+        }
+        capabilities
     }
 
     /// Generate SCIM ServiceProviderConfig from discovered capabilities
@@ -178,6 +216,24 @@ impl<P: ResourceProvider> ScimServer<P> {
         &self.config
     }
 
+    /// Register SCIM configuration for a tenant.
+    ///
+    /// Replaces any configuration previously registered for the same tenant.
+    /// See [`ScimServerBuilder::with_tenant_configuration`](super::ScimServerBuilder::with_tenant_configuration)
+    /// to register it when building the server.
+    pub fn register_tenant_configuration(&mut self, configuration: ScimTenantConfiguration) {
+        self.config
+            .tenant_configurations
+            .insert(configuration.tenant_id.clone(), configuration);
+        self.discovery_cache.invalidate();
+        self.validation_settings.invalidate();
+    }
+
+    /// SCIM configuration registered for a tenant.
+    pub fn tenant_configuration(&self, tenant_id: &str) -> Option<&ScimTenantConfiguration> {
+        self.config.tenant_configurations.get(tenant_id)
+    }
+
     /// Generate a $ref URL for a resource.
     ///
     /// Combines server configuration with tenant and resource information
@@ -210,7 +266,9 @@ impl<P: ResourceProvider> ScimServer<P> {
     ///
     /// This method post-processes resource JSON to add proper $ref fields
     /// to Group.members and User.groups arrays based on server configuration
-    /// and tenant context.
+    /// and tenant context. References in schema extension data are handled
+    /// separately when a resource is serialized. The configured [`LocationPolicy`](super::LocationPolicy)
+    /// decides whether they are absolute, relative or removed.
     ///
     /// # Arguments
     ///
@@ -234,17 +292,21 @@ impl<P: ResourceProvider> ScimServer<P> {
                             member_obj.get("value").and_then(|v| v.as_str()),
                             member_obj.get("type").and_then(|v| v.as_str()),
                         ) {
-                            // Determine resource type for $ref URL
-                            let resource_type = match member_type {
-                                "User" => "Users",
-                                "Group" => "Groups",
-                                _ => member_type, // Use as-is for unknown types
-                            };
-
-                            let ref_url =
-                                self.generate_ref_url(tenant_id, resource_type, member_id)?;
-                            member_obj
-                                .insert("$ref".to_string(), serde_json::Value::String(ref_url));
+                            let endpoint = self.resource_endpoint(member_type);
+                            match self
+                                .config
+                                .resource_location(tenant_id, &endpoint, member_id)?
+                            {
+                                Some(ref_url) => {
+                                    member_obj.insert(
+                                        "$ref".to_string(),
+                                        serde_json::Value::String(ref_url),
+                                    );
+                                }
+                                None => {
+                                    member_obj.remove("$ref");
+                                }
+                            }
                         }
                     }
                 }
@@ -257,9 +319,21 @@ impl<P: ResourceProvider> ScimServer<P> {
                 for group in groups {
                     if let Some(group_obj) = group.as_object_mut() {
                         if let Some(group_id) = group_obj.get("value").and_then(|v| v.as_str()) {
-                            let ref_url = self.generate_ref_url(tenant_id, "Groups", group_id)?;
-                            group_obj
-                                .insert("$ref".to_string(), serde_json::Value::String(ref_url));
+                            let endpoint = self.resource_endpoint("Group");
+                            match self
+                                .config
+                                .resource_location(tenant_id, &endpoint, group_id)?
+                            {
+                                Some(ref_url) => {
+                                    group_obj.insert(
+                                        "$ref".to_string(),
+                                        serde_json::Value::String(ref_url),
+                                    );
+                                }
+                                None => {
+                                    group_obj.remove("$ref");
+                                }
+                            }
                         }
                     }
                 }
@@ -273,7 +347,8 @@ impl<P: ResourceProvider> ScimServer<P> {
     ///
     /// This method updates the meta.location field to use the server's configured
     /// base URL instead of any hardcoded URLs that may have been set during
-    /// resource creation by the provider.
+    /// resource creation by the provider, or removes it when the
+    /// [`LocationPolicy`](super::LocationPolicy) omits locations.
     ///
     /// # Arguments
     ///
@@ -303,18 +378,21 @@ impl<P: ResourceProvider> ScimServer<P> {
                 resource_id.as_deref(),
             ) {
                 // Generate proper location URL using server configuration
-                let resource_type_plural = match resource_type {
-                    "User" => "Users",
-                    "Group" => "Groups",
-                    _ => resource_type, // Use as-is for unknown types
-                };
-
-                let location_url =
-                    self.generate_ref_url(tenant_id, resource_type_plural, resource_id)?;
-                meta_obj.insert(
-                    "location".to_string(),
-                    serde_json::Value::String(location_url),
-                );
+                let endpoint = self.resource_endpoint(resource_type);
+                match self
+                    .config
+                    .resource_location(tenant_id, &endpoint, resource_id)?
+                {
+                    Some(location_url) => {
+                        meta_obj.insert(
+                            "location".to_string(),
+                            serde_json::Value::String(location_url),
+                        );
+                    }
+                    None => {
+                        meta_obj.remove("location");
+                    }
+                }
             }
         }
         Ok(())
@@ -324,7 +402,8 @@ impl<P: ResourceProvider> ScimServer<P> {
     ///
     /// This method combines `Resource::to_json()` with $ref field injection and
     /// location URL correction to ensure SCIM 2.0 compliance for resource references
-    /// and proper server configuration usage.
+    /// and proper server configuration usage. Attributes that are never returned,
+    /// such as a User's `password`, are removed.
     ///
     /// # Arguments
     ///
@@ -343,7 +422,9 @@ impl<P: ResourceProvider> ScimServer<P> {
             .to_json()
             .map_err(|e| ScimError::internal(format!("Failed to serialize resource: {}", e)))?;
 
+        self.strip_never_returned_attributes(&resource.resource_type, &mut json);
         self.inject_ref_fields(&mut json, tenant_id)?;
+        self.inject_extension_ref_fields(&resource.resource_type, &mut json, tenant_id)?;
         self.inject_location_field(&mut json, tenant_id)?;
         Ok(json)
     }
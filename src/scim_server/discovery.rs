@@ -0,0 +1,171 @@
+//! Cached discovery documents.
+//!
+//! The `/Schemas`, `/ResourceTypes` and `/ServiceProviderConfig` documents
+//! only change when the server's registrations do, yet rendering them walks
+//! every registered schema and, for the ServiceProviderConfig, runs capability
+//! discovery. They are therefore rendered on first use and cached until a
+//! resource type, schema extension, resource endpoint or tenant configuration
+//! is registered.
+//!
+//! Each document carries a version derived from its content, so its ETag is
+//! stable across cache rebuilds and only changes when the document does.
+//! `/ResourceTypes` advertises per-tenant schema extensions and is cached per
+//! tenant.
+//!
+//! The ServiceProviderConfig and ResourceType documents report the configured
+//! [`protocol_version`](super::ScimServerConfig::protocol_version) as
+//! `meta.scimVersion`.
+
+use super::builder::validate_protocol_version;
+use super::core::ScimServer;
+use crate::error::{ScimError, ScimResult};
+use crate::providers::ResourceProvider;
+use crate::resource::RequestContext;
+use crate::resource::version::{HttpVersion, RawVersion};
+use crate::schema::Schema;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// A rendered discovery document and the version of its content.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveryDocument {
+    /// The document as served
+    pub body: Value,
+    /// Hash of the document's content
+    pub version: RawVersion,
+}
+
+impl DiscoveryDocument {
+    fn new(body: Value) -> Self {
+        let version = RawVersion::from_content(body.to_string().as_bytes());
+        Self { body, version }
+    }
+
+    /// Weak ETag for the document, e.g. `W/"abc123"`.
+    pub fn etag(&self) -> String {
+        HttpVersion::from(self.version.clone()).to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum DocumentKey {
+    Schemas,
+    ResourceTypes(Option<String>),
+    ServiceProviderConfig,
+}
+
+/// Discovery documents rendered since the last registration.
+#[derive(Debug, Default)]
+pub(super) struct DiscoveryCache {
+    documents: RwLock<HashMap<DocumentKey, Arc<DiscoveryDocument>>>,
+}
+
+impl DiscoveryCache {
+    fn get_or_render(
+        &self,
+        key: DocumentKey,
+        render: impl FnOnce() -> ScimResult<Value>,
+    ) -> ScimResult<Arc<DiscoveryDocument>> {
+        if let Some(document) = self.documents.read().unwrap().get(&key) {
+            return Ok(Arc::clone(document));
+        }
+
+        let document = Arc::new(DiscoveryDocument::new(render()?));
+        Ok(Arc::clone(
+            self.documents
+                .write()
+                .unwrap()
+                .entry(key)
+                .or_insert(document),
+        ))
+    }
+
+    /// Drop every cached document.
+    pub(super) fn invalidate(&mut self) {
+        self.documents.get_mut().unwrap().clear();
+    }
+}
+
+impl<P: ResourceProvider> ScimServer<P> {
+    /// The `/Schemas` document: a ListResponse of the schemas of every
+    /// registered resource type and schema extension, sorted by id.
+    pub fn schemas_document(&self) -> ScimResult<Arc<DiscoveryDocument>> {
+        self.discovery_cache
+            .get_or_render(DocumentKey::Schemas, || {
+                let mut schemas: Vec<&Schema> = self.get_all_schemas();
+                schemas.extend(self.schema_extensions.values().flatten());
+                schemas.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+                schemas.dedup_by(|a, b| a.id == b.id);
+
+                let resources = schemas
+                    .into_iter()
+                    .map(serde_json::to_value)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(list_response(resources))
+            })
+    }
+
+    /// The `/ResourceTypes` document for the request's tenant: a ListResponse
+    /// of [`get_resource_type_definitions`](Self::get_resource_type_definitions).
+    pub fn resource_types_document(
+        &self,
+        context: &RequestContext,
+    ) -> ScimResult<Arc<DiscoveryDocument>> {
+        let tenant_id = context.tenant_id().map(str::to_string);
+        self.discovery_cache
+            .get_or_render(DocumentKey::ResourceTypes(tenant_id), || {
+                Ok(list_response(self.get_resource_type_definitions(context)))
+            })
+    }
+
+    /// The `/ServiceProviderConfig` document, rendered from
+    /// [`get_service_provider_config`](Self::get_service_provider_config).
+    pub fn service_provider_config_document(&self) -> ScimResult<Arc<DiscoveryDocument>> {
+        self.discovery_cache
+            .get_or_render(DocumentKey::ServiceProviderConfig, || {
+                let mut body = serde_json::to_value(self.get_service_provider_config()?)?;
+                body.as_object_mut()
+                    .ok_or_else(|| {
+                        ScimError::internal("ServiceProviderConfig did not serialize to an object")
+                    })?
+                    .extend([
+                        (
+                            "schemas".to_string(),
+                            json!(["urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig"]),
+                        ),
+                        (
+                            "meta".to_string(),
+                            json!({
+                                "resourceType": "ServiceProviderConfig",
+                                "scimVersion": self.config.protocol_version,
+                            }),
+                        ),
+                    ]);
+                Ok(body)
+            })
+    }
+
+    /// Change the SCIM protocol version advertised in discovery documents.
+    ///
+    /// The version must be one of
+    /// [`SCIM_PROTOCOL_VERSIONS`](super::builder::SCIM_PROTOCOL_VERSIONS).
+    /// Cached documents are rebuilt with it on next use.
+    pub fn set_protocol_version(&mut self, version: impl Into<String>) -> ScimResult<()> {
+        let version = version.into();
+        validate_protocol_version(&version)?;
+        self.config.protocol_version = version;
+        self.discovery_cache.invalidate();
+        Ok(())
+    }
+}
+
+fn list_response(resources: Vec<Value>) -> Value {
+    json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:ListResponse"],
+        "totalResults": resources.len(),
+        "startIndex": 1,
+        "itemsPerPage": resources.len(),
+        "Resources": resources,
+    })
+}
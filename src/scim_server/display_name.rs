@@ -0,0 +1,159 @@
+//! Server-derived `displayName` for User resources.
+//!
+//! Many identity providers omit `displayName`. When enabled with
+//! [`ScimServerBuilder::with_display_name_derivation`](super::ScimServerBuilder::with_display_name_derivation),
+//! the server fills a missing User `displayName` on create, update and PATCH
+//! from `name.formatted`, falling back to `"givenName familyName"`.
+//!
+//! A derived value is recorded in `meta.derivedAttributes` so that it keeps
+//! following `name` on later updates, even when a client echoes it back
+//! unchanged. A `displayName` the client actually chose is never replaced.
+//! The marker is server-managed: a `meta.derivedAttributes` sent by a client
+//! is discarded, so it can neither claim a chosen value was derived nor hide
+//! a derivation.
+
+use super::core::ScimServer;
+use crate::providers::ResourceProvider;
+use crate::resource::Resource;
+use serde_json::{Map, Value};
+
+/// `meta` sub-attribute listing attributes whose values were server-derived.
+pub const DERIVED_ATTRIBUTES: &str = "derivedAttributes";
+
+const DISPLAY_NAME: &str = "displayName";
+
+/// Derive a display name from a User's `name` attribute.
+///
+/// Uses `name.formatted` if non-empty, otherwise the non-empty parts of
+/// `givenName` and `familyName` joined by a space.
+pub fn derive_display_name(data: &Value) -> Option<String> {
+    let name = data.get("name")?;
+    let part = |key: &str| {
+        name.get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    };
+
+    if let Some(formatted) = part("formatted") {
+        return Some(formatted.to_string());
+    }
+
+    let joined = [part("givenName"), part("familyName")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!joined.is_empty()).then_some(joined)
+}
+
+/// Whether a resource's `displayName` was derived by the server.
+pub fn is_display_name_derived(resource: &Resource) -> bool {
+    is_derived(resource, DISPLAY_NAME)
+}
+
+/// Whether `meta.derivedAttributes` of a resource lists `attribute`.
+pub(super) fn is_derived(resource: &Resource, attribute: &str) -> bool {
+    resource
+        .meta_extra()
+        .get(DERIVED_ATTRIBUTES)
+        .and_then(Value::as_array)
+        .is_some_and(|derived| derived.iter().any(|name| name == attribute))
+}
+
+/// Drop a client-supplied `meta.derivedAttributes` from a write payload.
+///
+/// Runs before validation and the derivation policies, so any marker the
+/// payload carries afterwards is the one those policies recorded, and an
+/// update without one keeps the stored marker.
+pub(super) fn strip_derived_marker(data: &mut Value) {
+    if let Some(meta) = data.get_mut("meta").and_then(Value::as_object_mut) {
+        meta.remove(DERIVED_ATTRIBUTES);
+    }
+}
+
+/// Add or remove `attribute` in the `meta.derivedAttributes` of a write
+/// payload, keeping the other entries.
+///
+/// The list starts from the marker an earlier policy recorded in the payload,
+/// or the stored resource's when there is none, because the replacement's
+/// marker overrides the stored one.
+pub(super) fn record_derived(
+    object: &mut Map<String, Value>,
+    stored: Option<&Resource>,
+    attribute: &str,
+    derived: bool,
+) {
+    let meta = object
+        .entry("meta")
+        .or_insert_with(|| Value::Object(Default::default()));
+    let Some(meta) = meta.as_object_mut() else {
+        return;
+    };
+    let mut names = match meta.get(DERIVED_ATTRIBUTES).and_then(Value::as_array) {
+        Some(names) => names.clone(),
+        None => stored
+            .and_then(|stored| stored.meta_extra().get(DERIVED_ATTRIBUTES).cloned())
+            .and_then(|names| names.as_array().cloned())
+            .unwrap_or_default(),
+    };
+    names.retain(|name| name != attribute);
+    if derived {
+        names.push(Value::String(attribute.to_string()));
+    }
+    meta.insert(DERIVED_ATTRIBUTES.to_string(), Value::Array(names));
+}
+
+impl<P: ResourceProvider> ScimServer<P> {
+    /// Apply the `displayName` derivation policy to a User write payload.
+    ///
+    /// `stored` is the current resource for updates, used to tell an echoed
+    /// derived value apart from one the client chose.
+    pub(super) fn apply_display_name_policy(
+        &self,
+        resource_type: &str,
+        data: &mut Value,
+        stored: Option<&Resource>,
+    ) {
+        if !self.config.derive_display_name || resource_type != "User" {
+            return;
+        }
+
+        let previously_derived = stored.filter(|stored| is_display_name_derived(stored));
+        let client_value = data
+            .get(DISPLAY_NAME)
+            .and_then(Value::as_str)
+            .filter(|value| !value.trim().is_empty());
+        let client_provided = client_value.is_some_and(|value| {
+            previously_derived
+                .and_then(|stored| stored.get_attribute(DISPLAY_NAME))
+                .and_then(Value::as_str)
+                != Some(value)
+        });
+
+        let derived = if client_provided {
+            None
+        } else {
+            derive_display_name(data)
+        };
+
+        let derives = derived.is_some();
+        let Some(object) = data.as_object_mut() else {
+            return;
+        };
+        match derived {
+            Some(display_name) => {
+                object.insert(DISPLAY_NAME.to_string(), Value::String(display_name));
+            }
+            None if client_provided => {}
+            None => {
+                object.remove(DISPLAY_NAME);
+            }
+        }
+
+        // Record the outcome, clearing a marker left by an earlier derivation
+        if derives || previously_derived.is_some() {
+            record_derived(object, stored, DISPLAY_NAME, derives);
+        }
+    }
+}
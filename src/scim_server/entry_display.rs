@@ -0,0 +1,156 @@
+//! Server-derived `display` for multi-valued entries.
+//!
+//! Some downstream systems expect every `emails` and `phoneNumbers` entry to
+//! carry a `display`. When enabled with
+//! [`ScimServerBuilder::with_entry_display_policy`](super::ScimServerBuilder::with_entry_display_policy),
+//! the server fills a missing `display` on create, update and PATCH from the
+//! entry's `value`, optionally prefixed by its `type` (see
+//! [`EntryDisplayPolicy`]).
+//! RFC 7643 gives `addresses` no `display`, so a missing `formatted` is filled
+//! from the address parts instead.
+//!
+//! Attributes holding derived values are recorded in `meta.derivedAttributes`
+//! as `emails.display`, `phoneNumbers.display` and `addresses.formatted`. On
+//! update and PATCH, an entry carrying a value that was derived for a stored
+//! entry is treated as an echo and derived again, so it keeps following
+//! `value` instead of freezing. A value the client actually chose is never
+//! replaced.
+
+use super::builder::EntryDisplayPolicy;
+use super::core::ScimServer;
+use super::display_name::{is_derived, record_derived};
+use crate::providers::ResourceProvider;
+use crate::resource::Resource;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Multi-valued attributes and the sub-attribute derived for their entries.
+const DERIVED_ENTRIES: [(&str, &str); 3] = [
+    ("emails", "display"),
+    ("phoneNumbers", "display"),
+    ("addresses", "formatted"),
+];
+
+/// Derive the `display` of an `emails` or `phoneNumbers` entry.
+///
+/// Returns `None` for [`EntryDisplayPolicy::Off`] or an entry without a
+/// non-empty `value`.
+pub fn derive_entry_display(entry: &Value, policy: EntryDisplayPolicy) -> Option<String> {
+    let value = text(entry, "value")?;
+    match (policy, text(entry, "type")) {
+        (EntryDisplayPolicy::Off, _) => None,
+        (EntryDisplayPolicy::TypeAndValue, Some(kind)) => Some(format!("{}: {}", kind, value)),
+        (EntryDisplayPolicy::Value | EntryDisplayPolicy::TypeAndValue, _) => {
+            Some(value.to_string())
+        }
+    }
+}
+
+/// Derive the `formatted` value of an `addresses` entry.
+///
+/// Joins `streetAddress`, then `locality, region postalCode`, then
+/// `country`, one line each, skipping empty parts.
+pub fn derive_address_formatted(entry: &Value) -> Option<String> {
+    let region_line = [text(entry, "region"), text(entry, "postalCode")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let city_line = [text(entry, "locality"), Some(region_line.as_str())]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let lines = [
+        text(entry, "streetAddress"),
+        Some(city_line.as_str()),
+        text(entry, "country"),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|line| !line.is_empty())
+    .collect::<Vec<_>>();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+fn text<'a>(entry: &'a Value, key: &str) -> Option<&'a str> {
+    entry
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn derive(attribute: &str, entry: &Value, policy: EntryDisplayPolicy) -> Option<String> {
+    match attribute {
+        "addresses" => derive_address_formatted(entry),
+        _ => derive_entry_display(entry, policy),
+    }
+}
+
+impl<P: ResourceProvider> ScimServer<P> {
+    /// Apply the entry `display` derivation policy to a write payload.
+    ///
+    /// `stored` is the current resource for updates, used to tell echoed
+    /// derived values apart from ones the client chose.
+    pub(super) fn apply_entry_display_policy(&self, data: &mut Value, stored: Option<&Resource>) {
+        let policy = self.config.entry_display;
+        if policy == EntryDisplayPolicy::Off {
+            return;
+        }
+        let Some(object) = data.as_object_mut() else {
+            return;
+        };
+        // Typed attributes such as `emails` are only visible in the JSON form
+        let stored_json = stored.and_then(|stored| stored.to_json().ok());
+
+        for (attribute, sub_attribute) in DERIVED_ENTRIES {
+            let marker = format!("{}.{}", attribute, sub_attribute);
+
+            // Values derived for the stored entries, which an update may echo
+            let previously_derived = stored.filter(|stored| is_derived(stored, &marker));
+            let echoed: HashSet<String> = previously_derived
+                .and(stored_json.as_ref())
+                .and_then(|stored| stored.get(attribute))
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| {
+                    let current = text(entry, sub_attribute)?;
+                    let derived = derive(attribute, entry, policy)?;
+                    (current == derived).then_some(derived)
+                })
+                .collect();
+
+            let mut derives = false;
+            if let Some(entries) = object.get_mut(attribute).and_then(Value::as_array_mut) {
+                for entry in entries {
+                    let client_provided =
+                        text(entry, sub_attribute).is_some_and(|current| !echoed.contains(current));
+                    if client_provided {
+                        continue;
+                    }
+                    let derived = derive(attribute, entry, policy);
+                    let Some(entry) = entry.as_object_mut() else {
+                        continue;
+                    };
+                    match derived {
+                        Some(derived) => {
+                            entry.insert(sub_attribute.to_string(), Value::String(derived));
+                            derives = true;
+                        }
+                        None => {
+                            entry.remove(sub_attribute);
+                        }
+                    }
+                }
+            }
+
+            // Record the outcome, clearing a marker left by an earlier derivation
+            if derives || previously_derived.is_some() {
+                record_derived(object, stored, &marker, derives);
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Tenant export with compliance-oriented PII scrubbing.
+//!
+//! [`ScimServer::export_tenant`] lists every resource of the request's tenant
+//! for each registered resource type that supports listing. When the tenant's
+//! [`ScimComplianceConfig`] enables PII scrubbing, the configured attributes
+//! are scrubbed from each exported resource before it is returned; the stored
+//! resources are never modified.
+//!
+//! Scrubbing replaces string values only. Objects, arrays, booleans and
+//! numbers under a PII attribute keep their shape, so `emails` still carries
+//! its `type` and `primary` sub-attributes and exports stay loadable by tools
+//! expecting SCIM structure. In [`PiiScrubbingMode::Hash`] equal values map
+//! to equal hashes, so exported records can still be joined on them.
+
+use super::core::ScimServer;
+use crate::error::ScimResult;
+use crate::multi_tenant::{PiiScrubbingMode, ScimComplianceConfig};
+use crate::providers::ResourceProvider;
+use crate::resource::{RequestContext, ScimOperation};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Replacement for every string value scrubbed in [`PiiScrubbingMode::Mask`].
+pub const PII_MASK: &str = "[REDACTED]";
+
+impl<P: ResourceProvider + Sync> ScimServer<P> {
+    /// Export every resource of the request's tenant.
+    ///
+    /// Resource types are exported in name order; types that do not support
+    /// listing are skipped. Attributes that are never returned, such as a
+    /// User's `password`, are left out. PII is scrubbed as configured by the tenant's
+    /// registered [`ScimComplianceConfig`]; requests without a tenant, or for
+    /// a tenant without configuration, are exported unscrubbed.
+    pub async fn export_tenant(&self, context: &RequestContext) -> ScimResult<Vec<Value>> {
+        let compliance = context
+            .tenant_id()
+            .and_then(|tenant_id| self.tenant_configuration(tenant_id))
+            .map(|configuration| &configuration.compliance_config)
+            .filter(|compliance| compliance.enable_pii_scrubbing);
+
+        let mut resource_types: Vec<&str> = self
+            .get_supported_resource_types()
+            .into_iter()
+            .filter(|resource_type| self.supports_operation(resource_type, &ScimOperation::List))
+            .collect();
+        resource_types.sort_unstable();
+
+        let mut exported = Vec::new();
+        for resource_type in resource_types {
+            for resource in self.list_resources(resource_type, context).await? {
+                let mut data = resource.to_json()?;
+                self.strip_never_returned_attributes(resource_type, &mut data);
+                if let Some(compliance) = compliance {
+                    scrub_pii(&mut data, compliance);
+                }
+                exported.push(data);
+            }
+        }
+        Ok(exported)
+    }
+
+    /// Export the request's tenant as newline-delimited JSON.
+    ///
+    /// Each line is one resource as produced by
+    /// [`export_tenant`](Self::export_tenant), including its scrubbing.
+    pub async fn export_tenant_ndjson(&self, context: &RequestContext) -> ScimResult<String> {
+        let mut ndjson = String::new();
+        for resource in self.export_tenant(context).await? {
+            ndjson.push_str(&serde_json::to_string(&resource)?);
+            ndjson.push('\n');
+        }
+        Ok(ndjson)
+    }
+}
+
+/// Scrub the configured PII attributes from a serialized resource in place.
+///
+/// Attribute paths are dotted and matched case-insensitively; extension
+/// attributes are named by their schema URN followed by `.` and the
+/// attribute name. Multi-valued attributes are scrubbed in every value.
+///
+/// # Examples
+///
+/// ```rust
+/// use scim_server::multi_tenant::ScimComplianceConfig;
+/// use scim_server::scim_server::export::{PII_MASK, scrub_pii};
+/// use serde_json::json;
+///
+/// let mut user = json!({
+///     "userName": "bjensen",
+///     "emails": [{"value": "bjensen@example.com", "type": "work", "primary": true}]
+/// });
+/// scrub_pii(&mut user, &ScimComplianceConfig::default());
+/// assert_eq!(user["emails"][0]["value"], PII_MASK);
+/// assert_eq!(user["emails"][0]["primary"], true);
+/// assert_eq!(user["userName"], "bjensen");
+/// ```
+pub fn scrub_pii(resource: &mut Value, compliance: &ScimComplianceConfig) {
+    for path in &compliance.pii_attributes {
+        scrub_path(resource, path, &compliance.pii_scrubbing_mode);
+    }
+}
+
+fn scrub_path(data: &mut Value, path: &str, mode: &PiiScrubbingMode) {
+    match data {
+        Value::Array(items) => {
+            for item in items {
+                scrub_path(item, path, mode);
+            }
+        }
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if key.eq_ignore_ascii_case(path) {
+                    scrub_value(value, mode);
+                } else if path.len() > key.len()
+                    && path.is_char_boundary(key.len())
+                    && path[..key.len()].eq_ignore_ascii_case(key)
+                    && path[key.len()..].starts_with('.')
+                {
+                    scrub_path(value, &path[key.len() + 1..], mode);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace every string within a value, keeping its structure.
+fn scrub_value(value: &mut Value, mode: &PiiScrubbingMode) {
+    match value {
+        Value::String(text) => {
+            *text = match mode {
+                PiiScrubbingMode::Mask => PII_MASK.to_string(),
+                PiiScrubbingMode::Hash { salt } => format!(
+                    "{:x}",
+                    Sha256::new()
+                        .chain_update(salt)
+                        .chain_update(text.as_bytes())
+                        .finalize()
+                ),
+            };
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| scrub_value(item, mode)),
+        Value::Object(object) => object
+            .values_mut()
+            .for_each(|value| scrub_value(value, mode)),
+        _ => {}
+    }
+}
@@ -0,0 +1,305 @@
+//! Schema extensions registered per resource type.
+//!
+//! A resource's `schemas` array may only list its resource type's base schema
+//! and the extensions registered for it with
+//! [`ScimServer::register_schema_extension`]. Extension attributes are carried
+//! under the extension URN and validated against the extension schema.
+//!
+//! With strict extension checking (the default) an unregistered URN in
+//! `schemas`, or data carried under one, is rejected with
+//! [`ValidationError::UnknownSchemaUri`]. In lenient mode such URNs and their
+//! data pass through unvalidated. Tenants may override the mode, see
+//! [`validation_settings`](super::validation_settings).
+//!
+//! A tenant's [`ScimSchemaConfig`](crate::multi_tenant::ScimSchemaConfig) can
+//! narrow this per tenant: an extension listed there as disabled is treated as
+//! unregistered for the tenant, and one listed as required must be present in
+//! every create and replace payload and cannot be removed by PATCH. Extensions
+//! the tenant configuration does not mention stay optional.
+//!
+//! Complex extension attributes with a `$ref` sub-attribute of type
+//! `reference` get it generated on output, like `members` and `groups`, from
+//! the entry's `value` and the resource type it references.
+
+use super::core::ScimServer;
+use crate::error::{ScimError, ScimResult, ValidationError};
+use crate::providers::ResourceProvider;
+use crate::resource::RequestContext;
+use crate::schema::{AttributeDefinition, AttributeType, OperationContext, Schema};
+use serde_json::{Map, Value};
+
+impl<P: ResourceProvider> ScimServer<P> {
+    /// Register a schema extension for a resource type.
+    ///
+    /// The resource type must already be registered. Registering an extension
+    /// with the same URN again replaces it.
+    pub fn register_schema_extension(
+        &mut self,
+        resource_type: &str,
+        extension: Schema,
+    ) -> Result<(), ScimError> {
+        self.get_handler(resource_type)?;
+
+        self.schema_registry
+            .add_schema(extension.clone())
+            .map_err(|e| ScimError::internal(format!("Failed to add schema: {}", e)))?;
+
+        let extensions = self
+            .schema_extensions
+            .entry(resource_type.to_string())
+            .or_default();
+        extensions.retain(|existing| existing.id != extension.id);
+        extensions.push(extension);
+        self.discovery_cache.invalidate();
+
+        Ok(())
+    }
+
+    /// Schema extensions registered for a resource type.
+    pub fn get_schema_extensions(&self, resource_type: &str) -> &[Schema] {
+        self.schema_extensions
+            .get(resource_type)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Schemas a resource type's resources may carry: its base schema first,
+    /// followed by its registered extensions in registration order.
+    ///
+    /// With a request context, extensions the request's tenant has disabled
+    /// are left out, as in [`tenant_schema_extensions`](Self::tenant_schema_extensions).
+    /// Unregistered resource types have no schemas.
+    pub fn list_registered_schemas_for(
+        &self,
+        resource_type: &str,
+        context: Option<&RequestContext>,
+    ) -> Vec<&Schema> {
+        let Some(handler) = self.resource_handlers.get(resource_type) else {
+            return Vec::new();
+        };
+
+        let mut schemas = vec![&handler.schema];
+        match context {
+            Some(context) => schemas.extend(
+                self.tenant_schema_extensions(resource_type, context)
+                    .into_iter()
+                    .map(|(extension, _)| extension),
+            ),
+            None => schemas.extend(self.get_schema_extensions(resource_type)),
+        }
+        schemas
+    }
+
+    /// Schema extensions available to the request's tenant, each paired with
+    /// whether the tenant requires it.
+    ///
+    /// Without tenant context or tenant configuration every registered
+    /// extension is available and optional.
+    pub fn tenant_schema_extensions(
+        &self,
+        resource_type: &str,
+        context: &RequestContext,
+    ) -> Vec<(&Schema, bool)> {
+        let configured = context
+            .tenant_id()
+            .and_then(|tenant_id| self.tenant_configuration(tenant_id))
+            .map(|configuration| configuration.schema_config.extensions.as_slice())
+            .unwrap_or_default();
+
+        self.get_schema_extensions(resource_type)
+            .iter()
+            .filter_map(|extension| {
+                match configured.iter().find(|entry| entry.uri == extension.id) {
+                    Some(entry) if !entry.enabled => None,
+                    Some(entry) => Some((extension, entry.required)),
+                    None => Some((extension, false)),
+                }
+            })
+            .collect()
+    }
+
+    /// Reject a resource that lacks data for an extension the request's
+    /// tenant requires.
+    pub(super) fn check_required_extensions(
+        &self,
+        resource_type: &str,
+        data: &Value,
+        context: &RequestContext,
+    ) -> ScimResult<()> {
+        let missing = self
+            .tenant_schema_extensions(resource_type, context)
+            .into_iter()
+            .any(|(extension, required)| {
+                let present = data
+                    .get(&extension.id)
+                    .and_then(Value::as_object)
+                    .is_some_and(|fields| !fields.is_empty());
+                required && !present
+            });
+        if missing {
+            return Err(ValidationError::MissingRequiredExtension.into());
+        }
+        Ok(())
+    }
+
+    /// Validate a write payload against its base schema and the extensions
+    /// available to the request's tenant named in its `schemas` array.
+    ///
+    /// `operation` decides how required attributes are enforced, as described
+    /// for [`OperationContext`], unless the tenant's
+    /// [`ValidationSettings`](super::ValidationSettings) turn that off.
+    pub(super) fn validate_with_extensions(
+        &self,
+        resource_type: &str,
+        schema: &Schema,
+        data: &Value,
+        operation: OperationContext,
+        context: &RequestContext,
+    ) -> ScimResult<()> {
+        let settings = self.validation_settings(context);
+        // Patch validation checks attributes without requiring any
+        let operation = if settings.enforce_required {
+            operation
+        } else {
+            OperationContext::Patch
+        };
+        if operation != OperationContext::Patch {
+            self.check_required(resource_type, operation, None, data, context)?;
+        }
+
+        let extensions = self.tenant_schema_extensions(resource_type, context);
+        let strict = settings.strict_schemas;
+        let extension = |uri: &str| {
+            extensions
+                .iter()
+                .map(|(extension, _)| *extension)
+                .find(|extension| extension.id == uri)
+        };
+
+        let declared = data
+            .get("schemas")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str);
+        for uri in declared {
+            if strict && uri != schema.id && extension(uri).is_none() {
+                return Err(ValidationError::UnknownSchemaUri {
+                    uri: uri.to_string(),
+                }
+                .into());
+            }
+        }
+
+        // Extension data lives under its URN, outside the base schema
+        let Some(object) = data.as_object() else {
+            return Ok(self
+                .schema_registry
+                .validate_resource_for_operation(schema, data, operation)?);
+        };
+        if !object.keys().any(|key| key.starts_with("urn:")) {
+            return Ok(self
+                .schema_registry
+                .validate_resource_for_operation(schema, data, operation)?);
+        }
+
+        let mut base = Map::new();
+        for (key, value) in object {
+            if !key.starts_with("urn:") {
+                base.insert(key.clone(), value.clone());
+                continue;
+            }
+            match extension(key) {
+                Some(extension) => self
+                    .schema_registry
+                    .validate_resource_for_operation(extension, value, operation)?,
+                None if strict => {
+                    return Err(ValidationError::UnknownSchemaUri { uri: key.clone() }.into());
+                }
+                None => {}
+            }
+        }
+
+        Ok(self.schema_registry.validate_resource_for_operation(
+            schema,
+            &Value::Object(base),
+            operation,
+        )?)
+    }
+
+    /// Generate `$ref` for references held in a resource's extension data.
+    ///
+    /// An entry's target type is its `type` when that is one of the
+    /// attribute's `referenceTypes`, otherwise the only reference type
+    /// declared. Entries whose target is not a registered resource type are
+    /// left untouched.
+    pub(super) fn inject_extension_ref_fields(
+        &self,
+        resource_type: &str,
+        resource_json: &mut Value,
+        tenant_id: Option<&str>,
+    ) -> ScimResult<()> {
+        for extension in self.get_schema_extensions(resource_type) {
+            let Some(data) = resource_json
+                .get_mut(&extension.id)
+                .and_then(Value::as_object_mut)
+            else {
+                continue;
+            };
+            for attribute in &extension.attributes {
+                let Some(reference) = attribute
+                    .sub_attributes
+                    .iter()
+                    .find(|sub| sub.name == "$ref" && sub.data_type == AttributeType::Reference)
+                else {
+                    continue;
+                };
+                match data.get_mut(&attribute.name) {
+                    Some(Value::Array(entries)) => {
+                        for entry in entries {
+                            self.inject_entry_ref(reference, entry, tenant_id)?;
+                        }
+                    }
+                    Some(entry) => self.inject_entry_ref(reference, entry, tenant_id)?,
+                    None => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn inject_entry_ref(
+        &self,
+        reference: &AttributeDefinition,
+        entry: &mut Value,
+        tenant_id: Option<&str>,
+    ) -> ScimResult<()> {
+        let Some(entry) = entry.as_object_mut() else {
+            return Ok(());
+        };
+        let Some(id) = entry.get("value").and_then(Value::as_str) else {
+            return Ok(());
+        };
+        let target = match entry.get("type").and_then(Value::as_str) {
+            Some(kind) if reference.reference_types.iter().any(|t| t == kind) => kind,
+            _ => match reference.reference_types.as_slice() {
+                [only] => only.as_str(),
+                _ => return Ok(()),
+            },
+        };
+        if !self.resource_handlers.contains_key(target) {
+            return Ok(());
+        }
+
+        let endpoint = self.resource_endpoint(target);
+        match self.config.resource_location(tenant_id, &endpoint, id)? {
+            Some(ref_url) => {
+                entry.insert("$ref".to_string(), Value::String(ref_url));
+            }
+            None => {
+                entry.remove("$ref");
+            }
+        }
+        Ok(())
+    }
+}
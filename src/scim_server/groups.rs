@@ -0,0 +1,184 @@
+//! User `groups` derived from Group memberships.
+//!
+//! RFC 7643 Section 4.1.2 defines a User's `groups` as read-only, derived
+//! from the Groups that list the User as a member. By default the server
+//! returns `groups` as stored; with
+//! [`GroupMembership::Direct`] or [`GroupMembership::Nested`] it is derived
+//! from the registered `Group` resources on every get and list instead.
+//!
+//! Nested resolution walks outwards from the User's direct Groups to the
+//! Groups containing them, level by level, up to the configured depth. Each
+//! Group is listed once, at the first level it is reached, so membership
+//! cycles terminate. Entries carry `type: "direct"` for Groups listing the
+//! User itself and `type: "indirect"` for Groups reached through another
+//! Group.
+//!
+//! Memberships are only derived for requests the authorization policy allows
+//! to list Groups; for other requests `groups` is omitted, so a User read
+//! cannot reveal Groups the caller may not see.
+//!
+//! Deriving `groups` loads every Group of the tenant once per request, with a
+//! single [`get_resources`](ResourceProvider::get_resources) call so all
+//! memberships come from one consistent view of the Groups. It is skipped
+//! when the request's `attributes`/`excludedAttributes` leave `groups` out.
+
+use super::builder::GroupMembership;
+use super::core::ScimServer;
+use crate::error::ScimResult;
+use crate::providers::ResourceProvider;
+use crate::resource::{ListQuery, RequestContext, Resource, ScimOperation};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+const GROUPS: &str = "groups";
+
+/// A Group as seen by membership resolution.
+struct GroupNode {
+    id: String,
+    display: Option<String>,
+    /// Member `value`s with their `type`, if given
+    members: Vec<(String, Option<String>)>,
+}
+
+impl GroupNode {
+    fn from_resource(resource: &Resource) -> Option<Self> {
+        let members = resource
+            .get_members()
+            .into_iter()
+            .flat_map(|members| members.iter())
+            .map(|member| {
+                (
+                    member.value().as_str().to_string(),
+                    member.member_type().map(str::to_string),
+                )
+            })
+            .collect();
+        Some(Self {
+            id: resource.get_id()?.to_string(),
+            display: resource
+                .get_attribute("displayName")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            members,
+        })
+    }
+
+    /// Whether the Group lists `id` as a member of `member_type`. Members
+    /// without a `type` match any type.
+    fn contains(&self, id: &str, member_type: &str) -> bool {
+        self.members.iter().any(|(value, value_type)| {
+            value == id
+                && value_type
+                    .as_deref()
+                    .is_none_or(|value_type| value_type.eq_ignore_ascii_case(member_type))
+        })
+    }
+
+    fn entry(&self, membership: &str) -> Value {
+        let mut entry = json!({"value": self.id, "type": membership});
+        if let Some(display) = &self.display {
+            entry["display"] = json!(display);
+        }
+        entry
+    }
+}
+
+/// The `groups` value for a User: direct memberships first, then indirect
+/// ones level by level, each Group once.
+fn memberships(groups: &[GroupNode], user_id: &str, max_depth: usize) -> Vec<Value> {
+    let mut seen = HashSet::new();
+    let mut level: Vec<&GroupNode> = groups
+        .iter()
+        .filter(|group| group.contains(user_id, "User") && seen.insert(group.id.as_str()))
+        .collect();
+    let mut entries: Vec<Value> = level.iter().map(|group| group.entry("direct")).collect();
+
+    for _ in 1..max_depth {
+        level = groups
+            .iter()
+            .filter(|parent| {
+                level
+                    .iter()
+                    .any(|child| parent.contains(&child.id, "Group"))
+                    && seen.insert(parent.id.as_str())
+            })
+            .collect();
+        if level.is_empty() {
+            break;
+        }
+        entries.extend(level.iter().map(|group| group.entry("indirect")));
+    }
+
+    entries
+}
+
+impl<P: ResourceProvider + Sync> ScimServer<P> {
+    /// Replace the `groups` of Users read from the provider with their
+    /// derived memberships, if configured.
+    pub(super) async fn apply_group_membership(
+        &self,
+        resource_type: &str,
+        resources: &mut [Resource],
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> ScimResult<()> {
+        let max_depth = match self.config.group_membership {
+            GroupMembership::Stored => return Ok(()),
+            GroupMembership::Direct => 1,
+            GroupMembership::Nested { max_depth } => max_depth,
+        };
+        if resource_type != "User"
+            || resources.is_empty()
+            || !self.resource_handlers.contains_key("Group")
+        {
+            return Ok(());
+        }
+        if let Some(query) = query {
+            let listed = |names: &[String]| names.iter().any(|n| n.eq_ignore_ascii_case(GROUPS));
+            if (!query.attributes.is_empty() && !listed(&query.attributes))
+                || listed(&query.excluded_attributes)
+            {
+                return Ok(());
+            }
+        }
+
+        if self
+            .ensure_operation_authorized(context, &ScimOperation::List, "Group", None)
+            .is_err()
+        {
+            for resource in resources {
+                resource.attributes.remove(GROUPS);
+            }
+            return Ok(());
+        }
+
+        let ids = self
+            .provider
+            .list_ids("Group", None, context)
+            .await
+            .map_err(|e| self.map_provider_error(e, context))?;
+        let groups: Vec<GroupNode> = self
+            .provider
+            .get_resources("Group", &ids, context)
+            .await
+            .map_err(|e| self.map_provider_error(e, context))?
+            .iter()
+            .flatten()
+            .filter_map(|group| GroupNode::from_resource(group.resource()))
+            .collect();
+
+        for resource in resources {
+            let Some(user_id) = resource.get_id().map(str::to_string) else {
+                continue;
+            };
+            let entries = memberships(&groups, &user_id, max_depth);
+            if entries.is_empty() {
+                resource.attributes.remove(GROUPS);
+            } else {
+                resource.set_attribute(GROUPS.to_string(), Value::Array(entries));
+            }
+        }
+
+        Ok(())
+    }
+}
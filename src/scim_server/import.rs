@@ -0,0 +1,233 @@
+//! Bulk import with deferred referential integrity.
+//!
+//! A large import rarely arrives in dependency order: a Group may list
+//! members whose Users come later in the same batch. [`ScimServer::import_resources`]
+//! therefore loads resources in two passes. The first creates each resource
+//! through the normal create path, so schema validation, defaults and
+//! uniqueness still apply per resource and a failing resource is reported
+//! without stopping the rest. The second pass runs once everything is loaded
+//! and checks every reference held by the imported resources, reporting all
+//! dangling ones at once as [`ValidationError::BrokenReference`].
+//!
+//! A reference is the `value` of an entry in a multi-valued complex attribute
+//! whose `$ref` sub-attribute names registered resource types in its
+//! `referenceTypes`, such as Group `members`. An entry whose `type` names one
+//! of those resource types is resolved against that type only.
+//! References are resolved with one
+//! [`get_resources`](crate::providers::ResourceProvider::get_resources) call
+//! per resource type, so a provider reading from a consistent snapshot never
+//! reports a membership half-way through a concurrent change.
+
+use super::core::ScimServer;
+use crate::error::{ScimError, ScimResult, ValidationError};
+use crate::providers::ResourceProvider;
+use crate::resource::{RequestContext, Resource};
+use crate::schema::{AttributeType, Schema};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+
+/// A resource that could not be created during an import.
+#[derive(Debug)]
+pub struct ImportFailure {
+    /// Position of the resource in the import
+    pub index: usize,
+    /// Resource type of the resource
+    pub resource_type: String,
+    /// Why the create failed
+    pub error: ScimError,
+}
+
+/// A reference held by an imported resource that resolves to nothing.
+#[derive(Debug)]
+pub struct DanglingReference {
+    /// Resource type of the referring resource
+    pub resource_type: String,
+    /// Id of the referring resource
+    pub id: String,
+    /// The [`ValidationError::BrokenReference`] describing the reference
+    pub error: ValidationError,
+}
+
+/// Result of [`ScimServer::import_resources`].
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Resources created, in import order
+    pub created: Vec<Resource>,
+    /// Resources that failed validation or creation, in import order
+    pub failed: Vec<ImportFailure>,
+    /// References that resolve to no resource after the import
+    pub dangling_references: Vec<DanglingReference>,
+}
+
+impl ImportReport {
+    /// Whether every resource was created and every reference resolves.
+    pub fn is_ok(&self) -> bool {
+        self.failed.is_empty() && self.dangling_references.is_empty()
+    }
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} created, {} failed, {} dangling references",
+            self.created.len(),
+            self.failed.len(),
+            self.dangling_references.len()
+        )?;
+        for failure in &self.failed {
+            write!(
+                f,
+                "\n- #{} ({}): {}",
+                failure.index, failure.resource_type, failure.error
+            )?;
+        }
+        for dangling in &self.dangling_references {
+            write!(
+                f,
+                "\n- {} '{}': {}",
+                dangling.resource_type, dangling.id, dangling.error
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<P: ResourceProvider + Sync> ScimServer<P> {
+    /// Import resources, checking references only once all are loaded.
+    ///
+    /// Each `(resource_type, data)` pair is created as by
+    /// [`create_resource`](Self::create_resource); failures are collected in
+    /// the report rather than aborting the import. Afterwards every reference
+    /// held by a created resource is resolved against the provider, and each
+    /// one that resolves to nothing is reported. Resources are not rolled back
+    /// when problems are found.
+    ///
+    /// Errors are returned only when the reference pass itself cannot run,
+    /// e.g. because the provider fails.
+    pub async fn import_resources(
+        &self,
+        resources: Vec<(String, Value)>,
+        context: &RequestContext,
+    ) -> ScimResult<ImportReport> {
+        let mut report = ImportReport::default();
+
+        for (index, (resource_type, data)) in resources.into_iter().enumerate() {
+            match self.create_resource(&resource_type, data, context).await {
+                Ok(resource) => report.created.push(resource),
+                Err(error) => report.failed.push(ImportFailure {
+                    index,
+                    resource_type,
+                    error,
+                }),
+            }
+        }
+
+        for resource in &report.created {
+            let broken = self.find_broken_references(resource, context).await?;
+            report
+                .dangling_references
+                .extend(broken.into_iter().map(|error| DanglingReference {
+                    resource_type: resource.resource_type.clone(),
+                    id: resource.get_id().unwrap_or_default().to_string(),
+                    error,
+                }));
+        }
+
+        Ok(report)
+    }
+
+    /// Resolve every reference held by a resource, returning the broken ones.
+    async fn find_broken_references(
+        &self,
+        resource: &Resource,
+        context: &RequestContext,
+    ) -> ScimResult<Vec<ValidationError>> {
+        let schema = self.get_schema_for_resource_type(&resource.resource_type)?;
+        let json = resource.to_json()?;
+        let mut broken = Vec::new();
+
+        for (attribute, reference_types) in self.reference_attributes(&schema) {
+            let Some(entries) = json.get(attribute).and_then(Value::as_array) else {
+                continue;
+            };
+            let references: Vec<(&str, Vec<&str>)> = entries
+                .iter()
+                .filter_map(|entry| {
+                    let value = entry.get("value").and_then(Value::as_str)?;
+                    let candidates = match entry.get("type").and_then(Value::as_str) {
+                        Some(entry_type) if reference_types.contains(&entry_type) => {
+                            vec![entry_type]
+                        }
+                        _ => reference_types.clone(),
+                    };
+                    Some((value, candidates))
+                })
+                .collect();
+
+            // Resolve each type's references in one read, so every reference
+            // is checked against the same snapshot of that type
+            let mut existing: HashSet<(&str, String)> = HashSet::new();
+            for &candidate in &reference_types {
+                let ids: Vec<String> = references
+                    .iter()
+                    .filter(|(_, candidates)| candidates.contains(&candidate))
+                    .map(|(value, _)| value.to_string())
+                    .collect();
+                if ids.is_empty() {
+                    continue;
+                }
+                self.ensure_operation_permitted(context, "read")?;
+                let found = self
+                    .provider
+                    .get_resources(candidate, &ids, context)
+                    .await
+                    .map_err(|e| self.map_provider_error(e, context))?;
+                existing.extend(
+                    ids.into_iter()
+                        .zip(found)
+                        .filter(|(_, resource)| resource.is_some())
+                        .map(|(id, _)| (candidate, id)),
+                );
+            }
+
+            for (value, candidates) in references {
+                let found = candidates
+                    .iter()
+                    .any(|candidate| existing.contains(&(*candidate, value.to_string())));
+                if !found {
+                    broken.push(ValidationError::BrokenReference {
+                        attribute: attribute.to_string(),
+                        reference: value.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(broken)
+    }
+
+    /// Multi-valued attributes of a schema whose `$ref` points at registered
+    /// resource types, with those types.
+    fn reference_attributes<'a>(&self, schema: &'a Schema) -> Vec<(&'a str, Vec<&'a str>)> {
+        schema
+            .attributes
+            .iter()
+            .filter(|attr| attr.multi_valued && attr.data_type == AttributeType::Complex)
+            .filter_map(|attr| {
+                let reference = attr
+                    .sub_attributes
+                    .iter()
+                    .find(|sub| sub.name == "$ref" && sub.data_type == AttributeType::Reference)?;
+                let types: Vec<&str> = reference
+                    .reference_types
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|name| self.resource_handlers.contains_key(*name))
+                    .collect();
+                (!types.is_empty()).then_some((attr.name.as_str(), types))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,214 @@
+//! Duplicate Group member handling.
+//!
+//! A `members` array listing the same member `value` twice would be stored
+//! and returned twice, each copy with its own `$ref`. Under the default
+//! [`DuplicateMemberPolicy::Deduplicate`] the server keeps the first entry for
+//! each `value` and drops later ones, preserving the order of the rest; under
+//! [`DuplicateMemberPolicy::Reject`] the request fails with a uniqueness
+//! error instead.
+//!
+//! The policy applies to create and replace payloads and to the values of
+//! PATCH `add` operations. Entries without a string `value` are left as they
+//! are.
+//!
+//! Group size can be capped with
+//! [`max_group_members`](super::ScimServerConfig::max_group_members), which a
+//! tenant's endpoint configuration may override. A create or replace payload
+//! listing more members, or a PATCH whose result would have more than the
+//! Group's existing members allow, fails with [`ScimError::TooManyMembers`].
+
+use super::builder::DuplicateMemberPolicy;
+use super::core::ScimServer;
+use crate::error::{ScimError, ScimResult, ValidationError};
+use crate::providers::ResourceProvider;
+use crate::providers::helpers::patch::apply_patch_operation;
+use crate::resource::RequestContext;
+use serde_json::Value;
+use std::collections::HashSet;
+
+const MEMBERS: &str = "members";
+
+impl<P: ResourceProvider> ScimServer<P> {
+    /// Apply the duplicate member policy to a write payload.
+    pub(crate) fn apply_member_policy(&self, data: &mut Value) -> ScimResult<()> {
+        match data.get_mut(MEMBERS) {
+            Some(members) => self.dedup_members(members),
+            None => Ok(()),
+        }
+    }
+
+    /// Apply the duplicate member policy to the values of PATCH `add`
+    /// operations that target `members`, or carry it when path-less.
+    pub(super) fn apply_member_policy_to_patch(&self, patch_request: &mut Value) -> ScimResult<()> {
+        let Some(operations) = patch_request
+            .get_mut("Operations")
+            .and_then(Value::as_array_mut)
+        else {
+            return Ok(());
+        };
+        for operation in operations {
+            let op = operation.get("op").and_then(Value::as_str).unwrap_or("");
+            if !op.eq_ignore_ascii_case("add") {
+                continue;
+            }
+            let targets_members = match operation.get("path").and_then(Value::as_str) {
+                Some(path) => path.eq_ignore_ascii_case(MEMBERS),
+                None => true,
+            };
+            let Some(value) = operation.get_mut("value") else {
+                continue;
+            };
+            if !targets_members {
+                continue;
+            }
+            if value.is_array() {
+                self.dedup_members(value)?;
+            } else {
+                self.apply_member_policy(value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject a Group payload listing more members than the request's
+    /// tenant allows.
+    pub(super) fn check_member_count(
+        &self,
+        resource_type: &str,
+        data: &Value,
+        context: &RequestContext,
+    ) -> ScimResult<()> {
+        if resource_type != "Group" {
+            return Ok(());
+        }
+        self.check_member_limit(member_count(data), context)
+    }
+
+    /// Reject a PATCH that would leave a Group with more members than the
+    /// request's tenant allows.
+    ///
+    /// `current` is the stored Group JSON. Members added are counted on top
+    /// of the existing ones, less any already present; other operations are
+    /// previewed. Patches that cannot be previewed are counted as the
+    /// existing members plus every member they add.
+    pub(super) fn check_patch_member_count(
+        &self,
+        resource_type: &str,
+        current: &Value,
+        patch_request: &Value,
+        context: &RequestContext,
+    ) -> ScimResult<()> {
+        if resource_type != "Group" || self.max_group_members(context).is_none() {
+            return Ok(());
+        }
+        let operations = patch_request
+            .get("Operations")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let count = match preview_members(current, operations) {
+            Some(count) => count,
+            None => {
+                member_count(current)
+                    + operations
+                        .iter()
+                        .filter_map(added_members)
+                        .map(|added| added.len())
+                        .sum::<usize>()
+            }
+        };
+        self.check_member_limit(count, context)
+    }
+
+    /// Maximum number of Group members for the request's tenant, if any.
+    fn max_group_members(&self, context: &RequestContext) -> Option<usize> {
+        context
+            .tenant_id()
+            .and_then(|tenant_id| self.tenant_configuration(tenant_id))
+            .and_then(|configuration| configuration.endpoint.max_group_members)
+            .or(self.config.max_group_members)
+    }
+
+    fn check_member_limit(&self, count: usize, context: &RequestContext) -> ScimResult<()> {
+        match self.max_group_members(context) {
+            Some(limit) if count > limit => Err(ScimError::too_many_members(count, limit)),
+            _ => Ok(()),
+        }
+    }
+
+    fn dedup_members(&self, members: &mut Value) -> ScimResult<()> {
+        let Some(members) = members.as_array_mut() else {
+            return Ok(());
+        };
+
+        let mut seen = HashSet::new();
+        let mut duplicate = None;
+        members.retain(|member| match member.get("value").and_then(Value::as_str) {
+            Some(value) if !seen.insert(value.to_string()) => {
+                duplicate.get_or_insert_with(|| value.to_string());
+                false
+            }
+            _ => true,
+        });
+
+        match duplicate {
+            Some(value) if self.config.duplicate_members == DuplicateMemberPolicy::Reject => {
+                Err(ValidationError::ServerUniquenessViolation {
+                    attribute: MEMBERS.to_string(),
+                    value,
+                }
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn member_count(data: &Value) -> usize {
+    data.get(MEMBERS)
+        .and_then(Value::as_array)
+        .map_or(0, Vec::len)
+}
+
+/// Members a PATCH operation adds, if it is an `add` targeting `members` or
+/// carrying it when path-less.
+fn added_members(operation: &Value) -> Option<Vec<Value>> {
+    let op = operation.get("op").and_then(Value::as_str)?;
+    if !op.eq_ignore_ascii_case("add") {
+        return None;
+    }
+    let value = operation.get("value")?;
+    let added = match operation.get("path").and_then(Value::as_str) {
+        Some(path) if path.eq_ignore_ascii_case(MEMBERS) => value,
+        Some(_) => return None,
+        None => value.get(MEMBERS)?,
+    };
+    match added {
+        Value::Array(members) => Some(members.clone()),
+        member => Some(vec![member.clone()]),
+    }
+}
+
+/// Number of members after applying `operations` to `current`, with `add`
+/// appending to `members` as RFC 7644 Section 3.5.2.1 requires.
+fn preview_members(current: &Value, operations: &[Value]) -> Option<usize> {
+    let mut patched = current.clone();
+    for operation in operations {
+        let Some(added) = added_members(operation) else {
+            apply_patch_operation(&mut patched, operation).ok()?;
+            continue;
+        };
+        let members = patched
+            .as_object_mut()?
+            .entry(MEMBERS)
+            .or_insert_with(|| Value::Array(Vec::new()))
+            .as_array_mut()?;
+        for member in added {
+            let value = member.get("value");
+            if value.is_none() || !members.iter().any(|m| m.get("value") == value) {
+                members.push(member);
+            }
+        }
+    }
+    Some(member_count(&patched))
+}
@@ -7,24 +7,66 @@
 //! # Module Organization
 //!
 //! * [`core`] - Core ScimServer struct and initialization
+//! * [`authorization`] - External authorization policy consulted before each operation
 //! * [`builder`] - Builder pattern for server configuration and tenant handling
+//! * [`computed`] - Read-only computed attributes evaluated on reads
+//! * [`display_name`] - Opt-in server derivation of User `displayName`
+//! * [`entry_display`] - Opt-in server derivation of multi-valued entry `display`
+//! * [`export`] - Tenant export with PII scrubbing
+//! * [`extensions`] - Schema extensions registered per resource type and enabled per tenant
+//! * [`groups`] - User `groups` derived from Group memberships
+//! * [`import`] - Bulk import with deferred referential integrity checks
+//! * [`members`] - Duplicate Group member handling
+//! * [`partial_patch`] - Opt-in PATCH mode applying and reporting each operation on its own
+//! * [`pagination`] - Global and per resource type page size caps for list responses
+//! * [`patch_targets`] - Up-front checks that PATCH operations target patchable attributes
 //! * [`registration`] - Resource type registration and operation support management
+//! * [`required`] - Tenant configuration and required attribute enforcement for PATCH
 //! * [`operations`] - CRUD operations for resources (create, read, update, delete, list, search)
 //! * [`schema_management`] - Schema-related operations and validation helpers
+//! * [`self_test`] - Startup self-test of registered resource types
+//! * [`validation_settings`] - Validation strictness resolved per tenant
 //! - `tests` - Test infrastructure and comprehensive test cases
 
+pub mod authorization;
 pub mod builder;
+pub mod computed;
 pub mod core;
+pub mod discovery;
+pub mod display_name;
+pub mod entry_display;
+pub mod export;
+pub mod extensions;
+pub mod groups;
+pub mod import;
+pub mod members;
 pub mod operations;
+pub mod pagination;
+pub mod partial_patch;
+pub mod patch_targets;
 pub mod registration;
+pub mod required;
 pub mod schema_management;
+pub mod self_test;
+pub mod validation_settings;
 
 #[cfg(test)]
 pub mod tests;
 
 // Re-export the main types to maintain API compatibility
+pub use authorization::{AllowAll, AuthorizationPolicy};
+pub use builder::{
+    DefaultListOrder, DuplicateMemberPolicy, EntryDisplayPolicy, GroupMembership, LocationPolicy,
+    PatchMode, ResourceTypeResolution, ScimServerBuilder, ScimServerConfig, TenantStrategy,
+    VersionExposure,
+};
+pub use computed::ComputedAttribute;
 pub use core::ScimServer;
-pub use builder::{ScimServerBuilder, ScimServerConfig, TenantStrategy};
+pub use discovery::DiscoveryDocument;
+pub use import::{DanglingReference, ImportFailure, ImportReport};
+pub use partial_patch::{PartialPatch, PatchOperationOutcome};
+pub use self_test::{SelfTestProblem, SelfTestReport};
+pub use validation_settings::ValidationSettings;
 
 #[cfg(test)]
 mod integration_tests {
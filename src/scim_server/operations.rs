@@ -4,15 +4,23 @@
 //! updating, deleting, listing, and searching resources through the
 //! registered resource providers.
 
+use super::builder::{DefaultListOrder, EntryDisplayPolicy};
 use super::core::ScimServer;
-use crate::error::ScimResult;
-use crate::providers::ResourceProvider;
-use crate::resource::{RequestContext, Resource, ScimOperation};
+use super::display_name::strip_derived_marker;
+use crate::error::{ScimError, ScimResult, ValidationError};
+use crate::multi_tenant::UnknownAttributePolicy;
+use crate::providers::{ResourceProvider, UpsertOutcome};
+use crate::resource::{ListQuery, RequestContext, Resource, ScimOperation, SortOrder};
+use crate::schema::OperationContext;
 use log::{debug, info, warn};
 use serde_json::Value;
 
 impl<P: ResourceProvider + Sync> ScimServer<P> {
     /// Generic create operation for any resource type
+    ///
+    /// Attributes omitted from `data` are populated with the defaults the
+    /// resource type's schema declares. Defaults are applied here only:
+    /// creating through the provider directly stores the data as given.
     pub async fn create_resource(
         &self,
         resource_type: &str,
@@ -26,12 +34,37 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
 
         // Check if resource type is supported
         self.ensure_operation_supported(resource_type, &ScimOperation::Create)?;
+        self.ensure_operation_permitted(context, "create")?;
+        self.ensure_operation_authorized(context, &ScimOperation::Create, resource_type, None)?;
 
         // Get the schema for validation
         let schema = self.get_schema_for_resource_type(resource_type)?;
 
-        // Validate against schema
-        self.schema_registry.validate_resource(&schema, &data)?;
+        // Populate schema-declared defaults for omitted attributes (create only)
+        let mut data = data;
+        self.normalize_attribute_names(resource_type, &schema, &mut data);
+        strip_derived_marker(&mut data);
+        self.apply_unknown_attribute_policy(resource_type, &schema, &mut data, context);
+        self.coerce_attribute_values(resource_type, &schema, &mut data)?;
+        self.apply_member_policy(&mut data)?;
+        self.check_member_count(resource_type, &data, context)?;
+        schema.apply_defaults(&mut data);
+
+        // Computed attributes are derived on read and never persisted
+        self.strip_computed_attributes(resource_type, &mut data);
+        self.run_on_save(resource_type, &mut data, context)?;
+
+        // Validate against schema and registered extensions
+        self.validate_with_extensions(
+            resource_type,
+            &schema,
+            &data,
+            OperationContext::Create,
+            context,
+        )?;
+
+        self.apply_display_name_policy(resource_type, &mut data, None);
+        self.apply_entry_display_policy(&mut data, None);
 
         // Delegate to provider
         let result = self
@@ -39,7 +72,8 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
             .create_resource(resource_type, data, context)
             .await
             .map(|versioned_resource| versioned_resource.into_resource())
-            .map_err(|e| crate::error::ScimError::ProviderError(e.to_string()));
+            .map_err(|e| self.map_provider_error(e, context))
+            .and_then(|resource| self.run_on_load(resource_type, resource, context));
 
         match &result {
             Ok(resource) => {
@@ -90,12 +124,14 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
 
         // Check if resource type is supported
         self.ensure_operation_supported(resource_type, &ScimOperation::Read)?;
+        self.ensure_operation_permitted(context, "read")?;
+        self.ensure_operation_authorized(context, &ScimOperation::Read, resource_type, Some(id))?;
 
         let result = self
             .provider
             .get_resource(resource_type, id, context)
             .await
-            .map_err(|e| crate::error::ScimError::ProviderError(e.to_string()));
+            .map_err(|e| self.map_provider_error(e, context));
 
         match &result {
             Ok(Some(_)) => {
@@ -118,7 +154,23 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
             }
         }
 
-        result.map(|opt| opt.map(|vr| vr.into_resource()))
+        let Some(versioned_resource) = result? else {
+            return Ok(None);
+        };
+        let mut resource = self.finish_read(
+            resource_type,
+            versioned_resource.into_resource(),
+            None,
+            context,
+        )?;
+        self.apply_group_membership(
+            resource_type,
+            std::slice::from_mut(&mut resource),
+            None,
+            context,
+        )
+        .await?;
+        Ok(Some(resource))
     }
 
     /// Generic update operation
@@ -134,21 +186,17 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
             resource_type, id, context.request_id
         );
 
-        // Check if resource type is supported
-        self.ensure_operation_supported(resource_type, &ScimOperation::Update)?;
-
-        // Get the schema for validation
-        let schema = self.get_schema_for_resource_type(resource_type)?;
-
-        // Validate against schema
-        self.schema_registry.validate_resource(&schema, &data)?;
+        let data = self
+            .prepare_update(resource_type, id, data, context)
+            .await?;
 
         let result = self
             .provider
             .update_resource(resource_type, id, data, None, context)
             .await
             .map(|versioned_resource| versioned_resource.into_resource())
-            .map_err(|e| crate::error::ScimError::ProviderError(e.to_string()));
+            .map_err(|e| self.map_provider_error(e, context))
+            .and_then(|resource| self.run_on_load(resource_type, resource, context));
 
         match &result {
             Ok(_) => {
@@ -168,13 +216,167 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
         result
     }
 
+    /// Check a replacement of resource `id` and bring it into the form the
+    /// provider stores: permissions, attribute normalization and coercion,
+    /// member policies, computed attributes, `on_save` hooks, schema
+    /// validation and derived display values.
+    ///
+    /// Shared by plain and conditional (`If-Match`) updates, so both enforce
+    /// the same rules.
+    pub(crate) async fn prepare_update(
+        &self,
+        resource_type: &str,
+        id: &str,
+        data: Value,
+        context: &RequestContext,
+    ) -> ScimResult<Value> {
+        // Check if resource type is supported
+        self.ensure_operation_supported(resource_type, &ScimOperation::Update)?;
+        self.ensure_operation_permitted(context, "update")?;
+        self.ensure_operation_authorized(context, &ScimOperation::Update, resource_type, Some(id))?;
+
+        // Get the schema for validation
+        let schema = self.get_schema_for_resource_type(resource_type)?;
+
+        // Computed attributes are derived on read and never persisted
+        let mut data = data;
+        self.normalize_attribute_names(resource_type, &schema, &mut data);
+        strip_derived_marker(&mut data);
+        self.apply_unknown_attribute_policy(resource_type, &schema, &mut data, context);
+        self.coerce_attribute_values(resource_type, &schema, &mut data)?;
+        self.apply_member_policy(&mut data)?;
+        self.check_member_count(resource_type, &data, context)?;
+        self.strip_computed_attributes(resource_type, &mut data);
+        self.run_on_save(resource_type, &mut data, context)?;
+
+        // Validate against schema and registered extensions
+        self.validate_with_extensions(
+            resource_type,
+            &schema,
+            &data,
+            OperationContext::Update,
+            context,
+        )?;
+
+        if (self.config.derive_display_name && resource_type == "User")
+            || self.config.entry_display != EntryDisplayPolicy::Off
+        {
+            let stored = self
+                .provider
+                .get_resource(resource_type, id, context)
+                .await
+                .map_err(|e| self.map_provider_error(e, context))?
+                .map(|versioned_resource| versioned_resource.into_resource());
+            self.apply_display_name_policy(resource_type, &mut data, stored.as_ref());
+            self.apply_entry_display_policy(&mut data, stored.as_ref());
+        }
+
+        Ok(data)
+    }
+
+    /// Apply the derived display policies to a patched resource, so a
+    /// derived value follows the attributes the patch changed. `current` is
+    /// the resource the patch was applied to.
+    pub(super) fn apply_patch_display_policies(
+        &self,
+        resource_type: &str,
+        current: &Value,
+        patched: &mut Value,
+    ) {
+        if !(self.config.derive_display_name && resource_type == "User")
+            && self.config.entry_display == EntryDisplayPolicy::Off
+        {
+            return;
+        }
+        let stored = Resource::from_json(resource_type.to_string(), current.clone()).ok();
+        self.apply_display_name_policy(resource_type, patched, stored.as_ref());
+        self.apply_entry_display_policy(patched, stored.as_ref());
+    }
+
+    /// Create a resource, or replace the one with the same `externalId`.
+    ///
+    /// The payload's `externalId` is looked up in the request's tenant. A
+    /// matching resource is replaced through
+    /// [`update_resource`](Self::update_resource), otherwise the payload is
+    /// created through [`create_resource`](Self::create_resource), so both
+    /// paths are validated and permission-checked as usual. The returned
+    /// [`UpsertOutcome`] says which of the two happened.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScimError::InvalidRequest`] if the payload has no string
+    /// `externalId`, a uniqueness conflict if several resources carry it, and
+    /// any error from the create or update.
+    pub async fn upsert_resource(
+        &self,
+        resource_type: &str,
+        data: Value,
+        context: &RequestContext,
+    ) -> ScimResult<(Resource, UpsertOutcome)> {
+        let external_id = data
+            .get("externalId")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ScimError::invalid_request("Upsert requires a string externalId"))?
+            .to_string();
+
+        let mut matches = self
+            .provider
+            .find_resources_by_attribute(resource_type, "externalId", &external_id, context)
+            .await
+            .map_err(|e| self.map_provider_error(e, context))?;
+        if matches.len() > 1 {
+            return Err(ScimError::conflict(resource_type, "externalId", None, None));
+        }
+        let existing_id = matches
+            .pop()
+            .and_then(|resource| resource.resource().get_id().map(str::to_string));
+
+        match existing_id {
+            Some(id) => {
+                debug!(
+                    "SCIM upsert of {} with externalId '{}' updates resource '{}' (request: '{}')",
+                    resource_type, external_id, id, context.request_id
+                );
+                let resource = self
+                    .update_resource(resource_type, &id, data, context)
+                    .await?;
+                Ok((resource, UpsertOutcome::Updated))
+            }
+            None => {
+                let resource = self.create_resource(resource_type, data, context).await?;
+                Ok((resource, UpsertOutcome::Created))
+            }
+        }
+    }
+
     /// Generic delete operation
+    ///
+    /// With [`idempotent_delete`](super::ScimServerConfig::idempotent_delete)
+    /// enabled, deleting a missing resource succeeds; use
+    /// [`remove_resource`](Self::remove_resource) to tell the cases apart.
     pub async fn delete_resource(
         &self,
         resource_type: &str,
         id: &str,
         context: &RequestContext,
     ) -> ScimResult<()> {
+        self.remove_resource(resource_type, id, context)
+            .await
+            .map(|_| ())
+    }
+
+    /// Delete a resource, returning whether one was actually removed.
+    ///
+    /// Returns `Ok(false)` instead of a not-found error when the resource
+    /// does not exist and
+    /// [`idempotent_delete`](super::ScimServerConfig::idempotent_delete) is
+    /// enabled.
+    pub async fn remove_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        context: &RequestContext,
+    ) -> ScimResult<bool> {
         info!(
             "SCIM delete {} operation initiated for ID '{}' (request: '{}')",
             resource_type, id, context.request_id
@@ -182,29 +384,40 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
 
         // Check if resource type is supported
         self.ensure_operation_supported(resource_type, &ScimOperation::Delete)?;
+        self.ensure_operation_permitted(context, "delete")?;
+        self.ensure_operation_authorized(context, &ScimOperation::Delete, resource_type, Some(id))?;
 
         let result = self
             .provider
             .delete_resource(resource_type, id, None, context)
             .await
-            .map_err(|e| crate::error::ScimError::ProviderError(e.to_string()));
+            .map_err(|e| self.map_provider_error(e, context));
 
-        match &result {
-            Ok(_) => {
+        match result {
+            Ok(()) => {
                 info!(
                     "SCIM delete {} operation completed successfully for ID '{}' (request: '{}')",
                     resource_type, id, context.request_id
                 );
+                Ok(true)
+            }
+            Err(ScimError::ResourceNotFound { .. } | ScimError::ResourceDeleted { .. })
+                if self.config.idempotent_delete =>
+            {
+                debug!(
+                    "SCIM delete {} found nothing to remove for ID '{}' (request: '{}')",
+                    resource_type, id, context.request_id
+                );
+                Ok(false)
             }
             Err(e) => {
                 warn!(
                     "SCIM delete {} operation failed for ID '{}': {} (request: '{}')",
                     resource_type, id, e, context.request_id
                 );
+                Err(e)
             }
         }
-
-        result
     }
 
     /// Generic list operation for any resource type
@@ -212,6 +425,21 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
         &self,
         resource_type: &str,
         context: &RequestContext,
+    ) -> ScimResult<Vec<Resource>> {
+        self.list_resources_with_query(resource_type, None, context)
+            .await
+    }
+
+    /// List resources of a type, applying filtering, sorting, pagination and
+    /// attribute projection from `query` in the provider.
+    ///
+    /// Without a `sortBy`, results are ordered by the configured
+    /// [`DefaultListOrder`].
+    pub async fn list_resources_with_query(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
     ) -> ScimResult<Vec<Resource>> {
         debug!(
             "SCIM list {} operation initiated (request: '{}')",
@@ -220,18 +448,35 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
 
         // Check if resource type is supported
         self.ensure_operation_supported(resource_type, &ScimOperation::List)?;
+        self.ensure_operation_permitted(context, "list")?;
+        self.ensure_operation_authorized(context, &ScimOperation::List, resource_type, None)?;
+        if let Some(query) = query {
+            query.validate_pagination()?;
+        }
 
+        let query = self.with_default_list_order(query);
+        let query = query
+            .as_ref()
+            .map(|query| self.with_case_exact_attributes(resource_type, query));
+        let query = query.as_ref();
         let result = self
             .provider
-            .list_resources(resource_type, None, context)
+            .list_resources(resource_type, query, context)
             .await
-            .map(|versioned_resources| {
+            .map_err(|e| self.map_provider_error(e, context))
+            .and_then(|versioned_resources| {
                 versioned_resources
                     .into_iter()
-                    .map(|vr| vr.into_resource())
-                    .collect::<Vec<_>>()
-            })
-            .map_err(|e| crate::error::ScimError::internal(format!("Provider error: {}", e)));
+                    .map(|vr| self.finish_read(resource_type, vr.into_resource(), query, context))
+                    .collect::<ScimResult<Vec<_>>>()
+            });
+        let result = match result {
+            Ok(mut resources) => self
+                .apply_group_membership(resource_type, &mut resources, query, context)
+                .await
+                .map(|()| resources),
+            Err(e) => Err(e),
+        };
 
         match &result {
             Ok(resources) => {
@@ -253,6 +498,62 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
         result
     }
 
+    /// Apply the configured default list order to a query without `sortBy`.
+    fn with_default_list_order(&self, query: Option<&ListQuery>) -> Option<ListQuery> {
+        if query.is_some_and(|query| query.sort_by.is_some()) {
+            return query.cloned();
+        }
+        match self.config.default_list_order {
+            DefaultListOrder::Id => query.cloned(),
+            DefaultListOrder::CreatedDescending => Some(
+                query
+                    .cloned()
+                    .unwrap_or_default()
+                    .with_sort_by("meta.created".to_string())
+                    .with_sort_order(SortOrder::Descending),
+            ),
+        }
+    }
+
+    /// Count the resources of a type matching `query`'s filter, ignoring
+    /// pagination, sorting and projection. This is a list's `totalResults`.
+    pub async fn count_resources(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> ScimResult<usize> {
+        self.ensure_operation_supported(resource_type, &ScimOperation::List)?;
+        self.ensure_operation_permitted(context, "list")?;
+        self.ensure_operation_authorized(context, &ScimOperation::List, resource_type, None)?;
+
+        let query = query.map(|query| self.with_case_exact_attributes(resource_type, query));
+        self.provider
+            .count_resources(resource_type, query.as_ref(), context)
+            .await
+            .map_err(|e| self.map_provider_error(e, context))
+    }
+
+    /// Ids of the resources of a type matching `query`'s filter, without
+    /// loading the resources themselves. Pagination, sorting and projection
+    /// are ignored.
+    pub async fn list_ids(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> ScimResult<Vec<String>> {
+        self.ensure_operation_supported(resource_type, &ScimOperation::List)?;
+        self.ensure_operation_permitted(context, "list")?;
+        self.ensure_operation_authorized(context, &ScimOperation::List, resource_type, None)?;
+
+        let query = query.map(|query| self.with_case_exact_attributes(resource_type, query));
+        self.provider
+            .list_ids(resource_type, query.as_ref(), context)
+            .await
+            .map_err(|e| self.map_provider_error(e, context))
+    }
+
     /// Generic search by attribute (replaces find_user_by_username)
     pub async fn find_resource_by_attribute(
         &self,
@@ -268,6 +569,8 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
 
         // Check if resource type is supported
         self.ensure_operation_supported(resource_type, &ScimOperation::Search)?;
+        self.ensure_operation_permitted(context, "read")?;
+        self.ensure_operation_authorized(context, &ScimOperation::Search, resource_type, None)?;
 
         let value_str = match value {
             serde_json::Value::String(s) => s.as_str(),
@@ -288,7 +591,12 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
                     .map(|vr| vr.into_resource())
                     .next() // Take first match for this API
             })
-            .map_err(|e| crate::error::ScimError::ProviderError(e.to_string()));
+            .map_err(|e| self.map_provider_error(e, context))
+            .and_then(|resource| {
+                resource
+                    .map(|resource| self.finish_read(resource_type, resource, None, context))
+                    .transpose()
+            });
 
         match &result {
             Ok(Some(resource)) => {
@@ -327,10 +635,12 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
             "SCIM resource exists check for {} with ID '{}' (request: '{}')",
             resource_type, id, context.request_id
         );
+        self.ensure_operation_permitted(context, "read")?;
+        self.ensure_operation_authorized(context, &ScimOperation::Read, resource_type, Some(id))?;
         self.provider
             .resource_exists(resource_type, id, context)
             .await
-            .map_err(|e| crate::error::ScimError::ProviderError(e.to_string()))
+            .map_err(|e| self.map_provider_error(e, context))
     }
 
     /// Generic patch operation for any resource type
@@ -348,31 +658,57 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
 
         // Check if resource type is supported for patch operations
         self.ensure_operation_supported(resource_type, &ScimOperation::Patch)?;
+        self.ensure_operation_permitted(context, "update")?;
+        self.ensure_operation_authorized(context, &ScimOperation::Patch, resource_type, Some(id))?;
 
         // Validate patch request structure
         let operations = patch_request
             .get("Operations")
             .and_then(|ops| ops.as_array())
             .ok_or_else(|| {
-                crate::error::ScimError::invalid_request(
-                    "PATCH request must contain Operations array".to_string(),
-                )
+                crate::error::ScimError::Json(serde::de::Error::custom(
+                    "PATCH request must contain Operations array",
+                ))
             })?;
 
         // Validate that operations array is not empty
         if operations.is_empty() {
-            return Err(crate::error::ScimError::invalid_request(
-                "Invalid Operations array: cannot be empty".to_string(),
-            ));
+            return Err(
+                ValidationError::custom("Invalid Operations array: cannot be empty").into(),
+            );
         }
+        let settings = self.validation_settings(context);
+        let patch_request = self.prepare_patch_request(resource_type, patch_request, context)?;
+
+        // Derived display values follow the patch and the on_save hook shapes
+        // the patched resource, which must then keep its required
+        // attributes, and Groups must stay within their member limit. The
+        // provider checks the resource it patches, so a concurrent write
+        // cannot slip past the check
+        let check = |current: &Value, patched: &mut Value| {
+            self.apply_patch_display_policies(resource_type, current, patched);
+            self.run_on_save(resource_type, patched, context)?;
+            if settings.enforce_required {
+                self.check_required(
+                    resource_type,
+                    OperationContext::Patch,
+                    Some(current),
+                    patched,
+                    context,
+                )?;
+            }
+            self.check_patch_member_count(resource_type, current, &patch_request, context)
+        };
 
         // Delegate to provider
         let result = self
             .provider
-            .patch_resource(resource_type, id, &patch_request, None, context)
+            .patch_resource_checked(resource_type, id, &patch_request, None, check, context)
             .await
+            .map_err(|e| self.map_provider_error(e, context))
+            .and_then(|patched| patched)
             .map(|versioned_resource| versioned_resource.into_resource())
-            .map_err(|e| crate::error::ScimError::ProviderError(e.to_string()));
+            .and_then(|resource| self.run_on_load(resource_type, resource, context));
 
         match &result {
             Ok(resource) => {
@@ -393,4 +729,73 @@ impl<P: ResourceProvider + Sync> ScimServer<P> {
 
         result
     }
+
+    /// Check the targets of a PATCH request's operations and shape their
+    /// values for the provider: unknown targets are pruned if the tenant
+    /// ignores them, computed attributes are stripped, values are coerced to
+    /// their schema types and the duplicate member policy is applied.
+    pub(super) fn prepare_patch_request(
+        &self,
+        resource_type: &str,
+        patch_request: &Value,
+        context: &RequestContext,
+    ) -> ScimResult<Value> {
+        let schema = self.get_schema_for_resource_type(resource_type)?;
+        let mut patch_request = patch_request.clone();
+        if self.validation_settings(context).unknown_attributes == UnknownAttributePolicy::Ignore {
+            self.prune_unknown_patch_targets(&schema, &mut patch_request);
+        }
+        let operations = patch_request["Operations"].as_array();
+        self.check_patch_targets(&schema, operations.map(Vec::as_slice).unwrap_or_default())?;
+
+        let mut patch_request = self.strip_computed_from_patch(resource_type, &patch_request)?;
+        self.coerce_patch_values(resource_type, &schema, &mut patch_request)?;
+        self.apply_member_policy_to_patch(&mut patch_request)?;
+        Ok(patch_request)
+    }
+
+    /// Run the resource type's `on_load` hook on a resource returned by the provider.
+    pub(crate) fn run_on_load(
+        &self,
+        resource_type: &str,
+        resource: Resource,
+        context: &RequestContext,
+    ) -> ScimResult<Resource> {
+        let handler = self.get_handler(resource_type)?;
+        let Some(on_load) = &handler.on_load else {
+            return Ok(resource);
+        };
+
+        let mut data = resource.to_json()?;
+        on_load(&mut data, context)?;
+        Ok(Resource::from_json(resource_type.to_string(), data)?)
+    }
+
+    /// Run the resource type's `on_save` hook on a payload about to be validated and stored.
+    pub(crate) fn run_on_save(
+        &self,
+        resource_type: &str,
+        data: &mut Value,
+        context: &RequestContext,
+    ) -> ScimResult<()> {
+        let handler = self.get_handler(resource_type)?;
+        match &handler.on_save {
+            Some(on_save) => Ok(on_save(data, context)?),
+            None => Ok(()),
+        }
+    }
+
+    /// Prepare a resource read from the provider for the caller: the
+    /// `on_load` hook runs first, then computed attributes are added.
+    fn finish_read(
+        &self,
+        resource_type: &str,
+        resource: Resource,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> ScimResult<Resource> {
+        let mut resource = self.run_on_load(resource_type, resource, context)?;
+        self.apply_computed_attributes(resource_type, &mut resource, query, context);
+        Ok(resource)
+    }
 }
@@ -0,0 +1,51 @@
+//! Page size caps for list responses.
+//!
+//! A list page can be capped globally with
+//! [`ScimServerBuilder::with_max_page_size`](super::ScimServerBuilder::with_max_page_size)
+//! and per resource type, for types whose resources are expensive to return,
+//! with
+//! [`with_resource_type_max_page_size`](super::ScimServerBuilder::with_resource_type_max_page_size)
+//! or a tenant's
+//! [`resource_type_max_results`](crate::multi_tenant::ScimSearchConfig::resource_type_max_results).
+//!
+//! The most specific cap applies: the tenant's cap for the resource type,
+//! then the server's cap for the resource type, then the global cap. A more
+//! specific cap replaces a less specific one rather than being bounded by
+//! it, so a tenant can allow larger pages for one type than the global cap.
+//! Requests asking for more than the cap, or not setting `count`, get a page
+//! of at most the cap.
+//!
+//! The ServiceProviderConfig reports the global cap as `filter.maxResults`.
+//! ResourceType definitions report the cap applying to them as `maxResults`
+//! when enabled with
+//! [`with_resource_type_page_size_discovery`](super::ScimServerBuilder::with_resource_type_page_size_discovery).
+
+use super::core::ScimServer;
+use crate::providers::ResourceProvider;
+use crate::resource::RequestContext;
+
+impl<P: ResourceProvider> ScimServer<P> {
+    /// The maximum page size for listing `resource_type` in the request's
+    /// tenant, or `None` when pages are uncapped.
+    pub fn max_page_size(&self, resource_type: &str, context: &RequestContext) -> Option<usize> {
+        let tenant_cap = context
+            .tenant_id()
+            .and_then(|tenant_id| self.tenant_configuration(tenant_id))
+            .and_then(|configuration| {
+                configuration
+                    .search_config
+                    .resource_type_max_results
+                    .get(resource_type)
+            })
+            .map(|max| *max as usize);
+
+        tenant_cap
+            .or_else(|| {
+                self.config
+                    .resource_type_max_page_sizes
+                    .get(resource_type)
+                    .copied()
+            })
+            .or(self.config.max_page_size)
+    }
+}
@@ -0,0 +1,224 @@
+//! Opt-in per-operation PATCH.
+//!
+//! RFC 7644 §3.5.2 makes a PATCH request atomic: if any operation fails, none
+//! is applied. That stays the default. With
+//! [`PatchMode::PerOperation`](super::PatchMode::PerOperation) an operation
+//! that fails is skipped instead and the remaining operations still run.
+//!
+//! Each operation's target is checked and its value shaped as in an atomic
+//! PATCH of that single operation. The operations are then applied in order
+//! to the stored resource within a single provider patch, so each sees the
+//! effects of the operations applied before it and no other write can come
+//! in between. The `on_save` hook and the required attribute check run once,
+//! on the result, and it is stored as one write; if they reject it, nothing
+//! is stored and the request fails.
+//!
+//! The result is a [`PartialPatch`]: the resource after every successful
+//! operation, and one [`PatchOperationOutcome`] per operation in request
+//! order. When no operation succeeds the request fails with the first
+//! operation's error, exactly as an atomic PATCH would.
+
+use super::core::ScimServer;
+use crate::error::{ScimError, ScimResult, ValidationError};
+use crate::providers::ResourceProvider;
+use crate::providers::helpers::patch::apply_patch_request;
+use crate::resource::{RequestContext, Resource, ScimOperation};
+use crate::schema::OperationContext;
+use serde_json::{Value, json};
+use std::sync::Mutex;
+
+/// Result of a PATCH applied operation by operation.
+#[derive(Debug)]
+pub struct PartialPatch {
+    /// The resource after every operation that succeeded
+    pub resource: Resource,
+    /// Outcome of each operation, in request order
+    pub outcomes: Vec<PatchOperationOutcome>,
+}
+
+impl PartialPatch {
+    /// Whether every operation was applied.
+    pub fn fully_applied(&self) -> bool {
+        self.outcomes.iter().all(PatchOperationOutcome::is_applied)
+    }
+}
+
+/// Outcome of one operation of a per-operation PATCH.
+#[derive(Debug)]
+pub struct PatchOperationOutcome {
+    /// Position of the operation in the `Operations` array
+    pub index: usize,
+    /// Why the operation was not applied, or `None` if it was
+    pub error: Option<ScimError>,
+}
+
+impl PatchOperationOutcome {
+    /// Whether the operation was applied.
+    pub fn is_applied(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The outcome as reported to clients: its index and status, plus the
+    /// `detail`, `status` code and `scimType` of the error for a failed
+    /// operation.
+    pub fn to_json(&self) -> Value {
+        match &self.error {
+            None => json!({ "index": self.index, "status": "applied" }),
+            Some(error) => {
+                let mut outcome = json!({
+                    "index": self.index,
+                    "status": "failed",
+                    "detail": error.to_string(),
+                    "statusCode": error.status_code(),
+                });
+                if let Some(scim_type) = error.scim_type() {
+                    outcome["scimType"] = Value::String(scim_type.as_str().to_string());
+                }
+                outcome
+            }
+        }
+    }
+}
+
+impl<P: ResourceProvider + Sync> ScimServer<P> {
+    /// Apply a PATCH request operation by operation, keeping those that
+    /// succeed.
+    ///
+    /// The operations are applied in order as one write, skipping those that
+    /// fail, so each sees the effects of the operations before it. Fails with
+    /// the first operation's error when no operation could be applied.
+    pub async fn patch_resource_per_operation(
+        &self,
+        resource_type: &str,
+        id: &str,
+        patch_request: &Value,
+        context: &RequestContext,
+    ) -> ScimResult<PartialPatch> {
+        self.ensure_operation_supported(resource_type, &ScimOperation::Patch)?;
+        self.ensure_operation_permitted(context, "update")?;
+        self.ensure_operation_authorized(context, &ScimOperation::Patch, resource_type, Some(id))?;
+
+        let operations = patch_request
+            .get("Operations")
+            .and_then(|ops| ops.as_array())
+            .ok_or_else(|| {
+                ScimError::Json(serde::de::Error::custom(
+                    "PATCH request must contain Operations array",
+                ))
+            })?;
+        if operations.is_empty() {
+            return Err(
+                ValidationError::custom("Invalid Operations array: cannot be empty").into(),
+            );
+        }
+
+        // Each operation is prepared as a PATCH of its own; one that cannot
+        // be is failed before anything is applied
+        let schemas = patch_request.get("schemas").cloned().unwrap_or(Value::Null);
+        let mut errors: Vec<Option<ScimError>> = Vec::with_capacity(operations.len());
+        let mut prepared = Vec::with_capacity(operations.len());
+        for (index, operation) in operations.iter().enumerate() {
+            let single = json!({ "schemas": schemas, "Operations": [operation] });
+            match self.prepare_patch_request(resource_type, &single, context) {
+                Ok(single) => {
+                    prepared.push((index, single));
+                    errors.push(None);
+                }
+                Err(error) => errors.push(Some(unwrap_rejection(error))),
+            }
+        }
+        if prepared.is_empty() {
+            return Err(first_error(errors));
+        }
+
+        // The operations are applied by the check, so the provider patches
+        // with none of its own and stores what the check leaves
+        let settings = self.validation_settings(context);
+        let failures = Mutex::new(Vec::new());
+        let check = |current: &Value, patched: &mut Value| {
+            let mut failed = Vec::new();
+            for (index, single) in &prepared {
+                let mut candidate = patched.clone();
+                let applied = apply_patch_request(&mut candidate, single)
+                    .map_err(ScimError::invalid_request)
+                    .and_then(|()| {
+                        self.check_patch_member_count(resource_type, patched, single, context)
+                    });
+                match applied {
+                    Ok(()) => *patched = candidate,
+                    Err(error) => failed.push((*index, error)),
+                }
+            }
+            let none_applied = failed.len() == prepared.len();
+            // The provider may run the check again, e.g. after a retry
+            *failures
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()) = failed;
+            if none_applied {
+                return Err(None);
+            }
+
+            self.apply_patch_display_policies(resource_type, current, patched);
+            self.run_on_save(resource_type, patched, context)
+                .map_err(Some)?;
+            if settings.enforce_required {
+                self.check_required(
+                    resource_type,
+                    OperationContext::Patch,
+                    Some(current),
+                    patched,
+                    context,
+                )
+                .map_err(Some)?;
+            }
+            Ok(())
+        };
+
+        let no_operations = json!({ "schemas": schemas, "Operations": [] });
+        let result = self
+            .provider
+            .patch_resource_checked(resource_type, id, &no_operations, None, check, context)
+            .await
+            .map_err(|e| self.map_provider_error(e, context))?;
+        for (index, error) in failures
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+        {
+            errors[index] = Some(error);
+        }
+
+        let resource = match result {
+            Ok(versioned_resource) => {
+                self.run_on_load(resource_type, versioned_resource.into_resource(), context)?
+            }
+            Err(Some(error)) => return Err(error),
+            Err(None) => return Err(first_error(errors)),
+        };
+        let outcomes = errors
+            .into_iter()
+            .enumerate()
+            .map(|(index, error)| PatchOperationOutcome { index, error })
+            .collect();
+        Ok(PartialPatch { resource, outcomes })
+    }
+}
+
+/// The error of the first operation that failed.
+fn first_error(errors: Vec<Option<ScimError>>) -> ScimError {
+    errors
+        .into_iter()
+        .flatten()
+        .next()
+        .expect("every operation failed")
+}
+
+/// Drop the `Operations[0]` wrapper a single-operation PATCH puts around an
+/// up-front rejection; the outcome already carries the operation's index.
+fn unwrap_rejection(error: ScimError) -> ScimError {
+    match error {
+        ScimError::Validation(ValidationError::PatchOperationRejected { error, .. }) => {
+            ScimError::Validation(*error)
+        }
+        error => error,
+    }
+}
@@ -0,0 +1,163 @@
+//! Up-front validation of the attributes PATCH operations target.
+//!
+//! Before a PATCH reaches the provider, every operation's target is resolved
+//! against the resource type's schema, so providers see the same rejections
+//! regardless of how they apply patches. A path naming no attribute is
+//! rejected with [`ValidationError::UnknownPatchPath`] (`invalidPath`), and
+//! a path naming a read-only attribute with
+//! [`ValidationError::ReadOnlyMutabilityViolation`] (`mutability`). Path-less
+//! operations are checked attribute by attribute against their value.
+//!
+//! The first rejected operation is reported as a
+//! [`ValidationError::PatchOperationRejected`] carrying its index in the
+//! `Operations` array. Nothing is applied unless every operation passes, and
+//! the provider then applies the whole sequence as one write.
+//!
+//! Tenants that ignore unknown attributes (see
+//! [`validation_settings`](super::validation_settings)) have unknown targets
+//! pruned from the request instead: operations whose path names no attribute
+//! are dropped, as are unknown attributes of path-less values.
+
+use super::core::ScimServer;
+use crate::error::{ScimResult, ValidationError, ValidationResult};
+use crate::providers::ResourceProvider;
+use crate::providers::helpers::patch::is_readonly_attribute;
+use crate::schema::{Mutability, Schema};
+use serde_json::Value;
+
+impl<P: ResourceProvider> ScimServer<P> {
+    /// Reject the first PATCH operation whose target is unknown or read-only.
+    pub(super) fn check_patch_targets(
+        &self,
+        schema: &Schema,
+        operations: &[Value],
+    ) -> ScimResult<()> {
+        for (index, operation) in operations.iter().enumerate() {
+            self.check_patch_operation(schema, operation)
+                .map_err(|error| ValidationError::PatchOperationRejected {
+                    index,
+                    error: Box::new(error),
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Remove operations and path-less value attributes that target no
+    /// attribute of the schema.
+    pub(super) fn prune_unknown_patch_targets(&self, schema: &Schema, patch_request: &mut Value) {
+        let is_unknown = |path: &str| {
+            matches!(
+                self.check_patch_path(schema, path),
+                Err(ValidationError::UnknownPatchPath { .. })
+            )
+        };
+        let Some(operations) = patch_request
+            .get_mut("Operations")
+            .and_then(Value::as_array_mut)
+        else {
+            return;
+        };
+
+        operations.retain(|operation| {
+            operation
+                .get("path")
+                .and_then(Value::as_str)
+                .is_none_or(|path| !is_unknown(path))
+        });
+        for operation in operations.iter_mut() {
+            if operation.get("path").is_some() {
+                continue;
+            }
+            let Some(value) = operation.get_mut("value").and_then(Value::as_object_mut) else {
+                continue;
+            };
+            value.retain(|name, attribute_value| {
+                if name == "schemas" {
+                    return true;
+                }
+                match self
+                    .schema_registry
+                    .resolve_attribute_path(&schema.id, name)
+                {
+                    Some((extension, "")) if extension.id != schema.id => {
+                        if let Some(attributes) = attribute_value.as_object_mut() {
+                            attributes.retain(|attribute, _| {
+                                !is_unknown(&format!("{}:{}", extension.id, attribute))
+                            });
+                        }
+                        true
+                    }
+                    _ => !is_unknown(name),
+                }
+            });
+        }
+    }
+
+    fn check_patch_operation(&self, schema: &Schema, operation: &Value) -> ValidationResult<()> {
+        if let Some(path) = operation.get("path").and_then(Value::as_str) {
+            return self.check_patch_path(schema, path);
+        }
+
+        // Without a path, each attribute of the value is a target
+        let Some(value) = operation.get("value").and_then(Value::as_object) else {
+            return Ok(());
+        };
+        for (name, attribute_value) in value {
+            if name == "schemas" {
+                continue;
+            }
+            match self
+                .schema_registry
+                .resolve_attribute_path(&schema.id, name)
+            {
+                // An extension URN carries that extension's attributes
+                Some((extension, "")) if extension.id != schema.id => {
+                    for attribute in attribute_value
+                        .as_object()
+                        .into_iter()
+                        .flat_map(|v| v.keys())
+                    {
+                        let path = format!("{}:{}", extension.id, attribute);
+                        self.check_patch_path(schema, &path)?;
+                    }
+                }
+                _ => self.check_patch_path(schema, name)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Check one attribute path against the schema.
+    fn check_patch_path(&self, schema: &Schema, path: &str) -> ValidationResult<()> {
+        let read_only = || ValidationError::ReadOnlyMutabilityViolation {
+            attribute: path.to_string(),
+        };
+        let unknown = || ValidationError::UnknownPatchPath {
+            path: path.to_string(),
+        };
+        if is_readonly_attribute(path) {
+            return Err(read_only());
+        }
+
+        // Qualified paths may name another registered schema
+        let unfiltered = path.split('[').next().unwrap_or(path);
+        let definition = if unfiltered.contains(':') {
+            let (target, attribute) = self
+                .schema_registry
+                .resolve_attribute_path(&schema.id, path)
+                .ok_or_else(unknown)?;
+            if attribute.is_empty() {
+                return Ok(());
+            }
+            target.attribute_definition(attribute)
+        } else {
+            schema.attribute_definition(path)
+        }
+        .ok_or_else(unknown)?;
+
+        if definition.mutability == Mutability::ReadOnly {
+            return Err(read_only());
+        }
+        Ok(())
+    }
+}
@@ -3,10 +3,11 @@
 //! This module handles the registration of resource types with their handlers
 //! and supported operations, as well as validation of operation support.
 
+use super::builder::endpoint_name;
 use super::core::ScimServer;
 use crate::error::{ScimError, ScimResult};
-use crate::providers::ResourceProvider;
-use crate::resource::{ResourceHandler, ScimOperation};
+use crate::providers::{ProviderError, ResourceProvider};
+use crate::resource::{RequestContext, ResourceHandler, ScimOperation};
 use crate::schema::Schema;
 use std::sync::Arc;
 
@@ -18,6 +19,20 @@ impl<P: ResourceProvider> ScimServer<P> {
         handler: ResourceHandler,
         operations: Vec<ScimOperation>,
     ) -> Result<(), ScimError> {
+        // Names this type answers to must not already resolve to another type
+        let aliases = self.config.resource_type_resolution.aliases(resource_type);
+        if let Some((alias, existing)) = aliases.iter().find_map(|alias| {
+            self.resource_type_aliases
+                .get(alias)
+                .filter(|existing| *existing != resource_type)
+                .map(|existing| (alias, existing))
+        }) {
+            return Err(ScimError::internal(format!(
+                "Resource type '{}' is ambiguous with '{}': both resolve from '{}'",
+                resource_type, existing, alias
+            )));
+        }
+
         // Register the schema
         self.schema_registry
             .add_schema(handler.schema.clone())
@@ -31,9 +46,133 @@ impl<P: ResourceProvider> ScimServer<P> {
         self.supported_operations
             .insert(resource_type.to_string(), operations);
 
+        for alias in aliases {
+            self.resource_type_aliases
+                .insert(alias, resource_type.to_string());
+        }
+        self.discovery_cache.invalidate();
+
         Ok(())
     }
 
+    /// Stop serving a registered resource type.
+    ///
+    /// Removes the type's handler and supported operations together with its
+    /// endpoint, aliases, computed attributes and schema extensions, so
+    /// requests for it fail with [`ScimError::UnsupportedResourceType`] and it
+    /// disappears from `/ResourceTypes`. Its schema stays in the schema
+    /// registry. Stored resources of the type are left untouched in the
+    /// provider and are served again if the type is registered anew.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScimError::UnsupportedResourceType`] if the resource type is
+    /// not registered.
+    pub fn unregister_resource_type(&mut self, resource_type: &str) -> Result<(), ScimError> {
+        if self.resource_handlers.remove(resource_type).is_none() {
+            return Err(ScimError::UnsupportedResourceType(
+                resource_type.to_string(),
+            ));
+        }
+        self.supported_operations.remove(resource_type);
+        self.resource_endpoints.remove(resource_type);
+        self.computed_attributes.remove(resource_type);
+        self.schema_extensions.remove(resource_type);
+        self.resource_type_aliases
+            .retain(|_, registered| registered != resource_type);
+        self.discovery_cache.invalidate();
+
+        Ok(())
+    }
+
+    /// Resolve a resource type name from a request to the registered name.
+    ///
+    /// Uses the configured [`ResourceTypeResolution`](super::ResourceTypeResolution),
+    /// so with lenient matching `users` and `/Users/` both resolve to `User`.
+    /// Returns `None` if no registered type matches.
+    pub fn resolve_resource_type(&self, name: &str) -> Option<&str> {
+        if let Some((registered, _)) = self.resource_handlers.get_key_value(name) {
+            return Some(registered);
+        }
+        let normalized = self.config.resource_type_resolution.normalize(name);
+        self.resource_type_aliases
+            .get(&normalized)
+            .map(String::as_str)
+    }
+
+    /// Serve a registered resource type from a custom endpoint, e.g.
+    /// `/People` for `Person`.
+    ///
+    /// The endpoint is used for discovery, `meta.location` and `$ref` URLs,
+    /// and requests naming it resolve to the resource type. Setting an
+    /// endpoint again replaces the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScimError::UnsupportedResourceType`] if the resource type is
+    /// not registered, or an internal error if the endpoint already resolves
+    /// to another resource type.
+    pub fn set_resource_endpoint(
+        &mut self,
+        resource_type: &str,
+        endpoint: &str,
+    ) -> Result<(), ScimError> {
+        self.get_handler(resource_type)?;
+
+        let endpoint = endpoint.trim_matches('/');
+        if endpoint.is_empty() {
+            return Err(ScimError::internal(format!(
+                "Resource type '{}' cannot be served from an empty endpoint",
+                resource_type
+            )));
+        }
+
+        let resolution = &self.config.resource_type_resolution;
+        let alias = resolution.normalize(endpoint);
+        let served_elsewhere = self.resource_handlers.keys().find(|existing| {
+            *existing != resource_type
+                && resolution.normalize(&self.resource_endpoint(existing)) == alias
+        });
+        if let Some(existing) = self
+            .resource_type_aliases
+            .get(&alias)
+            .filter(|existing| *existing != resource_type)
+            .or(served_elsewhere)
+        {
+            return Err(ScimError::internal(format!(
+                "Endpoint '{}' for resource type '{}' already resolves to '{}'",
+                endpoint, resource_type, existing
+            )));
+        }
+
+        if let Some(previous) = self
+            .resource_endpoints
+            .insert(resource_type.to_string(), endpoint.to_string())
+        {
+            let previous = resolution.normalize(&previous);
+            if !resolution.aliases(resource_type).contains(&previous) {
+                self.resource_type_aliases.remove(&previous);
+            }
+        }
+        self.resource_type_aliases
+            .insert(alias, resource_type.to_string());
+        self.discovery_cache.invalidate();
+
+        Ok(())
+    }
+
+    /// Endpoint a resource type is served from, without slashes.
+    ///
+    /// This is the endpoint set with
+    /// [`set_resource_endpoint`](Self::set_resource_endpoint), or else the
+    /// pluralized type name, e.g. `Users` for `User`.
+    pub fn resource_endpoint(&self, resource_type: &str) -> String {
+        self.resource_endpoints
+            .get(resource_type)
+            .cloned()
+            .unwrap_or_else(|| endpoint_name(resource_type))
+    }
+
     /// Get all registered resource types
     pub fn get_supported_resource_types(&self) -> Vec<&str> {
         self.resource_handlers.keys().map(|s| s.as_str()).collect()
@@ -44,6 +183,18 @@ impl<P: ResourceProvider> ScimServer<P> {
         self.supported_operations.get(resource_type)
     }
 
+    /// Operations supported by a resource type, exactly as passed to
+    /// [`register_resource_type`](Self::register_resource_type).
+    ///
+    /// Returns `None` if the resource type is not registered. Operations
+    /// missing from the list are rejected with
+    /// [`ScimError::UnsupportedOperation`] (HTTP 501).
+    pub fn supported_operations(&self, resource_type: &str) -> Option<&[ScimOperation]> {
+        self.supported_operations
+            .get(resource_type)
+            .map(Vec::as_slice)
+    }
+
     /// Helper method to ensure operation is supported for a resource type
     pub(super) fn ensure_operation_supported(
         &self,
@@ -51,8 +202,7 @@ impl<P: ResourceProvider> ScimServer<P> {
         operation: &ScimOperation,
     ) -> ScimResult<()> {
         let operations = self
-            .supported_operations
-            .get(resource_type)
+            .supported_operations(resource_type)
             .ok_or_else(|| ScimError::UnsupportedResourceType(resource_type.to_string()))?;
 
         if !operations.contains(operation) {
@@ -65,6 +215,96 @@ impl<P: ResourceProvider> ScimServer<P> {
         Ok(())
     }
 
+    /// Helper method to ensure the request's tenant is permitted to perform an operation.
+    ///
+    /// Single-tenant requests (no tenant context) are always permitted.
+    pub(crate) fn ensure_operation_permitted(
+        &self,
+        context: &RequestContext,
+        operation: &str,
+    ) -> ScimResult<()> {
+        if context.can_perform_operation(operation) {
+            Ok(())
+        } else {
+            Err(ScimError::permission_denied(
+                operation,
+                context
+                    .tenant_id()
+                    .unwrap_or(&self.config.default_tenant_id),
+            ))
+        }
+    }
+
+    /// Helper method to convert a provider error into a [`ScimError`].
+    ///
+    /// Uniqueness conflicts reported as [`ProviderError::DuplicateAttribute`]
+    /// become [`ScimError::Conflict`], carrying the existing resource's id and
+    /// location only when it belongs to the requesting tenant. Missing
+    /// resources become [`ScimError::ResourceNotFound`], soft-deleted ones
+    /// [`ScimError::ResourceDeleted`] and tenant permission failures
+    /// [`ScimError::PermissionDenied`]. Storage conflicts and outages become
+    /// [`ScimError::StorageConflict`] and [`ScimError::Unavailable`], filters
+    /// the provider could not parse become [`ScimError::InvalidFilter`] and
+    /// input it rejected becomes [`ScimError::InvalidRequest`]. Other errors
+    /// are wrapped as [`ScimError::ProviderError`].
+    pub(crate) fn map_provider_error(
+        &self,
+        error: P::Error,
+        context: &RequestContext,
+    ) -> ScimError {
+        let provider_error = (&error as &dyn std::error::Error).downcast_ref::<ProviderError>();
+        match provider_error {
+            Some(
+                ProviderError::NotFound { resource_type, id }
+                | ProviderError::ResourceNotFound {
+                    resource_type, id, ..
+                },
+            ) => ScimError::resource_not_found(resource_type, id),
+            Some(ProviderError::ResourceDeleted { resource_type, id }) => {
+                ScimError::resource_deleted(resource_type, id)
+            }
+            Some(ProviderError::PermissionDenied {
+                operation,
+                tenant_id,
+            }) => ScimError::permission_denied(operation, tenant_id),
+            Some(ProviderError::CapacityExceeded { message, limit }) => {
+                ScimError::capacity_exceeded(message, *limit)
+            }
+            Some(ProviderError::QueryError { message }) => ScimError::invalid_filter(message),
+            Some(
+                ProviderError::InvalidInput { message } | ProviderError::InvalidData { message },
+            ) => ScimError::invalid_request(message),
+            Some(ProviderError::Conflict { message }) => ScimError::storage_conflict(message),
+            Some(ProviderError::Unavailable {
+                message,
+                retry_after,
+            }) => ScimError::unavailable(message, *retry_after),
+            Some(ProviderError::DuplicateAttribute {
+                resource_type,
+                attribute,
+                tenant_id,
+                existing_id,
+                ..
+            }) => {
+                let id = existing_id.clone().filter(|_| {
+                    tenant_id
+                        == context
+                            .tenant_id()
+                            .unwrap_or(&self.config.default_tenant_id)
+                });
+                let endpoint = self.resource_endpoint(resource_type);
+                let location = id.as_deref().and_then(|id| {
+                    self.config
+                        .resource_location(context.tenant_id(), &endpoint, id)
+                        .ok()
+                        .flatten()
+                });
+                ScimError::conflict(resource_type, attribute, id, location)
+            }
+            _ => ScimError::ProviderError(error.to_string()),
+        }
+    }
+
     /// Helper method to get handler for a resource type
     pub(super) fn get_handler(&self, resource_type: &str) -> ScimResult<Arc<ResourceHandler>> {
         self.resource_handlers
@@ -0,0 +1,128 @@
+//! Required attribute enforcement per operation.
+//!
+//! Attributes are required by the schema or by the tenant's
+//! [`additional_required`](crate::multi_tenant::ScimSchemaConfig::additional_required)
+//! configuration, and how they are enforced depends on the
+//! [`OperationContext`]: create and replace payloads are validated as a whole
+//! and must carry every required attribute with a non-empty value.
+//!
+//! A PATCH only describes changes, so it is not required to repeat required
+//! attributes; but removing the last value of one would otherwise go
+//! unnoticed. The provider runs the check on the patched resource before
+//! storing it, through
+//! [`patch_resource_checked`](crate::providers::ResourceProvider::patch_resource_checked),
+//! and the patch is rejected if it empties a required attribute.
+//!
+//! Schema extensions the tenant requires are enforced the same way.
+//!
+//! Only attributes the patch itself empties are rejected, so patches to
+//! resources that already lack a newly required attribute still apply.
+//! Operations targeting unknown or server-managed attributes are rejected
+//! before the patch is applied (see [`patch_targets`](super::patch_targets)).
+
+use super::core::ScimServer;
+use crate::error::{ScimError, ScimResult, ValidationError};
+use crate::providers::ResourceProvider;
+use crate::resource::RequestContext;
+use crate::schema::OperationContext;
+use serde_json::Value;
+
+impl<P: ResourceProvider> ScimServer<P> {
+    /// Top-level attributes a resource must keep non-empty in the request's
+    /// tenant: those the schema marks required plus the tenant's additional
+    /// required attributes that the schema defines.
+    pub(super) fn required_attributes(
+        &self,
+        resource_type: &str,
+        context: &RequestContext,
+    ) -> ScimResult<Vec<String>> {
+        let schema = self.get_schema_for_resource_type(resource_type)?;
+        let additional = context
+            .tenant_id()
+            .and_then(|tenant_id| self.tenant_configuration(tenant_id))
+            .map(|configuration| configuration.schema_config.additional_required.as_slice())
+            .unwrap_or_default();
+
+        Ok(schema
+            .attributes
+            .iter()
+            .filter(|attr| {
+                attr.required
+                    || additional
+                        .iter()
+                        .any(|name| name.eq_ignore_ascii_case(&attr.name))
+            })
+            .map(|attr| attr.name.clone())
+            .collect())
+    }
+
+    /// Reject a resource lacking a required attribute or extension.
+    ///
+    /// For create and replace `data` is the payload, which must carry all of
+    /// them. For PATCH `data` is the patched resource and `current` the stored
+    /// one, and only attributes and extensions present in `current` are
+    /// required.
+    pub(super) fn check_required(
+        &self,
+        resource_type: &str,
+        operation: OperationContext,
+        current: Option<&Value>,
+        data: &Value,
+        context: &RequestContext,
+    ) -> ScimResult<()> {
+        let required = self.required_attributes(resource_type, context)?;
+        let requires_extension = self
+            .tenant_schema_extensions(resource_type, context)
+            .iter()
+            .any(|(_, required)| *required);
+        if required.is_empty() && !requires_extension {
+            return Ok(());
+        }
+
+        let patched_from = current.filter(|_| operation == OperationContext::Patch);
+
+        // Only extensions the patch removes are rejected, as for attributes
+        if requires_extension
+            && patched_from.is_none_or(|current| {
+                self.check_required_extensions(resource_type, current, context)
+                    .is_ok()
+            })
+        {
+            self.check_required_extensions(resource_type, data, context)?;
+        }
+
+        for attribute in required {
+            if patched_from.is_some_and(|current| is_empty(current.get(&attribute))) {
+                continue;
+            }
+            match data.get(&attribute) {
+                None | Some(Value::Null) => {
+                    return Err(ScimError::Validation(
+                        ValidationError::MissingRequiredAttribute { attribute },
+                    ));
+                }
+                value if is_empty(value) => {
+                    return Err(ScimError::Validation(
+                        ValidationError::RequiredCharacteristicViolation {
+                            attribute,
+                            characteristic: "required".to_string(),
+                        },
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_empty(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => true,
+        Some(Value::String(s)) => s.is_empty(),
+        Some(Value::Array(items)) => items.is_empty(),
+        Some(Value::Object(fields)) => fields.values().all(|v| is_empty(Some(v))),
+        Some(_) => false,
+    }
+}
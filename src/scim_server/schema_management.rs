@@ -6,7 +6,10 @@
 use super::core::ScimServer;
 use crate::error::ScimResult;
 use crate::providers::ResourceProvider;
+use crate::resource::serialization::strip_never_returned;
+use crate::resource::{ListQuery, RequestContext};
 use crate::schema::Schema;
+use serde_json::{Value, json};
 
 impl<P: ResourceProvider> ScimServer<P> {
     /// Get schema for any registered resource type
@@ -23,8 +26,202 @@ impl<P: ResourceProvider> ScimServer<P> {
             .collect()
     }
 
+    /// Remove `writeOnly` and `returned: never` attributes, such as a User's
+    /// `password`, from a serialized resource of a registered type, including
+    /// those of its schema extensions.
+    pub(super) fn strip_never_returned_attributes(&self, resource_type: &str, json: &mut Value) {
+        if let Some(handler) = self.resource_handlers.get(resource_type) {
+            strip_never_returned(json, &handler.schema.attributes);
+        }
+        for extension in self.get_schema_extensions(resource_type) {
+            if let Some(data) = json.get_mut(&extension.id) {
+                strip_never_returned(data, &extension.attributes);
+            }
+        }
+    }
+
     /// Get schema from schema registry by ID
     pub fn get_schema_by_id(&self, schema_id: &str) -> Option<&Schema> {
         self.schema_registry.get_schema(schema_id)
     }
+
+    /// ResourceType definition (RFC 7643 Section 6) for a registered
+    /// resource type, advertising the endpoint it is served from and the
+    /// schema extensions available to the request's tenant, and optionally
+    /// its page size cap.
+    pub fn get_resource_type_definition(
+        &self,
+        resource_type: &str,
+        context: &RequestContext,
+    ) -> ScimResult<Value> {
+        let handler = self.get_handler(resource_type)?;
+        let extensions: Vec<Value> = self
+            .tenant_schema_extensions(resource_type, context)
+            .into_iter()
+            .map(|(extension, required)| json!({"schema": extension.id, "required": required}))
+            .collect();
+
+        let mut definition = json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:ResourceType"],
+            "id": resource_type,
+            "name": resource_type,
+            "endpoint": format!("/{}", self.resource_endpoint(resource_type)),
+            "schema": handler.schema.id,
+            "meta": {
+                "resourceType": "ResourceType",
+                "scimVersion": self.config.protocol_version,
+            },
+        });
+        if !handler.schema.description.is_empty() {
+            definition["description"] = Value::String(handler.schema.description.clone());
+        }
+        if !extensions.is_empty() {
+            definition["schemaExtensions"] = Value::Array(extensions);
+        }
+        if self.config.advertise_resource_type_page_sizes
+            && let Some(max_page_size) = self.max_page_size(resource_type, context)
+        {
+            definition["maxResults"] = json!(max_page_size);
+        }
+        Ok(definition)
+    }
+
+    /// ResourceType definitions for every registered resource type, sorted
+    /// by name.
+    pub fn get_resource_type_definitions(&self, context: &RequestContext) -> Vec<Value> {
+        let mut resource_types = self.get_supported_resource_types();
+        resource_types.sort_unstable();
+        resource_types
+            .into_iter()
+            .filter_map(|resource_type| {
+                self.get_resource_type_definition(resource_type, context)
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Attribute paths of a resource type's schema and registered extensions
+    /// whose values are `caseExact`, e.g. `externalId` or `groups.value`.
+    pub fn case_exact_attributes(&self, resource_type: &str) -> Vec<String> {
+        self.list_registered_schemas_for(resource_type, None)
+            .into_iter()
+            .flat_map(|schema| schema.case_exact_attributes())
+            .collect()
+    }
+
+    /// A copy of `query` whose filter and sort honour the resource type's
+    /// `caseExact` attributes.
+    pub(super) fn with_case_exact_attributes(
+        &self,
+        resource_type: &str,
+        query: &ListQuery,
+    ) -> ListQuery {
+        let mut query = query.clone();
+        if query.filter.is_some() || query.sort_by.is_some() {
+            query
+                .case_exact_attributes
+                .extend(self.case_exact_attributes(resource_type));
+        }
+        query
+    }
+
+    /// Rename attribute keys in a write payload to the casing of the
+    /// resource type's schema and registered extensions, if enabled.
+    pub(super) fn normalize_attribute_names(
+        &self,
+        resource_type: &str,
+        schema: &Schema,
+        data: &mut Value,
+    ) {
+        if !self.config.normalize_attribute_names {
+            return;
+        }
+
+        schema.normalize_attribute_names(data);
+        for extension in self.get_schema_extensions(resource_type) {
+            if let Some(extension_data) = data.get_mut(&extension.id) {
+                extension.normalize_attribute_names(extension_data);
+            }
+        }
+    }
+
+    /// Convert string-encoded values in a write payload to the types declared
+    /// by the resource type's schema and registered extensions, if enabled.
+    pub(super) fn coerce_attribute_values(
+        &self,
+        resource_type: &str,
+        schema: &Schema,
+        data: &mut Value,
+    ) -> ScimResult<()> {
+        if !self.config.coerce_attribute_values {
+            return Ok(());
+        }
+
+        schema.coerce_values(data)?;
+        for extension in self.get_schema_extensions(resource_type) {
+            if let Some(extension_data) = data.get_mut(&extension.id) {
+                extension.coerce_values(extension_data)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert string-encoded values of PATCH `add` and `replace` operations,
+    /// if enabled.
+    ///
+    /// A value is only coerced when the schema defines the attribute its
+    /// operation targets; path-less operations are coerced like a write
+    /// payload.
+    pub(super) fn coerce_patch_values(
+        &self,
+        resource_type: &str,
+        schema: &Schema,
+        patch_request: &mut Value,
+    ) -> ScimResult<()> {
+        if !self.config.coerce_attribute_values {
+            return Ok(());
+        }
+
+        let Some(operations) = patch_request
+            .get_mut("Operations")
+            .and_then(Value::as_array_mut)
+        else {
+            return Ok(());
+        };
+        for operation in operations {
+            let op = operation.get("op").and_then(Value::as_str).unwrap_or("");
+            if !op.eq_ignore_ascii_case("add") && !op.eq_ignore_ascii_case("replace") {
+                continue;
+            }
+            let path = operation
+                .get("path")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            let Some(value) = operation.get_mut("value") else {
+                continue;
+            };
+
+            let Some(path) = path else {
+                self.coerce_attribute_values(resource_type, schema, value)?;
+                continue;
+            };
+
+            // Attributes may be qualified with their schema URN
+            match self
+                .schema_registry
+                .resolve_attribute_path(&schema.id, &path)
+            {
+                Some((target, "")) => target.coerce_values(value)?,
+                _ => {
+                    if let Some(definition) = self
+                        .schema_registry
+                        .get_attribute_definition(&schema.id, &path)
+                    {
+                        definition.coerce_value(value)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,240 @@
+//! Startup self-test of registered resource types.
+//!
+//! Registration accepts each piece of configuration on its own, so some
+//! mistakes only show up once requests arrive: a handler built from another
+//! type's schema, two resource types answering on the same endpoint, or a
+//! schema overwritten by a later registration with the same id.
+//! [`ScimServer::self_test`] checks the whole configuration at once so these
+//! can be caught at startup. It only inspects in-memory registrations and
+//! never calls the provider.
+
+use super::core::ScimServer;
+use crate::providers::ResourceProvider;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Core schemas, whose names must match the resource type they are used for.
+const CORE_SCHEMA_PREFIX: &str = "urn:ietf:params:scim:schemas:core:2.0:";
+
+/// A configuration problem found by [`ScimServer::self_test`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SelfTestProblem {
+    /// The handler's schema is not in the schema registry.
+    #[error("Resource type '{resource_type}' uses schema '{schema_id}', which is not registered")]
+    SchemaNotRegistered {
+        /// Resource type
+        resource_type: String,
+        /// Schema id of the handler
+        schema_id: String,
+    },
+    /// The schema registry holds a different definition under the handler's
+    /// schema id, typically because a later registration replaced it.
+    #[error(
+        "Resource type '{resource_type}' uses schema '{schema_id}', but the registry holds a different definition"
+    )]
+    SchemaMismatch {
+        /// Resource type
+        resource_type: String,
+        /// Schema id of the handler
+        schema_id: String,
+    },
+    /// A core schema is used for a resource type of another name, e.g. the
+    /// User schema registered for `Group`.
+    #[error("Resource type '{resource_type}' uses the core '{schema_name}' schema")]
+    SchemaNameMismatch {
+        /// Resource type
+        resource_type: String,
+        /// Name of the handler's schema
+        schema_name: String,
+    },
+    /// Several resource types share one base schema.
+    #[error("Resource types '{resource_type}' and '{other}' share schema '{schema_id}'")]
+    SharedSchema {
+        /// Resource type
+        resource_type: String,
+        /// Resource type registered with the same schema
+        other: String,
+        /// Shared schema id
+        schema_id: String,
+    },
+    /// The resource type supports no operations.
+    #[error("Resource type '{resource_type}' supports no operations")]
+    NoOperations {
+        /// Resource type
+        resource_type: String,
+    },
+    /// Several resource types are served from one endpoint.
+    #[error("Resource types '{resource_type}' and '{other}' are both served from '/{endpoint}'")]
+    EndpointConflict {
+        /// Resource type
+        resource_type: String,
+        /// Resource type served from the same endpoint
+        other: String,
+        /// Shared endpoint
+        endpoint: String,
+    },
+    /// A schema extension is missing from the schema registry or differs from
+    /// the registered definition.
+    #[error(
+        "Resource type '{resource_type}' has extension '{schema_id}', which is not registered as such"
+    )]
+    ExtensionNotRegistered {
+        /// Resource type
+        resource_type: String,
+        /// Extension schema id
+        schema_id: String,
+    },
+}
+
+/// Result of [`ScimServer::self_test`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Resource types checked, sorted by name
+    pub resource_types: Vec<String>,
+    /// Problems found, in the order of `resource_types`
+    pub problems: Vec<SelfTestProblem>,
+}
+
+impl SelfTestReport {
+    /// Whether no problems were found.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// The report as a `Result`, failing with every problem found.
+    pub fn into_result(self) -> Result<(), Vec<SelfTestProblem>> {
+        if self.problems.is_empty() {
+            Ok(())
+        } else {
+            Err(self.problems)
+        }
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "{} resource types OK", self.resource_types.len());
+        }
+        write!(f, "{} problems found", self.problems.len())?;
+        for problem in &self.problems {
+            write!(f, "\n- {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl<P: ResourceProvider> ScimServer<P> {
+    /// Check every registered resource type for configuration problems.
+    ///
+    /// Verifies that each handler's schema is the one registered under its id
+    /// and fits the resource type, that each type supports some operation and
+    /// has an endpoint of its own, and that its extensions are registered.
+    /// All problems are reported rather than just the first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::{ScimServer, providers::StandardResourceProvider};
+    /// use scim_server::resource::ScimOperation;
+    /// use scim_server::resource_handlers::create_user_resource_handler;
+    /// use scim_server::storage::InMemoryStorage;
+    ///
+    /// let mut server = ScimServer::new(StandardResourceProvider::new(InMemoryStorage::new())).unwrap();
+    /// let user_schema = server
+    ///     .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+    ///     .unwrap()
+    ///     .clone();
+    /// server
+    ///     .register_resource_type(
+    ///         "Group",
+    ///         create_user_resource_handler(user_schema),
+    ///         vec![ScimOperation::Read],
+    ///     )
+    ///     .unwrap();
+    ///
+    /// assert!(!server.self_test().is_ok());
+    /// ```
+    pub fn self_test(&self) -> SelfTestReport {
+        let mut resource_types: Vec<String> = self.resource_handlers.keys().cloned().collect();
+        resource_types.sort_unstable();
+
+        let mut problems = Vec::new();
+        let mut schema_owners: HashMap<&str, &str> = HashMap::new();
+        let mut endpoint_owners: HashMap<String, &str> = HashMap::new();
+        let resolution = &self.config.resource_type_resolution;
+
+        for resource_type in &resource_types {
+            let handler = &self.resource_handlers[resource_type];
+            let schema = &handler.schema;
+
+            match self.schema_registry.get_schema(&schema.id) {
+                None => problems.push(SelfTestProblem::SchemaNotRegistered {
+                    resource_type: resource_type.clone(),
+                    schema_id: schema.id.clone(),
+                }),
+                Some(registered) if registered != schema => {
+                    problems.push(SelfTestProblem::SchemaMismatch {
+                        resource_type: resource_type.clone(),
+                        schema_id: schema.id.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+
+            if schema.id.starts_with(CORE_SCHEMA_PREFIX)
+                && !schema.name.eq_ignore_ascii_case(resource_type)
+            {
+                problems.push(SelfTestProblem::SchemaNameMismatch {
+                    resource_type: resource_type.clone(),
+                    schema_name: schema.name.clone(),
+                });
+            }
+
+            if let Some(other) = schema_owners.insert(&schema.id, resource_type) {
+                problems.push(SelfTestProblem::SharedSchema {
+                    resource_type: resource_type.clone(),
+                    other: other.to_string(),
+                    schema_id: schema.id.clone(),
+                });
+            }
+
+            if self
+                .supported_operations
+                .get(resource_type)
+                .is_none_or(Vec::is_empty)
+            {
+                problems.push(SelfTestProblem::NoOperations {
+                    resource_type: resource_type.clone(),
+                });
+            }
+
+            let endpoint = self.resource_endpoint(resource_type);
+            if let Some(other) =
+                endpoint_owners.insert(resolution.normalize(&endpoint), resource_type)
+            {
+                problems.push(SelfTestProblem::EndpointConflict {
+                    resource_type: resource_type.clone(),
+                    other: other.to_string(),
+                    endpoint,
+                });
+            }
+
+            for extension in self.get_schema_extensions(resource_type) {
+                if extension.id == schema.id
+                    || self.schema_registry.get_schema(&extension.id) != Some(extension)
+                {
+                    problems.push(SelfTestProblem::ExtensionNotRegistered {
+                        resource_type: resource_type.clone(),
+                        schema_id: extension.id.clone(),
+                    });
+                }
+            }
+        }
+
+        SelfTestReport {
+            resource_types,
+            problems,
+        }
+    }
+}
@@ -6,7 +6,7 @@
 #[cfg(test)]
 use super::core::ScimServer;
 #[cfg(test)]
-use crate::providers::ResourceProvider;
+use crate::providers::{ProviderError, ResourceProvider};
 use crate::resource::version::RawVersion;
 use crate::resource::versioned::VersionedResource;
 use crate::resource::{ListQuery, RequestContext, Resource, SchemaResourceBuilder, ScimOperation};
@@ -29,6 +29,8 @@ pub enum TestError {
     Test,
     #[error("Validation error: {0}")]
     ValidationError(String),
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
 }
 
 #[cfg(test)]
@@ -281,6 +283,12 @@ mod tests {
         let operations = server.get_supported_operations("User");
         assert!(operations.is_some(), "User operations should be defined");
         assert_eq!(operations.unwrap().len(), 2, "Should have 2 operations");
+
+        assert_eq!(
+            server.supported_operations("User"),
+            Some(&[ScimOperation::Create, ScimOperation::Read][..])
+        );
+        assert_eq!(server.supported_operations("Group"), None);
     }
 
     #[tokio::test]
@@ -486,4 +494,572 @@ mod tests {
             "Minimal group should be created successfully"
         );
     }
+
+    struct RiskScore;
+
+    impl crate::scim_server::ComputedAttribute for RiskScore {
+        fn name(&self) -> &str {
+            "riskScore"
+        }
+
+        fn data_type(&self) -> crate::schema::AttributeType {
+            crate::schema::AttributeType::Integer
+        }
+
+        fn compute(&self, resource: &Resource, _context: &RequestContext) -> Value {
+            json!(resource.get_username().map_or(0, str::len))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_computed_attributes() {
+        let provider = TestProvider::new();
+        let mut server = ScimServer::new(provider).expect("Failed to create server");
+        server
+            .register_resource_type(
+                "User",
+                create_user_resource_handler(create_test_user_schema()),
+                vec![
+                    ScimOperation::Create,
+                    ScimOperation::Read,
+                    ScimOperation::Update,
+                    ScimOperation::List,
+                    ScimOperation::Patch,
+                ],
+            )
+            .expect("Failed to register User resource type");
+        server
+            .register_computed_attribute("User", RiskScore)
+            .expect("Failed to register computed attribute");
+
+        // Declared read-only and returned by default
+        let schema = server.get_resource_schema("User").unwrap();
+        let definition = schema
+            .attributes
+            .iter()
+            .find(|attr| attr.name == "riskScore")
+            .expect("riskScore should be in the schema");
+        assert_eq!(definition.mutability, crate::schema::Mutability::ReadOnly);
+        assert_eq!(definition.returned.as_deref(), Some("default"));
+
+        let context = RequestContext::new("test-request".to_string());
+        let created = server
+            .create_resource(
+                "User",
+                json!({"userName": "alice", "riskScore": 99}),
+                &context,
+            )
+            .await
+            .expect("Failed to create user");
+        let id = created.get_id().unwrap().to_string();
+
+        // Never persisted, even when supplied on create or update
+        let stored = server
+            .provider
+            .get_resource("User", &id, &context)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.resource().get_attribute("riskScore").is_none());
+        server
+            .update_resource(
+                "User",
+                &id,
+                json!({"id": id, "userName": "alice", "riskScore": 99}),
+                &context,
+            )
+            .await
+            .expect("Failed to update user");
+        let stored = server
+            .provider
+            .get_resource("User", &id, &context)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.resource().get_attribute("riskScore").is_none());
+
+        // Computed on get and list
+        let fetched = server
+            .get_resource("User", &id, &context)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fetched.get_attribute("riskScore"), Some(&json!(5)));
+        let listed = server.list_resources("User", &context).await.unwrap();
+        assert_eq!(listed[0].get_attribute("riskScore"), Some(&json!(5)));
+
+        // Participates in projection
+        let query = ListQuery::new().with_attributes(vec!["userName".to_string()]);
+        let listed = server
+            .list_resources_with_query("User", Some(&query), &context)
+            .await
+            .unwrap();
+        assert!(listed[0].get_attribute("riskScore").is_none());
+        let query = ListQuery::new().with_excluded_attributes(vec!["riskScore".to_string()]);
+        let listed = server
+            .list_resources_with_query("User", Some(&query), &context)
+            .await
+            .unwrap();
+        assert!(listed[0].get_attribute("riskScore").is_none());
+
+        // Cannot be targeted by PATCH
+        let patch = json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "riskScore", "value": 1}]
+        });
+        assert!(
+            server
+                .patch_resource("User", &id, &patch, &context)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resource_handler_load_and_save_hooks() {
+        let provider = TestProvider::new();
+        let mut server = ScimServer::new(provider).expect("Failed to create server");
+        let handler = SchemaResourceBuilder::new(create_test_user_schema())
+            .on_save(|data, _context| {
+                if data["userName"] == "forbidden" {
+                    return Err(crate::error::ValidationError::custom(
+                        "userName is reserved",
+                    ));
+                }
+                if let Some(title) = data.get("title").and_then(Value::as_str) {
+                    data["title"] = json!(title.to_uppercase());
+                }
+                Ok(())
+            })
+            .on_load(|data, context| {
+                data["nickName"] = json!(context.tenant_id().unwrap_or("single-tenant"));
+                Ok(())
+            })
+            .build();
+        server
+            .register_resource_type(
+                "User",
+                handler,
+                vec![
+                    ScimOperation::Create,
+                    ScimOperation::Read,
+                    ScimOperation::List,
+                ],
+            )
+            .expect("Failed to register User resource type");
+
+        let context = RequestContext::new("test-request".to_string());
+        let created = server
+            .create_resource(
+                "User",
+                json!({"userName": "alice", "title": "engineer"}),
+                &context,
+            )
+            .await
+            .expect("Failed to create user");
+        let id = created.get_id().unwrap().to_string();
+        assert_eq!(
+            created.get_attribute("nickName"),
+            Some(&json!("single-tenant"))
+        );
+
+        // on_save shaped what was stored; on_load output is not persisted
+        let stored = server
+            .provider
+            .get_resource("User", &id, &context)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stored.resource().get_attribute("title"),
+            Some(&json!("ENGINEER"))
+        );
+        assert!(stored.resource().get_attribute("nickName").is_none());
+
+        let fetched = server
+            .get_resource("User", &id, &context)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            fetched.get_attribute("nickName"),
+            Some(&json!("single-tenant"))
+        );
+        let listed = server.list_resources("User", &context).await.unwrap();
+        assert_eq!(
+            listed[0].get_attribute("nickName"),
+            Some(&json!("single-tenant"))
+        );
+
+        let rejected = server
+            .create_resource("User", json!({"userName": "forbidden"}), &context)
+            .await;
+        assert!(matches!(
+            rejected,
+            Err(crate::error::ScimError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resource_handler_save_hook_runs_on_patch() {
+        let provider = TestProvider::new();
+        let mut server = ScimServer::new(provider).expect("Failed to create server");
+        let handler = SchemaResourceBuilder::new(create_test_user_schema())
+            .on_save(|data, _context| {
+                if data["title"] == "forbidden" {
+                    return Err(crate::error::ValidationError::custom("title is reserved"));
+                }
+                if let Some(title) = data.get("title").and_then(Value::as_str) {
+                    data["title"] = json!(title.to_uppercase());
+                }
+                Ok(())
+            })
+            .build();
+        server
+            .register_resource_type(
+                "User",
+                handler,
+                vec![
+                    ScimOperation::Create,
+                    ScimOperation::Read,
+                    ScimOperation::Patch,
+                ],
+            )
+            .expect("Failed to register User resource type");
+
+        let context = RequestContext::new("test-request".to_string());
+        let created = server
+            .create_resource("User", json!({"userName": "alice"}), &context)
+            .await
+            .expect("Failed to create user");
+        let id = created.get_id().unwrap().to_string();
+
+        let patch = json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "add", "path": "title", "value": "engineer"}]
+        });
+        let patched = server
+            .patch_resource("User", &id, &patch, &context)
+            .await
+            .expect("Failed to patch user");
+        assert_eq!(patched.get_attribute("title"), Some(&json!("ENGINEER")));
+        let stored = server
+            .provider
+            .get_resource("User", &id, &context)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stored.resource().get_attribute("title"),
+            Some(&json!("ENGINEER"))
+        );
+
+        // A rejecting hook leaves the stored resource untouched
+        let patch = json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "title", "value": "forbidden"}]
+        });
+        let rejected = server.patch_resource("User", &id, &patch, &context).await;
+        assert!(matches!(
+            rejected,
+            Err(crate::error::ScimError::Validation(_))
+        ));
+        let stored = server
+            .provider
+            .get_resource("User", &id, &context)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stored.resource().get_attribute("title"),
+            Some(&json!("ENGINEER"))
+        );
+    }
+
+    #[test]
+    fn test_self_test_reports_misconfiguration() {
+        use crate::scim_server::SelfTestProblem;
+
+        let mut server = ScimServer::new(TestProvider::new()).unwrap();
+        let user_schema = create_test_user_schema();
+        server
+            .register_resource_type(
+                "User",
+                create_user_resource_handler(user_schema.clone()),
+                vec![ScimOperation::Create, ScimOperation::Read],
+            )
+            .unwrap();
+        let report = server.self_test();
+        assert!(report.is_ok(), "{}", report);
+        assert_eq!(report.resource_types, ["User"]);
+
+        // The User schema registered for groups, and a type without operations
+        server
+            .register_resource_type(
+                "Group",
+                create_user_resource_handler(user_schema),
+                vec![ScimOperation::Read],
+            )
+            .unwrap();
+        let mut device_schema = create_test_user_schema();
+        device_schema.id = "urn:example:schemas:Device".to_string();
+        device_schema.name = "Device".to_string();
+        server
+            .register_resource_type(
+                "Device",
+                create_user_resource_handler(device_schema),
+                vec![],
+            )
+            .unwrap();
+        server.set_resource_endpoint("Device", "Things").unwrap();
+
+        // Registered later under the name whose plural another type claimed
+        let mut thing_schema = create_test_user_schema();
+        thing_schema.id = "urn:example:schemas:Thing".to_string();
+        thing_schema.name = "Thing".to_string();
+        server
+            .register_resource_type(
+                "Thing",
+                create_user_resource_handler(thing_schema),
+                vec![ScimOperation::Read],
+            )
+            .unwrap();
+
+        let problems = server.self_test().into_result().unwrap_err();
+        assert_eq!(
+            problems,
+            [
+                SelfTestProblem::NoOperations {
+                    resource_type: "Device".to_string(),
+                },
+                SelfTestProblem::SchemaNameMismatch {
+                    resource_type: "Group".to_string(),
+                    schema_name: "User".to_string(),
+                },
+                SelfTestProblem::EndpointConflict {
+                    resource_type: "Thing".to_string(),
+                    other: "Device".to_string(),
+                    endpoint: "Things".to_string(),
+                },
+                SelfTestProblem::SharedSchema {
+                    resource_type: "User".to_string(),
+                    other: "Group".to_string(),
+                    schema_id: "urn:ietf:params:scim:schemas:core:2.0:User".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discovery_documents_are_cached_until_registration() {
+        let mut server = ScimServer::new(TestProvider::new()).unwrap();
+        let context = RequestContext::new("discovery".to_string());
+        server
+            .register_resource_type(
+                "User",
+                create_user_resource_handler(create_test_user_schema()),
+                vec![ScimOperation::Create, ScimOperation::Read],
+            )
+            .unwrap();
+
+        // Repeated requests are served from the cache
+        let schemas = server.schemas_document().unwrap();
+        let resource_types = server.resource_types_document(&context).unwrap();
+        let config = server.service_provider_config_document().unwrap();
+        assert!(Arc::ptr_eq(&schemas, &server.schemas_document().unwrap()));
+        assert!(Arc::ptr_eq(
+            &resource_types,
+            &server.resource_types_document(&context).unwrap()
+        ));
+        assert_eq!(resource_types.body["totalResults"], 1);
+        assert_eq!(resource_types.body["Resources"][0]["endpoint"], "/Users");
+        assert_eq!(
+            config.body["schemas"],
+            json!(["urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig"])
+        );
+        assert!(schemas.etag().starts_with("W/\""));
+
+        // Registration busts the cache; only changed documents get a new ETag
+        server.set_resource_endpoint("User", "People").unwrap();
+        let rebuilt = server.schemas_document().unwrap();
+        assert!(!Arc::ptr_eq(&schemas, &rebuilt));
+        assert_eq!(rebuilt.etag(), schemas.etag());
+        let changed = server.resource_types_document(&context).unwrap();
+        assert_eq!(changed.body["Resources"][0]["endpoint"], "/People");
+        assert_ne!(changed.etag(), resource_types.etag());
+
+        let mut extension = create_test_user_schema();
+        extension.id = "urn:example:schemas:extension:Badge".to_string();
+        extension.name = "Badge".to_string();
+        server.register_schema_extension("User", extension).unwrap();
+        let extended = server.schemas_document().unwrap();
+        assert_ne!(extended.etag(), schemas.etag());
+        assert_eq!(extended.body["totalResults"], 2);
+        assert_eq!(
+            server.resource_types_document(&context).unwrap().body["Resources"][0]["schemaExtensions"],
+            json!([{"schema": "urn:example:schemas:extension:Badge", "required": false}])
+        );
+    }
+
+    #[test]
+    fn test_protocol_version_in_discovery_documents() {
+        let mut server = ScimServer::new(TestProvider::new()).unwrap();
+        let context = RequestContext::new("protocol".to_string());
+        server
+            .register_resource_type(
+                "User",
+                create_user_resource_handler(create_test_user_schema()),
+                vec![ScimOperation::Create, ScimOperation::Read],
+            )
+            .unwrap();
+
+        let config = server.service_provider_config_document().unwrap();
+        let resource_types = server.resource_types_document(&context).unwrap();
+        assert_eq!(config.body["meta"]["scimVersion"], "2.0");
+        assert_eq!(
+            resource_types.body["Resources"][0]["meta"]["scimVersion"],
+            "2.0"
+        );
+
+        server.set_protocol_version("1.1").unwrap();
+        let changed_config = server.service_provider_config_document().unwrap();
+        let changed_types = server.resource_types_document(&context).unwrap();
+        assert_eq!(changed_config.body["meta"]["scimVersion"], "1.1");
+        assert_eq!(
+            changed_types.body["Resources"][0]["meta"]["scimVersion"],
+            "1.1"
+        );
+        assert_ne!(changed_config.etag(), config.etag());
+        assert_ne!(changed_types.etag(), resource_types.etag());
+
+        assert!(server.set_protocol_version("3.0").is_err());
+        assert_eq!(
+            server.service_provider_config_document().unwrap().body["meta"]["scimVersion"],
+            "1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unregister_resource_type_keeps_stored_data() {
+        let mut server = ScimServer::new(TestProvider::new()).unwrap();
+        let operations = vec![ScimOperation::Create, ScimOperation::Read];
+        server
+            .register_resource_type(
+                "User",
+                create_user_resource_handler(create_test_user_schema()),
+                operations.clone(),
+            )
+            .unwrap();
+        server.set_resource_endpoint("User", "People").unwrap();
+        let context = RequestContext::new("unregister".to_string());
+        let created = server
+            .create_resource("User", json!({"userName": "kept"}), &context)
+            .await
+            .unwrap();
+        let id = created.get_id().unwrap().to_string();
+        let resource_types = server.resource_types_document(&context).unwrap();
+
+        server.unregister_resource_type("User").unwrap();
+        assert!(server.get_supported_resource_types().is_empty());
+        assert_eq!(server.supported_operations("User"), None);
+        assert_eq!(server.resolve_resource_type("People"), None);
+        assert!(matches!(
+            server.get_resource("User", &id, &context).await,
+            Err(crate::error::ScimError::UnsupportedResourceType(_))
+        ));
+        let updated = server.resource_types_document(&context).unwrap();
+        assert!(!Arc::ptr_eq(&resource_types, &updated));
+        assert_eq!(updated.body["totalResults"], 0);
+
+        // Unregistering again is an error
+        assert!(matches!(
+            server.unregister_resource_type("User"),
+            Err(crate::error::ScimError::UnsupportedResourceType(_))
+        ));
+
+        // Stored data is served again once the type is re-registered
+        server
+            .register_resource_type(
+                "User",
+                create_user_resource_handler(create_test_user_schema()),
+                operations,
+            )
+            .unwrap();
+        assert!(
+            server
+                .get_resource("User", &id, &context)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_list_registered_schemas_for() {
+        use crate::multi_tenant::{ScimSchemaExtension, ScimTenantConfiguration};
+        use crate::resource::TenantContext;
+
+        let mut server = ScimServer::new(TestProvider::new()).unwrap();
+        server
+            .register_resource_type(
+                "User",
+                create_user_resource_handler(create_test_user_schema()),
+                vec![ScimOperation::Create],
+            )
+            .unwrap();
+        let extension = |id: &str| {
+            let mut extension = create_test_user_schema();
+            extension.id = id.to_string();
+            extension
+        };
+        server
+            .register_schema_extension("User", extension("urn:example:schemas:extension:Badge"))
+            .unwrap();
+        server
+            .register_schema_extension("User", extension("urn:example:schemas:extension:Desk"))
+            .unwrap();
+
+        let mut configuration = ScimTenantConfiguration::builder("no-badges".to_string())
+            .build()
+            .unwrap();
+        configuration
+            .schema_config
+            .extensions
+            .push(ScimSchemaExtension {
+                uri: "urn:example:schemas:extension:Badge".to_string(),
+                enabled: false,
+                required: false,
+                attributes: HashMap::new(),
+            });
+        server.register_tenant_configuration(configuration);
+
+        let ids = |schemas: Vec<&Schema>| -> Vec<String> {
+            schemas
+                .into_iter()
+                .map(|schema| schema.id.clone())
+                .collect()
+        };
+        let base = create_test_user_schema().id;
+
+        // The base schema comes first, then extensions in registration order
+        assert_eq!(
+            ids(server.list_registered_schemas_for("User", None)),
+            [
+                base.as_str(),
+                "urn:example:schemas:extension:Badge",
+                "urn:example:schemas:extension:Desk"
+            ]
+        );
+
+        // Extensions the tenant disabled are left out
+        let context = RequestContext::with_tenant_generated_id(TenantContext::new(
+            "no-badges".to_string(),
+            "client".to_string(),
+        ));
+        assert_eq!(
+            ids(server.list_registered_schemas_for("User", Some(&context))),
+            [base.as_str(), "urn:example:schemas:extension:Desk"]
+        );
+        assert!(server.list_registered_schemas_for("Group", None).is_empty());
+    }
 }
@@ -0,0 +1,122 @@
+//! Validation strictness resolved per tenant.
+//!
+//! How strictly write requests are validated is set server-wide by
+//! [`ScimServerConfig`](super::ScimServerConfig) and may be overridden per
+//! tenant through the `validation_config` of its
+//! [`ScimTenantConfiguration`](crate::multi_tenant::ScimTenantConfiguration).
+//! Each override left unset falls back to the server-wide setting.
+//!
+//! Resolved [`ValidationSettings`] are cached per tenant. Registering a
+//! tenant configuration clears the cache, so a changed configuration takes
+//! effect on the next request.
+
+use super::core::ScimServer;
+use crate::multi_tenant::UnknownAttributePolicy;
+use crate::providers::ResourceProvider;
+use crate::resource::RequestContext;
+use crate::schema::Schema;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Attributes every resource may carry whatever its schema.
+const COMMON_ATTRIBUTES: [&str; 4] = ["schemas", "id", "externalId", "meta"];
+
+/// Validation strictness in effect for a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationSettings {
+    /// Whether schema URNs not registered for a resource type are rejected
+    pub strict_schemas: bool,
+    /// How attributes the schema does not define are handled
+    pub unknown_attributes: UnknownAttributePolicy,
+    /// Whether required attributes are enforced
+    pub enforce_required: bool,
+}
+
+/// Settings resolved since the last tenant configuration change, keyed by
+/// tenant (`None` for requests without one).
+#[derive(Debug, Default)]
+pub(super) struct ValidationSettingsCache {
+    settings: RwLock<HashMap<Option<String>, ValidationSettings>>,
+}
+
+impl ValidationSettingsCache {
+    fn get_or_resolve(
+        &self,
+        tenant_id: Option<&str>,
+        resolve: impl FnOnce() -> ValidationSettings,
+    ) -> ValidationSettings {
+        let key = tenant_id.map(str::to_string);
+        if let Some(settings) = self.settings.read().unwrap().get(&key) {
+            return *settings;
+        }
+        *self
+            .settings
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(resolve)
+    }
+
+    /// Drop every resolved setting.
+    pub(super) fn invalidate(&mut self) {
+        self.settings.get_mut().unwrap().clear();
+    }
+}
+
+impl<P: ResourceProvider> ScimServer<P> {
+    /// Validation strictness for the request's tenant.
+    ///
+    /// Settings the tenant's configuration does not override are taken from
+    /// the server configuration.
+    pub fn validation_settings(&self, context: &RequestContext) -> ValidationSettings {
+        let tenant_id = context.tenant_id();
+        self.validation_settings.get_or_resolve(tenant_id, || {
+            let overrides = tenant_id
+                .and_then(|tenant_id| self.tenant_configuration(tenant_id))
+                .map(|configuration| &configuration.validation_config);
+            ValidationSettings {
+                strict_schemas: overrides
+                    .and_then(|config| config.strict_schemas)
+                    .unwrap_or(self.config.strict_schema_extensions),
+                unknown_attributes: overrides
+                    .and_then(|config| config.unknown_attributes)
+                    .unwrap_or(self.config.unknown_attributes),
+                enforce_required: overrides
+                    .and_then(|config| config.enforce_required)
+                    .unwrap_or(self.config.enforce_required),
+            }
+        })
+    }
+
+    /// Drop attributes neither the schema nor an available extension defines
+    /// from a write payload, when the tenant ignores unknown attributes.
+    pub(super) fn apply_unknown_attribute_policy(
+        &self,
+        resource_type: &str,
+        schema: &Schema,
+        data: &mut Value,
+        context: &RequestContext,
+    ) {
+        if self.validation_settings(context).unknown_attributes != UnknownAttributePolicy::Ignore {
+            return;
+        }
+        let extensions = self.tenant_schema_extensions(resource_type, context);
+        let Some(object) = data.as_object_mut() else {
+            return;
+        };
+
+        object.retain(|key, _| {
+            key.starts_with("urn:")
+                || COMMON_ATTRIBUTES.contains(&key.as_str())
+                || schema.attribute_definition(key).is_some()
+        });
+        for (extension, _) in extensions {
+            if let Some(extension_data) =
+                object.get_mut(&extension.id).and_then(Value::as_object_mut)
+            {
+                extension_data.retain(|key, _| extension.attribute_definition(key).is_some());
+            }
+        }
+    }
+}
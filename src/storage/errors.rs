@@ -50,6 +50,13 @@ pub enum StorageError {
         limit: Option<usize>,
     },
 
+    /// The write conflicts with the stored state, e.g. a unique constraint
+    /// violated in the backend.
+    Conflict {
+        message: String,
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+
     /// Concurrent modification detected (optimistic locking failure).
     ConcurrentModification {
         tenant_id: String,
@@ -63,6 +70,7 @@ pub enum StorageError {
     Unavailable {
         message: String,
         retry_after: Option<std::time::Duration>,
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
     },
 
     /// Permission denied for the storage operation.
@@ -92,14 +100,22 @@ pub enum StorageError {
     Network {
         message: String,
         endpoint: Option<String>,
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
     },
 
     /// Serialization or deserialization error.
     Serialization {
         message: String,
         data_type: Option<String>,
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
     },
 
+    /// The storage backend does not support the operation.
+    Unsupported { operation: String },
+
+    /// A change stream consumer fell behind and events were dropped.
+    Lagged { skipped: u64 },
+
     /// Generic internal storage error.
     Internal {
         message: String,
@@ -169,6 +185,9 @@ impl fmt::Display for StorageError {
                 }
                 _ => write!(f, "Capacity exceeded: {}", message),
             },
+            StorageError::Conflict { message, .. } => {
+                write!(f, "Storage conflict: {}", message)
+            }
             StorageError::ConcurrentModification {
                 tenant_id,
                 resource_type,
@@ -194,6 +213,7 @@ impl fmt::Display for StorageError {
             StorageError::Unavailable {
                 message,
                 retry_after,
+                ..
             } => {
                 if let Some(duration) = retry_after {
                     write!(
@@ -244,20 +264,38 @@ impl fmt::Display for StorageError {
                     write!(f, "Configuration error: {}", message)
                 }
             }
-            StorageError::Network { message, endpoint } => {
+            StorageError::Network {
+                message, endpoint, ..
+            } => {
                 if let Some(ep) = endpoint {
                     write!(f, "Network error: {} (endpoint: {})", message, ep)
                 } else {
                     write!(f, "Network error: {}", message)
                 }
             }
-            StorageError::Serialization { message, data_type } => {
+            StorageError::Serialization {
+                message, data_type, ..
+            } => {
                 if let Some(dtype) = data_type {
                     write!(f, "Serialization error: {} (type: {})", message, dtype)
                 } else {
                     write!(f, "Serialization error: {}", message)
                 }
             }
+            StorageError::Unsupported { operation } => {
+                write!(
+                    f,
+                    "Operation not supported by this storage backend: {}",
+                    operation
+                )
+            }
+            StorageError::Lagged { skipped } => {
+                write!(
+                    f,
+                    "Change stream lagged: {} events were dropped before they could be read",
+                    skipped
+                )
+            }
             StorageError::Internal { message, .. } => {
                 write!(f, "Internal storage error: {}", message)
             }
@@ -268,7 +306,11 @@ impl fmt::Display for StorageError {
 impl std::error::Error for StorageError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            StorageError::Internal { source, .. } => source
+            StorageError::Conflict { source, .. }
+            | StorageError::Unavailable { source, .. }
+            | StorageError::Network { source, .. }
+            | StorageError::Serialization { source, .. }
+            | StorageError::Internal { source, .. } => source
                 .as_ref()
                 .map(|e| e.as_ref() as &(dyn std::error::Error + 'static)),
             _ => None,
@@ -344,6 +386,14 @@ impl StorageError {
         }
     }
 
+    /// Create a new Conflict error.
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::Conflict {
+            message: message.into(),
+            source: None,
+        }
+    }
+
     /// Create a new ConcurrentModification error.
     pub fn concurrent_modification(
         tenant_id: impl Into<String>,
@@ -364,6 +414,7 @@ impl StorageError {
         Self::Unavailable {
             message: message.into(),
             retry_after: None,
+            source: None,
         }
     }
 
@@ -410,6 +461,7 @@ impl StorageError {
         Self::Network {
             message: message.into(),
             endpoint: None,
+            source: None,
         }
     }
 
@@ -418,9 +470,22 @@ impl StorageError {
         Self::Serialization {
             message: message.into(),
             data_type: None,
+            source: None,
         }
     }
 
+    /// Create a new Unsupported error.
+    pub fn unsupported(operation: impl Into<String>) -> Self {
+        Self::Unsupported {
+            operation: operation.into(),
+        }
+    }
+
+    /// Create a new Lagged error.
+    pub fn lagged(skipped: u64) -> Self {
+        Self::Lagged { skipped }
+    }
+
     /// Create a new Internal error.
     pub fn internal(message: impl Into<String>) -> Self {
         Self::Internal {
@@ -440,16 +505,34 @@ impl StorageError {
         }
     }
 
+    /// Attach the backend error that caused this one, e.g. a driver error.
+    ///
+    /// Only `Conflict`, `Unavailable`, `Network`, `Serialization` and
+    /// `Internal` carry a source; other errors are returned unchanged.
+    pub fn with_source(mut self, error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        match &mut self {
+            StorageError::Conflict { source, .. }
+            | StorageError::Unavailable { source, .. }
+            | StorageError::Network { source, .. }
+            | StorageError::Serialization { source, .. }
+            | StorageError::Internal { source, .. } => *source = Some(Box::new(error)),
+            _ => {}
+        }
+        self
+    }
+
     /// Check if this error indicates a resource was not found.
     pub fn is_not_found(&self) -> bool {
         matches!(self, StorageError::ResourceNotFound { .. })
     }
 
-    /// Check if this error indicates a conflict (resource already exists or concurrent modification).
+    /// Check if this error indicates a conflict (resource already exists,
+    /// conflicting write or concurrent modification).
     pub fn is_conflict(&self) -> bool {
         matches!(
             self,
             StorageError::ResourceAlreadyExists { .. }
+                | StorageError::Conflict { .. }
                 | StorageError::ConcurrentModification { .. }
         )
     }
@@ -508,6 +591,31 @@ mod tests {
 
         let invalid = StorageError::invalid_data("bad format");
         assert!(invalid.is_invalid_input());
+
+        let conflict = StorageError::conflict("unique constraint violated");
+        assert!(conflict.is_conflict());
+        assert!(!conflict.is_temporary());
+    }
+
+    #[test]
+    fn test_storage_error_source() {
+        use std::error::Error;
+
+        let error = StorageError::unavailable("connection refused")
+            .with_source(std::io::Error::other("refused"));
+        assert!(error.is_temporary());
+        assert_eq!(error.to_string(), "Storage unavailable: connection refused");
+        assert_eq!(error.source().unwrap().to_string(), "refused");
+
+        let error =
+            StorageError::serialization("bad row").with_source(std::io::Error::other("eof"));
+        assert_eq!(error.source().unwrap().to_string(), "eof");
+
+        // Errors without a source field are left as they are
+        let error = StorageError::resource_not_found("tenant1", "User", "123")
+            .with_source(std::io::Error::other("ignored"));
+        assert!(error.is_not_found());
+        assert!(error.source().is_none());
     }
 
     #[test]
@@ -10,7 +10,12 @@
 //! * Automatic tenant isolation through hierarchical key structure
 //! * Efficient querying with attribute-based searches
 //! * Consistent ordering for list operations
+//! * Multi-key reads from a single consistent snapshot
 //! * No external dependencies beyond standard library
+//! * Optional resource count limit, per tenant or global, that either rejects
+//!   new resources or evicts the oldest ones
+//! * Change streams of puts and deletes, including evictions, with the
+//!   `watch` feature
 //!
 //! # Performance Characteristics
 //!
@@ -56,11 +61,85 @@
 //! # }
 //! ```
 
-use crate::storage::{StorageError, StorageKey, StoragePrefix, StorageProvider, StorageStats};
+#[cfg(feature = "watch")]
+use crate::storage::watch::{ChangeEvent, ChangeStream, DEFAULT_WATCH_CAPACITY};
+use crate::storage::{
+    StorageError, StorageKey, StoragePrefix, StorageProvider, StorageStats, attribute_values,
+};
 use serde_json::Value;
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use tokio::sync::RwLock;
+#[cfg(feature = "watch")]
+use tokio::sync::broadcast;
+
+// Structure: tenant_id -> resource_type -> resource_id -> data
+type TenantData = HashMap<String, HashMap<String, HashMap<String, Value>>>;
+
+/// Which resources count towards an [`InMemoryStorage`] capacity limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CapacityScope {
+    /// Each tenant may hold up to the limit. This is the default.
+    #[default]
+    PerTenant,
+    /// All tenants together may hold up to the limit.
+    Global,
+}
+
+/// What happens when storing a new resource would exceed an
+/// [`InMemoryStorage`] capacity limit.
+///
+/// Updates to existing resources are never affected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CapacityPolicy {
+    /// Fail with [`StorageError::CapacityExceeded`]. This is the default.
+    #[default]
+    Reject,
+    /// Delete the oldest stored resource in the same scope to make room.
+    EvictOldest,
+}
+
+/// Capacity usage of an [`InMemoryStorage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InMemoryStorageStats {
+    /// Tenant, resource type and resource counts
+    pub storage: StorageStats,
+    /// Number of resources held by each tenant
+    pub tenant_resources: HashMap<String, usize>,
+    /// Maximum number of resources, `None` when unbounded
+    pub max_resources: Option<usize>,
+    /// Which resources count towards `max_resources`
+    pub scope: CapacityScope,
+    /// Behaviour when the limit is reached
+    pub policy: CapacityPolicy,
+    /// Number of resources evicted to make room
+    pub evictions: u64,
+}
+
+impl InMemoryStorageStats {
+    /// Number of resources that count towards the limit for `tenant_id`.
+    pub fn used(&self, tenant_id: &str) -> usize {
+        match self.scope {
+            CapacityScope::PerTenant => self.tenant_resources.get(tenant_id).copied().unwrap_or(0),
+            CapacityScope::Global => self.storage.total_resources,
+        }
+    }
+
+    /// Number of resources `tenant_id` can add before the limit is reached,
+    /// `None` when unbounded.
+    pub fn remaining(&self, tenant_id: &str) -> Option<usize> {
+        self.max_resources
+            .map(|max| max.saturating_sub(self.used(tenant_id)))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Capacity {
+    max_resources: usize,
+    scope: CapacityScope,
+    policy: CapacityPolicy,
+}
 
 /// Thread-safe in-memory storage implementation.
 ///
@@ -68,10 +147,22 @@ use tokio::sync::RwLock;
 /// `tenant_id` → `resource_type` → `resource_id` → `data`
 ///
 /// All operations are async and thread-safe using tokio's RwLock.
+///
+/// Storage is unbounded by default; use [`with_capacity`](Self::with_capacity)
+/// to limit the number of resources held.
 #[derive(Clone)]
 pub struct InMemoryStorage {
-    // Structure: tenant_id -> resource_type -> resource_id -> data
-    data: Arc<RwLock<HashMap<String, HashMap<String, HashMap<String, Value>>>>>,
+    data: Arc<RwLock<TenantData>>,
+    capacity: Option<Capacity>,
+    // Keys in insertion order, only tracked when a capacity is set
+    insertion_order: Arc<Mutex<VecDeque<StorageKey>>>,
+    evictions: Arc<AtomicU64>,
+    // One channel per watched prefix, so a busy prefix cannot make the
+    // watchers of a quiet one lag
+    #[cfg(feature = "watch")]
+    watchers: Arc<Mutex<HashMap<StoragePrefix, broadcast::Sender<ChangeEvent>>>>,
+    #[cfg(feature = "watch")]
+    watch_capacity: usize,
 }
 
 impl InMemoryStorage {
@@ -79,30 +170,201 @@ impl InMemoryStorage {
     pub fn new() -> Self {
         Self {
             data: Arc::new(RwLock::new(HashMap::new())),
+            capacity: None,
+            insertion_order: Arc::new(Mutex::new(VecDeque::new())),
+            evictions: Arc::new(AtomicU64::new(0)),
+            #[cfg(feature = "watch")]
+            watchers: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "watch")]
+            watch_capacity: DEFAULT_WATCH_CAPACITY,
         }
     }
 
-    /// Extract a nested attribute value from JSON data using dot notation.
-    fn extract_attribute_value(data: &Value, attribute_path: &str) -> Option<String> {
-        let parts: Vec<&str> = attribute_path.split('.').collect();
-        let mut current = data;
+    /// Create an empty storage instance holding at most `max_resources`
+    /// resources in each tenant or overall, depending on `scope`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use scim_server::storage::{
+    ///     CapacityPolicy, CapacityScope, InMemoryStorage, StorageKey, StorageProvider,
+    /// };
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let storage = InMemoryStorage::with_capacity(1, CapacityScope::PerTenant, CapacityPolicy::Reject);
+    ///
+    /// storage.put(StorageKey::new("t1", "User", "1"), json!({"id": "1"})).await?;
+    /// assert!(storage.put(StorageKey::new("t1", "User", "2"), json!({"id": "2"})).await.is_err());
+    /// assert_eq!(storage.usage_stats().await.remaining("t2"), Some(1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_capacity(
+        max_resources: usize,
+        scope: CapacityScope,
+        policy: CapacityPolicy,
+    ) -> Self {
+        Self {
+            capacity: Some(Capacity {
+                max_resources,
+                scope,
+                policy,
+            }),
+            ..Self::new()
+        }
+    }
 
-        for part in parts {
-            if let Ok(index) = part.parse::<usize>() {
-                // Array index
-                current = current.get(index)?;
-            } else {
-                // Object key
-                current = current.get(part)?;
-            }
+    /// Buffer up to `capacity` change events for each watched prefix, instead
+    /// of [`DEFAULT_WATCH_CAPACITY`].
+    ///
+    /// A watcher further behind receives [`StorageError::Lagged`]. Streams
+    /// already returned by [`watch`](StorageProvider::watch) stop receiving
+    /// events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    #[cfg(feature = "watch")]
+    pub fn with_watch_capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "watch capacity must be positive");
+        self.watchers = Arc::new(Mutex::new(HashMap::new()));
+        self.watch_capacity = capacity;
+        self
+    }
+
+    /// Notify the watchers of `key`'s prefix of a change. The event is only
+    /// built when someone is watching that prefix.
+    #[cfg(feature = "watch")]
+    fn publish(&self, key: StorageKey, event: impl FnOnce(StorageKey) -> ChangeEvent) {
+        let watchers = self
+            .watchers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if watchers.is_empty() {
+            return;
         }
+        let prefix = StorageKey::prefix(key.tenant_id(), key.resource_type());
+        if let Some(sender) = watchers.get(&prefix)
+            && sender.receiver_count() > 0
+        {
+            // Sending only fails once every watcher has gone
+            let _ = sender.send(event(key));
+        }
+    }
+
+    /// Report resource counts and capacity usage.
+    pub async fn usage_stats(&self) -> InMemoryStorageStats {
+        let data_guard = self.data.read().await;
+        let tenant_resources: HashMap<String, usize> = data_guard
+            .iter()
+            .map(|(tenant_id, tenant_data)| (tenant_id.clone(), Self::tenant_count(tenant_data)))
+            .collect();
 
-        // Convert the final value to string for comparison
-        match current {
-            Value::String(s) => Some(s.clone()),
-            Value::Number(n) => Some(n.to_string()),
-            Value::Bool(b) => Some(b.to_string()),
-            _ => current.as_str().map(|s| s.to_string()),
+        InMemoryStorageStats {
+            storage: Self::storage_stats(&data_guard),
+            tenant_resources,
+            max_resources: self.capacity.map(|capacity| capacity.max_resources),
+            scope: self
+                .capacity
+                .map(|capacity| capacity.scope)
+                .unwrap_or_default(),
+            policy: self
+                .capacity
+                .map(|capacity| capacity.policy)
+                .unwrap_or_default(),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Lock the insertion order, reporting a lock poisoned by a panicking
+    /// writer as [`StorageError::Unavailable`].
+    fn insertion_order(&self) -> Result<MutexGuard<'_, VecDeque<StorageKey>>, StorageError> {
+        self.insertion_order
+            .lock()
+            .map_err(|_| StorageError::unavailable("insertion order lock poisoned"))
+    }
+
+    fn storage_stats(data: &TenantData) -> StorageStats {
+        let resource_counts: BTreeMap<String, BTreeMap<String, usize>> = data
+            .iter()
+            .map(|(tenant_id, tenant_data)| {
+                let types = tenant_data
+                    .iter()
+                    .map(|(resource_type, type_data)| (resource_type.clone(), type_data.len()))
+                    .collect();
+                (tenant_id.clone(), types)
+            })
+            .collect();
+
+        StorageStats {
+            tenant_count: resource_counts.len(),
+            resource_type_count: resource_counts.values().map(BTreeMap::len).sum(),
+            total_resources: resource_counts.values().flat_map(BTreeMap::values).sum(),
+            resource_counts,
+        }
+    }
+
+    fn tenant_count(tenant_data: &HashMap<String, HashMap<String, Value>>) -> usize {
+        tenant_data.values().map(HashMap::len).sum()
+    }
+
+    /// Ensure a new resource at `key` fits, evicting the oldest resources in
+    /// its scope if the policy allows.
+    fn make_room(
+        &self,
+        data: &mut TenantData,
+        key: &StorageKey,
+        capacity: Capacity,
+    ) -> Result<(), StorageError> {
+        let in_scope = |candidate: &StorageKey| {
+            capacity.scope == CapacityScope::Global || candidate.tenant_id() == key.tenant_id()
+        };
+        let used = |data: &TenantData| match capacity.scope {
+            CapacityScope::PerTenant => data
+                .get(key.tenant_id())
+                .map(Self::tenant_count)
+                .unwrap_or(0),
+            CapacityScope::Global => data.values().map(Self::tenant_count).sum(),
+        };
+
+        let mut order = self.insertion_order()?;
+        loop {
+            let current = used(data);
+            if current < capacity.max_resources {
+                return Ok(());
+            }
+
+            let oldest = match capacity.policy {
+                CapacityPolicy::Reject => None,
+                CapacityPolicy::EvictOldest => order
+                    .iter()
+                    .position(in_scope)
+                    .and_then(|position| order.remove(position)),
+            };
+            let Some(oldest) = oldest else {
+                let message = match capacity.scope {
+                    CapacityScope::PerTenant => {
+                        format!("tenant '{}' is at its resource limit", key.tenant_id())
+                    }
+                    CapacityScope::Global => "storage is at its resource limit".to_string(),
+                };
+                return Err(StorageError::CapacityExceeded {
+                    message,
+                    current_count: Some(current),
+                    limit: Some(capacity.max_resources),
+                });
+            };
+
+            if let Some(type_data) = data
+                .get_mut(oldest.tenant_id())
+                .and_then(|tenant_data| tenant_data.get_mut(oldest.resource_type()))
+            {
+                type_data.remove(oldest.resource_id());
+            }
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            #[cfg(feature = "watch")]
+            self.publish(oldest, ChangeEvent::delete);
         }
     }
 }
@@ -119,6 +381,18 @@ impl StorageProvider for InMemoryStorage {
     async fn put(&self, key: StorageKey, data: Value) -> Result<Value, Self::Error> {
         let mut data_guard = self.data.write().await;
 
+        // Only new resources count towards the capacity
+        if let Some(capacity) = self.capacity {
+            let exists = data_guard
+                .get(key.tenant_id())
+                .and_then(|tenant_data| tenant_data.get(key.resource_type()))
+                .is_some_and(|type_data| type_data.contains_key(key.resource_id()));
+            if !exists {
+                self.make_room(&mut data_guard, &key, capacity)?;
+                self.insertion_order()?.push_back(key.clone());
+            }
+        }
+
         // Ensure the nested structure exists
         let tenant_data = data_guard
             .entry(key.tenant_id().to_string())
@@ -130,6 +404,8 @@ impl StorageProvider for InMemoryStorage {
 
         // Store the data
         type_data.insert(key.resource_id().to_string(), data.clone());
+        #[cfg(feature = "watch")]
+        self.publish(key, |key| ChangeEvent::put(key, data.clone()));
 
         // Return the stored data (in this implementation, it's unchanged)
         Ok(data)
@@ -147,6 +423,26 @@ impl StorageProvider for InMemoryStorage {
         Ok(result)
     }
 
+    async fn get_many(
+        &self,
+        keys: &[StorageKey],
+    ) -> Result<Vec<(StorageKey, Option<Value>)>, Self::Error> {
+        // One read lock for all keys gives a consistent snapshot
+        let data_guard = self.data.read().await;
+
+        Ok(keys
+            .iter()
+            .map(|key| {
+                let data = data_guard
+                    .get(key.tenant_id())
+                    .and_then(|tenant_data| tenant_data.get(key.resource_type()))
+                    .and_then(|type_data| type_data.get(key.resource_id()))
+                    .cloned();
+                (key.clone(), data)
+            })
+            .collect())
+    }
+
     async fn delete(&self, key: StorageKey) -> Result<bool, Self::Error> {
         let mut data_guard = self.data.write().await;
 
@@ -160,6 +456,14 @@ impl StorageProvider for InMemoryStorage {
             false
         };
 
+        if existed && self.capacity.is_some() {
+            self.insertion_order()?.retain(|stored| *stored != key);
+        }
+        #[cfg(feature = "watch")]
+        if existed {
+            self.publish(key, ChangeEvent::delete);
+        }
+
         Ok(existed)
     }
 
@@ -205,6 +509,26 @@ impl StorageProvider for InMemoryStorage {
         Ok(results)
     }
 
+    async fn list_keys(&self, prefix: StoragePrefix) -> Result<Vec<StorageKey>, Self::Error> {
+        let data_guard = self.data.read().await;
+
+        let Some(type_data) = data_guard
+            .get(prefix.tenant_id())
+            .and_then(|tenant_data| tenant_data.get(prefix.resource_type()))
+        else {
+            return Ok(Vec::new());
+        };
+
+        let mut keys: Vec<_> = type_data
+            .keys()
+            .map(|resource_id| {
+                StorageKey::new(prefix.tenant_id(), prefix.resource_type(), resource_id)
+            })
+            .collect();
+        keys.sort_by(|a, b| a.resource_id().cmp(b.resource_id()));
+        Ok(keys)
+    }
+
     async fn find_by_attribute(
         &self,
         prefix: StoragePrefix,
@@ -224,13 +548,14 @@ impl StorageProvider for InMemoryStorage {
         let mut results = Vec::new();
 
         for (resource_id, resource_data) in type_data {
-            if let Some(attr_value) = Self::extract_attribute_value(resource_data, attribute) {
-                if attr_value == value {
-                    results.push((
-                        StorageKey::new(prefix.tenant_id(), prefix.resource_type(), resource_id),
-                        resource_data.clone(),
-                    ));
-                }
+            if attribute_values(resource_data, attribute)
+                .iter()
+                .any(|v| v == value)
+            {
+                results.push((
+                    StorageKey::new(prefix.tenant_id(), prefix.resource_type(), resource_id),
+                    resource_data.clone(),
+                ));
             }
         }
 
@@ -292,29 +617,40 @@ impl StorageProvider for InMemoryStorage {
 
     async fn clear(&self) -> Result<(), Self::Error> {
         let mut data_guard = self.data.write().await;
+        #[cfg(feature = "watch")]
+        for (tenant_id, tenant_data) in data_guard.iter() {
+            for (resource_type, type_data) in tenant_data {
+                for id in type_data.keys() {
+                    self.publish(
+                        StorageKey::new(tenant_id, resource_type, id),
+                        ChangeEvent::delete,
+                    );
+                }
+            }
+        }
         data_guard.clear();
+        self.insertion_order()?.clear();
         Ok(())
     }
 
     async fn stats(&self) -> Result<StorageStats, Self::Error> {
         let data_guard = self.data.read().await;
-        let mut tenant_count = 0;
-        let mut resource_type_count = 0;
-        let mut total_resources = 0;
-
-        for (_, tenant_data) in data_guard.iter() {
-            tenant_count += 1;
-            for (_, type_data) in tenant_data.iter() {
-                resource_type_count += 1;
-                total_resources += type_data.len();
-            }
-        }
+        Ok(Self::storage_stats(&data_guard))
+    }
 
-        Ok(StorageStats {
-            tenant_count,
-            resource_type_count,
-            total_resources,
-        })
+    #[cfg(feature = "watch")]
+    fn watch(&self, prefix: StoragePrefix) -> Result<ChangeStream, StorageError> {
+        let mut watchers = self
+            .watchers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Drop the channels of prefixes nobody watches any more
+        watchers.retain(|_, sender| sender.receiver_count() > 0);
+        let receiver = watchers
+            .entry(prefix.clone())
+            .or_insert_with(|| broadcast::channel(self.watch_capacity).0)
+            .subscribe();
+        Ok(ChangeStream::new(receiver, prefix))
     }
 }
 
@@ -553,6 +889,93 @@ mod tests {
         assert_eq!(stats.tenant_count, 2);
         assert_eq!(stats.resource_type_count, 3); // tenant1:User, tenant1:Group, tenant2:User
         assert_eq!(stats.total_resources, 4);
+        assert_eq!(stats.resource_counts["tenant1"]["User"], 2);
+        assert_eq!(stats.resource_counts["tenant1"]["Group"], 1);
+        assert_eq!(stats.resource_counts["tenant2"]["User"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_capacity_limits() {
+        let user = |tenant: &str, id: &str| StorageKey::new(tenant, "User", id);
+
+        // Unbounded by default
+        let stats = InMemoryStorage::new().usage_stats().await;
+        assert_eq!(stats.max_resources, None);
+        assert_eq!(stats.remaining("tenant1"), None);
+
+        // Rejecting new resources per tenant; updates still apply
+        let storage =
+            InMemoryStorage::with_capacity(2, CapacityScope::PerTenant, CapacityPolicy::Reject);
+        storage
+            .put(user("tenant1", "1"), json!({"id": "1"}))
+            .await
+            .unwrap();
+        storage
+            .put(user("tenant1", "2"), json!({"id": "2"}))
+            .await
+            .unwrap();
+        let error = storage
+            .put(user("tenant1", "3"), json!({"id": "3"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            StorageError::CapacityExceeded {
+                current_count: Some(2),
+                limit: Some(2),
+                ..
+            }
+        ));
+        storage
+            .put(user("tenant1", "2"), json!({"id": "2", "active": false}))
+            .await
+            .unwrap();
+        storage
+            .put(user("tenant2", "1"), json!({"id": "1"}))
+            .await
+            .unwrap();
+
+        let stats = storage.usage_stats().await;
+        assert_eq!(stats.max_resources, Some(2));
+        assert_eq!(stats.used("tenant1"), 2);
+        assert_eq!(stats.remaining("tenant1"), Some(0));
+        assert_eq!(stats.remaining("tenant2"), Some(1));
+        assert_eq!(stats.storage.total_resources, 3);
+
+        // Deleting frees room
+        storage.delete(user("tenant1", "1")).await.unwrap();
+        storage
+            .put(user("tenant1", "3"), json!({"id": "3"}))
+            .await
+            .unwrap();
+
+        // Evicting the oldest resource across all tenants
+        let storage =
+            InMemoryStorage::with_capacity(2, CapacityScope::Global, CapacityPolicy::EvictOldest);
+        storage
+            .put(user("tenant1", "1"), json!({"id": "1"}))
+            .await
+            .unwrap();
+        storage
+            .put(user("tenant2", "1"), json!({"id": "1"}))
+            .await
+            .unwrap();
+        storage
+            .put(user("tenant1", "1"), json!({"id": "1", "active": false}))
+            .await
+            .unwrap();
+        storage
+            .put(user("tenant1", "2"), json!({"id": "2"}))
+            .await
+            .unwrap();
+
+        assert!(!storage.exists(user("tenant1", "1")).await.unwrap());
+        assert!(storage.exists(user("tenant2", "1")).await.unwrap());
+        assert!(storage.exists(user("tenant1", "2")).await.unwrap());
+        let stats = storage.usage_stats().await;
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.used("tenant1"), 2);
+        assert_eq!(stats.remaining("tenant1"), Some(0));
     }
 
     #[tokio::test]
@@ -626,7 +1049,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_extract_attribute_value() {
+    async fn test_attribute_values() {
         let data = json!({
             "userName": "john.doe",
             "emails": [
@@ -640,39 +1063,142 @@ mod tests {
         });
 
         // Simple attribute
-        assert_eq!(
-            InMemoryStorage::extract_attribute_value(&data, "userName"),
-            Some("john.doe".to_string())
-        );
+        assert_eq!(attribute_values(&data, "userName"), ["john.doe"]);
 
         // Nested object
-        assert_eq!(
-            InMemoryStorage::extract_attribute_value(&data, "address.city"),
-            Some("Anytown".to_string())
-        );
+        assert_eq!(attribute_values(&data, "address.city"), ["Anytown"]);
 
         // Array index
         assert_eq!(
-            InMemoryStorage::extract_attribute_value(&data, "emails.0.value"),
-            Some("john@example.com".to_string())
+            attribute_values(&data, "emails.0.value"),
+            ["john@example.com"]
         );
 
         // Boolean value
-        assert_eq!(
-            InMemoryStorage::extract_attribute_value(&data, "emails.0.primary"),
-            Some("true".to_string())
-        );
+        assert_eq!(attribute_values(&data, "emails.0.primary"), ["true"]);
 
         // Non-existent path
+        assert_eq!(attribute_values(&data, "nonexistent"), Vec::<String>::new());
+
+        // Unindexed arrays yield every element's value
         assert_eq!(
-            InMemoryStorage::extract_attribute_value(&data, "nonexistent"),
-            None
+            attribute_values(&data, "emails.value"),
+            ["john@example.com", "john.doe@work.com"]
         );
 
         // Invalid array index
         assert_eq!(
-            InMemoryStorage::extract_attribute_value(&data, "emails.99.value"),
-            None
+            attribute_values(&data, "emails.99.value"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[cfg(feature = "watch")]
+    #[tokio::test]
+    async fn test_watch_is_scoped_to_prefix() {
+        use crate::storage::watch::ChangeKind;
+        use tokio_stream::StreamExt;
+
+        let storage = InMemoryStorage::with_capacity(
+            1,
+            CapacityScope::PerTenant,
+            CapacityPolicy::EvictOldest,
+        );
+        let changes = storage.watch(StorageKey::prefix("t1", "User")).unwrap();
+
+        storage
+            .put(StorageKey::new("t1", "User", "1"), json!({"id": "1"}))
+            .await
+            .unwrap();
+        storage
+            .put(StorageKey::new("t2", "User", "1"), json!({"id": "1"}))
+            .await
+            .unwrap();
+        storage
+            .put(StorageKey::new("t1", "Group", "g"), json!({"id": "g"}))
+            .await
+            .unwrap();
+        storage
+            .put(StorageKey::new("t1", "User", "2"), json!({"id": "2"}))
+            .await
+            .unwrap();
+        storage
+            .delete(StorageKey::new("t1", "User", "2"))
+            .await
+            .unwrap();
+        storage
+            .delete(StorageKey::new("t1", "User", "missing"))
+            .await
+            .unwrap();
+        drop(storage);
+
+        // Other tenants and types are filtered out; the eviction of User 1 to
+        // make room for Group g is reported
+        let events: Vec<_> = changes
+            .map(|event| {
+                let event = event.unwrap();
+                (event.kind, event.key.resource_id().to_string(), event.data)
+            })
+            .collect()
+            .await;
+        assert_eq!(
+            events,
+            [
+                (ChangeKind::Put, "1".to_string(), Some(json!({"id": "1"}))),
+                (ChangeKind::Delete, "1".to_string(), None),
+                (ChangeKind::Put, "2".to_string(), Some(json!({"id": "2"}))),
+                (ChangeKind::Delete, "2".to_string(), None),
+            ]
         );
     }
+
+    #[cfg(feature = "watch")]
+    #[tokio::test]
+    async fn test_watch_reports_lag_and_ends() {
+        use tokio_stream::StreamExt;
+
+        let storage = InMemoryStorage::new().with_watch_capacity(2);
+        let mut changes = storage.watch(StorageKey::prefix("t1", "User")).unwrap();
+        for id in 0..5 {
+            let id = id.to_string();
+            storage
+                .put(StorageKey::new("t1", "User", &id), json!({"id": id}))
+                .await
+                .unwrap();
+        }
+
+        match changes.next().await {
+            Some(Err(StorageError::Lagged { skipped })) => assert_eq!(skipped, 3),
+            other => panic!("expected a lag error, got {:?}", other),
+        }
+        assert!(changes.next().await.is_none());
+    }
+
+    #[cfg(feature = "watch")]
+    #[tokio::test]
+    async fn test_busy_prefix_does_not_lag_other_watchers() {
+        use tokio_stream::StreamExt;
+
+        let storage = InMemoryStorage::new().with_watch_capacity(2);
+        let quiet = storage.watch(StorageKey::prefix("t1", "User")).unwrap();
+        let _busy = storage.watch(StorageKey::prefix("t2", "User")).unwrap();
+        for id in 0..5 {
+            let id = id.to_string();
+            storage
+                .put(StorageKey::new("t2", "User", &id), json!({"id": id}))
+                .await
+                .unwrap();
+        }
+        storage
+            .put(StorageKey::new("t1", "User", "1"), json!({"id": "1"}))
+            .await
+            .unwrap();
+        drop(storage);
+
+        let events: Vec<_> = quiet
+            .map(|event| event.unwrap().key.resource_id().to_string())
+            .collect()
+            .await;
+        assert_eq!(events, ["1"]);
+    }
 }
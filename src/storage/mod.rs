@@ -11,6 +11,7 @@
 //! - Tenant isolation and data organization
 //! - Basic querying and filtering
 //! - Data persistence and retrieval
+//! - Optionally, streaming changes to watchers (`watch` feature)
 //!
 //! The storage layer is NOT responsible for:
 //! - SCIM metadata generation (timestamps, versions, etc.)
@@ -56,17 +57,24 @@
 pub mod errors;
 pub mod in_memory;
 pub mod sqlite;
+#[cfg(feature = "watch")]
+pub mod watch;
 
 #[cfg(test)]
 pub mod tests;
 
 pub use errors::StorageError;
-pub use in_memory::InMemoryStorage;
+pub use in_memory::{CapacityPolicy, CapacityScope, InMemoryStorage, InMemoryStorageStats};
 pub use sqlite::SqliteStorage;
+#[cfg(feature = "watch")]
+pub use watch::{ChangeEvent, ChangeKind, ChangeStream, DEFAULT_WATCH_CAPACITY};
 
+use chrono::{DateTime, Utc};
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::future::Future;
+use std::str::FromStr;
 
 /// Statistics about storage usage.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -77,6 +85,8 @@ pub struct StorageStats {
     pub resource_type_count: usize,
     /// Total number of individual resources
     pub total_resources: usize,
+    /// Number of resources per tenant ID and resource type
+    pub resource_counts: BTreeMap<String, BTreeMap<String, usize>>,
 }
 
 /// Statistics about provider usage (moved from obsolete in_memory module).
@@ -85,6 +95,8 @@ pub struct StorageStats {
 /// for monitoring and debugging purposes.
 #[derive(Debug, Clone)]
 pub struct ProviderStats {
+    /// When the statistics were collected
+    pub collected_at: DateTime<Utc>,
     /// Number of active tenants in the provider
     pub tenant_count: usize,
     /// Total number of resources across all tenants
@@ -93,19 +105,39 @@ pub struct ProviderStats {
     pub resource_type_count: usize,
     /// List of resource type names
     pub resource_types: Vec<String>,
+    /// Number of resources per tenant ID and resource type
+    pub resource_counts: BTreeMap<String, BTreeMap<String, usize>>,
 }
 
 impl ProviderStats {
     /// Create new empty statistics.
     pub fn new() -> Self {
         Self {
+            collected_at: Utc::now(),
             tenant_count: 0,
             total_resources: 0,
             resource_type_count: 0,
             resource_types: Vec::new(),
+            resource_counts: BTreeMap::new(),
         }
     }
 
+    /// Number of resources of a type stored for a tenant.
+    pub fn count(&self, tenant_id: &str, resource_type: &str) -> usize {
+        self.resource_counts
+            .get(tenant_id)
+            .and_then(|types| types.get(resource_type))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Number of resources of any type stored for a tenant.
+    pub fn tenant_total(&self, tenant_id: &str) -> usize {
+        self.resource_counts
+            .get(tenant_id)
+            .map_or(0, |types| types.values().sum())
+    }
+
     /// Check if the provider is empty (no resources).
     pub fn is_empty(&self) -> bool {
         self.total_resources == 0
@@ -158,6 +190,32 @@ impl StorageKey {
         &self.resource_id
     }
 
+    /// Parse a key from its `tenant/type/id` string form.
+    ///
+    /// The first two `/` separate the tenant ID and resource type, so a
+    /// resource ID may itself contain `/`. `parse(&key.to_string())` returns
+    /// `key` for every key whose tenant ID and resource type contain no `/`
+    /// and whose resource type and resource ID are non-empty.
+    pub fn parse(s: &str) -> Result<Self, StorageError> {
+        let mut parts = s.splitn(3, '/');
+        let (Some(tenant_id), Some(resource_type), Some(resource_id)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(StorageError::invalid_data(format!(
+                "Storage key '{}' is not of the form tenant/type/id",
+                s
+            )));
+        };
+        if resource_type.is_empty() || resource_id.is_empty() {
+            return Err(StorageError::invalid_data(format!(
+                "Storage key '{}' has an empty resource type or id",
+                s
+            )));
+        }
+
+        Ok(Self::new(tenant_id, resource_type, resource_id))
+    }
+
     /// Create a prefix key for listing resources of a type within a tenant.
     pub fn prefix(tenant_id: impl Into<String>, resource_type: impl Into<String>) -> StoragePrefix {
         StoragePrefix {
@@ -177,8 +235,16 @@ impl fmt::Display for StorageKey {
     }
 }
 
+impl FromStr for StorageKey {
+    type Err = StorageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
 /// A prefix for querying resources by tenant and type.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct StoragePrefix {
     tenant_id: String,
     resource_type: String,
@@ -202,6 +268,42 @@ impl fmt::Display for StoragePrefix {
     }
 }
 
+/// String forms of the values at a dot-separated attribute path, as compared
+/// by [`StorageProvider::find_by_attribute`].
+///
+/// Numeric path segments index arrays; other segments are applied to every
+/// element of an array they meet, as are arrays at the end of the path.
+pub(crate) fn attribute_values(data: &Value, attribute_path: &str) -> Vec<String> {
+    let mut current = vec![data];
+    for part in attribute_path.split('.') {
+        current = match part.parse::<usize>() {
+            Ok(index) => current.into_iter().filter_map(|v| v.get(index)).collect(),
+            Err(_) => current
+                .into_iter()
+                .flat_map(|v| match v {
+                    Value::Array(items) => items.iter().collect(),
+                    other => vec![other],
+                })
+                .filter_map(|v| v.get(part))
+                .collect(),
+        };
+    }
+
+    current
+        .into_iter()
+        .flat_map(|v| match v {
+            Value::Array(items) => items.iter().collect(),
+            other => vec![other],
+        })
+        .filter_map(|v| match v {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            Value::Bool(b) => Some(b.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Core trait for storage providers that handle pure data persistence operations.
 ///
 /// This trait defines a protocol-agnostic interface for storing and retrieving JSON data
@@ -261,6 +363,33 @@ pub trait StorageProvider: Send + Sync {
         key: StorageKey,
     ) -> impl Future<Output = Result<Option<Value>, Self::Error>> + Send;
 
+    /// Retrieve several resources at once.
+    ///
+    /// # Arguments
+    /// * `keys` - The storage keys identifying the resources
+    ///
+    /// # Returns
+    /// One `(key, data)` pair per key, in the order of `keys`, with `None` for
+    /// resources that don't exist.
+    ///
+    /// # Behavior
+    /// - The default implementation calls `get()` for each key in turn, so a
+    ///   concurrent write may land between two reads
+    /// - Implementations that can read all keys from one consistent snapshot
+    ///   should override it, so callers never see a partially applied change
+    fn get_many(
+        &self,
+        keys: &[StorageKey],
+    ) -> impl Future<Output = Result<Vec<(StorageKey, Option<Value>)>, Self::Error>> + Send {
+        async move {
+            let mut results = Vec::with_capacity(keys.len());
+            for key in keys {
+                results.push((key.clone(), self.get(key.clone()).await?));
+            }
+            Ok(results)
+        }
+    }
+
     /// Delete data by key.
     ///
     /// # Arguments
@@ -295,6 +424,28 @@ pub trait StorageProvider: Send + Sync {
         limit: usize,
     ) -> impl Future<Output = Result<Vec<(StorageKey, Value)>, Self::Error>> + Send;
 
+    /// List the keys of all resources matching a prefix, without their data.
+    ///
+    /// # Arguments
+    /// * `prefix` - The storage prefix (tenant + resource type)
+    ///
+    /// # Returns
+    /// The matching keys, ordered as by `list()`.
+    ///
+    /// # Behavior
+    /// - The default implementation calls `list()` and discards the data
+    /// - Implementations that can enumerate keys without reading or copying
+    ///   resource data should override it
+    fn list_keys(
+        &self,
+        prefix: StoragePrefix,
+    ) -> impl Future<Output = Result<Vec<StorageKey>, Self::Error>> + Send {
+        async move {
+            let results = self.list(prefix, 0, usize::MAX).await?;
+            Ok(results.into_iter().map(|(key, _data)| key).collect())
+        }
+    }
+
     /// Find resources by a specific attribute value.
     ///
     /// # Arguments
@@ -308,7 +459,9 @@ pub trait StorageProvider: Send + Sync {
     /// # Behavior
     /// - Performs exact string matching on the specified attribute
     /// - Supports nested attributes using dot notation
-    /// - Returns all matching resources (no pagination)
+    /// - Arrays are searched element by element unless the path indexes them,
+    ///   so `emails.value` matches a resource if any of its emails does
+    /// - Returns all matching resources (no pagination), ordered as by `list()`
     /// - Empty vector if no matches found
     fn find_by_attribute(
         &self,
@@ -474,7 +627,9 @@ pub trait StorageProvider: Send + Sync {
     /// Get storage statistics for debugging and monitoring.
     ///
     /// Returns statistics about storage usage including tenant count, resource type count,
-    /// and total number of resources across all tenants.
+    /// total number of resources across all tenants, and the number of resources per
+    /// tenant and resource type. Implementations should collect these in a single pass
+    /// over storage where the backend allows, as callers may have many tenants.
     ///
     /// # Returns
     ///
@@ -497,4 +652,28 @@ pub trait StorageProvider: Send + Sync {
     /// # }
     /// ```
     fn stats(&self) -> impl Future<Output = Result<StorageStats, Self::Error>> + Send;
+
+    /// Stream the changes made to resources under a prefix.
+    ///
+    /// # Arguments
+    /// * `prefix` - The storage prefix (tenant + resource type) to watch
+    ///
+    /// # Returns
+    /// A [`ChangeStream`] of the puts and deletes made under `prefix` after
+    /// the call, in the order they were applied.
+    ///
+    /// # Behavior
+    /// - The default implementation fails with [`StorageError::Unsupported`];
+    ///   backends able to publish their changes override it
+    /// - Changes under other tenants or resource types are never delivered
+    /// - A consumer that falls too far behind receives
+    ///   [`StorageError::Lagged`] and its stream ends, see the
+    ///   [`watch`](crate::storage::watch) module
+    ///
+    /// Errors are [`StorageError`]s whatever the backend's error type.
+    /// Requires the `watch` feature.
+    #[cfg(feature = "watch")]
+    fn watch(&self, _prefix: StoragePrefix) -> Result<ChangeStream, StorageError> {
+        Err(StorageError::unsupported("watch"))
+    }
 }
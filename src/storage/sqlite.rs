@@ -98,9 +98,12 @@
 //! # }
 //! ```
 
-use crate::storage::{StorageError, StorageKey, StoragePrefix, StorageProvider, StorageStats};
+use crate::storage::{
+    StorageError, StorageKey, StoragePrefix, StorageProvider, StorageStats, attribute_values,
+};
 use serde_json::Value;
 use sqlx::{Row, SqlitePool};
+use std::collections::BTreeMap;
 
 /// SQLite-based storage provider for SCIM resources.
 ///
@@ -202,38 +205,32 @@ impl SqliteStorage {
 
         Ok(())
     }
+}
 
-    /// Extract a nested attribute value from JSON data using dot notation.
-    fn extract_attribute_value(data: &Value, attribute_path: &str) -> Option<String> {
-        let parts: Vec<&str> = attribute_path.split('.').collect();
-        let mut current = data;
-
-        for part in parts {
-            if let Ok(index) = part.parse::<usize>() {
-                // Array index
-                current = current.get(index)?;
-            } else {
-                // Object key
-                current = current.get(part)?;
-            }
-        }
-
-        // Convert the final value to string for comparison
-        match current {
-            Value::String(s) => Some(s.clone()),
-            Value::Number(n) => Some(n.to_string()),
-            Value::Bool(b) => Some(b.to_string()),
-            _ => current.as_str().map(|s| s.to_string()),
+/// Classify a failed query, keeping the driver error as the source.
+///
+/// Pool timeouts and I/O failures are reported as
+/// [`StorageError::Unavailable`] and unique constraint violations as
+/// [`StorageError::Conflict`]; anything else is an internal error.
+fn query_error(context: &str, error: sqlx::Error) -> StorageError {
+    let message = format!("{}: {}", context, error);
+    let storage_error = match &error {
+        sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_) => {
+            StorageError::unavailable(message)
         }
-    }
+        sqlx::Error::Database(db) if db.is_unique_violation() => StorageError::conflict(message),
+        _ => StorageError::internal(message),
+    };
+    storage_error.with_source(error)
 }
 
 impl StorageProvider for SqliteStorage {
     type Error = StorageError;
 
     async fn put(&self, key: StorageKey, data: Value) -> Result<Value, Self::Error> {
-        let data_str = serde_json::to_string(&data)
-            .map_err(|e| StorageError::serialization(format!("Failed to serialize data: {}", e)))?;
+        let data_str = serde_json::to_string(&data).map_err(|e| {
+            StorageError::serialization(format!("Failed to serialize data: {}", e)).with_source(e)
+        })?;
 
         sqlx::query(
             "INSERT OR REPLACE INTO scim_resources (tenant_id, resource_type, resource_id, data) VALUES (?, ?, ?, ?)"
@@ -244,7 +241,7 @@ impl StorageProvider for SqliteStorage {
         .bind(&data_str)
         .execute(&self.pool)
         .await
-        .map_err(|e| StorageError::internal(format!("Failed to store resource: {}", e)))?;
+        .map_err(|e| query_error("Failed to store resource", e))?;
 
         Ok(data)
     }
@@ -258,13 +255,14 @@ impl StorageProvider for SqliteStorage {
         .bind(key.resource_id())
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| StorageError::internal(format!("Failed to fetch resource: {}", e)))?;
+        .map_err(|e| query_error("Failed to fetch resource", e))?;
 
         match row {
             Some(row) => {
                 let data_str: String = row.get("data");
                 let value = serde_json::from_str(&data_str).map_err(|e| {
                     StorageError::serialization(format!("Failed to deserialize data: {}", e))
+                        .with_source(e)
                 })?;
                 Ok(Some(value))
             }
@@ -281,7 +279,7 @@ impl StorageProvider for SqliteStorage {
         .bind(key.resource_id())
         .execute(&self.pool)
         .await
-        .map_err(|e| StorageError::internal(format!("Failed to delete resource: {}", e)))?;
+        .map_err(|e| query_error("Failed to delete resource", e))?;
 
         Ok(result.rows_affected() > 0)
     }
@@ -304,7 +302,7 @@ impl StorageProvider for SqliteStorage {
         .bind(offset as i64)
         .fetch_all(&self.pool)
         .await
-        .map_err(|e| StorageError::internal(format!("Failed to list resources: {}", e)))?;
+        .map_err(|e| query_error("Failed to list resources", e))?;
 
         let mut results = Vec::new();
         for row in rows {
@@ -312,6 +310,7 @@ impl StorageProvider for SqliteStorage {
             let data_str: String = row.get("data");
             let data: Value = serde_json::from_str(&data_str).map_err(|e| {
                 StorageError::serialization(format!("Failed to deserialize data: {}", e))
+                    .with_source(e)
             })?;
 
             let key = StorageKey::new(prefix.tenant_id(), prefix.resource_type(), resource_id);
@@ -321,6 +320,27 @@ impl StorageProvider for SqliteStorage {
         Ok(results)
     }
 
+    async fn list_keys(&self, prefix: StoragePrefix) -> Result<Vec<StorageKey>, Self::Error> {
+        let rows = sqlx::query(
+            "SELECT resource_id FROM scim_resources
+             WHERE tenant_id = ? AND resource_type = ?
+             ORDER BY resource_id",
+        )
+        .bind(prefix.tenant_id())
+        .bind(prefix.resource_type())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| query_error("Failed to list resource keys", e))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let resource_id: String = row.get("resource_id");
+                StorageKey::new(prefix.tenant_id(), prefix.resource_type(), resource_id)
+            })
+            .collect())
+    }
+
     async fn find_by_attribute(
         &self,
         prefix: StoragePrefix,
@@ -328,13 +348,15 @@ impl StorageProvider for SqliteStorage {
         value: &str,
     ) -> Result<Vec<(StorageKey, Value)>, Self::Error> {
         let rows = sqlx::query(
-            "SELECT resource_id, data FROM scim_resources WHERE tenant_id = ? AND resource_type = ?"
+            "SELECT resource_id, data FROM scim_resources
+             WHERE tenant_id = ? AND resource_type = ?
+             ORDER BY resource_id",
         )
         .bind(prefix.tenant_id())
         .bind(prefix.resource_type())
         .fetch_all(&self.pool)
         .await
-        .map_err(|e| StorageError::internal(format!("Failed to search resources: {}", e)))?;
+        .map_err(|e| query_error("Failed to search resources", e))?;
 
         let mut results = Vec::new();
         for row in rows {
@@ -342,14 +364,15 @@ impl StorageProvider for SqliteStorage {
             let data_str: String = row.get("data");
             let data: Value = serde_json::from_str(&data_str).map_err(|e| {
                 StorageError::serialization(format!("Failed to deserialize data: {}", e))
+                    .with_source(e)
             })?;
 
-            if let Some(attr_value) = Self::extract_attribute_value(&data, attribute) {
-                if attr_value == value {
-                    let key =
-                        StorageKey::new(prefix.tenant_id(), prefix.resource_type(), resource_id);
-                    results.push((key, data));
-                }
+            if attribute_values(&data, attribute)
+                .iter()
+                .any(|v| v == value)
+            {
+                let key = StorageKey::new(prefix.tenant_id(), prefix.resource_type(), resource_id);
+                results.push((key, data));
             }
         }
 
@@ -365,7 +388,7 @@ impl StorageProvider for SqliteStorage {
         .bind(key.resource_id())
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| StorageError::internal(format!("Failed to check resource existence: {}", e)))?;
+        .map_err(|e| query_error("Failed to check resource existence", e))?;
 
         Ok(row.is_some())
     }
@@ -378,7 +401,7 @@ impl StorageProvider for SqliteStorage {
         .bind(prefix.resource_type())
         .fetch_one(&self.pool)
         .await
-        .map_err(|e| StorageError::internal(format!("Failed to count resources: {}", e)))?;
+        .map_err(|e| query_error("Failed to count resources", e))?;
 
         let count: i64 = row.get("count");
         Ok(count as usize)
@@ -388,7 +411,7 @@ impl StorageProvider for SqliteStorage {
         let rows = sqlx::query("SELECT DISTINCT tenant_id FROM scim_resources ORDER BY tenant_id")
             .fetch_all(&self.pool)
             .await
-            .map_err(|e| StorageError::internal(format!("Failed to list tenants: {}", e)))?;
+            .map_err(|e| query_error("Failed to list tenants", e))?;
 
         let tenants = rows.into_iter().map(|row| row.get("tenant_id")).collect();
         Ok(tenants)
@@ -401,7 +424,7 @@ impl StorageProvider for SqliteStorage {
         .bind(tenant_id)
         .fetch_all(&self.pool)
         .await
-        .map_err(|e| StorageError::internal(format!("Failed to list resource types: {}", e)))?;
+        .map_err(|e| query_error("Failed to list resource types", e))?;
 
         let types = rows
             .into_iter()
@@ -415,9 +438,7 @@ impl StorageProvider for SqliteStorage {
             sqlx::query("SELECT DISTINCT resource_type FROM scim_resources ORDER BY resource_type")
                 .fetch_all(&self.pool)
                 .await
-                .map_err(|e| {
-                    StorageError::internal(format!("Failed to list all resource types: {}", e))
-                })?;
+                .map_err(|e| query_error("Failed to list all resource types", e))?;
 
         let types = rows
             .into_iter()
@@ -430,39 +451,34 @@ impl StorageProvider for SqliteStorage {
         sqlx::query("DELETE FROM scim_resources")
             .execute(&self.pool)
             .await
-            .map_err(|e| StorageError::internal(format!("Failed to clear storage: {}", e)))?;
+            .map_err(|e| query_error("Failed to clear storage", e))?;
 
         Ok(())
     }
 
     async fn stats(&self) -> Result<StorageStats, Self::Error> {
-        let tenant_count_row =
-            sqlx::query("SELECT COUNT(DISTINCT tenant_id) as count FROM scim_resources")
-                .fetch_one(&self.pool)
-                .await
-                .map_err(|e| StorageError::internal(format!("Failed to count tenants: {}", e)))?;
-        let tenant_count: i64 = tenant_count_row.get("count");
-
-        let resource_type_count_row = sqlx::query(
-            "SELECT COUNT(DISTINCT tenant_id || '/' || resource_type) as count FROM scim_resources",
+        // One grouped scan yields every count
+        let rows = sqlx::query(
+            "SELECT tenant_id, resource_type, COUNT(*) as count FROM scim_resources GROUP BY tenant_id, resource_type",
         )
-        .fetch_one(&self.pool)
+        .fetch_all(&self.pool)
         .await
-        .map_err(|e| StorageError::internal(format!("Failed to count resource types: {}", e)))?;
-        let resource_type_count: i64 = resource_type_count_row.get("count");
-
-        let total_resources_row = sqlx::query("SELECT COUNT(*) as count FROM scim_resources")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| {
-                StorageError::internal(format!("Failed to count total resources: {}", e))
-            })?;
-        let total_resources: i64 = total_resources_row.get("count");
+        .map_err(|e| query_error("Failed to count resources", e))?;
+
+        let mut resource_counts: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+        for row in &rows {
+            let count: i64 = row.get("count");
+            resource_counts
+                .entry(row.get("tenant_id"))
+                .or_default()
+                .insert(row.get("resource_type"), count as usize);
+        }
 
         Ok(StorageStats {
-            tenant_count: tenant_count as usize,
-            resource_type_count: resource_type_count as usize,
-            total_resources: total_resources as usize,
+            tenant_count: resource_counts.len(),
+            resource_type_count: rows.len(),
+            total_resources: resource_counts.values().flat_map(BTreeMap::values).sum(),
+            resource_counts,
         })
     }
 }
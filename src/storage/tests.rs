@@ -16,6 +16,7 @@ where
 {
     test_put_and_get(&storage).await;
     test_get_nonexistent(&storage).await;
+    test_get_many(&storage).await;
     test_delete(&storage).await;
     test_exists(&storage).await;
     test_list_with_pagination(&storage).await;
@@ -55,6 +56,47 @@ where
     assert!(result.is_none());
 }
 
+async fn test_get_many<S>(storage: &S)
+where
+    S: StorageProvider<Error = StorageError> + Send + Sync,
+{
+    storage.clear().await.unwrap();
+    let first = StorageKey::new("tenant1", "User", "1");
+    let second = StorageKey::new("tenant1", "User", "2");
+    let missing = StorageKey::new("tenant1", "User", "3");
+    let other_tenant = StorageKey::new("tenant2", "User", "1");
+    storage
+        .put(first.clone(), json!({"id": "1"}))
+        .await
+        .unwrap();
+    storage
+        .put(second.clone(), json!({"id": "2"}))
+        .await
+        .unwrap();
+
+    // Results follow the order of the keys, duplicates included
+    let keys = vec![
+        second.clone(),
+        missing.clone(),
+        first.clone(),
+        other_tenant.clone(),
+        second.clone(),
+    ];
+    let results = storage.get_many(&keys).await.unwrap();
+    assert_eq!(
+        results,
+        vec![
+            (second.clone(), Some(json!({"id": "2"}))),
+            (missing, None),
+            (first, Some(json!({"id": "1"}))),
+            (other_tenant, None),
+            (second, Some(json!({"id": "2"}))),
+        ]
+    );
+
+    assert!(storage.get_many(&[]).await.unwrap().is_empty());
+}
+
 async fn test_delete<S>(storage: &S)
 where
     S: StorageProvider<Error = StorageError> + Send + Sync,
@@ -177,6 +219,14 @@ where
     assert_eq!(found.len(), 1);
     assert_eq!(found[0].0.resource_id(), "2");
 
+    // Unindexed arrays match on any element
+    let found = storage
+        .find_by_attribute(prefix.clone(), "emails.value", "john@example.com")
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].0.resource_id(), "1");
+
     // Find non-existent
     let found = storage
         .find_by_attribute(prefix, "userName", "nonexistent")
@@ -352,6 +402,39 @@ mod tests {
     use super::*;
     use crate::storage::{InMemoryStorage, SqliteStorage};
 
+    #[test]
+    fn test_storage_key_parse() {
+        for key in [
+            StorageKey::new("tenant1", "User", "123"),
+            StorageKey::new("", "Group", "456"),
+            StorageKey::new("tenant1", "User", "a/b/c"),
+        ] {
+            assert_eq!(StorageKey::parse(&key.to_string()).unwrap(), key);
+        }
+
+        let key: StorageKey = "tenant1/User/ou=people/123".parse().unwrap();
+        assert_eq!(key.tenant_id(), "tenant1");
+        assert_eq!(key.resource_type(), "User");
+        assert_eq!(key.resource_id(), "ou=people/123");
+
+        for invalid in [
+            "",
+            "tenant1",
+            "tenant1/User",
+            "tenant1//123",
+            "tenant1/User/",
+        ] {
+            assert!(
+                matches!(
+                    StorageKey::parse(invalid),
+                    Err(StorageError::InvalidData { .. })
+                ),
+                "{:?} should not parse",
+                invalid
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_in_memory_storage() {
         let storage = InMemoryStorage::new();
@@ -364,6 +447,16 @@ mod tests {
         test_storage_provider(storage).await;
     }
 
+    #[cfg(feature = "watch")]
+    #[tokio::test]
+    async fn test_watch_unsupported_by_default() {
+        let storage = SqliteStorage::new_in_memory().await.unwrap();
+        assert!(matches!(
+            storage.watch(StorageKey::prefix("tenant1", "User")),
+            Err(StorageError::Unsupported { .. })
+        ));
+    }
+
     #[tokio::test]
     async fn test_sqlite_persistence() {
         // This test demonstrates that SQLite storage persists data
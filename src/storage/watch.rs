@@ -0,0 +1,151 @@
+//! Change streams over storage writes.
+//!
+//! [`StorageProvider::watch`](super::StorageProvider::watch) returns a
+//! [`ChangeStream`] of the puts and deletes made under one tenant and resource
+//! type, for consumers that sync in real time by pulling changes rather than
+//! polling `list`. Backends that can publish their changes do so on a
+//! `tokio::sync::broadcast` channel and wrap a receiver with
+//! [`ChangeStream::new`]; the rest report watching as unsupported.
+//!
+//! The channel buffers a fixed number of events. A consumer that falls
+//! further behind receives [`StorageError::Lagged`] with the number of events
+//! it missed, after which its stream ends: it should reload the resources it
+//! tracks with `list` and watch again. Events are never buffered without
+//! bound for a slow consumer.
+//!
+//! Requires the `watch` feature.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use scim_server::storage::{ChangeKind, InMemoryStorage, StorageKey, StorageProvider};
+//! use serde_json::json;
+//! use tokio_stream::StreamExt;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let storage = InMemoryStorage::new();
+//! let mut changes = storage.watch(StorageKey::prefix("tenant1", "User"))?;
+//!
+//! storage.put(StorageKey::new("tenant1", "User", "1"), json!({"id": "1"})).await?;
+//! let event = changes.next().await.unwrap()?;
+//! assert_eq!(event.kind, ChangeKind::Put);
+//! assert_eq!(event.key.resource_id(), "1");
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{StorageError, StorageKey, StoragePrefix};
+use serde_json::Value;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use tokio::sync::broadcast;
+use tokio_stream::Stream;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+
+/// Number of events a backend buffers for its slowest watcher by default.
+pub const DEFAULT_WATCH_CAPACITY: usize = 1024;
+
+/// What happened to a stored resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The resource was stored, whether new or replaced
+    Put,
+    /// The resource was removed
+    Delete,
+}
+
+/// A change to one stored resource.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeEvent {
+    /// Where the resource is stored
+    pub key: StorageKey,
+    /// Whether it was stored or removed
+    pub kind: ChangeKind,
+    /// The stored data for a put, `None` for a delete
+    pub data: Option<Value>,
+}
+
+impl ChangeEvent {
+    /// A resource stored at `key`.
+    pub fn put(key: StorageKey, data: Value) -> Self {
+        Self {
+            key,
+            kind: ChangeKind::Put,
+            data: Some(data),
+        }
+    }
+
+    /// A resource removed from `key`.
+    pub fn delete(key: StorageKey) -> Self {
+        Self {
+            key,
+            kind: ChangeKind::Delete,
+            data: None,
+        }
+    }
+
+    /// Whether the change falls under `prefix`.
+    pub fn matches(&self, prefix: &StoragePrefix) -> bool {
+        self.key.tenant_id() == prefix.tenant_id()
+            && self.key.resource_type() == prefix.resource_type()
+    }
+}
+
+/// Stream of the changes under one storage prefix.
+///
+/// Yields events in the order the backend applied them. Ends after reporting
+/// [`StorageError::Lagged`], or when the backend is dropped.
+pub struct ChangeStream {
+    events: BroadcastStream<ChangeEvent>,
+    prefix: StoragePrefix,
+    lagged: bool,
+}
+
+impl ChangeStream {
+    /// Stream the events of `receiver` that fall under `prefix`.
+    pub fn new(receiver: broadcast::Receiver<ChangeEvent>, prefix: StoragePrefix) -> Self {
+        Self {
+            events: BroadcastStream::new(receiver),
+            prefix,
+            lagged: false,
+        }
+    }
+
+    /// The prefix whose changes are streamed.
+    pub fn prefix(&self) -> &StoragePrefix {
+        &self.prefix
+    }
+}
+
+impl std::fmt::Debug for ChangeStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChangeStream")
+            .field("prefix", &self.prefix)
+            .field("lagged", &self.lagged)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for ChangeStream {
+    type Item = Result<ChangeEvent, StorageError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.lagged {
+            return Poll::Ready(None);
+        }
+        loop {
+            match ready!(Pin::new(&mut self.events).poll_next(cx)) {
+                Some(Ok(event)) if event.matches(&self.prefix) => {
+                    return Poll::Ready(Some(Ok(event)));
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(BroadcastStreamRecvError::Lagged(skipped))) => {
+                    self.lagged = true;
+                    return Poll::Ready(Some(Err(StorageError::lagged(skipped))));
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
@@ -9,6 +9,7 @@ pub mod builders;
 pub mod fixtures;
 pub mod multi_tenant;
 pub mod providers;
+pub mod servers;
 pub mod test_utils;
 
 /// Validation error codes for tracking test coverage
@@ -79,6 +80,30 @@ pub enum ValidationErrorCode {
     RequiredCharacteristicViolation, // Error #52
 }
 
+impl ValidationErrorCode {
+    /// Code of a schema structure error (1-8), or `None` for other errors.
+    ///
+    /// Malformed `schemas` values report the code of the error they refine:
+    /// a non-array value counts as missing schemas and a bad element as an
+    /// invalid schema URI.
+    pub fn of_schema_structure_error(error: &scim_server::error::ValidationError) -> Option<Self> {
+        use scim_server::error::ValidationError as E;
+        Some(match error {
+            E::MissingSchemas | E::SchemasNotArray { .. } => Self::MissingSchemas,
+            E::EmptySchemas => Self::EmptySchemas,
+            E::InvalidSchemaUri { .. } | E::InvalidSchemaUriElement { .. } => {
+                Self::InvalidSchemaUri
+            }
+            E::UnknownSchemaUri { .. } => Self::UnknownSchemaUri,
+            E::DuplicateSchemaUri { .. } => Self::DuplicateSchemaUri,
+            E::MissingBaseSchema => Self::MissingBaseSchema,
+            E::ExtensionWithoutBase => Self::ExtensionWithoutBase,
+            E::MissingRequiredExtension => Self::MissingRequiredExtension,
+            _ => return None,
+        })
+    }
+}
+
 /// Custom assertion macro for validation errors
 #[macro_export]
 macro_rules! assert_validation_error {
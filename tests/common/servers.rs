@@ -0,0 +1,98 @@
+//! Server setup shared by tests that drive a [`ScimServer`].
+//!
+//! [`TestServerBuilder`] replaces the usual preamble of building a server,
+//! fetching the core schemas and registering the User and Group resource
+//! types, so a test only spells out the configuration it is about.
+//!
+//! ```rust,ignore
+//! let handler = TestServerBuilder::new()
+//!     .configure(|builder| builder.with_tenant_strategy(TenantStrategy::PathBased))
+//!     .with_users([ScimOperation::Create, ScimOperation::Read])
+//!     .build_handler();
+//! ```
+
+use scim_server::multi_tenant::ScimOperation;
+use scim_server::operation_handler::ScimOperationHandler;
+use scim_server::providers::{ResourceProvider, StandardResourceProvider};
+use scim_server::resource_handlers::{create_group_resource_handler, create_user_resource_handler};
+use scim_server::storage::InMemoryStorage;
+use scim_server::{ScimServer, ScimServerBuilder};
+
+const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+const GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+
+/// Builds a [`ScimServer`] with the core resource types registered.
+pub struct TestServerBuilder<P> {
+    builder: ScimServerBuilder<P>,
+    users: Option<Vec<ScimOperation>>,
+    groups: Option<Vec<ScimOperation>>,
+}
+
+impl TestServerBuilder<StandardResourceProvider<InMemoryStorage>> {
+    /// A server over a standard provider with in-memory storage.
+    pub fn new() -> Self {
+        Self::with_provider(StandardResourceProvider::new(InMemoryStorage::new()))
+    }
+}
+
+impl Default for TestServerBuilder<StandardResourceProvider<InMemoryStorage>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: ResourceProvider + Sync> TestServerBuilder<P> {
+    /// A server over `provider`.
+    pub fn with_provider(provider: P) -> Self {
+        Self {
+            builder: ScimServerBuilder::new(provider),
+            users: None,
+            groups: None,
+        }
+    }
+
+    /// Adjust the server configuration.
+    pub fn configure(
+        mut self,
+        configure: impl FnOnce(ScimServerBuilder<P>) -> ScimServerBuilder<P>,
+    ) -> Self {
+        self.builder = configure(self.builder);
+        self
+    }
+
+    /// Register the core User resource type with `operations`.
+    pub fn with_users(mut self, operations: impl IntoIterator<Item = ScimOperation>) -> Self {
+        self.users = Some(operations.into_iter().collect());
+        self
+    }
+
+    /// Register the core Group resource type with `operations`.
+    pub fn with_groups(mut self, operations: impl IntoIterator<Item = ScimOperation>) -> Self {
+        self.groups = Some(operations.into_iter().collect());
+        self
+    }
+
+    /// Build the server, for tests that register more before handling
+    /// requests.
+    pub fn build_server(self) -> ScimServer<P> {
+        let mut server = self.builder.build().expect("test server configuration");
+        if let Some(operations) = self.users {
+            let schema = server.get_schema_by_id(USER_SCHEMA).unwrap().clone();
+            server
+                .register_resource_type("User", create_user_resource_handler(schema), operations)
+                .unwrap();
+        }
+        if let Some(operations) = self.groups {
+            let schema = server.get_schema_by_id(GROUP_SCHEMA).unwrap().clone();
+            server
+                .register_resource_type("Group", create_group_resource_handler(schema), operations)
+                .unwrap();
+        }
+        server
+    }
+
+    /// Build an operation handler over the server.
+    pub fn build_handler(self) -> ScimOperationHandler<P> {
+        ScimOperationHandler::new(self.build_server())
+    }
+}
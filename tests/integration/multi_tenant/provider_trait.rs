@@ -5,6 +5,7 @@
 //! proper scoping, and all CRUD operations within multi-tenant contexts.
 
 use scim_server::ResourceProvider;
+use scim_server::providers::ProviderError;
 use scim_server::resource::value_objects::{ExternalId, ResourceId, UserName};
 use scim_server::resource::{ListQuery, RequestContext, Resource, builder::ResourceBuilder};
 use scim_server::resource::{version::RawVersion, versioned::VersionedResource};
@@ -119,6 +120,9 @@ pub enum TestProviderError {
 
     #[error("Validation error: {message}")]
     ValidationError { message: String },
+
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
 }
 
 impl ResourceProvider for TestMultiTenantProvider {
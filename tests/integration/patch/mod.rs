@@ -10,6 +10,7 @@ pub mod scenarios;
 pub mod test_data;
 pub mod test_helpers;
 
+use scim_server::ScimType;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -72,6 +73,7 @@ pub enum ScimErrorType {
     TooMany,
     InvalidFilter,
     InvalidSyntax,
+    NoTarget,
 }
 
 /// Validation error types
@@ -342,6 +344,7 @@ pub struct PatchTestResult {
     pub resource: Option<Value>,
     pub error: Option<String>,
     pub status_code: Option<u16>,
+    pub scim_type: Option<ScimType>,
     pub etag: Option<String>,
 }
 
@@ -355,41 +358,27 @@ impl PatchTestResult {
     }
 
     pub fn error_type(&self) -> Option<ScimErrorType> {
-        // Parse error message to determine SCIM error type
-        if let Some(error_msg) = &self.error {
-            if error_msg.contains("readonly attribute")
-                || error_msg.contains("Cannot modify readonly")
-            {
-                return Some(ScimErrorType::Mutability);
-            }
-            if error_msg.contains("not found") || error_msg.contains("NotFound") {
-                return Some(ScimErrorType::NotFound);
-            }
-            if error_msg.contains("ETag mismatch") || error_msg.contains("Precondition failed") {
-                return Some(ScimErrorType::PreconditionFailed);
-            }
-            if error_msg.contains("syntax")
-                || error_msg.contains("Syntax")
-                || error_msg.contains("JSON")
-                || error_msg.contains("PATCH request must contain Operations array")
-            {
-                // Check if this is a malformed filter syntax error which should be InvalidPath
-                if error_msg.contains("malformed filter syntax") {
-                    return Some(ScimErrorType::InvalidPath);
-                }
-                return Some(ScimErrorType::InvalidSyntax);
-            }
-            if error_msg.contains("invalid") || error_msg.contains("Invalid") {
-                if error_msg.contains("path") {
-                    return Some(ScimErrorType::InvalidPath);
-                }
-                return Some(ScimErrorType::InvalidValue);
-            }
-            if error_msg.contains("duplicate") || error_msg.contains("uniqueness") {
-                return Some(ScimErrorType::Uniqueness);
-            }
+        if self.success {
+            return None;
+        }
+        match self.scim_type {
+            Some(ScimType::InvalidFilter) => Some(ScimErrorType::InvalidFilter),
+            Some(ScimType::TooMany) => Some(ScimErrorType::TooMany),
+            Some(ScimType::Uniqueness) => Some(ScimErrorType::Uniqueness),
+            Some(ScimType::Mutability) => Some(ScimErrorType::Mutability),
+            Some(ScimType::InvalidSyntax) => Some(ScimErrorType::InvalidSyntax),
+            Some(ScimType::InvalidPath) => Some(ScimErrorType::InvalidPath),
+            Some(ScimType::NoTarget) => Some(ScimErrorType::NoTarget),
+            Some(ScimType::InvalidValue) => Some(ScimErrorType::InvalidValue),
+            Some(ScimType::InvalidVers | ScimType::Sensitive) => None,
+            // Errors without a scimType are told apart by their status
+            None => match self.status_code? {
+                404 => Some(ScimErrorType::NotFound),
+                409 => Some(ScimErrorType::Conflict),
+                412 => Some(ScimErrorType::PreconditionFailed),
+                _ => None,
+            },
         }
-        None
     }
 
     pub fn get_attribute_value(&self, path: &str) -> Option<&Value> {
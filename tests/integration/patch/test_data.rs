@@ -289,7 +289,17 @@ impl TestDataFactory {
                         continue;
                     }
 
-                    let expected_result = if *should_succeed {
+                    // Attributes the Group schema does not define are
+                    // rejected as unknown targets
+                    let expected_result = if *resource_type == "Group"
+                        && *should_succeed
+                        && !PathUtils::is_group_attribute(path)
+                    {
+                        ExpectedResult::ScimError {
+                            error_type: ScimErrorType::InvalidPath,
+                            status_code: 400,
+                        }
+                    } else if *should_succeed {
                         match path_type {
                             PathType::ReadOnly | PathType::Immutable => ExpectedResult::ScimError {
                                 error_type: ScimErrorType::Mutability,
@@ -546,6 +556,14 @@ impl PathUtils {
             .unwrap_or(path)
     }
 
+    /// Check if a path targets an attribute defined by the Group schema
+    pub fn is_group_attribute(path: &str) -> bool {
+        let root = Self::get_root_attribute(path);
+        ["displayName", "members", "externalId"]
+            .iter()
+            .any(|attribute| attribute.eq_ignore_ascii_case(root))
+    }
+
     /// Check if path targets a multi-valued attribute
     pub fn is_multivalued_path(path: &str) -> bool {
         let root = Self::get_root_attribute(path);
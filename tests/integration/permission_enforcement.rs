@@ -4,8 +4,11 @@
 //! and maintains proper isolation between tenants.
 
 use scim_server::{
-    RequestContext, ResourceProvider, TenantContext, TenantPermissions,
-    providers::StandardResourceProvider, storage::InMemoryStorage,
+    RequestContext, ResourceProvider, ScimError, ScimOperationHandler, ScimOperationRequest,
+    ScimServer, TenantContext, TenantPermissions, create_user_resource_handler,
+    multi_tenant::ScimOperation,
+    providers::{ProviderError, StandardResourceProvider},
+    storage::InMemoryStorage,
 };
 use serde_json::json;
 
@@ -529,3 +532,113 @@ async fn test_operation_specific_permissions() {
         &json!("Updated Writeonly User")
     );
 }
+
+/// Test that patch, search and existence checks also consult tenant permissions
+#[tokio::test]
+async fn test_permission_enforcement_patch_and_queries() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+
+    let setup_context = RequestContext::with_tenant_generated_id(TenantContext::new(
+        "locked-tenant".to_string(),
+        "client".to_string(),
+    ));
+    let user = provider
+        .create_resource("User", json!({"userName": "locked.user"}), &setup_context)
+        .await
+        .expect("Setup create should succeed");
+    let user_id = user.resource().get_id().unwrap();
+
+    let no_access = TenantPermissions {
+        can_read: false,
+        can_update: false,
+        ..Default::default()
+    };
+    let locked_context = RequestContext::with_tenant_generated_id(
+        TenantContext::new("locked-tenant".to_string(), "client".to_string())
+            .with_permissions(no_access),
+    );
+
+    let patch = json!({
+        "Operations": [{"op": "replace", "path": "displayName", "value": "Patched"}]
+    });
+    let patch_result = provider
+        .patch_resource("User", user_id, &patch, None, &locked_context)
+        .await;
+    assert!(matches!(
+        patch_result,
+        Err(ProviderError::PermissionDenied { ref operation, .. }) if operation == "update"
+    ));
+
+    let find_result = provider
+        .find_resources_by_attribute("User", "userName", "locked.user", &locked_context)
+        .await;
+    assert!(matches!(
+        find_result,
+        Err(ProviderError::PermissionDenied { .. })
+    ));
+
+    let exists_result = provider
+        .resource_exists("User", user_id, &locked_context)
+        .await;
+    assert!(matches!(
+        exists_result,
+        Err(ProviderError::PermissionDenied { .. })
+    ));
+}
+
+/// Test that permission failures surface as 403 errors through the server and handler
+#[tokio::test]
+async fn test_permission_denied_maps_to_forbidden() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let mut server = ScimServer::new(provider).unwrap();
+
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    server
+        .register_resource_type(
+            "User",
+            create_user_resource_handler(user_schema),
+            vec![ScimOperation::Create, ScimOperation::Read],
+        )
+        .unwrap();
+
+    let readonly_perms = TenantPermissions {
+        can_create: false,
+        ..Default::default()
+    };
+    let readonly_tenant = TenantContext::new("readonly".to_string(), "client".to_string())
+        .with_permissions(readonly_perms);
+    let readonly_context = RequestContext::with_tenant_generated_id(readonly_tenant.clone());
+
+    let user_data = json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+        "userName": "blocked.user"
+    });
+
+    let error = server
+        .create_resource("User", user_data.clone(), &readonly_context)
+        .await
+        .expect_err("Create should be blocked for readonly tenant");
+    assert!(matches!(error, ScimError::PermissionDenied { .. }));
+    assert_eq!(error.status_code(), 403);
+
+    // Single-tenant requests are unaffected by tenant permissions
+    let single_context = RequestContext::with_generated_id();
+    server
+        .create_resource("User", user_data.clone(), &single_context)
+        .await
+        .expect("Single-tenant create should be allowed");
+
+    let handler = ScimOperationHandler::new(server);
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::create("User", user_data).with_tenant(readonly_tenant),
+        )
+        .await;
+    assert!(!response.success);
+    assert_eq!(response.error_code.as_deref(), Some("PERMISSION_DENIED"));
+}
@@ -8,6 +8,7 @@
 use scim_server::{
     ListQuery, RequestContext, Resource, ResourceProvider, ScimTenantConfiguration,
     StaticTenantResolver, TenantContext, TenantResolver,
+    providers::ProviderError,
     resource::{version::RawVersion, versioned::VersionedResource},
 };
 use serde_json::{Value, json};
@@ -47,6 +48,12 @@ impl TestScimProvider {
 #[error("Test provider error")]
 struct TestProviderError;
 
+impl From<ProviderError> for TestProviderError {
+    fn from(_: ProviderError) -> Self {
+        TestProviderError
+    }
+}
+
 impl ResourceProvider for TestScimProvider {
     type Error = TestProviderError;
 
@@ -7,6 +7,7 @@ use scim_server::{
     BulkCapabilities, CapabilityIntrospectable, ExtendedCapabilities, ListQuery,
     PaginationCapabilities, RequestContext, Resource, ResourceProvider, ScimOperation, ScimServer,
     create_user_resource_handler,
+    providers::ProviderError,
     resource::{version::RawVersion, versioned::VersionedResource},
 };
 use serde_json::{Value, json};
@@ -26,6 +27,12 @@ impl TestProvider {
 #[error("Test provider error")]
 struct TestError;
 
+impl From<ProviderError> for TestError {
+    fn from(_: ProviderError) -> Self {
+        TestError
+    }
+}
+
 impl ResourceProvider for TestProvider {
     type Error = TestError;
 
@@ -241,6 +241,10 @@ fn test_group_invalid_schema() {
         Err(ValidationError::UnknownSchemaUri { uri }) => {
             assert_eq!(uri, "urn:invalid:schema");
         }
+        Err(ValidationError::InvalidSchemaUriElement { index, value }) => {
+            assert_eq!(index, 0);
+            assert_eq!(value, "urn:invalid:schema");
+        }
         Err(other) => panic!(
             "Expected InvalidSchemaUri or UnknownSchemaUri error, got {:?}",
             other
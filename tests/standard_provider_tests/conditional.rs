@@ -0,0 +1,296 @@
+//! StandardResourceProvider tests for version-checked updates and deletes.
+
+use super::create_test_user_data;
+use scim_server::ResourceProvider;
+use scim_server::providers::helpers::conditional::ConditionalOperations;
+use scim_server::providers::{ProviderError, StandardResourceProvider};
+use scim_server::resource::RequestContext;
+use scim_server::resource::version::ConditionalResult;
+use scim_server::storage::InMemoryStorage;
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_conditional_operations_via_resource_provider() {
+    // This test ensures StandardResourceProvider implements conditional operations via ResourceProvider trait
+    // Using static dispatch with generic function to enforce trait bounds
+    async fn test_provider<P>(provider: &P, context: &RequestContext)
+    where
+        P: ResourceProvider<Error = ProviderError> + Sync,
+    {
+        // Create a user first using regular provider
+        let user_data = create_test_user_data("jane.doe");
+        let user = provider
+            .create_resource("User", user_data, context)
+            .await
+            .unwrap();
+        let user_id = user.resource().get_id().unwrap();
+
+        // Get versioned resource using trait method
+        let versioned = provider
+            .get_resource("User", user_id, context)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Test conditional update using trait method
+        let update_data = json!({
+            "userName": "jane.doe",
+            "displayName": "Jane Updated",
+            "active": false
+        });
+
+        let result = provider
+            .update_resource(
+                "User",
+                user_id,
+                update_data,
+                Some(versioned.version()),
+                context,
+            )
+            .await
+            .unwrap();
+
+        // Should succeed since version matches
+        assert!(result.resource().get_id().is_some());
+    }
+
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let context = RequestContext::with_generated_id();
+
+    // This tests that conditional operations work via ResourceProvider trait
+    test_provider(&provider, &context).await;
+}
+
+#[tokio::test]
+async fn test_conditional_provider_concurrent_updates() {
+    use tokio::task::JoinSet;
+
+    let storage = InMemoryStorage::new();
+    let provider = Arc::new(StandardResourceProvider::new(storage));
+    let context = RequestContext::with_generated_id();
+
+    // Create a user first
+    let user_data = create_test_user_data("concurrent.user");
+    let user = provider
+        .create_resource("User", user_data, &context)
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap().to_string();
+
+    // Get initial version
+    let initial_versioned = provider
+        .get_versioned_resource("User", &user_id, &context)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Launch concurrent updates with the same version
+    let mut tasks = JoinSet::new();
+    let num_concurrent = 10;
+
+    for i in 0..num_concurrent {
+        let provider_clone: Arc<StandardResourceProvider<InMemoryStorage>> = Arc::clone(&provider);
+        let context_clone = context.clone();
+        let user_id_clone = user_id.clone();
+        let version_clone = initial_versioned.version().clone();
+
+        tasks.spawn(async move {
+            let update_data = json!({
+                "userName": "concurrent.user",
+                "displayName": format!("Update {}", i),
+                "active": true
+            });
+
+            provider_clone
+                .update_resource(
+                    "User",
+                    &user_id_clone,
+                    update_data,
+                    Some(&version_clone),
+                    &context_clone,
+                )
+                .await
+        });
+    }
+
+    // Collect results
+    let mut success_count = 0;
+    let mut conflict_count = 0;
+
+    while let Some(result) = tasks.join_next().await {
+        match result.unwrap() {
+            Ok(_) => success_count += 1,
+            Err(_) => conflict_count += 1,
+        }
+    }
+
+    // Only one update should succeed, others should get version conflicts
+    assert_eq!(success_count, 1, "Exactly one update should succeed");
+    assert_eq!(
+        conflict_count,
+        num_concurrent - 1,
+        "Other updates should conflict"
+    );
+}
+
+#[tokio::test]
+async fn test_conditional_provider_delete_version_conflict() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let context = RequestContext::with_generated_id();
+
+    // Create a user
+    let user_data = create_test_user_data("delete.user");
+    let user = provider
+        .create_resource("User", user_data, &context)
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap();
+
+    // Get initial version
+    let initial_versioned = provider
+        .get_versioned_resource("User", user_id, &context)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Update the resource to change its version
+    let update_data = json!({
+        "userName": "delete.user",
+        "displayName": "Updated Before Delete",
+        "active": false
+    });
+    provider
+        .update_resource("User", user_id, update_data, None, &context)
+        .await
+        .unwrap();
+
+    // Try to delete with old version - should fail
+    let delete_result = provider
+        .conditional_delete_resource("User", user_id, initial_versioned.version(), &context)
+        .await
+        .unwrap();
+
+    // Should get version conflict
+    assert!(matches!(
+        delete_result,
+        ConditionalResult::VersionMismatch(_)
+    ));
+
+    // Resource should still exist
+    let still_exists = provider
+        .resource_exists("User", user_id, &context)
+        .await
+        .unwrap();
+    assert!(still_exists);
+}
+
+#[tokio::test]
+async fn test_conditional_provider_successful_delete() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let context = RequestContext::with_generated_id();
+
+    // Create a user
+    let user_data = create_test_user_data("delete.success");
+    let user = provider
+        .create_resource("User", user_data, &context)
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap();
+
+    // Get current version
+    let current_versioned = provider
+        .get_versioned_resource("User", user_id, &context)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Delete with correct version - should succeed
+    let delete_result = provider
+        .conditional_delete_resource("User", user_id, current_versioned.version(), &context)
+        .await
+        .unwrap();
+
+    // Should succeed
+    assert!(matches!(delete_result, ConditionalResult::Success(())));
+
+    // Resource should no longer exist
+    let exists = provider
+        .resource_exists("User", user_id, &context)
+        .await
+        .unwrap();
+    assert!(!exists);
+}
+
+#[tokio::test]
+async fn test_conditional_results_report_current_version() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let context = RequestContext::with_generated_id();
+
+    let user = provider
+        .create_resource("User", create_test_user_data("conditional.user"), &context)
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap();
+    let stale = user.version().clone();
+
+    let updated = match provider
+        .update_resource_conditional(
+            "User",
+            user_id,
+            create_test_user_data("conditional.renamed"),
+            &stale,
+            &context,
+        )
+        .await
+        .unwrap()
+    {
+        ConditionalResult::Success(updated) => updated,
+        other => panic!("expected success, got {:?}", other),
+    };
+    let current = updated.version().clone();
+
+    // Both operations report the stored version on a mismatch
+    let update_result = provider
+        .update_resource_conditional(
+            "User",
+            user_id,
+            create_test_user_data("conditional.user"),
+            &stale,
+            &context,
+        )
+        .await
+        .unwrap();
+    let delete_result = provider
+        .delete_resource_conditional("User", user_id, &stale, &context)
+        .await
+        .unwrap();
+    for result in [update_result.map(|_| ()), delete_result] {
+        match result {
+            ConditionalResult::VersionMismatch(conflict) => {
+                assert_eq!(conflict.expected, stale);
+                assert_eq!(conflict.current, current);
+            }
+            other => panic!("expected version mismatch, got {:?}", other),
+        }
+    }
+
+    assert_eq!(
+        provider
+            .delete_resource_conditional("User", user_id, &current, &context)
+            .await
+            .unwrap(),
+        ConditionalResult::Success(())
+    );
+    assert_eq!(
+        provider
+            .delete_resource_conditional("User", user_id, &current, &context)
+            .await
+            .unwrap(),
+        ConditionalResult::NotFound
+    );
+}
@@ -0,0 +1,612 @@
+//! StandardResourceProvider tests for create, read, update and delete.
+
+use super::create_test_user_data;
+use scim_server::ResourceProvider;
+use scim_server::providers::{ProviderError, StandardResourceProvider};
+use scim_server::resource::{ListQuery, RequestContext, TenantContext, VersionedResource};
+use scim_server::storage::InMemoryStorage;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_single_tenant_operations() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let context = RequestContext::with_generated_id();
+
+    // Create user
+    let user_data = create_test_user_data("john.doe");
+    let user = provider
+        .create_resource("User", user_data, &context)
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap();
+
+    // Get user
+    let retrieved = provider
+        .get_resource("User", user_id, &context)
+        .await
+        .unwrap();
+    assert!(retrieved.is_some());
+    assert_eq!(
+        retrieved.unwrap().resource().get_username(),
+        Some("john.doe")
+    );
+
+    // Update user
+    let update_data = json!({
+        "userName": "john.doe",
+        "displayName": "John Updated",
+        "active": false
+    });
+    let _updated = provider
+        .update_resource("User", user_id, update_data, None, &context)
+        .await
+        .unwrap();
+    // Check that the resource was updated (we'll verify via getting it back)
+    let verified = provider
+        .get_resource("User", user_id, &context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        verified.resource().get_attribute("displayName"),
+        Some(&json!("John Updated"))
+    );
+
+    // List users
+    let query = ListQuery::default();
+    let users = provider
+        .list_resources("User", Some(&query), &context)
+        .await
+        .unwrap();
+    assert_eq!(users.len(), 1);
+
+    // Delete user
+    provider
+        .delete_resource("User", user_id, None, &context)
+        .await
+        .unwrap();
+    let deleted = provider
+        .get_resource("User", user_id, &context)
+        .await
+        .unwrap();
+    assert!(deleted.is_none());
+}
+
+#[tokio::test]
+async fn test_username_duplicate_detection() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let context = RequestContext::with_generated_id();
+
+    // Create first user
+    let user1_data = create_test_user_data("duplicate");
+    let _user1 = provider
+        .create_resource("User", user1_data, &context)
+        .await
+        .unwrap();
+
+    // Attempt to create second user with same username
+    let user2_data = create_test_user_data("duplicate");
+    let result = provider.create_resource("User", user2_data, &context).await;
+
+    assert!(result.is_err());
+    match result.unwrap_err() {
+        ProviderError::DuplicateAttribute {
+            attribute, value, ..
+        } => {
+            assert_eq!(attribute, "userName");
+            assert_eq!(value, "duplicate");
+        }
+        _ => panic!("Expected DuplicateAttribute error"),
+    }
+
+    // userName is not caseExact, so a case variant is also a duplicate
+    let user3_data = create_test_user_data("DUPLICATE");
+    let result = provider.create_resource("User", user3_data, &context).await;
+    assert!(matches!(
+        result,
+        Err(ProviderError::DuplicateAttribute { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_find_resource_by_attribute() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let context = RequestContext::with_generated_id();
+
+    // Create a user
+    let user_data = create_test_user_data("john.doe");
+    let _user = provider
+        .create_resource("User", user_data, &context)
+        .await
+        .unwrap();
+
+    // Find by userName
+    let found = provider
+        .find_resources_by_attribute("User", "userName", "john.doe", &context)
+        .await
+        .unwrap();
+
+    assert!(!found.is_empty());
+    assert_eq!(found[0].resource().get_username(), Some("john.doe"));
+
+    // Find by non-existent attribute value
+    let not_found = provider
+        .find_resources_by_attribute("User", "userName", "nonexistent", &context)
+        .await
+        .unwrap();
+    assert!(not_found.is_empty());
+}
+
+#[tokio::test]
+async fn test_resource_exists() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let context = RequestContext::with_generated_id();
+
+    // Create a user
+    let user_data = create_test_user_data("test.user");
+    let user = provider
+        .create_resource("User", user_data, &context)
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap();
+
+    // Check existence
+    let exists = provider
+        .resource_exists("User", user_id, &context)
+        .await
+        .unwrap();
+    assert!(exists);
+
+    // Check non-existent resource
+    let not_exists = provider
+        .resource_exists("User", "nonexistent-id", &context)
+        .await
+        .unwrap();
+    assert!(!not_exists);
+
+    // Delete and check again
+    provider
+        .delete_resource("User", user_id, None, &context)
+        .await
+        .unwrap();
+
+    let exists_after_delete = provider
+        .resource_exists("User", user_id, &context)
+        .await
+        .unwrap();
+    assert!(!exists_after_delete);
+}
+
+#[tokio::test]
+async fn test_soft_delete_tombstones() {
+    let context = RequestContext::with_generated_id();
+    let is_deleted = |error: &ProviderError| matches!(error, ProviderError::ResourceDeleted { .. });
+
+    let provider = StandardResourceProvider::new(InMemoryStorage::new()).with_soft_delete(true);
+    let user = provider
+        .create_resource("User", create_test_user_data("gone"), &context)
+        .await
+        .unwrap();
+    let id = user.resource().get_id().unwrap().to_string();
+    provider
+        .delete_resource("User", &id, None, &context)
+        .await
+        .unwrap();
+
+    // Operations on the deleted resource say so, unlike on one that never existed
+    let error = provider
+        .get_resource("User", &id, &context)
+        .await
+        .unwrap_err();
+    assert!(is_deleted(&error), "{:?}", error);
+    let error = provider
+        .update_resource("User", &id, create_test_user_data("gone"), None, &context)
+        .await
+        .unwrap_err();
+    assert!(is_deleted(&error), "{:?}", error);
+    let patch = json!({"Operations": [{"op": "replace", "path": "title", "value": "x"}]});
+    let error = provider
+        .patch_resource("User", &id, &patch, None, &context)
+        .await
+        .unwrap_err();
+    assert!(is_deleted(&error), "{:?}", error);
+    let error = provider
+        .delete_resource("User", &id, None, &context)
+        .await
+        .unwrap_err();
+    assert!(is_deleted(&error), "{:?}", error);
+    assert!(
+        provider
+            .get_resource("User", "never-existed", &context)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let error = provider
+        .delete_resource("User", "never-existed", None, &context)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, ProviderError::ResourceNotFound { .. }));
+
+    // Tombstones are not resources
+    assert!(
+        provider
+            .list_resources("User", None, &context)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(provider.stats().await.is_empty());
+
+    // Without soft delete a deleted resource is simply not found
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let user = provider
+        .create_resource("User", create_test_user_data("gone"), &context)
+        .await
+        .unwrap();
+    let id = user.resource().get_id().unwrap().to_string();
+    provider
+        .delete_resource("User", &id, None, &context)
+        .await
+        .unwrap();
+    assert!(
+        provider
+            .get_resource("User", &id, &context)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let error = provider
+        .delete_resource("User", &id, None, &context)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, ProviderError::ResourceNotFound { .. }));
+}
+
+#[tokio::test]
+async fn test_version_covers_extension_data() {
+    const ENTERPRISE: &str = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+    const CORE: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let context = RequestContext::with_generated_id();
+
+    let user = |schemas: [&str; 2], employee_number: &str| {
+        json!({
+            "schemas": schemas,
+            "userName": "bjensen",
+            ENTERPRISE: {"employeeNumber": employee_number, "department": "Sales"}
+        })
+    };
+    let created = provider
+        .create_resource("User", user([CORE, ENTERPRISE], "701984"), &context)
+        .await
+        .unwrap();
+    let id = created.resource().get_id().unwrap().to_string();
+
+    // An extension-only change produces a new version
+    let changed = provider
+        .update_resource(
+            "User",
+            &id,
+            user([CORE, ENTERPRISE], "701985"),
+            None,
+            &context,
+        )
+        .await
+        .unwrap();
+    assert_ne!(changed.version(), created.version());
+
+    // Reordering schemas and object keys alone does not
+    let reordered = json!({
+        ENTERPRISE: {"department": "Sales", "employeeNumber": "701985"},
+        "userName": "bjensen",
+        "schemas": [ENTERPRISE, CORE]
+    });
+    let unchanged = provider
+        .update_resource("User", &id, reordered, None, &context)
+        .await
+        .unwrap();
+    assert_eq!(unchanged.version(), changed.version());
+}
+
+#[tokio::test]
+async fn test_get_resources_preserves_order() {
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let context = RequestContext::with_generated_id();
+    let other_tenant = RequestContext::with_tenant_generated_id(TenantContext::new(
+        "tenant-b".to_string(),
+        "client-b".to_string(),
+    ));
+
+    let alice = provider
+        .create_resource("User", create_test_user_data("alice"), &context)
+        .await
+        .unwrap();
+    let bob = provider
+        .create_resource("User", create_test_user_data("bob"), &context)
+        .await
+        .unwrap();
+    let alice_id = alice.resource().get_id().unwrap().to_string();
+    let bob_id = bob.resource().get_id().unwrap().to_string();
+
+    let ids = vec![bob_id.clone(), "missing".to_string(), alice_id.clone()];
+    let resources = provider
+        .get_resources("User", &ids, &context)
+        .await
+        .unwrap();
+    assert_eq!(resources.len(), 3);
+    assert_eq!(
+        resources[0].as_ref().unwrap().resource().get_id(),
+        Some(bob_id.as_str())
+    );
+    assert!(resources[1].is_none());
+    let fetched = resources[2].as_ref().unwrap();
+    assert_eq!(fetched.resource().get_id(), Some(alice_id.as_str()));
+    assert_eq!(fetched.version(), alice.version());
+
+    // Lookups stay within the requesting tenant
+    let resources = provider
+        .get_resources("User", &ids, &other_tenant)
+        .await
+        .unwrap();
+    assert!(resources.iter().all(Option::is_none));
+}
+
+#[tokio::test]
+#[allow(deprecated)]
+async fn test_in_memory_provider_shim() {
+    use scim_server::providers::InMemoryProvider;
+
+    let provider = InMemoryProvider::new();
+    let context = RequestContext::with_generated_id();
+
+    let user = provider
+        .create_resource("User", create_test_user_data("shim.user"), &context)
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap();
+    assert!(
+        provider
+            .get_resource("User", user_id, &context)
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    // Inherent methods of the standard provider are available too
+    assert_eq!(provider.stats().await.count("default", "User"), 1);
+
+    let provider: StandardResourceProvider<InMemoryStorage> = provider.into();
+    assert!(
+        provider
+            .resource_exists("User", user_id, &context)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_empty_versus_absent_multi_valued_attributes() {
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let context = RequestContext::with_generated_id();
+
+    let with_emails = |user_name: &str| {
+        json!({
+            "userName": user_name,
+            "emails": [{"value": format!("{}@example.com", user_name), "primary": true}],
+            "phoneNumbers": [{"value": "+1-555-0100"}]
+        })
+    };
+    let stored = |id: String| {
+        let provider = &provider;
+        let context = &context;
+        async move {
+            provider
+                .get_resource("User", &id, context)
+                .await
+                .unwrap()
+                .unwrap()
+                .resource()
+                .to_json()
+                .unwrap()
+        }
+    };
+
+    // PUT with an empty array clears the attribute and keeps it as []
+    let id = provider
+        .create_resource("User", with_emails("put.empty"), &context)
+        .await
+        .unwrap()
+        .resource()
+        .get_id()
+        .unwrap()
+        .to_string();
+    provider
+        .update_resource(
+            "User",
+            &id,
+            json!({"userName": "put.empty", "emails": []}),
+            None,
+            &context,
+        )
+        .await
+        .unwrap();
+    let json = stored(id).await;
+    assert_eq!(json["emails"], json!([]));
+    // Omitted on PUT means cleared: a PUT is a full replacement
+    assert!(json.get("phoneNumbers").is_none());
+
+    // PATCH leaves attributes it does not mention unchanged
+    let id = provider
+        .create_resource("User", with_emails("patch.absent"), &context)
+        .await
+        .unwrap()
+        .resource()
+        .get_id()
+        .unwrap()
+        .to_string();
+    let patch = json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+        "Operations": [{"op": "replace", "path": "displayName", "value": "Patched"}]
+    });
+    provider
+        .patch_resource("User", &id, &patch, None, &context)
+        .await
+        .unwrap();
+    let json = stored(id.clone()).await;
+    assert_eq!(json["emails"][0]["value"], "patch.absent@example.com");
+    assert_eq!(json["phoneNumbers"][0]["value"], "+1-555-0100");
+
+    // PATCH replace with an empty array clears to []
+    let patch = json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+        "Operations": [{"op": "replace", "path": "emails", "value": []}]
+    });
+    provider
+        .patch_resource("User", &id, &patch, None, &context)
+        .await
+        .unwrap();
+    let json = stored(id).await;
+    assert_eq!(json["emails"], json!([]));
+    assert_eq!(json["phoneNumbers"][0]["value"], "+1-555-0100");
+
+    // Unset multi-valued attributes are omitted, never serialized as null
+    let resource = scim_server::Resource::from_json(
+        "User".to_string(),
+        json!({"userName": "plain", "addresses": []}),
+    )
+    .unwrap();
+    let json = resource.to_json().unwrap();
+    assert_eq!(json["addresses"], json!([]));
+    assert!(json.get("emails").is_none());
+    assert!(json.get("phoneNumbers").is_none());
+}
+
+#[tokio::test]
+async fn test_meta_extra_fields_survive_create_and_update() {
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let context = RequestContext::with_generated_id();
+
+    let created = provider
+        .create_resource(
+            "User",
+            json!({"userName": "extra", "meta": {"idpSource": "okta", "created": "1999-01-01T00:00:00Z"}}),
+            &context,
+        )
+        .await
+        .unwrap();
+    let id = created.resource().get_id().unwrap().to_string();
+    let created_meta = created.resource().get_meta().unwrap().clone();
+    assert_eq!(created_meta.extra()["idpSource"], "okta");
+    // Server-managed fields are not taken from the client
+    assert_ne!(
+        created_meta.created().to_rfc3339(),
+        "1999-01-01T00:00:00+00:00"
+    );
+
+    // An update that omits meta keeps the extras
+    let updated = provider
+        .update_resource(
+            "User",
+            &id,
+            json!({"userName": "extra", "displayName": "Extra"}),
+            None,
+            &context,
+        )
+        .await
+        .unwrap();
+    let meta = updated.resource().get_meta().unwrap();
+    assert_eq!(meta.extra()["idpSource"], "okta");
+    assert_eq!(meta.created(), created_meta.created());
+
+    // An update that sends extras changes them, but not the server-managed fields
+    let updated = provider
+        .update_resource(
+            "User",
+            &id,
+            json!({
+                "userName": "extra",
+                "meta": {"idpSource": "azure", "created": "1999-01-01T00:00:00Z"}
+            }),
+            None,
+            &context,
+        )
+        .await
+        .unwrap();
+    let meta = updated.resource().get_meta().unwrap();
+    assert_eq!(meta.extra()["idpSource"], "azure");
+    assert_eq!(meta.created(), created_meta.created());
+}
+
+#[tokio::test]
+async fn test_noop_update_detection() {
+    let context = RequestContext::with_generated_id();
+    let data = create_test_user_data("noop.user");
+    let last_modified = |resource: &VersionedResource| {
+        resource
+            .resource()
+            .get_meta()
+            .map(|meta| meta.last_modified)
+    };
+
+    let provider =
+        StandardResourceProvider::new(InMemoryStorage::new()).with_noop_update_detection(true);
+    let user = provider
+        .create_resource("User", data.clone(), &context)
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+    // An unchanged PUT keeps the version and timestamp, even when conditional
+    let unchanged = provider
+        .update_resource(
+            "User",
+            user_id,
+            data.clone(),
+            Some(user.version()),
+            &context,
+        )
+        .await
+        .unwrap();
+    assert_eq!(unchanged.version(), user.version());
+    assert_eq!(last_modified(&unchanged), last_modified(&user));
+
+    // A PATCH that sets the current value is likewise a no-op
+    let patch = json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+        "Operations": [{"op": "replace", "path": "displayName", "value": "User noop.user"}]
+    });
+    let unchanged = provider
+        .patch_resource("User", user_id, &patch, Some(user.version()), &context)
+        .await
+        .unwrap();
+    assert_eq!(unchanged.version(), user.version());
+
+    // Real changes still advance the version
+    let mut changed = data.clone();
+    changed["displayName"] = json!("Changed");
+    let updated = provider
+        .update_resource("User", user_id, changed, Some(user.version()), &context)
+        .await
+        .unwrap();
+    assert_ne!(updated.version(), user.version());
+
+    // Without detection every PUT writes a new version
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let user = provider
+        .create_resource("User", data.clone(), &context)
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    let rewritten = provider
+        .update_resource("User", user_id, data, Some(user.version()), &context)
+        .await
+        .unwrap();
+    assert_ne!(last_modified(&rewritten), last_modified(&user));
+}
@@ -0,0 +1,505 @@
+//! StandardResourceProvider tests for externalId storage keys, lookup and upsert.
+
+use super::create_test_user_data;
+use scim_server::ResourceProvider;
+use scim_server::providers::{
+    ProviderError, StandardResourceProvider, StorageKeyPolicy, UpsertOutcome,
+};
+use scim_server::resource::version::ConditionalResult;
+use scim_server::resource::{RequestContext, TenantContext};
+use scim_server::storage::{InMemoryStorage, StorageKey, StorageProvider};
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_creates_under_one_external_id() {
+    let provider = StandardResourceProvider::new(SlowExistsStorage(InMemoryStorage::new()))
+        .with_storage_key_policy(StorageKeyPolicy::ExternalId);
+    let context = RequestContext::with_generated_id();
+
+    let creates = (0..8).map(|i| {
+        let provider = provider.clone();
+        let context = context.clone();
+        tokio::spawn(async move {
+            provider
+                .create_resource(
+                    "User",
+                    json!({"userName": format!("racer{}", i), "externalId": "hr-1"}),
+                    &context,
+                )
+                .await
+        })
+    });
+    let results = futures::future::join_all(creates).await;
+
+    // Exactly one create wins; the others see the key taken
+    let created = results
+        .iter()
+        .filter(|result| result.as_ref().unwrap().is_ok())
+        .count();
+    assert_eq!(created, 1);
+    let users = provider
+        .list_resources("User", None, &context)
+        .await
+        .unwrap();
+    assert_eq!(users.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_moves_to_one_external_id() {
+    let provider = StandardResourceProvider::new(SlowExistsStorage(InMemoryStorage::new()))
+        .with_storage_key_policy(StorageKeyPolicy::ExternalId);
+    let context = RequestContext::with_generated_id();
+    let mut ids = Vec::new();
+    for i in 0..4 {
+        let user = provider
+            .create_resource(
+                "User",
+                json!({"userName": format!("mover{}", i), "externalId": format!("hr-{}", i)}),
+                &context,
+            )
+            .await
+            .unwrap();
+        ids.push(user.resource().get_id().unwrap().to_string());
+    }
+
+    let moves = ids.iter().enumerate().map(|(i, id)| {
+        let provider = provider.clone();
+        let context = context.clone();
+        let id = id.clone();
+        tokio::spawn(async move {
+            provider
+                .update_resource(
+                    "User",
+                    &id,
+                    json!({"userName": format!("mover{}", i), "externalId": "shared"}),
+                    None,
+                    &context,
+                )
+                .await
+        })
+    });
+    let results = futures::future::join_all(moves).await;
+
+    // Exactly one move wins and no resource is overwritten
+    let moved = results
+        .iter()
+        .filter(|result| result.as_ref().unwrap().is_ok())
+        .count();
+    assert_eq!(moved, 1);
+    let users = provider
+        .list_resources("User", None, &context)
+        .await
+        .unwrap();
+    assert_eq!(users.len(), 4);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_writes_follow_a_moving_resource() {
+    let provider = StandardResourceProvider::new(SlowExistsStorage(InMemoryStorage::new()))
+        .with_storage_key_policy(StorageKeyPolicy::ExternalId);
+    let context = RequestContext::with_generated_id();
+    let user = provider
+        .create_resource(
+            "User",
+            json!({"userName": "wanderer", "externalId": "m"}),
+            &context,
+        )
+        .await
+        .unwrap();
+    let id = user.resource().get_id().unwrap().to_string();
+
+    // Each patch moves the resource to a key sorting before or after the
+    // current one; writers queued behind a move find it at its new key
+    let patches = ["a", "z", "b", "y", "c", "x"].map(|external_id| {
+        let provider = provider.clone();
+        let context = context.clone();
+        let id = id.clone();
+        tokio::spawn(async move {
+            let patch = json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{"op": "replace", "path": "externalId", "value": external_id}]
+            });
+            provider
+                .patch_resource("User", &id, &patch, None, &context)
+                .await
+        })
+    });
+    let results = tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        futures::future::join_all(patches),
+    )
+    .await
+    .expect("moves deadlocked");
+    for result in results {
+        result.unwrap().unwrap();
+    }
+
+    let users = provider
+        .list_resources("User", None, &context)
+        .await
+        .unwrap();
+    assert_eq!(users.len(), 1);
+    provider
+        .delete_resource("User", &id, None, &context)
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_swapping_external_ids_does_not_deadlock() {
+    let provider = StandardResourceProvider::new(SlowExistsStorage(InMemoryStorage::new()))
+        .with_storage_key_policy(StorageKeyPolicy::ExternalId);
+    let context = RequestContext::with_generated_id();
+    let mut ids = Vec::new();
+    for external_id in ["a", "b"] {
+        let user = provider
+            .create_resource(
+                "User",
+                json!({"userName": format!("user-{}", external_id), "externalId": external_id}),
+                &context,
+            )
+            .await
+            .unwrap();
+        ids.push(user.resource().get_id().unwrap().to_string());
+    }
+
+    let swaps = ids.iter().zip(["b", "a"]).map(|(id, external_id)| {
+        let provider = provider.clone();
+        let context = context.clone();
+        let id = id.clone();
+        tokio::spawn(async move {
+            let patch = json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{"op": "replace", "path": "externalId", "value": external_id}]
+            });
+            provider
+                .patch_resource("User", &id, &patch, None, &context)
+                .await
+        })
+    });
+    let results = tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        futures::future::join_all(swaps),
+    )
+    .await
+    .expect("swap deadlocked");
+
+    // Each target key is taken by the other resource
+    for result in results {
+        assert!(matches!(
+            result.unwrap(),
+            Err(ProviderError::DuplicateAttribute { .. })
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_external_id_storage_keys() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage.clone())
+        .with_storage_key_policy(StorageKeyPolicy::ExternalId);
+    let context = RequestContext::with_generated_id();
+    let user_data = |username: &str, external_id: &str| json!({"userName": username, "externalId": external_id});
+
+    let missing = provider
+        .create_resource("User", create_test_user_data("no.external"), &context)
+        .await;
+    assert!(matches!(missing, Err(ProviderError::InvalidInput { .. })));
+
+    let user = provider
+        .create_resource("User", user_data("keyed", "hr-1"), &context)
+        .await
+        .unwrap();
+    let id = user.resource().get_id().unwrap().to_string();
+    assert_ne!(id, "hr-1");
+    let location = user
+        .resource()
+        .get_meta()
+        .unwrap()
+        .location
+        .clone()
+        .unwrap();
+    assert!(location.ends_with(&format!("/User/{}", id)));
+
+    // Stored under the externalId, with the SCIM id kept as data
+    let stored = storage
+        .get(StorageKey::new("default", "User", "hr-1"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored["id"], id.as_str());
+    assert!(
+        provider
+            .get_resource("User", &id, &context)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert!(
+        provider
+            .resource_exists("User", &id, &context)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !provider
+            .resource_exists("User", "hr-1", &context)
+            .await
+            .unwrap()
+    );
+
+    // Both the key and unique attributes report the SCIM id of the holder
+    match provider
+        .create_resource("User", user_data("other", "hr-1"), &context)
+        .await
+    {
+        Err(ProviderError::DuplicateAttribute {
+            attribute,
+            existing_id,
+            ..
+        }) => {
+            assert_eq!(attribute, "externalId");
+            assert_eq!(existing_id.as_deref(), Some(id.as_str()));
+        }
+        other => panic!("expected duplicate externalId, got {:?}", other),
+    }
+    match provider
+        .create_resource("User", user_data("keyed", "hr-2"), &context)
+        .await
+    {
+        Err(ProviderError::DuplicateAttribute { existing_id, .. }) => {
+            assert_eq!(existing_id.as_deref(), Some(id.as_str()))
+        }
+        other => panic!("expected duplicate userName, got {:?}", other),
+    }
+
+    // Conditional updates resolve the SCIM id, and a new externalId moves the key
+    let stale = user.version().clone();
+    let updated = match provider
+        .update_resource_conditional("User", &id, user_data("keyed", "hr-9"), &stale, &context)
+        .await
+        .unwrap()
+    {
+        ConditionalResult::Success(updated) => updated,
+        other => panic!("expected success, got {:?}", other),
+    };
+    assert_eq!(updated.resource().get_id(), Some(id.as_str()));
+    assert!(
+        !storage
+            .exists(StorageKey::new("default", "User", "hr-1"))
+            .await
+            .unwrap()
+    );
+    assert!(matches!(
+        provider
+            .update_resource_conditional("User", &id, user_data("keyed", "hr-1"), &stale, &context)
+            .await
+            .unwrap(),
+        ConditionalResult::VersionMismatch(_)
+    ));
+
+    let patch = json!({"Operations": [{"op": "replace", "path": "displayName", "value": "Keyed"}]});
+    let patched = provider
+        .patch_resource("User", &id, &patch, Some(updated.version()), &context)
+        .await
+        .unwrap();
+    let stored = storage
+        .get(StorageKey::new("default", "User", "hr-9"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored["displayName"], "Keyed");
+
+    provider
+        .delete_resource("User", &id, Some(patched.version()), &context)
+        .await
+        .unwrap();
+    assert_eq!(
+        storage
+            .count(StorageKey::prefix("default", "User"))
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_get_by_external_id_and_upsert() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage.clone());
+    let tenant_a = RequestContext::with_tenant_generated_id(TenantContext::new(
+        "tenant-a".to_string(),
+        "client-a".to_string(),
+    ));
+    let tenant_b = RequestContext::with_tenant_generated_id(TenantContext::new(
+        "tenant-b".to_string(),
+        "client-b".to_string(),
+    ));
+
+    // Upsert creates when no resource carries the externalId
+    let (created, outcome) = provider
+        .upsert_by_external_id(
+            "User",
+            json!({"userName": "jdoe", "externalId": "hr-42", "displayName": "John"}),
+            &tenant_a,
+        )
+        .await
+        .unwrap();
+    assert_eq!(outcome, UpsertOutcome::Created);
+    let id = created.resource().get_id().unwrap().to_string();
+
+    let found = provider
+        .get_by_external_id("User", "hr-42", &tenant_a)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.get_id(), Some(id.as_str()));
+
+    // Lookups are tenant-scoped
+    assert!(
+        provider
+            .get_by_external_id("User", "hr-42", &tenant_b)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // Upsert replaces the matching resource
+    let (updated, outcome) = provider
+        .upsert_by_external_id(
+            "User",
+            json!({"userName": "jdoe", "externalId": "hr-42", "displayName": "Johnny"}),
+            &tenant_a,
+        )
+        .await
+        .unwrap();
+    assert_eq!(outcome, UpsertOutcome::Updated);
+    assert_eq!(updated.resource().get_id(), Some(id.as_str()));
+    assert_eq!(
+        updated.resource().get_attribute("displayName"),
+        Some(&json!("Johnny"))
+    );
+    assert_eq!(
+        provider
+            .list_resources("User", None, &tenant_a)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // Upsert needs an externalId to match on
+    let result = provider
+        .upsert_by_external_id("User", json!({"userName": "nobody"}), &tenant_a)
+        .await;
+    assert!(matches!(result, Err(ProviderError::InvalidInput { .. })));
+
+    // A second resource cannot take the externalId
+    let result = provider
+        .create_resource(
+            "User",
+            json!({"userName": "jdoe2", "externalId": "hr-42"}),
+            &tenant_a,
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(ProviderError::DuplicateAttribute { ref attribute, ref existing_id, .. })
+            if attribute == "externalId" && existing_id.as_deref() == Some(id.as_str())
+    ));
+
+    // Duplicates already in storage are reported rather than picking one
+    storage
+        .put(
+            StorageKey::new("tenant-a", "User", "legacy"),
+            json!({"id": "legacy", "userName": "legacy", "externalId": "hr-42"}),
+        )
+        .await
+        .unwrap();
+    let result = provider
+        .get_by_external_id("User", "hr-42", &tenant_a)
+        .await;
+    assert!(matches!(
+        result,
+        Err(ProviderError::DuplicateAttribute { .. })
+    ));
+}
+
+/// In-memory storage whose existence checks are slow, widening the window
+/// between a check and the write that follows it.
+#[derive(Clone)]
+struct SlowExistsStorage(InMemoryStorage);
+
+impl scim_server::storage::StorageProvider for SlowExistsStorage {
+    type Error = scim_server::storage::StorageError;
+
+    async fn put(
+        &self,
+        key: scim_server::storage::StorageKey,
+        data: serde_json::Value,
+    ) -> Result<serde_json::Value, Self::Error> {
+        self.0.put(key, data).await
+    }
+
+    async fn get(
+        &self,
+        key: scim_server::storage::StorageKey,
+    ) -> Result<Option<serde_json::Value>, Self::Error> {
+        self.0.get(key).await
+    }
+
+    async fn delete(&self, key: scim_server::storage::StorageKey) -> Result<bool, Self::Error> {
+        self.0.delete(key).await
+    }
+
+    async fn list(
+        &self,
+        prefix: scim_server::storage::StoragePrefix,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(scim_server::storage::StorageKey, serde_json::Value)>, Self::Error> {
+        self.0.list(prefix, offset, limit).await
+    }
+
+    async fn find_by_attribute(
+        &self,
+        prefix: scim_server::storage::StoragePrefix,
+        attribute: &str,
+        value: &str,
+    ) -> Result<Vec<(scim_server::storage::StorageKey, serde_json::Value)>, Self::Error> {
+        self.0.find_by_attribute(prefix, attribute, value).await
+    }
+
+    async fn exists(&self, key: scim_server::storage::StorageKey) -> Result<bool, Self::Error> {
+        let exists = self.0.exists(key).await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        exists
+    }
+
+    async fn count(
+        &self,
+        prefix: scim_server::storage::StoragePrefix,
+    ) -> Result<usize, Self::Error> {
+        self.0.count(prefix).await
+    }
+
+    async fn list_tenants(&self) -> Result<Vec<String>, Self::Error> {
+        self.0.list_tenants().await
+    }
+
+    async fn list_resource_types(&self, tenant_id: &str) -> Result<Vec<String>, Self::Error> {
+        self.0.list_resource_types(tenant_id).await
+    }
+
+    async fn list_all_resource_types(&self) -> Result<Vec<String>, Self::Error> {
+        self.0.list_all_resource_types().await
+    }
+
+    async fn clear(&self) -> Result<(), Self::Error> {
+        self.0.clear().await
+    }
+
+    async fn stats(&self) -> Result<scim_server::storage::StorageStats, Self::Error> {
+        self.0.stats().await
+    }
+}
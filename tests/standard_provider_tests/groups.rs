@@ -0,0 +1,250 @@
+//! StandardResourceProvider tests for Group membership and reference checks.
+
+use scim_server::ResourceProvider;
+use scim_server::providers::StandardResourceProvider;
+use scim_server::resource::RequestContext;
+use scim_server::storage::InMemoryStorage;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_import_defers_reference_checks() {
+    use scim_server::ScimServer;
+    use scim_server::error::ValidationError;
+    use scim_server::resource::ScimOperation;
+    use scim_server::resource_handlers::{
+        create_group_resource_handler, create_user_resource_handler,
+    };
+
+    let mut server =
+        ScimServer::new(StandardResourceProvider::new(InMemoryStorage::new())).unwrap();
+    let registry = scim_server::SchemaRegistry::new().unwrap();
+    server
+        .register_resource_type(
+            "User",
+            create_user_resource_handler(registry.get_user_schema().clone()),
+            vec![ScimOperation::Create, ScimOperation::Read],
+        )
+        .unwrap();
+    server
+        .register_resource_type(
+            "Group",
+            create_group_resource_handler(registry.get_group_schema().clone()),
+            vec![ScimOperation::Create, ScimOperation::Read],
+        )
+        .unwrap();
+    let context = RequestContext::with_generated_id();
+
+    // The group comes before the user it references
+    let report = server
+        .import_resources(
+            vec![
+                (
+                    "Group".to_string(),
+                    json!({
+                        "displayName": "Engineering",
+                        "members": [
+                            {"value": "u-1", "type": "User"},
+                            {"value": "u-missing", "type": "User"},
+                            {"value": "g-missing"}
+                        ]
+                    }),
+                ),
+                (
+                    "User".to_string(),
+                    json!({"id": "u-1", "userName": "alice"}),
+                ),
+                ("User".to_string(), json!({"userName": 42})),
+            ],
+            &context,
+        )
+        .await
+        .unwrap();
+
+    assert!(!report.is_ok());
+    assert_eq!(report.created.len(), 2);
+
+    // Non-referential validation still fails the resource up front
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].index, 2);
+
+    // All dangling references are reported together
+    let dangling: Vec<&str> = report
+        .dangling_references
+        .iter()
+        .map(|dangling| match &dangling.error {
+            ValidationError::BrokenReference {
+                attribute,
+                reference,
+            } => {
+                assert_eq!(attribute, "members");
+                assert_eq!(dangling.resource_type, "Group");
+                reference.as_str()
+            }
+            other => panic!("Expected BrokenReference, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(dangling, ["u-missing", "g-missing"]);
+}
+
+#[tokio::test]
+async fn test_derived_group_membership() {
+    use scim_server::resource::{ListQuery, ScimOperation};
+    use scim_server::resource_handlers::{
+        create_group_resource_handler, create_user_resource_handler,
+    };
+    use scim_server::{AuthorizationPolicy, GroupMembership, ScimError, ScimServerBuilder};
+
+    let storage = InMemoryStorage::new();
+    let context = RequestContext::with_generated_id();
+    let build = |membership: GroupMembership| {
+        let mut server = ScimServerBuilder::new(StandardResourceProvider::new(storage.clone()))
+            .with_group_membership(membership)
+            .build()
+            .unwrap();
+        let registry = scim_server::SchemaRegistry::new().unwrap();
+        let operations = vec![
+            ScimOperation::Create,
+            ScimOperation::Read,
+            ScimOperation::List,
+        ];
+        server
+            .register_resource_type(
+                "User",
+                create_user_resource_handler(registry.get_user_schema().clone()),
+                operations.clone(),
+            )
+            .unwrap();
+        server
+            .register_resource_type(
+                "Group",
+                create_group_resource_handler(registry.get_group_schema().clone()),
+                operations,
+            )
+            .unwrap();
+        server
+    };
+
+    // u-1 is in g-a, which is in g-b, which is in g-c, which is in g-a
+    let server = build(GroupMembership::Stored);
+    server
+        .create_resource("User", json!({"id": "u-1", "userName": "alice"}), &context)
+        .await
+        .unwrap();
+    for (id, member, member_type) in [
+        ("g-a", "u-1", "User"),
+        ("g-b", "g-a", "Group"),
+        ("g-c", "g-b", "Group"),
+    ] {
+        server
+            .create_resource(
+                "Group",
+                json!({
+                    "id": id,
+                    "displayName": id,
+                    "members": [{"value": member, "type": member_type}]
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+    }
+    // g-a also contains g-c, closing the cycle
+    server
+        .provider()
+        .update_resource(
+            "Group",
+            "g-a",
+            json!({
+                "id": "g-a",
+                "displayName": "g-a",
+                "members": [
+                    {"value": "u-1", "type": "User"},
+                    {"value": "g-c", "type": "Group"}
+                ]
+            }),
+            None,
+            &context,
+        )
+        .await
+        .unwrap();
+
+    async fn groups(
+        server: &scim_server::ScimServer<StandardResourceProvider<InMemoryStorage>>,
+        context: &RequestContext,
+    ) -> Vec<String> {
+        let user = server
+            .get_resource("User", "u-1", context)
+            .await
+            .unwrap()
+            .unwrap();
+        user.get_attribute("groups")
+            .and_then(|groups| groups.as_array())
+            .into_iter()
+            .flatten()
+            .map(|group| format!("{}:{}", group["value"], group["type"]).replace('"', ""))
+            .collect()
+    }
+
+    assert!(groups(&server, &context).await.is_empty());
+    assert_eq!(
+        groups(&build(GroupMembership::Direct), &context).await,
+        ["g-a:direct"]
+    );
+    assert_eq!(
+        groups(&build(GroupMembership::Nested { max_depth: 2 }), &context).await,
+        ["g-a:direct", "g-b:indirect"]
+    );
+    assert_eq!(
+        groups(&build(GroupMembership::Nested { max_depth: 10 }), &context).await,
+        ["g-a:direct", "g-b:indirect", "g-c:indirect"]
+    );
+
+    // Callers that may not list Groups do not see them through a User
+    struct DenyGroupListing;
+    impl AuthorizationPolicy for DenyGroupListing {
+        fn authorize(
+            &self,
+            _context: &RequestContext,
+            operation: &ScimOperation,
+            resource_type: &str,
+            resource_id: Option<&str>,
+        ) -> Result<(), ScimError> {
+            if *operation == ScimOperation::List && resource_type == "Group" {
+                return Err(ScimError::authorization_denied(
+                    operation,
+                    resource_type,
+                    resource_id,
+                    "groups are private",
+                ));
+            }
+            Ok(())
+        }
+    }
+    let mut server = build(GroupMembership::Direct);
+    server.set_authorization_policy(DenyGroupListing);
+    assert!(groups(&server, &context).await.is_empty());
+
+    // Excluding groups skips derivation
+    let server = build(GroupMembership::Direct);
+    let query = ListQuery {
+        excluded_attributes: vec!["groups".to_string()],
+        ..Default::default()
+    };
+    let users = server
+        .list_resources_with_query("User", Some(&query), &context)
+        .await
+        .unwrap();
+    assert!(users[0].get_attribute("groups").is_none());
+    let users = server.list_resources("User", &context).await.unwrap();
+    assert_eq!(
+        users[0].get_attribute("groups").unwrap()[0]["display"],
+        "g-a"
+    );
+
+    assert!(
+        ScimServerBuilder::new(StandardResourceProvider::new(InMemoryStorage::new()))
+            .with_group_membership(GroupMembership::Nested { max_depth: 0 })
+            .build()
+            .is_err()
+    );
+}
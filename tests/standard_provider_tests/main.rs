@@ -0,0 +1,23 @@
+//! Unit tests for the StandardResourceProvider.
+//!
+//! These tests verify StandardResourceProvider<InMemoryStorage> functionality
+//! and ensure complete behavioral compatibility with SCIM specifications.
+
+mod conditional;
+mod crud;
+mod external_id;
+mod groups;
+mod patch;
+mod queries;
+mod storage;
+mod tenancy;
+
+use serde_json::json;
+
+fn create_test_user_data(username: &str) -> serde_json::Value {
+    json!({
+        "userName": username,
+        "displayName": format!("User {}", username),
+        "active": true
+    })
+}
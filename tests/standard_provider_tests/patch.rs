@@ -0,0 +1,256 @@
+//! StandardResourceProvider tests for PATCH.
+
+use super::create_test_user_data;
+use scim_server::ResourceProvider;
+use scim_server::providers::{ProviderError, StandardResourceProvider};
+use scim_server::resource::version::RawVersion;
+use scim_server::resource::{
+    ListQuery, RequestContext, TenantContext, TenantPermissions, VersionedResource,
+};
+use scim_server::storage::InMemoryStorage;
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_patches_are_not_lost() {
+    let provider = Arc::new(StandardResourceProvider::new(InMemoryStorage::new()));
+    let context = RequestContext::with_generated_id();
+    let user = provider
+        .create_resource(
+            "User",
+            json!({"userName": "patched", "emails": []}),
+            &context,
+        )
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap().to_string();
+
+    let patches = (0..20).map(|i| {
+        let provider = Arc::clone(&provider);
+        let context = context.clone();
+        let user_id = user_id.clone();
+        tokio::spawn(async move {
+            let patch = json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{"op": "add", "path": format!("attribute{}", i), "value": i}]
+            });
+            provider
+                .patch_resource("User", &user_id, &patch, None, &context)
+                .await
+                .unwrap();
+        })
+    });
+    for patch in patches.collect::<Vec<_>>() {
+        patch.await.unwrap();
+    }
+
+    let stored = provider
+        .get_resource("User", &user_id, &context)
+        .await
+        .unwrap()
+        .unwrap();
+    let stored = stored.resource().to_json().unwrap();
+    for i in 0..20 {
+        assert_eq!(stored[format!("attribute{}", i)], json!(i));
+    }
+}
+
+#[tokio::test]
+async fn test_patch_resource_checked() {
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let context = RequestContext::with_generated_id();
+    let user = provider
+        .create_resource("User", create_test_user_data("checked"), &context)
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap();
+    let patch = json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+        "Operations": [{"op": "remove", "path": "displayName"}]
+    });
+
+    // The check sees the stored resource and the patched result; rejecting
+    // it leaves the resource as it was
+    let rejected = provider
+        .patch_resource_checked(
+            "User",
+            user_id,
+            &patch,
+            None,
+            |current, patched| match patched.get("displayName") {
+                None => Err(current["displayName"].clone()),
+                Some(_) => Ok(()),
+            },
+            &context,
+        )
+        .await
+        .unwrap();
+    assert_eq!(rejected.unwrap_err(), json!("User checked"));
+    let stored = provider
+        .get_resource("User", user_id, &context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.version(), user.version());
+    assert!(stored.resource().get_attribute("displayName").is_some());
+
+    let accepted = provider
+        .patch_resource_checked(
+            "User",
+            user_id,
+            &patch,
+            None,
+            |_, _| Ok::<(), ()>(()),
+            &context,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(accepted.resource().get_attribute("displayName").is_none());
+}
+
+/// Delegates everything but PATCH, which uses the default `patch_via_update`.
+struct UpdateOnlyProvider(StandardResourceProvider<InMemoryStorage>);
+
+impl ResourceProvider for UpdateOnlyProvider {
+    type Error = ProviderError;
+
+    async fn create_resource(
+        &self,
+        resource_type: &str,
+        data: serde_json::Value,
+        context: &RequestContext,
+    ) -> Result<VersionedResource, ProviderError> {
+        self.0.create_resource(resource_type, data, context).await
+    }
+
+    async fn get_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        context: &RequestContext,
+    ) -> Result<Option<VersionedResource>, ProviderError> {
+        self.0.get_resource(resource_type, id, context).await
+    }
+
+    async fn update_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        data: serde_json::Value,
+        expected_version: Option<&RawVersion>,
+        context: &RequestContext,
+    ) -> Result<VersionedResource, ProviderError> {
+        self.0
+            .update_resource(resource_type, id, data, expected_version, context)
+            .await
+    }
+
+    async fn delete_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        expected_version: Option<&RawVersion>,
+        context: &RequestContext,
+    ) -> Result<(), ProviderError> {
+        self.0
+            .delete_resource(resource_type, id, expected_version, context)
+            .await
+    }
+
+    async fn list_resources(
+        &self,
+        resource_type: &str,
+        query: Option<&ListQuery>,
+        context: &RequestContext,
+    ) -> Result<Vec<VersionedResource>, ProviderError> {
+        self.0.list_resources(resource_type, query, context).await
+    }
+
+    async fn find_resources_by_attribute(
+        &self,
+        resource_type: &str,
+        attribute_name: &str,
+        attribute_value: &str,
+        context: &RequestContext,
+    ) -> Result<Vec<VersionedResource>, ProviderError> {
+        self.0
+            .find_resources_by_attribute(resource_type, attribute_name, attribute_value, context)
+            .await
+    }
+
+    async fn resource_exists(
+        &self,
+        resource_type: &str,
+        id: &str,
+        context: &RequestContext,
+    ) -> Result<bool, ProviderError> {
+        self.0.resource_exists(resource_type, id, context).await
+    }
+}
+
+#[tokio::test]
+async fn test_patch_via_update() {
+    let provider = UpdateOnlyProvider(StandardResourceProvider::new(InMemoryStorage::new()));
+    let context = RequestContext::with_generated_id();
+    let user = provider
+        .create_resource("User", create_test_user_data("via.update"), &context)
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap();
+    let patch = json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+        "Operations": [{"op": "replace", "path": "displayName", "value": "Patched"}]
+    });
+
+    let patched = provider
+        .patch_resource("User", user_id, &patch, Some(user.version()), &context)
+        .await
+        .unwrap();
+    assert_eq!(
+        patched.resource().get_attribute("displayName"),
+        Some(&json!("Patched"))
+    );
+    assert_eq!(patched.resource().get_username(), Some("via.update"));
+
+    // A stale version is rejected rather than overwriting the newer resource
+    let stale = provider
+        .patch_resource("User", user_id, &patch, Some(user.version()), &context)
+        .await;
+    assert!(matches!(stale, Err(ProviderError::VersionConflict { .. })));
+
+    let missing = provider
+        .patch_resource("User", "missing", &patch, None, &context)
+        .await;
+    assert!(matches!(missing, Err(ProviderError::NotFound { .. })));
+}
+
+#[tokio::test]
+async fn test_patch_needs_update_permission_only() {
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let tenant = TenantContext::new("patch-tenant".to_string(), "client".to_string());
+    let context = RequestContext::with_tenant_generated_id(tenant.clone());
+    let user = provider
+        .create_resource("User", create_test_user_data("write.only"), &context)
+        .await
+        .unwrap();
+    let user_id = user.resource().get_id().unwrap();
+
+    let write_only =
+        RequestContext::with_tenant_generated_id(tenant.with_permissions(TenantPermissions {
+            can_read: false,
+            ..TenantPermissions::default()
+        }));
+    let patch = json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+        "Operations": [{"op": "replace", "path": "displayName", "value": "Patched"}]
+    });
+    let patched = provider
+        .patch_resource("User", user_id, &patch, Some(user.version()), &write_only)
+        .await
+        .unwrap();
+    assert_eq!(
+        patched.resource().get_attribute("displayName"),
+        Some(&json!("Patched"))
+    );
+}
@@ -0,0 +1,557 @@
+//! StandardResourceProvider tests for list queries, ids and delete-by-filter.
+
+use super::create_test_user_data;
+use chrono::{SubsecRound, Utc};
+use scim_server::ResourceProvider;
+use scim_server::providers::{ProviderError, StandardResourceProvider, StorageKeyPolicy};
+use scim_server::resource::{
+    ListQuery, RequestContext, SortOrder, TenantContext, TimestampPrecision, VersionedResource,
+};
+use scim_server::storage::{InMemoryStorage, SqliteStorage, StorageKey, StorageProvider};
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_delete_by_filter_is_tenant_scoped_with_dry_run() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+
+    let tenant_a = RequestContext::with_tenant_generated_id(TenantContext::new(
+        "tenant-a".to_string(),
+        "client-a".to_string(),
+    ));
+    let tenant_b = RequestContext::with_tenant_generated_id(TenantContext::new(
+        "tenant-b".to_string(),
+        "client-b".to_string(),
+    ));
+
+    for (context, username, department) in [
+        (&tenant_a, "a.sales.1", "Sales"),
+        (&tenant_a, "a.sales.2", "sales"),
+        (&tenant_a, "a.eng.1", "Engineering"),
+        (&tenant_b, "b.sales.1", "Sales"),
+    ] {
+        provider
+            .create_resource(
+                "User",
+                json!({
+                    "userName": username,
+                    "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+                        "department": department
+                    }
+                }),
+                context,
+            )
+            .await
+            .unwrap();
+    }
+
+    let filter =
+        r#"urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:department eq "Sales""#;
+
+    // Dry run reports the count without deleting
+    let preview = provider
+        .delete_by_filter_dry_run("User", filter, &tenant_a)
+        .await
+        .unwrap();
+    assert_eq!(preview, 2);
+    assert_eq!(
+        provider
+            .list_resources("User", None, &tenant_a)
+            .await
+            .unwrap()
+            .len(),
+        3
+    );
+
+    let deleted = provider
+        .delete_by_filter("User", filter, &tenant_a)
+        .await
+        .unwrap();
+    assert_eq!(deleted, 2);
+
+    let remaining = provider
+        .list_resources("User", None, &tenant_a)
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].resource().get_username(), Some("a.eng.1"));
+
+    // Other tenants are untouched
+    assert_eq!(
+        provider
+            .list_resources("User", None, &tenant_b)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // caseExact attributes match case-sensitively, as in a filtered list
+    for (username, external_id) in [("a.hr.1", "HR-1"), ("a.hr.2", "hr-1")] {
+        provider
+            .create_resource(
+                "User",
+                json!({"userName": username, "externalId": external_id}),
+                &tenant_a,
+            )
+            .await
+            .unwrap();
+    }
+    let deleted = provider
+        .delete_by_filter("User", r#"externalId eq "hr-1""#, &tenant_a)
+        .await
+        .unwrap();
+    assert_eq!(deleted, 1);
+    assert!(
+        provider
+            .get_by_external_id("User", "HR-1", &tenant_a)
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    // Invalid filters are rejected
+    let result = provider
+        .delete_by_filter("User", "department eq", &tenant_a)
+        .await;
+    assert!(matches!(result, Err(ProviderError::QueryError { .. })));
+}
+
+#[tokio::test]
+async fn test_list_query_filter_sort_pagination_and_projection() {
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let context = RequestContext::with_generated_id();
+
+    for (user_name, active) in [
+        ("carol", true),
+        ("alice", true),
+        ("dave", false),
+        ("bob", true),
+    ] {
+        provider
+            .create_resource(
+                "User",
+                json!({
+                    "userName": user_name,
+                    "displayName": format!("User {}", user_name),
+                    "active": active
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+    }
+
+    let user_names = |resources: &[scim_server::resource::versioned::VersionedResource]| {
+        resources
+            .iter()
+            .map(|r| r.resource().get_username().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // Filter then sort
+    let query = ListQuery::new()
+        .with_filter("active eq true".to_string())
+        .with_sort_by("userName".to_string());
+    let resources = provider
+        .list_resources("User", Some(&query), &context)
+        .await
+        .unwrap();
+    assert_eq!(user_names(&resources), ["alice", "bob", "carol"]);
+
+    // Pagination applies after filtering and sorting
+    let query = query
+        .with_sort_order(SortOrder::Descending)
+        .with_start_index(2)
+        .with_count(1);
+    let resources = provider
+        .list_resources("User", Some(&query), &context)
+        .await
+        .unwrap();
+    assert_eq!(user_names(&resources), ["bob"]);
+
+    // Counting ignores pagination, sorting and projection
+    let count = provider
+        .count_resources("User", Some(&query), &context)
+        .await
+        .unwrap();
+    assert_eq!(count, 3);
+    let count = provider
+        .count_resources("User", None, &context)
+        .await
+        .unwrap();
+    assert_eq!(count, 4);
+
+    // Projection keeps only the requested attributes plus id, schemas and meta
+    let query = ListQuery::new()
+        .with_filter(r#"userName eq "dave""#.to_string())
+        .with_attribute("userName".to_string());
+    let resources = provider
+        .list_resources("User", Some(&query), &context)
+        .await
+        .unwrap();
+    assert_eq!(resources.len(), 1);
+    let json = resources[0].resource().to_json().unwrap();
+    assert_eq!(json["userName"], "dave");
+    assert!(json.get("displayName").is_none());
+    assert!(json.get("active").is_none());
+    assert!(json.get("id").is_some());
+
+    // The filter sees attributes the projection then removes
+    let query = ListQuery::new()
+        .with_filter("active eq false".to_string())
+        .with_excluded_attribute("active".to_string());
+    let resources = provider
+        .list_resources("User", Some(&query), &context)
+        .await
+        .unwrap();
+    assert_eq!(user_names(&resources), ["dave"]);
+    assert!(resources[0].resource().get_attribute("active").is_none());
+
+    // Invalid filters are rejected
+    let query = ListQuery::new().with_filter("userName eq".to_string());
+    let result = provider
+        .list_resources("User", Some(&query), &context)
+        .await;
+    assert!(matches!(result, Err(ProviderError::QueryError { .. })));
+    let result = provider
+        .count_resources("User", Some(&query), &context)
+        .await;
+    assert!(matches!(result, Err(ProviderError::QueryError { .. })));
+}
+
+#[tokio::test]
+async fn test_list_ids() {
+    let tenant = |id: &str| {
+        RequestContext::with_tenant_generated_id(TenantContext::new(
+            id.to_string(),
+            "client".to_string(),
+        ))
+    };
+
+    for policy in [StorageKeyPolicy::Id, StorageKeyPolicy::ExternalId] {
+        let provider =
+            StandardResourceProvider::new(InMemoryStorage::new()).with_storage_key_policy(policy);
+        let mut created = Vec::new();
+        for (username, active) in [("alice", true), ("bob", false), ("carol", true)] {
+            let user = provider
+                .create_resource(
+                    "User",
+                    json!({"userName": username, "externalId": username, "active": active}),
+                    &tenant("acme"),
+                )
+                .await
+                .unwrap();
+            created.push(user.resource().get_id().unwrap().to_string());
+        }
+        provider
+            .create_resource(
+                "User",
+                json!({"userName": "dave", "externalId": "dave"}),
+                &tenant("globex"),
+            )
+            .await
+            .unwrap();
+
+        // Scoped to the tenant, with the SCIM ids whatever the storage keys
+        let mut ids = provider
+            .list_ids("User", None, &tenant("acme"))
+            .await
+            .unwrap();
+        ids.sort();
+        let mut expected = created.clone();
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        // Filters apply; pagination does not
+        let query = ListQuery::new()
+            .with_filter("active eq true".to_string())
+            .with_count(1);
+        let mut ids = provider
+            .list_ids("User", Some(&query), &tenant("acme"))
+            .await
+            .unwrap();
+        ids.sort();
+        let mut expected = vec![created[0].clone(), created[2].clone()];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        let query = ListQuery::new().with_filter("userName eq".to_string());
+        let result = provider
+            .list_ids("User", Some(&query), &tenant("acme"))
+            .await;
+        assert!(matches!(result, Err(ProviderError::QueryError { .. })));
+    }
+
+    // Backends enumerate keys without their data
+    let storage = SqliteStorage::new_in_memory().await.unwrap();
+    for id in ["b", "a"] {
+        storage
+            .put(StorageKey::new("acme", "User", id), json!({"id": id}))
+            .await
+            .unwrap();
+    }
+    let keys = storage
+        .list_keys(StorageKey::prefix("acme", "User"))
+        .await
+        .unwrap();
+    assert_eq!(
+        keys,
+        [
+            StorageKey::new("acme", "User", "a"),
+            StorageKey::new("acme", "User", "b")
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_list_modified_since() {
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let context = RequestContext::with_generated_id();
+    let pause = || tokio::time::sleep(std::time::Duration::from_millis(5));
+
+    let early = provider
+        .create_resource("User", create_test_user_data("early"), &context)
+        .await
+        .unwrap();
+    let early_id = early.resource().get_id().unwrap().to_string();
+    pause().await;
+    let checkpoint = Utc::now();
+    pause().await;
+
+    provider
+        .create_resource("User", create_test_user_data("late"), &context)
+        .await
+        .unwrap();
+    pause().await;
+    provider
+        .update_resource(
+            "User",
+            &early_id,
+            create_test_user_data("early.updated"),
+            None,
+            &context,
+        )
+        .await
+        .unwrap();
+
+    let user_names = |resources: Vec<VersionedResource>| {
+        resources
+            .iter()
+            .map(|r| r.resource().get_username().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // Oldest modification first
+    let modified = provider
+        .list_modified_since("User", checkpoint, None, &context)
+        .await
+        .unwrap();
+    assert_eq!(user_names(modified), ["late", "early.updated"]);
+
+    // Combined with a filter and pagination
+    let query = ListQuery::new().with_filter(r#"userName sw "early""#.to_string());
+    let modified = provider
+        .list_modified_since("User", checkpoint, Some(&query), &context)
+        .await
+        .unwrap();
+    assert_eq!(user_names(modified), ["early.updated"]);
+
+    let query = ListQuery::new().with_count(1);
+    let modified = provider
+        .list_modified_since("User", checkpoint, Some(&query), &context)
+        .await
+        .unwrap();
+    assert_eq!(user_names(modified), ["late"]);
+
+    // Tenant-scoped
+    let other_tenant = RequestContext::with_tenant_generated_id(TenantContext::new(
+        "tenant-b".to_string(),
+        "client-b".to_string(),
+    ));
+    assert!(
+        provider
+            .list_modified_since("User", checkpoint, None, &other_tenant)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_list_modified_since_uses_timestamp_precision() {
+    let context = RequestContext::with_generated_id();
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage.clone())
+        .with_timestamp_precision(TimestampPrecision::Seconds);
+
+    let created = provider
+        .create_resource("User", create_test_user_data("boundary"), &context)
+        .await
+        .unwrap();
+    let last_modified = created.resource().get_meta().unwrap().last_modified();
+
+    // Later than the stored timestamp, but within the same second
+    let since = last_modified.trunc_subsecs(0) + chrono::Duration::nanoseconds(999_999_999);
+    assert!(since > last_modified);
+
+    let modified = provider
+        .list_modified_since("User", since, None, &context)
+        .await
+        .unwrap();
+    assert_eq!(modified.len(), 1);
+
+    let exact = StandardResourceProvider::new(storage)
+        .with_timestamp_precision(TimestampPrecision::Nanoseconds);
+    let modified = exact
+        .list_modified_since("User", since, None, &context)
+        .await
+        .unwrap();
+    assert!(modified.is_empty());
+}
+
+/// In-memory storage counting full scans through `list`.
+#[derive(Clone, Default)]
+struct ScanCountingStorage {
+    inner: InMemoryStorage,
+    scans: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl ScanCountingStorage {
+    fn scans(&self) -> usize {
+        self.scans.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+impl StorageProvider for ScanCountingStorage {
+    type Error = scim_server::storage::StorageError;
+
+    async fn put(
+        &self,
+        key: StorageKey,
+        data: serde_json::Value,
+    ) -> Result<serde_json::Value, Self::Error> {
+        self.inner.put(key, data).await
+    }
+
+    async fn get(&self, key: StorageKey) -> Result<Option<serde_json::Value>, Self::Error> {
+        self.inner.get(key).await
+    }
+
+    async fn delete(&self, key: StorageKey) -> Result<bool, Self::Error> {
+        self.inner.delete(key).await
+    }
+
+    async fn list(
+        &self,
+        prefix: scim_server::storage::StoragePrefix,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(StorageKey, serde_json::Value)>, Self::Error> {
+        self.scans.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        self.inner.list(prefix, offset, limit).await
+    }
+
+    async fn find_by_attribute(
+        &self,
+        prefix: scim_server::storage::StoragePrefix,
+        attribute: &str,
+        value: &str,
+    ) -> Result<Vec<(StorageKey, serde_json::Value)>, Self::Error> {
+        self.inner.find_by_attribute(prefix, attribute, value).await
+    }
+
+    async fn exists(&self, key: StorageKey) -> Result<bool, Self::Error> {
+        self.inner.exists(key).await
+    }
+
+    async fn count(
+        &self,
+        prefix: scim_server::storage::StoragePrefix,
+    ) -> Result<usize, Self::Error> {
+        self.inner.count(prefix).await
+    }
+
+    async fn list_tenants(&self) -> Result<Vec<String>, Self::Error> {
+        self.inner.list_tenants().await
+    }
+
+    async fn list_resource_types(&self, tenant_id: &str) -> Result<Vec<String>, Self::Error> {
+        self.inner.list_resource_types(tenant_id).await
+    }
+
+    async fn list_all_resource_types(&self) -> Result<Vec<String>, Self::Error> {
+        self.inner.list_all_resource_types().await
+    }
+
+    async fn clear(&self) -> Result<(), Self::Error> {
+        self.inner.clear().await
+    }
+
+    async fn stats(&self) -> Result<scim_server::storage::StorageStats, Self::Error> {
+        self.inner.stats().await
+    }
+}
+
+#[tokio::test]
+async fn test_list_filter_pushdown() {
+    use scim_server::filter::Filter;
+
+    let storage = ScanCountingStorage::default();
+    let provider = StandardResourceProvider::new(storage.clone());
+    let context = RequestContext::with_generated_id();
+    for (username, external_id, emails) in [
+        ("alice", "hr-1", vec!["a@example.com"]),
+        ("bob", "hr-2", vec!["b@example.com", "shared@example.com"]),
+        ("carol", "HR-2", vec!["shared@example.com"]),
+    ] {
+        let emails: Vec<_> = emails.iter().map(|e| json!({"value": e})).collect();
+        provider
+            .create_resource(
+                "User",
+                json!({"userName": username, "externalId": external_id, "emails": emails}),
+                &context,
+            )
+            .await
+            .unwrap();
+    }
+    let case_exact = vec!["externalId".to_string(), "emails.value".to_string()];
+    let all = provider
+        .list_resources("User", None, &context)
+        .await
+        .unwrap();
+
+    for (filter, pushed_down) in [
+        (r#"externalId eq "hr-2""#, true),
+        (r#"EXTERNALID eq "HR-2""#, true),
+        (r#"emails.value eq "shared@example.com""#, true),
+        (r#"externalId eq "hr-3""#, true),
+        (r#"userName eq "ALICE""#, false),
+        (r#"externalId eq "hr-2" or userName eq "alice""#, false),
+        (r#"externalId sw "hr""#, false),
+    ] {
+        let query = ListQuery::new()
+            .with_filter(filter.to_string())
+            .with_case_exact_attributes(case_exact.clone());
+        let scans = storage.scans();
+        let listed: Vec<_> = provider
+            .list_resources("User", Some(&query), &context)
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| r.resource().get_id().unwrap().to_string())
+            .collect();
+        assert_eq!(storage.scans() == scans, pushed_down, "{}", filter);
+
+        // Identical to evaluating the filter over every resource
+        let parsed = Filter::parse(filter).unwrap();
+        let expected: Vec<_> = all
+            .iter()
+            .map(|r| r.resource().to_json().unwrap())
+            .filter(|json| parsed.matches_with_case_exact(json, |path| query.is_case_exact(path)))
+            .map(|json| json["id"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(listed, expected, "{}", filter);
+    }
+}
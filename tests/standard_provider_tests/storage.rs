@@ -0,0 +1,471 @@
+//! StandardResourceProvider tests for storage errors, encryption at rest and export.
+
+use super::create_test_user_data;
+use scim_server::ResourceProvider;
+use scim_server::providers::{ProviderError, StandardResourceProvider};
+use scim_server::resource::{ListQuery, RequestContext, TenantContext};
+use scim_server::storage::InMemoryStorage;
+use serde_json::json;
+
+/// Storage backend whose every operation fails with the same error.
+struct FailingStorage(fn() -> scim_server::storage::StorageError);
+
+impl scim_server::storage::StorageProvider for FailingStorage {
+    type Error = scim_server::storage::StorageError;
+
+    async fn put(
+        &self,
+        _key: scim_server::storage::StorageKey,
+        _data: serde_json::Value,
+    ) -> Result<serde_json::Value, Self::Error> {
+        Err((self.0)())
+    }
+
+    async fn get(
+        &self,
+        _key: scim_server::storage::StorageKey,
+    ) -> Result<Option<serde_json::Value>, Self::Error> {
+        Err((self.0)())
+    }
+
+    async fn delete(&self, _key: scim_server::storage::StorageKey) -> Result<bool, Self::Error> {
+        Err((self.0)())
+    }
+
+    async fn list(
+        &self,
+        _prefix: scim_server::storage::StoragePrefix,
+        _offset: usize,
+        _limit: usize,
+    ) -> Result<Vec<(scim_server::storage::StorageKey, serde_json::Value)>, Self::Error> {
+        Err((self.0)())
+    }
+
+    async fn find_by_attribute(
+        &self,
+        _prefix: scim_server::storage::StoragePrefix,
+        _attribute: &str,
+        _value: &str,
+    ) -> Result<Vec<(scim_server::storage::StorageKey, serde_json::Value)>, Self::Error> {
+        Err((self.0)())
+    }
+
+    async fn exists(&self, _key: scim_server::storage::StorageKey) -> Result<bool, Self::Error> {
+        Err((self.0)())
+    }
+
+    async fn count(
+        &self,
+        _prefix: scim_server::storage::StoragePrefix,
+    ) -> Result<usize, Self::Error> {
+        Err((self.0)())
+    }
+
+    async fn list_tenants(&self) -> Result<Vec<String>, Self::Error> {
+        Err((self.0)())
+    }
+
+    async fn list_resource_types(&self, _tenant_id: &str) -> Result<Vec<String>, Self::Error> {
+        Err((self.0)())
+    }
+
+    async fn list_all_resource_types(&self) -> Result<Vec<String>, Self::Error> {
+        Err((self.0)())
+    }
+
+    async fn clear(&self) -> Result<(), Self::Error> {
+        Err((self.0)())
+    }
+
+    async fn stats(&self) -> Result<scim_server::storage::StorageStats, Self::Error> {
+        Err((self.0)())
+    }
+}
+
+#[tokio::test]
+async fn test_storage_errors_map_to_provider_errors() {
+    use scim_server::storage::StorageError;
+    use std::time::Duration;
+
+    let context = RequestContext::with_generated_id();
+    let get = |error: fn() -> StorageError| {
+        let context = context.clone();
+        async move {
+            StandardResourceProvider::new(FailingStorage(error))
+                .get_resource("User", "123", &context)
+                .await
+                .unwrap_err()
+        }
+    };
+
+    assert!(matches!(
+        get(|| StorageError::Unavailable {
+            message: "connection refused".to_string(),
+            retry_after: Some(Duration::from_secs(5)),
+            source: None,
+        })
+        .await,
+        ProviderError::Unavailable {
+            retry_after: Some(retry_after),
+            ..
+        } if retry_after == Duration::from_secs(5)
+    ));
+    assert!(matches!(
+        get(|| StorageError::timeout("get", Duration::from_secs(1))).await,
+        ProviderError::Unavailable {
+            retry_after: None,
+            ..
+        }
+    ));
+    assert!(matches!(
+        get(|| StorageError::conflict("row locked")).await,
+        ProviderError::Conflict { .. }
+    ));
+    assert!(matches!(
+        get(|| StorageError::resource_not_found("default", "User", "123")).await,
+        ProviderError::ResourceNotFound { id, .. } if id == "123"
+    ));
+    assert!(matches!(
+        get(|| StorageError::serialization("truncated row")).await,
+        ProviderError::Storage { .. }
+    ));
+
+    // The server answers with the matching SCIM status
+    let mut server =
+        scim_server::ScimServer::new(StandardResourceProvider::new(FailingStorage(|| {
+            StorageError::unavailable("connection refused")
+        })))
+        .unwrap();
+    let registry = scim_server::SchemaRegistry::new().unwrap();
+    server
+        .register_resource_type(
+            "User",
+            scim_server::resource_handlers::create_user_resource_handler(
+                registry.get_user_schema().clone(),
+            ),
+            vec![
+                scim_server::resource::ScimOperation::Read,
+                scim_server::resource::ScimOperation::List,
+                scim_server::resource::ScimOperation::Search,
+            ],
+        )
+        .unwrap();
+    let error = server
+        .get_resource("User", "123", &context)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, scim_server::ScimError::Unavailable { .. }));
+    assert_eq!(error.status_code(), 503);
+
+    // Every read path maps provider errors the same way
+    let errors = [
+        server
+            .list_resources_with_query("User", None, &context)
+            .await
+            .unwrap_err(),
+        server
+            .count_resources("User", None, &context)
+            .await
+            .unwrap_err(),
+        server.list_ids("User", None, &context).await.unwrap_err(),
+        server
+            .find_resource_by_attribute("User", "userName", &json!("bjensen"), &context)
+            .await
+            .unwrap_err(),
+        server
+            .resource_exists("User", "123", &context)
+            .await
+            .unwrap_err(),
+    ];
+    for error in errors {
+        assert_eq!(error.status_code(), 503, "{}", error);
+    }
+}
+
+/// Test cipher that reverses the plaintext behind a nonce prefix, which is
+/// a counter unless deterministic.
+#[derive(Debug, Default)]
+struct CounterCrypto {
+    attributes: Vec<String>,
+    deterministic: bool,
+    counter: std::sync::atomic::AtomicUsize,
+}
+
+impl scim_server::providers::AttributeCrypto for CounterCrypto {
+    fn encrypted_attributes(&self, _resource_type: &str) -> &[String] {
+        &self.attributes
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    fn encrypt(
+        &self,
+        plaintext: &[u8],
+    ) -> Result<String, scim_server::providers::AttributeCryptoError> {
+        let nonce = if self.deterministic {
+            0
+        } else {
+            self.counter
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        };
+        let sealed: String = plaintext.iter().rev().map(|&b| b as char).collect();
+        Ok(format!("{}:{}", nonce, sealed))
+    }
+
+    fn decrypt(
+        &self,
+        ciphertext: &str,
+    ) -> Result<Vec<u8>, scim_server::providers::AttributeCryptoError> {
+        let (_nonce, sealed) = ciphertext
+            .split_once(':')
+            .ok_or_else(|| scim_server::providers::AttributeCryptoError::new("no nonce"))?;
+        Ok(sealed.bytes().rev().collect())
+    }
+}
+
+#[tokio::test]
+async fn test_attribute_encryption_at_rest() {
+    use scim_server::storage::{StorageKey, StorageProvider};
+
+    let enterprise = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+    let storage = InMemoryStorage::new();
+    let provider =
+        StandardResourceProvider::new(storage.clone()).with_attribute_crypto(CounterCrypto {
+            attributes: vec![
+                "userName".to_string(),
+                format!("{}.employeeNumber", enterprise),
+            ],
+            ..Default::default()
+        });
+    let context = RequestContext::with_generated_id();
+
+    let mut data = create_test_user_data("secret.agent");
+    data[enterprise] = json!({"employeeNumber": "007"});
+    let created = provider
+        .create_resource("User", data, &context)
+        .await
+        .unwrap();
+    let id = created.resource().get_id().unwrap();
+    assert_eq!(created.resource().get_username(), Some("secret.agent"));
+
+    // Only ciphertext reaches storage
+    let stored = storage
+        .get(StorageKey::new("default", "User", id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored["userName"], "0:\"tnega.terces\"");
+    assert_eq!(stored[enterprise]["employeeNumber"], "1:\"700\"");
+    assert_eq!(stored["displayName"], "User secret.agent");
+
+    // Reads decrypt, and versions come from the plaintext
+    let fetched = provider
+        .get_resource("User", id, &context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(fetched.resource().get_username(), Some("secret.agent"));
+    assert_eq!(fetched.version(), created.version());
+
+    // Rewriting with fresh nonces changes the ciphertext but not the version
+    let patch = json!({"Operations": [{"op": "replace", "path": "displayName", "value": "Bond"}]});
+    let patched = provider
+        .patch_resource("User", id, &patch, None, &context)
+        .await
+        .unwrap();
+    let stored = storage
+        .get(StorageKey::new("default", "User", id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored["userName"], "2:\"tnega.terces\"");
+    assert_eq!(
+        provider
+            .get_resource("User", id, &context)
+            .await
+            .unwrap()
+            .unwrap()
+            .version(),
+        patched.version()
+    );
+
+    // Filters see plaintext
+    let query = ListQuery::new().with_filter(r#"userName eq "secret.agent""#.to_string());
+    let listed = provider
+        .list_resources("User", Some(&query), &context)
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 1);
+
+    // Randomly encrypted attributes cannot be looked up or kept unique
+    assert!(
+        provider
+            .find_resources_by_attribute("User", "userName", "secret.agent", &context)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    provider
+        .create_resource("User", create_test_user_data("secret.agent"), &context)
+        .await
+        .unwrap();
+
+    // Deterministic encryption keeps both working
+    let provider = StandardResourceProvider::new(InMemoryStorage::new()).with_attribute_crypto(
+        CounterCrypto {
+            attributes: vec!["userName".to_string()],
+            deterministic: true,
+            ..Default::default()
+        },
+    );
+    provider
+        .create_resource("User", create_test_user_data("secret.agent"), &context)
+        .await
+        .unwrap();
+    let found = provider
+        .find_resources_by_attribute("User", "userName", "secret.agent", &context)
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].resource().get_username(), Some("secret.agent"));
+    assert!(matches!(
+        provider
+            .create_resource("User", create_test_user_data("secret.agent"), &context)
+            .await,
+        Err(ProviderError::DuplicateAttribute { value, .. }) if value == "secret.agent"
+    ));
+}
+
+#[tokio::test]
+async fn test_export_scrubs_pii() {
+    use scim_server::ScimServer;
+    use scim_server::multi_tenant::{PiiScrubbingMode, ScimTenantConfiguration};
+    use scim_server::resource::ScimOperation;
+    use scim_server::resource_handlers::{
+        create_group_resource_handler, create_user_resource_handler,
+    };
+    use scim_server::scim_server::export::PII_MASK;
+
+    let mut server =
+        ScimServer::new(StandardResourceProvider::new(InMemoryStorage::new())).unwrap();
+    let registry = scim_server::SchemaRegistry::new().unwrap();
+    let operations = vec![
+        ScimOperation::Create,
+        ScimOperation::Read,
+        ScimOperation::List,
+    ];
+    server
+        .register_resource_type(
+            "User",
+            create_user_resource_handler(registry.get_user_schema().clone()),
+            operations.clone(),
+        )
+        .unwrap();
+    server
+        .register_resource_type(
+            "Group",
+            create_group_resource_handler(registry.get_group_schema().clone()),
+            operations,
+        )
+        .unwrap();
+
+    let context = |tenant: &str| {
+        RequestContext::with_tenant_generated_id(TenantContext::new(
+            tenant.to_string(),
+            "client".to_string(),
+        ))
+    };
+    server.register_tenant_configuration(
+        ScimTenantConfiguration::builder("masked".to_string())
+            .with_pii_scrubbing(PiiScrubbingMode::Mask)
+            .build()
+            .unwrap(),
+    );
+    server.register_tenant_configuration(
+        ScimTenantConfiguration::builder("hashed".to_string())
+            .with_pii_scrubbing(PiiScrubbingMode::Hash {
+                salt: "pepper".to_string(),
+            })
+            .with_pii_attributes(vec!["userName".to_string(), "emails.value".to_string()])
+            .build()
+            .unwrap(),
+    );
+
+    for tenant in ["masked", "hashed", "plain"] {
+        let user = server
+            .create_resource(
+                "User",
+                json!({
+                    "userName": "bjensen",
+                    "name": {"givenName": "Barbara", "familyName": "Jensen"},
+                    "emails": [{"value": "bjensen@example.com", "type": "work", "primary": true}],
+                    "password": "t1meMa$heen",
+                    "active": true
+                }),
+                &context(tenant),
+            )
+            .await
+            .unwrap();
+        server
+            .create_resource(
+                "Group",
+                json!({
+                    "displayName": "Admins",
+                    "members": [{"value": user.get_id().unwrap(), "display": "Barbara Jensen"}]
+                }),
+                &context(tenant),
+            )
+            .await
+            .unwrap();
+    }
+
+    // Masking keeps non-PII values and the structure around PII
+    let exported = server.export_tenant(&context("masked")).await.unwrap();
+    let (group, user) = (&exported[0], &exported[1]);
+    assert_eq!(user["userName"], "bjensen");
+    assert_eq!(user["active"], true);
+    assert_eq!(user["name"]["givenName"], PII_MASK);
+    assert_eq!(user["name"]["familyName"], PII_MASK);
+    assert_eq!(user["emails"][0]["value"], PII_MASK);
+    assert_eq!(user["emails"][0]["type"], "work");
+    assert_eq!(user["emails"][0]["primary"], true);
+    assert_eq!(group["displayName"], PII_MASK);
+    assert_eq!(group["members"][0]["value"], user["id"]);
+    assert_eq!(group["members"][0]["display"], PII_MASK);
+
+    // Hashing only touches the configured attributes and is deterministic
+    let exported = server.export_tenant(&context("hashed")).await.unwrap();
+    let user = &exported[1];
+    assert_ne!(user["userName"], "bjensen");
+    assert_eq!(user["userName"].as_str().unwrap().len(), 64);
+    assert_eq!(user["name"]["givenName"], "Barbara");
+    let again = server.export_tenant(&context("hashed")).await.unwrap();
+    assert_eq!(again[1]["userName"], user["userName"]);
+    assert_eq!(again[1]["emails"], user["emails"]);
+
+    // Tenants without scrubbing export as stored, one resource per line,
+    // except for attributes that are never returned
+    let ndjson = server
+        .export_tenant_ndjson(&context("plain"))
+        .await
+        .unwrap();
+    let lines: Vec<serde_json::Value> = ndjson
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1]["name"]["givenName"], "Barbara");
+    assert!(lines[1].get("password").is_none());
+
+    // The stored resources are untouched
+    let stored = server
+        .list_resources("User", &context("masked"))
+        .await
+        .unwrap();
+    assert_eq!(
+        stored[0].to_json().unwrap()["emails"][0]["value"],
+        "bjensen@example.com"
+    );
+}
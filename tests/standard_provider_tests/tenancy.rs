@@ -0,0 +1,334 @@
+//! StandardResourceProvider tests for tenant isolation, stats and the default tenant.
+
+use super::create_test_user_data;
+use chrono::Utc;
+use scim_server::ResourceProvider;
+use scim_server::providers::StandardResourceProvider;
+use scim_server::resource::{ListQuery, RequestContext, TenantContext};
+use scim_server::storage::{InMemoryStorage, SqliteStorage, StorageProvider};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_multi_tenant_isolation() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+
+    // Create users in different tenants
+    let tenant_a_context = TenantContext::new("tenant-a".to_string(), "client-a".to_string());
+    let context_a = RequestContext::with_tenant_generated_id(tenant_a_context);
+
+    let tenant_b_context = TenantContext::new("tenant-b".to_string(), "client-b".to_string());
+    let context_b = RequestContext::with_tenant_generated_id(tenant_b_context);
+
+    // Create user in tenant A
+    let user_a_data = create_test_user_data("alice.tenant.a");
+    let user_a = provider
+        .create_resource("User", user_a_data, &context_a)
+        .await
+        .unwrap();
+    let _user_a_id = user_a.resource().get_id().unwrap();
+
+    // Create user in tenant B (different username)
+    let user_b_data = create_test_user_data("alice.tenant.b");
+    let user_b = provider
+        .create_resource("User", user_b_data, &context_b)
+        .await
+        .unwrap();
+    let _user_b_id = user_b.resource().get_id().unwrap();
+
+    // Verify isolation using username search - tenant B should not find tenant A's user
+    let alice_a_from_b = provider
+        .find_resources_by_attribute("User", "userName", "alice.tenant.a", &context_b)
+        .await
+        .unwrap();
+    assert!(
+        alice_a_from_b.is_empty(),
+        "Tenant B should not find tenant A's user by username"
+    );
+
+    // Verify tenant A should not find tenant B's user
+    let alice_b_from_a = provider
+        .find_resources_by_attribute("User", "userName", "alice.tenant.b", &context_a)
+        .await
+        .unwrap();
+    assert!(
+        alice_b_from_a.is_empty(),
+        "Tenant A should not find tenant B's user by username"
+    );
+
+    // Each tenant should find their own user
+    let alice_a_from_a = provider
+        .find_resources_by_attribute("User", "userName", "alice.tenant.a", &context_a)
+        .await
+        .unwrap();
+    assert!(
+        !alice_a_from_a.is_empty(),
+        "Tenant A should find its own user"
+    );
+
+    let alice_b_from_b = provider
+        .find_resources_by_attribute("User", "userName", "alice.tenant.b", &context_b)
+        .await
+        .unwrap();
+    assert!(
+        !alice_b_from_b.is_empty(),
+        "Tenant B should find its own user"
+    );
+
+    // Each tenant should see only their own user
+    let query = ListQuery::default();
+    let users_a = provider
+        .list_resources("User", Some(&query), &context_a)
+        .await
+        .unwrap();
+    let users_b = provider
+        .list_resources("User", Some(&query), &context_b)
+        .await
+        .unwrap();
+
+    assert_eq!(users_a.len(), 1);
+    assert_eq!(users_b.len(), 1);
+    assert_eq!(users_a[0].resource().get_username(), Some("alice.tenant.a"));
+    assert_eq!(users_b[0].resource().get_username(), Some("alice.tenant.b"));
+}
+
+#[tokio::test]
+async fn test_cross_tenant_username_allowed() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+
+    let tenant_a_context = TenantContext::new("tenant-a".to_string(), "client-a".to_string());
+    let context_a = RequestContext::with_tenant_generated_id(tenant_a_context);
+
+    let tenant_b_context = TenantContext::new("tenant-b".to_string(), "client-b".to_string());
+    let context_b = RequestContext::with_tenant_generated_id(tenant_b_context);
+
+    // Create user with same username in both tenants - should succeed
+    let user_data = create_test_user_data("shared.name");
+
+    let user_a = provider
+        .create_resource("User", user_data.clone(), &context_a)
+        .await
+        .unwrap();
+    let user_b = provider
+        .create_resource("User", user_data, &context_b)
+        .await
+        .unwrap();
+
+    assert_eq!(user_a.resource().get_username(), Some("shared.name"));
+    assert_eq!(user_b.resource().get_username(), Some("shared.name"));
+}
+
+#[tokio::test]
+async fn test_provider_stats() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+
+    let tenant_a_context = TenantContext::new("tenant-a".to_string(), "client-a".to_string());
+    let context_a = RequestContext::with_tenant_generated_id(tenant_a_context);
+
+    let single_context = RequestContext::with_generated_id();
+
+    // Create resources in different tenants and types
+    let _user1 = provider
+        .create_resource("User", create_test_user_data("user1"), &context_a)
+        .await
+        .unwrap();
+    let _user2 = provider
+        .create_resource("User", create_test_user_data("user2"), &single_context)
+        .await
+        .unwrap();
+    let _group = provider
+        .create_resource("Group", json!({"displayName": "Test Group"}), &context_a)
+        .await
+        .unwrap();
+
+    let final_stats = provider.get_stats().await;
+    assert_eq!(final_stats.tenant_count, 2); // tenant-a and default
+    assert_eq!(final_stats.total_resources, 3);
+    assert_eq!(final_stats.resource_type_count, 2); // User and Group
+    assert!(final_stats.resource_types.contains(&"User".to_string()));
+    assert!(final_stats.resource_types.contains(&"Group".to_string()));
+}
+
+#[tokio::test]
+async fn test_dynamic_tenant_discovery() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+
+    // Create resources in tenants with arbitrary names (not hardcoded patterns)
+    let tenants = ["arbitrary-tenant-123", "perf-tenant-999", "custom-org-456"];
+
+    for (idx, tenant_name) in tenants.iter().enumerate() {
+        let tenant_context = TenantContext::new(tenant_name.to_string(), "client".to_string());
+        let context = RequestContext::with_tenant_generated_id(tenant_context);
+
+        // Create 2 users per tenant
+        for i in 0..2 {
+            let user_data = create_test_user_data(&format!("user{}", idx * 2 + i));
+            provider
+                .create_resource("User", user_data, &context)
+                .await
+                .unwrap();
+        }
+
+        // Create 1 group per tenant
+        let group_data = json!({"displayName": format!("Group for {}", tenant_name)});
+        provider
+            .create_resource("Group", group_data, &context)
+            .await
+            .unwrap();
+    }
+
+    // The stats should dynamically discover all tenants, regardless of naming pattern
+    let stats = provider.get_stats().await;
+    assert_eq!(
+        stats.tenant_count, 3,
+        "Should discover all 3 tenants dynamically"
+    );
+    assert_eq!(
+        stats.total_resources, 9,
+        "Should count 6 users + 3 groups = 9 total"
+    );
+    assert_eq!(
+        stats.resource_type_count, 2,
+        "Should discover User and Group types"
+    );
+    assert!(stats.resource_types.contains(&"User".to_string()));
+    assert!(stats.resource_types.contains(&"Group".to_string()));
+}
+
+#[tokio::test]
+async fn test_stats_counts_per_tenant_and_type() {
+    async fn populate<S: StorageProvider>(provider: &StandardResourceProvider<S>) {
+        for (tenant, users) in [("acme", 2), ("globex", 1)] {
+            let context = RequestContext::with_tenant_generated_id(TenantContext::new(
+                tenant.to_string(),
+                "client".to_string(),
+            ));
+            for i in 0..users {
+                provider
+                    .create_resource(
+                        "User",
+                        create_test_user_data(&format!("{}-{}", tenant, i)),
+                        &context,
+                    )
+                    .await
+                    .unwrap();
+            }
+            if tenant == "acme" {
+                provider
+                    .create_resource("Group", json!({"displayName": "Admins"}), &context)
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+
+    let before = Utc::now();
+    let in_memory = StandardResourceProvider::new(InMemoryStorage::new());
+    populate(&in_memory).await;
+    let sqlite = StandardResourceProvider::new(SqliteStorage::new_in_memory().await.unwrap());
+    populate(&sqlite).await;
+
+    for stats in [in_memory.stats().await, sqlite.stats().await] {
+        assert!(stats.collected_at >= before);
+        assert_eq!(stats.tenant_count, 2);
+        assert_eq!(stats.total_resources, 4);
+        assert_eq!(stats.resource_types, vec!["Group", "User"]);
+        assert_eq!(stats.count("acme", "User"), 2);
+        assert_eq!(stats.count("acme", "Group"), 1);
+        assert_eq!(stats.count("globex", "User"), 1);
+        assert_eq!(stats.count("globex", "Group"), 0);
+        assert_eq!(stats.tenant_total("acme"), 3);
+        assert_eq!(stats.tenant_total("initech"), 0);
+    }
+}
+
+#[tokio::test]
+async fn test_clear_functionality() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let context = RequestContext::with_generated_id();
+
+    // Create some data
+    let _user = provider
+        .create_resource("User", create_test_user_data("test"), &context)
+        .await
+        .unwrap();
+
+    let stats_before = provider.get_stats().await;
+    assert!(stats_before.total_resources > 0);
+
+    // Clear all data
+    provider.clear().await;
+
+    let stats_after = provider.get_stats().await;
+    assert_eq!(stats_after.total_resources, 0);
+    assert_eq!(stats_after.tenant_count, 0);
+}
+
+#[tokio::test]
+async fn test_configurable_default_tenant() {
+    use scim_server::storage::{StorageKey, StorageProvider};
+    use scim_server::{ScimServer, ScimServerBuilder};
+
+    let storage = InMemoryStorage::new();
+    let context = RequestContext::with_generated_id();
+
+    // Without configuration, single-tenant data stays under "default"
+    let server = ScimServer::new(StandardResourceProvider::new(storage.clone())).unwrap();
+    assert_eq!(server.provider().default_tenant_id(), "default");
+    let legacy = server
+        .provider()
+        .create_resource("User", create_test_user_data("legacy"), &context)
+        .await
+        .unwrap();
+    let legacy_id = legacy.resource().get_id().unwrap();
+    assert!(
+        storage
+            .exists(StorageKey::new("default", "User", legacy_id))
+            .await
+            .unwrap()
+    );
+
+    // A configured default tenant is passed to the provider's storage keys
+    let server = ScimServerBuilder::new(StandardResourceProvider::new(storage.clone()))
+        .with_default_tenant_id("primary")
+        .build()
+        .unwrap();
+    assert_eq!(server.provider().default_tenant_id(), "primary");
+    let created = server
+        .provider()
+        .create_resource("User", create_test_user_data("current"), &context)
+        .await
+        .unwrap();
+    assert!(
+        storage
+            .exists(StorageKey::new(
+                "primary",
+                "User",
+                created.resource().get_id().unwrap()
+            ))
+            .await
+            .unwrap()
+    );
+    assert!(
+        server
+            .provider()
+            .get_resource("User", legacy_id, &context)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // The tenant ID must be usable in a storage key
+    for invalid in ["", "a/b"] {
+        assert!(
+            ScimServerBuilder::new(StandardResourceProvider::new(InMemoryStorage::new()))
+                .with_default_tenant_id(invalid)
+                .build()
+                .is_err()
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! Unit tests for the multi-tenant provider trait.
 
 use scim_server::ResourceProvider;
+use scim_server::providers::ProviderError;
 use scim_server::resource::{
     ListQuery, RequestContext, Resource, TenantContext, version::RawVersion,
     versioned::VersionedResource,
@@ -29,6 +30,8 @@ pub enum TestProviderError {
     },
     #[error("Internal error: {message}")]
     Internal { message: String },
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
 }
 
 /// Test multi-tenant provider implementation
@@ -0,0 +1,830 @@
+//! Operation handler tests for create, read, update and delete.
+
+use crate::common::servers::TestServerBuilder;
+use scim_server::ScimServer;
+use scim_server::multi_tenant::ScimOperation;
+use scim_server::operation_handler::{ScimOperationHandler, ScimOperationRequest};
+use scim_server::providers::{StandardResourceProvider, StorageKeyPolicy};
+use scim_server::resource::version::RawVersion;
+use scim_server::resource_handlers::{create_group_resource_handler, create_user_resource_handler};
+use scim_server::storage::InMemoryStorage;
+use scim_server::{ScimServerConfig, TenantContext, TenantStrategy};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_operation_handler_create() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let mut server = ScimServer::new(provider).unwrap();
+
+    // Register User resource type
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let user_handler = create_user_resource_handler(user_schema);
+    server
+        .register_resource_type("User", user_handler, vec![ScimOperation::Create])
+        .unwrap();
+
+    let handler = ScimOperationHandler::new(server);
+
+    let request = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "testuser",
+            "name": {
+                "givenName": "Test",
+                "familyName": "User"
+            }
+        }),
+    );
+
+    let response = handler.handle_operation(request).await;
+    assert!(response.success);
+    assert!(response.data.is_some());
+    assert!(response.error.is_none());
+}
+
+#[tokio::test]
+async fn test_operation_handler_get_schemas() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let server = ScimServer::new(provider).unwrap();
+    let handler = ScimOperationHandler::new(server);
+
+    let request = ScimOperationRequest::get_schemas();
+    let response = handler.handle_operation(request).await;
+
+    assert!(response.success);
+    assert!(response.data.is_some());
+    if let Some(data) = response.data {
+        assert!(data.get("schemas").is_some());
+    }
+}
+
+#[tokio::test]
+async fn test_operation_handler_error_handling() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let server = ScimServer::new(provider).unwrap();
+    let handler = ScimOperationHandler::new(server);
+
+    // Try to get a non-existent resource
+    let request = ScimOperationRequest::get("User", "non-existent-id");
+    let response = handler.handle_operation(request).await;
+
+    assert!(!response.success);
+    assert!(response.error.is_some());
+    assert!(response.error_code.is_some());
+}
+
+#[tokio::test]
+async fn test_phase_3_complete_integration() {
+    // Comprehensive test demonstrating complete Phase 3 ETag functionality
+
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let mut server = ScimServer::new(provider).unwrap();
+
+    // Register User resource type with all operations
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let user_handler = create_user_resource_handler(user_schema);
+    server
+        .register_resource_type(
+            "User",
+            user_handler,
+            vec![
+                ScimOperation::Create,
+                ScimOperation::Read,
+                ScimOperation::Update,
+                ScimOperation::Delete,
+            ],
+        )
+        .unwrap();
+
+    let handler = ScimOperationHandler::new(server);
+
+    // 1. Create user - should return version information
+    let create_request = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "integration.test",
+            "name": {
+                "givenName": "Integration",
+                "familyName": "Test"
+            },
+            "active": true
+        }),
+    );
+
+    let create_response = handler.handle_operation(create_request).await;
+    assert!(create_response.success);
+    assert!(create_response.metadata.additional.contains_key("version"));
+    assert!(create_response.metadata.additional.contains_key("etag"));
+
+    let user_data = create_response.data.unwrap();
+    let user_id = user_data["id"].as_str().unwrap();
+    let v1_etag = create_response.metadata.additional["etag"]
+        .as_str()
+        .unwrap();
+
+    // 2. Get user - should return same version
+    let get_request = ScimOperationRequest::get("User", user_id);
+    let get_response = handler.handle_operation(get_request).await;
+    assert!(get_response.success);
+    assert_eq!(
+        get_response.metadata.additional["etag"].as_str().unwrap(),
+        v1_etag
+    );
+
+    // 3. Regular update (no expected_version) - should succeed and return new version
+    let v1_version = RawVersion::from_hash(
+        create_response.metadata.additional["version"]
+            .as_str()
+            .unwrap(),
+    );
+
+    let update1_request = ScimOperationRequest::update(
+        "User",
+        user_id,
+        json!({
+            "userName": "integration.updated",
+            "name": {
+                "givenName": "Integration",
+                "familyName": "Updated"
+            },
+            "active": true
+        }),
+    );
+
+    let update1_response = handler.handle_operation(update1_request).await;
+    assert!(update1_response.success);
+    assert!(update1_response.metadata.additional.contains_key("version"));
+    let v2_etag = update1_response.metadata.additional["etag"]
+        .as_str()
+        .unwrap();
+    assert_ne!(v1_etag, v2_etag); // Version should have changed
+
+    // 4. Conditional update with correct version - should succeed
+    let v2_version = RawVersion::from_hash(
+        update1_response.metadata.additional["version"]
+            .as_str()
+            .unwrap(),
+    );
+
+    let conditional_update_request = ScimOperationRequest::update(
+        "User",
+        user_id,
+        json!({
+            "userName": "integration.conditional",
+            "active": false
+        }),
+    )
+    .with_expected_version(v2_version);
+
+    let conditional_update_response = handler.handle_operation(conditional_update_request).await;
+    assert!(conditional_update_response.success);
+    let v3_etag = conditional_update_response.metadata.additional["etag"]
+        .as_str()
+        .unwrap();
+    assert_ne!(v2_etag, v3_etag); // Version should have changed again
+
+    // 5. Conditional update with old version - should fail
+    let stale_update_request = ScimOperationRequest::update(
+        "User",
+        user_id,
+        json!({
+            "userName": "should.fail"
+        }),
+    )
+    .with_expected_version(v1_version); // Using old version
+
+    let stale_update_response = handler.handle_operation(stale_update_request).await;
+    assert!(!stale_update_response.success);
+    assert_eq!(
+        stale_update_response.error_code.as_deref(),
+        Some("version_mismatch")
+    );
+    assert!(
+        stale_update_response
+            .metadata
+            .additional
+            .contains_key("expected_version")
+    );
+    assert!(
+        stale_update_response
+            .metadata
+            .additional
+            .contains_key("current_version")
+    );
+
+    // 6. Conditional delete with correct version - should succeed
+    let v3_version = RawVersion::from_hash(
+        conditional_update_response.metadata.additional["version"]
+            .as_str()
+            .unwrap(),
+    );
+
+    let conditional_delete_request =
+        ScimOperationRequest::delete("User", user_id).with_expected_version(v3_version);
+
+    let conditional_delete_response = handler.handle_operation(conditional_delete_request).await;
+    assert!(conditional_delete_response.success);
+
+    // 7. Verify user is actually deleted
+    let verify_request = ScimOperationRequest::get("User", user_id);
+    let verify_response = handler.handle_operation(verify_request).await;
+    assert!(!verify_response.success);
+    assert!(
+        verify_response
+            .error_code
+            .as_deref()
+            .unwrap()
+            .contains("NOT_FOUND")
+    );
+}
+
+#[tokio::test]
+async fn test_uniqueness_conflict_points_at_existing_resource() {
+    let handler = TestServerBuilder::new()
+        .configure(|builder| {
+            builder
+                .with_base_url("https://scim.example.com")
+                .with_tenant_strategy(TenantStrategy::PathBased)
+        })
+        .with_users([ScimOperation::Create])
+        .build_handler();
+
+    let acme = TenantContext::new("acme".to_string(), "client-a".to_string());
+    let globex = TenantContext::new("globex".to_string(), "client-g".to_string());
+
+    let existing = handler
+        .handle_operation(
+            ScimOperationRequest::create("User", json!({"userName": "taken"}))
+                .with_tenant(acme.clone()),
+        )
+        .await;
+    let existing_id = existing.metadata.resource_id.unwrap();
+
+    // Another tenant may use the same value and learns nothing about acme
+    let other_tenant = handler
+        .handle_operation(
+            ScimOperationRequest::create("User", json!({"userName": "taken"})).with_tenant(globex),
+        )
+        .await;
+    assert!(other_tenant.success);
+
+    let conflict = handler
+        .handle_operation(
+            ScimOperationRequest::create("User", json!({"userName": "taken"})).with_tenant(acme),
+        )
+        .await;
+    assert!(!conflict.success);
+    assert_eq!(conflict.error_code.as_deref(), Some("UNIQUENESS_CONFLICT"));
+    let additional = &conflict.metadata.additional;
+    assert_eq!(additional["scim_type"], "uniqueness");
+    assert_eq!(additional["conflicting_id"], existing_id.as_str());
+    assert_eq!(
+        additional["conflicting_location"],
+        format!("https://scim.example.com/acme/v2/Users/{}", existing_id)
+    );
+}
+
+#[tokio::test]
+async fn test_operation_handler_rejects_oversized_payload() {
+    let handler = TestServerBuilder::new()
+        .configure(|builder| builder.with_max_payload_size(Some(256)))
+        .with_users([ScimOperation::Create])
+        .build_handler();
+
+    let small = ScimOperationRequest::create("User", json!({"userName": "small"}));
+    let response = handler.handle_operation(small).await;
+    assert!(response.success);
+
+    let oversized = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "large",
+            "displayName": "x".repeat(512)
+        }),
+    );
+    let response = handler.handle_operation(oversized).await;
+    assert!(!response.success);
+    assert_eq!(response.error_code.as_deref(), Some("PAYLOAD_TOO_LARGE"));
+
+    // Batches are bounded as a whole, even when each body fits on its own
+    let batch = || {
+        (0..10)
+            .map(|i| {
+                ScimOperationRequest::create(
+                    "User",
+                    json!({"userName": format!("user{}", i), "displayName": "x".repeat(32)}),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    for concurrent in [false, true] {
+        let responses = if concurrent {
+            handler.handle_batch_concurrent(batch()).await
+        } else {
+            handler.handle_batch(batch()).await
+        };
+        assert_eq!(responses.len(), 10);
+        assert!(responses.iter().all(|response| {
+            !response.success && response.error_code.as_deref() == Some("PAYLOAD_TOO_LARGE")
+        }));
+    }
+
+    let config = ScimServerConfig {
+        max_payload_size: Some(256),
+        ..Default::default()
+    };
+    let bodies = batch()
+        .into_iter()
+        .filter_map(|request| request.data)
+        .collect::<Vec<_>>();
+    assert!(
+        bodies
+            .iter()
+            .all(|body| config.check_payload_size(body).is_ok())
+    );
+    let err = config.check_batch_payload_size(&bodies).unwrap_err();
+    assert_eq!(err.status_code(), 413);
+
+    // Unless configured, payloads of any size are accepted
+    assert!(
+        ScimServerConfig::default()
+            .check_batch_payload_size(&bodies)
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn test_resource_type_resolution_from_endpoint_names() {
+    use scim_server::ResourceTypeResolution;
+
+    let build = |resolution: ResourceTypeResolution| {
+        TestServerBuilder::new()
+            .configure(|builder| {
+                builder
+                    .with_base_url("https://scim.example.com")
+                    .with_resource_type_resolution(resolution)
+            })
+            .with_users([ScimOperation::Create, ScimOperation::Read])
+            .build_server()
+    };
+
+    let server = build(ResourceTypeResolution::lenient());
+    for name in ["User", "Users", "users", "/Users/", "user"] {
+        assert_eq!(server.resolve_resource_type(name), Some("User"), "{}", name);
+    }
+    assert_eq!(server.resolve_resource_type("Groups"), None);
+
+    // Resources keep the canonical type name whatever the request called it
+    let handler = ScimOperationHandler::new(server);
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "/users/",
+            json!({"userName": "lenient"}),
+        ))
+        .await;
+    assert!(response.success, "create failed: {:?}", response.error);
+    assert_eq!(response.metadata.resource_type.as_deref(), Some("User"));
+    let data = response.data.unwrap();
+    assert_eq!(data["meta"]["resourceType"], "User");
+    assert!(
+        data["meta"]["location"]
+            .as_str()
+            .unwrap()
+            .contains("/Users/")
+    );
+
+    let user_id = data["id"].as_str().unwrap();
+    let response = handler
+        .handle_operation(ScimOperationRequest::get("Users", user_id))
+        .await;
+    assert!(response.success);
+
+    // Exact resolution still rejects anything but the registered name
+    let handler = ScimOperationHandler::new(build(ResourceTypeResolution::exact()));
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "users",
+            json!({"userName": "exact"}),
+        ))
+        .await;
+    assert!(!response.success);
+
+    // Names that would resolve to two types are rejected at registration
+    let mut server = build(ResourceTypeResolution::lenient());
+    let group_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:Group")
+        .unwrap()
+        .clone();
+    let result = server.register_resource_type(
+        "Users",
+        create_group_resource_handler(group_schema),
+        vec![ScimOperation::Create],
+    );
+    assert!(result.unwrap_err().to_string().contains("ambiguous"));
+}
+
+#[tokio::test]
+async fn test_attribute_name_normalization() {
+    let build = |enabled: bool| {
+        TestServerBuilder::new()
+            .configure(|builder| builder.with_attribute_name_normalization(enabled))
+            .with_users([ScimOperation::Create])
+            .build_handler()
+    };
+    let user = json!({
+        "username": "casing",
+        "NAME": {"GivenName": "Casey", "familyname": "Ing"},
+        "Emails": [{"VALUE": "casey@example.com", "Type": "work"}]
+    });
+
+    let handler = build(true);
+    let response = handler
+        .handle_operation(ScimOperationRequest::create("User", user.clone()))
+        .await;
+    assert!(response.success, "create failed: {:?}", response.error);
+    let data = response.data.unwrap();
+    assert_eq!(data["userName"], "casing");
+    assert_eq!(data["name"]["givenName"], "Casey");
+    assert_eq!(data["name"]["familyName"], "Ing");
+    assert_eq!(data["emails"][0]["value"], "casey@example.com");
+    assert_eq!(data["emails"][0]["type"], "work");
+
+    // Keys that match no schema attribute are still unknown
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "unknown", "NAME": {"nickName": "x"}}),
+        ))
+        .await;
+    assert!(!response.success);
+    assert!(response.error.unwrap().contains("nickName"));
+
+    // Disabled by default
+    let response = build(false)
+        .handle_operation(ScimOperationRequest::create("User", user))
+        .await;
+    assert!(!response.success);
+}
+
+#[tokio::test]
+async fn test_storage_capacity_maps_to_scim_error() {
+    use scim_server::storage::{CapacityPolicy, CapacityScope};
+
+    let storage =
+        InMemoryStorage::with_capacity(1, CapacityScope::PerTenant, CapacityPolicy::Reject);
+    let handler = TestServerBuilder::with_provider(StandardResourceProvider::new(storage))
+        .with_users([ScimOperation::Create])
+        .build_handler();
+
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "first"}),
+        ))
+        .await;
+    assert!(response.success);
+
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "second"}),
+        ))
+        .await;
+    assert!(!response.success);
+    assert_eq!(response.error_code.as_deref(), Some("CAPACITY_EXCEEDED"));
+    assert!(response.error.unwrap().contains("resource limit"));
+
+    let error = scim_server::ScimError::capacity_exceeded("full", Some(1));
+    assert_eq!(error.status_code(), 507);
+}
+
+#[tokio::test]
+async fn test_custom_resource_endpoint() {
+    let mut server = TestServerBuilder::new()
+        .with_users([ScimOperation::Create, ScimOperation::Read])
+        .with_groups([ScimOperation::Create, ScimOperation::Read])
+        .build_server();
+
+    // Defaults to the pluralized name; endpoints already in use are rejected
+    assert_eq!(server.resource_endpoint("Group"), "Groups");
+    assert!(server.set_resource_endpoint("User", "/Groups").is_err());
+    assert!(server.set_resource_endpoint("Device", "/Devices").is_err());
+    server.set_resource_endpoint("User", "/People").unwrap();
+    assert_eq!(server.resource_endpoint("User"), "People");
+    assert_eq!(server.resolve_resource_type("People"), Some("User"));
+
+    let definitions =
+        server.get_resource_type_definitions(&scim_server::RequestContext::with_generated_id());
+    let endpoints: Vec<_> = definitions
+        .iter()
+        .map(|definition| definition["endpoint"].as_str().unwrap())
+        .collect();
+    assert_eq!(endpoints, ["/Groups", "/People"]);
+    assert_eq!(
+        definitions[1]["schema"],
+        "urn:ietf:params:scim:schemas:core:2.0:User"
+    );
+
+    // Requests routed by endpoint are stored and located under the type
+    let handler = ScimOperationHandler::new(server);
+    let created = handler
+        .handle_operation(ScimOperationRequest::create(
+            "People",
+            json!({"userName": "endpoint.user"}),
+        ))
+        .await;
+    assert!(created.success, "{:?}", created.error);
+    let user = created.data.unwrap();
+    let id = user["id"].as_str().unwrap();
+    assert_eq!(user["meta"]["resourceType"], "User");
+    assert!(
+        user["meta"]["location"]
+            .as_str()
+            .unwrap()
+            .ends_with(&format!("/People/{}", id))
+    );
+
+    let group = handler
+        .handle_operation(ScimOperationRequest::create(
+            "Group",
+            json!({
+                "displayName": "Endpoint Group",
+                "members": [{"value": id, "type": "User"}]
+            }),
+        ))
+        .await;
+    assert!(group.success, "{:?}", group.error);
+    assert!(
+        group.data.unwrap()["members"][0]["$ref"]
+            .as_str()
+            .unwrap()
+            .ends_with(&format!("/People/{}", id))
+    );
+}
+
+#[tokio::test]
+async fn test_upsert_reports_created_or_updated() {
+    use scim_server::operation_handler::{
+        OperationMiddleware, ScimOperationResponse, ScimOperationType,
+    };
+    use scim_server::resource::RequestContext;
+    use std::sync::{Arc, Mutex};
+
+    /// Records the operation each successful response reports.
+    struct Events(Arc<Mutex<Vec<ScimOperationType>>>);
+
+    impl OperationMiddleware for Events {
+        fn after(&self, response: &mut ScimOperationResponse, _context: &RequestContext) {
+            if let (true, Some(operation)) = (response.success, response.metadata.operation) {
+                self.0.lock().unwrap().push(operation);
+            }
+        }
+    }
+
+    let server = TestServerBuilder::new()
+        .configure(|builder| builder.with_base_url("https://scim.example.com"))
+        .with_users([ScimOperation::Create, ScimOperation::Update])
+        .build_server();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let handler = ScimOperationHandler::new(server).with_middleware(Events(events.clone()));
+
+    // No resource carries the externalId yet, so the upsert creates one
+    let response = handler
+        .handle_operation(ScimOperationRequest::upsert(
+            "User",
+            json!({"userName": "jdoe", "externalId": "hr-42", "title": "Intern"}),
+        ))
+        .await;
+    assert!(response.success, "upsert failed: {:?}", response.error);
+    let id = response.metadata.resource_id.clone().unwrap();
+    let (status, headers, body) = response.into_http_parts();
+    assert_eq!(status, 201);
+    assert!(headers.contains(&(
+        "Location".to_string(),
+        format!("https://scim.example.com/v2/Users/{}", id)
+    )));
+    assert_eq!(body["title"], "Intern");
+
+    // The second upsert replaces it
+    let response = handler
+        .handle_operation(ScimOperationRequest::upsert(
+            "User",
+            json!({"userName": "jdoe", "externalId": "hr-42", "title": "Engineer"}),
+        ))
+        .await;
+    assert!(response.success, "upsert failed: {:?}", response.error);
+    assert_eq!(response.metadata.resource_id.as_deref(), Some(id.as_str()));
+    let (status, headers, body) = response.into_http_parts();
+    assert_eq!(status, 200);
+    assert!(!headers.iter().any(|(name, _)| name == "Location"));
+    assert_eq!(body["title"], "Engineer");
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [ScimOperationType::Create, ScimOperationType::Update]
+    );
+
+    // Upserts match on externalId
+    let response = handler
+        .handle_operation(ScimOperationRequest::upsert(
+            "User",
+            json!({"userName": "nobody"}),
+        ))
+        .await;
+    assert!(!response.success);
+    assert_eq!(response.metadata.operation, Some(ScimOperationType::Upsert));
+    assert_eq!(response.into_http_parts().0, 400);
+
+    // Duplicates already in storage are reported as a conflict
+    use scim_server::storage::{StorageKey, StorageProvider};
+    let storage = InMemoryStorage::new();
+    for id in ["legacy-1", "legacy-2"] {
+        storage
+            .put(
+                StorageKey::new("default", "User", id),
+                json!({"id": id, "userName": id, "externalId": "hr-7"}),
+            )
+            .await
+            .unwrap();
+    }
+    let handler = TestServerBuilder::with_provider(StandardResourceProvider::new(storage))
+        .with_users([ScimOperation::Create, ScimOperation::Update])
+        .build_handler();
+    let response = handler
+        .handle_operation(ScimOperationRequest::upsert(
+            "User",
+            json!({"userName": "jdoe", "externalId": "hr-7"}),
+        ))
+        .await;
+    assert!(!response.success);
+    assert_eq!(response.into_http_parts().0, 409);
+}
+
+#[tokio::test]
+async fn test_idempotent_delete() {
+    let handler_with = |idempotent: bool| {
+        TestServerBuilder::new()
+            .configure(|builder| builder.with_idempotent_delete(idempotent))
+            .with_users([ScimOperation::Create, ScimOperation::Delete])
+            .build_handler()
+    };
+
+    // By default a missing resource is not found
+    let strict = handler_with(false);
+    let missing = strict
+        .handle_operation(ScimOperationRequest::delete("User", "missing"))
+        .await;
+    assert!(!missing.success);
+    assert_eq!(missing.error_code.as_deref(), Some("RESOURCE_NOT_FOUND"));
+    assert_eq!(missing.into_http_parts().0, 404);
+
+    let idempotent = handler_with(true);
+    let created = idempotent
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "once"}),
+        ))
+        .await;
+    let id = created.metadata.resource_id.unwrap();
+
+    // The first delete removes the resource, repeats succeed without removing anything
+    let removed = idempotent
+        .handle_operation(ScimOperationRequest::delete("User", &id))
+        .await;
+    assert!(removed.success);
+    assert_eq!(removed.metadata.additional["removed"], json!(true));
+    for _ in 0..2 {
+        let repeated = idempotent
+            .handle_operation(ScimOperationRequest::delete("User", &id))
+            .await;
+        assert!(repeated.success);
+        assert_eq!(repeated.metadata.additional["removed"], json!(false));
+        assert_eq!(repeated.into_http_parts().0, 204);
+    }
+}
+
+#[tokio::test]
+async fn test_soft_deleted_resources_are_reported_as_deleted() {
+    let handler_with = |idempotent: bool| {
+        TestServerBuilder::with_provider(
+            StandardResourceProvider::new(InMemoryStorage::new()).with_soft_delete(true),
+        )
+        .configure(|builder| builder.with_idempotent_delete(idempotent))
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Read,
+            ScimOperation::Update,
+            ScimOperation::Delete,
+        ])
+        .build_handler()
+    };
+    async fn create_and_delete(
+        handler: &ScimOperationHandler<StandardResourceProvider<InMemoryStorage>>,
+    ) -> String {
+        let created = handler
+            .handle_operation(ScimOperationRequest::create(
+                "User",
+                json!({"userName": "tombstoned"}),
+            ))
+            .await;
+        let id = created.metadata.resource_id.unwrap();
+        let deleted = handler
+            .handle_operation(ScimOperationRequest::delete("User", &id))
+            .await;
+        assert!(deleted.success);
+        id
+    }
+
+    let handler = handler_with(false);
+    let id = create_and_delete(&handler).await;
+
+    // Both stay 404, but only the deleted resource is flagged as such
+    for request in [
+        ScimOperationRequest::get("User", &id),
+        ScimOperationRequest::update("User", &id, json!({"userName": "tombstoned"})),
+        ScimOperationRequest::delete("User", &id),
+    ] {
+        let response = handler.handle_operation(request).await;
+        assert!(!response.success);
+        assert_eq!(response.error_code.as_deref(), Some("RESOURCE_DELETED"));
+        assert_eq!(response.metadata.additional["deleted"], json!(true));
+        assert_eq!(response.into_http_parts().0, 404);
+    }
+    let missing = handler
+        .handle_operation(ScimOperationRequest::get("User", "missing"))
+        .await;
+    assert_eq!(missing.error_code.as_deref(), Some("RESOURCE_NOT_FOUND"));
+    assert!(!missing.metadata.additional.contains_key("deleted"));
+    assert_eq!(missing.into_http_parts().0, 404);
+
+    // Idempotent deletes treat a deleted resource like a missing one
+    let handler = handler_with(true);
+    let id = create_and_delete(&handler).await;
+    let repeated = handler
+        .handle_operation(ScimOperationRequest::delete("User", &id))
+        .await;
+    assert!(repeated.success);
+    assert_eq!(repeated.metadata.additional["removed"], json!(false));
+}
+
+#[tokio::test]
+async fn test_deeply_nested_payload_is_invalid_syntax() {
+    let handler = TestServerBuilder::new()
+        .with_users([ScimOperation::Create])
+        .build_handler();
+
+    // Deeply nested payloads are rejected before they are walked
+    let mut deep = json!("leaf");
+    for _ in 0..100 {
+        deep = json!([deep]);
+    }
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "deep", "urn:example:deep": deep}),
+        ))
+        .await;
+    assert!(!response.success);
+    let (status, _, body) = response.into_http_parts();
+    assert_eq!(status, 400);
+    assert_eq!(body["scimType"], "invalidSyntax");
+    assert!(
+        body["detail"]
+            .as_str()
+            .unwrap()
+            .contains("nested deeper than the maximum")
+    );
+}
+
+#[tokio::test]
+async fn test_missing_storage_key_is_a_client_error() {
+    let handler = TestServerBuilder::with_provider(
+        StandardResourceProvider::new(InMemoryStorage::new())
+            .with_storage_key_policy(StorageKeyPolicy::ExternalId),
+    )
+    .with_users([ScimOperation::Create])
+    .build_handler();
+
+    // Resources are keyed by externalId, so one without it cannot be stored
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "no.key"}),
+        ))
+        .await;
+    assert!(!response.success);
+    assert_eq!(response.error_code.as_deref(), Some("INVALID_REQUEST"));
+    let (status, _, body) = response.into_http_parts();
+    assert_eq!(status, 400);
+    assert!(body["detail"].as_str().unwrap().contains("externalId"));
+}
@@ -0,0 +1,883 @@
+//! Operation handler tests for Group members, `$ref` fields and display derivation.
+
+use crate::common::servers::TestServerBuilder;
+use scim_server::multi_tenant::ScimOperation;
+use scim_server::operation_handler::{
+    ScimOperationHandler, ScimOperationRequest, ScimOperationType,
+};
+use scim_server::providers::StandardResourceProvider;
+use scim_server::resource::version::RawVersion;
+use scim_server::resource_handlers::{create_group_resource_handler, create_user_resource_handler};
+use scim_server::storage::InMemoryStorage;
+use scim_server::{EntryDisplayPolicy, ScimServerBuilder, TenantContext, TenantStrategy};
+use serde_json::json;
+
+/// Test that operation handler create operation returns Groups with $ref fields in members array
+#[tokio::test]
+async fn test_operation_handler_create_group_includes_ref_fields() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+
+    // Create server with specific configuration for $ref generation
+    let mut server = ScimServerBuilder::new(provider)
+        .with_base_url("https://scim.company.com")
+        .with_tenant_strategy(TenantStrategy::SingleTenant)
+        .build()
+        .unwrap();
+
+    // Register User and Group resource types
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let user_handler = create_user_resource_handler(user_schema);
+    server
+        .register_resource_type(
+            "User",
+            user_handler,
+            vec![ScimOperation::Create, ScimOperation::Read],
+        )
+        .unwrap();
+
+    let group_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:Group")
+        .unwrap()
+        .clone();
+    let group_handler = create_group_resource_handler(group_schema);
+    server
+        .register_resource_type("Group", group_handler, vec![ScimOperation::Create])
+        .unwrap();
+
+    let handler = ScimOperationHandler::new(server);
+
+    // First create a user to reference
+    let user_request = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "testuser@company.com",
+            "name": {
+                "givenName": "Test",
+                "familyName": "User"
+            }
+        }),
+    );
+
+    let user_response = handler.handle_operation(user_request).await;
+    assert!(user_response.success, "User creation should succeed");
+    let user_id = user_response.metadata.resource_id.unwrap();
+
+    // Create group with the user as a member (without $ref - should be added automatically)
+    let group_request = ScimOperationRequest::create(
+        "Group",
+        json!({
+            "displayName": "Test Engineering Team",
+            "members": [{
+                "value": user_id,
+                "type": "User",
+                "display": "Test User"
+            }]
+        }),
+    );
+
+    let group_response = handler.handle_operation(group_request).await;
+
+    // Verify response succeeded
+    assert!(group_response.success, "Group creation should succeed");
+    assert!(
+        group_response.data.is_some(),
+        "Group response should contain data"
+    );
+
+    let group_data = group_response.data.unwrap();
+    let members = group_data["members"].as_array().unwrap();
+    let member = &members[0];
+
+    // This is the key test - operation handler should include $ref fields
+    assert!(
+        member["$ref"].is_string(),
+        "Operation handler should automatically generate $ref field"
+    );
+
+    let ref_url = member["$ref"].as_str().unwrap();
+    let expected_url = format!("https://scim.company.com/v2/Users/{}", user_id);
+    assert_eq!(
+        ref_url, expected_url,
+        "Operation handler $ref should use server configuration"
+    );
+
+    // Verify other member fields are preserved
+    assert_eq!(member["value"], user_id);
+    assert_eq!(member["type"], "User");
+    assert_eq!(member["display"], "Test User");
+}
+
+/// Test that operation handler get operation returns Groups with $ref fields
+#[tokio::test]
+async fn test_operation_handler_get_group_includes_ref_fields() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+
+    let mut server = ScimServerBuilder::new(provider)
+        .with_base_url("https://api.example.com")
+        .with_tenant_strategy(TenantStrategy::SingleTenant)
+        .build()
+        .unwrap();
+
+    // Register resource types
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let user_handler = create_user_resource_handler(user_schema);
+    server
+        .register_resource_type(
+            "User",
+            user_handler,
+            vec![ScimOperation::Create, ScimOperation::Read],
+        )
+        .unwrap();
+
+    let group_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:Group")
+        .unwrap()
+        .clone();
+    let group_handler = create_group_resource_handler(group_schema);
+    server
+        .register_resource_type(
+            "Group",
+            group_handler,
+            vec![ScimOperation::Create, ScimOperation::Read],
+        )
+        .unwrap();
+
+    let handler = ScimOperationHandler::new(server);
+
+    // Create user and group first
+    let user_request = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "gettest@example.com",
+            "name": { "givenName": "Get", "familyName": "Test" }
+        }),
+    );
+    let user_response = handler.handle_operation(user_request).await;
+    let user_id = user_response.metadata.resource_id.unwrap();
+
+    let group_request = ScimOperationRequest::create(
+        "Group",
+        json!({
+            "displayName": "Get Test Group",
+            "members": [{
+                "value": user_id,
+                "type": "User",
+                "display": "Get Test"
+            }]
+        }),
+    );
+    let group_response = handler.handle_operation(group_request).await;
+    let group_id = group_response.metadata.resource_id.unwrap();
+
+    // Now get the group - this should include $ref fields
+    let get_request = ScimOperationRequest::get("Group", &group_id);
+    let get_response = handler.handle_operation(get_request).await;
+
+    assert!(get_response.success, "Group get should succeed");
+    assert!(get_response.data.is_some(), "Group get should return data");
+
+    let group_data = get_response.data.unwrap();
+    let members = group_data["members"].as_array().unwrap();
+    let member = &members[0];
+
+    // The get operation should also include $ref fields
+    assert!(
+        member["$ref"].is_string(),
+        "Operation handler get should include $ref fields"
+    );
+
+    let ref_url = member["$ref"].as_str().unwrap();
+    let expected_url = format!("https://api.example.com/v2/Users/{}", user_id);
+    assert_eq!(
+        ref_url, expected_url,
+        "Get operation $ref should use correct base URL"
+    );
+}
+
+/// Test that operation handler works with multi-tenant $ref generation
+#[tokio::test]
+async fn test_operation_handler_multitenant_ref_fields() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+
+    // Configure for subdomain-based multi-tenancy
+    let mut server = ScimServerBuilder::new(provider)
+        .with_base_url("https://scim.example.com")
+        .with_tenant_strategy(TenantStrategy::Subdomain)
+        .build()
+        .unwrap();
+
+    // Register resource types
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let user_handler = create_user_resource_handler(user_schema);
+    server
+        .register_resource_type("User", user_handler, vec![ScimOperation::Create])
+        .unwrap();
+
+    let group_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:Group")
+        .unwrap()
+        .clone();
+    let group_handler = create_group_resource_handler(group_schema);
+    server
+        .register_resource_type("Group", group_handler, vec![ScimOperation::Create])
+        .unwrap();
+
+    let handler = ScimOperationHandler::new(server);
+
+    // Create tenant context
+    let tenant_context = TenantContext::new("acme-corp".to_string(), "client-123".to_string());
+
+    // Create user in tenant
+    let mut user_request = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "tenant.user@acme.com",
+            "name": { "givenName": "Tenant", "familyName": "User" }
+        }),
+    );
+    user_request = user_request.with_tenant(tenant_context.clone());
+
+    let user_response = handler.handle_operation(user_request).await;
+    assert!(user_response.success);
+    let user_id = user_response.metadata.resource_id.unwrap();
+
+    // Create group in same tenant
+    let mut group_request = ScimOperationRequest::create(
+        "Group",
+        json!({
+            "displayName": "Tenant Test Group",
+            "members": [{
+                "value": user_id,
+                "type": "User",
+                "display": "Tenant User"
+            }]
+        }),
+    );
+    group_request = group_request.with_tenant(tenant_context);
+
+    let group_response = handler.handle_operation(group_request).await;
+
+    assert!(
+        group_response.success,
+        "Tenant group creation should succeed"
+    );
+
+    let group_data = group_response.data.unwrap();
+    let members = group_data["members"].as_array().unwrap();
+    let member = &members[0];
+
+    // Should generate subdomain-based $ref URL
+    assert!(
+        member["$ref"].is_string(),
+        "Multi-tenant operation should include $ref"
+    );
+
+    let ref_url = member["$ref"].as_str().unwrap();
+    let expected_url = format!("https://acme-corp.scim.example.com/v2/Users/{}", user_id);
+    assert_eq!(
+        ref_url, expected_url,
+        "Multi-tenant $ref should use subdomain strategy"
+    );
+}
+
+/// Test that operation handler list operations include $ref fields
+#[tokio::test]
+async fn test_operation_handler_list_includes_ref_fields() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+
+    let mut server = ScimServerBuilder::new(provider)
+        .with_base_url("https://list.test.com")
+        .with_tenant_strategy(TenantStrategy::SingleTenant)
+        .build()
+        .unwrap();
+
+    // Register resource types
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let user_handler = create_user_resource_handler(user_schema);
+    server
+        .register_resource_type(
+            "User",
+            user_handler,
+            vec![ScimOperation::Create, ScimOperation::List],
+        )
+        .unwrap();
+
+    let group_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:Group")
+        .unwrap()
+        .clone();
+    let group_handler = create_group_resource_handler(group_schema);
+    server
+        .register_resource_type(
+            "Group",
+            group_handler,
+            vec![ScimOperation::Create, ScimOperation::List],
+        )
+        .unwrap();
+
+    let handler = ScimOperationHandler::new(server);
+
+    // Create test data
+    let user_request = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "listuser@test.com",
+            "name": { "givenName": "List", "familyName": "User" }
+        }),
+    );
+    let user_response = handler.handle_operation(user_request).await;
+    let user_id = user_response.metadata.resource_id.unwrap();
+
+    let group_request = ScimOperationRequest::create(
+        "Group",
+        json!({
+            "displayName": "List Test Group",
+            "members": [{
+                "value": user_id,
+                "type": "User",
+                "display": "List User"
+            }]
+        }),
+    );
+    let _group_response = handler.handle_operation(group_request).await;
+
+    // List groups - should include $ref fields
+    let list_request = ScimOperationRequest::list("Group");
+    let list_response = handler.handle_operation(list_request).await;
+
+    assert!(list_response.success, "List operation should succeed");
+    assert!(list_response.data.is_some(), "List should return data");
+
+    let list_data = list_response.data.unwrap();
+    let groups = list_data.as_array().unwrap();
+    assert!(!groups.is_empty(), "Should have at least one group");
+
+    let group = &groups[0];
+    let members = group["members"].as_array().unwrap();
+    let member = &members[0];
+
+    // List operations should also include $ref fields
+    assert!(
+        member["$ref"].is_string(),
+        "List operation should include $ref fields"
+    );
+
+    let ref_url = member["$ref"].as_str().unwrap();
+    let expected_url = format!("https://list.test.com/v2/Users/{}", user_id);
+    assert_eq!(
+        ref_url, expected_url,
+        "List operation $ref should be correct"
+    );
+}
+
+#[tokio::test]
+async fn test_display_name_derivation() {
+    let build_handler = |enabled: bool| {
+        TestServerBuilder::new()
+            .configure(|builder| builder.with_display_name_derivation(enabled))
+            .with_users([
+                ScimOperation::Create,
+                ScimOperation::Update,
+                ScimOperation::Patch,
+            ])
+            .build_handler()
+    };
+    let ann_lee = json!({"userName": "ann", "name": {"givenName": "Ann", "familyName": "Lee"}});
+
+    // Off by default
+    let handler = build_handler(false);
+    let user = handler
+        .handle_operation(ScimOperationRequest::create("User", ann_lee.clone()))
+        .await
+        .data
+        .unwrap();
+    assert!(user.get("displayName").is_none());
+
+    let handler = build_handler(true);
+    let user = handler
+        .handle_operation(ScimOperationRequest::create("User", ann_lee))
+        .await
+        .data
+        .unwrap();
+    let id = user["id"].as_str().unwrap().to_string();
+    assert_eq!(user["displayName"], "Ann Lee");
+    assert_eq!(user["meta"]["derivedAttributes"], json!(["displayName"]));
+
+    // An echoed derived value follows the name
+    let user = handler
+        .handle_operation(ScimOperationRequest::update(
+            "User",
+            &id,
+            json!({
+                "userName": "ann",
+                "displayName": "Ann Lee",
+                "name": {"givenName": "Ann", "familyName": "Smith"}
+            }),
+        ))
+        .await
+        .data
+        .unwrap();
+    assert_eq!(user["displayName"], "Ann Smith");
+
+    // A client-chosen value is kept, and stays kept when the name changes
+    for formatted in ["Ann Smith", "Dr. Ann Smith"] {
+        let user = handler
+            .handle_operation(ScimOperationRequest::update(
+                "User",
+                &id,
+                json!({
+                    "userName": "ann",
+                    "displayName": "Annie",
+                    "name": {"formatted": formatted}
+                }),
+            ))
+            .await
+            .data
+            .unwrap();
+        assert_eq!(user["displayName"], "Annie");
+        assert_eq!(user["meta"]["derivedAttributes"], json!([]));
+    }
+
+    // The marker is server-managed: a forged one does not make a chosen value
+    // follow the name
+    let user = handler
+        .handle_operation(ScimOperationRequest::update(
+            "User",
+            &id,
+            json!({
+                "userName": "ann",
+                "displayName": "Annie",
+                "name": {"formatted": "Ann Smith"},
+                "meta": {"derivedAttributes": ["displayName"]}
+            }),
+        ))
+        .await
+        .data
+        .unwrap();
+    assert_eq!(user["meta"]["derivedAttributes"], json!([]));
+    let user = handler
+        .handle_operation(ScimOperationRequest::update(
+            "User",
+            &id,
+            json!({"userName": "ann", "displayName": "Annie", "name": {"formatted": "Ann Jones"}}),
+        ))
+        .await
+        .data
+        .unwrap();
+    assert_eq!(user["displayName"], "Annie");
+
+    let user = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "bob", "displayName": "Bobby", "name": {"formatted": "Bob Jones"}}),
+        ))
+        .await
+        .data
+        .unwrap();
+    assert_eq!(user["displayName"], "Bobby");
+    assert!(user["meta"].get("derivedAttributes").is_none());
+
+    // Nor can a client hide a derivation by clearing the marker
+    let user = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({
+                "userName": "cy",
+                "name": {"formatted": "Cy Young"},
+                "meta": {"derivedAttributes": []}
+            }),
+        ))
+        .await
+        .data
+        .unwrap();
+    assert_eq!(user["meta"]["derivedAttributes"], json!(["displayName"]));
+    let user = handler
+        .handle_operation(ScimOperationRequest::update(
+            "User",
+            user["id"].as_str().unwrap(),
+            json!({
+                "userName": "cy",
+                "displayName": "Cy Young",
+                "name": {"formatted": "Cy Old"},
+                "meta": {"derivedAttributes": []}
+            }),
+        ))
+        .await
+        .data
+        .unwrap();
+    assert_eq!(user["displayName"], "Cy Old");
+
+    // A derived value also follows a PATCH of the name
+    let user = handler
+        .handle_operation(ScimOperationRequest {
+            operation: ScimOperationType::Patch,
+            ..ScimOperationRequest::update(
+                "User",
+                user["id"].as_str().unwrap(),
+                json!({
+                    "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                    "Operations": [
+                        {"op": "replace", "path": "name.formatted", "value": "Cy Younger"}
+                    ]
+                }),
+            )
+        })
+        .await
+        .data
+        .unwrap();
+    assert_eq!(user["displayName"], "Cy Younger");
+    assert_eq!(user["meta"]["derivedAttributes"], json!(["displayName"]));
+}
+
+#[tokio::test]
+async fn test_entry_display_derivation() {
+    let handler = TestServerBuilder::new()
+        .configure(|builder| {
+            builder
+                .with_display_name_derivation(true)
+                .with_entry_display_policy(EntryDisplayPolicy::TypeAndValue)
+        })
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Update,
+            ScimOperation::Patch,
+        ])
+        .build_handler();
+
+    let user = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({
+                "userName": "ann",
+                "name": {"givenName": "Ann", "familyName": "Lee"},
+                "emails": [
+                    {"value": "ann@work.example.com", "type": "work"},
+                    {"value": "ann@home.example.com", "display": "Personal"}
+                ],
+                "phoneNumbers": [{"value": "555-0100"}],
+                "addresses": [{"locality": "Hollywood", "region": "CA", "postalCode": "91608"}]
+            }),
+        ))
+        .await
+        .data
+        .unwrap();
+    let id = user["id"].as_str().unwrap().to_string();
+    assert_eq!(user["emails"][0]["display"], "work: ann@work.example.com");
+    assert_eq!(user["emails"][1]["display"], "Personal");
+    assert_eq!(user["phoneNumbers"][0]["display"], "555-0100");
+    assert_eq!(user["addresses"][0]["formatted"], "Hollywood, CA 91608");
+    assert_eq!(
+        user["meta"]["derivedAttributes"],
+        json!([
+            "displayName",
+            "emails.display",
+            "phoneNumbers.display",
+            "addresses.formatted"
+        ])
+    );
+
+    // Echoed derived values follow the entry; client-chosen ones are kept
+    let user = handler
+        .handle_operation(ScimOperationRequest::update(
+            "User",
+            &id,
+            json!({
+                "userName": "ann",
+                "displayName": "Annie",
+                "emails": [
+                    {"value": "ann@corp.example.com", "type": "work", "display": "work: ann@work.example.com"},
+                    {"value": "ann@home.example.com", "display": "Personal"}
+                ],
+                "phoneNumbers": [{"value": "555-0199", "display": "Desk"}]
+            }),
+        ))
+        .await
+        .data
+        .unwrap();
+    assert_eq!(user["displayName"], "Annie");
+    assert_eq!(user["emails"][0]["display"], "work: ann@corp.example.com");
+    assert_eq!(user["emails"][1]["display"], "Personal");
+    assert_eq!(user["phoneNumbers"][0]["display"], "Desk");
+    assert_eq!(user["meta"]["derivedAttributes"], json!(["emails.display"]));
+
+    // A derived value also follows a PATCH of the entry
+    let user = handler
+        .handle_operation(ScimOperationRequest {
+            operation: ScimOperationType::Patch,
+            ..ScimOperationRequest::update(
+                "User",
+                &id,
+                json!({
+                    "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                    "Operations": [{
+                        "op": "replace",
+                        "path": "emails",
+                        "value": [
+                            {"value": "ann@new.example.com", "type": "work", "display": "work: ann@corp.example.com"},
+                            {"value": "ann@home.example.com", "display": "Personal"}
+                        ]
+                    }]
+                }),
+            )
+        })
+        .await
+        .data
+        .unwrap();
+    assert_eq!(user["emails"][0]["value"], "ann@new.example.com");
+    assert_eq!(user["emails"][0]["display"], "work: ann@new.example.com");
+    assert_eq!(user["emails"][1]["display"], "Personal");
+    assert_eq!(user["phoneNumbers"][0]["display"], "Desk");
+    assert_eq!(user["meta"]["derivedAttributes"], json!(["emails.display"]));
+}
+
+#[tokio::test]
+async fn test_duplicate_group_members() {
+    use scim_server::DuplicateMemberPolicy;
+
+    let build_handler = |policy: DuplicateMemberPolicy| {
+        TestServerBuilder::new()
+            .configure(|builder| {
+                builder
+                    .with_base_url("https://scim.example.com")
+                    .with_duplicate_member_policy(policy)
+            })
+            .with_groups([
+                ScimOperation::Create,
+                ScimOperation::Update,
+                ScimOperation::Patch,
+            ])
+            .build_handler()
+    };
+    let member_values = |response: &scim_server::ScimOperationResponse| -> Vec<String> {
+        response.data.as_ref().unwrap()["members"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|member| member["value"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let members = json!([
+        {"value": "b", "type": "User"},
+        {"value": "a", "type": "User"},
+        {"value": "b", "type": "User", "display": "again"},
+        {"value": "c", "type": "User"},
+        {"value": "a", "type": "User"}
+    ]);
+
+    // The first entry for each value is kept, in order, with a single $ref
+    let handler = build_handler(DuplicateMemberPolicy::Deduplicate);
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "Group",
+            json!({"displayName": "Engineering", "members": members}),
+        ))
+        .await;
+    assert!(response.success, "create failed: {:?}", response.error);
+    assert_eq!(member_values(&response), ["b", "a", "c"]);
+    let data = response.data.as_ref().unwrap();
+    assert!(data["members"][0].get("display").is_none());
+    assert_eq!(
+        data["members"][0]["$ref"],
+        "https://scim.example.com/v2/Users/b"
+    );
+    let id = response.metadata.resource_id.clone().unwrap();
+
+    let response = handler
+        .handle_operation(ScimOperationRequest::update(
+            "Group",
+            &id,
+            json!({"displayName": "Engineering", "members": [{"value": "c"}, {"value": "c"}]}),
+        ))
+        .await;
+    assert!(response.success, "update failed: {:?}", response.error);
+    assert_eq!(member_values(&response), ["c"]);
+
+    let response = handler
+        .handle_operation(ScimOperationRequest {
+            operation: scim_server::operation_handler::ScimOperationType::Patch,
+            resource_type: "Group".to_string(),
+            resource_id: Some(id.clone()),
+            data: Some(json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{"op": "add", "path": "members", "value": members}]
+            })),
+            query: None,
+            tenant_context: None,
+            request_id: None,
+            expected_version: None,
+            idempotency_key: None,
+            context_attributes: Default::default(),
+        })
+        .await;
+    assert!(response.success, "patch failed: {:?}", response.error);
+    assert_eq!(member_values(&response), ["b", "a", "c"]);
+
+    // Rejecting duplicates fails the request with a uniqueness error
+    let handler = build_handler(DuplicateMemberPolicy::Reject);
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "Group",
+            json!({"displayName": "Engineering", "members": members}),
+        ))
+        .await;
+    assert!(!response.success);
+    let (status, _, body) = response.into_http_parts();
+    assert_eq!(status, 400);
+    assert_eq!(body["scimType"], "uniqueness");
+}
+
+#[tokio::test]
+async fn test_max_group_members() {
+    use scim_server::ScimTenantConfiguration;
+
+    let mut server = TestServerBuilder::new()
+        .configure(|builder| {
+            builder
+                .with_tenant_strategy(TenantStrategy::PathBased)
+                .with_max_group_members(Some(2))
+        })
+        .with_groups([
+            ScimOperation::Create,
+            ScimOperation::Update,
+            ScimOperation::Patch,
+        ])
+        .build_server();
+    server.register_tenant_configuration(
+        ScimTenantConfiguration::builder("large".to_string())
+            .with_max_group_members(3)
+            .build()
+            .unwrap(),
+    );
+    let handler = ScimOperationHandler::new(server);
+
+    let tenant = |id: &str| TenantContext::new(id.to_string(), "client".to_string());
+    let members = |values: &[&str]| {
+        values
+            .iter()
+            .map(|value| json!({"value": value}))
+            .collect::<Vec<_>>()
+    };
+    let group = |values: &[&str]| json!({"displayName": "Team", "members": members(values)});
+    let patch = |tenant_id: &str, id: &str, operations: serde_json::Value| ScimOperationRequest {
+        operation: scim_server::operation_handler::ScimOperationType::Patch,
+        resource_type: "Group".to_string(),
+        resource_id: Some(id.to_string()),
+        data: Some(json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": operations
+        })),
+        query: None,
+        tenant_context: Some(tenant(tenant_id)),
+        request_id: None,
+        expected_version: None,
+        idempotency_key: None,
+        context_attributes: Default::default(),
+    };
+    let assert_too_many = |response: scim_server::operation_handler::ScimOperationResponse,
+                           count: usize,
+                           limit: usize| {
+        assert!(!response.success);
+        let error = response.error.clone().unwrap();
+        assert!(error.contains(&format!("{} members", count)), "{}", error);
+        assert!(
+            error.contains(&format!("maximum of {}", limit)),
+            "{}",
+            error
+        );
+        let (status, _, body) = response.into_http_parts();
+        assert_eq!(status, 413);
+        assert_eq!(body["scimType"], "tooMany");
+    };
+
+    // Create and replace are capped at the server-wide limit
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::create("Group", group(&["a", "b", "c"]))
+                .with_tenant(tenant("small")),
+        )
+        .await;
+    assert_too_many(response, 3, 2);
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::create("Group", group(&["a", "b"])).with_tenant(tenant("small")),
+        )
+        .await;
+    assert!(response.success, "{:?}", response.error);
+    let id = response.metadata.resource_id.clone().unwrap();
+    let etag = response.metadata.additional["etag"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::update("Group", &id, group(&["a", "b", "c"]))
+                .with_tenant(tenant("small")),
+        )
+        .await;
+    assert_too_many(response, 3, 2);
+
+    // A conditional replace is held to the same limit
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::update("Group", &id, group(&["a", "b", "c"]))
+                .with_tenant(tenant("small"))
+                .with_expected_version(RawVersion::from_hash(&etag)),
+        )
+        .await;
+    assert_too_many(response, 3, 2);
+
+    // PATCH counts the existing members plus those added
+    let response = handler
+        .handle_operation(patch(
+            "small",
+            &id,
+            json!([{"op": "add", "path": "members", "value": members(&["c"])}]),
+        ))
+        .await;
+    assert_too_many(response, 3, 2);
+    let response = handler
+        .handle_operation(patch(
+            "small",
+            &id,
+            json!([
+                {"op": "replace", "path": "members", "value": members(&["b"])},
+                {"op": "add", "path": "members", "value": members(&["b", "c"])}
+            ]),
+        ))
+        .await;
+    assert!(response.success, "{:?}", response.error);
+
+    // The tenant's own limit overrides the server's
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::create("Group", group(&["a", "b", "c"]))
+                .with_tenant(tenant("large")),
+        )
+        .await;
+    assert!(response.success, "{:?}", response.error);
+    let id = response.metadata.resource_id.unwrap();
+    let response = handler
+        .handle_operation(patch(
+            "large",
+            &id,
+            json!([{"op": "add", "value": {"members": members(&["d"])}}]),
+        ))
+        .await;
+    assert_too_many(response, 4, 3);
+}
@@ -0,0 +1,560 @@
+//! Operation handler tests for PATCH.
+
+use crate::common::servers::TestServerBuilder;
+use scim_server::multi_tenant::ScimOperation;
+use scim_server::operation_handler::{ScimOperationHandler, ScimOperationRequest, ScimQuery};
+use scim_server::providers::StandardResourceProvider;
+use scim_server::storage::InMemoryStorage;
+use scim_server::{PatchMode, TenantContext, TenantStrategy};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_patch_rejects_emptying_required_attributes() {
+    use scim_server::ScimTenantConfiguration;
+
+    let mut server = TestServerBuilder::new()
+        .configure(|builder| builder.with_tenant_strategy(TenantStrategy::PathBased))
+        .with_users([ScimOperation::Create, ScimOperation::Patch])
+        .build_server();
+    let mut configuration = ScimTenantConfiguration::builder("strict".to_string())
+        .build()
+        .unwrap();
+    configuration.schema_config.additional_required = vec!["emails".to_string()];
+    server.register_tenant_configuration(configuration);
+    let handler = ScimOperationHandler::new(server);
+
+    let strict = TenantContext::new("strict".to_string(), "client".to_string());
+    let relaxed = TenantContext::new("relaxed".to_string(), "client".to_string());
+    let create = |tenant: &TenantContext| {
+        ScimOperationRequest::create(
+            "User",
+            json!({
+                "userName": "required.emails",
+                "emails": [{"value": "only@example.com", "type": "work"}]
+            }),
+        )
+        .with_tenant(tenant.clone())
+    };
+    let patch =
+        |id: &str, operation: serde_json::Value, tenant: &TenantContext| ScimOperationRequest {
+            operation: scim_server::operation_handler::ScimOperationType::Patch,
+            resource_type: "User".to_string(),
+            resource_id: Some(id.to_string()),
+            data: Some(json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [operation]
+            })),
+            query: None,
+            tenant_context: Some(tenant.clone()),
+            request_id: None,
+            expected_version: None,
+            idempotency_key: None,
+            context_attributes: Default::default(),
+        };
+
+    let user_id = handler
+        .handle_operation(create(&strict))
+        .await
+        .metadata
+        .resource_id
+        .unwrap();
+
+    // Removing the attribute, or its last value, is rejected and names it
+    for operation in [
+        json!({"op": "remove", "path": "emails"}),
+        json!({"op": "replace", "path": "emails", "value": []}),
+    ] {
+        let response = handler
+            .handle_operation(patch(&user_id, operation, &strict))
+            .await;
+        assert!(!response.success);
+        assert_eq!(response.error_code.as_deref(), Some("VALIDATION_ERROR"));
+        assert!(response.error.unwrap().contains("'emails'"));
+    }
+
+    // Other changes still apply
+    let response = handler
+        .handle_operation(patch(
+            &user_id,
+            json!({"op": "replace", "path": "displayName", "value": "Still Valid"}),
+            &strict,
+        ))
+        .await;
+    assert!(response.success, "patch failed: {:?}", response.error);
+
+    // Tenants without the requirement may remove the attribute
+    let user_id = handler
+        .handle_operation(create(&relaxed))
+        .await
+        .metadata
+        .resource_id
+        .unwrap();
+    let response = handler
+        .handle_operation(patch(
+            &user_id,
+            json!({"op": "remove", "path": "emails"}),
+            &relaxed,
+        ))
+        .await;
+    assert!(response.success, "patch failed: {:?}", response.error);
+}
+
+#[tokio::test]
+async fn test_patch_values_coerced_to_schema_types() {
+    async fn handler_with_coercion(
+        enabled: bool,
+    ) -> ScimOperationHandler<StandardResourceProvider<InMemoryStorage>> {
+        TestServerBuilder::new()
+            .configure(|builder| builder.with_value_coercion(enabled))
+            .with_users([ScimOperation::Create, ScimOperation::Patch])
+            .build_handler()
+    }
+    let patch = |id: &str, operation: serde_json::Value| ScimOperationRequest {
+        operation: scim_server::operation_handler::ScimOperationType::Patch,
+        resource_type: "User".to_string(),
+        resource_id: Some(id.to_string()),
+        data: Some(json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [operation]
+        })),
+        query: None,
+        tenant_context: None,
+        request_id: None,
+        expected_version: None,
+        idempotency_key: None,
+        context_attributes: Default::default(),
+    };
+    let user = json!({
+        "userName": "coerced",
+        "active": "true",
+        "emails": [{"value": "work@example.com", "type": "work", "primary": "false"}]
+    });
+
+    let handler = handler_with_coercion(true).await;
+    let created = handler
+        .handle_operation(ScimOperationRequest::create("User", user.clone()))
+        .await;
+    assert!(created.success, "create failed: {:?}", created.error);
+    let data = created.data.unwrap();
+    assert_eq!(data["active"], json!(true));
+    assert_eq!(data["emails"][0]["primary"], json!(false));
+    let id = data["id"].as_str().unwrap().to_string();
+
+    let response = handler
+        .handle_operation(patch(
+            &id,
+            json!({"op": "replace", "path": "active", "value": "False"}),
+        ))
+        .await;
+    assert!(response.success, "patch failed: {:?}", response.error);
+    assert_eq!(response.data.unwrap()["active"], json!(false));
+
+    let response = handler
+        .handle_operation(patch(
+            &id,
+            json!({
+                "op": "replace",
+                "path": "emails",
+                "value": [{"value": "work@example.com", "type": "work", "primary": "true"}]
+            }),
+        ))
+        .await;
+    assert!(response.success, "patch failed: {:?}", response.error);
+    assert_eq!(response.data.unwrap()["emails"][0]["primary"], json!(true));
+
+    let response = handler
+        .handle_operation(patch(
+            &id,
+            json!({"op": "replace", "value": {"active": "true", "displayName": "true"}}),
+        ))
+        .await;
+    assert!(response.success, "patch failed: {:?}", response.error);
+    let data = response.data.unwrap();
+    assert_eq!(data["active"], json!(true));
+    assert_eq!(data["displayName"], json!("true"));
+
+    // Values that are not of the declared type are still rejected
+    let response = handler
+        .handle_operation(patch(
+            &id,
+            json!({"op": "replace", "path": "active", "value": "maybe"}),
+        ))
+        .await;
+    assert!(!response.success);
+    assert_eq!(response.error_code.as_deref(), Some("VALIDATION_ERROR"));
+    assert!(
+        response
+            .error
+            .unwrap()
+            .contains("invalid boolean value: maybe")
+    );
+    let response = handler
+        .handle_operation(patch(
+            &id,
+            json!({"op": "add", "value": {"emails": [{"value": "x@example.com", "primary": "yes"}]}}),
+        ))
+        .await;
+    assert_eq!(response.error_code.as_deref(), Some("VALIDATION_ERROR"));
+    assert!(response.error.unwrap().contains("'emails.primary'"));
+
+    // The target attribute is resolved through value filters
+    let user_schema = handler
+        .handle_operation(ScimOperationRequest::get_schema(
+            "urn:ietf:params:scim:schemas:core:2.0:User",
+        ))
+        .await
+        .data
+        .unwrap();
+    let user_schema: scim_server::schema::Schema = serde_json::from_value(user_schema).unwrap();
+    let primary = user_schema
+        .attribute_definition("emails[type eq \"work\"].primary")
+        .unwrap();
+    let mut value = json!("TRUE");
+    primary.coerce_value(&mut value).unwrap();
+    assert_eq!(value, json!(true));
+
+    // Without coercion string-encoded values are rejected on create
+    let handler = handler_with_coercion(false).await;
+    let created = handler
+        .handle_operation(ScimOperationRequest::create("User", user))
+        .await;
+    assert_eq!(created.error_code.as_deref(), Some("VALIDATION_ERROR"));
+}
+
+#[tokio::test]
+async fn test_patch_rejects_unpatchable_targets_up_front() {
+    let handler = TestServerBuilder::new()
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Read,
+            ScimOperation::Patch,
+        ])
+        .build_handler();
+
+    let patch = |id: &str, operations: serde_json::Value| ScimOperationRequest {
+        operation: scim_server::operation_handler::ScimOperationType::Patch,
+        resource_type: "User".to_string(),
+        resource_id: Some(id.to_string()),
+        data: Some(json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": operations
+        })),
+        query: None,
+        tenant_context: None,
+        request_id: None,
+        expected_version: None,
+        idempotency_key: None,
+        context_attributes: Default::default(),
+    };
+
+    let id = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "patch.targets", "displayName": "Before"}),
+        ))
+        .await
+        .metadata
+        .resource_id
+        .unwrap();
+    let rename = json!({"op": "replace", "path": "displayName", "value": "After"});
+
+    // The failing operation is named, and earlier ones are not applied
+    for (operations, scim_type, index) in [
+        (
+            json!([rename, {"op": "replace", "path": "favoriteColor", "value": "blue"}]),
+            "invalidPath",
+            1,
+        ),
+        (
+            json!([rename, rename, {"op": "add", "path": "groups", "value": []}]),
+            "mutability",
+            2,
+        ),
+        (
+            json!([{"op": "replace", "value": {"displayName": "After", "shoeSize": 9}}]),
+            "invalidPath",
+            0,
+        ),
+        (
+            json!([{"op": "remove", "path": "meta.created"}]),
+            "mutability",
+            0,
+        ),
+    ] {
+        let response = handler.handle_operation(patch(&id, operations)).await;
+        assert!(!response.success);
+        assert!(
+            response
+                .error
+                .as_deref()
+                .unwrap()
+                .contains(&format!("Operations[{}]", index))
+        );
+        let (status, _, body) = response.into_http_parts();
+        assert_eq!(status, 400);
+        assert_eq!(body["scimType"], scim_type);
+
+        let current = handler
+            .handle_operation(ScimOperationRequest::get("User", &id))
+            .await;
+        assert_eq!(current.data.unwrap()["displayName"], "Before");
+    }
+
+    // Qualified and path-less targets the schema defines all apply
+    let response = handler
+        .handle_operation(patch(
+            &id,
+            json!([
+                {"op": "add", "path": "emails", "value": [{"value": "a@example.com", "type": "work"}]},
+                {"op": "replace", "path": "urn:ietf:params:scim:schemas:core:2.0:User:nickName", "value": "pt"},
+                {"op": "replace", "value": {"displayName": "After", "name": {"givenName": "Pat"}}}
+            ]),
+        ))
+        .await;
+    assert!(response.success, "patch failed: {:?}", response.error);
+    let data = response.data.unwrap();
+    assert_eq!(data["displayName"], "After");
+    assert_eq!(data["nickName"], "pt");
+    assert_eq!(data["emails"][0]["value"], "a@example.com");
+}
+
+#[tokio::test]
+async fn test_per_operation_patch_mode() {
+    let handler_with = |mode: PatchMode| {
+        TestServerBuilder::new()
+            .configure(|builder| builder.with_patch_mode(mode))
+            .with_users([
+                ScimOperation::Create,
+                ScimOperation::Read,
+                ScimOperation::Patch,
+            ])
+            .build_handler()
+    };
+    let patch = |id: &str, operations: serde_json::Value| ScimOperationRequest {
+        operation: scim_server::operation_handler::ScimOperationType::Patch,
+        resource_type: "User".to_string(),
+        resource_id: Some(id.to_string()),
+        data: Some(json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": operations
+        })),
+        query: None,
+        tenant_context: None,
+        request_id: None,
+        expected_version: None,
+        idempotency_key: None,
+        context_attributes: Default::default(),
+    };
+    let operations = json!([
+        {"op": "replace", "path": "displayName", "value": "First"},
+        {"op": "replace", "path": "noSuchAttribute", "value": "ignored"},
+        {"op": "add", "path": "title", "value": "Engineer"}
+    ]);
+
+    for mode in [PatchMode::Atomic, PatchMode::PerOperation] {
+        let handler = handler_with(mode);
+        let created = handler
+            .handle_operation(ScimOperationRequest::create(
+                "User",
+                json!({"userName": "partial", "displayName": "Original"}),
+            ))
+            .await;
+        let id = created.metadata.resource_id.unwrap();
+
+        let response = handler
+            .handle_operation(patch(&id, operations.clone()))
+            .await;
+        let stored = handler
+            .handle_operation(ScimOperationRequest::get("User", &id))
+            .await
+            .data
+            .unwrap();
+
+        if mode == PatchMode::Atomic {
+            // The failing operation rejects the whole request
+            assert!(!response.success);
+            assert_eq!(stored["displayName"], "Original");
+            assert!(stored.get("title").is_none());
+            continue;
+        }
+
+        // The other operations are applied and every outcome is reported
+        assert!(response.success);
+        assert_eq!(response.data.as_ref().unwrap()["displayName"], "First");
+        assert_eq!(stored["displayName"], "First");
+        assert_eq!(stored["title"], "Engineer");
+        let results = &response.metadata.additional["operation_results"];
+        assert_eq!(results[0], json!({"index": 0, "status": "applied"}));
+        assert_eq!(results[1]["index"], 1);
+        assert_eq!(results[1]["status"], "failed");
+        assert_eq!(results[1]["scimType"], "invalidPath");
+        assert_eq!(results[1]["statusCode"], 400);
+        assert_eq!(results[2], json!({"index": 2, "status": "applied"}));
+
+        // A later operation builds on the effect of an earlier one
+        let response = handler
+            .handle_operation(patch(
+                &id,
+                json!([
+                    {"op": "add", "path": "name", "value": {"givenName": "Barbara"}},
+                    {"op": "add", "path": "name.familyName", "value": "Jensen"}
+                ]),
+            ))
+            .await;
+        assert!(response.success);
+        let name = &response.data.unwrap()["name"];
+        assert_eq!(name["givenName"], "Barbara");
+        assert_eq!(name["familyName"], "Jensen");
+
+        // When no operation applies the request fails as an atomic PATCH would
+        let response = handler
+            .handle_operation(patch(
+                &id,
+                json!([{"op": "replace", "path": "noSuchAttribute", "value": 1}]),
+            ))
+            .await;
+        assert!(!response.success);
+    }
+}
+
+#[tokio::test]
+async fn test_per_operation_patch_is_one_write() {
+    use scim_server::ScimServerBuilder;
+    use scim_server::resource::SchemaResourceBuilder;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let saves = Arc::new(AtomicUsize::new(0));
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let mut server = ScimServerBuilder::new(provider)
+        .with_base_url("https://scim.example.com")
+        .with_patch_mode(PatchMode::PerOperation)
+        .build()
+        .unwrap();
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let counter = saves.clone();
+    let handler = SchemaResourceBuilder::new(user_schema)
+        .on_save(move |_data, _context| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        })
+        .build();
+    server
+        .register_resource_type(
+            "User",
+            handler,
+            vec![ScimOperation::Create, ScimOperation::Patch],
+        )
+        .unwrap();
+    let handler = ScimOperationHandler::new(server);
+
+    let created = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "onewrite"}),
+        ))
+        .await;
+    let id = created.metadata.resource_id.unwrap();
+    saves.store(0, Ordering::SeqCst);
+
+    let response = handler
+        .handle_operation(ScimOperationRequest {
+            operation: scim_server::operation_handler::ScimOperationType::Patch,
+            resource_type: "User".to_string(),
+            resource_id: Some(id),
+            data: Some(json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [
+                    {"op": "add", "path": "name", "value": {"givenName": "Barbara"}},
+                    {"op": "add", "path": "nickName"},
+                    {"op": "add", "path": "name.familyName", "value": "Jensen"},
+                    {"op": "replace", "path": "title", "value": "Engineer"}
+                ]
+            })),
+            query: None,
+            tenant_context: None,
+            request_id: None,
+            expected_version: None,
+            idempotency_key: None,
+            context_attributes: Default::default(),
+        })
+        .await;
+    assert!(response.success, "patch failed: {:?}", response.error);
+
+    // The operations that applied are stored together, with the hook run once
+    assert_eq!(saves.load(Ordering::SeqCst), 1);
+    let data = response.data.as_ref().unwrap();
+    assert_eq!(data["name"]["givenName"], "Barbara");
+    assert_eq!(data["name"]["familyName"], "Jensen");
+    assert_eq!(data["title"], "Engineer");
+    let results = &response.metadata.additional["operation_results"];
+    assert_eq!(results[1]["status"], "failed");
+    assert_eq!(results[1]["statusCode"], 400);
+    for index in [0, 2, 3] {
+        assert_eq!(results[index]["status"], "applied");
+    }
+}
+
+#[tokio::test]
+async fn test_patch_never_returns_password() {
+    let handler = TestServerBuilder::new()
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Read,
+            ScimOperation::List,
+            ScimOperation::Patch,
+        ])
+        .build_handler();
+
+    let created = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "bjensen", "password": "t1meMa$heen"}),
+        ))
+        .await;
+    assert!(created.success);
+    assert!(created.data.as_ref().unwrap().get("password").is_none());
+    let id = created.metadata.resource_id.clone().unwrap();
+
+    let patched = handler
+        .handle_operation(ScimOperationRequest {
+            operation: scim_server::operation_handler::ScimOperationType::Patch,
+            resource_type: "User".to_string(),
+            resource_id: Some(id.clone()),
+            data: Some(json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{"op": "replace", "path": "password", "value": "n3wSecr3t"}]
+            })),
+            query: None,
+            tenant_context: None,
+            request_id: None,
+            expected_version: None,
+            idempotency_key: None,
+            context_attributes: Default::default(),
+        })
+        .await;
+    assert!(patched.success, "{:?}", patched.error);
+    let data = patched.data.as_ref().unwrap();
+    assert!(data.get("password").is_none());
+
+    assert_eq!(
+        data["meta"]["version"],
+        patched.metadata.additional["version"]
+    );
+
+    // Explicitly requesting it does not bring it back
+    let fetched = handler
+        .handle_operation(
+            ScimOperationRequest::get("User", &id)
+                .with_query(ScimQuery::new().with_attributes(vec!["password".to_string()])),
+        )
+        .await;
+    assert!(fetched.data.unwrap().get("password").is_none());
+    let listed = handler
+        .handle_operation(ScimOperationRequest::list("User"))
+        .await;
+    assert!(listed.data.unwrap()[0].get("password").is_none());
+}
@@ -0,0 +1,545 @@
+//! Operation handler tests for batches, idempotency, middleware and authorization.
+
+use crate::common::servers::TestServerBuilder;
+use scim_server::multi_tenant::ScimOperation;
+use scim_server::operation_handler::{ScimOperationHandler, ScimOperationRequest};
+use scim_server::providers::StandardResourceProvider;
+use scim_server::storage::InMemoryStorage;
+use scim_server::{ScimServerBuilder, TenantContext, TenantStrategy};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_handle_batch() {
+    let handler = TestServerBuilder::new()
+        .configure(|builder| {
+            builder
+                .with_base_url("https://scim.example.com")
+                .with_tenant_strategy(TenantStrategy::PathBased)
+        })
+        .with_users([ScimOperation::Create, ScimOperation::List])
+        .build_handler();
+
+    let acme = TenantContext::new("acme".to_string(), "client-a".to_string());
+    let globex = TenantContext::new("globex".to_string(), "client-g".to_string());
+    let batch = |user_name: &str| {
+        vec![
+            ScimOperationRequest::create("User", json!({"userName": user_name}))
+                .with_tenant(acme.clone()),
+            ScimOperationRequest::create("User", json!({"active": true})).with_tenant(acme.clone()),
+            // userName only needs to be unique within a tenant
+            ScimOperationRequest::create("User", json!({"userName": user_name}))
+                .with_tenant(globex.clone()),
+        ]
+    };
+
+    for concurrent in [false, true] {
+        let user_name = if concurrent {
+            "concurrent"
+        } else {
+            "sequential"
+        };
+        let responses = if concurrent {
+            handler.handle_batch_concurrent(batch(user_name)).await
+        } else {
+            handler.handle_batch(batch(user_name)).await
+        };
+
+        let outcomes: Vec<_> = responses.iter().map(|response| response.success).collect();
+        assert_eq!(outcomes, [true, false, true]);
+        assert_eq!(responses[0].metadata.tenant_id.as_deref(), Some("acme"));
+        assert_eq!(responses[2].metadata.tenant_id.as_deref(), Some("globex"));
+        assert_eq!(responses[0].data.as_ref().unwrap()["userName"], user_name);
+        assert!(responses[1].error.is_some());
+    }
+
+    let listed = handler
+        .handle_batch(vec![
+            ScimOperationRequest::list("User").with_tenant(acme),
+            ScimOperationRequest::list("User").with_tenant(globex),
+        ])
+        .await;
+    assert_eq!(listed[0].metadata.total_results, Some(2));
+    assert_eq!(listed[1].metadata.total_results, Some(2));
+}
+
+#[tokio::test]
+async fn test_idempotency_key_replays_mutating_operations() {
+    use scim_server::operation_handler::InMemoryIdempotencyStore;
+    use scim_server::operation_handler::idempotency::{
+        IdempotencyFingerprint, IdempotencyKey, IdempotencyStore,
+    };
+    use std::sync::Arc;
+
+    let server = TestServerBuilder::new()
+        .configure(|builder| {
+            builder
+                .with_base_url("https://scim.example.com")
+                .with_tenant_strategy(TenantStrategy::PathBased)
+        })
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Update,
+            ScimOperation::List,
+        ])
+        .build_server();
+    let store = Arc::new(InMemoryIdempotencyStore::default());
+    let handler = ScimOperationHandler::new(server).with_idempotency_store(store.clone());
+
+    let acme = TenantContext::new("acme".to_string(), "client-a".to_string());
+    let globex = TenantContext::new("globex".to_string(), "client-g".to_string());
+    let create = |user_name: &str, tenant: &TenantContext| {
+        ScimOperationRequest::create("User", json!({"userName": user_name}))
+            .with_tenant(tenant.clone())
+            .with_idempotency_key("key-1")
+    };
+    let user_count = |tenant: &TenantContext| {
+        let request = ScimOperationRequest::list("User").with_tenant(tenant.clone());
+        let handler = &handler;
+        async move {
+            handler
+                .handle_operation(request)
+                .await
+                .metadata
+                .total_results
+        }
+    };
+
+    // Concurrent deliveries of the same create apply it once
+    let responses =
+        futures::future::join_all((0..4).map(|_| handler.handle_operation(create("alice", &acme))))
+            .await;
+    assert_eq!(user_count(&acme).await, Some(1));
+    let first = &responses[0];
+    assert!(first.success);
+    assert!(!first.metadata.additional.contains_key("idempotent_replay"));
+    for replay in &responses[1..] {
+        assert_eq!(replay.metadata.resource_id, first.metadata.resource_id);
+        assert_eq!(replay.metadata.additional["idempotent_replay"], true);
+    }
+
+    // Keys are scoped to the tenant
+    let other_tenant = handler.handle_operation(create("alice", &globex)).await;
+    assert!(other_tenant.success);
+    assert!(
+        !other_tenant
+            .metadata
+            .additional
+            .contains_key("idempotent_replay")
+    );
+    assert_eq!(user_count(&globex).await, Some(1));
+
+    // A key cannot be reused for a different request
+    let user_id = first.metadata.resource_id.clone().unwrap();
+    let update = handler
+        .handle_operation(
+            ScimOperationRequest::update("User", &user_id, json!({"userName": "alice2"}))
+                .with_tenant(acme.clone())
+                .with_idempotency_key("key-1"),
+        )
+        .await;
+    assert!(!update.success);
+    assert_eq!(
+        update.error_code.as_deref(),
+        Some("IDEMPOTENCY_KEY_MISMATCH")
+    );
+
+    // Nor for the same operation with a different body
+    let different_body = handler.handle_operation(create("mallory", &acme)).await;
+    assert_eq!(
+        different_body.error_code.as_deref(),
+        Some("IDEMPOTENCY_KEY_MISMATCH")
+    );
+
+    // A replay that arrives while the original is executing is rejected
+    let in_flight = IdempotencyKey {
+        tenant_id: Some("acme".to_string()),
+        key: "key-2".to_string(),
+    };
+    let request = ScimOperationRequest::create("User", json!({"userName": "bob"}))
+        .with_tenant(acme.clone())
+        .with_idempotency_key("key-2");
+    store.claim(&in_flight, &IdempotencyFingerprint::of(&request));
+    let response = handler.handle_operation(request).await;
+    assert_eq!(
+        response.error_code.as_deref(),
+        Some("IDEMPOTENCY_KEY_IN_PROGRESS")
+    );
+
+    // Failures release the key so a corrected retry runs
+    let failed = handler
+        .handle_operation(
+            ScimOperationRequest::create("User", json!({"userName": "alice"}))
+                .with_tenant(acme.clone())
+                .with_idempotency_key("key-3"),
+        )
+        .await;
+    assert!(!failed.success);
+    let retried = handler
+        .handle_operation(
+            ScimOperationRequest::create("User", json!({"userName": "carol"}))
+                .with_tenant(acme.clone())
+                .with_idempotency_key("key-3"),
+        )
+        .await;
+    assert!(retried.success);
+
+    // Reads ignore the key
+    let list = handler
+        .handle_operation(
+            ScimOperationRequest::list("User")
+                .with_tenant(acme.clone())
+                .with_idempotency_key("key-1"),
+        )
+        .await;
+    assert!(list.success);
+    assert_eq!(list.metadata.total_results, Some(2));
+}
+
+#[tokio::test]
+async fn test_idempotency_key_released_when_operation_is_dropped() {
+    use scim_server::operation_handler::InMemoryIdempotencyStore;
+    use scim_server::providers::{UniquenessChecker, UniquenessQuery};
+    use scim_server::storage::StorageKey;
+    use std::sync::Arc;
+
+    // Stalls every create at its uniqueness check
+    struct Stalled;
+
+    impl UniquenessChecker<InMemoryStorage> for Stalled {
+        async fn find_conflict(
+            &self,
+            _storage: &InMemoryStorage,
+            _query: &UniquenessQuery<'_>,
+        ) -> Result<Option<StorageKey>, scim_server::storage::StorageError> {
+            std::future::pending().await
+        }
+    }
+
+    let server = TestServerBuilder::with_provider(
+        StandardResourceProvider::with_uniqueness_checker(InMemoryStorage::new(), Stalled),
+    )
+    .configure(|builder| builder.with_base_url("https://scim.example.com"))
+    .with_users([ScimOperation::Create])
+    .build_server();
+    let store = Arc::new(InMemoryIdempotencyStore::default());
+    let handler = ScimOperationHandler::new(server).with_idempotency_store(store.clone());
+
+    let mut operation = Box::pin(
+        handler.handle_operation(
+            ScimOperationRequest::create("User", json!({"userName": "alice"}))
+                .with_idempotency_key("key-1"),
+        ),
+    );
+    assert!(futures::poll!(operation.as_mut()).is_pending());
+    assert_eq!(store.len(), 1);
+
+    // Cancelling the request frees its key instead of leaving it in progress
+    drop(operation);
+    assert!(store.is_empty());
+}
+
+#[tokio::test]
+async fn test_middleware_chain() {
+    use scim_server::ScimError;
+    use scim_server::operation_handler::{OperationMiddleware, ScimOperationResponse};
+    use scim_server::resource::RequestContext;
+    use std::sync::{Arc, Mutex};
+
+    /// Records hook calls and fills in a default `title` on creates.
+    struct Recorder {
+        name: &'static str,
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl OperationMiddleware for Recorder {
+        fn before(
+            &self,
+            request: &mut ScimOperationRequest,
+            context: &RequestContext,
+        ) -> scim_server::ScimResult<()> {
+            self.calls.lock().unwrap().push(format!(
+                "{} before {}",
+                self.name,
+                context.tenant_id().unwrap_or("-")
+            ));
+            if let Some(data) = request.data.as_mut().and_then(|data| data.as_object_mut()) {
+                data.entry("title").or_insert(json!(self.name));
+            }
+            Ok(())
+        }
+
+        fn after(&self, response: &mut ScimOperationResponse, _context: &RequestContext) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{} after {}", self.name, response.success));
+        }
+    }
+
+    /// Rejects userNames that are not lowercase.
+    struct LowercaseUserNames;
+
+    impl OperationMiddleware for LowercaseUserNames {
+        fn before(
+            &self,
+            request: &mut ScimOperationRequest,
+            _context: &RequestContext,
+        ) -> scim_server::ScimResult<()> {
+            let user_name = request
+                .data
+                .as_ref()
+                .and_then(|data| data["userName"].as_str());
+            match user_name {
+                Some(name) if name != name.to_lowercase() => {
+                    Err(ScimError::invalid_request("userName must be lowercase"))
+                }
+                _ => Ok(()),
+            }
+        }
+    }
+
+    let server = TestServerBuilder::new()
+        .configure(|builder| {
+            builder
+                .with_base_url("https://scim.example.com")
+                .with_tenant_strategy(TenantStrategy::PathBased)
+        })
+        .with_users([ScimOperation::Create, ScimOperation::List])
+        .build_server();
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let handler = ScimOperationHandler::new(server)
+        .with_middleware(Recorder {
+            name: "outer",
+            calls: calls.clone(),
+        })
+        .with_middleware(LowercaseUserNames)
+        .with_middleware(Recorder {
+            name: "inner",
+            calls: calls.clone(),
+        });
+    let acme = TenantContext::new("acme".to_string(), "client-a".to_string());
+
+    // Before hooks run in registration order and after hooks in reverse,
+    // with the request's tenant context
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::create("User", json!({"userName": "alice"}))
+                .with_tenant(acme.clone()),
+        )
+        .await;
+    assert!(response.success, "create failed: {:?}", response.error);
+    assert_eq!(response.data.unwrap()["title"], "outer");
+    assert_eq!(
+        *calls.lock().unwrap(),
+        [
+            "outer before acme",
+            "inner before acme",
+            "inner after true",
+            "outer after true"
+        ]
+    );
+
+    // A rejecting before hook short-circuits the rest of the chain
+    calls.lock().unwrap().clear();
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::create("User", json!({"userName": "Bob"}))
+                .with_tenant(acme.clone()),
+        )
+        .await;
+    assert!(!response.success);
+    assert!(
+        response
+            .error
+            .unwrap()
+            .contains("userName must be lowercase")
+    );
+    assert_eq!(
+        *calls.lock().unwrap(),
+        ["outer before acme", "outer after false"]
+    );
+
+    let response = handler
+        .handle_operation(ScimOperationRequest::list("User").with_tenant(acme))
+        .await;
+    assert_eq!(response.metadata.total_results, Some(1));
+}
+
+#[tokio::test]
+async fn test_context_attributes_reach_hooks() {
+    use scim_server::operation_handler::{OperationMiddleware, ScimOperationResponse};
+    use scim_server::resource::{RequestContext, SchemaResourceBuilder};
+    use std::sync::{Arc, Mutex};
+
+    /// Records the client IP each request was made from.
+    struct ClientIpAudit(Arc<Mutex<Vec<String>>>);
+
+    impl OperationMiddleware for ClientIpAudit {
+        fn after(&self, _response: &mut ScimOperationResponse, context: &RequestContext) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(context.attribute("client_ip").unwrap_or("-").to_string());
+        }
+    }
+
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let mut server = ScimServerBuilder::new(provider)
+        .with_base_url("https://scim.example.com")
+        .build()
+        .unwrap();
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let handler = SchemaResourceBuilder::new(user_schema)
+        .on_save(|data, context| {
+            if let Some(agent) = context.attribute("user_agent") {
+                data["title"] = json!(agent);
+            }
+            Ok(())
+        })
+        .build();
+    server
+        .register_resource_type("User", handler, vec![ScimOperation::Create])
+        .unwrap();
+
+    let audit = Arc::new(Mutex::new(Vec::new()));
+    let handler = ScimOperationHandler::new(server).with_middleware(ClientIpAudit(audit.clone()));
+
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::create("User", json!({"userName": "alice"}))
+                .with_context_attribute("client_ip", "203.0.113.7")
+                .with_context_attribute("user_agent", "okta-scim/1.0"),
+        )
+        .await;
+    assert!(response.success, "create failed: {:?}", response.error);
+    assert_eq!(response.data.unwrap()["title"], "okta-scim/1.0");
+
+    // Attributes are per request
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "bob"}),
+        ))
+        .await;
+    assert!(response.success, "create failed: {:?}", response.error);
+    assert!(response.data.unwrap().get("title").is_none());
+    assert_eq!(*audit.lock().unwrap(), ["203.0.113.7", "-"]);
+}
+
+#[tokio::test]
+async fn test_authorization_policy() {
+    use scim_server::{AuthorizationPolicy, RequestContext, ScimError};
+    use std::sync::{Arc, Mutex};
+
+    type Decision = (
+        Option<String>,
+        Option<String>,
+        ScimOperation,
+        String,
+        Option<String>,
+    );
+
+    /// Records every decision and denies deletes by anyone but the provisioner.
+    struct RecordingPolicy(Arc<Mutex<Vec<Decision>>>);
+
+    impl AuthorizationPolicy for RecordingPolicy {
+        fn authorize(
+            &self,
+            context: &RequestContext,
+            operation: &ScimOperation,
+            resource_type: &str,
+            resource_id: Option<&str>,
+        ) -> Result<(), ScimError> {
+            self.0.lock().unwrap().push((
+                context.tenant_id().map(str::to_string),
+                context.client_id().map(str::to_string),
+                operation.clone(),
+                resource_type.to_string(),
+                resource_id.map(str::to_string),
+            ));
+            if *operation == ScimOperation::Delete && context.client_id() != Some("provisioner") {
+                return Err(ScimError::authorization_denied(
+                    operation,
+                    resource_type,
+                    resource_id,
+                    "only the provisioner may delete",
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    let mut server = TestServerBuilder::new()
+        .configure(|builder| builder.with_tenant_strategy(TenantStrategy::PathBased))
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Read,
+            ScimOperation::Delete,
+        ])
+        .build_server();
+    let decisions = Arc::new(Mutex::new(Vec::new()));
+    server.set_authorization_policy(RecordingPolicy(decisions.clone()));
+    let handler = ScimOperationHandler::new(server);
+    let tenant = |client: &str| TenantContext::new("acme".to_string(), client.to_string());
+
+    let created = handler
+        .handle_operation(
+            ScimOperationRequest::create("User", json!({"userName": "guarded"}))
+                .with_tenant(tenant("helpdesk")),
+        )
+        .await;
+    assert!(created.success);
+    let id = created.metadata.resource_id.clone().unwrap();
+
+    // The policy sees tenant, client, operation and target
+    let denied = handler
+        .handle_operation(ScimOperationRequest::delete("User", &id).with_tenant(tenant("helpdesk")))
+        .await;
+    assert!(!denied.success);
+    assert_eq!(denied.error_code.as_deref(), Some("AUTHORIZATION_DENIED"));
+    let detail = denied.error.clone().unwrap();
+    assert!(
+        detail.contains("only the provisioner may delete"),
+        "{}",
+        detail
+    );
+    assert!(detail.contains(&format!("User/{}", id)), "{}", detail);
+    let (status, _, body) = denied.into_http_parts();
+    assert_eq!(status, 403);
+    assert_eq!(body["status"], "403");
+    assert_eq!(
+        decisions.lock().unwrap().clone(),
+        vec![
+            (
+                Some("acme".to_string()),
+                Some("helpdesk".to_string()),
+                ScimOperation::Create,
+                "User".to_string(),
+                None,
+            ),
+            (
+                Some("acme".to_string()),
+                Some("helpdesk".to_string()),
+                ScimOperation::Delete,
+                "User".to_string(),
+                Some(id.clone()),
+            ),
+        ]
+    );
+
+    // The resource survives the denied delete, and an allowed one goes through
+    let read = handler
+        .handle_operation(ScimOperationRequest::get("User", &id).with_tenant(tenant("helpdesk")))
+        .await;
+    assert!(read.success);
+    let deleted = handler
+        .handle_operation(
+            ScimOperationRequest::delete("User", &id).with_tenant(tenant("provisioner")),
+        )
+        .await;
+    assert!(deleted.success);
+}
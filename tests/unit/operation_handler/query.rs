@@ -0,0 +1,546 @@
+//! Operation handler tests for list queries, filtering and projection.
+
+use crate::common::servers::TestServerBuilder;
+use scim_server::ScimServer;
+use scim_server::multi_tenant::ScimOperation;
+use scim_server::operation_handler::{ScimOperationHandler, ScimOperationRequest, ScimQuery};
+use scim_server::resource::SortOrder;
+use scim_server::{DefaultListOrder, TenantContext, TenantStrategy};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_create_and_update_responses_honour_attribute_projection() {
+    let handler = TestServerBuilder::new()
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Update,
+            ScimOperation::Read,
+        ])
+        .build_handler();
+
+    let create_request = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "projected",
+            "displayName": "Projected User",
+            "name": {
+                "givenName": "Projected",
+                "familyName": "User"
+            }
+        }),
+    )
+    .with_query(ScimQuery::new().with_attributes(vec!["userName".to_string()]));
+
+    let create_response = handler.handle_operation(create_request).await;
+    assert!(create_response.success);
+    let data = create_response.data.unwrap();
+    assert_eq!(data["userName"], "projected");
+    assert!(data.get("displayName").is_none());
+    assert!(data.get("name").is_none());
+    assert!(data.get("id").is_some());
+    assert!(data.get("meta").is_some());
+
+    let user_id = data["id"].as_str().unwrap().to_string();
+
+    // The full resource was persisted
+    let get_response = handler
+        .handle_operation(ScimOperationRequest::get("User", &user_id))
+        .await;
+    let stored = get_response.data.unwrap();
+    assert_eq!(stored["displayName"], "Projected User");
+    assert_eq!(stored["name"]["givenName"], "Projected");
+
+    let update_request = ScimOperationRequest::update(
+        "User",
+        &user_id,
+        json!({
+            "id": user_id,
+            "userName": "projected",
+            "displayName": "Renamed User",
+            "name": {
+                "givenName": "Projected",
+                "familyName": "User"
+            }
+        }),
+    )
+    .with_query(ScimQuery::new().with_excluded_attributes(vec!["name".to_string()]));
+
+    let update_response = handler.handle_operation(update_request).await;
+    assert!(update_response.success);
+    let data = update_response.data.unwrap();
+    assert_eq!(data["displayName"], "Renamed User");
+    assert!(data.get("name").is_none());
+    assert!(data.get("id").is_some());
+    assert!(data.get("meta").is_some());
+}
+
+#[tokio::test]
+async fn test_list_honours_query_like_provider() {
+    let handler = TestServerBuilder::new()
+        .with_users([ScimOperation::Create, ScimOperation::List])
+        .build_handler();
+
+    for user_name in ["carol", "alice", "bob", "dave"] {
+        let response = handler
+            .handle_operation(ScimOperationRequest::create(
+                "User",
+                json!({
+                    "userName": user_name,
+                    "displayName": format!("User {}", user_name)
+                }),
+            ))
+            .await;
+        assert!(response.success);
+    }
+
+    let request = ScimOperationRequest::list("User").with_query(
+        ScimQuery::new()
+            .with_filter(r#"userName ne "dave""#)
+            .with_sort("userName", SortOrder::Descending)
+            .with_pagination(1, 2)
+            .with_attributes(vec!["userName".to_string()]),
+    );
+    let response = handler.handle_operation(request).await;
+    assert!(response.success);
+
+    let resources = response.data.unwrap();
+    let resources = resources.as_array().unwrap();
+    let user_names: Vec<_> = resources
+        .iter()
+        .map(|r| r["userName"].as_str().unwrap())
+        .collect();
+    assert_eq!(user_names, ["carol", "bob"]);
+    assert!(resources.iter().all(|r| r.get("displayName").is_none()));
+
+    // totalResults counts every match, not just the page
+    assert_eq!(response.metadata.resource_count, Some(2));
+    assert_eq!(response.metadata.total_results, Some(3));
+    assert_eq!(response.metadata.additional["start_index"], 1);
+
+    // A page past the end is empty but still reports the filtered total
+    let request = ScimOperationRequest::list("User").with_query(
+        ScimQuery::new()
+            .with_filter(r#"userName ne "dave""#)
+            .with_pagination(10, 2),
+    );
+    let response = handler.handle_operation(request).await;
+    assert!(response.success);
+    assert_eq!(response.data.as_ref().unwrap().as_array().unwrap().len(), 0);
+    assert_eq!(response.metadata.resource_count, Some(0));
+    assert_eq!(response.metadata.total_results, Some(3));
+    assert_eq!(response.metadata.additional["start_index"], 10);
+    let (status, _, body) = response.into_http_parts();
+    assert_eq!(status, 200);
+    assert_eq!(body["Resources"], json!([]));
+    assert_eq!(body["itemsPerPage"], 0);
+    assert_eq!(body["totalResults"], 3);
+
+    // The last result is at startIndex == totalResults
+    let request = ScimOperationRequest::list("User").with_query(
+        ScimQuery::new()
+            .with_filter(r#"userName ne "dave""#)
+            .with_sort("userName", SortOrder::Ascending)
+            .with_pagination(3, 2),
+    );
+    let response = handler.handle_operation(request).await;
+    assert_eq!(response.data.unwrap()[0]["userName"], "carol");
+    assert_eq!(response.metadata.resource_count, Some(1));
+
+    // startIndex is 1-based, so 0 is rejected for lists and searches
+    for request in [
+        ScimOperationRequest::list("User"),
+        ScimOperationRequest::search("User", "userName", json!("carol")),
+    ] {
+        let query = request
+            .query
+            .clone()
+            .unwrap_or_default()
+            .with_pagination(0, 2);
+        let (status, _, body) = handler
+            .handle_operation(request.with_query(query))
+            .await
+            .into_http_parts();
+        assert_eq!(status, 400);
+        assert_eq!(body["scimType"], "invalidValue");
+    }
+
+    let request =
+        ScimOperationRequest::list("User").with_query(ScimQuery::new().with_filter("userName eq"));
+    let response = handler.handle_operation(request).await;
+    assert!(!response.success);
+    assert_eq!(response.error_code.as_deref(), Some("INVALID_FILTER"));
+    let (status, _, body) = response.into_http_parts();
+    assert_eq!(status, 400);
+    assert_eq!(body["scimType"], "invalidFilter");
+}
+
+#[tokio::test]
+async fn test_default_list_order() {
+    let handler = TestServerBuilder::new()
+        .configure(|builder| builder.with_default_list_order(DefaultListOrder::CreatedDescending))
+        .with_users([ScimOperation::Create, ScimOperation::List])
+        .build_handler();
+
+    for user_name in ["bob", "alice", "carol"] {
+        let response = handler
+            .handle_operation(ScimOperationRequest::create(
+                "User",
+                json!({"userName": user_name}),
+            ))
+            .await;
+        assert!(response.success);
+        // Distinct creation timestamps at millisecond precision
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+
+    let user_names = |query: ScimQuery| {
+        let handler = &handler;
+        async move {
+            let response = handler
+                .handle_operation(ScimOperationRequest::list("User").with_query(query))
+                .await;
+            response
+                .data
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|r| r["userName"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    // Newest first without sortBy, across pages
+    assert_eq!(
+        user_names(ScimQuery::new().with_pagination(1, 2)).await,
+        ["carol", "alice"]
+    );
+    assert_eq!(
+        user_names(ScimQuery::new().with_pagination(3, 2)).await,
+        ["bob"]
+    );
+    let response = handler
+        .handle_operation(ScimOperationRequest::list("User"))
+        .await;
+    assert_eq!(response.data.unwrap()[0]["userName"], "carol");
+
+    // An explicit sortBy overrides the default
+    assert_eq!(
+        user_names(ScimQuery::new().with_sort("userName", SortOrder::Ascending)).await,
+        ["alice", "bob", "carol"]
+    );
+    assert_eq!(
+        user_names(ScimQuery::new().with_sort("meta.created", SortOrder::Ascending)).await,
+        ["bob", "alice", "carol"]
+    );
+}
+
+#[tokio::test]
+async fn test_resource_type_page_size_caps() {
+    use scim_server::ScimTenantConfiguration;
+
+    let mut server = TestServerBuilder::new()
+        .configure(|builder| {
+            builder
+                .with_tenant_strategy(TenantStrategy::PathBased)
+                .with_max_page_size(3)
+                .with_resource_type_max_page_size("Group", 2)
+                .with_resource_type_page_size_discovery(true)
+        })
+        .with_users([ScimOperation::Create, ScimOperation::List])
+        .with_groups([ScimOperation::Create, ScimOperation::List])
+        .build_server();
+    server.register_tenant_configuration(
+        ScimTenantConfiguration::builder("large".to_string())
+            .with_resource_type_max_results("User", 5)
+            .build()
+            .unwrap(),
+    );
+
+    // The ServiceProviderConfig reports the global cap, ResourceTypes their own
+    assert_eq!(
+        server
+            .get_service_provider_config()
+            .unwrap()
+            .filter_max_results,
+        Some(3)
+    );
+    let tenant = |id: &str| TenantContext::new(id.to_string(), "client".to_string());
+    let advertised = |server: &ScimServer<_>, tenant_id: &str, resource_type: &str| {
+        let context = scim_server::RequestContext::with_tenant_generated_id(tenant(tenant_id));
+        server
+            .get_resource_type_definition(resource_type, &context)
+            .unwrap()["maxResults"]
+            .clone()
+    };
+    assert_eq!(advertised(&server, "small", "User"), json!(3));
+    assert_eq!(advertised(&server, "small", "Group"), json!(2));
+    assert_eq!(advertised(&server, "large", "User"), json!(5));
+
+    let handler = ScimOperationHandler::new(server);
+    for tenant_id in ["small", "large"] {
+        for i in 0..6 {
+            for request in [
+                ScimOperationRequest::create("User", json!({"userName": format!("user{}", i)})),
+                ScimOperationRequest::create(
+                    "Group",
+                    json!({"displayName": format!("group{}", i)}),
+                ),
+            ] {
+                let response = handler
+                    .handle_operation(request.with_tenant(tenant(tenant_id)))
+                    .await;
+                assert!(response.success, "{:?}", response.error);
+            }
+        }
+    }
+
+    // The most specific cap applies, with or without a requested count
+    let page = |tenant_id: &str, resource_type: &str, count: Option<usize>| {
+        let mut request = ScimOperationRequest::list(resource_type).with_tenant(tenant(tenant_id));
+        if let Some(count) = count {
+            request = request.with_query(ScimQuery::new().with_pagination(1, count));
+        }
+        let handler = &handler;
+        async move {
+            let response = handler.handle_operation(request).await;
+            assert!(response.success, "{:?}", response.error);
+            assert_eq!(response.metadata.total_results, Some(6));
+            response.metadata.resource_count.unwrap()
+        }
+    };
+    assert_eq!(page("small", "User", None).await, 3);
+    assert_eq!(page("small", "User", Some(10)).await, 3);
+    assert_eq!(page("small", "User", Some(1)).await, 1);
+    assert_eq!(page("small", "Group", Some(10)).await, 2);
+    assert_eq!(page("large", "User", Some(10)).await, 5);
+    assert_eq!(page("large", "Group", None).await, 2);
+}
+
+#[tokio::test]
+async fn test_request_query_builders() {
+    let handler = TestServerBuilder::new()
+        .with_users([ScimOperation::Create, ScimOperation::List])
+        .build_handler();
+
+    for user_name in ["carol", "alice", "bob"] {
+        let response = handler
+            .handle_operation(ScimOperationRequest::create(
+                "User",
+                json!({"userName": user_name, "displayName": "Staff"}),
+            ))
+            .await;
+        assert!(response.success);
+    }
+
+    // The builders produce the same query as building ScimQuery by hand
+    let request = ScimOperationRequest::list("User")
+        .with_filter(r#"userName ne "alice""#)
+        .with_sort("userName", SortOrder::Descending)
+        .with_pagination(1, 1)
+        .with_attributes(vec!["userName".to_string()]);
+    assert_eq!(
+        request.query,
+        Some(
+            ScimQuery::new()
+                .with_filter(r#"userName ne "alice""#)
+                .with_sort("userName", SortOrder::Descending)
+                .with_pagination(1, 1)
+                .with_attributes(vec!["userName".to_string()])
+        )
+    );
+    let response = handler.handle_operation(request).await;
+    assert!(response.success);
+    assert_eq!(response.metadata.total_results, Some(2));
+    let resources = response.data.unwrap();
+    assert_eq!(resources[0]["userName"], "carol");
+    assert!(resources[0].get("displayName").is_none());
+
+    // Searches keep their search attribute alongside the builders
+    let request = ScimOperationRequest::search("User", "displayName", json!("Staff"))
+        .with_sort("userName", SortOrder::Ascending)
+        .with_excluded_attributes(vec!["displayName".to_string()]);
+    let query = request.query.as_ref().unwrap();
+    assert_eq!(query.search_attribute.as_deref(), Some("displayName"));
+    let response = handler.handle_operation(request).await;
+    assert!(response.success);
+    let resources = response.data.unwrap();
+    assert_eq!(resources[0]["userName"], "alice");
+    assert!(resources[0].get("displayName").is_none());
+
+    // attributes and excludedAttributes are mutually exclusive
+    for request in [
+        ScimOperationRequest::list("User"),
+        ScimOperationRequest::search("User", "displayName", json!("Staff")),
+    ] {
+        let request = request
+            .with_attributes(vec!["userName".to_string()])
+            .with_excluded_attributes(vec!["displayName".to_string()]);
+        let response = handler.handle_operation(request).await;
+        assert!(!response.success);
+        assert_eq!(response.error_code.as_deref(), Some("INVALID_REQUEST"));
+        assert_eq!(response.into_http_parts().0, 400);
+    }
+}
+
+#[tokio::test]
+async fn test_filter_applies_before_projection() {
+    let handler = TestServerBuilder::new()
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::List,
+            ScimOperation::Search,
+        ])
+        .build_handler();
+
+    for (user_name, title) in [
+        ("alice", "Engineer"),
+        ("bob", "Manager"),
+        ("carol", "Engineer"),
+    ] {
+        let response = handler
+            .handle_operation(ScimOperationRequest::create(
+                "User",
+                json!({"userName": user_name, "title": title, "nickName": "x"}),
+            ))
+            .await;
+        assert!(response.success);
+    }
+
+    let user_names = |response: scim_server::ScimOperationResponse| {
+        let resources = response.data.unwrap();
+        let resources = resources.as_array().unwrap().clone();
+        assert!(resources.iter().all(|r| r.get("title").is_none()));
+        let mut names: Vec<_> = resources
+            .iter()
+            .map(|r| r["userName"].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    };
+
+    // Filtering on an attribute left out by `attributes`...
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::list("User").with_query(
+                ScimQuery::new()
+                    .with_filter(r#"title eq "Engineer""#)
+                    .with_attributes(vec!["userName".to_string()]),
+            ),
+        )
+        .await;
+    assert_eq!(user_names(response), ["alice", "carol"]);
+
+    // ...or removed by `excludedAttributes`
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::list("User").with_query(
+                ScimQuery::new()
+                    .with_filter(r#"title eq "Manager""#)
+                    .with_excluded_attributes(vec!["title".to_string()]),
+            ),
+        )
+        .await;
+    assert_eq!(user_names(response), ["bob"]);
+
+    // Search matches on complete resources too, then projects and paginates
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::search("User", "title", json!("Engineer")).with_query(
+                ScimQuery::new()
+                    .with_search("title", json!("Engineer"))
+                    .with_filter(r#"userName ne "alice""#)
+                    .with_excluded_attributes(vec!["title".to_string()]),
+            ),
+        )
+        .await;
+    assert_eq!(user_names(response), ["carol"]);
+
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::search("User", "title", json!("Engineer")).with_query(
+                ScimQuery::new()
+                    .with_search("nickName", json!("x"))
+                    .with_pagination(2, 1)
+                    .with_attributes(vec!["userName".to_string()]),
+            ),
+        )
+        .await;
+    assert_eq!(response.metadata.resource_count, Some(1));
+    let resources = response.data.unwrap();
+    assert!(resources[0].get("nickName").is_none());
+}
+
+#[tokio::test]
+async fn test_filters_respect_case_exact_attributes() {
+    let server = TestServerBuilder::new()
+        .with_users([ScimOperation::Create, ScimOperation::List])
+        .build_server();
+    assert!(
+        server
+            .case_exact_attributes("User")
+            .contains(&"externalId".to_string())
+    );
+    let handler = ScimOperationHandler::new(server);
+
+    for (user_name, external_id, email) in [
+        ("alice", "HR-1", "alice@example.com"),
+        ("admin.bob", "hr-2", "bob@EXAMPLE.com"),
+    ] {
+        let response = handler
+            .handle_operation(ScimOperationRequest::create(
+                "User",
+                json!({
+                    "userName": user_name,
+                    "externalId": external_id,
+                    "emails": [
+                        {"value": email, "type": "work"},
+                        {"value": format!("{}@home.org", user_name), "type": "home"}
+                    ]
+                }),
+            ))
+            .await;
+        assert!(response.success, "{:?}", response.error);
+    }
+    let matching = |filter: &str| {
+        let request = ScimOperationRequest::list("User").with_query(
+            ScimQuery::new()
+                .with_filter(filter)
+                .with_sort("userName", SortOrder::Ascending),
+        );
+        let handler = &handler;
+        async move {
+            let response = handler.handle_operation(request).await;
+            assert!(response.success, "{:?}", response.error);
+            assert_eq!(
+                response.metadata.total_results,
+                Some(response.data.as_ref().unwrap().as_array().unwrap().len())
+            );
+            response
+                .data
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|user| user["userName"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    // caseExact attributes compare case-sensitively, others do not
+    assert_eq!(matching(r#"externalId sw "HR""#).await, ["alice"]);
+    assert_eq!(matching(r#"externalId ew "-2""#).await, ["admin.bob"]);
+    assert_eq!(matching(r#"userName sw "ADMIN""#).await, ["admin.bob"]);
+
+    // Multi-valued attributes match when any value does
+    assert_eq!(
+        matching(r#"emails[value co "@example.com"]"#).await,
+        ["admin.bob", "alice"]
+    );
+    assert_eq!(matching(r#"emails.value sw "admin""#).await, ["admin.bob"]);
+    assert_eq!(
+        matching(r#"not (emails.value ew "@home.org")"#).await,
+        Vec::<String>::new()
+    );
+    assert_eq!(matching(r#"not (emails.value co "bob")"#).await, ["alice"]);
+}
@@ -0,0 +1,402 @@
+//! Operation handler tests for response shape: locations, externalId echo and HTTP parts.
+
+use crate::common::servers::TestServerBuilder;
+use scim_server::multi_tenant::ScimOperation;
+use scim_server::operation_handler::{ScimOperationHandler, ScimOperationRequest, ScimQuery};
+use scim_server::providers::StandardResourceProvider;
+use scim_server::resource::version::RawVersion;
+use scim_server::storage::InMemoryStorage;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_base_path_applies_to_location_and_refs() {
+    let handler = TestServerBuilder::new()
+        .configure(|builder| {
+            builder
+                .with_base_url("https://scim.example.com")
+                .with_base_path("/api/scim")
+        })
+        .with_users([ScimOperation::Create])
+        .with_groups([ScimOperation::Create])
+        .build_handler();
+
+    let user = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "based"}),
+        ))
+        .await
+        .data
+        .unwrap();
+    let user_id = user["id"].as_str().unwrap();
+    assert_eq!(
+        user["meta"]["location"],
+        format!("https://scim.example.com/api/scim/Users/{}", user_id)
+    );
+
+    let group = handler
+        .handle_operation(ScimOperationRequest::create(
+            "Group",
+            json!({"displayName": "Based", "members": [{"value": user_id, "type": "User"}]}),
+        ))
+        .await
+        .data
+        .unwrap();
+    assert_eq!(
+        group["members"][0]["$ref"],
+        format!("https://scim.example.com/api/scim/Users/{}", user_id)
+    );
+}
+
+#[tokio::test]
+async fn test_location_policy_relative_and_omitted() {
+    use scim_server::LocationPolicy;
+
+    let build = |policy: LocationPolicy| {
+        TestServerBuilder::new()
+            .configure(|builder| {
+                builder
+                    .with_base_url("https://internal.example.com")
+                    .with_location_policy(policy)
+            })
+            .with_users([
+                ScimOperation::Create,
+                ScimOperation::Read,
+                ScimOperation::Update,
+            ])
+            .with_groups([
+                ScimOperation::Create,
+                ScimOperation::Read,
+                ScimOperation::Update,
+            ])
+            .build_handler()
+    };
+
+    let handler = build(LocationPolicy::Relative);
+    let user = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "relative"}),
+        ))
+        .await;
+    let user_id = user.metadata.resource_id.clone().unwrap();
+    assert_eq!(
+        user.data.unwrap()["meta"]["location"],
+        format!("/v2/Users/{}", user_id)
+    );
+
+    let group = handler
+        .handle_operation(ScimOperationRequest::create(
+            "Group",
+            json!({
+                "displayName": "Relative",
+                "members": [{"value": user_id, "type": "User"}]
+            }),
+        ))
+        .await;
+    assert!(group.success, "create failed: {:?}", group.error);
+    let group_id = group.metadata.resource_id.clone().unwrap();
+    let etag = group.metadata.additional["etag"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let mut group = group.data.unwrap();
+    assert_eq!(
+        group["members"][0]["$ref"],
+        format!("/v2/Users/{}", user_id)
+    );
+
+    // Echoing back relative refs works, including conditional updates
+    group["displayName"] = json!("Relative Renamed");
+    let update = handler
+        .handle_operation(
+            ScimOperationRequest::update("Group", &group_id, group)
+                .with_expected_version(RawVersion::from_hash(&etag)),
+        )
+        .await;
+    assert!(update.success, "update failed: {:?}", update.error);
+    assert_eq!(
+        update.data.unwrap()["members"][0]["$ref"],
+        format!("/v2/Users/{}", user_id)
+    );
+
+    let handler = build(LocationPolicy::Omit);
+    let user = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "omitted"}),
+        ))
+        .await;
+    let user_id = user.metadata.resource_id.clone().unwrap();
+    assert!(user.data.unwrap()["meta"].get("location").is_none());
+
+    let group = handler
+        .handle_operation(ScimOperationRequest::create(
+            "Group",
+            json!({
+                "displayName": "Omitted",
+                "members": [{"value": user_id, "type": "User"}]
+            }),
+        ))
+        .await;
+    let group = group.data.unwrap();
+    assert!(group["meta"].get("location").is_none());
+    assert!(group["members"][0].get("$ref").is_none());
+}
+
+#[tokio::test]
+async fn test_external_id_echo_policy() {
+    async fn handler_with_echo(
+        echo: bool,
+    ) -> ScimOperationHandler<StandardResourceProvider<InMemoryStorage>> {
+        TestServerBuilder::new()
+            .configure(|builder| builder.with_external_id_echo(echo))
+            .with_users([
+                ScimOperation::Create,
+                ScimOperation::Read,
+                ScimOperation::Patch,
+                ScimOperation::List,
+            ])
+            .build_handler()
+    }
+    let patch = |id: &str, operation: serde_json::Value| ScimOperationRequest {
+        operation: scim_server::operation_handler::ScimOperationType::Patch,
+        resource_type: "User".to_string(),
+        resource_id: Some(id.to_string()),
+        data: Some(json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [operation]
+        })),
+        query: None,
+        tenant_context: None,
+        request_id: None,
+        expected_version: None,
+        idempotency_key: None,
+        context_attributes: Default::default(),
+    };
+    let user = json!({"userName": "echo.user", "externalId": "hr-42"});
+
+    // Echoed by default, always the stored value
+    let handler = handler_with_echo(true).await;
+    let created = handler
+        .handle_operation(ScimOperationRequest::create("User", user.clone()))
+        .await;
+    assert_eq!(created.data.as_ref().unwrap()["externalId"], "hr-42");
+    let id = created.metadata.resource_id.unwrap();
+
+    let response = handler
+        .handle_operation(patch(
+            &id,
+            json!({"op": "replace", "path": "externalId", "value": "hr-43"}),
+        ))
+        .await;
+    assert_eq!(response.data.unwrap()["externalId"], "hr-43");
+    let fetched = handler
+        .handle_operation(ScimOperationRequest::get("User", &id))
+        .await;
+    assert_eq!(fetched.data.unwrap()["externalId"], "hr-43");
+
+    // Returned by default, so projection selects and excludes it
+    let selected = handler
+        .handle_operation(
+            ScimOperationRequest::get("User", &id)
+                .with_query(ScimQuery::new().with_attributes(vec!["userName".to_string()])),
+        )
+        .await;
+    assert!(selected.data.unwrap().get("externalId").is_none());
+    let selected = handler
+        .handle_operation(
+            ScimOperationRequest::get("User", &id)
+                .with_query(ScimQuery::new().with_attributes(vec!["externalId".to_string()])),
+        )
+        .await;
+    assert_eq!(selected.data.unwrap()["externalId"], "hr-43");
+    let excluded =
+        handler
+            .handle_operation(ScimOperationRequest::get("User", &id).with_query(
+                ScimQuery::new().with_excluded_attributes(vec!["externalId".to_string()]),
+            ))
+            .await;
+    let excluded = excluded.data.unwrap();
+    assert!(excluded.get("externalId").is_none());
+    assert_eq!(excluded["userName"], "echo.user");
+
+    // Clearing it via PATCH is reflected
+    let response = handler
+        .handle_operation(patch(&id, json!({"op": "remove", "path": "externalId"})))
+        .await;
+    assert!(response.success, "patch failed: {:?}", response.error);
+    assert!(response.data.unwrap().get("externalId").is_none());
+    let fetched = handler
+        .handle_operation(ScimOperationRequest::get("User", &id))
+        .await;
+    assert!(fetched.data.unwrap().get("externalId").is_none());
+
+    // Stored but never returned when echo is disabled
+    let handler = handler_with_echo(false).await;
+    let created = handler
+        .handle_operation(ScimOperationRequest::create("User", user))
+        .await;
+    assert!(created.data.unwrap().get("externalId").is_none());
+    let listed = handler
+        .handle_operation(ScimOperationRequest::list("User"))
+        .await;
+    let listed = listed.data.unwrap();
+    assert!(listed[0].get("externalId").is_none());
+    assert_eq!(listed[0]["userName"], "echo.user");
+    let found = handler
+        .handle_operation(
+            ScimOperationRequest::list("User")
+                .with_query(ScimQuery::new().with_filter("externalId eq \"hr-42\"")),
+        )
+        .await;
+    assert_eq!(found.metadata.total_results, Some(1));
+}
+
+#[tokio::test]
+async fn test_response_into_http_parts() {
+    let handler = TestServerBuilder::new()
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Read,
+            ScimOperation::Delete,
+            ScimOperation::List,
+        ])
+        .build_handler();
+    let header = |headers: &[(String, String)], name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    };
+
+    // Create: 201 with Location and ETag
+    let (status, headers, body) = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "http.user"}),
+        ))
+        .await
+        .into_http_parts();
+    assert_eq!(status, 201);
+    let id = body["id"].as_str().unwrap().to_string();
+    assert_eq!(
+        header(&headers, "Location").as_ref(),
+        body["meta"]["location"]
+            .as_str()
+            .map(str::to_string)
+            .as_ref()
+    );
+    assert!(header(&headers, "ETag").unwrap().starts_with("W/\""));
+    assert_eq!(
+        header(&headers, "Content-Type").as_deref(),
+        Some(scim_server::operation_handler::SCIM_CONTENT_TYPE)
+    );
+
+    // Read: 200 without Location; list: 200 with a ListResponse envelope
+    let (status, headers, body) = handler
+        .handle_operation(ScimOperationRequest::get("User", &id))
+        .await
+        .into_http_parts();
+    assert_eq!(status, 200);
+    assert!(header(&headers, "Location").is_none());
+    assert_eq!(body["userName"], "http.user");
+
+    let (status, _, body) = handler
+        .handle_operation(ScimOperationRequest::list("User"))
+        .await
+        .into_http_parts();
+    assert_eq!(status, 200);
+    assert_eq!(
+        body["schemas"],
+        json!(["urn:ietf:params:scim:api:messages:2.0:ListResponse"])
+    );
+    assert_eq!(body["totalResults"], 1);
+    assert_eq!(body["Resources"][0]["id"], id.as_str());
+
+    // Conflict: 409 SCIM error naming the existing resource
+    let (status, _, body) = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "http.user"}),
+        ))
+        .await
+        .into_http_parts();
+    assert_eq!(status, 409);
+    assert_eq!(body["status"], "409");
+    assert_eq!(body["scimType"], "uniqueness");
+    assert_eq!(body["conflictingResource"]["id"], id.as_str());
+
+    // Delete: 204 with no body; the resource is then gone
+    let (status, headers, body) = handler
+        .handle_operation(ScimOperationRequest::delete("User", &id))
+        .await
+        .into_http_parts();
+    assert_eq!(status, 204);
+    assert!(body.is_null());
+    assert!(header(&headers, "Content-Type").is_none());
+
+    let (status, _, body) = handler
+        .handle_operation(ScimOperationRequest::get("User", &id))
+        .await
+        .into_http_parts();
+    assert_eq!(status, 404);
+    assert_eq!(
+        body["schemas"],
+        json!(["urn:ietf:params:scim:api:messages:2.0:Error"])
+    );
+    assert_eq!(body["status"], "404");
+    assert!(body["detail"].as_str().unwrap().contains(&id));
+}
+
+#[tokio::test]
+async fn test_response_size_hints() {
+    let handler = TestServerBuilder::new()
+        .configure(|builder| builder.with_compression_threshold(Some(400)))
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Read,
+            ScimOperation::Delete,
+        ])
+        .build_handler();
+
+    // The size is that of the compact response data
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "small"}),
+        ))
+        .await;
+    assert!(response.success);
+    let data = response.data.as_ref().unwrap();
+    assert_eq!(
+        response.metadata.response_size_bytes,
+        Some(serde_json::to_vec(data).unwrap().len())
+    );
+    assert!(!response.metadata.compressible);
+    let small_id = data["id"].as_str().unwrap().to_string();
+
+    // Responses at or above the threshold are hinted as compressible
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "large", "displayName": "x".repeat(400)}),
+        ))
+        .await;
+    assert!(response.metadata.response_size_bytes.unwrap() >= 400);
+    assert!(response.metadata.compressible);
+
+    // The hint is advisory and leaves the data untouched
+    assert_eq!(
+        response.data.as_ref().unwrap()["displayName"],
+        "x".repeat(400)
+    );
+
+    // Responses without data carry no size
+    let response = handler
+        .handle_operation(ScimOperationRequest::delete("User", &small_id))
+        .await;
+    assert!(response.success);
+    assert_eq!(response.metadata.response_size_bytes, None);
+    assert!(!response.metadata.compressible);
+}
@@ -0,0 +1,619 @@
+//! Operation handler tests for schema extensions and required attributes.
+
+use crate::common::servers::TestServerBuilder;
+use scim_server::ScimServer;
+use scim_server::multi_tenant::ScimOperation;
+use scim_server::operation_handler::{ScimOperationHandler, ScimOperationRequest, ScimQuery};
+use scim_server::{TenantContext, TenantStrategy};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_schemas_checked_against_registered_extensions() {
+    use scim_server::schema::{AttributeDefinition, AttributeType, Schema};
+
+    const ENTERPRISE: &str = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+    const UNREGISTERED: &str = "urn:example:params:scim:schemas:extension:custom:2.0:User";
+
+    let build = |strict: bool| {
+        let mut server = TestServerBuilder::new()
+            .configure(|builder| builder.with_strict_schema_extensions(strict))
+            .with_users([ScimOperation::Create, ScimOperation::Read])
+            .build_server();
+        server
+            .register_schema_extension(
+                "User",
+                Schema {
+                    id: ENTERPRISE.to_string(),
+                    name: "EnterpriseUser".to_string(),
+                    description: "Enterprise User".to_string(),
+                    attributes: vec![AttributeDefinition {
+                        name: "employeeNumber".to_string(),
+                        data_type: AttributeType::String,
+                        ..Default::default()
+                    }],
+                },
+            )
+            .unwrap();
+        ScimOperationHandler::new(server)
+    };
+    let create = |user_name: &str, extension: &str, data: serde_json::Value| {
+        let mut user = json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User", extension],
+            "userName": user_name,
+        });
+        user[extension] = data;
+        ScimOperationRequest::create("User", user)
+    };
+
+    let handler = build(true);
+
+    // Registered extensions are accepted and validated
+    let response = handler
+        .handle_operation(create(
+            "registered",
+            ENTERPRISE,
+            json!({"employeeNumber": "701984"}),
+        ))
+        .await;
+    assert!(response.success, "create failed: {:?}", response.error);
+    assert_eq!(
+        response.data.unwrap()[ENTERPRISE]["employeeNumber"],
+        "701984"
+    );
+
+    let response = handler
+        .handle_operation(create(
+            "invalid.extension",
+            ENTERPRISE,
+            json!({"employeeNumber": 701984}),
+        ))
+        .await;
+    assert!(!response.success);
+
+    // Unregistered URNs are rejected in strict mode
+    let response = handler
+        .handle_operation(create(
+            "unregistered",
+            UNREGISTERED,
+            json!({"favouriteColour": "blue"}),
+        ))
+        .await;
+    assert!(!response.success);
+    assert!(
+        response
+            .error
+            .unwrap()
+            .contains(&format!("Unknown schema URI: {}", UNREGISTERED))
+    );
+
+    // ...and pass through in lenient mode
+    let handler = build(false);
+    let response = handler
+        .handle_operation(create(
+            "passthrough",
+            UNREGISTERED,
+            json!({"favouriteColour": "blue"}),
+        ))
+        .await;
+    assert!(response.success, "create failed: {:?}", response.error);
+    assert_eq!(
+        response.data.unwrap()[UNREGISTERED]["favouriteColour"],
+        "blue"
+    );
+}
+
+#[tokio::test]
+async fn test_required_attributes_per_operation() {
+    use scim_server::ScimTenantConfiguration;
+
+    let mut server = TestServerBuilder::new()
+        .configure(|builder| builder.with_tenant_strategy(TenantStrategy::PathBased))
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Update,
+            ScimOperation::Patch,
+        ])
+        .build_server();
+    let mut configuration = ScimTenantConfiguration::builder("strict".to_string())
+        .build()
+        .unwrap();
+    configuration.schema_config.additional_required = vec!["emails".to_string()];
+    server.register_tenant_configuration(configuration);
+    let handler = ScimOperationHandler::new(server);
+    let strict = TenantContext::new("strict".to_string(), "client".to_string());
+
+    // Create must carry the tenant's required attributes
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::create("User", json!({"userName": "no.emails"}))
+                .with_tenant(strict.clone()),
+        )
+        .await;
+    assert!(!response.success);
+    assert_eq!(response.error_code.as_deref(), Some("VALIDATION_ERROR"));
+    assert!(response.error.unwrap().contains("'emails'"));
+
+    let user = json!({
+        "userName": "azure.user",
+        "emails": [{"value": "azure@example.com", "type": "work", "primary": true}],
+        "active": true
+    });
+    let response = handler
+        .handle_operation(ScimOperationRequest::create("User", user).with_tenant(strict.clone()))
+        .await;
+    assert!(response.success, "create failed: {:?}", response.error);
+    let user_id = response.metadata.resource_id.unwrap();
+
+    // Azure-style PATCH requests name only what changes
+    let response = handler
+        .handle_operation(ScimOperationRequest {
+            operation: scim_server::operation_handler::ScimOperationType::Patch,
+            resource_type: "User".to_string(),
+            resource_id: Some(user_id.clone()),
+            data: Some(json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [
+                    {"op": "Replace", "path": "active", "value": false},
+                    {"op": "Add", "path": "title", "value": "Engineer"},
+                    {"op": "Replace", "value": {"displayName": "Azure User"}}
+                ]
+            })),
+            query: None,
+            tenant_context: Some(strict.clone()),
+            request_id: None,
+            expected_version: None,
+            idempotency_key: None,
+            context_attributes: Default::default(),
+        })
+        .await;
+    assert!(response.success, "patch failed: {:?}", response.error);
+    let data = response.data.unwrap();
+    assert_eq!(data["active"], false);
+    assert_eq!(data["emails"][0]["value"], "azure@example.com");
+
+    // Replacing the resource must still carry every required attribute
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::update("User", &user_id, json!({"userName": "azure.user"}))
+                .with_tenant(strict.clone()),
+        )
+        .await;
+    assert!(!response.success);
+    assert!(response.error.unwrap().contains("'emails'"));
+
+    let response = handler
+        .handle_operation(
+            ScimOperationRequest::update("User", &user_id, json!({"emails": []}))
+                .with_tenant(strict.clone()),
+        )
+        .await;
+    assert!(!response.success);
+    assert!(response.error.unwrap().contains("'userName'"));
+}
+
+#[tokio::test]
+async fn test_tenant_schema_extension_requiredness() {
+    use scim_server::ScimTenantConfiguration;
+    use scim_server::schema::{AttributeDefinition, AttributeType, Schema};
+
+    const ENTERPRISE: &str = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+
+    let mut server = TestServerBuilder::new()
+        .configure(|builder| builder.with_tenant_strategy(TenantStrategy::PathBased))
+        .with_users([ScimOperation::Create, ScimOperation::Patch])
+        .build_server();
+    server
+        .register_schema_extension(
+            "User",
+            Schema {
+                id: ENTERPRISE.to_string(),
+                name: "EnterpriseUser".to_string(),
+                description: "Enterprise User".to_string(),
+                attributes: vec![AttributeDefinition {
+                    name: "employeeNumber".to_string(),
+                    data_type: AttributeType::String,
+                    ..Default::default()
+                }],
+            },
+        )
+        .unwrap();
+    server.register_tenant_configuration(
+        ScimTenantConfiguration::builder("required".to_string())
+            .with_schema_extension(ENTERPRISE, true)
+            .build()
+            .unwrap(),
+    );
+    let mut disabled = ScimTenantConfiguration::builder("disabled".to_string())
+        .with_schema_extension(ENTERPRISE, false)
+        .build()
+        .unwrap();
+    disabled.schema_config.extensions[0].enabled = false;
+    server.register_tenant_configuration(disabled);
+
+    let tenant = |id: &str| TenantContext::new(id.to_string(), "client".to_string());
+    let discovered = |server: &ScimServer<_>, id: &str| {
+        let context = scim_server::RequestContext::with_tenant_generated_id(tenant(id));
+        server
+            .get_resource_type_definition("User", &context)
+            .unwrap()["schemaExtensions"]
+            .clone()
+    };
+    assert_eq!(
+        discovered(&server, "required"),
+        json!([{"schema": ENTERPRISE, "required": true}])
+    );
+    assert_eq!(
+        discovered(&server, "other"),
+        json!([{"schema": ENTERPRISE, "required": false}])
+    );
+    assert!(discovered(&server, "disabled").is_null());
+
+    let handler = ScimOperationHandler::new(server);
+    let create = |id: &str, with_extension: bool| {
+        let mut user = json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": format!("{}.{}", id, with_extension),
+        });
+        if with_extension {
+            user["schemas"]
+                .as_array_mut()
+                .unwrap()
+                .push(json!(ENTERPRISE));
+            user[ENTERPRISE] = json!({"employeeNumber": "701984"});
+        }
+        ScimOperationRequest::create("User", user).with_tenant(tenant(id))
+    };
+
+    // Required for one tenant, optional for another, unavailable for a third
+    let response = handler.handle_operation(create("required", false)).await;
+    assert!(!response.success);
+    assert_eq!(response.error_code.as_deref(), Some("VALIDATION_ERROR"));
+    let created = handler.handle_operation(create("required", true)).await;
+    assert!(created.success, "{:?}", created.error);
+    assert!(
+        handler
+            .handle_operation(create("other", false))
+            .await
+            .success
+    );
+    assert!(
+        handler
+            .handle_operation(create("other", true))
+            .await
+            .success
+    );
+    assert!(
+        !handler
+            .handle_operation(create("disabled", true))
+            .await
+            .success
+    );
+    assert!(
+        handler
+            .handle_operation(create("disabled", false))
+            .await
+            .success
+    );
+
+    // A required extension cannot be patched away
+    let response = handler
+        .handle_operation(ScimOperationRequest {
+            operation: scim_server::operation_handler::ScimOperationType::Patch,
+            resource_type: "User".to_string(),
+            resource_id: created.metadata.resource_id.clone(),
+            data: Some(json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{"op": "replace", "value": {ENTERPRISE: {}}}]
+            })),
+            query: None,
+            tenant_context: Some(tenant("required")),
+            request_id: None,
+            expected_version: None,
+            idempotency_key: None,
+            context_attributes: Default::default(),
+        })
+        .await;
+    assert!(!response.success);
+    assert_eq!(response.error_code.as_deref(), Some("VALIDATION_ERROR"));
+}
+
+#[tokio::test]
+async fn test_tenant_group_schema_extension() {
+    use scim_server::ScimTenantConfiguration;
+    use scim_server::schema::{AttributeDefinition, AttributeType, Schema};
+
+    const COST_CENTER: &str = "urn:example:params:scim:schemas:extension:costCenter:2.0:Group";
+
+    let mut server = TestServerBuilder::new()
+        .configure(|builder| {
+            builder
+                .with_base_url("https://scim.example.com")
+                .with_tenant_strategy(TenantStrategy::PathBased)
+        })
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Read,
+            ScimOperation::List,
+            ScimOperation::Patch,
+        ])
+        .with_groups([
+            ScimOperation::Create,
+            ScimOperation::Read,
+            ScimOperation::List,
+            ScimOperation::Patch,
+        ])
+        .build_server();
+    server
+        .register_schema_extension(
+            "Group",
+            Schema {
+                id: COST_CENTER.to_string(),
+                name: "CostCenterGroup".to_string(),
+                description: "Group cost center".to_string(),
+                attributes: vec![
+                    AttributeDefinition {
+                        name: "costCenter".to_string(),
+                        data_type: AttributeType::String,
+                        required: true,
+                        ..Default::default()
+                    },
+                    AttributeDefinition {
+                        name: "owner".to_string(),
+                        data_type: AttributeType::Complex,
+                        sub_attributes: vec![
+                            AttributeDefinition {
+                                name: "value".to_string(),
+                                data_type: AttributeType::String,
+                                ..Default::default()
+                            },
+                            AttributeDefinition {
+                                name: "$ref".to_string(),
+                                data_type: AttributeType::Reference,
+                                reference_types: vec!["User".to_string()],
+                                ..Default::default()
+                            },
+                        ],
+                        ..Default::default()
+                    },
+                ],
+            },
+        )
+        .unwrap();
+    server.register_tenant_configuration(
+        ScimTenantConfiguration::builder("acme".to_string())
+            .with_schema_extension(COST_CENTER, true)
+            .build()
+            .unwrap(),
+    );
+
+    let acme = TenantContext::new("acme".to_string(), "client-a".to_string());
+    let discovered = server
+        .get_resource_type_definition(
+            "Group",
+            &scim_server::RequestContext::with_tenant_generated_id(acme.clone()),
+        )
+        .unwrap();
+    assert_eq!(
+        discovered["schemaExtensions"],
+        json!([{"schema": COST_CENTER, "required": true}])
+    );
+
+    let handler = ScimOperationHandler::new(server);
+    let user = handler
+        .handle_operation(
+            ScimOperationRequest::create("User", json!({"userName": "owner"}))
+                .with_tenant(acme.clone()),
+        )
+        .await;
+    let user_id = user.metadata.resource_id.unwrap();
+
+    // Required for the tenant
+    let missing = handler
+        .handle_operation(
+            ScimOperationRequest::create("Group", json!({"displayName": "No cost center"}))
+                .with_tenant(acme.clone()),
+        )
+        .await;
+    assert!(!missing.success);
+
+    let group = handler
+        .handle_operation(
+            ScimOperationRequest::create(
+                "Group",
+                json!({
+                    "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group", COST_CENTER],
+                    "displayName": "Finance",
+                    "members": [{"value": user_id, "type": "User"}],
+                    COST_CENTER: {"costCenter": "CC-42", "owner": {"value": user_id}}
+                }),
+            )
+            .with_tenant(acme.clone()),
+        )
+        .await;
+    assert!(group.success, "{:?}", group.error);
+    let group = group.data.unwrap();
+    let user_location = format!("https://scim.example.com/acme/v2/Users/{}", user_id);
+    assert_eq!(group[COST_CENTER]["costCenter"], "CC-42");
+    assert_eq!(group[COST_CENTER]["owner"]["$ref"], user_location.as_str());
+    assert_eq!(group["members"][0]["$ref"], user_location.as_str());
+    let group_id = group["id"].as_str().unwrap().to_string();
+
+    // Invalid extension data is rejected
+    let invalid = handler
+        .handle_operation(
+            ScimOperationRequest::create(
+                "Group",
+                json!({
+                    "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group", COST_CENTER],
+                    "displayName": "Invalid",
+                    COST_CENTER: {"costCenter": 42}
+                }),
+            )
+            .with_tenant(acme.clone()),
+        )
+        .await;
+    assert!(!invalid.success);
+
+    // Projection by the qualified path
+    let listed = handler
+        .handle_operation(
+            ScimOperationRequest::list("Group")
+                .with_query(
+                    ScimQuery::new().with_attributes(vec![format!("{}:costCenter", COST_CENTER)]),
+                )
+                .with_tenant(acme.clone()),
+        )
+        .await;
+    assert!(listed.success, "{:?}", listed.error);
+    let listed = &listed.data.unwrap()[0];
+    assert_eq!(listed[COST_CENTER], json!({"costCenter": "CC-42"}));
+    assert!(listed.get("members").is_none());
+    assert!(listed.get("displayName").is_none());
+
+    // PATCH of extension attributes leaves members alone
+    let patched = handler
+        .handle_operation(ScimOperationRequest {
+            operation: scim_server::operation_handler::ScimOperationType::Patch,
+            resource_type: "Group".to_string(),
+            resource_id: Some(group_id),
+            data: Some(json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{
+                    "op": "replace",
+                    "path": format!("{}:costCenter", COST_CENTER),
+                    "value": "CC-7"
+                }]
+            })),
+            query: None,
+            tenant_context: Some(acme),
+            request_id: None,
+            expected_version: None,
+            idempotency_key: None,
+            context_attributes: Default::default(),
+        })
+        .await;
+    assert!(patched.success, "{:?}", patched.error);
+    let patched = patched.data.unwrap();
+    assert_eq!(patched[COST_CENTER]["costCenter"], "CC-7");
+    assert_eq!(patched["members"][0]["value"], user_id.as_str());
+}
+
+#[tokio::test]
+async fn test_tenant_validation_settings() {
+    use scim_server::{RequestContext, ScimTenantConfiguration, UnknownAttributePolicy};
+
+    let mut server = TestServerBuilder::new()
+        .configure(|builder| builder.with_tenant_strategy(TenantStrategy::PathBased))
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Read,
+            ScimOperation::Patch,
+        ])
+        .build_server();
+    let legacy = || {
+        ScimTenantConfiguration::builder("legacy".to_string())
+            .with_strict_schemas(false)
+            .with_unknown_attribute_policy(UnknownAttributePolicy::Ignore)
+            .with_required_enforcement(false)
+            .build()
+            .unwrap()
+    };
+    server.register_tenant_configuration(legacy());
+
+    let context = |tenant_id: &str| {
+        RequestContext::with_tenant_generated_id(TenantContext::new(
+            tenant_id.to_string(),
+            "client".to_string(),
+        ))
+    };
+    let user = json!({
+        "schemas": [
+            "urn:ietf:params:scim:schemas:core:2.0:User",
+            "urn:example:params:scim:schemas:legacy:2.0:User"
+        ],
+        "userName": "pat",
+        "shoeSize": 9
+    });
+
+    // Tenants without configuration fall back to the server's strict defaults
+    let settings = server.validation_settings(&context("modern"));
+    assert!(settings.strict_schemas && settings.enforce_required);
+    assert_eq!(settings.unknown_attributes, UnknownAttributePolicy::Reject);
+    assert!(
+        server
+            .create_resource("User", user.clone(), &context("modern"))
+            .await
+            .is_err()
+    );
+    assert!(
+        server
+            .create_resource(
+                "User",
+                json!({"displayName": "No Name"}),
+                &context("modern")
+            )
+            .await
+            .is_err()
+    );
+
+    // The legacy tenant accepts both, dropping what its schema lacks
+    let created = server
+        .create_resource("User", user.clone(), &context("legacy"))
+        .await
+        .unwrap()
+        .to_json()
+        .unwrap();
+    assert!(created.get("shoeSize").is_none());
+    assert!(
+        server
+            .create_resource(
+                "User",
+                json!({"displayName": "No Name"}),
+                &context("legacy")
+            )
+            .await
+            .is_ok()
+    );
+
+    let id = created["id"].as_str().unwrap();
+    let patched = server
+        .patch_resource(
+            "User",
+            id,
+            &json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [
+                    {"op": "replace", "path": "shoeSize", "value": 10},
+                    {"op": "replace", "value": {"displayName": "Pat", "hatSize": 7}},
+                    {"op": "remove", "path": "userName"}
+                ]
+            }),
+            &context("legacy"),
+        )
+        .await
+        .unwrap()
+        .to_json()
+        .unwrap();
+    assert_eq!(patched["displayName"], "Pat");
+    assert!(patched.get("hatSize").is_none() && patched.get("userName").is_none());
+
+    // Re-registering the configuration takes effect on the next request
+    server.register_tenant_configuration(
+        ScimTenantConfiguration::builder("legacy".to_string())
+            .build()
+            .unwrap(),
+    );
+    assert!(
+        server
+            .create_resource("User", user.clone(), &context("legacy"))
+            .await
+            .is_err()
+    );
+    server.register_tenant_configuration(legacy());
+    assert!(
+        server
+            .create_resource("User", user, &context("legacy"))
+            .await
+            .is_ok()
+    );
+}
@@ -0,0 +1,455 @@
+//! Operation handler tests for conditional operations and version exposure.
+
+use crate::common::servers::TestServerBuilder;
+use scim_server::ScimServer;
+use scim_server::VersionExposure;
+use scim_server::multi_tenant::ScimOperation;
+use scim_server::operation_handler::{ScimOperationHandler, ScimOperationRequest};
+use scim_server::providers::StandardResourceProvider;
+use scim_server::resource::version::RawVersion;
+use scim_server::resource_handlers::create_user_resource_handler;
+use scim_server::storage::InMemoryStorage;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_conditional_update_with_correct_version() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let mut server = ScimServer::new(provider).unwrap();
+
+    // Register User resource type
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let user_handler = create_user_resource_handler(user_schema);
+    server
+        .register_resource_type(
+            "User",
+            user_handler,
+            vec![
+                ScimOperation::Create,
+                ScimOperation::Update,
+                ScimOperation::Read,
+            ],
+        )
+        .unwrap();
+
+    let handler = ScimOperationHandler::new(server);
+
+    // Create a user first
+    let create_request = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "testuser",
+            "name": {
+                "givenName": "Test",
+                "familyName": "User"
+            }
+        }),
+    );
+
+    let create_response = handler.handle_operation(create_request).await;
+    assert!(create_response.success);
+
+    let user_data = create_response.data.unwrap();
+    let user_id = user_data["id"].as_str().unwrap();
+
+    // Get the user to obtain current version
+    let get_request = ScimOperationRequest::get("User", user_id);
+    let get_response = handler.handle_operation(get_request).await;
+    assert!(get_response.success);
+
+    // Extract current version from response metadata
+    let current_version = get_response
+        .metadata
+        .additional
+        .get("version")
+        .and_then(|v| v.as_str())
+        .map(RawVersion::from_hash)
+        .expect("Response should include version information");
+
+    // Update with correct version should succeed
+    let update_request = ScimOperationRequest::update(
+        "User",
+        user_id,
+        json!({
+            "userName": "updateduser",
+            "name": {
+                "givenName": "Updated",
+                "familyName": "User"
+            }
+        }),
+    )
+    .with_expected_version(current_version);
+
+    let update_response = handler.handle_operation(update_request).await;
+    assert!(update_response.success);
+    assert!(update_response.metadata.additional.contains_key("version"));
+    assert!(update_response.metadata.additional.contains_key("etag"));
+}
+
+#[tokio::test]
+async fn test_conditional_update_version_mismatch() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let mut server = ScimServer::new(provider).unwrap();
+
+    // Register User resource type
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let user_handler = create_user_resource_handler(user_schema);
+    server
+        .register_resource_type(
+            "User",
+            user_handler,
+            vec![ScimOperation::Create, ScimOperation::Update],
+        )
+        .unwrap();
+
+    let handler = ScimOperationHandler::new(server);
+
+    // Create a user first
+    let create_request = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "testuser",
+            "name": {
+                "givenName": "Test",
+                "familyName": "User"
+            }
+        }),
+    );
+
+    let create_response = handler.handle_operation(create_request).await;
+    assert!(create_response.success);
+
+    let user_data = create_response.data.unwrap();
+    let user_id = user_data["id"].as_str().unwrap();
+
+    // Try to update with incorrect version should fail with version mismatch
+    let old_version = RawVersion::from_hash("incorrect-version");
+    let update_request = ScimOperationRequest::update(
+        "User",
+        user_id,
+        json!({
+            "userName": "updateduser"
+        }),
+    )
+    .with_expected_version(old_version);
+
+    let update_response = handler.handle_operation(update_request).await;
+    assert!(!update_response.success);
+    assert_eq!(
+        update_response.error_code.as_deref(),
+        Some("version_mismatch")
+    );
+    assert!(update_response.error.is_some());
+    assert!(
+        update_response
+            .metadata
+            .additional
+            .contains_key("expected_version")
+    );
+    assert_eq!(
+        update_response.metadata.additional["current_version"],
+        create_response.metadata.additional["version"]
+    );
+}
+
+#[tokio::test]
+async fn test_conditional_delete_with_correct_version() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let mut server = ScimServer::new(provider).unwrap();
+
+    // Register User resource type
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let user_handler = create_user_resource_handler(user_schema);
+    server
+        .register_resource_type(
+            "User",
+            user_handler,
+            vec![
+                ScimOperation::Create,
+                ScimOperation::Delete,
+                ScimOperation::Read,
+            ],
+        )
+        .unwrap();
+
+    let handler = ScimOperationHandler::new(server);
+
+    // Create a user first
+    let create_request = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "testuser",
+            "name": {
+                "givenName": "Test",
+                "familyName": "User"
+            }
+        }),
+    );
+
+    let create_response = handler.handle_operation(create_request).await;
+    assert!(create_response.success);
+
+    let user_data = create_response.data.unwrap();
+    let user_id = user_data["id"].as_str().unwrap();
+
+    // Get the user to obtain current version
+    let get_request = ScimOperationRequest::get("User", user_id);
+    let get_response = handler.handle_operation(get_request).await;
+    assert!(get_response.success);
+
+    // Extract current version from response metadata
+    let current_version = get_response
+        .metadata
+        .additional
+        .get("version")
+        .and_then(|v| v.as_str())
+        .map(RawVersion::from_hash)
+        .expect("Response should include version information");
+
+    // Delete with correct version should succeed
+    let delete_request =
+        ScimOperationRequest::delete("User", user_id).with_expected_version(current_version);
+
+    let delete_response = handler.handle_operation(delete_request).await;
+    assert!(delete_response.success);
+}
+
+#[tokio::test]
+async fn test_conditional_delete_version_mismatch() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let mut server = ScimServer::new(provider).unwrap();
+
+    // Register User resource type
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let user_handler = create_user_resource_handler(user_schema);
+    server
+        .register_resource_type(
+            "User",
+            user_handler,
+            vec![ScimOperation::Create, ScimOperation::Delete],
+        )
+        .unwrap();
+
+    let handler = ScimOperationHandler::new(server);
+
+    // Create a user first
+    let create_request = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "testuser",
+            "name": {
+                "givenName": "Test",
+                "familyName": "User"
+            }
+        }),
+    );
+
+    let create_response = handler.handle_operation(create_request).await;
+    assert!(create_response.success);
+
+    let user_data = create_response.data.unwrap();
+    let user_id = user_data["id"].as_str().unwrap();
+
+    // Try to delete with incorrect version should fail with version mismatch
+    let old_version = RawVersion::from_hash("incorrect-version");
+    let delete_request =
+        ScimOperationRequest::delete("User", user_id).with_expected_version(old_version);
+
+    let delete_response = handler.handle_operation(delete_request).await;
+    assert!(!delete_response.success);
+    assert_eq!(
+        delete_response.error_code.as_deref(),
+        Some("version_mismatch")
+    );
+    assert!(delete_response.error.is_some());
+    assert!(
+        delete_response
+            .metadata
+            .additional
+            .contains_key("expected_version")
+    );
+    assert!(
+        delete_response
+            .metadata
+            .additional
+            .contains_key("current_version")
+    );
+}
+
+#[tokio::test]
+async fn test_regular_operations_include_version_info() {
+    let storage = InMemoryStorage::new();
+    let provider = StandardResourceProvider::new(storage);
+    let mut server = ScimServer::new(provider).unwrap();
+
+    // Register User resource type
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    let user_handler = create_user_resource_handler(user_schema);
+    server
+        .register_resource_type(
+            "User",
+            user_handler,
+            vec![
+                ScimOperation::Create,
+                ScimOperation::Update,
+                ScimOperation::Read,
+            ],
+        )
+        .unwrap();
+
+    let handler = ScimOperationHandler::new(server);
+
+    // Create a user (should include version info)
+    let create_request = ScimOperationRequest::create(
+        "User",
+        json!({
+            "userName": "testuser",
+            "name": {
+                "givenName": "Test",
+                "familyName": "User"
+            }
+        }),
+    );
+
+    let create_response = handler.handle_operation(create_request).await;
+    assert!(create_response.success);
+    // CREATE operations should include version info too
+    assert!(create_response.metadata.additional.contains_key("version"));
+    assert!(create_response.metadata.additional.contains_key("etag"));
+
+    let user_data = create_response.data.unwrap();
+    let user_id = user_data["id"].as_str().unwrap();
+
+    // Get user (should include version info)
+    let get_request = ScimOperationRequest::get("User", user_id);
+    let get_response = handler.handle_operation(get_request).await;
+    assert!(get_response.success);
+    assert!(get_response.metadata.additional.contains_key("version"));
+    assert!(get_response.metadata.additional.contains_key("etag"));
+
+    // Update without expected_version (should still include version info)
+    let update_request = ScimOperationRequest::update(
+        "User",
+        user_id,
+        json!({
+            "userName": "updateduser",
+            "name": {
+                "givenName": "Updated",
+                "familyName": "User"
+            }
+        }),
+    );
+
+    let update_response = handler.handle_operation(update_request).await;
+    assert!(update_response.success);
+    assert!(update_response.metadata.additional.contains_key("version"));
+    assert!(update_response.metadata.additional.contains_key("etag"));
+}
+
+fn version_exposure_handler(
+    exposure: VersionExposure,
+) -> ScimOperationHandler<StandardResourceProvider<InMemoryStorage>> {
+    TestServerBuilder::new()
+        .configure(|builder| builder.with_version_exposure(exposure))
+        .with_users([
+            ScimOperation::Create,
+            ScimOperation::Read,
+            ScimOperation::Update,
+        ])
+        .build_handler()
+}
+
+#[tokio::test]
+async fn test_version_exposure_default_keeps_both_in_sync() {
+    let handler = version_exposure_handler(VersionExposure::default());
+
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "both"}),
+        ))
+        .await;
+    assert!(response.success);
+
+    let data = response.data.unwrap();
+    let meta_version = data["meta"]["version"].as_str().unwrap();
+    let etag = response.metadata.additional["etag"].as_str().unwrap();
+    assert_eq!(etag, format!("W/\"{}\"", meta_version));
+}
+
+#[tokio::test]
+async fn test_version_exposure_etag_only() {
+    let handler = version_exposure_handler(VersionExposure::EtagOnly);
+
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "etag.only"}),
+        ))
+        .await;
+    assert!(response.success);
+
+    let data = response.data.unwrap();
+    assert!(data["meta"].get("version").is_none());
+    let etag = response.metadata.additional["etag"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let user_id = data["id"].as_str().unwrap().to_string();
+
+    // The ETag is accepted as the expected version even when passed unparsed
+    let update = ScimOperationRequest::update(
+        "User",
+        &user_id,
+        json!({"id": user_id, "userName": "etag.only.updated"}),
+    )
+    .with_expected_version(RawVersion::from_hash(&etag));
+    let response = handler.handle_operation(update).await;
+    assert!(response.success, "update failed: {:?}", response.error);
+}
+
+#[tokio::test]
+async fn test_version_exposure_meta_only() {
+    let handler = version_exposure_handler(VersionExposure::MetaOnly);
+
+    let response = handler
+        .handle_operation(ScimOperationRequest::create(
+            "User",
+            json!({"userName": "meta.only"}),
+        ))
+        .await;
+    assert!(response.success);
+    assert!(!response.metadata.additional.contains_key("etag"));
+    assert!(!response.metadata.additional.contains_key("version"));
+
+    let data = response.data.unwrap();
+    let meta_version = data["meta"]["version"].as_str().unwrap().to_string();
+    let user_id = data["id"].as_str().unwrap().to_string();
+
+    let update = ScimOperationRequest::update(
+        "User",
+        &user_id,
+        json!({"id": user_id, "userName": "meta.only.updated"}),
+    )
+    .with_expected_version(RawVersion::from_hash(&meta_version));
+    let response = handler.handle_operation(update).await;
+    assert!(response.success, "update failed: {:?}", response.error);
+}
@@ -1,3 +1,3 @@
 //! Unit tests for provider implementations.
 
-// in_memory module removed - tests consolidated into standard_provider_tests.rs
+// in_memory module removed - tests consolidated into standard_provider_tests/
@@ -982,3 +982,71 @@ async fn test_uniqueness_update_same_resource() {
         ),
     }
 }
+
+/// Schema defaults are populated on server creates and persisted, but never
+/// applied by the provider or re-applied by PATCH
+#[tokio::test]
+async fn test_schema_defaults_apply_on_create_only() {
+    use scim_server::ScimServer;
+    use scim_server::multi_tenant::ScimOperation;
+    use scim_server::resource_handlers::create_user_resource_handler;
+
+    let provider = StandardResourceProvider::new(InMemoryStorage::new());
+    let mut server = ScimServer::new(provider).unwrap();
+    let user_schema = server
+        .get_schema_by_id("urn:ietf:params:scim:schemas:core:2.0:User")
+        .unwrap()
+        .clone();
+    server
+        .register_resource_type(
+            "User",
+            create_user_resource_handler(user_schema),
+            vec![
+                ScimOperation::Create,
+                ScimOperation::Read,
+                ScimOperation::Patch,
+            ],
+        )
+        .unwrap();
+    let context = RequestContext::with_generated_id();
+
+    let created = server
+        .create_resource("User", json!({"userName": "default.active"}), &context)
+        .await
+        .unwrap();
+    assert_eq!(created.get_attribute("active"), Some(&json!(true)));
+
+    let user_id = created.get_id().unwrap().to_string();
+    let stored = server
+        .provider()
+        .get_resource("User", &user_id, &context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        stored.resource().get_attribute("active"),
+        Some(&json!(true))
+    );
+
+    // Creating through the provider directly stores the data as given
+    let direct = server
+        .provider()
+        .create_resource("User", json!({"userName": "direct"}), &context)
+        .await
+        .unwrap();
+    assert!(direct.resource().get_attribute("active").is_none());
+
+    let patched = server
+        .patch_resource(
+            "User",
+            &user_id,
+            &json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{"op": "remove", "path": "active"}]
+            }),
+            &context,
+        )
+        .await
+        .unwrap();
+    assert!(patched.get_attribute("active").is_none());
+}
@@ -8,6 +8,7 @@ use scim_server::resource::Resource;
 use scim_server::resource::value_objects::{ExternalId, ResourceId, SchemaUri, UserName};
 use scim_server::schema::registry::SchemaRegistry;
 use scim_server::schema::validation::OperationContext;
+use scim_server::schema::{AttributeDefinition, AttributeType, Schema};
 use serde_json::{Map, json};
 
 #[test]
@@ -350,3 +351,222 @@ fn test_resource_serde() {
         Some(&json!("Serde User"))
     );
 }
+
+#[test]
+fn test_binary_attributes_validated_and_decoded() {
+    let user_data = json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+        "userName": "certuser",
+        "badgePhoto": "AAEC",
+        "x509Certificates": [
+            {"value": "MIIB", "primary": true},
+            {"value": "TQ=="}
+        ]
+    });
+
+    let resource = Resource::from_json("User".to_string(), user_data).unwrap();
+    assert_eq!(
+        resource.get_x509_certificates().unwrap(),
+        vec![vec![0x30, 0x82, 0x01], b"M".to_vec()]
+    );
+    assert_eq!(
+        resource.get_binary_attribute("badgePhoto").unwrap(),
+        Some(vec![0, 1, 2])
+    );
+    assert_eq!(resource.get_binary_attribute("missing").unwrap(), None);
+
+    let invalid = json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+        "userName": "certuser",
+        "x509Certificates": [{"value": "not-base64!"}]
+    });
+    match Resource::from_json("User".to_string(), invalid) {
+        Err(ValidationError::InvalidBinaryData { attribute, .. }) => {
+            assert_eq!(attribute, "x509Certificates.value");
+        }
+        other => panic!("Expected InvalidBinaryData, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_validate_against_base_and_extension_schemas() {
+    use scim_server::schema::{AttributeDefinition, AttributeType, Schema};
+
+    const EXTENSION: &str = "urn:example:params:scim:schemas:extension:badge:2.0:User";
+    let registry = SchemaRegistry::new().unwrap();
+    let user_schema = registry.get_user_schema();
+    let badge_schema = Schema {
+        id: EXTENSION.to_string(),
+        name: "Badge".to_string(),
+        description: "Badge extension".to_string(),
+        attributes: vec![
+            AttributeDefinition {
+                name: "badgeNumber".to_string(),
+                data_type: AttributeType::Integer,
+                required: true,
+                ..Default::default()
+            },
+            AttributeDefinition {
+                name: "site".to_string(),
+                data_type: AttributeType::String,
+                ..Default::default()
+            },
+        ],
+    };
+    let schemas = [user_schema, &badge_schema];
+    let validate = |data: serde_json::Value| {
+        Resource::from_json("User".to_string(), data)
+            .unwrap()
+            .validate_against(&schemas)
+    };
+
+    // The extension's required attribute only applies when its URN is declared
+    assert!(
+        validate(json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "jdoe"
+        }))
+        .is_ok()
+    );
+    assert!(matches!(
+        validate(json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User", EXTENSION],
+            "userName": "jdoe"
+        })),
+        Err(ValidationError::MissingRequiredAttribute { attribute }) if attribute == "badgeNumber"
+    ));
+    assert!(
+        validate(json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User", EXTENSION],
+            "userName": "jdoe",
+            EXTENSION: {"badgeNumber": 42, "site": "HQ"}
+        }))
+        .is_ok()
+    );
+
+    // Extension attributes are type-checked
+    assert!(matches!(
+        validate(json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User", EXTENSION],
+            "userName": "jdoe",
+            EXTENSION: {"badgeNumber": "forty-two"}
+        })),
+        Err(ValidationError::InvalidAttributeType { .. })
+    ));
+
+    // Extension data needs its URN, and declared URNs need a schema
+    assert!(matches!(
+        validate(json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "jdoe",
+            EXTENSION: {"badgeNumber": 42}
+        })),
+        Err(ValidationError::UnknownAttributeForSchema { .. })
+    ));
+    let resource = Resource::from_json(
+        "User".to_string(),
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User", EXTENSION],
+            "userName": "jdoe",
+            EXTENSION: {"badgeNumber": 42}
+        }),
+    )
+    .unwrap();
+    assert!(matches!(
+        resource.validate_against(&[user_schema]),
+        Err(ValidationError::UnknownSchemaUri { .. })
+    ));
+
+    // The base schema's required attributes always apply
+    assert!(matches!(
+        validate(json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "displayName": "No Username"
+        })),
+        Err(ValidationError::MissingRequiredAttribute { attribute }) if attribute == "userName"
+    ));
+}
+
+#[test]
+fn test_extension_attribute_accessors() {
+    let extension = Schema {
+        id: "urn:example:params:scim:schemas:extension:badge:2.0:User".to_string(),
+        name: "Badge".to_string(),
+        description: "Badge extension".to_string(),
+        attributes: vec![
+            AttributeDefinition {
+                name: "badgeNumber".to_string(),
+                data_type: AttributeType::Integer,
+                ..Default::default()
+            },
+            AttributeDefinition {
+                name: "clearance".to_string(),
+                canonical_values: vec!["public".to_string(), "secret".to_string()],
+                ..Default::default()
+            },
+            AttributeDefinition {
+                name: "sites".to_string(),
+                multi_valued: true,
+                ..Default::default()
+            },
+        ],
+    };
+    let mut user = Resource::from_json("User".to_string(), json!({"userName": "bjensen"})).unwrap();
+    assert_eq!(user.get_extension(&extension, "badgeNumber").unwrap(), None);
+
+    // Setting an attribute declares the extension and uses the schema's casing
+    user.set_extension(&extension, "BADGENUMBER", json!(42))
+        .unwrap();
+    user.set_extension(&extension, "sites", json!(["north", "south"]))
+        .unwrap();
+    assert!(user.get_schemas().contains(&extension.id));
+    assert_eq!(
+        user.to_json().unwrap()[&extension.id],
+        json!({"badgeNumber": 42, "sites": ["north", "south"]})
+    );
+    assert_eq!(
+        user.get_extension(&extension, "badgenumber").unwrap(),
+        Some(&json!(42))
+    );
+
+    // Values are checked against the attribute definition
+    assert!(matches!(
+        user.set_extension(&extension, "badgeNumber", json!("42")),
+        Err(ValidationError::InvalidAttributeType { .. })
+    ));
+    assert!(matches!(
+        user.set_extension(&extension, "clearance", json!("top")),
+        Err(ValidationError::InvalidCanonicalValue { .. })
+    ));
+    assert!(matches!(
+        user.set_extension(&extension, "sites", json!("north")),
+        Err(ValidationError::ExpectedMultiValue { .. })
+    ));
+    assert!(matches!(
+        user.set_extension(&extension, "floor", json!(3)),
+        Err(ValidationError::UnknownAttribute { .. })
+    ));
+    assert_eq!(user.get_extension(&extension, "clearance").unwrap(), None);
+
+    // Reads validate stored data too
+    let stored = Resource::from_json(
+        "User".to_string(),
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User", extension.id],
+            "userName": "bjensen",
+            extension.id.clone(): {"badgeNumber": "not a number"}
+        }),
+    )
+    .unwrap();
+    assert!(stored.get_extension(&extension, "badgeNumber").is_err());
+
+    let extensions = stored.extensions().unwrap();
+    assert_eq!(extensions.schema_uris(), [extension.id.as_str()]);
+    assert_eq!(
+        extensions
+            .get_attribute(&extension.id, "badgeNumber")
+            .unwrap()
+            .value(),
+        "not a number"
+    );
+}
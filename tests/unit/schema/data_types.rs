@@ -341,21 +341,13 @@ fn test_invalid_binary_data() {
         OperationContext::Update,
     );
 
-    // This will likely fail with UnknownAttribute since x509Certificates might not be in current User schema
-    // But this documents the expected behavior for binary validation
     assert!(result.is_err());
     match result {
         Err(ValidationError::InvalidBinaryData { attribute, details }) => {
-            assert_eq!(attribute, "value");
+            assert_eq!(attribute, "x509Certificates.value");
             assert!(details.contains("base64"));
         }
-        Err(ValidationError::UnknownAttribute { attribute, .. }) => {
-            assert_eq!(attribute, "x509Certificates");
-        }
-        Err(other) => {
-            // Accept other validation errors for now
-            println!("Got validation error: {:?}", other);
-        }
+        Err(other) => panic!("Expected InvalidBinaryData error, got {:?}", other),
         Ok(_) => panic!("Expected validation to fail, but it passed"),
     }
 }
@@ -649,13 +641,19 @@ fn test_valid_binary_data() {
         result
     );
 
-    // Document valid base64 formats for future validation
-    let _valid_base64_cases = vec![
+    let valid_base64_cases = vec![
         "TWFu",                                     // "Man" encoded
         "bGVhc3VyZS4=",                             // "leasure." encoded
         "c3VyZS4=",                                 // "sure." encoded
         "YWxsIHlvdXIgYmFzZSBhcmUgYmVsb25nIHRvIHVz", // Longer string
     ];
+    for value in valid_base64_cases {
+        let mut user = user_with_cert.clone();
+        user["x509Certificates"] = json!([{"value": value}]);
+        let result =
+            registry.validate_json_resource_with_context("User", &user, OperationContext::Update);
+        assert!(result.is_ok(), "{} should be valid: {:?}", value, result);
+    }
 }
 
 /// Test multiple data type errors in a single resource
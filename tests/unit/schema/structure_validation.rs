@@ -91,14 +91,83 @@ fn test_invalid_schema_uri_format() {
     // Assert that validation fails with the expected error
     assert!(result.is_err());
     match result {
-        Err(ValidationError::InvalidSchemaUri { uri }) => {
-            assert_eq!(uri, "not-a-valid-uri");
+        Err(ValidationError::InvalidSchemaUriElement { index, value }) => {
+            assert_eq!(index, 0);
+            assert_eq!(value, "not-a-valid-uri");
         }
-        Err(other) => panic!("Expected InvalidSchemaUri error, got {:?}", other),
+        Err(other) => panic!("Expected InvalidSchemaUriElement error, got {:?}", other),
         Ok(_) => panic!("Expected validation to fail, but it passed"),
     }
 }
 
+/// Test Errors #1 and #3: `schemas` values that are not arrays of schema URIs
+#[test]
+fn test_malformed_schemas_values() {
+    let registry = SchemaRegistry::new().expect("Failed to create registry");
+    let user_schema = "urn:ietf:params:scim:schemas:core:2.0:User";
+
+    let cases = [
+        (
+            json!({"uri": user_schema}),
+            ValidationErrorCode::MissingSchemas,
+            "found object",
+        ),
+        (
+            json!(user_schema),
+            ValidationErrorCode::MissingSchemas,
+            "found string",
+        ),
+        (
+            json!([]),
+            ValidationErrorCode::EmptySchemas,
+            "cannot be empty",
+        ),
+        (
+            json!([user_schema, 42]),
+            ValidationErrorCode::InvalidSchemaUri,
+            "schemas[1]: 42",
+        ),
+        (
+            json!([
+                user_schema,
+                "urn:ietf:params:scim:schemas:core:2.0:Group",
+                "bogus"
+            ]),
+            ValidationErrorCode::InvalidSchemaUri,
+            "schemas[2]: bogus",
+        ),
+    ];
+
+    for (schemas, expected_code, expected_message) in cases {
+        let mut user = UserBuilder::new().build();
+        user["schemas"] = schemas.clone();
+
+        let error = registry
+            .validate_json_resource_with_context("User", &user, OperationContext::Update)
+            .expect_err("malformed schemas should fail validation");
+        assert_eq!(
+            ValidationErrorCode::of_schema_structure_error(&error),
+            Some(expected_code),
+            "wrong code for {}",
+            schemas
+        );
+        assert!(
+            error.to_string().contains(expected_message),
+            "'{}' does not contain '{}'",
+            error,
+            expected_message
+        );
+    }
+
+    // The resource model rejects the same values instead of ignoring them
+    let mut user = UserBuilder::new().build();
+    user["schemas"] = json!([user_schema, null]);
+    assert!(matches!(
+        scim_server::resource::Resource::from_json("User".to_string(), user),
+        Err(ValidationError::InvalidSchemaUriElement { index: 1, .. })
+    ));
+}
+
 /// Test Error #4: Unknown/unregistered schema URI referenced
 #[test]
 fn test_unknown_schema_uri() {
@@ -26,6 +26,8 @@ fn create_string_definition(name: &str, required: bool) -> AttributeDefinition {
         canonical_values: vec![],
         sub_attributes: vec![],
         returned: None,
+        reference_types: Vec::new(),
+        default_value: None,
     }
 }
 
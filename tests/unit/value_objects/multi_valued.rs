@@ -611,3 +611,66 @@ fn test_phase_3_3_complete_functionality() -> ValidationResult<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_multi_valued_order_is_stable() -> ValidationResult<()> {
+    let emails = json!([
+        {"value": "c@example.com", "type": "other"},
+        {"value": "a@example.com", "type": "work", "primary": true},
+        {"value": "b@example.com", "type": "home"}
+    ]);
+    let user = Resource::from_json(
+        "User".to_string(),
+        json!({"userName": "ordered", "emails": emails}),
+    )?;
+    let values = |resource: &Resource| -> Vec<String> {
+        resource
+            .get_emails()
+            .unwrap()
+            .iter()
+            .map(|email| email.value().to_string())
+            .collect()
+    };
+    assert_eq!(
+        values(&user),
+        ["c@example.com", "a@example.com", "b@example.com"]
+    );
+    assert_eq!(user.get_emails().unwrap().primary_index(), Some(1));
+
+    // Round trips keep the order, the primary's position and the version
+    let mut current = user.clone();
+    for _ in 0..3 {
+        let json = current.to_json()?;
+        assert_eq!(json["emails"][1]["value"], "a@example.com");
+        assert_eq!(json["emails"][1]["primary"], true);
+        current = Resource::from_json("User".to_string(), json)?;
+        assert_eq!(values(&current), values(&user));
+        assert_eq!(current.get_emails().unwrap().primary_index(), Some(1));
+        assert_eq!(current.content_version()?, user.content_version()?);
+    }
+
+    // Reordering is a change
+    let reordered = Resource::from_json(
+        "User".to_string(),
+        json!({"userName": "ordered", "emails": [emails[1], emails[0], emails[2]]}),
+    )?;
+    assert_ne!(reordered.content_version()?, user.content_version()?);
+
+    // De-duplication keeps first-seen order and moves the primary to the kept copy
+    let deduped = MultiValuedEmails::new(vec![
+        EmailAddress::new_simple("b@example.com".to_string())?,
+        EmailAddress::new_simple("a@example.com".to_string())?,
+        EmailAddress::new_simple("b@example.com".to_string())?,
+        EmailAddress::new_simple("c@example.com".to_string())?,
+    ])?
+    .with_primary(2)?
+    .dedup_by_key(|email| email.value().to_string());
+    let deduped_values: Vec<&str> = deduped.iter().map(|email| email.value()).collect();
+    assert_eq!(
+        deduped_values,
+        ["b@example.com", "a@example.com", "c@example.com"]
+    );
+    assert_eq!(deduped.primary_index(), Some(0));
+
+    Ok(())
+}
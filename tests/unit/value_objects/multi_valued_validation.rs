@@ -129,20 +129,83 @@ fn test_array_for_single_valued_display_name() {
     // Assert that validation fails with the expected error
     assert!(result.is_err());
     match result {
-        Err(ValidationError::InvalidAttributeType {
-            attribute,
-            expected,
-            actual,
-        }) => {
+        Err(ValidationError::ArrayForSingleValued { attribute }) => {
             assert_eq!(attribute, "displayName");
-            assert_eq!(expected, "string");
-            assert_eq!(actual, "array");
         }
-        Err(other) => panic!("Expected InvalidAttributeType error, got {:?}", other),
+        Err(other) => panic!("Expected ArrayForSingleValued error, got {:?}", other),
         Ok(_) => panic!("Expected validation to fail, but it passed"),
     }
 }
 
+/// Test Error #33/#34: multiValued shape is enforced for complex attributes,
+/// sub-attributes and custom schemas
+#[test]
+fn test_multi_valued_shape_enforced_by_schema() {
+    use scim_server::schema::{AttributeDefinition, AttributeType, Schema};
+
+    let registry = SchemaRegistry::new().expect("Failed to create registry");
+    let user_schema = registry.get_user_schema();
+
+    // A single-valued complex attribute rejects arrays
+    let user_array_name = json!({
+        "userName": "test@example.com",
+        "name": [{"givenName": "John"}]
+    });
+    match registry.validate_resource(user_schema, &user_array_name) {
+        Err(ValidationError::ArrayForSingleValued { attribute }) => {
+            assert_eq!(attribute, "name");
+        }
+        other => panic!("Expected ArrayForSingleValued error, got {:?}", other),
+    }
+
+    // Sub-attribute errors carry the full attribute path
+    let user_array_given_name = json!({
+        "userName": "test@example.com",
+        "name": {"givenName": ["John", "Johnny"]}
+    });
+    let error = registry
+        .validate_resource(user_schema, &user_array_given_name)
+        .unwrap_err();
+    assert!(error.to_string().contains("name.givenName"), "{}", error);
+
+    // Custom schemas are checked the same way
+    let schema = Schema {
+        id: "urn:example:params:scim:schemas:Device".to_string(),
+        name: "Device".to_string(),
+        description: "Device".to_string(),
+        attributes: vec![
+            AttributeDefinition {
+                name: "tags".to_string(),
+                data_type: AttributeType::String,
+                multi_valued: true,
+                ..Default::default()
+            },
+            AttributeDefinition {
+                name: "serialNumber".to_string(),
+                data_type: AttributeType::String,
+                ..Default::default()
+            },
+        ],
+    };
+    assert!(
+        registry
+            .validate_resource(&schema, &json!({"tags": ["a", "b"], "serialNumber": "X1"}))
+            .is_ok()
+    );
+    match registry.validate_resource(&schema, &json!({"tags": "a"})) {
+        Err(ValidationError::SingleValueForMultiValued { attribute }) => {
+            assert_eq!(attribute, "tags");
+        }
+        other => panic!("Expected SingleValueForMultiValued error, got {:?}", other),
+    }
+    match registry.validate_resource(&schema, &json!({"serialNumber": ["X1"]})) {
+        Err(ValidationError::ArrayForSingleValued { attribute }) => {
+            assert_eq!(attribute, "serialNumber");
+        }
+        other => panic!("Expected ArrayForSingleValued error, got {:?}", other),
+    }
+}
+
 /// Test Error #35: Multiple primary values in multi-valued attribute
 #[test]
 fn test_multiple_primary_values() {
@@ -249,12 +312,12 @@ fn test_invalid_multi_valued_structure() {
     // Assert that validation fails with the expected error
     assert!(result.is_err());
     match result {
-        Err(ValidationError::Custom { message }) => {
-            assert!(message.contains("Invalid emails format"));
-            assert!(message.contains("invalid type: string"));
+        Err(ValidationError::MalformedJson { location, problem }) => {
+            assert_eq!(location, "emails[0]");
+            assert_eq!(problem, "unexpected string");
         }
         Err(other) => panic!(
-            "Expected Custom error about invalid emails format, got {:?}",
+            "Expected malformed JSON error about the first email, got {:?}",
             other
         ),
         Ok(_) => panic!("Expected validation to fail, but it passed"),